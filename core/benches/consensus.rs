@@ -5,7 +5,10 @@ extern crate test;
 
 use {
     solana_core::{
-        consensus::{tower_storage::FileTowerStorage, Tower},
+        consensus::{
+            tower_storage::{DurabilityLevel, FileTowerStorage},
+            Tower,
+        },
         vote_simulator::VoteSimulator,
     },
     solana_keypair::Keypair,
@@ -21,8 +24,7 @@ use {
     trees::tr,
 };
 
-#[bench]
-fn bench_save_tower(bench: &mut Bencher) {
+fn bench_save_tower_with_durability(bench: &mut Bencher, durability: DurabilityLevel) {
     let dir = TempDir::new().unwrap();
 
     let vote_account_pubkey = &Pubkey::default();
@@ -31,7 +33,7 @@ fn bench_save_tower(bench: &mut Bencher) {
         .read()
         .unwrap()
         .working_bank();
-    let tower_storage = FileTowerStorage::new(dir.path().to_path_buf());
+    let tower_storage = FileTowerStorage::new(dir.path().to_path_buf()).with_durability(durability);
     let tower = Tower::new(
         &node_keypair.pubkey(),
         vote_account_pubkey,
@@ -44,6 +46,21 @@ fn bench_save_tower(bench: &mut Bencher) {
     });
 }
 
+#[bench]
+fn bench_save_tower(bench: &mut Bencher) {
+    bench_save_tower_with_durability(bench, DurabilityLevel::None);
+}
+
+#[bench]
+fn bench_save_tower_data_only(bench: &mut Bencher) {
+    bench_save_tower_with_durability(bench, DurabilityLevel::DataOnly);
+}
+
+#[bench]
+fn bench_save_tower_full(bench: &mut Bencher) {
+    bench_save_tower_with_durability(bench, DurabilityLevel::Full);
+}
+
 #[bench]
 #[ignore]
 fn bench_generate_ancestors_descendants(bench: &mut Bencher) {
@@ -829,6 +829,7 @@ impl BankingSimulator {
             None,
             bank_forks.clone(),
             prioritization_fee_cache,
+            None,
         );
 
         let (&_slot, &raw_base_event_time) = freeze_time_by_slot
@@ -7,7 +7,8 @@ use qualifier_attr::qualifiers;
 use {
     self::{
         committer::Committer, consumer::Consumer, decision_maker::DecisionMaker,
-        packet_receiver::PacketReceiver, qos_service::QosService, vote_storage::VoteStorage,
+        duplicate_vote_filter::VoteAdmissionFilter, packet_receiver::PacketReceiver,
+        qos_service::QosService, vote_storage::VoteStorage,
     },
     crate::{
         banking_stage::{
@@ -59,6 +60,7 @@ use {
 // Below modules are pub to allow use by banking_stage bench
 pub mod committer;
 pub mod consumer;
+pub mod duplicate_vote_filter;
 pub mod leader_slot_metrics;
 pub mod qos_service;
 pub mod vote_storage;
@@ -73,6 +75,7 @@ conditional_vis_mod!(packet_deserializer, feature = "dev-context-only-utils", pu
 mod packet_filter;
 mod packet_receiver;
 mod read_write_account_set;
+mod weighted_shuffle;
 conditional_vis_mod!(scheduler_messages, feature = "dev-context-only-utils", pub);
 conditional_vis_mod!(
     transaction_scheduler,
@@ -377,6 +380,7 @@ impl BankingStage {
         log_messages_bytes_limit: Option<usize>,
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        vote_admission_filter: Option<Arc<dyn VoteAdmissionFilter>>,
     ) -> Self {
         Self::new_num_threads(
             block_production_method,
@@ -393,6 +397,7 @@ impl BankingStage {
             log_messages_bytes_limit,
             bank_forks,
             prioritization_fee_cache,
+            vote_admission_filter,
         )
     }
 
@@ -412,6 +417,7 @@ impl BankingStage {
         log_messages_bytes_limit: Option<usize>,
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        vote_admission_filter: Option<Arc<dyn VoteAdmissionFilter>>,
     ) -> Self {
         let use_greedy_scheduler = matches!(
             block_production_method,
@@ -432,6 +438,7 @@ impl BankingStage {
             log_messages_bytes_limit,
             bank_forks,
             prioritization_fee_cache,
+            vote_admission_filter,
         )
     }
 
@@ -451,11 +458,16 @@ impl BankingStage {
         log_messages_bytes_limit: Option<usize>,
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        vote_admission_filter: Option<Arc<dyn VoteAdmissionFilter>>,
     ) -> Self {
         assert!(num_threads >= MIN_TOTAL_THREADS);
         let vote_storage = {
             let bank = bank_forks.read().unwrap().working_bank();
-            VoteStorage::new(&bank)
+            let mut vote_storage = VoteStorage::new(&bank);
+            if let Some(filter) = vote_admission_filter {
+                vote_storage.set_admission_filter(filter);
+            }
+            vote_storage
         };
 
         let decision_maker = DecisionMaker::new(cluster_info.id(), poh_recorder.clone());
@@ -778,6 +790,7 @@ mod tests {
             None,
             bank_forks,
             &Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
         );
         drop(non_vote_sender);
         drop(tpu_vote_sender);
@@ -836,6 +849,7 @@ mod tests {
             None,
             bank_forks,
             &Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
         );
         trace!("sending bank");
         drop(non_vote_sender);
@@ -903,6 +917,7 @@ mod tests {
             None,
             bank_forks.clone(), // keep a local-copy of bank-forks so worker threads do not lose weak access to bank-forks
             &Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
         );
 
         // fund another account so we can send 2 good transactions in a single batch.
@@ -1058,6 +1073,7 @@ mod tests {
                 None,
                 bank_forks,
                 &Arc::new(PrioritizationFeeCache::new(0u64)),
+                None,
             );
 
             // wait for banking_stage to eat the packets
@@ -1247,6 +1263,7 @@ mod tests {
             None,
             bank_forks,
             &Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
         );
 
         let keypairs = (0..100).map(|_| Keypair::new()).collect_vec();
@@ -0,0 +1,171 @@
+//! Keeps track of the slots proven duplicate through gossip, so that banking
+//! stage does not hold or forward votes for them.
+
+use {
+    crossbeam_channel::{Receiver, RecvTimeoutError},
+    solana_clock::Slot,
+    solana_gossip::duplicate_shred_handler::DuplicateSlotProofNotification,
+    solana_runtime::bank_forks::BankForks,
+    std::{
+        collections::BTreeSet,
+        fmt::Debug,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+const RECV_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Decides which votes are admitted to the vote storage
+pub trait VoteAdmissionFilter: Debug + Send + Sync {
+    /// Returns true if the vote for `slot` is on a fork known to be
+    /// duplicate, and so is of no use
+    fn is_duplicate_fork_vote(&self, slot: Slot) -> bool;
+}
+
+/// Set of the slots above the root which are proven duplicate
+#[derive(Debug, Default)]
+pub struct DuplicateVoteFilter {
+    // Root and duplicate slots above the root
+    duplicate_slots: RwLock<(Slot, BTreeSet<Slot>)>,
+}
+
+impl DuplicateVoteFilter {
+    pub fn add_duplicate_slot(&self, slot: Slot) {
+        let mut duplicate_slots = self.duplicate_slots.write().unwrap();
+        let (root, slots) = &mut *duplicate_slots;
+        if slot > *root {
+            slots.insert(slot);
+        }
+    }
+
+    /// Drops the duplicate slots at or below `root`, since votes for them
+    /// can no longer land anyway
+    pub fn set_root(&self, root: Slot) {
+        let mut duplicate_slots = self.duplicate_slots.write().unwrap();
+        let (old_root, slots) = &mut *duplicate_slots;
+        if root > *old_root {
+            *old_root = root;
+            *slots = slots.split_off(&root.saturating_add(1));
+        }
+    }
+
+    /// Adds the duplicate slots notified within `timeout`
+    fn recv_duplicate_slots(
+        &self,
+        receiver: &Receiver<DuplicateSlotProofNotification>,
+        timeout: Duration,
+    ) -> Result<(), RecvTimeoutError> {
+        let notification = receiver.recv_timeout(timeout)?;
+        for notification in std::iter::once(notification).chain(receiver.try_iter()) {
+            self.add_duplicate_slot(notification.slot);
+        }
+        Ok(())
+    }
+}
+
+impl VoteAdmissionFilter for DuplicateVoteFilter {
+    fn is_duplicate_fork_vote(&self, slot: Slot) -> bool {
+        self.duplicate_slots.read().unwrap().1.contains(&slot)
+    }
+}
+
+/// Feeds the duplicate slots notified by the gossip duplicate shred handler,
+/// see `DuplicateShredHandler::with_duplicate_slot_subscribers`, and the root
+/// of `bank_forks` to a `DuplicateVoteFilter`
+pub struct DuplicateVoteFilterService {
+    t_duplicate_vote_filter: JoinHandle<()>,
+}
+
+impl DuplicateVoteFilterService {
+    pub fn new(
+        filter: Arc<DuplicateVoteFilter>,
+        duplicate_slots_receiver: Receiver<DuplicateSlotProofNotification>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        exit: Arc<AtomicBool>,
+    ) -> Self {
+        let t_duplicate_vote_filter = Builder::new()
+            .name("solDupVoteFiltr".to_string())
+            .spawn(move || {
+                while !exit.load(Ordering::Relaxed) {
+                    match filter.recv_duplicate_slots(&duplicate_slots_receiver, RECV_TIMEOUT) {
+                        Ok(()) | Err(RecvTimeoutError::Timeout) => (),
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                    filter.set_root(bank_forks.read().unwrap().root());
+                }
+            })
+            .unwrap();
+        Self {
+            t_duplicate_vote_filter,
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.t_duplicate_vote_filter.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crossbeam_channel::unbounded, solana_ledger::shred::ShredType,
+        solana_pubkey::Pubkey,
+    };
+
+    fn new_notification(slot: Slot) -> DuplicateSlotProofNotification {
+        DuplicateSlotProofNotification {
+            slot,
+            origin: Pubkey::new_unique(),
+            shred1_index: 0,
+            shred1_type: ShredType::Data,
+            shred2_index: 0,
+            shred2_type: ShredType::Data,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_vote_filter() {
+        let filter = DuplicateVoteFilter::default();
+        let (sender, receiver) = unbounded();
+        assert!(!filter.is_duplicate_fork_vote(10));
+
+        sender.send(new_notification(10)).unwrap();
+        sender.send(new_notification(12)).unwrap();
+        filter
+            .recv_duplicate_slots(&receiver, Duration::ZERO)
+            .unwrap();
+        assert!(filter.is_duplicate_fork_vote(10));
+        assert!(!filter.is_duplicate_fork_vote(11));
+        assert!(filter.is_duplicate_fork_vote(12));
+        assert_eq!(
+            filter.recv_duplicate_slots(&receiver, Duration::ZERO),
+            Err(RecvTimeoutError::Timeout)
+        );
+
+        // Duplicate slots are pruned once rooted past.
+        filter.set_root(10);
+        assert!(!filter.is_duplicate_fork_vote(10));
+        assert!(filter.is_duplicate_fork_vote(12));
+        // Slots at or below the root are ignored, and the root does not go
+        // back.
+        filter.add_duplicate_slot(9);
+        filter.set_root(5);
+        filter.add_duplicate_slot(8);
+        assert!(!filter.is_duplicate_fork_vote(8));
+        assert!(!filter.is_duplicate_fork_vote(9));
+        filter.set_root(12);
+        assert!(!filter.is_duplicate_fork_vote(12));
+
+        drop(sender);
+        assert_eq!(
+            filter.recv_duplicate_slots(&receiver, Duration::ZERO),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+}
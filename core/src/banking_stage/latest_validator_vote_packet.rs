@@ -8,7 +8,7 @@ use {
     solana_packet::PACKET_DATA_SIZE,
     solana_pubkey::Pubkey,
     solana_vote_program::vote_instruction::VoteInstruction,
-    std::sync::Arc,
+    std::{sync::Arc, time::Instant},
 };
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
@@ -26,6 +26,8 @@ pub struct LatestValidatorVotePacket {
     slot: Slot,
     hash: Hash,
     timestamp: Option<UnixTimestamp>,
+    forwarded: bool,
+    received: Instant,
 }
 
 impl LatestValidatorVotePacket {
@@ -77,6 +79,8 @@ impl LatestValidatorVotePacket {
                     vote_pubkey,
                     vote_source,
                     timestamp,
+                    forwarded: false,
+                    received: Instant::now(),
                 })
             }
             _ => Err(DeserializedPacketError::VoteTransactionError),
@@ -121,9 +125,26 @@ impl LatestValidatorVotePacket {
         self.vote.is_none()
     }
 
+    pub fn vote(&self) -> Option<Arc<ImmutableDeserializedPacket>> {
+        self.vote.clone()
+    }
+
     pub fn take_vote(&mut self) -> Option<Arc<ImmutableDeserializedPacket>> {
         self.vote.take()
     }
+
+    /// When the vote was received, or reinserted after failing to process
+    pub fn received(&self) -> Instant {
+        self.received
+    }
+
+    pub fn is_forwarded(&self) -> bool {
+        self.forwarded
+    }
+
+    pub fn set_forwarded(&mut self, forwarded: bool) {
+        self.forwarded = forwarded;
+    }
 }
 
 #[cfg(test)]
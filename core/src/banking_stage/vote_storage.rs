@@ -1,23 +1,48 @@
 use {
     super::{
+        duplicate_vote_filter::VoteAdmissionFilter,
         immutable_deserialized_packet::ImmutableDeserializedPacket,
         latest_validator_vote_packet::{LatestValidatorVotePacket, VoteSource},
+        weighted_shuffle::weighted_random_order,
     },
     agave_feature_set as feature_set,
     ahash::HashMap,
     itertools::Itertools,
-    rand::{thread_rng, Rng},
+    rand::thread_rng,
     solana_account::from_account,
-    solana_clock::Epoch,
+    solana_clock::{Epoch, Slot, DEFAULT_MS_PER_SLOT},
+    solana_hash::Hash,
     solana_pubkey::Pubkey,
     solana_runtime::{bank::Bank, epoch_stakes::VersionedEpochStakes},
     solana_sysvar::{self as sysvar, slot_hashes::SlotHashes},
-    std::{cmp, sync::Arc},
+    std::{
+        cmp,
+        collections::BTreeSet,
+        fmt::{Debug, Write},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
 };
 
 /// Maximum number of votes a single receive call will accept
 const MAX_NUM_VOTES_RECEIVE: usize = 10_000;
 
+/// Default maximum number of vote pubkeys whose latest vote is tracked
+pub const DEFAULT_MAX_VOTE_PUBKEYS: usize = 40_000;
+
+// Votes held for this many slots trail the tip far enough that newer votes
+// have superseded them for fork choice. This is well short of blockhash
+// expiry (MAX_PROCESSING_AGE), so the vote itself may still be valid.
+const DEFAULT_MAX_HELD_VOTE_AGE_SLOTS: u64 = 25;
+/// Suggested age past which held votes are expired, see
+/// `VoteStorage::set_max_held_vote_age`
+pub const DEFAULT_MAX_HELD_VOTE_AGE: Duration =
+    Duration::from_millis(DEFAULT_MAX_HELD_VOTE_AGE_SLOTS * DEFAULT_MS_PER_SLOT);
+
 #[derive(Default, Debug)]
 pub(crate) struct VoteBatchInsertionMetrics {
     pub(crate) num_dropped_gossip: usize,
@@ -38,10 +63,128 @@ impl VoteBatchInsertionMetrics {
     }
 }
 
+/// Upper bounds, exclusive, of the slot lag buckets of
+/// `VoteStorage::slot_lag_histogram`. The last bucket holds all larger lags.
+const SLOT_LAG_BUCKETS: [Slot; 5] = [1, 4, 16, 64, 256];
+pub const NUM_SLOT_LAG_BUCKETS: usize = SLOT_LAG_BUCKETS.len() + 1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VoteStorageStats {
+    /// Number of votes offered to the storage, including reinserted ones
+    pub num_updates: u64,
+    /// Number of votes rejected because a newer vote is held
+    pub num_stale_updates: u64,
+    /// Number of votes rejected because the storage is full
+    pub num_rejected_full: u64,
+    /// Number of votes rejected for slots too far beyond the current slot
+    pub num_rejected_future: u64,
+    /// Number of votes rejected for duplicate slots
+    pub num_rejected_duplicate: u64,
+    /// Number of unprocessed votes replaced by a newer one
+    pub num_replacements: u64,
+    /// Number of votes replaced by a refreshed vote for the same slot
+    pub num_refreshes: u64,
+    /// Number of votes drained for processing
+    pub num_drained: u64,
+    /// Number of unprocessed votes purged for being too old
+    pub num_purged: u64,
+    /// Number of unprocessed votes expired for being held too long
+    pub num_expired: u64,
+}
+
+/// Metadata of an unprocessed vote, for debugging
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeldVoteInfo {
+    pub vote_pubkey: Pubkey,
+    pub slot: Slot,
+    pub source: VoteSource,
+    pub message_hash: Hash,
+    pub forwarded: bool,
+    pub received: Instant,
+}
+
+/// Thresholds of `VoteStorage::report_starvation`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StarvationThresholds {
+    /// Starvation is reported once the storage is found empty on more than
+    /// this many consecutive checks
+    pub max_empty_checks: u32,
+    /// Stale votes are reported once more than this many are held
+    pub max_stale_votes: usize,
+    /// Unprocessed votes received more than this many slots ago are stale
+    pub stale_slots: Slot,
+}
+
+impl Default for StarvationThresholds {
+    fn default() -> Self {
+        Self {
+            max_empty_checks: 4,
+            max_stale_votes: 100,
+            stale_slots: 4,
+        }
+    }
+}
+
+/// Receives the reports of `VoteStorage::report_starvation`
+pub trait StarvationReporter: Debug + Send + Sync {
+    /// No unprocessed votes were held at `slot`, nor at the
+    /// `num_empty_checks - 1` checks before
+    fn report_empty(&self, slot: Slot, num_empty_checks: u32);
+
+    /// `num_stale_votes` unprocessed votes held at `slot` are stale
+    fn report_stale(&self, slot: Slot, num_stale_votes: usize);
+}
+
+#[derive(Debug)]
+struct DatapointStarvationReporter;
+
+impl StarvationReporter for DatapointStarvationReporter {
+    fn report_empty(&self, slot: Slot, num_empty_checks: u32) {
+        datapoint_warn!(
+            "latest_unprocessed_votes-starved",
+            ("slot", slot, i64),
+            ("num_empty_checks", num_empty_checks, i64)
+        );
+    }
+
+    fn report_stale(&self, slot: Slot, num_stale_votes: usize) {
+        datapoint_warn!(
+            "latest_unprocessed_votes-stale",
+            ("slot", slot, i64),
+            ("num_stale_votes", num_stale_votes, i64)
+        );
+    }
+}
+
 #[derive(Debug)]
 pub struct VoteStorage {
     latest_vote_per_vote_pubkey: HashMap<Pubkey, LatestValidatorVotePacket>,
     num_unprocessed_votes: usize,
+    /// Lower bound of the slots of the unprocessed votes, or `Slot::MAX` if
+    /// there were none as of the last purge. Exact right after a purge.
+    min_held_slot: Slot,
+    max_vote_pubkeys: usize,
+    /// If set, votes for slots more than this many slots beyond
+    /// `current_slot` are rejected
+    max_future_slot_offset: Option<Slot>,
+    /// If set, `expire_held_votes` drops the votes held for longer
+    max_held_vote_age: Option<Duration>,
+    current_slot: Slot,
+    admission_filter: Option<Arc<dyn VoteAdmissionFilter>>,
+    stats: VoteStorageStats,
+    /// Number of consecutive `drain_up_to` rounds which left the vote of a
+    /// pubkey unprocessed in the current epoch
+    skipped_drain_rounds: HashMap<Pubkey, u32>,
+    /// (stake, pubkey) of the pubkeys whose vote was taken, ordered for
+    /// eviction. May hold stale entries, which are checked on eviction.
+    evictable: BTreeSet<(u64, Pubkey)>,
+    starvation_thresholds: StarvationThresholds,
+    starvation_reporter: Arc<dyn StarvationReporter>,
+    /// Slot of the last starvation check
+    last_starvation_check: Option<Slot>,
+    /// Number of consecutive starvation checks which found no unprocessed
+    /// votes
+    num_empty_checks: u32,
     cached_epoch_stakes: VersionedEpochStakes,
     deprecate_legacy_vote_ixs: bool,
     current_epoch: Epoch,
@@ -49,9 +192,28 @@ pub struct VoteStorage {
 
 impl VoteStorage {
     pub fn new(bank: &Bank) -> Self {
+        Self::new_with_max_vote_pubkeys(bank, DEFAULT_MAX_VOTE_PUBKEYS)
+    }
+
+    /// Creates storage which tracks the latest votes of at most
+    /// `max_vote_pubkeys` vote accounts
+    pub fn new_with_max_vote_pubkeys(bank: &Bank, max_vote_pubkeys: usize) -> Self {
         Self {
             latest_vote_per_vote_pubkey: HashMap::default(),
             num_unprocessed_votes: 0,
+            min_held_slot: Slot::MAX,
+            max_vote_pubkeys,
+            max_future_slot_offset: None,
+            max_held_vote_age: None,
+            current_slot: bank.slot(),
+            admission_filter: None,
+            stats: VoteStorageStats::default(),
+            skipped_drain_rounds: HashMap::default(),
+            evictable: BTreeSet::default(),
+            starvation_thresholds: StarvationThresholds::default(),
+            starvation_reporter: Arc::new(DatapointStarvationReporter),
+            last_starvation_check: None,
+            num_empty_checks: 0,
             cached_epoch_stakes: bank.current_epoch_stakes().clone(),
             current_epoch: bank.epoch(),
             deprecate_legacy_vote_ixs: bank
@@ -62,17 +224,39 @@ impl VoteStorage {
 
     #[cfg(test)]
     pub fn new_for_tests(vote_pubkeys_to_stake: &[Pubkey]) -> Self {
+        let vote_pubkeys_and_stakes: Vec<_> = vote_pubkeys_to_stake
+            .iter()
+            .map(|pubkey| (*pubkey, 1))
+            .collect();
+        Self::new_for_tests_with_stakes(&vote_pubkeys_and_stakes)
+    }
+
+    #[cfg(test)]
+    fn new_for_tests_with_stakes(vote_pubkeys_and_stakes: &[(Pubkey, u64)]) -> Self {
         use solana_vote::vote_account::VoteAccount;
 
-        let vote_accounts = vote_pubkeys_to_stake
+        let vote_accounts = vote_pubkeys_and_stakes
             .iter()
-            .map(|pubkey| (*pubkey, (1u64, VoteAccount::new_random())))
+            .map(|(pubkey, stake)| (*pubkey, (*stake, VoteAccount::new_random())))
             .collect();
         let epoch_stakes = VersionedEpochStakes::new_for_tests(vote_accounts, 0);
 
         Self {
             latest_vote_per_vote_pubkey: HashMap::default(),
             num_unprocessed_votes: 0,
+            min_held_slot: Slot::MAX,
+            max_vote_pubkeys: DEFAULT_MAX_VOTE_PUBKEYS,
+            max_future_slot_offset: None,
+            max_held_vote_age: None,
+            current_slot: 0,
+            admission_filter: None,
+            stats: VoteStorageStats::default(),
+            skipped_drain_rounds: HashMap::default(),
+            evictable: BTreeSet::default(),
+            starvation_thresholds: StarvationThresholds::default(),
+            starvation_reporter: Arc::new(DatapointStarvationReporter),
+            last_starvation_check: None,
+            num_empty_checks: 0,
             cached_epoch_stakes: epoch_stakes,
             current_epoch: 0,
             deprecate_legacy_vote_ixs: true,
@@ -87,6 +271,100 @@ impl VoteStorage {
         self.num_unprocessed_votes
     }
 
+    /// Returns true if an unprocessed vote is held for `vote_pubkey`
+    pub fn contains(&self, vote_pubkey: &Pubkey) -> bool {
+        self.latest_vote_per_vote_pubkey
+            .get(vote_pubkey)
+            .is_some_and(|vote| !vote.is_vote_taken())
+    }
+
+    /// Drops the unprocessed vote held for `vote_pubkey`, if any. The pubkey
+    /// stays tracked, so that older votes are still rejected.
+    pub fn remove(&mut self, vote_pubkey: &Pubkey) -> Option<Arc<ImmutableDeserializedPacket>> {
+        let vote = self
+            .latest_vote_per_vote_pubkey
+            .get_mut(vote_pubkey)?
+            .take_vote()?;
+        self.num_unprocessed_votes -= 1;
+        self.mark_evictable(*vote_pubkey);
+        Some(vote)
+    }
+
+    /// Drops the unprocessed vote held for `vote_pubkey` if a vote of the
+    /// validator for `slot`, or a later slot, landed in a block. The pubkey
+    /// stays tracked, so that older votes are still rejected.
+    pub fn mark_processed(
+        &mut self,
+        vote_pubkey: &Pubkey,
+        slot: Slot,
+    ) -> Option<Arc<ImmutableDeserializedPacket>> {
+        let latest_vote = self.latest_vote_per_vote_pubkey.get_mut(vote_pubkey)?;
+        if latest_vote.slot() > slot {
+            return None;
+        }
+        let vote = latest_vote.take_vote()?;
+        self.num_unprocessed_votes -= 1;
+        self.mark_evictable(*vote_pubkey);
+        Some(vote)
+    }
+
+    /// Calls `mark_processed` for each landed (vote pubkey, slot), and
+    /// returns the number of unprocessed votes dropped
+    pub fn mark_processed_batch(&mut self, landed: &[(Pubkey, Slot)]) -> usize {
+        landed
+            .iter()
+            .filter_map(|(vote_pubkey, slot)| self.mark_processed(vote_pubkey, *slot))
+            .count()
+    }
+
+    /// Stops tracking the vote pubkeys for which `f` returns false, given the
+    /// pubkey and the slot of its latest vote
+    pub fn retain(&mut self, mut f: impl FnMut(&Pubkey, Slot) -> bool) {
+        let mut num_removed = 0;
+        self.latest_vote_per_vote_pubkey
+            .retain(|vote_pubkey, vote| {
+                let keep = f(vote_pubkey, vote.slot());
+                if !keep && !vote.is_vote_taken() {
+                    num_removed += 1;
+                }
+                keep
+            });
+        self.num_unprocessed_votes -= num_removed;
+    }
+
+    /// Rejects votes for slots more than `max_future_slot_offset` slots beyond
+    /// the current slot, so that a bogus vote for a far future slot cannot
+    /// make all real votes of a validator look stale. None disables the check.
+    pub fn set_max_future_slot_offset(&mut self, max_future_slot_offset: Option<Slot>) {
+        self.max_future_slot_offset = max_future_slot_offset;
+    }
+
+    /// Makes `expire_held_votes()` drop the votes held for longer than
+    /// `max_held_vote_age`. None, the default, disables expiry.
+    pub fn set_max_held_vote_age(&mut self, max_held_vote_age: Option<Duration>) {
+        self.max_held_vote_age = max_held_vote_age;
+    }
+
+    /// Sets the slot of the working bank, which bounds the slots of the votes
+    /// accepted if `max_future_slot_offset` is set
+    pub fn set_current_slot(&mut self, slot: Slot) {
+        self.current_slot = slot;
+    }
+
+    /// Sets the thresholds at which `report_starvation()` reports starvation
+    pub fn set_starvation_thresholds(&mut self, thresholds: StarvationThresholds) {
+        self.starvation_thresholds = thresholds;
+    }
+
+    pub fn set_starvation_reporter(&mut self, reporter: Arc<dyn StarvationReporter>) {
+        self.starvation_reporter = reporter;
+    }
+
+    /// Rejects the votes for which `filter` reports a duplicate fork
+    pub fn set_admission_filter(&mut self, filter: Arc<dyn VoteAdmissionFilter>) {
+        self.admission_filter = Some(filter);
+    }
+
     pub fn max_receive_size(&self) -> usize {
         MAX_NUM_VOTES_RECEIVE
     }
@@ -130,6 +408,78 @@ impl VoteStorage {
     }
 
     pub fn drain_unprocessed(&mut self, bank: &Bank) -> Vec<Arc<ImmutableDeserializedPacket>> {
+        let slot_hashes = Self::slot_hashes(bank);
+        self.drain_votes(&slot_hashes, None)
+    }
+
+    /// Like `drain_unprocessed`, but only drains votes received from `source`.
+    /// Votes from other sources are left unprocessed.
+    pub fn drain_unprocessed_from_source(
+        &mut self,
+        bank: &Bank,
+        source: VoteSource,
+    ) -> Vec<Arc<ImmutableDeserializedPacket>> {
+        let slot_hashes = Self::slot_hashes(bank);
+        self.drain_votes(&slot_hashes, Some(source))
+    }
+
+    /// Drains at most `max_packets` unprocessed votes in stake weighted order,
+    /// and returns them along with the number of unprocessed votes left.
+    /// The stake of a validator whose vote is left unprocessed is doubled in
+    /// the next round, so that low staked validators are not starved.
+    pub fn drain_up_to(
+        &mut self,
+        bank: &Bank,
+        max_packets: usize,
+    ) -> (Vec<Arc<ImmutableDeserializedPacket>>, usize) {
+        let slot_hashes = Self::slot_hashes(bank);
+        self.drain_votes_up_to(&slot_hashes, max_packets)
+    }
+
+    fn drain_votes_up_to(
+        &mut self,
+        slot_hashes: &Option<SlotHashes>,
+        max_packets: usize,
+    ) -> (Vec<Arc<ImmutableDeserializedPacket>>, usize) {
+        let stakes = self
+            .latest_vote_per_vote_pubkey
+            .iter()
+            .filter(|(_, vote)| !vote.is_vote_taken())
+            .filter_map(|(pubkey, _)| {
+                let stake = self.cached_epoch_stakes.vote_account_stake(pubkey);
+                if stake == 0 {
+                    return None; // Ignore votes from unstaked validators
+                }
+                let skipped_rounds = self.skipped_drain_rounds.get(pubkey).copied();
+                let boost = 1u64 << skipped_rounds.unwrap_or_default().min(u64::BITS - 1);
+                Some((*pubkey, stake.saturating_mul(boost)))
+            })
+            .collect_vec();
+        let mut votes = Vec::with_capacity(max_packets.min(stakes.len()));
+        for pubkey in weighted_random_order(stakes, &mut thread_rng()) {
+            let latest_vote = self.latest_vote_per_vote_pubkey.get_mut(&pubkey).unwrap();
+            // Only votes left behind for lack of room are boosted, a vote
+            // invalid for our fork must not outweigh fresh votes
+            if !Self::is_valid_for_our_fork(latest_vote, slot_hashes) {
+                self.skipped_drain_rounds.remove(&pubkey);
+                continue;
+            }
+            if votes.len() >= max_packets {
+                *self.skipped_drain_rounds.entry(pubkey).or_default() += 1;
+                continue;
+            }
+            if let Some(vote) = latest_vote.take_vote() {
+                self.num_unprocessed_votes -= 1;
+                self.skipped_drain_rounds.remove(&pubkey);
+                self.mark_evictable(pubkey);
+                votes.push(vote);
+            }
+        }
+        self.stats.num_drained += votes.len() as u64;
+        (votes, self.num_unprocessed_votes)
+    }
+
+    fn slot_hashes(bank: &Bank) -> Option<SlotHashes> {
         let slot_hashes = bank
             .get_account(&sysvar::slot_hashes::id())
             .and_then(|account| from_account::<SlotHashes, _>(&account));
@@ -140,23 +490,77 @@ impl VoteStorage {
                 bank.slot()
             );
         }
+        slot_hashes
+    }
 
-        self.weighted_random_order_by_stake()
+    fn drain_votes(
+        &mut self,
+        slot_hashes: &Option<SlotHashes>,
+        source: Option<VoteSource>,
+    ) -> Vec<Arc<ImmutableDeserializedPacket>> {
+        let votes = self
+            .weighted_random_order_by_stake()
             .filter_map(|pubkey| {
                 self.latest_vote_per_vote_pubkey
                     .get_mut(&pubkey)
                     .and_then(|latest_vote| {
-                        if !Self::is_valid_for_our_fork(latest_vote, &slot_hashes) {
+                        if source.is_some_and(|source| source != latest_vote.source())
+                            || !Self::is_valid_for_our_fork(latest_vote, slot_hashes)
+                        {
                             return None;
                         }
                         latest_vote.take_vote().inspect(|_vote| {
                             self.num_unprocessed_votes -= 1;
+                            let stake = self.cached_epoch_stakes.vote_account_stake(&pubkey);
+                            self.evictable.insert((stake, pubkey));
                         })
                     })
             })
+            .collect_vec();
+        self.stats.num_drained += votes.len() as u64;
+        votes
+    }
+
+    /// Returns up to `max_packets` unprocessed votes which are not forwarded
+    /// yet, in stake weighted order, and marks them forwarded. The votes are
+    /// left to be processed locally.
+    pub fn get_and_mark_forwarded(
+        &mut self,
+        bank: &Bank,
+        max_packets: usize,
+    ) -> Vec<Arc<ImmutableDeserializedPacket>> {
+        let slot_hashes = Self::slot_hashes(bank);
+        self.mark_forwarded(&slot_hashes, max_packets)
+    }
+
+    fn mark_forwarded(
+        &mut self,
+        slot_hashes: &Option<SlotHashes>,
+        max_packets: usize,
+    ) -> Vec<Arc<ImmutableDeserializedPacket>> {
+        self.weighted_random_order_by_stake()
+            .filter_map(|pubkey| {
+                let latest_vote = self.latest_vote_per_vote_pubkey.get_mut(&pubkey)?;
+                if latest_vote.is_forwarded()
+                    || !Self::is_valid_for_our_fork(latest_vote, slot_hashes)
+                {
+                    return None;
+                }
+                let vote = latest_vote.vote()?;
+                latest_vote.set_forwarded(true);
+                Some(vote)
+            })
+            .take(max_packets)
             .collect_vec()
     }
 
+    /// Allows all held votes to be forwarded again, e.g. to a new leader
+    pub fn clear_forwarded_flags(&mut self) {
+        self.latest_vote_per_vote_pubkey
+            .values_mut()
+            .for_each(|vote| vote.set_forwarded(false));
+    }
+
     pub fn clear(&mut self) {
         self.latest_vote_per_vote_pubkey
             .values_mut()
@@ -165,6 +569,262 @@ impl VoteStorage {
                     self.num_unprocessed_votes -= 1;
                 }
             });
+        self.min_held_slot = Slot::MAX;
+        self.rebuild_evictable();
+    }
+
+    /// Drops unprocessed votes for slots below `slot`. The pubkeys stay
+    /// tracked, so that older votes are still rejected. Returns the number
+    /// of votes dropped.
+    pub fn purge_below(&mut self, slot: Slot) -> usize {
+        let mut num_purged = 0;
+        let mut min_held_slot = Slot::MAX;
+        for (pubkey, vote) in self.latest_vote_per_vote_pubkey.iter_mut() {
+            if vote.is_vote_taken() {
+                continue;
+            }
+            if vote.slot() < slot {
+                vote.take_vote();
+                num_purged += 1;
+                let stake = self.cached_epoch_stakes.vote_account_stake(pubkey);
+                self.evictable.insert((stake, *pubkey));
+            } else {
+                min_held_slot = min_held_slot.min(vote.slot());
+            }
+        }
+        self.min_held_slot = min_held_slot;
+        self.num_unprocessed_votes -= num_purged;
+        self.stats.num_purged += num_purged as u64;
+        num_purged
+    }
+
+    /// Drops the unprocessed votes for slots below the new `root`, which can
+    /// no longer land. Cheap when no vote held is below the root, so it can
+    /// be called whenever the root is observed. Returns the number of votes
+    /// dropped.
+    pub fn handle_new_root(&mut self, root: Slot) -> usize {
+        if root <= self.min_held_slot {
+            return 0;
+        }
+        let num_purged = self.purge_below(root);
+        if num_purged > 0 {
+            datapoint_info!(
+                "vote_storage-root_purge",
+                ("root", root, i64),
+                ("num_purged", num_purged, i64),
+            );
+        }
+        num_purged
+    }
+
+    /// Drops the unprocessed votes held for longer than `max_age`, and returns
+    /// them. The pubkeys stay tracked, so that older votes are still rejected.
+    pub fn expire_older_than(
+        &mut self,
+        max_age: Duration,
+    ) -> Vec<Arc<ImmutableDeserializedPacket>> {
+        match Instant::now().checked_sub(max_age) {
+            Some(deadline) => self.expire_received_before(deadline),
+            None => Vec::default(),
+        }
+    }
+
+    /// Drops the unprocessed votes held for longer than the age set with
+    /// `set_max_held_vote_age`, if any, and returns them
+    pub fn expire_held_votes(&mut self) -> Vec<Arc<ImmutableDeserializedPacket>> {
+        match self.max_held_vote_age {
+            Some(max_age) => self.expire_older_than(max_age),
+            None => Vec::default(),
+        }
+    }
+
+    /// Spawns a thread which calls `expire_held_votes()` on `storage` every
+    /// `period`, until `exit` is set
+    pub fn spawn_expiry_task(
+        storage: Arc<Mutex<Self>>,
+        period: Duration,
+        exit: Arc<AtomicBool>,
+    ) -> JoinHandle<()> {
+        Builder::new()
+            .name("solVoteExpiry".to_string())
+            .spawn(move || {
+                while !exit.load(Ordering::Relaxed) {
+                    storage.lock().unwrap().expire_held_votes();
+                    thread::sleep(period);
+                }
+            })
+            .unwrap()
+    }
+
+    fn expire_received_before(
+        &mut self,
+        deadline: Instant,
+    ) -> Vec<Arc<ImmutableDeserializedPacket>> {
+        let expired = self
+            .latest_vote_per_vote_pubkey
+            .iter_mut()
+            .filter(|(_, vote)| vote.received() < deadline)
+            .filter_map(|(pubkey, vote)| {
+                let vote = vote.take_vote()?;
+                let stake = self.cached_epoch_stakes.vote_account_stake(pubkey);
+                self.evictable.insert((stake, *pubkey));
+                Some(vote)
+            })
+            .collect_vec();
+        self.num_unprocessed_votes -= expired.len();
+        self.stats.num_expired += expired.len() as u64;
+        expired
+    }
+
+    pub fn snapshot_stats(&self) -> VoteStorageStats {
+        self.stats
+    }
+
+    /// Counts the unprocessed votes by how many slots they are behind
+    /// `current_slot`, bucketed by `SLOT_LAG_BUCKETS`
+    pub fn slot_lag_histogram(&self, current_slot: Slot) -> [usize; NUM_SLOT_LAG_BUCKETS] {
+        let mut histogram = [0; NUM_SLOT_LAG_BUCKETS];
+        for vote in self.latest_vote_per_vote_pubkey.values() {
+            if vote.is_vote_taken() {
+                continue;
+            }
+            let lag = current_slot.saturating_sub(vote.slot());
+            let bucket = SLOT_LAG_BUCKETS.partition_point(|&bound| bound <= lag);
+            histogram[bucket] += 1;
+        }
+        histogram
+    }
+
+    /// Returns the metadata of the unprocessed votes, oldest first
+    pub fn export(&self) -> Vec<HeldVoteInfo> {
+        self.latest_vote_per_vote_pubkey
+            .values()
+            .filter_map(|vote| {
+                let packet = vote.vote()?;
+                Some(HeldVoteInfo {
+                    vote_pubkey: vote.vote_pubkey(),
+                    slot: vote.slot(),
+                    source: vote.source(),
+                    message_hash: *packet.message_hash(),
+                    forwarded: vote.is_forwarded(),
+                    received: vote.received(),
+                })
+            })
+            .sorted_unstable_by_key(|info| info.received)
+            .collect()
+    }
+
+    /// Renders the `top_n` oldest unprocessed votes, one per line
+    pub fn fmt_summary(&self, top_n: usize) -> String {
+        let held_votes = self.export();
+        let mut summary = format!(
+            "{} unprocessed votes from {} vote accounts",
+            held_votes.len(),
+            self.latest_vote_per_vote_pubkey.len()
+        );
+        let now = Instant::now();
+        for info in held_votes.iter().take(top_n) {
+            write!(
+                summary,
+                "\n{} slot: {} source: {:?} forwarded: {} age: {:?}",
+                info.vote_pubkey,
+                info.slot,
+                info.source,
+                info.forwarded,
+                now.saturating_duration_since(info.received),
+            )
+            .unwrap();
+        }
+        summary
+    }
+
+    /// Reports the stats accumulated since the last report, along with the
+    /// slot lag of the unprocessed votes, and resets the stats.
+    pub fn report(&mut self, leader_slot: Slot) {
+        let VoteStorageStats {
+            num_updates,
+            num_stale_updates,
+            num_rejected_full,
+            num_rejected_future,
+            num_rejected_duplicate,
+            num_replacements,
+            num_refreshes,
+            num_drained,
+            num_purged,
+            num_expired,
+        } = std::mem::take(&mut self.stats);
+        let [lag_0, lag_1_3, lag_4_15, lag_16_63, lag_64_255, lag_256_plus] =
+            self.slot_lag_histogram(leader_slot);
+        datapoint_info!(
+            "latest_unprocessed_votes-stats",
+            ("slot", leader_slot, i64),
+            ("num_unprocessed_votes", self.num_unprocessed_votes, i64),
+            (
+                "num_vote_pubkeys",
+                self.latest_vote_per_vote_pubkey.len(),
+                i64
+            ),
+            ("num_updates", num_updates, i64),
+            ("num_stale_updates", num_stale_updates, i64),
+            ("num_rejected_full", num_rejected_full, i64),
+            ("num_rejected_future", num_rejected_future, i64),
+            ("num_rejected_duplicate", num_rejected_duplicate, i64),
+            ("num_replacements", num_replacements, i64),
+            ("num_refreshes", num_refreshes, i64),
+            ("num_drained", num_drained, i64),
+            ("num_purged", num_purged, i64),
+            ("num_expired", num_expired, i64),
+            ("slot_lag_0", lag_0, i64),
+            ("slot_lag_1_3", lag_1_3, i64),
+            ("slot_lag_4_15", lag_4_15, i64),
+            ("slot_lag_16_63", lag_16_63, i64),
+            ("slot_lag_64_255", lag_64_255, i64),
+            ("slot_lag_256_plus", lag_256_plus, i64)
+        );
+    }
+
+    /// Checks whether banking is starved of votes, to be called while leader:
+    /// reports when the storage was empty on more than `max_empty_checks`
+    /// consecutive checks, which points at votes not arriving, and when more
+    /// than `max_stale_votes` unprocessed votes were received over
+    /// `stale_slots` slots ago, which points at votes stuck in the storage.
+    /// Only the first call for each slot checks, so that this can be called on
+    /// every pass of the leader loop.
+    pub fn report_starvation(&mut self, current_slot: Slot) {
+        self.report_starvation_at(current_slot, Instant::now())
+    }
+
+    fn report_starvation_at(&mut self, current_slot: Slot, now: Instant) {
+        if self.last_starvation_check == Some(current_slot) {
+            return;
+        }
+        self.last_starvation_check = Some(current_slot);
+        let StarvationThresholds {
+            max_empty_checks,
+            max_stale_votes,
+            stale_slots,
+        } = self.starvation_thresholds;
+        if self.is_empty() {
+            self.num_empty_checks = self.num_empty_checks.saturating_add(1);
+            if self.num_empty_checks > max_empty_checks {
+                self.starvation_reporter
+                    .report_empty(current_slot, self.num_empty_checks);
+            }
+            return;
+        }
+        self.num_empty_checks = 0;
+        let stale_age = Duration::from_millis(stale_slots.saturating_mul(DEFAULT_MS_PER_SLOT));
+        let num_stale_votes = self
+            .latest_vote_per_vote_pubkey
+            .values()
+            .filter(|vote| {
+                !vote.is_vote_taken() && now.saturating_duration_since(vote.received()) > stale_age
+            })
+            .count();
+        if num_stale_votes > max_stale_votes {
+            self.starvation_reporter
+                .report_stale(current_slot, num_stale_votes);
+        }
     }
 
     pub fn cache_epoch_boundary_info(&mut self, bank: &Bank) {
@@ -174,28 +834,41 @@ impl VoteStorage {
         {
             self.cached_epoch_stakes = bank.current_epoch_stakes().clone();
             self.current_epoch = bank.epoch();
+            self.skipped_drain_rounds.clear();
             self.deprecate_legacy_vote_ixs = bank
                 .feature_set
                 .is_active(&feature_set::deprecate_legacy_vote_ixs::id());
         }
 
         // Evict any now unstaked pubkeys
+        let unstaked_votes = self.evict_unstaked(bank);
+        // The stakes the eviction order is based on changed
+        self.rebuild_evictable();
+        // The leaders of the new epoch may not have received the held votes.
+        self.clear_forwarded_flags();
+        datapoint_info!(
+            "latest_unprocessed_votes-epoch-boundary",
+            ("epoch", bank.epoch(), i64),
+            ("evicted_unstaked_votes", unstaked_votes, i64)
+        );
+    }
+
+    /// Stops tracking vote pubkeys which are unstaked in the current epoch of
+    /// `bank`. Returns the number of unprocessed votes dropped.
+    pub fn evict_unstaked(&mut self, bank: &Bank) -> usize {
+        let epoch_stakes = bank.current_epoch_stakes();
         let mut unstaked_votes = 0;
         self.latest_vote_per_vote_pubkey
             .retain(|vote_pubkey, vote| {
                 let is_present = !vote.is_vote_taken();
-                let should_evict = self.cached_epoch_stakes.vote_account_stake(vote_pubkey) == 0;
+                let should_evict = epoch_stakes.vote_account_stake(vote_pubkey) == 0;
                 if is_present && should_evict {
                     unstaked_votes += 1;
                 }
                 !should_evict
             });
         self.num_unprocessed_votes -= unstaked_votes;
-        datapoint_info!(
-            "latest_unprocessed_votes-epoch-boundary",
-            ("epoch", bank.epoch(), i64),
-            ("evicted_unstaked_votes", unstaked_votes, i64)
-        );
+        unstaked_votes
     }
 
     fn insert_batch_with_replenish(
@@ -230,39 +903,125 @@ impl VoteStorage {
 
     /// If this vote causes an unprocessed vote to be removed, returns Some(old_vote)
     /// If there is a newer vote processed / waiting to be processed returns Some(vote)
+    /// If the storage is full and no pubkey can be evicted returns Some(vote)
+    /// If the vote is for a slot too far in the future returns Some(vote)
+    /// If the vote is for a duplicate slot returns Some(vote)
     /// Otherwise returns None
     fn update_latest_vote(
         &mut self,
-        vote: LatestValidatorVotePacket,
+        mut vote: LatestValidatorVotePacket,
         should_replenish_taken_votes: bool,
     ) -> Option<LatestValidatorVotePacket> {
         let vote_pubkey = vote.vote_pubkey();
+        let slot = vote.slot();
+        self.stats.num_updates += 1;
+        if self
+            .max_future_slot_offset
+            .is_some_and(|offset| slot > self.current_slot.saturating_add(offset))
+        {
+            self.stats.num_rejected_future += 1;
+            return Some(vote);
+        }
+        if self
+            .admission_filter
+            .as_ref()
+            .is_some_and(|filter| filter.is_duplicate_fork_vote(slot))
+        {
+            self.stats.num_rejected_duplicate += 1;
+            return Some(vote);
+        }
+        if self.latest_vote_per_vote_pubkey.len() >= self.max_vote_pubkeys
+            && !self.latest_vote_per_vote_pubkey.contains_key(&vote_pubkey)
+            && !self.evict_for(&vote_pubkey)
+        {
+            self.stats.num_rejected_full += 1;
+            return Some(vote);
+        }
         // Grab write-lock to insert new vote.
         match self.latest_vote_per_vote_pubkey.entry(vote_pubkey) {
             std::collections::hash_map::Entry::Occupied(mut entry) => {
                 let latest_vote = entry.get_mut();
                 if Self::allow_update(&vote, latest_vote, should_replenish_taken_votes) {
+                    if vote.slot() == latest_vote.slot() {
+                        if vote.timestamp() == latest_vote.timestamp() {
+                            // Another copy of the same vote, which does not
+                            // need to be forwarded again.
+                            vote.set_forwarded(latest_vote.is_forwarded());
+                        } else {
+                            self.stats.num_refreshes += 1;
+                        }
+                    }
                     let old_vote = std::mem::replace(latest_vote, vote);
+                    self.min_held_slot = self.min_held_slot.min(slot);
                     if old_vote.is_vote_taken() {
                         self.num_unprocessed_votes += 1;
                         return None;
                     } else {
+                        self.stats.num_replacements += 1;
                         return Some(old_vote);
                     }
                 }
+                self.stats.num_stale_updates += 1;
                 Some(vote)
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
                 entry.insert(vote);
+                self.min_held_slot = self.min_held_slot.min(slot);
                 self.num_unprocessed_votes += 1;
                 None
             }
         }
     }
 
+    /// Makes room for `vote_pubkey` by evicting the lowest staked vote pubkey
+    /// whose latest vote is already processed, provided it has less stake
+    /// than `vote_pubkey`. Returns false if there is no such pubkey.
+    fn evict_for(&mut self, vote_pubkey: &Pubkey) -> bool {
+        let stake = self.cached_epoch_stakes.vote_account_stake(vote_pubkey);
+        while let Some(&(evicted_stake, pubkey)) = self.evictable.first() {
+            if evicted_stake >= stake {
+                return false;
+            }
+            self.evictable.pop_first();
+            // Skip stale entries, of pubkeys no longer tracked or holding a
+            // newer vote again
+            if self
+                .latest_vote_per_vote_pubkey
+                .get(&pubkey)
+                .is_some_and(|vote| vote.is_vote_taken())
+            {
+                self.latest_vote_per_vote_pubkey.remove(&pubkey);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Records that the vote of `vote_pubkey` was taken, so that the pubkey
+    /// can be evicted to make room for a higher staked one
+    fn mark_evictable(&mut self, vote_pubkey: Pubkey) {
+        let stake = self.cached_epoch_stakes.vote_account_stake(&vote_pubkey);
+        self.evictable.insert((stake, vote_pubkey));
+    }
+
+    fn rebuild_evictable(&mut self) {
+        self.evictable = self
+            .latest_vote_per_vote_pubkey
+            .iter()
+            .filter(|(_, vote)| vote.is_vote_taken())
+            .map(|(pubkey, _)| (self.cached_epoch_stakes.vote_account_stake(pubkey), *pubkey))
+            .collect();
+    }
+
     /// Allow votes for later slots or the same slot with later timestamp (refreshed votes)
     /// We directly compare as options to prioritize votes for same slot with timestamp as
     /// Some > None
+    ///
+    /// - Votes for earlier slots are always rejected.
+    /// - Refreshed votes replace the held vote, so that the vote with the freshest blockhash
+    ///   is processed and forwarded.
+    /// - Copies of the held vote are rejected, unless received over TPU or replenishing a
+    ///   taken vote.
     fn allow_update(
         vote: &LatestValidatorVotePacket,
         latest_vote: &LatestValidatorVotePacket,
@@ -283,27 +1042,27 @@ impl VoteStorage {
             cmp::Ordering::Equal => {}
         };
 
-        // Timestamps are equal, lastly check if vote was taken previously
-        // and should be replenished
+        // Timestamps are equal, so this is another copy of the same vote.
+        // A copy received over TPU replaces a pending copy from gossip, but
+        // not vice versa.
+        if vote.source() == VoteSource::Tpu
+            && latest_vote.source() == VoteSource::Gossip
+            && !latest_vote.is_vote_taken()
+        {
+            return true;
+        }
+
+        // Lastly check if vote was taken previously and should be replenished
         should_replenish_taken_votes && latest_vote.is_vote_taken()
     }
 
     fn weighted_random_order_by_stake(&self) -> impl Iterator<Item = Pubkey> {
-        // Efraimidis and Spirakis algo for weighted random sample without replacement
-        let mut pubkey_with_weight: Vec<(f64, Pubkey)> = self
+        let stakes = self
             .latest_vote_per_vote_pubkey
             .keys()
-            .filter_map(|&pubkey| {
-                let stake = self.cached_epoch_stakes.vote_account_stake(&pubkey);
-                if stake == 0 {
-                    None // Ignore votes from unstaked validators
-                } else {
-                    Some((thread_rng().gen::<f64>().powf(1.0 / (stake as f64)), pubkey))
-                }
-            })
-            .collect::<Vec<_>>();
-        pubkey_with_weight.sort_by(|(w1, _), (w2, _)| w1.partial_cmp(w2).unwrap());
-        pubkey_with_weight.into_iter().map(|(_, pubkey)| pubkey)
+            .map(|&pubkey| (pubkey, self.cached_epoch_stakes.vote_account_stake(&pubkey)))
+            .filter(|(_, stake)| *stake != 0); // Ignore votes from unstaked validators
+        weighted_random_order(stakes, &mut thread_rng()).into_iter()
     }
 
     /// Check if `vote` can land in our fork based on `slot_hashes`
@@ -321,6 +1080,14 @@ impl VoteStorage {
             .unwrap_or(false)
     }
 
+    /// Returns the slot and source of the latest vote held for `pubkey`,
+    /// whether or not it is processed yet
+    pub fn get_latest_vote(&self, pubkey: &Pubkey) -> Option<(Slot, VoteSource)> {
+        self.latest_vote_per_vote_pubkey
+            .get(pubkey)
+            .map(|vote| (vote.slot(), vote.source()))
+    }
+
     #[cfg(test)]
     pub fn get_latest_vote_slot(&self, pubkey: Pubkey) -> Option<solana_clock::Slot> {
         self.latest_vote_per_vote_pubkey
@@ -340,13 +1107,18 @@ impl VoteStorage {
 mod tests {
     use {
         super::*,
+        crate::banking_stage::duplicate_vote_filter::{
+            DuplicateVoteFilter, DuplicateVoteFilterService,
+        },
         solana_clock::UnixTimestamp,
         solana_epoch_schedule::MINIMUM_SLOTS_PER_EPOCH,
         solana_genesis_config::GenesisConfig,
-        solana_hash::Hash,
         solana_keypair::Keypair,
         solana_perf::packet::{BytesPacket, PacketFlags},
-        solana_runtime::genesis_utils::{self, ValidatorVoteKeypairs},
+        solana_runtime::{
+            bank_forks::BankForks,
+            genesis_utils::{self, ValidatorVoteKeypairs},
+        },
         solana_signer::Signer,
         solana_vote::vote_transaction::new_tower_sync_transaction,
         solana_vote_program::vote_state::TowerSync,
@@ -618,39 +1390,1159 @@ mod tests {
     }
 
     #[test]
-    fn test_clear() {
+    fn test_tpu_vote_replaces_gossip_copy() {
+        let keypair = ValidatorVoteKeypairs::new_rand();
+        let vote_pubkey = keypair.vote_keypair.pubkey();
+        let mut vote_storage = VoteStorage::new_for_tests(&[vote_pubkey]);
+        let new_vote = |source| from_slots(vec![(0, 2), (1, 1)], source, &keypair, Some(7));
+
+        assert!(vote_storage
+            .update_latest_vote(
+                new_vote(VoteSource::Gossip),
+                false /* should replenish */
+            )
+            .is_none());
+        assert_eq!(
+            vote_storage.get_latest_vote(&vote_pubkey),
+            Some((1, VoteSource::Gossip))
+        );
+
+        // A copy of the same vote from TPU replaces the copy from gossip.
+        let old_vote = vote_storage
+            .update_latest_vote(new_vote(VoteSource::Tpu), false /* should replenish */)
+            .unwrap();
+        assert_eq!(old_vote.source(), VoteSource::Gossip);
+        assert_eq!(
+            vote_storage.get_latest_vote(&vote_pubkey),
+            Some((1, VoteSource::Tpu))
+        );
+        assert_eq!(1, vote_storage.len());
+
+        // But not vice versa.
+        let vote = vote_storage
+            .update_latest_vote(
+                new_vote(VoteSource::Gossip),
+                false, /* should replenish */
+            )
+            .unwrap();
+        assert_eq!(vote.source(), VoteSource::Gossip);
+        assert_eq!(
+            vote_storage.get_latest_vote(&vote_pubkey),
+            Some((1, VoteSource::Tpu))
+        );
+
+        // Neither does a TPU copy replace a gossip copy which is already
+        // processed, so that the vote is not processed twice.
+        let mut vote_storage = VoteStorage::new_for_tests(&[vote_pubkey]);
+        vote_storage.update_latest_vote(
+            new_vote(VoteSource::Gossip),
+            false, /* should replenish */
+        );
+        assert_eq!(vote_storage.drain_votes(&None, None).len(), 1);
+        assert!(vote_storage
+            .update_latest_vote(new_vote(VoteSource::Tpu), false /* should replenish */)
+            .is_some());
+        assert_eq!(0, vote_storage.len());
+        assert_eq!(
+            vote_storage.get_latest_vote(&vote_pubkey),
+            Some((1, VoteSource::Gossip))
+        );
+    }
+
+    #[test]
+    fn test_drain_votes_from_source() {
         let keypair_a = ValidatorVoteKeypairs::new_rand();
         let keypair_b = ValidatorVoteKeypairs::new_rand();
         let keypair_c = ValidatorVoteKeypairs::new_rand();
-        let keypair_d = ValidatorVoteKeypairs::new_rand();
-        let mut vote_storage = VoteStorage::new_for_tests(&[
-            keypair_a.vote_keypair.pubkey(),
-            keypair_b.vote_keypair.pubkey(),
-            keypair_c.vote_keypair.pubkey(),
-            keypair_d.vote_keypair.pubkey(),
-        ]);
-
-        let vote_a = from_slots(vec![(1, 1)], VoteSource::Gossip, &keypair_a, None);
-        let vote_b = from_slots(vec![(2, 1)], VoteSource::Tpu, &keypair_b, None);
+        let vote_pubkey_a = keypair_a.vote_keypair.pubkey();
+        let vote_pubkey_b = keypair_b.vote_keypair.pubkey();
+        let vote_pubkey_c = keypair_c.vote_keypair.pubkey();
+        let mut vote_storage =
+            VoteStorage::new_for_tests(&[vote_pubkey_a, vote_pubkey_b, vote_pubkey_c]);
+        let vote_a = from_slots(vec![(1, 1)], VoteSource::Tpu, &keypair_a, None);
+        let vote_b = from_slots(vec![(2, 1)], VoteSource::Gossip, &keypair_b, None);
         let vote_c = from_slots(vec![(3, 1)], VoteSource::Tpu, &keypair_c, None);
-        let vote_d = from_slots(vec![(4, 1)], VoteSource::Gossip, &keypair_d, None);
-
         vote_storage.update_latest_vote(vote_a, false /* should replenish */);
         vote_storage.update_latest_vote(vote_b, false /* should replenish */);
         vote_storage.update_latest_vote(vote_c, false /* should replenish */);
-        vote_storage.update_latest_vote(vote_d, false /* should replenish */);
-        assert_eq!(4, vote_storage.len());
+        assert_eq!(3, vote_storage.len());
 
-        vote_storage.clear();
-        assert_eq!(0, vote_storage.len());
+        // Only the votes received over TPU are drained.
+        let drained = vote_storage.drain_votes(&None, Some(VoteSource::Tpu));
+        assert_eq!(drained.len(), 2);
+        assert_eq!(1, vote_storage.len());
+        assert!(vote_storage
+            .drain_votes(&None, Some(VoteSource::Tpu))
+            .is_empty());
 
+        // The gossip vote is left to be drained.
         assert_eq!(
-            Some(1),
-            vote_storage.get_latest_vote_slot(keypair_a.vote_keypair.pubkey())
+            vote_storage.get_latest_vote(&vote_pubkey_b),
+            Some((2, VoteSource::Gossip))
         );
         assert_eq!(
-            Some(2),
-            vote_storage.get_latest_vote_slot(keypair_b.vote_keypair.pubkey())
+            vote_storage
+                .drain_votes(&None, Some(VoteSource::Gossip))
+                .len(),
+            1
+        );
+        assert!(vote_storage.is_empty());
+        assert!(vote_storage.drain_votes(&None, None).is_empty());
+    }
+
+    #[test]
+    fn test_get_and_mark_forwarded() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let keypair_c = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[
+            keypair_a.vote_keypair.pubkey(),
+            keypair_b.vote_keypair.pubkey(),
+            keypair_c.vote_keypair.pubkey(),
+        ]);
+        for (slot, keypair) in [(1, &keypair_a), (2, &keypair_b), (3, &keypair_c)] {
+            let vote = from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+
+        // The cap on the number of packets is honored, and votes are only
+        // forwarded once.
+        assert_eq!(vote_storage.mark_forwarded(&None, 2).len(), 2);
+        assert_eq!(vote_storage.mark_forwarded(&None, 2).len(), 1);
+        assert!(vote_storage.mark_forwarded(&None, 2).is_empty());
+        // Forwarded votes are still processed locally.
+        assert_eq!(3, vote_storage.len());
+
+        // Another copy of the same vote is not forwarded again, but a vote
+        // for a newer slot is.
+        let vote = from_slots(vec![(1, 1)], VoteSource::Tpu, &keypair_a, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        assert!(vote_storage.mark_forwarded(&None, 2).is_empty());
+        let vote = from_slots(vec![(1, 2), (4, 1)], VoteSource::Gossip, &keypair_a, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        assert_eq!(vote_storage.mark_forwarded(&None, 2).len(), 1);
+        assert!(vote_storage.mark_forwarded(&None, 2).is_empty());
+
+        // All held votes can be forwarded again, e.g. to a new leader.
+        vote_storage.clear_forwarded_flags();
+        assert_eq!(vote_storage.mark_forwarded(&None, 10).len(), 3);
+
+        // Forwarded votes are still drained, after which there is nothing
+        // left to forward.
+        assert_eq!(vote_storage.drain_votes(&None, None).len(), 3);
+        vote_storage.clear_forwarded_flags();
+        assert!(vote_storage.mark_forwarded(&None, 10).is_empty());
+    }
+
+    #[test]
+    fn test_max_vote_pubkeys() {
+        let keypairs: Vec<_> = (0..4).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let stakes = [5, 10, 20, 1];
+        let mut vote_storage = VoteStorage::new_for_tests_with_stakes(
+            &keypairs
+                .iter()
+                .zip(stakes)
+                .map(|(keypairs, stake)| (keypairs.vote_keypair.pubkey(), stake))
+                .collect_vec(),
+        );
+        vote_storage.max_vote_pubkeys = 2;
+        let vote = |slot, keypair| from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+
+        // Full storage rejects new pubkeys while all votes are unprocessed.
+        assert!(vote_storage
+            .update_latest_vote(vote(1, &keypairs[0]), false /* should replenish */)
+            .is_none());
+        assert!(vote_storage
+            .update_latest_vote(vote(1, &keypairs[1]), false /* should replenish */)
+            .is_none());
+        assert!(vote_storage
+            .update_latest_vote(vote(1, &keypairs[2]), false /* should replenish */)
+            .is_some());
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypairs[2].vote_keypair.pubkey()),
+            None
+        );
+        // Pubkeys already tracked can still be updated.
+        assert!(vote_storage
+            .update_latest_vote(vote(2, &keypairs[1]), false /* should replenish */)
+            .is_some());
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypairs[1].vote_keypair.pubkey()),
+            Some(2)
+        );
+
+        // Once processed, the lowest staked pubkey is evicted in favor of a
+        // higher staked one.
+        assert_eq!(vote_storage.drain_votes(&None, None).len(), 2);
+        assert!(vote_storage
+            .update_latest_vote(vote(1, &keypairs[2]), false /* should replenish */)
+            .is_none());
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypairs[0].vote_keypair.pubkey()),
+            None
+        );
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypairs[1].vote_keypair.pubkey()),
+            Some(2)
+        );
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypairs[2].vote_keypair.pubkey()),
+            Some(1)
+        );
+
+        // Staked pubkeys are not evicted in favor of lower staked or unstaked
+        // ones.
+        assert!(vote_storage
+            .update_latest_vote(vote(1, &keypairs[3]), false /* should replenish */)
+            .is_some());
+        let unstaked = ValidatorVoteKeypairs::new_rand();
+        assert!(vote_storage
+            .update_latest_vote(vote(1, &unstaked), false /* should replenish */)
+            .is_some());
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypairs[1].vote_keypair.pubkey()),
+            Some(2)
+        );
+        assert_eq!(vote_storage.len(), 1);
+    }
+
+    #[test]
+    fn test_max_vote_pubkeys_replenished() {
+        let keypairs: Vec<_> = (0..3).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let stakes = [5, 10, 20];
+        let mut vote_storage = VoteStorage::new_for_tests_with_stakes(
+            &keypairs
+                .iter()
+                .zip(stakes)
+                .map(|(keypairs, stake)| (keypairs.vote_keypair.pubkey(), stake))
+                .collect_vec(),
+        );
+        vote_storage.max_vote_pubkeys = 2;
+        let vote = |slot, keypair| from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+
+        vote_storage.update_latest_vote(vote(1, &keypairs[0]), false /* should replenish */);
+        vote_storage.update_latest_vote(vote(1, &keypairs[1]), false /* should replenish */);
+        assert_eq!(vote_storage.drain_votes(&None, None).len(), 2);
+        // The lowest staked pubkey holds an unprocessed vote again, so the
+        // next lowest one is evicted instead.
+        assert!(vote_storage
+            .update_latest_vote(vote(2, &keypairs[0]), false /* should replenish */)
+            .is_none());
+        assert!(vote_storage
+            .update_latest_vote(vote(1, &keypairs[2]), false /* should replenish */)
+            .is_none());
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypairs[0].vote_keypair.pubkey()),
+            Some(2)
+        );
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypairs[1].vote_keypair.pubkey()),
+            None
+        );
+        assert_eq!(vote_storage.len(), 2);
+    }
+
+    #[test]
+    fn test_evict_unstaked() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let keypair_c = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[
+            keypair_a.vote_keypair.pubkey(),
+            keypair_b.vote_keypair.pubkey(),
+            keypair_c.vote_keypair.pubkey(),
+        ]);
+        for keypairs in [&keypair_a, &keypair_b, &keypair_c] {
+            let vote = from_slots(vec![(1, 1)], VoteSource::Gossip, keypairs, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+        vote_storage
+            .latest_vote_per_vote_pubkey
+            .get_mut(&keypair_c.vote_keypair.pubkey())
+            .unwrap()
+            .take_vote()
+            .unwrap();
+        vote_storage.num_unprocessed_votes -= 1;
+
+        // Only keypair_a is staked in the bank.
+        let config =
+            genesis_utils::create_genesis_config_with_vote_accounts(100, &[&keypair_a], vec![200])
+                .genesis_config;
+        let bank = Bank::new_for_tests(&config);
+        assert_eq!(vote_storage.evict_unstaked(&bank), 1);
+        assert_eq!(vote_storage.len(), 1);
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypair_a.vote_keypair.pubkey()),
+            Some(1)
+        );
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypair_b.vote_keypair.pubkey()),
+            None
+        );
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypair_c.vote_keypair.pubkey()),
+            None
+        );
+        assert_eq!(vote_storage.evict_unstaked(&bank), 0);
+    }
+
+    #[test]
+    fn test_purge_below() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let keypair_c = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[
+            keypair_a.vote_keypair.pubkey(),
+            keypair_b.vote_keypair.pubkey(),
+            keypair_c.vote_keypair.pubkey(),
+        ]);
+        for (slot, keypair) in [(1, &keypair_a), (2, &keypair_b), (10, &keypair_c)] {
+            let vote = from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+
+        assert_eq!(vote_storage.purge_below(1), 0);
+        assert_eq!(vote_storage.purge_below(5), 2);
+        assert_eq!(vote_storage.len(), 1);
+        // Purged votes are not counted again.
+        assert_eq!(vote_storage.purge_below(5), 0);
+        assert_eq!(vote_storage.len(), 1);
+
+        // Votes no newer than the purged ones are still rejected.
+        let vote = from_slots(vec![(1, 1)], VoteSource::Tpu, &keypair_a, None);
+        assert!(vote_storage
+            .update_latest_vote(vote, false /* should replenish */)
+            .is_some());
+        assert_eq!(vote_storage.len(), 1);
+
+        // A newer vote for a purged pubkey is held again.
+        let vote = from_slots(vec![(1, 2), (6, 1)], VoteSource::Gossip, &keypair_a, None);
+        assert!(vote_storage
+            .update_latest_vote(vote, false /* should replenish */)
+            .is_none());
+        assert_eq!(vote_storage.len(), 2);
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypair_a.vote_keypair.pubkey()),
+            Some(6)
+        );
+        let drained = vote_storage.drain_votes(&None, None);
+        assert_eq!(drained.len(), 2);
+        assert!(vote_storage.is_empty());
+    }
+
+    #[test]
+    fn test_handle_new_root() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let keypair_c = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[
+            keypair_a.vote_keypair.pubkey(),
+            keypair_b.vote_keypair.pubkey(),
+            keypair_c.vote_keypair.pubkey(),
+        ]);
+        assert_eq!(vote_storage.min_held_slot, Slot::MAX);
+        assert_eq!(vote_storage.handle_new_root(100), 0);
+
+        for (slot, keypair) in [(10, &keypair_a), (5, &keypair_b), (20, &keypair_c)] {
+            let vote = from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+        assert_eq!(vote_storage.min_held_slot, 5);
+        // Nothing held below the root.
+        assert_eq!(vote_storage.handle_new_root(5), 0);
+        assert_eq!(vote_storage.len(), 3);
+
+        assert_eq!(vote_storage.handle_new_root(8), 1);
+        assert_eq!(vote_storage.min_held_slot, 10);
+        assert_eq!(vote_storage.len(), 2);
+
+        // A newer vote replacing a held one keeps the bound.
+        let vote = from_slots(vec![(10, 2), (15, 1)], VoteSource::Tpu, &keypair_a, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        assert_eq!(vote_storage.min_held_slot, 10);
+        assert_eq!(vote_storage.handle_new_root(10), 0);
+        assert_eq!(vote_storage.handle_new_root(12), 0);
+        // Recomputed from the votes left after the scan.
+        assert_eq!(vote_storage.min_held_slot, 15);
+
+        // A vote below the bound lowers it.
+        let vote = from_slots(vec![(5, 2), (9, 1)], VoteSource::Gossip, &keypair_b, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        assert_eq!(vote_storage.min_held_slot, 9);
+        assert_eq!(vote_storage.len(), 3);
+        assert_eq!(vote_storage.handle_new_root(16), 2);
+        assert_eq!(vote_storage.min_held_slot, 20);
+        assert_eq!(vote_storage.len(), 1);
+        assert_eq!(vote_storage.snapshot_stats().num_purged, 3);
+
+        // Drained votes leave a stale bound, which the next scan corrects.
+        let vote = from_slots(vec![(17, 1)], VoteSource::Tpu, &keypair_a, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        assert_eq!(vote_storage.min_held_slot, 17);
+        assert_eq!(vote_storage.drain_votes(&None, None).len(), 2);
+        assert_eq!(vote_storage.handle_new_root(18), 0);
+        assert_eq!(vote_storage.min_held_slot, Slot::MAX);
+
+        let vote = from_slots(vec![(30, 1)], VoteSource::Tpu, &keypair_c, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        assert_eq!(vote_storage.min_held_slot, 30);
+        vote_storage.clear();
+        assert_eq!(vote_storage.min_held_slot, Slot::MAX);
+        assert_eq!(vote_storage.handle_new_root(40), 0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let keypair_c = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[
+            keypair_a.vote_keypair.pubkey(),
+            keypair_b.vote_keypair.pubkey(),
+            keypair_c.vote_keypair.pubkey(),
+        ]);
+        vote_storage.max_vote_pubkeys = 2;
+        let vote = |slot, keypair| from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+
+        vote_storage.update_latest_vote(vote(10, &keypair_a), false /* should replenish */);
+        vote_storage.update_latest_vote(vote(2, &keypair_b), false /* should replenish */);
+        // Replaces the unprocessed vote of keypair_a.
+        vote_storage.update_latest_vote(vote(12, &keypair_a), false /* should replenish */);
+        // Stale votes.
+        vote_storage.update_latest_vote(vote(11, &keypair_a), false /* should replenish */);
+        vote_storage.update_latest_vote(vote(1, &keypair_b), false /* should replenish */);
+        // The storage is full.
+        vote_storage.update_latest_vote(vote(3, &keypair_c), false /* should replenish */);
+        assert_eq!(
+            vote_storage.snapshot_stats(),
+            VoteStorageStats {
+                num_updates: 6,
+                num_stale_updates: 2,
+                num_rejected_full: 1,
+                num_rejected_future: 0,
+                num_rejected_duplicate: 0,
+                num_replacements: 1,
+                num_refreshes: 0,
+                num_drained: 0,
+                num_purged: 0,
+                num_expired: 0,
+            }
+        );
+        assert_eq!(vote_storage.slot_lag_histogram(12), [1, 0, 1, 0, 0, 0]);
+        assert_eq!(vote_storage.slot_lag_histogram(300), [0, 0, 0, 0, 0, 2]);
+        assert_eq!(vote_storage.slot_lag_histogram(0), [2, 0, 0, 0, 0, 0]);
+
+        assert_eq!(vote_storage.purge_below(5), 1);
+        assert_eq!(vote_storage.drain_votes(&None, None).len(), 1);
+        let stats = vote_storage.snapshot_stats();
+        assert_eq!(stats.num_purged, 1);
+        assert_eq!(stats.num_drained, 1);
+        assert_eq!(
+            vote_storage.slot_lag_histogram(12),
+            [0; NUM_SLOT_LAG_BUCKETS]
+        );
+
+        vote_storage.report(12);
+        assert_eq!(vote_storage.snapshot_stats(), VoteStorageStats::default());
+    }
+
+    #[test]
+    fn test_contains_and_remove() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let pubkey_a = keypair_a.vote_keypair.pubkey();
+        let pubkey_b = keypair_b.vote_keypair.pubkey();
+        let mut vote_storage = VoteStorage::new_for_tests(&[pubkey_a, pubkey_b]);
+        let vote = from_slots(vec![(5, 1)], VoteSource::Gossip, &keypair_a, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+
+        assert!(vote_storage.contains(&pubkey_a));
+        assert!(!vote_storage.contains(&pubkey_b));
+        assert!(vote_storage.remove(&pubkey_b).is_none());
+        assert_eq!(vote_storage.len(), 1);
+
+        let removed = vote_storage.remove(&pubkey_a).unwrap();
+        assert!(
+            LatestValidatorVotePacket::new_from_immutable(removed, VoteSource::Tpu, true)
+                .is_ok_and(|vote| vote.slot() == 5)
+        );
+        assert!(!vote_storage.contains(&pubkey_a));
+        assert!(vote_storage.is_empty());
+        assert!(vote_storage.remove(&pubkey_a).is_none());
+        assert!(vote_storage.is_empty());
+
+        // Older votes are still rejected after the removal, newer ones are
+        // held again.
+        let vote = from_slots(vec![(4, 1)], VoteSource::Gossip, &keypair_a, None);
+        assert!(vote_storage
+            .update_latest_vote(vote, false /* should replenish */)
+            .is_some());
+        assert!(!vote_storage.contains(&pubkey_a));
+        let vote = from_slots(vec![(6, 1)], VoteSource::Gossip, &keypair_a, None);
+        assert!(vote_storage
+            .update_latest_vote(vote, false /* should replenish */)
+            .is_none());
+        assert!(vote_storage.contains(&pubkey_a));
+        assert_eq!(vote_storage.len(), 1);
+    }
+
+    #[test]
+    fn test_retain() {
+        let keypairs: Vec<_> = (0..8).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let mut vote_storage = VoteStorage::new_for_tests(
+            &keypairs
+                .iter()
+                .map(|keypair| keypair.vote_keypair.pubkey())
+                .collect_vec(),
+        );
+        for (slot, keypair) in (1..).zip(&keypairs) {
+            let vote = from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+        // One of the votes is already processed.
+        vote_storage.remove(&keypairs[0].vote_keypair.pubkey());
+        assert_eq!(vote_storage.len(), 7);
+
+        let mut visited = 0;
+        vote_storage.retain(|_, slot| {
+            visited += 1;
+            slot % 2 == 0
+        });
+        assert_eq!(visited, 8);
+        assert_eq!(vote_storage.len(), 4);
+        for (slot, keypair) in (1..).zip(&keypairs) {
+            let pubkey = keypair.vote_keypair.pubkey();
+            assert_eq!(vote_storage.contains(&pubkey), slot % 2 == 0);
+            assert_eq!(
+                vote_storage.get_latest_vote_slot(pubkey),
+                (slot % 2 == 0).then_some(slot)
+            );
+        }
+        assert_eq!(vote_storage.drain_votes(&None, None).len(), 4);
+    }
+
+    #[test]
+    fn test_refreshed_vote() {
+        let keypair = ValidatorVoteKeypairs::new_rand();
+        let pubkey = keypair.vote_keypair.pubkey();
+        let mut vote_storage = VoteStorage::new_for_tests(&[pubkey]);
+        let vote = |slot, timestamp| {
+            from_slots(
+                vec![(slot, 1)],
+                VoteSource::Gossip,
+                &keypair,
+                Some(timestamp),
+            )
+        };
+        assert!(vote_storage
+            .update_latest_vote(vote(5, 100), false /* should replenish */)
+            .is_none());
+        assert_eq!(vote_storage.mark_forwarded(&None, 1).len(), 1);
+
+        // Votes for earlier slots are rejected, even if newer.
+        assert!(vote_storage
+            .update_latest_vote(vote(4, 200), false /* should replenish */)
+            .is_some_and(|vote| vote.slot() == 4));
+        // Copies of the held vote are rejected.
+        assert!(vote_storage
+            .update_latest_vote(vote(5, 100), false /* should replenish */)
+            .is_some_and(|vote| vote.timestamp() == Some(100)));
+        assert_eq!(vote_storage.get_latest_timestamp(pubkey), Some(100));
+        assert_eq!(vote_storage.snapshot_stats().num_refreshes, 0);
+
+        // A refreshed vote replaces the held one, which is returned, and is
+        // forwarded again.
+        assert!(vote_storage
+            .update_latest_vote(vote(5, 101), false /* should replenish */)
+            .is_some_and(|vote| vote.timestamp() == Some(100)));
+        assert_eq!(vote_storage.get_latest_timestamp(pubkey), Some(101));
+        assert_eq!(vote_storage.len(), 1);
+        assert_eq!(vote_storage.snapshot_stats().num_refreshes, 1);
+        assert_eq!(vote_storage.snapshot_stats().num_replacements, 1);
+        assert_eq!(vote_storage.mark_forwarded(&None, 1).len(), 1);
+
+        // A refresh of an already processed vote is held again.
+        assert_eq!(vote_storage.drain_votes(&None, None).len(), 1);
+        assert!(vote_storage
+            .update_latest_vote(vote(5, 102), false /* should replenish */)
+            .is_none());
+        assert_eq!(vote_storage.len(), 1);
+        assert_eq!(vote_storage.snapshot_stats().num_refreshes, 2);
+    }
+
+    #[test]
+    fn test_drain_up_to() {
+        let heavy_keypairs: Vec<_> = (0..4).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let light_keypairs: Vec<_> = (0..2).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let mut vote_storage = VoteStorage::new_for_tests_with_stakes(
+            &heavy_keypairs
+                .iter()
+                .map(|keypair| (keypair.vote_keypair.pubkey(), 1_000_000))
+                .chain(
+                    light_keypairs
+                        .iter()
+                        .map(|keypair| (keypair.vote_keypair.pubkey(), 1)),
+                )
+                .collect_vec(),
+        );
+        let vote = |slot, keypair| from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+        for keypair in heavy_keypairs.iter().chain(&light_keypairs) {
+            vote_storage.update_latest_vote(vote(1, keypair), false /* should replenish */);
+        }
+
+        let (votes, num_left) = vote_storage.drain_votes_up_to(&None, 2);
+        assert_eq!(votes.len(), 2);
+        assert_eq!(num_left, 4);
+        assert_eq!(vote_storage.len(), 4);
+
+        // Heavy validators vote again every round, but the light ones still
+        // get their votes drained eventually.
+        let mut num_rounds = 1;
+        while light_keypairs
+            .iter()
+            .any(|keypair| vote_storage.contains(&keypair.vote_keypair.pubkey()))
+        {
+            assert!(num_rounds < 64, "light validators are starved");
+            for keypair in &heavy_keypairs {
+                let vote = vote(num_rounds + 1, keypair);
+                vote_storage.update_latest_vote(vote, false /* should replenish */);
+            }
+            let (votes, num_left) = vote_storage.drain_votes_up_to(&None, 2);
+            assert_eq!(votes.len(), 2);
+            assert_eq!(num_left, vote_storage.len());
+            num_rounds += 1;
+        }
+
+        // A drained validator is not boosted anymore.
+        assert!(light_keypairs.iter().all(|keypair| !vote_storage
+            .skipped_drain_rounds
+            .contains_key(&keypair.vote_keypair.pubkey())));
+        let num_pending = vote_storage.len();
+        let (votes, num_left) = vote_storage.drain_votes_up_to(&None, usize::MAX);
+        assert_eq!(votes.len(), num_pending);
+        assert_eq!(num_left, 0);
+        assert!(vote_storage.skipped_drain_rounds.is_empty());
+    }
+
+    #[test]
+    fn test_drain_up_to_invalid_for_fork() {
+        let keypairs: Vec<_> = (0..2).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let pubkeys = keypairs
+            .iter()
+            .map(|keypair| keypair.vote_keypair.pubkey())
+            .collect_vec();
+        let mut vote_storage = VoteStorage::new_for_tests(&pubkeys);
+        for (slot, keypair) in [1, 2].into_iter().zip(&keypairs) {
+            let vote = from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+
+        // The vote for slot 1 isn't on our fork, it's left unprocessed but
+        // isn't boosted in the next round
+        let slot_hashes = Some(SlotHashes::new(&[
+            (1, Hash::new_unique()),
+            (2, Hash::default()),
+            (3, Hash::default()),
+        ]));
+        let (votes, num_left) = vote_storage.drain_votes_up_to(&slot_hashes, 2);
+        assert_eq!(votes.len(), 1);
+        assert_eq!(num_left, 1);
+        assert!(vote_storage.skipped_drain_rounds.is_empty());
+
+        // Nor is it once the budget runs out before reaching it
+        let vote = from_slots(vec![(3, 1)], VoteSource::Gossip, &keypairs[1], None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        for _ in 0..8 {
+            let (votes, _) = vote_storage.drain_votes_up_to(&slot_hashes, 0);
+            assert!(votes.is_empty());
+        }
+        assert!(!vote_storage.skipped_drain_rounds.contains_key(&pubkeys[0]));
+        assert_eq!(vote_storage.skipped_drain_rounds.get(&pubkeys[1]), Some(&8));
+    }
+
+    #[test]
+    fn test_max_future_slot_offset() {
+        let keypair = ValidatorVoteKeypairs::new_rand();
+        let pubkey = keypair.vote_keypair.pubkey();
+        let mut vote_storage = VoteStorage::new_for_tests(&[pubkey]);
+        let vote = |slot| from_slots(vec![(slot, 1)], VoteSource::Gossip, &keypair, None);
+
+        // Unset, votes for any slot are accepted.
+        assert!(vote_storage
+            .update_latest_vote(vote(1_000), false /* should replenish */)
+            .is_none());
+        assert_eq!(vote_storage.get_latest_vote_slot(pubkey), Some(1_000));
+        vote_storage.retain(|_, _| false);
+        assert!(vote_storage.is_empty());
+
+        vote_storage.set_max_future_slot_offset(Some(10));
+        vote_storage.set_current_slot(100);
+        // A vote for a far future slot is rejected, without tracking the
+        // pubkey.
+        assert!(vote_storage
+            .update_latest_vote(vote(1_000_000), false /* should replenish */)
+            .is_some());
+        assert_eq!(vote_storage.get_latest_vote_slot(pubkey), None);
+        assert_eq!(vote_storage.snapshot_stats().num_rejected_future, 1);
+
+        // Real votes are still accepted.
+        assert!(vote_storage
+            .update_latest_vote(vote(99), false /* should replenish */)
+            .is_none());
+        assert!(vote_storage
+            .update_latest_vote(vote(110), false /* should replenish */)
+            .is_some());
+        assert_eq!(vote_storage.get_latest_vote_slot(pubkey), Some(110));
+        assert!(vote_storage
+            .update_latest_vote(vote(111), false /* should replenish */)
+            .is_some_and(|vote| vote.slot() == 111));
+        assert_eq!(vote_storage.get_latest_vote_slot(pubkey), Some(110));
+        assert_eq!(vote_storage.snapshot_stats().num_rejected_future, 2);
+
+        // The horizon moves with the current slot.
+        vote_storage.set_current_slot(101);
+        assert!(vote_storage
+            .update_latest_vote(vote(111), false /* should replenish */)
+            .is_some_and(|vote| vote.slot() == 110));
+        assert_eq!(vote_storage.get_latest_vote_slot(pubkey), Some(111));
+        assert_eq!(vote_storage.len(), 1);
+    }
+
+    #[test]
+    fn test_mark_processed() {
+        let keypairs: Vec<_> = (0..4).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let pubkeys = keypairs
+            .iter()
+            .map(|keypair| keypair.vote_keypair.pubkey())
+            .collect_vec();
+        let mut vote_storage = VoteStorage::new_for_tests(&pubkeys);
+        for keypair in &keypairs {
+            let vote = from_slots(vec![(5, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+
+        // The held vote landed.
+        assert!(vote_storage.mark_processed(&pubkeys[0], 5).is_some());
+        assert!(!vote_storage.contains(&pubkeys[0]));
+        assert_eq!(vote_storage.len(), 3);
+        assert!(vote_storage.mark_processed(&pubkeys[0], 5).is_none());
+        assert_eq!(vote_storage.len(), 3);
+        // Older votes are still rejected.
+        let vote = from_slots(vec![(4, 1)], VoteSource::Tpu, &keypairs[0], None);
+        assert!(vote_storage
+            .update_latest_vote(vote, false /* should replenish */)
+            .is_some());
+        assert!(!vote_storage.contains(&pubkeys[0]));
+
+        // A later vote landed, so the held one is obsolete.
+        assert!(vote_storage.mark_processed(&pubkeys[1], 6).is_some());
+        assert!(!vote_storage.contains(&pubkeys[1]));
+        assert_eq!(vote_storage.len(), 2);
+
+        // An older vote landed, the newer held vote is kept.
+        assert!(vote_storage.mark_processed(&pubkeys[2], 4).is_none());
+        assert!(vote_storage.contains(&pubkeys[2]));
+        assert_eq!(vote_storage.len(), 2);
+
+        // Unknown pubkeys are ignored.
+        assert!(vote_storage
+            .mark_processed(&Pubkey::new_unique(), 5)
+            .is_none());
+        assert_eq!(vote_storage.len(), 2);
+    }
+
+    #[test]
+    fn test_mark_processed_batch() {
+        let keypairs: Vec<_> = (0..4).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let pubkeys = keypairs
+            .iter()
+            .map(|keypair| keypair.vote_keypair.pubkey())
+            .collect_vec();
+        let mut vote_storage = VoteStorage::new_for_tests(&pubkeys);
+        for keypair in &keypairs {
+            let vote = from_slots(vec![(5, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+
+        let landed = [
+            (pubkeys[0], 5),
+            (pubkeys[1], 7),
+            (pubkeys[2], 3),
+            (pubkeys[0], 6),
+            (Pubkey::new_unique(), 5),
+        ];
+        assert_eq!(vote_storage.mark_processed_batch(&landed), 2);
+        assert_eq!(vote_storage.len(), 2);
+        assert!(!vote_storage.contains(&pubkeys[0]));
+        assert!(!vote_storage.contains(&pubkeys[1]));
+        assert!(vote_storage.contains(&pubkeys[2]));
+        assert!(vote_storage.contains(&pubkeys[3]));
+        assert_eq!(vote_storage.mark_processed_batch(&landed), 0);
+        assert_eq!(vote_storage.len(), 2);
+    }
+
+    #[test]
+    fn test_admission_filter() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let keypair_c = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[
+            keypair_a.vote_keypair.pubkey(),
+            keypair_b.vote_keypair.pubkey(),
+            keypair_c.vote_keypair.pubkey(),
+        ]);
+        let filter = Arc::new(DuplicateVoteFilter::default());
+        vote_storage.set_admission_filter(filter.clone());
+        let vote = |slot, keypair| from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+
+        assert!(vote_storage
+            .update_latest_vote(vote(10, &keypair_a), false /* should replenish */)
+            .is_none());
+
+        // Once the slot is known to be duplicate, votes for it are rejected,
+        // while votes for other slots are not.
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let exit = Arc::new(AtomicBool::new(false));
+        let bank_forks = BankForks::new_rw_arc(Bank::new_for_tests(&GenesisConfig::default()));
+        let service =
+            DuplicateVoteFilterService::new(filter.clone(), receiver, bank_forks, exit.clone());
+        sender.send(10).unwrap();
+        while !filter.is_duplicate_fork_vote(10) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(vote_storage
+            .update_latest_vote(vote(10, &keypair_b), false /* should replenish */)
+            .is_some());
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypair_b.vote_keypair.pubkey()),
+            None
+        );
+        assert!(vote_storage
+            .update_latest_vote(vote(11, &keypair_b), false /* should replenish */)
+            .is_none());
+        assert_eq!(vote_storage.snapshot_stats().num_rejected_duplicate, 1);
+        exit.store(true, Ordering::Relaxed);
+        service.join().unwrap();
+
+        // Once rooted past, the slot does not matter anymore.
+        filter.set_root(10);
+        assert!(vote_storage
+            .update_latest_vote(vote(10, &keypair_c), false /* should replenish */)
+            .is_none());
+        assert_eq!(
+            vote_storage.get_latest_vote_slot(keypair_c.vote_keypair.pubkey()),
+            Some(10)
+        );
+        assert_eq!(vote_storage.snapshot_stats().num_rejected_duplicate, 1);
+        assert_eq!(vote_storage.len(), 3);
+    }
+
+    #[test]
+    fn test_export() {
+        let keypairs: Vec<_> = (0..3).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let pubkeys = keypairs
+            .iter()
+            .map(|keypair| keypair.vote_keypair.pubkey())
+            .collect_vec();
+        let mut vote_storage = VoteStorage::new_for_tests(&pubkeys);
+        assert!(vote_storage.export().is_empty());
+        for (slot, keypair) in (1..).zip(&keypairs) {
+            let vote = from_slots(vec![(slot, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        vote_storage.mark_forwarded(&None, 3);
+        vote_storage.remove(&pubkeys[1]);
+        // Replacing a vote makes it the newest one.
+        let vote = from_slots(vec![(1, 2), (4, 1)], VoteSource::Tpu, &keypairs[0], None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+
+        let export = vote_storage.export();
+        assert_eq!(export.len(), 2);
+        assert_eq!(
+            export
+                .iter()
+                .map(|info| (info.vote_pubkey, info.slot, info.source, info.forwarded))
+                .collect_vec(),
+            vec![
+                (pubkeys[2], 3, VoteSource::Gossip, true),
+                (pubkeys[0], 4, VoteSource::Tpu, false),
+            ]
+        );
+        assert!(export[0].received < export[1].received);
+
+        let summary = vote_storage.fmt_summary(1);
+        let lines = summary.lines().collect_vec();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "2 unprocessed votes from 3 vote accounts");
+        assert!(lines[1].starts_with(&format!("{} slot: 3 source: Gossip", pubkeys[2])));
+        assert_eq!(vote_storage.fmt_summary(10).lines().count(), 3);
+    }
+
+    #[test]
+    fn test_expire_older_than() {
+        let keypairs: Vec<_> = (0..3).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let pubkeys = keypairs
+            .iter()
+            .map(|keypair| keypair.vote_keypair.pubkey())
+            .collect_vec();
+        let mut vote_storage = VoteStorage::new_for_tests(&pubkeys);
+        for keypair in &keypairs {
+            let vote = from_slots(vec![(5, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let received = vote_storage
+            .export()
+            .into_iter()
+            .map(|info| info.received)
+            .collect_vec();
+        assert_eq!(received.len(), 3);
+
+        // Votes received exactly at the deadline are kept.
+        assert!(vote_storage.expire_received_before(received[0]).is_empty());
+        assert_eq!(vote_storage.expire_received_before(received[1]).len(), 1);
+        assert_eq!(vote_storage.len(), 2);
+        assert!(!vote_storage.contains(&pubkeys[0]));
+        assert!(vote_storage.expire_received_before(received[1]).is_empty());
+
+        // A newer vote refreshes the receive time.
+        let vote = from_slots(vec![(5, 2), (6, 1)], VoteSource::Gossip, &keypairs[1], None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        assert_eq!(vote_storage.expire_received_before(received[2]).len(), 0);
+        let deadline = received[2] + Duration::from_nanos(1);
+        assert_eq!(vote_storage.expire_received_before(deadline).len(), 1);
+        assert!(vote_storage.contains(&pubkeys[1]));
+        assert!(!vote_storage.contains(&pubkeys[2]));
+        assert_eq!(vote_storage.len(), 1);
+        assert_eq!(vote_storage.snapshot_stats().num_expired, 2);
+
+        // Expired pubkeys still reject older votes.
+        let vote = from_slots(vec![(4, 1)], VoteSource::Gossip, &keypairs[0], None);
+        assert!(vote_storage
+            .update_latest_vote(vote, false /* should replenish */)
+            .is_some());
+        assert_eq!(vote_storage.get_latest_vote_slot(pubkeys[0]), Some(5));
+
+        assert!(vote_storage
+            .expire_older_than(Duration::from_secs(3600))
+            .is_empty());
+        assert_eq!(vote_storage.expire_older_than(Duration::ZERO).len(), 1);
+        assert!(vote_storage.is_empty());
+    }
+
+    #[test]
+    fn test_expire_held_votes() {
+        let keypair = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[keypair.vote_keypair.pubkey()]);
+        let vote = from_slots(vec![(5, 1)], VoteSource::Gossip, &keypair, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+
+        // Expiry is disabled by default.
+        assert!(vote_storage.expire_held_votes().is_empty());
+        assert_eq!(vote_storage.len(), 1);
+
+        vote_storage.set_max_held_vote_age(Some(Duration::from_secs(3600)));
+        assert!(vote_storage.expire_held_votes().is_empty());
+        vote_storage.set_max_held_vote_age(Some(Duration::ZERO));
+        assert_eq!(vote_storage.expire_held_votes().len(), 1);
+        assert!(vote_storage.is_empty());
+    }
+
+    #[test]
+    fn test_spawn_expiry_task() {
+        let keypair = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[keypair.vote_keypair.pubkey()]);
+        let vote = from_slots(vec![(5, 1)], VoteSource::Gossip, &keypair, None);
+        vote_storage.update_latest_vote(vote, false /* should replenish */);
+        vote_storage.set_max_held_vote_age(Some(Duration::ZERO));
+        let vote_storage = Arc::new(Mutex::new(vote_storage));
+        let exit = Arc::<AtomicBool>::default();
+        let expiry_task = VoteStorage::spawn_expiry_task(
+            vote_storage.clone(),
+            Duration::from_millis(1),
+            exit.clone(),
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !vote_storage.lock().unwrap().is_empty() {
+            assert!(Instant::now() < deadline);
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(vote_storage.lock().unwrap().snapshot_stats().num_expired, 1);
+
+        exit.store(true, Ordering::Relaxed);
+        expiry_task.join().unwrap();
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum StarvationReport {
+        Empty(Slot, u32),
+        Stale(Slot, usize),
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingStarvationReporter {
+        reports: Mutex<Vec<StarvationReport>>,
+    }
+
+    impl RecordingStarvationReporter {
+        fn take_reports(&self) -> Vec<StarvationReport> {
+            std::mem::take(&mut self.reports.lock().unwrap())
+        }
+    }
+
+    impl StarvationReporter for RecordingStarvationReporter {
+        fn report_empty(&self, slot: Slot, num_empty_checks: u32) {
+            self.reports
+                .lock()
+                .unwrap()
+                .push(StarvationReport::Empty(slot, num_empty_checks));
+        }
+
+        fn report_stale(&self, slot: Slot, num_stale_votes: usize) {
+            self.reports
+                .lock()
+                .unwrap()
+                .push(StarvationReport::Stale(slot, num_stale_votes));
+        }
+    }
+
+    #[test]
+    fn test_report_starvation() {
+        let keypairs: Vec<_> = (0..2).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let pubkeys = keypairs
+            .iter()
+            .map(|keypair| keypair.vote_keypair.pubkey())
+            .collect_vec();
+        let mut vote_storage = VoteStorage::new_for_tests(&pubkeys);
+        let reporter = Arc::new(RecordingStarvationReporter::default());
+        vote_storage.set_starvation_reporter(reporter.clone());
+        vote_storage.set_starvation_thresholds(StarvationThresholds {
+            max_empty_checks: 2,
+            max_stale_votes: 1,
+            stale_slots: 4,
+        });
+
+        // Empty for more than max_empty_checks checks, once per slot.
+        let now = Instant::now();
+        vote_storage.report_starvation_at(1, now);
+        vote_storage.report_starvation_at(2, now);
+        vote_storage.report_starvation_at(2, now);
+        assert!(reporter.take_reports().is_empty());
+        vote_storage.report_starvation_at(3, now);
+        vote_storage.report_starvation_at(4, now);
+        assert_eq!(
+            reporter.take_reports(),
+            vec![StarvationReport::Empty(3, 3), StarvationReport::Empty(4, 4)]
+        );
+
+        // Holding votes resets the count.
+        let now = Instant::now();
+        for keypair in &keypairs {
+            let vote = from_slots(vec![(5, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+        let stale_age = Duration::from_millis(4 * DEFAULT_MS_PER_SLOT);
+        vote_storage.report_starvation_at(5, now + stale_age);
+        assert!(reporter.take_reports().is_empty());
+
+        // More than max_stale_votes votes received over stale_slots ago.
+        let later = now + stale_age + Duration::from_millis(100);
+        vote_storage.report_starvation_at(6, later);
+        vote_storage.report_starvation_at(6, later);
+        assert_eq!(reporter.take_reports(), vec![StarvationReport::Stale(6, 2)]);
+        vote_storage.remove(&pubkeys[0]);
+        vote_storage.report_starvation_at(7, later);
+        assert!(reporter.take_reports().is_empty());
+
+        vote_storage.clear();
+        vote_storage.report_starvation_at(8, later);
+        vote_storage.report_starvation_at(9, later);
+        assert!(reporter.take_reports().is_empty());
+        vote_storage.report_starvation_at(10, later);
+        assert_eq!(
+            reporter.take_reports(),
+            vec![StarvationReport::Empty(10, 3)]
+        );
+    }
+
+    #[test]
+    fn test_cache_epoch_boundary_info_resets_rounds() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let config = genesis_utils::create_genesis_config_with_vote_accounts(
+            100,
+            &[&keypair_a, &keypair_b],
+            vec![200, 200],
+        )
+        .genesis_config;
+        let bank_0 = Arc::new(Bank::new_for_tests(&config));
+        let mut vote_storage = VoteStorage::new(&bank_0);
+        for keypair in [&keypair_a, &keypair_b] {
+            let vote = from_slots(vec![(1, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+        assert_eq!(vote_storage.mark_forwarded(&None, 2).len(), 2);
+        let (_, num_left) = vote_storage.drain_votes_up_to(&None, 1);
+        assert_eq!(num_left, 1);
+        assert_eq!(vote_storage.skipped_drain_rounds.len(), 1);
+
+        // Nothing is reset within the epoch.
+        let bank = Bank::new_from_parent(
+            bank_0.clone(),
+            &Pubkey::new_unique(),
+            MINIMUM_SLOTS_PER_EPOCH - 1,
+        );
+        vote_storage.cache_epoch_boundary_info(&bank);
+        assert!(vote_storage.mark_forwarded(&None, 2).is_empty());
+        assert_eq!(vote_storage.skipped_drain_rounds.len(), 1);
+
+        // The next epoch resets forwarding and drain rounds, and keeps the
+        // votes of validators still staked.
+        let bank = Bank::new_from_parent(bank_0, &Pubkey::new_unique(), MINIMUM_SLOTS_PER_EPOCH);
+        assert_eq!(bank.epoch(), 1);
+        vote_storage.cache_epoch_boundary_info(&bank);
+        assert_eq!(vote_storage.current_epoch, 1);
+        assert!(vote_storage.skipped_drain_rounds.is_empty());
+        assert_eq!(vote_storage.len(), 1);
+        assert_eq!(vote_storage.mark_forwarded(&None, 2).len(), 1);
+    }
+
+    #[test]
+    fn test_clear() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let keypair_c = ValidatorVoteKeypairs::new_rand();
+        let keypair_d = ValidatorVoteKeypairs::new_rand();
+        let mut vote_storage = VoteStorage::new_for_tests(&[
+            keypair_a.vote_keypair.pubkey(),
+            keypair_b.vote_keypair.pubkey(),
+            keypair_c.vote_keypair.pubkey(),
+            keypair_d.vote_keypair.pubkey(),
+        ]);
+
+        let vote_a = from_slots(vec![(1, 1)], VoteSource::Gossip, &keypair_a, None);
+        let vote_b = from_slots(vec![(2, 1)], VoteSource::Tpu, &keypair_b, None);
+        let vote_c = from_slots(vec![(3, 1)], VoteSource::Tpu, &keypair_c, None);
+        let vote_d = from_slots(vec![(4, 1)], VoteSource::Gossip, &keypair_d, None);
+
+        vote_storage.update_latest_vote(vote_a, false /* should replenish */);
+        vote_storage.update_latest_vote(vote_b, false /* should replenish */);
+        vote_storage.update_latest_vote(vote_c, false /* should replenish */);
+        vote_storage.update_latest_vote(vote_d, false /* should replenish */);
+        assert_eq!(4, vote_storage.len());
+
+        vote_storage.clear();
+        assert_eq!(0, vote_storage.len());
+
+        assert_eq!(
+            Some(1),
+            vote_storage.get_latest_vote_slot(keypair_a.vote_keypair.pubkey())
+        );
+        assert_eq!(
+            Some(2),
+            vote_storage.get_latest_vote_slot(keypair_b.vote_keypair.pubkey())
         );
         assert_eq!(
             Some(3),
@@ -662,6 +2554,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_weighted_random_order_by_stake() {
+        let keypair_a = ValidatorVoteKeypairs::new_rand();
+        let keypair_b = ValidatorVoteKeypairs::new_rand();
+        let keypair_c = ValidatorVoteKeypairs::new_rand();
+        let vote_pubkey_a = keypair_a.vote_keypair.pubkey();
+        let vote_pubkey_b = keypair_b.vote_keypair.pubkey();
+        let vote_pubkey_c = keypair_c.vote_keypair.pubkey();
+        let mut vote_storage = VoteStorage::new_for_tests_with_stakes(&[
+            (vote_pubkey_a, 1),
+            (vote_pubkey_b, 9),
+            (vote_pubkey_c, 90),
+        ]);
+        for keypair in [&keypair_a, &keypair_b, &keypair_c] {
+            let vote = from_slots(vec![(1, 1)], VoteSource::Gossip, keypair, None);
+            vote_storage.update_latest_vote(vote, false /* should replenish */);
+        }
+
+        // Every pubkey is ordered exactly once.
+        let order = vote_storage.weighted_random_order_by_stake().collect_vec();
+        assert_eq!(order.len(), 3);
+        assert!([vote_pubkey_a, vote_pubkey_b, vote_pubkey_c]
+            .iter()
+            .all(|pubkey| order.contains(pubkey)));
+
+        // Pubkeys with more stake tend to come first.
+        const NUM_TRIALS: usize = 1_000;
+        let mut num_first = HashMap::<Pubkey, usize>::default();
+        for _ in 0..NUM_TRIALS {
+            let first = vote_storage
+                .weighted_random_order_by_stake()
+                .next()
+                .unwrap();
+            *num_first.entry(first).or_default() += 1;
+        }
+        let num_first = |pubkey| num_first.get(&pubkey).copied().unwrap_or_default();
+        // Expected 900, 90 and 10 respectively.
+        assert!(num_first(vote_pubkey_c) > 800);
+        assert!(num_first(vote_pubkey_b) > num_first(vote_pubkey_a));
+    }
+
     #[test]
     fn test_insert_batch_unstaked() {
         let keypair_a = ValidatorVoteKeypairs::new_rand();
@@ -20,7 +20,10 @@ use {
     solana_clock::FORWARD_TRANSACTIONS_TO_LEADER_AT_SLOT_OFFSET,
     solana_measure::{measure::Measure, measure_us},
     solana_poh::poh_recorder::{BankStart, PohRecorderError},
-    solana_runtime::{bank::Bank, bank_forks::BankForks},
+    solana_runtime::{
+        bank::Bank,
+        bank_forks::{BankForks, ReadOnlyAtomicSlot},
+    },
     solana_runtime_transaction::{
         runtime_transaction::RuntimeTransaction, transaction_with_meta::TransactionWithMeta,
     },
@@ -32,7 +35,7 @@ use {
     solana_transaction_error::TransactionError,
     std::{
         sync::{atomic::Ordering, Arc, RwLock},
-        time::Instant,
+        time::{Duration, Instant},
     },
 };
 
@@ -45,12 +48,15 @@ mod transaction {
 // 2. Constrain max entry size for FEC set packing (Smaller is better)
 pub const UNPROCESSED_BUFFER_STEP_SIZE: usize = 16;
 
+const VOTE_EXPIRY_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
 pub struct VoteWorker {
     decision_maker: DecisionMaker,
     tpu_receiver: PacketReceiver,
     gossip_receiver: PacketReceiver,
     storage: VoteStorage,
     bank_forks: Arc<RwLock<BankForks>>,
+    root: ReadOnlyAtomicSlot,
     consumer: Consumer,
 }
 
@@ -63,12 +69,14 @@ impl VoteWorker {
         bank_forks: Arc<RwLock<BankForks>>,
         consumer: Consumer,
     ) -> Self {
+        let root = bank_forks.read().unwrap().get_atomic_root();
         Self {
             decision_maker,
             tpu_receiver,
             gossip_receiver,
             storage,
             bank_forks,
+            root,
             consumer,
         }
     }
@@ -78,6 +86,7 @@ impl VoteWorker {
         let mut slot_metrics_tracker = LeaderSlotMetricsTracker::default();
 
         let mut last_metrics_update = Instant::now();
+        let mut last_vote_expiry = Instant::now();
 
         loop {
             if !self.storage.is_empty()
@@ -110,6 +119,12 @@ impl VoteWorker {
                 Ok(()) | Err(RecvTimeoutError::Timeout) => (),
                 Err(RecvTimeoutError::Disconnected) => break,
             }
+            // Votes below the root can no longer land.
+            self.storage.handle_new_root(self.root.get());
+            if last_vote_expiry.elapsed() >= VOTE_EXPIRY_CHECK_PERIOD {
+                self.storage.expire_held_votes();
+                last_vote_expiry = Instant::now();
+            }
             banking_stage_stats.report(1000);
         }
     }
@@ -131,6 +146,10 @@ impl VoteWorker {
                 // packet processing metrics from the next slot towards the metrics
                 // of the previous slot
                 slot_metrics_tracker.apply_action(metrics_action);
+                self.storage
+                    .set_current_slot(bank_start.working_bank.slot());
+                self.storage
+                    .report_starvation(bank_start.working_bank.slot());
                 let (_, consume_buffered_packets_us) = measure_us!(self.consume_buffered_packets(
                     &bank_start,
                     banking_stage_stats,
@@ -143,6 +162,7 @@ impl VoteWorker {
                 // get current working bank from bank_forks, use it to sanitize transaction and
                 // load all accounts from address loader;
                 let current_bank = self.bank_forks.read().unwrap().working_bank();
+                self.storage.set_current_slot(current_bank.slot());
                 self.storage.cache_epoch_boundary_info(&current_bank);
                 self.storage.clear();
             }
@@ -150,6 +170,7 @@ impl VoteWorker {
                 // get current working bank from bank_forks, use it to sanitize transaction and
                 // load all accounts from address loader;
                 let current_bank = self.bank_forks.read().unwrap().working_bank();
+                self.storage.set_current_slot(current_bank.slot());
                 self.storage.cache_epoch_boundary_info(&current_bank);
             }
             BufferedPacketsDecision::Hold => {}
@@ -181,6 +202,7 @@ impl VoteWorker {
 
         if reached_end_of_slot {
             slot_metrics_tracker.set_end_of_slot_unprocessed_buffer_len(self.storage.len() as u64);
+            self.storage.report(bank_start.working_bank.slot());
         }
 
         proc_start.stop();
@@ -0,0 +1,131 @@
+//! Stake weighted random ordering of keys, used to prioritize votes from
+//! validators with more stake.
+
+use {rand::Rng, solana_pubkey::Pubkey};
+
+/// Orders `stakes` by weighted random sampling without replacement, i.e. the
+/// probability of a key coming next is proportional to its stake among the
+/// remaining keys. Keys with zero stake are ordered after all staked keys,
+/// uniformly at random among themselves.
+///
+/// The order depends only on the input order and `rng`, so it is
+/// deterministic under a seeded rng.
+pub(crate) fn weighted_random_order<R: Rng>(
+    stakes: impl IntoIterator<Item = (Pubkey, u64)>,
+    rng: &mut R,
+) -> Vec<Pubkey> {
+    // Efraimidis and Spirakis algo for weighted random sample without
+    // replacement: keys are taken in descending order of u^(1/stake), with u
+    // drawn uniformly from (0, 1]. Comparing ln(u)/stake instead is
+    // equivalent, but does not lose all precision to rounding for large
+    // stakes.
+    let mut keys: Vec<_> = stakes
+        .into_iter()
+        .map(|(pubkey, stake)| {
+            let key = (1.0 - rng.gen::<f64>()).ln();
+            let key = if stake == 0 { key } else { key / stake as f64 };
+            ((stake != 0, key), pubkey)
+        })
+        .collect();
+    keys.sort_by(|((staked1, key1), _), ((staked2, key2), _)| {
+        staked2.cmp(staked1).then(key2.total_cmp(key1))
+    });
+    keys.into_iter().map(|(_, pubkey)| pubkey).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, rand::SeedableRng, rand_chacha::ChaChaRng, std::collections::HashMap};
+
+    // Returns how many times each key is ordered first, out of `num_trials`.
+    fn count_first(stakes: &[(Pubkey, u64)], num_trials: usize) -> HashMap<Pubkey, usize> {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let mut counts = HashMap::new();
+        for _ in 0..num_trials {
+            let order = weighted_random_order(stakes.iter().copied(), &mut rng);
+            *counts.entry(order[0]).or_default() += 1;
+        }
+        counts
+    }
+
+    // Asserts that the observed counts fit the expected distribution, using
+    // the chi-squared statistic with a generous threshold.
+    fn assert_fits(counts: &HashMap<Pubkey, usize>, expected: &[(Pubkey, f64)], threshold: f64) {
+        let num_trials: usize = counts.values().sum();
+        let chi_squared: f64 = expected
+            .iter()
+            .map(|(pubkey, probability)| {
+                let expected = probability * num_trials as f64;
+                let observed = counts.get(pubkey).copied().unwrap_or_default() as f64;
+                (observed - expected).powi(2) / expected
+            })
+            .sum();
+        assert!(chi_squared < threshold, "chi squared: {chi_squared}");
+    }
+
+    #[test]
+    fn test_weighted_random_order_tracks_stake() {
+        let stakes: Vec<_> = [1, 2, 3, 4, 10]
+            .into_iter()
+            .map(|stake| (Pubkey::new_unique(), stake))
+            .collect();
+        let counts = count_first(&stakes, 20_000);
+        let expected: Vec<_> = stakes
+            .iter()
+            .map(|(pubkey, stake)| (*pubkey, *stake as f64 / 20.0))
+            .collect();
+        // The 99.9th percentile with 4 degrees of freedom is 18.47.
+        assert_fits(&counts, &expected, 18.47);
+    }
+
+    #[test]
+    fn test_weighted_random_order_large_stakes() {
+        // Stakes in lamports, which exceed the precision of u^(1/stake).
+        let stakes = [
+            (Pubkey::new_unique(), 3_000_000_000_000_000),
+            (Pubkey::new_unique(), 1_000_000_000_000_000),
+        ];
+        let counts = count_first(&stakes, 10_000);
+        // The 99.9th percentile with 1 degree of freedom is 10.83.
+        assert_fits(&counts, &[(stakes[0].0, 0.75), (stakes[1].0, 0.25)], 10.83);
+    }
+
+    #[test]
+    fn test_weighted_random_order_zero_and_equal_stakes() {
+        let staked = Pubkey::new_unique();
+        let unstaked: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let stakes: Vec<_> = unstaked
+            .iter()
+            .map(|pubkey| (*pubkey, 0))
+            .chain([(staked, 1)])
+            .collect();
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let order = weighted_random_order(stakes.iter().copied(), &mut rng);
+            assert_eq!(order.len(), 5);
+            assert_eq!(order[0], staked);
+            assert!(unstaked.iter().all(|pubkey| order.contains(pubkey)));
+        }
+
+        // Keys with equal stakes, whether zero or not, are equally likely to
+        // come first.
+        let expected: Vec<_> = unstaked.iter().map(|pubkey| (*pubkey, 0.25)).collect();
+        // The 99.9th percentile with 3 degrees of freedom is 16.27.
+        for stake in [0, 1, 1_000_000] {
+            let stakes: Vec<_> = unstaked.iter().map(|pubkey| (*pubkey, stake)).collect();
+            assert_fits(&count_first(&stakes, 10_000), &expected, 16.27);
+        }
+    }
+
+    #[test]
+    fn test_weighted_random_order_deterministic() {
+        let stakes: Vec<_> = (0..20).map(|stake| (Pubkey::new_unique(), stake)).collect();
+        let order = |seed| {
+            let mut rng = ChaChaRng::seed_from_u64(seed);
+            weighted_random_order(stakes.iter().copied(), &mut rng)
+        };
+        assert_eq!(order(7), order(7));
+        assert_ne!(order(7), order(8));
+        assert!(weighted_random_order(vec![], &mut ChaChaRng::seed_from_u64(7)).is_empty());
+    }
+}
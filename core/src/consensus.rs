@@ -16,7 +16,7 @@ use {
         progress_map::{LockoutIntervals, ProgressMap},
         tower1_14_11::Tower1_14_11,
         tower1_7_14::Tower1_7_14,
-        tower_storage::{SavedTower, SavedTowerVersions, TowerStorage},
+        tower_storage::{SavedTowerVersions, SavedTowerWriteFormat, TowerStorage},
         tower_vote_state::TowerVoteState,
     },
     crate::replay_stage::DUPLICATE_THRESHOLD,
@@ -325,6 +325,14 @@ impl Tower {
         tower
     }
 
+    /// Tower without any votes, for nodes that don't vote
+    pub fn default_for(node_pubkey: Pubkey) -> Self {
+        Tower {
+            node_pubkey,
+            ..Tower::default()
+        }
+    }
+
     #[cfg(test)]
     pub fn new_for_tests(threshold_depth: usize, threshold_size: f64) -> Self {
         Self {
@@ -1643,8 +1651,21 @@ impl Tower {
     }
 
     pub fn save(&self, tower_storage: &dyn TowerStorage, node_keypair: &Keypair) -> Result<()> {
-        let saved_tower = SavedTower::new(self, node_keypair)?;
-        tower_storage.store(&SavedTowerVersions::from(saved_tower))?;
+        self.save_with_format(
+            tower_storage,
+            node_keypair,
+            SavedTowerWriteFormat::default(),
+        )
+    }
+
+    pub fn save_with_format(
+        &self,
+        tower_storage: &dyn TowerStorage,
+        node_keypair: &Keypair,
+        format: SavedTowerWriteFormat,
+    ) -> Result<()> {
+        let saved_tower = SavedTowerVersions::new(self, node_keypair, format)?;
+        tower_storage.store(&saved_tower)?;
         Ok(())
     }
 
@@ -1655,42 +1676,154 @@ impl Tower {
 
 #[derive(Error, Debug)]
 pub enum TowerError {
-    #[error("IO Error: {0}")]
+    #[error("E001: IO Error: {0}")]
     IoError(#[from] std::io::Error),
 
-    #[error("Serialization Error: {0}")]
+    #[error("E002: Serialization Error: {0}")]
     SerializeError(#[from] bincode::Error),
 
-    #[error("The signature on the saved tower is invalid")]
+    #[error("E003: The signature on the saved tower is invalid")]
     InvalidSignature,
 
-    #[error("The tower does not match this validator: {0}")]
-    WrongTower(String),
+    #[error("E004: The tower does not match this validator: expected {expected}, found {found}")]
+    WrongTower { expected: Pubkey, found: Pubkey },
 
     #[error(
-        "The tower is too old: newest slot in tower ({0}) << oldest slot in available history \
-         ({1})"
+        "E005: The tower is too old: newest slot in tower ({0}) << oldest slot in available \
+         history ({1})"
     )]
     TooOldTower(Slot, Slot),
 
-    #[error("The tower is fatally inconsistent with blockstore: {0}")]
+    #[error("E006: The tower is fatally inconsistent with blockstore: {0}")]
     FatallyInconsistent(&'static str),
 
-    #[error("The tower is useless because of new hard fork: {0}")]
+    #[error("E007: The tower is useless because of new hard fork: {0}")]
     HardFork(Slot),
+
+    #[error("E008: No saved tower found for {0}")]
+    TowerNotFound(Pubkey),
+
+    #[error("E009: The tower is locked by another instance: {0}")]
+    TowerLocked(String),
+
+    #[error("E010: The saved tower is corrupt: {0}")]
+    CorruptTower(String),
+
+    #[error("E011: Unsupported saved tower format version {0}, expected {1}")]
+    UnsupportedTowerFormat(u16, u16),
+
+    #[error("E012: The saved tower could not be decrypted: {0}")]
+    DecryptionFailed(String),
+
+    #[error("E013: Failed to store the tower after {0} attempts: {1}")]
+    StoreRetriesExhausted(usize, Box<TowerError>),
+
+    #[error(
+        "E014: Refusing to store a tower older than the saved one: last vote {new_last_vote:?} \
+         and root {new_root:?}, saved last vote {stored_last_vote:?} and root {stored_root:?}"
+    )]
+    TowerRegression {
+        stored_last_vote: Option<Slot>,
+        new_last_vote: Option<Slot>,
+        stored_root: Option<Slot>,
+        new_root: Option<Slot>,
+    },
+
+    #[error("E015: The tower storage is read-only")]
+    ReadOnly,
+
+    #[error("E016: Invalid tower storage URI: {0}")]
+    InvalidStorageUri(String),
+
+    #[error("E017: Invalid tower path template: {0}")]
+    InvalidPathTemplate(String),
+
+    #[error("E018: The tower is inconsistent with blockstore: {0}")]
+    InconsistentWithBlockstore(String),
+
+    #[error("E019: {0} is not one of the identities of the tower storage")]
+    UnknownIdentity(Pubkey),
 }
 
 impl TowerError {
     pub fn is_file_missing(&self) -> bool {
-        if let TowerError::IoError(io_err) = &self {
-            io_err.kind() == std::io::ErrorKind::NotFound
-        } else {
-            false
+        match self {
+            TowerError::IoError(io_err) => io_err.kind() == std::io::ErrorKind::NotFound,
+            TowerError::TowerNotFound(_) => true,
+            _ => false,
         }
     }
     pub fn is_too_old(&self) -> bool {
         matches!(self, TowerError::TooOldTower(_, _))
     }
+
+    /// Whether retrying the failed operation might succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            TowerError::IoError(io_err) => !matches!(
+                io_err.kind(),
+                std::io::ErrorKind::NotFound
+                    | std::io::ErrorKind::PermissionDenied
+                    | std::io::ErrorKind::Unsupported
+            ),
+            _ => false,
+        }
+    }
+
+    /// Stable code identifying the kind of error, for operators and metrics.
+    /// Codes are never reused, and prefix the `Display` output, zero padded to
+    /// three digits.
+    pub fn code(&self) -> u32 {
+        match self {
+            TowerError::IoError(_) => 1,
+            TowerError::SerializeError(_) => 2,
+            TowerError::InvalidSignature => 3,
+            TowerError::WrongTower { .. } => 4,
+            TowerError::TooOldTower(_, _) => 5,
+            TowerError::FatallyInconsistent(_) => 6,
+            TowerError::HardFork(_) => 7,
+            TowerError::TowerNotFound(_) => 8,
+            TowerError::TowerLocked(_) => 9,
+            TowerError::CorruptTower(_) => 10,
+            TowerError::UnsupportedTowerFormat(_, _) => 11,
+            TowerError::DecryptionFailed(_) => 12,
+            TowerError::StoreRetriesExhausted(_, _) => 13,
+            TowerError::TowerRegression { .. } => 14,
+            TowerError::ReadOnly => 15,
+            TowerError::InvalidStorageUri(_) => 16,
+            TowerError::InvalidPathTemplate(_) => 17,
+            TowerError::InconsistentWithBlockstore(_) => 18,
+            TowerError::UnknownIdentity(_) => 19,
+        }
+    }
+
+    /// Whether the tower storage itself failed, rather than the saved tower
+    /// being unusable
+    pub fn is_storage_error(&self) -> bool {
+        match self {
+            TowerError::IoError(_)
+            | TowerError::TowerLocked(_)
+            | TowerError::ReadOnly
+            | TowerError::InvalidStorageUri(_)
+            | TowerError::InvalidPathTemplate(_)
+            | TowerError::UnknownIdentity(_) => true,
+            TowerError::StoreRetriesExhausted(_, err) => err.is_storage_error(),
+            _ => false,
+        }
+    }
+
+    /// Whether the saved tower can never be used by this validator, as
+    /// opposed to e.g. IO and serialization errors which might be transient
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            TowerError::InvalidSignature
+            | TowerError::WrongTower { .. }
+            | TowerError::FatallyInconsistent(_)
+            | TowerError::CorruptTower(_) => true,
+            TowerError::StoreRetriesExhausted(_, err) => err.is_fatal(),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -3194,10 +3327,104 @@ pub mod test {
         let tower_storage = FileTowerStorage::default();
         assert_matches!(
             tower.save(&tower_storage, &identity_keypair),
-            Err(TowerError::WrongTower(_))
+            Err(TowerError::WrongTower { .. })
         )
     }
 
+    #[test]
+    fn test_tower_error_codes() {
+        let errors = [
+            (TowerError::IoError(std::io::Error::other("io")), 1),
+            (
+                TowerError::SerializeError(Box::new(bincode::ErrorKind::SizeLimit)),
+                2,
+            ),
+            (TowerError::InvalidSignature, 3),
+            (
+                TowerError::WrongTower {
+                    expected: Pubkey::new_unique(),
+                    found: Pubkey::new_unique(),
+                },
+                4,
+            ),
+            (TowerError::TooOldTower(1, 2), 5),
+            (TowerError::FatallyInconsistent("inconsistent"), 6),
+            (TowerError::HardFork(1), 7),
+            (TowerError::TowerNotFound(Pubkey::new_unique()), 8),
+            (TowerError::TowerLocked("locked".to_string()), 9),
+            (TowerError::CorruptTower("corrupt".to_string()), 10),
+            (TowerError::UnsupportedTowerFormat(1, 2), 11),
+            (TowerError::DecryptionFailed("bad key".to_string()), 12),
+            (
+                TowerError::StoreRetriesExhausted(3, Box::new(TowerError::ReadOnly)),
+                13,
+            ),
+            (
+                TowerError::TowerRegression {
+                    stored_last_vote: Some(2),
+                    new_last_vote: Some(1),
+                    stored_root: None,
+                    new_root: None,
+                },
+                14,
+            ),
+            (TowerError::ReadOnly, 15),
+            (TowerError::InvalidStorageUri("uri".to_string()), 16),
+            (TowerError::InvalidPathTemplate("template".to_string()), 17),
+            (
+                TowerError::InconsistentWithBlockstore("missing".to_string()),
+                18,
+            ),
+            (TowerError::UnknownIdentity(Pubkey::new_unique()), 19),
+        ];
+        for (err, code) in &errors {
+            assert_eq!(err.code(), *code);
+            assert!(
+                err.to_string().starts_with(&format!("E{code:03}: ")),
+                "{err}"
+            );
+        }
+        assert!(errors.iter().map(|(err, _)| err.code()).all_unique());
+
+        let fatal: Vec<_> = errors
+            .iter()
+            .filter(|(err, _)| err.is_fatal())
+            .map(|(_, code)| *code)
+            .collect();
+        assert_eq!(fatal, vec![3, 4, 6, 10]);
+        assert!(
+            TowerError::StoreRetriesExhausted(3, Box::new(TowerError::InvalidSignature)).is_fatal()
+        );
+
+        let storage_errors: Vec<_> = errors
+            .iter()
+            .filter(|(err, _)| err.is_storage_error())
+            .map(|(_, code)| *code)
+            .collect();
+        assert_eq!(storage_errors, vec![1, 9, 13, 15, 16, 17, 19]);
+    }
+
+    #[test]
+    fn test_wrong_tower_display() {
+        let expected = Pubkey::new_unique();
+        let found = Pubkey::new_unique();
+        let err = TowerError::WrongTower { expected, found };
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "E004: The tower does not match this validator: expected {expected}, found \
+                 {found}"
+            )
+        );
+
+        // Both pubkeys can be recovered from the message
+        let message = err.to_string();
+        let (_, pubkeys) = message.split_once("expected ").unwrap();
+        let (parsed_expected, parsed_found) = pubkeys.split_once(", found ").unwrap();
+        assert_eq!(parsed_expected.parse::<Pubkey>().unwrap(), expected);
+        assert_eq!(parsed_found.parse::<Pubkey>().unwrap(), found);
+    }
+
     #[test]
     fn test_load_tower_invalid_signature() {
         let (_, loaded) = run_test_load_tower_snapshot(
@@ -3232,7 +3459,10 @@ pub mod test {
                     .unwrap_or_else(|_| panic!("Failed to truncate file: {path:?}"));
             },
         );
-        assert_matches!(loaded, Err(TowerError::SerializeError(_)))
+        assert_matches!(
+            loaded,
+            Err(TowerError::CorruptTower(reason)) if reason.contains("tower-1_9-")
+        )
     }
 
     #[test]
@@ -3243,7 +3473,7 @@ pub mod test {
                 remove_file(path).unwrap();
             },
         );
-        assert_matches!(loaded, Err(TowerError::IoError(_)))
+        assert_matches!(loaded, Err(TowerError::TowerNotFound(_)))
     }
 
     #[test]
@@ -1,5 +1,7 @@
 use {
-    crate::consensus::{BlockhashStatus, Result, SwitchForkDecision, TowerError},
+    crate::consensus::{
+        tower_storage::SavedTowerSummary, BlockhashStatus, Result, SwitchForkDecision, TowerError,
+    },
     solana_clock::Slot,
     solana_pubkey::Pubkey,
     solana_signature::Signature,
@@ -55,10 +57,10 @@ impl SavedTower1_7_14 {
     pub fn new<T: Signer>(tower: &Tower1_7_14, keypair: &T) -> Result<Self> {
         let node_pubkey = keypair.pubkey();
         if tower.node_pubkey != node_pubkey {
-            return Err(TowerError::WrongTower(format!(
-                "node_pubkey is {:?} but found tower for {:?}",
-                node_pubkey, tower.node_pubkey
-            )));
+            return Err(TowerError::WrongTower {
+                expected: node_pubkey,
+                found: tower.node_pubkey,
+            });
         }
 
         let data = bincode::serialize(tower)?;
@@ -69,4 +71,15 @@ impl SavedTower1_7_14 {
             node_pubkey,
         })
     }
+
+    /// Summarizes the tower without verifying its signature
+    pub fn summary(&self) -> Result<SavedTowerSummary> {
+        let tower: Tower1_7_14 = bincode::deserialize(&self.data)?;
+        Ok(SavedTowerSummary {
+            last_vote_slot: tower.last_vote.slots.last().copied(),
+            root_slot: tower.vote_state.root_slot,
+            vote_count: tower.vote_state.votes.len(),
+            serialized_size: self.data.len(),
+        })
+    }
 }
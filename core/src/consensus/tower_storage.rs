@@ -1,65 +1,314 @@
+mod etcd;
+mod file;
+mod object_store;
+#[cfg(feature = "postgres-tower-storage")]
+mod postgres;
+mod wrappers;
+#[cfg(feature = "zk-tower-storage")]
+mod zk;
+
+#[cfg(feature = "s3-tower-storage")]
+pub use self::object_store::{ObjectStoreConfig, S3ObjectStore};
+#[cfg(feature = "postgres-tower-storage")]
+pub use self::postgres::{PostgresTlsConfig, PostgresTowerStorage, PostgresTowerStorageConfig};
+#[cfg(feature = "zk-tower-storage")]
+pub use self::zk::{ZkTowerStorage, ZkTowerStorageConfig};
+pub use self::{
+    etcd::{
+        EtcdHealth, EtcdOperationMetrics, EtcdTlsConfig, EtcdTowerStorage, EtcdTowerStorageConfig,
+        EtcdTowerStorageMetrics,
+    },
+    file::{DurabilityLevel, FileTowerStorage, LayoutMigrationReport, PathTemplate},
+    object_store::{ETag, ObjectStore, ObjectStoreTowerStorage, PutCondition, PutOutcome},
+    wrappers::{
+        read_audit_log, AuditingTowerStorage, CompositeTowerStorage, CompositeWritePolicy,
+        EncryptedTowerStorage, MonotonicTowerStorage, MultiIdentityTowerStorage,
+        PipelinedTowerStorage, ReadOnlyTowerStorage, ReplicatingTowerStorage, RetryingTowerStorage,
+        ThrottledTowerStorage, TowerAuditOp, TowerAuditRecord, TowerReplicaEndpoint,
+        TowerReplicaServer, DEFAULT_TOWER_REPLICA_QUEUE_LEN,
+    },
+};
 use {
+    self::etcd::DEFAULT_ETCD_LEASE_TTL,
     crate::consensus::{
         tower1_14_11::Tower1_14_11, tower1_7_14::SavedTower1_7_14, Result, Tower, TowerError,
         TowerVersions,
     },
+    async_trait::async_trait,
+    bincode::Options,
+    solana_clock::Slot,
+    solana_keypair::Keypair,
+    solana_ledger::{ancestor_iterator::AncestorIterator, blockstore::Blockstore},
     solana_pubkey::Pubkey,
     solana_signature::Signature,
     solana_signer::Signer,
+    solana_vote_program::vote_state::{BlockTimestamp, Lockout},
     std::{
-        fs::{self, File},
-        io::{self, BufReader},
+        collections::{HashMap, HashSet},
+        fmt,
+        fs::File,
+        future::Future,
+        io::{self, Write},
         path::PathBuf,
+        str::FromStr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex, RwLock,
+        },
+        time::{Duration, Instant},
     },
 };
 
+// `SavedTower2` towers are framed as: magic, format version (u16), payload
+// length (u64), payload (bincode `SavedTowerVersions`), crc32 of the payload
+// (u32), all little endian. Older formats are left unframed so that older
+// validators can read them, and start with the bincode enum tag (0 or 1)
+// instead of the magic.
+const SAVED_TOWER_MAGIC: [u8; 4] = *b"STWR";
+const SAVED_TOWER_FORMAT_VERSION: u16 = 1;
+pub(crate) const SAVED_TOWER_FRAME_HEADER_LEN: usize = 14;
+pub(crate) const SAVED_TOWER_FRAME_TRAILER_LEN: usize = 4;
+
 #[cfg_attr(feature = "frozen-abi", derive(AbiExample))]
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum SavedTowerVersions {
     V1_17_14(SavedTower1_7_14),
     Current(SavedTower),
+    V2(SavedTower2),
+}
+
+/// Which `Tower` fields loading a saved tower restored, and which it left at
+/// their defaults because no saved tower format persists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub preserved: &'static [&'static str],
+    pub defaulted: &'static [&'static str],
+}
+
+impl ConversionReport {
+    // Every format skips serializing the same fields
+    const SAVED_TOWER: Self = Self {
+        preserved: &[
+            "node_pubkey",
+            "threshold_depth",
+            "threshold_size",
+            "vote_state",
+            "last_vote",
+            "last_timestamp",
+        ],
+        defaulted: &[
+            "last_vote_tx_blockhash",
+            "stray_restored_slot",
+            "last_switch_threshold_check",
+        ],
+    };
+}
+
+/// Format `Tower::save_with_format()` and replay stage save towers in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SavedTowerWriteFormat {
+    /// Unframed `SavedTower`, which older releases can read
+    #[default]
+    Legacy,
+    /// Framed and checksummed `SavedTower2`, which older releases can't read
+    V2,
+}
+
+impl FromStr for SavedTowerWriteFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "legacy" => Ok(Self::Legacy),
+            "v2" => Ok(Self::V2),
+            _ => Err(format!("unknown saved tower format {s}")),
+        }
+    }
 }
 
 impl SavedTowerVersions {
+    pub fn new<T: Signer>(
+        tower: &Tower,
+        keypair: &T,
+        format: SavedTowerWriteFormat,
+    ) -> Result<Self> {
+        Ok(match format {
+            SavedTowerWriteFormat::Legacy => SavedTower::new(tower, keypair)?.into(),
+            SavedTowerWriteFormat::V2 => SavedTower2::new(tower, keypair)?.into(),
+        })
+    }
+
     fn try_into_tower(&self, node_pubkey: &Pubkey) -> Result<Tower> {
-        // This method assumes that `self` was just deserialized
-        assert_eq!(self.pubkey(), Pubkey::default());
+        self.try_into_tower_with_report(node_pubkey)
+            .map(|(tower, _report)| tower)
+    }
+
+    /// Verifies and converts the saved tower like `load()`, also reporting
+    /// which fields the saved format didn't carry
+    pub fn try_into_tower_with_report(
+        &self,
+        node_pubkey: &Pubkey,
+    ) -> Result<(Tower, ConversionReport)> {
+        let verified = match self {
+            SavedTowerVersions::V1_17_14(t) => t.signature.verify(node_pubkey.as_ref(), &t.data),
+            SavedTowerVersions::Current(t) => t.signature.verify(node_pubkey.as_ref(), &t.data),
+            SavedTowerVersions::V2(t) => {
+                if t.node_pubkey != *node_pubkey {
+                    return Err(TowerError::WrongTower {
+                        expected: *node_pubkey,
+                        found: t.node_pubkey,
+                    });
+                }
+                t.signature.verify(t.node_pubkey.as_ref(), &t.data)
+            }
+        };
+        if !verified {
+            return Err(TowerError::InvalidSignature);
+        }
+        self.to_tower_unverified().and_then(|tower| {
+            if tower.node_pubkey != *node_pubkey {
+                return Err(TowerError::WrongTower {
+                    expected: *node_pubkey,
+                    found: tower.node_pubkey,
+                });
+            }
+            if let SavedTowerVersions::V2(t) = self {
+                let root_slot = tower.vote_state.root_slot;
+                let last_vote_slot = tower.last_voted_slot();
+                if (t.root_slot, t.last_vote_slot) != (root_slot, last_vote_slot) {
+                    return Err(TowerError::CorruptTower(format!(
+                        "saved tower metadata (root {:?}, last vote {:?}) does not match its \
+                         data (root {root_slot:?}, last vote {last_vote_slot:?})",
+                        t.root_slot, t.last_vote_slot
+                    )));
+                }
+            }
+            Ok((tower, ConversionReport::SAVED_TOWER))
+        })
+    }
 
+    /// Converts the saved tower without verifying its signature or who saved it
+    fn to_tower_unverified(&self) -> Result<Tower> {
         let tv = match self {
             SavedTowerVersions::V1_17_14(t) => {
-                if !t.signature.verify(node_pubkey.as_ref(), &t.data) {
-                    return Err(TowerError::InvalidSignature);
-                }
                 bincode::deserialize(&t.data).map(TowerVersions::V1_7_14)
             }
             SavedTowerVersions::Current(t) => {
-                if !t.signature.verify(node_pubkey.as_ref(), &t.data) {
-                    return Err(TowerError::InvalidSignature);
-                }
                 bincode::deserialize(&t.data).map(TowerVersions::V1_14_11)
             }
+            SavedTowerVersions::V2(t) => bincode::deserialize(&t.data).map(TowerVersions::V1_14_11),
         };
-        tv.map_err(|e| e.into()).and_then(|tv: TowerVersions| {
-            let tower = tv.convert_to_current();
-            if tower.node_pubkey != *node_pubkey {
-                return Err(TowerError::WrongTower(format!(
-                    "node_pubkey is {:?} but found tower for {:?}",
-                    node_pubkey, tower.node_pubkey
-                )));
-            }
-            Ok(tower)
-        })
+        Ok(tv?.convert_to_current())
     }
 
     fn serialize_into(&self, file: &mut File) -> Result<()> {
-        bincode::serialize_into(file, self).map_err(|e| e.into())
+        file.write_all(&self.serialize()?)?;
+        Ok(())
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>> {
+        let payload = bincode::serialize(self)?;
+        if !matches!(self, SavedTowerVersions::V2(_)) {
+            return Ok(payload);
+        }
+        let mut data = Vec::with_capacity(
+            SAVED_TOWER_FRAME_HEADER_LEN + payload.len() + SAVED_TOWER_FRAME_TRAILER_LEN,
+        );
+        data.extend_from_slice(&SAVED_TOWER_MAGIC);
+        data.extend_from_slice(&SAVED_TOWER_FORMAT_VERSION.to_le_bytes());
+        data.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        data.extend_from_slice(&payload);
+        data.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        Ok(data)
+    }
+
+    fn deserialize(data: &[u8]) -> Result<Self> {
+        let Some(frame) = data.strip_prefix(&SAVED_TOWER_MAGIC) else {
+            // Unframed tower
+            return bincode::deserialize(data).map_err(|e| e.into());
+        };
+        if frame.len() < SAVED_TOWER_FRAME_HEADER_LEN - SAVED_TOWER_MAGIC.len() {
+            return Err(TowerError::CorruptTower(format!(
+                "truncated header: {} bytes",
+                data.len()
+            )));
+        }
+        let (version, frame) = frame.split_at(2);
+        let version = u16::from_le_bytes(version.try_into().unwrap());
+        if version != SAVED_TOWER_FORMAT_VERSION {
+            return Err(TowerError::UnsupportedTowerFormat(
+                version,
+                SAVED_TOWER_FORMAT_VERSION,
+            ));
+        }
+        let (payload_len, frame) = frame.split_at(8);
+        let payload_len = u64::from_le_bytes(payload_len.try_into().unwrap());
+        if frame.len() as u64 != payload_len.saturating_add(SAVED_TOWER_FRAME_TRAILER_LEN as u64) {
+            return Err(TowerError::CorruptTower(format!(
+                "expected {payload_len} payload bytes and a {SAVED_TOWER_FRAME_TRAILER_LEN} byte \
+                 checksum, found {} bytes",
+                frame.len()
+            )));
+        }
+        let (payload, crc) = frame.split_at(payload_len as usize);
+        let expected_crc = u32::from_le_bytes(crc.try_into().unwrap());
+        let actual_crc = crc32fast::hash(payload);
+        if actual_crc != expected_crc {
+            return Err(TowerError::CorruptTower(format!(
+                "crc32 mismatch: expected {expected_crc:#010x}, actual {actual_crc:#010x}"
+            )));
+        }
+        bincode::deserialize(payload).map_err(|e| e.into())
     }
 
     fn pubkey(&self) -> Pubkey {
         match self {
             SavedTowerVersions::V1_17_14(t) => t.node_pubkey,
             SavedTowerVersions::Current(t) => t.node_pubkey,
+            SavedTowerVersions::V2(t) => t.node_pubkey,
+        }
+    }
+
+    /// Summarizes the tower without verifying its signature, so neither the
+    /// summary nor the format it came from can be trusted
+    pub fn summary(&self) -> Result<SavedTowerSummary> {
+        match self {
+            SavedTowerVersions::V1_17_14(t) => t.summary(),
+            SavedTowerVersions::Current(t) => t.summary(),
+            SavedTowerVersions::V2(t) => t.summary(),
+        }
+    }
+
+    /// Drops the metadata of a `SavedTower2`, which older releases can't
+    /// deserialize. The signature only covers the tower data so it stays valid.
+    fn to_legacy(&self) -> Self {
+        match self {
+            SavedTowerVersions::V2(t) => SavedTowerVersions::Current(SavedTower {
+                signature: t.signature,
+                data: t.data.clone(),
+                node_pubkey: t.node_pubkey,
+            }),
+            _ => self.clone(),
+        }
+    }
+
+    fn data(&self) -> &[u8] {
+        match self {
+            SavedTowerVersions::V1_17_14(t) => &t.data,
+            SavedTowerVersions::Current(t) => &t.data,
+            SavedTowerVersions::V2(t) => &t.data,
+        }
+    }
+
+    /// Replaces the signed tower data with `f(data)`, keeping the signature
+    fn try_map_data(&self, f: impl FnOnce(&[u8]) -> Result<Vec<u8>>) -> Result<Self> {
+        let mut saved_tower = self.clone();
+        match &mut saved_tower {
+            SavedTowerVersions::V1_17_14(t) => t.data = f(&t.data)?,
+            SavedTowerVersions::Current(t) => t.data = f(&t.data)?,
+            SavedTowerVersions::V2(t) => t.data = f(&t.data)?,
         }
+        Ok(saved_tower)
     }
 }
 
@@ -69,6 +318,12 @@ impl From<SavedTower> for SavedTowerVersions {
     }
 }
 
+impl From<SavedTower2> for SavedTowerVersions {
+    fn from(tower: SavedTower2) -> SavedTowerVersions {
+        SavedTowerVersions::V2(tower)
+    }
+}
+
 impl From<SavedTower1_7_14> for SavedTowerVersions {
     fn from(tower: SavedTower1_7_14) -> SavedTowerVersions {
         SavedTowerVersions::V1_17_14(tower)
@@ -93,10 +348,10 @@ impl SavedTower {
     pub fn new<T: Signer>(tower: &Tower, keypair: &T) -> Result<Self> {
         let node_pubkey = keypair.pubkey();
         if tower.node_pubkey != node_pubkey {
-            return Err(TowerError::WrongTower(format!(
-                "node_pubkey is {:?} but found tower for {:?}",
-                node_pubkey, tower.node_pubkey
-            )));
+            return Err(TowerError::WrongTower {
+                expected: node_pubkey,
+                found: tower.node_pubkey,
+            });
         }
 
         // SavedTower always stores its data in 1_14_11 format
@@ -110,310 +365,1514 @@ impl SavedTower {
             node_pubkey,
         })
     }
+
+    /// Summarizes the tower without verifying its signature
+    pub fn summary(&self) -> Result<SavedTowerSummary> {
+        SavedTowerSummary::from_tower1_14_11(&self.data)
+    }
+}
+
+/// What a saved tower claims about itself, read without verifying its
+/// signature or who saved it. Only fit for monitoring; restore the tower to
+/// trust any of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedTowerSummary {
+    pub last_vote_slot: Option<Slot>,
+    pub root_slot: Option<Slot>,
+    pub vote_count: usize,
+    /// Length of the signed tower data
+    pub serialized_size: usize,
+}
+
+impl SavedTowerSummary {
+    fn from_tower1_14_11(data: &[u8]) -> Result<Self> {
+        let tower: Tower1_14_11 = bincode::deserialize(data)?;
+        Ok(Self {
+            last_vote_slot: tower.last_vote.last_voted_slot(),
+            root_slot: tower.vote_state.root_slot,
+            vote_count: tower.vote_state.votes.len(),
+            serialized_size: data.len(),
+        })
+    }
+}
+
+/// Saved tower that also records who saved it and where its root and last
+/// vote are, so the stored blob can be attributed without verifying it
+#[cfg_attr(
+    feature = "frozen-abi",
+    derive(AbiExample),
+    frozen_abi(digest = "GPaWqLYtKTcUWub9oaCjWsYntcNeRdrtUhiEZqAAdw7e")
+)]
+#[derive(Default, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SavedTower2 {
+    signature: Signature,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+    node_pubkey: Pubkey,
+    root_slot: Option<Slot>,
+    last_vote_slot: Option<Slot>,
+}
+
+impl SavedTower2 {
+    pub fn new<T: Signer>(tower: &Tower, keypair: &T) -> Result<Self> {
+        let SavedTower {
+            signature,
+            data,
+            node_pubkey,
+        } = SavedTower::new(tower, keypair)?;
+        Ok(Self {
+            signature,
+            data,
+            node_pubkey,
+            root_slot: tower.vote_state.root_slot,
+            last_vote_slot: tower.last_voted_slot(),
+        })
+    }
+
+    pub fn node_pubkey(&self) -> &Pubkey {
+        &self.node_pubkey
+    }
+
+    pub fn root_slot(&self) -> Option<Slot> {
+        self.root_slot
+    }
+
+    pub fn last_vote_slot(&self) -> Option<Slot> {
+        self.last_vote_slot
+    }
+
+    /// Summarizes the tower without verifying its signature. Unlike the
+    /// metadata above, the slots are read from the tower data itself.
+    pub fn summary(&self) -> Result<SavedTowerSummary> {
+        SavedTowerSummary::from_tower1_14_11(&self.data)
+    }
+}
+
+// Written by `TowerStorage::store_sentinel()` in place of a tower
+const PREFLIGHT_SENTINEL: &[u8] = b"preflight";
+
+/// Outcome of `TowerStorage::preflight()`
+#[derive(Debug)]
+pub struct PreflightReport {
+    /// Whether a tower is saved for the node
+    pub tower_found: bool,
+    /// Why the saved tower can't be loaded, if it can't. This is left to tower
+    /// restore to handle, rather than failing preflight.
+    pub load_error: Option<TowerError>,
+    pub load_latency: Duration,
+    /// None if the storage can't store a sentinel, leaving stores unchecked
+    pub store_latency: Option<Duration>,
+}
+
+fn run_preflight<T: TowerStorage + ?Sized>(
+    tower_storage: &T,
+    node_pubkey: &Pubkey,
+) -> Result<PreflightReport> {
+    let start = Instant::now();
+    let (tower_found, load_error) = match tower_storage.exists(node_pubkey) {
+        Ok(tower_found) => (tower_found, None),
+        Err(err) if err.is_storage_error() => return Err(err),
+        Err(err) => (true, Some(err)),
+    };
+    let load_latency = start.elapsed();
+
+    let start = Instant::now();
+    let store_latency = match tower_storage.store_sentinel(node_pubkey) {
+        Ok(()) => Some(start.elapsed()),
+        Err(TowerError::IoError(err)) if err.kind() == io::ErrorKind::Unsupported => None,
+        Err(err) => return Err(err),
+    };
+    Ok(PreflightReport {
+        tower_found,
+        load_error,
+        load_latency,
+        store_latency,
+    })
 }
 
 pub trait TowerStorage: Sync + Send {
     fn load(&self, node_pubkey: &Pubkey) -> Result<Tower>;
     fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()>;
-}
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
-pub struct NullTowerStorage {}
+    /// Loads the saved tower without verifying it, for wrappers that must
+    /// transform it first, such as `EncryptedTowerStorage`
+    fn load_saved_tower(&self, _node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        Err(TowerError::IoError(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "load_saved_tower() not supported",
+        )))
+    }
 
-impl TowerStorage for NullTowerStorage {
-    fn load(&self, _node_pubkey: &Pubkey) -> Result<Tower> {
-        Err(TowerError::IoError(io::Error::other(
-            "NullTowerStorage::load() not available",
+    /// Whether a tower is saved for `node_pubkey`. A saved tower that can't be
+    /// loaded is an error rather than missing.
+    fn exists(&self, node_pubkey: &Pubkey) -> Result<bool> {
+        match self.load(node_pubkey) {
+            Ok(_) => Ok(true),
+            Err(err) if err.is_file_missing() => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Stores a sentinel under a scratch key next to the tower of
+    /// `node_pubkey` and deletes it again, leaving the saved tower untouched
+    fn store_sentinel(&self, _node_pubkey: &Pubkey) -> Result<()> {
+        Err(TowerError::IoError(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "store_sentinel() not supported",
         )))
     }
 
-    fn store(&self, _saved_tower: &SavedTowerVersions) -> Result<()> {
-        Ok(())
+    /// Checks that the storage can be read, and that towers can be stored, so
+    /// that broken tower storage fails validator startup rather than the first
+    /// vote. A saved tower that can't be loaded is reported rather than failing
+    /// preflight.
+    fn preflight(&self, node_pubkey: &Pubkey) -> Result<PreflightReport> {
+        run_preflight(self, node_pubkey)
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
-pub struct FileTowerStorage {
-    pub tower_path: PathBuf,
+/// Deserializes a saved tower in any format: a `SavedTowerVersions`, framed or
+/// not, or a bare `SavedTower1_7_14` as 1.7.14 validators wrote it. Bytes that
+/// parse as both are taken as whichever is signed by `node_pubkey`.
+fn detect_and_deserialize(data: &[u8], node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+    // A bare tower must span the whole blob, so framed towers never parse as one
+    let bare_tower = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(data)
+        .map(|saved_tower: SavedTower1_7_14| {
+            SavedTowerVersions::from(SavedTower1_7_14 {
+                node_pubkey: *node_pubkey,
+                ..saved_tower
+            })
+        });
+    match (SavedTowerVersions::deserialize(data), bare_tower) {
+        (Ok(saved_tower), Ok(bare_tower)) => {
+            if saved_tower.try_into_tower(node_pubkey).is_err()
+                && bare_tower.try_into_tower(node_pubkey).is_ok()
+            {
+                Ok(bare_tower)
+            } else {
+                Ok(saved_tower)
+            }
+        }
+        (Ok(saved_tower), Err(_)) => Ok(saved_tower),
+        (Err(_), Ok(bare_tower)) => Ok(bare_tower),
+        (Err(err), Err(_)) => Err(err),
+    }
 }
 
-impl FileTowerStorage {
-    pub fn new(tower_path: PathBuf) -> Self {
-        Self { tower_path }
+fn tower_not_found(err: io::Error, node_pubkey: &Pubkey) -> TowerError {
+    match err.kind() {
+        io::ErrorKind::NotFound => TowerError::TowerNotFound(*node_pubkey),
+        _ => err.into(),
     }
+}
 
-    // Old filename for towers pre 1.9 (VoteStateUpdate)
-    pub fn old_filename(&self, node_pubkey: &Pubkey) -> PathBuf {
-        self.tower_path
-            .join(format!("tower-{node_pubkey}"))
-            .with_extension("bin")
+// Reports a saved tower that fails to deserialize as corruption of wherever it
+// was loaded from
+fn corrupt_tower_at(location: impl fmt::Display) -> impl FnOnce(TowerError) -> TowerError {
+    move |err| match err {
+        TowerError::SerializeError(err) => TowerError::CorruptTower(format!("{location}: {err}")),
+        TowerError::CorruptTower(reason) => {
+            TowerError::CorruptTower(format!("{location}: {reason}"))
+        }
+        err => err,
     }
+}
 
-    pub fn filename(&self, node_pubkey: &Pubkey) -> PathBuf {
-        self.tower_path
-            .join(format!("tower-1_9-{node_pubkey}"))
-            .with_extension("bin")
-    }
+/// Why an attempt of an operation run by `execute_with_retries()` failed
+enum AttemptError {
+    Failed(TowerError),
+    TimedOut(Duration),
+}
 
-    #[cfg(test)]
-    fn store_old(&self, saved_tower: &SavedTower1_7_14) -> Result<()> {
-        let pubkey = saved_tower.node_pubkey;
-        let filename = self.old_filename(&pubkey);
-        trace!("store: {}", filename.display());
-        let new_filename = filename.with_extension("bin.new");
+impl fmt::Display for AttemptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttemptError::Failed(err) => write!(f, "{err}"),
+            AttemptError::TimedOut(timeout) => write!(f, "timed out after {timeout:?}"),
+        }
+    }
+}
 
-        {
-            // overwrite anything if exists
-            let file = File::create(&new_filename)?;
-            bincode::serialize_into(file, saved_tower)?;
-            // file.sync_all() hurts performance; pipeline sync-ing and submitting votes to the cluster!
+/// Runs `operation`, timing each attempt out after `timeout` and retrying
+/// failed attempts up to `max_retries` times. `operation` is passed whether
+/// this is a retry, and `on_failure` each failed attempt and whether it will be
+/// retried. Shared by the etcd, ZooKeeper and Postgres tower storages.
+async fn execute_with_retries<T, F, Fut>(
+    timeout: Duration,
+    max_retries: usize,
+    mut operation: F,
+    mut on_failure: impl FnMut(&AttemptError, bool),
+) -> std::result::Result<T, AttemptError>
+where
+    F: FnMut(bool) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut retries = 0;
+    loop {
+        let err = match tokio::time::timeout(timeout, operation(retries > 0)).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(err)) => AttemptError::Failed(err),
+            Err(_) => AttemptError::TimedOut(timeout),
+        };
+        let retry = retries < max_retries;
+        on_failure(&err, retry);
+        if !retry {
+            return Err(err);
         }
-        fs::rename(&new_filename, &filename)?;
-        // self.path.parent().sync_all() hurts performance same as the above sync
-        Ok(())
+        retries += 1;
+    }
+}
+
+/// Non-blocking counterpart of `TowerStorage`, for storages that make network
+/// round-trips
+#[async_trait]
+pub trait AsyncTowerStorage: Sync + Send {
+    async fn load(&self, node_pubkey: &Pubkey) -> Result<Tower>;
+    async fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()>;
+}
+
+/// `TowerStorage` that blocks on an `AsyncTowerStorage` using a shared runtime
+pub struct BlockingAdapter<T> {
+    inner: T,
+    runtime: Arc<tokio::runtime::Runtime>,
+}
+
+impl<T: AsyncTowerStorage> BlockingAdapter<T> {
+    pub fn new(inner: T, runtime: Arc<tokio::runtime::Runtime>) -> Self {
+        Self { inner, runtime }
     }
 }
 
-impl TowerStorage for FileTowerStorage {
+impl<T: AsyncTowerStorage> TowerStorage for BlockingAdapter<T> {
     fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
-        let filename = self.filename(node_pubkey);
-        trace!("load {}", filename.display());
-
-        // Ensure to create parent dir here, because restore() precedes save() always
-        fs::create_dir_all(filename.parent().unwrap())?;
-
-        if let Ok(file) = File::open(&filename) {
-            // New format
-            let mut stream = BufReader::new(file);
-
-            bincode::deserialize_from(&mut stream)
-                .map_err(|e| e.into())
-                .and_then(|t: SavedTowerVersions| t.try_into_tower(node_pubkey))
-        } else {
-            // Old format
-            let file = File::open(self.old_filename(node_pubkey))?;
-            let mut stream = BufReader::new(file);
-            bincode::deserialize_from(&mut stream)
-                .map_err(|e| e.into())
-                .and_then(|t: SavedTower1_7_14| {
-                    SavedTowerVersions::from(t).try_into_tower(node_pubkey)
-                })
-        }
+        self.runtime.block_on(self.inner.load(node_pubkey))
     }
 
     fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
-        let pubkey = saved_tower.pubkey();
-        let filename = self.filename(&pubkey);
-        trace!("store: {}", filename.display());
-        let new_filename = filename.with_extension("bin.new");
+        self.runtime.block_on(self.inner.store(saved_tower))
+    }
+}
 
-        {
-            // overwrite anything if exists
-            let mut file = File::create(&new_filename)?;
-            saved_tower.serialize_into(&mut file)?;
-            // file.sync_all() hurts performance; pipeline sync-ing and submitting votes to the cluster!
+/// `AsyncTowerStorage` that runs a `TowerStorage` on the blocking thread pool
+pub struct AsyncAdapter<T> {
+    inner: Arc<T>,
+}
+
+impl<T: TowerStorage + 'static> AsyncAdapter<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner: Arc::new(inner),
         }
-        fs::rename(&new_filename, &filename)?;
-        // self.path.parent().sync_all() hurts performance same as the above sync
-        Ok(())
     }
 }
 
-pub struct EtcdTowerStorage {
-    client: tokio::sync::Mutex<etcd_client::Client>,
-    instance_id: [u8; 8],
-    runtime: tokio::runtime::Runtime,
+#[async_trait]
+impl<T: TowerStorage + 'static> AsyncTowerStorage for AsyncAdapter<T> {
+    async fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        let inner = self.inner.clone();
+        let node_pubkey = *node_pubkey;
+        tokio::task::spawn_blocking(move || inner.load(&node_pubkey))
+            .await
+            .map_err(|err| TowerError::IoError(io::Error::other(err)))?
+    }
+
+    async fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let inner = self.inner.clone();
+        let saved_tower = saved_tower.clone();
+        tokio::task::spawn_blocking(move || inner.store(&saved_tower))
+            .await
+            .map_err(|err| TowerError::IoError(io::Error::other(err)))?
+    }
 }
 
-pub struct EtcdTlsConfig {
-    pub domain_name: String,
-    pub ca_certificate: Vec<u8>,
-    pub identity_certificate: Vec<u8>,
-    pub identity_private_key: Vec<u8>,
+/// Doesn't store towers. By default loads fail with `TowerNotFound`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NullTowerStorage {
+    default_tower: Option<Pubkey>,
 }
 
-impl EtcdTowerStorage {
-    pub fn new<E: AsRef<str>, S: AsRef<[E]>>(
-        endpoints: S,
-        tls_config: Option<EtcdTlsConfig>,
-    ) -> Result<Self> {
-        let runtime = tokio::runtime::Builder::new_current_thread()
-            .enable_io()
-            .enable_time()
-            .build()
-            .unwrap();
+impl NullTowerStorage {
+    /// Loads an empty tower for `node_pubkey`, so that non-voting nodes can
+    /// load their tower like voting ones
+    pub fn new_with_default_tower(node_pubkey: Pubkey) -> Self {
+        Self {
+            default_tower: Some(node_pubkey),
+        }
+    }
+}
 
-        let client = runtime
-            .block_on(etcd_client::Client::connect(
-                endpoints,
-                tls_config.map(|tls_config| {
-                    etcd_client::ConnectOptions::default().with_tls(
-                        etcd_client::TlsOptions::new()
-                            .domain_name(tls_config.domain_name)
-                            .ca_certificate(etcd_client::Certificate::from_pem(
-                                tls_config.ca_certificate,
-                            ))
-                            .identity(etcd_client::Identity::from_pem(
-                                tls_config.identity_certificate,
-                                tls_config.identity_private_key,
-                            )),
-                    )
-                }),
-            ))
-            .map_err(Self::etdc_to_tower_error)?;
+impl TowerStorage for NullTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        if self.default_tower == Some(*node_pubkey) {
+            return Ok(Tower::default_for(*node_pubkey));
+        }
+        Err(TowerError::TowerNotFound(*node_pubkey))
+    }
 
-        Ok(Self {
-            client: tokio::sync::Mutex::new(client),
-            instance_id: solana_time_utils::timestamp().to_le_bytes(),
-            runtime,
-        })
+    fn store(&self, _saved_tower: &SavedTowerVersions) -> Result<()> {
+        Ok(())
+    }
+
+    fn store_sentinel(&self, _node_pubkey: &Pubkey) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps towers in memory, for tests and single-process simulations. Towers
+/// are serialized as by `FileTowerStorage`, so loads still verify signatures.
+#[derive(Debug, Default)]
+pub struct MemoryTowerStorage {
+    towers: RwLock<HashMap<Pubkey, Vec<u8>>>,
+    fail_next_store: AtomicBool,
+    fail_next_load: AtomicBool,
+}
+
+impl MemoryTowerStorage {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn get_keys(node_pubkey: &Pubkey) -> (String, String) {
-        let instance_key = format!("{node_pubkey}/instance");
-        let tower_key = format!("{node_pubkey}/tower");
-        (instance_key, tower_key)
+    /// Make the next call to `store()` fail without storing the tower
+    pub fn fail_next_store(&self) {
+        self.fail_next_store.store(true, Ordering::Relaxed);
     }
 
-    fn etdc_to_tower_error(error: etcd_client::Error) -> TowerError {
-        TowerError::IoError(io::Error::other(error.to_string()))
+    /// Make the next call to `load()` fail
+    pub fn fail_next_load(&self) {
+        self.fail_next_load.store(true, Ordering::Relaxed);
     }
 }
 
-impl TowerStorage for EtcdTowerStorage {
+impl TowerStorage for MemoryTowerStorage {
     fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
-        let (instance_key, tower_key) = Self::get_keys(node_pubkey);
-
-        let txn = etcd_client::Txn::new().and_then(vec![etcd_client::TxnOp::put(
-            instance_key.clone(),
-            self.instance_id,
-            None,
-        )]);
-        self.runtime
-            .block_on(async { self.client.lock().await.txn(txn).await })
-            .map_err(|err| {
-                error!("Failed to acquire etcd instance lock: {}", err);
-                Self::etdc_to_tower_error(err)
-            })?;
-
-        let txn = etcd_client::Txn::new()
-            .when(vec![etcd_client::Compare::value(
-                instance_key,
-                etcd_client::CompareOp::Equal,
-                self.instance_id,
-            )])
-            .and_then(vec![etcd_client::TxnOp::get(tower_key, None)]);
-
-        let response = self
-            .runtime
-            .block_on(async { self.client.lock().await.txn(txn).await })
-            .map_err(|err| {
-                error!("Failed to read etcd saved tower: {}", err);
-                Self::etdc_to_tower_error(err)
-            })?;
-
-        if !response.succeeded() {
-            return Err(TowerError::IoError(io::Error::other(format!(
-                "Lost etcd instance lock for {node_pubkey}"
-            ))));
-        }
-
-        for op_response in response.op_responses() {
-            if let etcd_client::TxnOpResponse::Get(get_response) = op_response {
-                if let Some(kv) = get_response.kvs().first() {
-                    return bincode::deserialize_from(kv.value())
-                        .map_err(|e| e.into())
-                        .and_then(|t: SavedTowerVersions| t.try_into_tower(node_pubkey));
-                }
-            }
+        self.load_saved_tower(node_pubkey)?
+            .try_into_tower(node_pubkey)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        if self.fail_next_store.swap(false, Ordering::Relaxed) {
+            return Err(TowerError::IoError(io::Error::other(
+                "MemoryTowerStorage::store() failure injected",
+            )));
         }
+        let data = saved_tower.serialize()?;
+        self.towers
+            .write()
+            .unwrap()
+            .insert(saved_tower.pubkey(), data);
+        Ok(())
+    }
 
-        // Should never happen...
-        Err(TowerError::IoError(io::Error::other(
-            "Saved tower response missing".to_string(),
-        )))
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        if self.fail_next_load.swap(false, Ordering::Relaxed) {
+            return Err(TowerError::IoError(io::Error::other(
+                "MemoryTowerStorage::load() failure injected",
+            )));
+        }
+        let towers = self.towers.read().unwrap();
+        let data = towers
+            .get(node_pubkey)
+            .ok_or(TowerError::TowerNotFound(*node_pubkey))?;
+        SavedTowerVersions::deserialize(data)
     }
+}
 
-    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
-        let (instance_key, tower_key) = Self::get_keys(&saved_tower.pubkey());
-
-        let txn = etcd_client::Txn::new()
-            .when(vec![etcd_client::Compare::value(
-                instance_key,
-                etcd_client::CompareOp::Equal,
-                self.instance_id,
-            )])
-            .and_then(vec![etcd_client::TxnOp::put(
-                tower_key,
-                bincode::serialize(&saved_tower)?,
-                None,
-            )]);
-
-        let response = self
-            .runtime
-            .block_on(async { self.client.lock().await.txn(txn).await })
-            .map_err(|err| {
-                error!("Failed to write etcd saved tower: {}", err);
-                err
-            })
-            .map_err(Self::etdc_to_tower_error)?;
+/// Format a tower was saved in, as found by `migrate()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SavedTowerFormat {
+    /// `SavedTower1_7_14`
+    V1_7_14,
+    /// `SavedTower`
+    V1_14_11,
+    /// `SavedTower2`
+    V2,
+}
 
-        if !response.succeeded() {
-            return Err(TowerError::IoError(io::Error::other(format!(
-                "Lost etcd instance lock for {}",
-                saved_tower.pubkey()
-            ))));
+impl From<&SavedTowerVersions> for SavedTowerFormat {
+    fn from(saved_tower: &SavedTowerVersions) -> Self {
+        match saved_tower {
+            SavedTowerVersions::V1_17_14(_) => Self::V1_7_14,
+            SavedTowerVersions::Current(_) => Self::V1_14_11,
+            SavedTowerVersions::V2(_) => Self::V2,
         }
-        Ok(())
     }
 }
 
-#[cfg(test)]
-pub mod test {
-    use {
-        super::*,
-        crate::consensus::{
-            tower1_7_14::{SavedTower1_7_14, Tower1_7_14},
-            BlockhashStatus, Tower,
-        },
-        solana_hash::Hash,
-        solana_keypair::Keypair,
-        solana_vote::vote_transaction::VoteTransaction,
-        solana_vote_program::vote_state::{
-            BlockTimestamp, LandedVote, Vote, VoteState, VoteState1_14_11, MAX_LOCKOUT_HISTORY,
-        },
-        tempfile::TempDir,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub source_format: SavedTowerFormat,
+    pub last_vote_slot: Option<Slot>,
+    /// Whether the tower read back from the destination matches the migrated
+    /// tower
+    pub verified: bool,
+}
+
+/// Copies the tower of `node_pubkey` from `source` to `dest`, re-signed with
+/// `node_keypair` in the current format. Nothing is stored unless the source
+/// tower is validly signed by `node_pubkey`.
+pub fn migrate(
+    source: &dyn TowerStorage,
+    dest: &dyn TowerStorage,
+    node_pubkey: &Pubkey,
+    node_keypair: &Keypair,
+) -> Result<MigrationReport> {
+    if node_keypair.pubkey() != *node_pubkey {
+        return Err(TowerError::WrongTower {
+            expected: *node_pubkey,
+            found: node_keypair.pubkey(),
+        });
+    }
+
+    // Storages already fall back to the formats of older releases
+    let saved_tower = source.load_saved_tower(node_pubkey)?;
+    let source_format = SavedTowerFormat::from(&saved_tower);
+    let tower = saved_tower.try_into_tower(node_pubkey)?;
+
+    let saved_tower = SavedTower2::new(&tower, node_keypair)?;
+    dest.store(&SavedTowerVersions::from(saved_tower))?;
+    let verified = dest.load(node_pubkey)? == tower;
+
+    let report = MigrationReport {
+        source_format,
+        last_vote_slot: tower.last_voted_slot(),
+        verified,
     };
+    info!("Migrated tower for {node_pubkey}: {report:?}");
+    Ok(report)
+}
 
-    #[test]
-    fn test_tower_migration() {
-        let tower_path = TempDir::new().unwrap();
-        let identity_keypair = Keypair::new();
-        let node_pubkey = identity_keypair.pubkey();
-        let mut vote_state = VoteState::default();
-        vote_state
-            .votes
-            .resize(MAX_LOCKOUT_HISTORY, LandedVote::default());
-        vote_state.root_slot = Some(1);
+/// A saved tower as read by `inspect_tower()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TowerInspection {
+    pub node_pubkey: Pubkey,
+    pub format: SavedTowerFormat,
+    /// Always true: the tower's signature isn't verified, so nothing here can
+    /// be trusted to be what the validator saved
+    pub unverified: bool,
+    /// Each vote's slot and confirmation count, oldest first
+    pub votes: Vec<Lockout>,
+    pub root_slot: Option<Slot>,
+    pub last_vote_slot: Option<Slot>,
+    pub last_timestamp: BlockTimestamp,
+}
 
-        let vote = Vote::new(vec![1, 2, 3, 4], Hash::default());
-        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+/// Reads the tower of `node_pubkey` from `storage` for debugging, without the
+/// node keypair or verifying the tower's signature
+pub fn inspect_tower(storage: &dyn TowerStorage, node_pubkey: &Pubkey) -> Result<TowerInspection> {
+    let saved_tower = storage.load_saved_tower(node_pubkey)?;
+    let tower = saved_tower.to_tower_unverified()?;
+    Ok(TowerInspection {
+        node_pubkey: tower.node_pubkey,
+        format: SavedTowerFormat::from(&saved_tower),
+        unverified: true,
+        votes: tower.vote_state.votes.iter().copied().collect(),
+        root_slot: tower.vote_state.root_slot,
+        last_vote_slot: tower.last_voted_slot(),
+        last_timestamp: tower.last_timestamp,
+    })
+}
 
-        let old_tower = Tower1_7_14 {
-            node_pubkey,
-            threshold_depth: 10,
-            threshold_size: 0.9,
-            vote_state: VoteState1_14_11::from(vote_state),
-            last_vote: vote.clone(),
-            last_timestamp: BlockTimestamp::default(),
-            last_vote_tx_blockhash: BlockhashStatus::Uninitialized,
-            stray_restored_slot: Some(2),
-            last_switch_threshold_check: Option::default(),
-        };
+/// Options for the tower storage selected by `from_uri()` that don't fit in a
+/// URI
+#[derive(Default)]
+pub struct StorageOptions {
+    /// Used by `etcd://` URIs with `tls=true`
+    pub etcd_tls: Option<EtcdTlsConfig>,
+    /// Used by `postgres://` URIs with `tls=true`
+    #[cfg(feature = "postgres-tower-storage")]
+    pub postgres_tls: Option<PostgresTlsConfig>,
+    /// Node whose tower `null:` loads as an empty tower, see
+    /// `NullTowerStorage::new_with_default_tower()`
+    pub default_tower: Option<Pubkey>,
+}
 
-        {
-            let saved_tower = SavedTower1_7_14::new(&old_tower, &identity_keypair).unwrap();
-            tower_storage.store_old(&saved_tower).unwrap();
+/// Selects and configures a tower storage with a URI, one of:
+///
+/// - `file:///path`, with parameters `durability` (`none`, `data` or `full`),
+///   `backups` and `lock`
+/// - `etcd://host1:2379,host2:2379`, with parameters `tls`,
+///   `operation_timeout_ms`, `max_retries`, `max_reconnect_backoff_ms`,
+///   `lease_ttl_ms` and `force`
+/// - `zk://host1:2181,host2:2181/chroot`, with parameters `auth_digest`,
+///   `session_timeout_ms`, `operation_timeout_ms`, `max_retries` and `force`.
+///   Requires the `zk-tower-storage` feature.
+/// - `postgres://user@host/dbname`, with parameters `tls`, `pool_size`,
+///   `stale_lock_age_ms`, `operation_timeout_ms`, `max_retries` and `force`.
+///   Other parameters are passed on to Postgres. Requires the
+///   `postgres-tower-storage` feature.
+/// - `null:` or `memory:`
+///
+/// Paths and parameter values are percent-decoded. Parameters left out take
+/// the backend's defaults. Networked backends are only connected to on first
+/// use, so an unreachable backend fails its loads and stores rather than this.
+pub fn from_uri(uri: &str, extra: StorageOptions) -> Result<Box<dyn TowerStorage>> {
+    let default_tower = extra.default_tower;
+    let tower_storage: Box<dyn TowerStorage> = match parse_storage_uri(uri, extra)? {
+        StorageUri::File {
+            tower_path,
+            durability,
+            backup_count,
+            instance_lock,
+        } => {
+            let tower_storage = FileTowerStorage::new(tower_path)
+                .with_durability(durability)
+                .with_backup_count(backup_count);
+            if instance_lock {
+                Box::new(tower_storage.with_instance_lock())
+            } else {
+                Box::new(tower_storage)
+            }
+        }
+        StorageUri::Etcd {
+            config,
+            lease_ttl,
+            force,
+        } => Box::new(LazyTowerStorage::new(move || {
+            Ok(EtcdTowerStorage::new(config.clone())?
+                .with_lease_ttl(lease_ttl)
+                .with_force(force))
+        })),
+        #[cfg(feature = "zk-tower-storage")]
+        StorageUri::ZooKeeper { config, force } => Box::new(LazyTowerStorage::new(move || {
+            Ok(ZkTowerStorage::new(config.clone())?.with_force(force))
+        })),
+        #[cfg(feature = "postgres-tower-storage")]
+        StorageUri::Postgres { config, force } => Box::new(LazyTowerStorage::new(move || {
+            Ok(PostgresTowerStorage::new(config.clone())?.with_force(force))
+        })),
+        StorageUri::Null => Box::new(
+            default_tower
+                .map(NullTowerStorage::new_with_default_tower)
+                .unwrap_or_default(),
+        ),
+        StorageUri::Memory => Box::new(MemoryTowerStorage::new()),
+    };
+    Ok(tower_storage)
+}
+
+// Tower storage selected by a URI, before connecting to it
+enum StorageUri {
+    File {
+        tower_path: PathBuf,
+        durability: DurabilityLevel,
+        backup_count: usize,
+        instance_lock: bool,
+    },
+    Etcd {
+        config: EtcdTowerStorageConfig,
+        lease_ttl: Duration,
+        force: bool,
+    },
+    #[cfg(feature = "zk-tower-storage")]
+    ZooKeeper {
+        config: ZkTowerStorageConfig,
+        force: bool,
+    },
+    #[cfg(feature = "postgres-tower-storage")]
+    Postgres {
+        config: PostgresTowerStorageConfig,
+        force: bool,
+    },
+    Null,
+    Memory,
+}
+
+fn parse_storage_uri(uri: &str, extra: StorageOptions) -> Result<StorageUri> {
+    let (scheme, rest) = uri
+        .split_once(':')
+        .ok_or_else(|| invalid_uri("missing scheme"))?;
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut params = UriParams::parse(query)?;
+    let (authority, path) = match rest.strip_prefix("//") {
+        Some(rest) => {
+            let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            (Some(authority), path)
+        }
+        None => (None, rest),
+    };
+
+    let storage_uri = match scheme.to_ascii_lowercase().as_str() {
+        "file" => {
+            if !matches!(authority, Some("" | "localhost")) || path.is_empty() {
+                return Err(invalid_uri("expected file:///path"));
+            }
+            StorageUri::File {
+                tower_path: PathBuf::from(percent_decode(path)?),
+                durability: params.take("durability")?.unwrap_or_default(),
+                backup_count: params.take("backups")?.unwrap_or_default(),
+                instance_lock: params.take("lock")?.unwrap_or_default(),
+            }
+        }
+        "etcd" => {
+            if !matches!(path, "" | "/") {
+                return Err(invalid_uri("etcd URIs take no path"));
+            }
+            let default_config = EtcdTowerStorageConfig::default();
+            let tls = match params.take("tls")? {
+                Some(true) => Some(extra.etcd_tls.ok_or_else(|| {
+                    invalid_uri("parameter tls: no etcd TLS configuration given")
+                })?),
+                Some(false) | None => None,
+            };
+            let config = EtcdTowerStorageConfig {
+                endpoints: parse_endpoints(authority)?,
+                tls,
+                operation_timeout: params
+                    .take_millis("operation_timeout_ms")?
+                    .unwrap_or(default_config.operation_timeout),
+                max_retries: params
+                    .take("max_retries")?
+                    .unwrap_or(default_config.max_retries),
+                max_reconnect_backoff: params
+                    .take_millis("max_reconnect_backoff_ms")?
+                    .unwrap_or(default_config.max_reconnect_backoff),
+            };
+            StorageUri::Etcd {
+                config,
+                lease_ttl: params
+                    .take_millis("lease_ttl_ms")?
+                    .unwrap_or(DEFAULT_ETCD_LEASE_TTL),
+                force: params.take("force")?.unwrap_or_default(),
+            }
+        }
+        #[cfg(feature = "zk-tower-storage")]
+        "zk" => {
+            let default_config = ZkTowerStorageConfig::default();
+            let chroot = match path {
+                "" | "/" => None,
+                path => Some(percent_decode(path)?),
+            };
+            let config = ZkTowerStorageConfig {
+                hosts: parse_endpoints(authority)?,
+                chroot,
+                auth_digest: params.take("auth_digest")?,
+                session_timeout: params
+                    .take_millis("session_timeout_ms")?
+                    .unwrap_or(default_config.session_timeout),
+                operation_timeout: params
+                    .take_millis("operation_timeout_ms")?
+                    .unwrap_or(default_config.operation_timeout),
+                max_retries: params
+                    .take("max_retries")?
+                    .unwrap_or(default_config.max_retries),
+            };
+            StorageUri::ZooKeeper {
+                config,
+                force: params.take("force")?.unwrap_or_default(),
+            }
+        }
+        #[cfg(feature = "postgres-tower-storage")]
+        "postgres" | "postgresql" => {
+            let default_config = PostgresTowerStorageConfig::default();
+            let tls = match params.take("tls")? {
+                Some(true) => Some(extra.postgres_tls.ok_or_else(|| {
+                    invalid_uri("parameter tls: no Postgres TLS configuration given")
+                })?),
+                Some(false) | None => None,
+            };
+            let pool_size = params
+                .take("pool_size")?
+                .unwrap_or(default_config.pool_size);
+            let stale_lock_age = params
+                .take_millis("stale_lock_age_ms")?
+                .unwrap_or(default_config.stale_lock_age);
+            let operation_timeout = params
+                .take_millis("operation_timeout_ms")?
+                .unwrap_or(default_config.operation_timeout);
+            let max_retries = params
+                .take("max_retries")?
+                .unwrap_or(default_config.max_retries);
+            let force = params.take("force")?.unwrap_or_default();
+            // Postgres parses the rest of the URI itself
+            let query = params.into_query();
+            let dsn = if query.is_empty() {
+                format!("{scheme}:{rest}")
+            } else {
+                format!("{scheme}:{rest}?{query}")
+            };
+            let config = PostgresTowerStorageConfig {
+                dsn,
+                tls,
+                pool_size,
+                stale_lock_age,
+                operation_timeout,
+                max_retries,
+            };
+            return Ok(StorageUri::Postgres { config, force });
+        }
+        "null" | "memory" => {
+            if !matches!(authority, None | Some("")) || !path.is_empty() {
+                return Err(invalid_uri(format!("expected {scheme}:")));
+            }
+            if scheme.eq_ignore_ascii_case("null") {
+                StorageUri::Null
+            } else {
+                StorageUri::Memory
+            }
+        }
+        _ => return Err(invalid_uri(format!("unknown scheme {scheme}"))),
+    };
+    params.finish()?;
+    Ok(storage_uri)
+}
+
+fn invalid_uri(reason: impl fmt::Display) -> TowerError {
+    TowerError::InvalidStorageUri(reason.to_string())
+}
+
+// Comma separated `host[:port]` list of a URI's authority
+fn parse_endpoints(authority: Option<&str>) -> Result<Vec<String>> {
+    let authority = authority
+        .filter(|authority| !authority.is_empty())
+        .ok_or_else(|| invalid_uri("missing endpoints"))?;
+    authority
+        .split(',')
+        .map(|endpoint| {
+            let (host, port) = match endpoint.rsplit_once(':') {
+                // Not a colon within an IPv6 address
+                Some((host, port)) if !port.contains(']') => (host, Some(port)),
+                _ => (endpoint, None),
+            };
+            if host.is_empty() || host.contains(['@', '/', ' ']) {
+                return Err(invalid_uri(format!("invalid endpoint {endpoint:?}")));
+            }
+            if let Some(port) = port {
+                port.parse::<u16>()
+                    .map_err(|_| invalid_uri(format!("invalid port in endpoint {endpoint:?}")))?;
+            }
+            Ok(endpoint.to_string())
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> Result<String> {
+    let invalid = || invalid_uri(format!("invalid percent-encoding in {s:?}"));
+    let mut decoded = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+        let hex = [bytes.next(), bytes.next()];
+        let [Some(high), Some(low)] = hex.map(|digit| (digit? as char).to_digit(16)) else {
+            return Err(invalid());
+        };
+        decoded.push((high * 16 + low) as u8);
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+// Query parameters of a URI, each taken by the backend that uses it
+struct UriParams {
+    // (decoded name, decoded value, raw `name=value`)
+    params: Vec<(String, String, String)>,
+}
+
+impl UriParams {
+    fn parse(query: &str) -> Result<Self> {
+        let mut params: Vec<(String, String, String)> = vec![];
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (name, value) = param
+                .split_once('=')
+                .ok_or_else(|| invalid_uri(format!("parameter {param} has no value")))?;
+            let name = percent_decode(name)?;
+            if params.iter().any(|(other, _, _)| *other == name) {
+                return Err(invalid_uri(format!("duplicate parameter {name}")));
+            }
+            params.push((name, percent_decode(value)?, param.to_string()));
+        }
+        Ok(Self { params })
+    }
+
+    fn take<T: FromStr>(&mut self, name: &str) -> Result<Option<T>> {
+        let Some(index) = self.params.iter().position(|(other, _, _)| other == name) else {
+            return Ok(None);
+        };
+        let (_, value, _) = self.params.remove(index);
+        value
+            .parse()
+            .map(Some)
+            .map_err(|_| invalid_uri(format!("parameter {name}: invalid value {value:?}")))
+    }
+
+    fn take_millis(&mut self, name: &str) -> Result<Option<Duration>> {
+        Ok(self.take(name)?.map(Duration::from_millis))
+    }
+
+    // The parameters not taken, as they appeared in the URI
+    #[cfg(feature = "postgres-tower-storage")]
+    fn into_query(self) -> String {
+        self.params
+            .into_iter()
+            .map(|(_, _, param)| param)
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    // Fails if any parameter wasn't taken
+    fn finish(self) -> Result<()> {
+        match self.params.first() {
+            Some((name, _, _)) => Err(invalid_uri(format!("unknown parameter {name}"))),
+            None => Ok(()),
+        }
+    }
+}
+
+type ConnectFn = Box<dyn Fn() -> Result<Arc<dyn TowerStorage>> + Send + Sync>;
+
+/// Connects to a networked tower storage on first use rather than when
+/// constructed. A failed connect is retried on the next use.
+struct LazyTowerStorage {
+    connect: ConnectFn,
+    tower_storage: Mutex<Option<Arc<dyn TowerStorage>>>,
+}
+
+impl LazyTowerStorage {
+    fn new<T: TowerStorage + 'static>(
+        connect: impl Fn() -> Result<T> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            connect: Box::new(move || Ok(Arc::new(connect()?) as Arc<dyn TowerStorage>)),
+            tower_storage: Mutex::default(),
+        }
+    }
+
+    fn get(&self) -> Result<Arc<dyn TowerStorage>> {
+        let mut tower_storage = self.tower_storage.lock().unwrap();
+        if let Some(tower_storage) = &*tower_storage {
+            return Ok(tower_storage.clone());
+        }
+        let connected = (self.connect)()?;
+        *tower_storage = Some(connected.clone());
+        Ok(connected)
+    }
+}
+
+impl TowerStorage for LazyTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.get()?.load(node_pubkey)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        self.get()?.store(saved_tower)
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.get()?.load_saved_tower(node_pubkey)
+    }
+
+    fn exists(&self, node_pubkey: &Pubkey) -> Result<bool> {
+        self.get()?.exists(node_pubkey)
+    }
+
+    fn store_sentinel(&self, node_pubkey: &Pubkey) -> Result<()> {
+        self.get()?.store_sentinel(node_pubkey)
+    }
+
+    fn preflight(&self, node_pubkey: &Pubkey) -> Result<PreflightReport> {
+        self.get()?.preflight(node_pubkey)
+    }
+}
+
+/// Whether `TowerConsistencyReport::into_result()` fails on mismatches
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TowerConsistencyStrictness {
+    /// Mismatches are only reported
+    #[default]
+    Lenient,
+    /// Mismatches fail the tower restore
+    Strict,
+}
+
+impl FromStr for TowerConsistencyStrictness {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "lenient" => Ok(Self::Lenient),
+            "strict" => Ok(Self::Strict),
+            _ => Err(format!("unknown tower consistency strictness {s}")),
+        }
+    }
+}
+
+/// Outcome of `verify_against_blockstore()`. Only the lockouts above the
+/// root bank are checked, the older ones being superseded by the root.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TowerConsistencyReport {
+    pub root_bank_slot: Slot,
+    pub blockstore_root: Slot,
+    /// Lockouts found in blockstore, on the fork of the newest lockout
+    pub present: Vec<Slot>,
+    /// Lockouts above the blockstore root which are missing from it
+    pub missing: Vec<Slot>,
+    /// Lockouts found in blockstore without a known parent
+    pub orphaned: Vec<Slot>,
+    /// Lockouts found in blockstore which are not ancestors of the newest
+    /// lockout, i.e. the tower votes on several forks
+    pub off_fork: Vec<Slot>,
+}
+
+impl TowerConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.orphaned.is_empty() && self.off_fork.is_empty()
+    }
+
+    /// Fails with `TowerError::InconsistentWithBlockstore` if the tower
+    /// mismatches blockstore and `strictness` is `Strict`
+    pub fn into_result(self, strictness: TowerConsistencyStrictness) -> Result<Self> {
+        match strictness {
+            TowerConsistencyStrictness::Strict if !self.is_consistent() => {
+                Err(TowerError::InconsistentWithBlockstore(self.to_string()))
+            }
+            _ => Ok(self),
+        }
+    }
+}
+
+impl fmt::Display for TowerConsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} tower slots present, missing: {:?}, orphaned: {:?}, off fork: {:?} (root bank \
+             {}, blockstore root {})",
+            self.present.len(),
+            self.missing,
+            self.orphaned,
+            self.off_fork,
+            self.root_bank_slot,
+            self.blockstore_root,
+        )
+    }
+}
+
+/// Checks that the lockouts of a restored tower above `root_bank_slot` are
+/// in `blockstore` and on a single fork, to catch towers restored from the
+/// wrong backup before voting resumes. Lockouts at or below the blockstore
+/// root may have been purged from the ledger, so only the ones above it are
+/// reported missing.
+pub fn verify_against_blockstore(
+    tower: &Tower,
+    blockstore: &Blockstore,
+    root_bank_slot: Slot,
+) -> TowerConsistencyReport {
+    let blockstore_root = blockstore.max_root();
+    let mut report = TowerConsistencyReport {
+        root_bank_slot,
+        blockstore_root,
+        ..TowerConsistencyReport::default()
+    };
+    let tower_slots = tower.tower_slots();
+    let ancestors: HashSet<Slot> = tower_slots
+        .last()
+        .map(|&slot| {
+            AncestorIterator::new_inclusive(slot, blockstore)
+                .take_while(|&ancestor| ancestor > root_bank_slot)
+                .collect()
+        })
+        .unwrap_or_default();
+    for slot in tower_slots {
+        if slot <= root_bank_slot {
+            continue;
+        }
+        match blockstore.meta(slot).ok().flatten() {
+            None if slot > blockstore_root => report.missing.push(slot),
+            None => (),
+            Some(meta) if meta.parent_slot.is_none() => report.orphaned.push(slot),
+            // Without the newest lockout, the fork is unknown.
+            Some(_) if !ancestors.is_empty() && !ancestors.contains(&slot) => {
+                report.off_fork.push(slot)
+            }
+            Some(_) => report.present.push(slot),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+pub mod test {
+    use {
+        super::*,
+        crate::consensus::{
+            tower1_7_14::{SavedTower1_7_14, Tower1_7_14},
+            BlockhashStatus, Tower,
+        },
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_ledger::{blockstore::make_slot_entries, get_tmp_ledger_path_auto_delete},
+        solana_vote::vote_transaction::VoteTransaction,
+        solana_vote_program::vote_state::{
+            BlockTimestamp, LandedVote, Vote, VoteState, VoteState1_14_11, MAX_LOCKOUT_HISTORY,
+        },
+        std::{fs, path::Path, thread},
+        tempfile::TempDir,
+    };
+
+    pub(super) fn new_tower_for_tests(identity_keypair: &Keypair, root_slot: Option<u64>) -> Tower {
+        let mut tower = Tower::new_for_tests(10, 0.9);
+        tower.node_pubkey = identity_keypair.pubkey();
+        tower.vote_state.root_slot = root_slot;
+        tower
+    }
+
+    #[test]
+    fn test_saved_tower_frame() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        // Older formats are left unframed for older releases
+        let saved_tower =
+            SavedTowerVersions::new(&tower, &identity_keypair, SavedTowerWriteFormat::Legacy)
+                .unwrap();
+        let data = saved_tower.serialize().unwrap();
+        assert_eq!(data, bincode::serialize(&saved_tower).unwrap());
+        assert_eq!(SavedTowerVersions::deserialize(&data).unwrap(), saved_tower);
+
+        let saved_tower =
+            SavedTowerVersions::new(&tower, &identity_keypair, SavedTowerWriteFormat::V2).unwrap();
+        let data = saved_tower.serialize().unwrap();
+        assert!(data.starts_with(&SAVED_TOWER_MAGIC));
+
+        // Round trip
+        let loaded = SavedTowerVersions::deserialize(&data)
+            .and_then(|t| t.try_into_tower(&node_pubkey))
+            .unwrap();
+        assert_eq!(loaded, tower);
+
+        // Bit flip in the payload
+        let mut corrupt = data.clone();
+        corrupt[SAVED_TOWER_FRAME_HEADER_LEN + 10] ^= 1;
+        assert_matches!(
+            SavedTowerVersions::deserialize(&corrupt),
+            Err(TowerError::CorruptTower(msg)) if msg.starts_with("crc32 mismatch")
+        );
+
+        // Unknown format version
+        let mut unsupported = data.clone();
+        unsupported[SAVED_TOWER_MAGIC.len()] = 2;
+        assert_matches!(
+            SavedTowerVersions::deserialize(&unsupported),
+            Err(TowerError::UnsupportedTowerFormat(
+                2,
+                SAVED_TOWER_FORMAT_VERSION
+            ))
+        );
+
+        // Truncation
+        for len in [8, SAVED_TOWER_FRAME_HEADER_LEN, data.len() - 1] {
+            assert_matches!(
+                SavedTowerVersions::deserialize(&data[..len]),
+                Err(TowerError::CorruptTower(_))
+            );
+        }
+    }
+
+    #[test]
+    fn test_tower_storage_load_errors() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+
+        // File
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::TowerNotFound(pubkey)) if pubkey == node_pubkey
+        );
+        assert!(!tower_storage.exists(&node_pubkey).unwrap());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert!(tower_storage.exists(&node_pubkey).unwrap());
+        let filename = tower_storage.filename(&node_pubkey);
+        fs::write(&filename, b"garbage").unwrap();
+        let filename = filename.display().to_string();
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::CorruptTower(reason)) if reason.starts_with(&filename)
+        );
+        assert_matches!(
+            tower_storage.exists(&node_pubkey),
+            Err(TowerError::CorruptTower(_))
+        );
+
+        // Null
+        let tower_storage = NullTowerStorage::default();
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::TowerNotFound(pubkey)) if pubkey == node_pubkey
+        );
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert!(!tower_storage.exists(&node_pubkey).unwrap());
+    }
+
+    #[test]
+    fn test_null_tower_storage_default_tower() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = NullTowerStorage::new_with_default_tower(node_pubkey);
+
+        let tower = Tower::restore(&tower_storage, &node_pubkey).unwrap();
+        assert_eq!(tower, Tower::default_for(node_pubkey));
+        assert!(tower_storage.exists(&node_pubkey).unwrap());
+        // Only for the configured node
+        assert_matches!(
+            Tower::restore(&tower_storage, &Keypair::new().pubkey()),
+            Err(TowerError::TowerNotFound(_))
+        );
+
+        // The synthesized tower is a valid tower
+        let saved_tower =
+            SavedTowerVersions::from(SavedTower2::new(&tower, &identity_keypair).unwrap());
+        assert_eq!(saved_tower.try_into_tower(&node_pubkey).unwrap(), tower);
+
+        // Stores are still a no-op
+        let mut new_tower = new_tower_for_tests(&identity_keypair, Some(1));
+        new_tower.record_vote(5, Hash::default());
+        new_tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_null_tower_storage_preflight() {
+        let node_pubkey = Pubkey::new_unique();
+        let report = NullTowerStorage::default().preflight(&node_pubkey).unwrap();
+        assert!(!report.tower_found);
+        assert!(report.store_latency.is_some());
+        let tower_storage = NullTowerStorage::new_with_default_tower(node_pubkey);
+        assert!(tower_storage.preflight(&node_pubkey).unwrap().tower_found);
+
+        // Storages without a scratch key only check loads
+        let report = MemoryTowerStorage::new().preflight(&node_pubkey).unwrap();
+        assert!(!report.tower_found);
+        assert_eq!(report.store_latency, None);
+    }
+
+    fn tower_with_lockouts(slots: &[Slot]) -> Tower {
+        let mut tower = Tower::default();
+        tower.vote_state.votes = slots.iter().map(|&slot| Lockout::new(slot)).collect();
+        tower
+    }
+
+    #[test]
+    fn test_verify_against_blockstore() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        // 0 - 1 - 2 - 3 - 5
+        //      \
+        //       4
+        // and 8, whose parent 7 is unknown
+        for (slot, parent) in [(0, 0), (1, 0), (2, 1), (3, 2), (4, 1), (5, 3), (8, 7)] {
+            let (shreds, _) = make_slot_entries(slot, parent, 42, /*merkle_variant:*/ true);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+        }
+        blockstore.set_roots([0, 1].iter()).unwrap();
+
+        // Lockouts at or below the root bank are not checked.
+        let tower = tower_with_lockouts(&[1, 2, 3, 5]);
+        let report = verify_against_blockstore(&tower, &blockstore, 1);
+        assert_eq!(
+            report,
+            TowerConsistencyReport {
+                root_bank_slot: 1,
+                blockstore_root: 1,
+                present: vec![2, 3, 5],
+                ..TowerConsistencyReport::default()
+            }
+        );
+        assert!(report.is_consistent());
+        assert_eq!(
+            report
+                .clone()
+                .into_result(TowerConsistencyStrictness::Strict)
+                .unwrap(),
+            report
+        );
+        assert!(verify_against_blockstore(&Tower::default(), &blockstore, 1).is_consistent());
+
+        // Voted past the end of the ledger
+        let report = verify_against_blockstore(&tower_with_lockouts(&[2, 3, 5, 6]), &blockstore, 1);
+        assert_eq!(report.present, vec![2, 3, 5]);
+        assert_eq!(report.missing, vec![6]);
+        assert!(!report.is_consistent());
+        assert_eq!(
+            report
+                .clone()
+                .into_result(TowerConsistencyStrictness::Lenient)
+                .unwrap(),
+            report
+        );
+        assert_matches!(
+            report.into_result(TowerConsistencyStrictness::Strict),
+            Err(TowerError::InconsistentWithBlockstore(message)) if message.contains("missing: [6]")
+        );
+
+        // Voted on both forks
+        let report = verify_against_blockstore(&tower_with_lockouts(&[2, 3, 4]), &blockstore, 1);
+        assert_eq!(report.present, vec![4]);
+        assert_eq!(report.off_fork, vec![2, 3]);
+        assert!(!report.is_consistent());
+
+        let report = verify_against_blockstore(&tower_with_lockouts(&[7, 8]), &blockstore, 1);
+        assert_eq!(report.present, vec![8]);
+        assert_eq!(report.orphaned, vec![7]);
+        assert!(report.missing.is_empty());
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn test_saved_tower2() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.record_vote(5, Hash::default());
+        let saved_tower = SavedTower2::new(&tower, &identity_keypair).unwrap();
+        assert_eq!(saved_tower.node_pubkey(), &node_pubkey);
+        assert_eq!(saved_tower.root_slot(), Some(1));
+        assert_eq!(saved_tower.last_vote_slot(), Some(5));
+
+        // The embedded pubkey survives a round trip, so the blob can be
+        // attributed before it's verified
+        let data = SavedTowerVersions::from(saved_tower.clone())
+            .serialize()
+            .unwrap();
+        let loaded = SavedTowerVersions::deserialize(&data).unwrap();
+        assert_eq!(loaded.pubkey(), node_pubkey);
+        assert_eq!(loaded.try_into_tower(&node_pubkey).unwrap(), tower);
+
+        // The embedded pubkey must match the caller's
+        let other_pubkey = Keypair::new().pubkey();
+        assert_matches!(
+            loaded.try_into_tower(&other_pubkey),
+            Err(TowerError::WrongTower { .. })
+        );
+
+        // and must have signed the data
+        let mut forged = saved_tower.clone();
+        forged.node_pubkey = other_pubkey;
+        assert_matches!(
+            SavedTowerVersions::from(forged).try_into_tower(&other_pubkey),
+            Err(TowerError::InvalidSignature)
+        );
+
+        // The metadata must match the tower data
+        let mut corrupt = saved_tower;
+        corrupt.root_slot = Some(2);
+        assert_matches!(
+            SavedTowerVersions::from(corrupt).try_into_tower(&node_pubkey),
+            Err(TowerError::CorruptTower(_))
+        );
+    }
+
+    /// Checks that towers saved by older releases load from `tower_storage`,
+    /// and are stored back as `SavedTower2`
+    pub(super) fn check_saved_tower2_migration(tower_storage: &dyn TowerStorage) {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+
+        // Towers saved by older releases still load
+        let old_tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let saved_tower =
+            SavedTowerVersions::from(SavedTower::new(&old_tower, &identity_keypair).unwrap());
+        tower_storage.store(&saved_tower).unwrap();
+        assert_matches!(
+            tower_storage.load_saved_tower(&node_pubkey).unwrap(),
+            SavedTowerVersions::Current(_)
+        );
+        let mut tower = Tower::restore(tower_storage, &node_pubkey).unwrap();
+        assert_eq!(tower, old_tower);
+
+        // and are stored back as `SavedTower2`
+        tower.record_vote(5, Hash::default());
+        tower.save(tower_storage, &identity_keypair).unwrap();
+        let SavedTowerVersions::V2(saved_tower) =
+            tower_storage.load_saved_tower(&node_pubkey).unwrap()
+        else {
+            panic!("expected a SavedTower2");
+        };
+        assert_eq!(saved_tower.node_pubkey(), &node_pubkey);
+        assert_eq!(saved_tower.root_slot(), Some(1));
+        assert_eq!(saved_tower.last_vote_slot(), Some(5));
+        assert_eq!(Tower::restore(tower_storage, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_saved_tower2_migration() {
+        let tower_path = TempDir::new().unwrap();
+        check_saved_tower2_migration(&FileTowerStorage::new(tower_path.path().to_path_buf()));
+    }
+
+    /// Records the towers stored into a `MemoryTowerStorage`, checking that
+    /// stores never overlap. Async stores wait while `paused` is set.
+    #[derive(Default)]
+    pub(super) struct RecordingTowerStorage {
+        pub(super) inner: MemoryTowerStorage,
+        pub(super) paused: AtomicBool,
+        storing: AtomicBool,
+        pub(super) stored: Mutex<Vec<SavedTowerVersions>>,
+    }
+
+    #[async_trait]
+    impl AsyncTowerStorage for RecordingTowerStorage {
+        async fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+            self.inner.load(node_pubkey)
+        }
+
+        async fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+            assert!(!self.storing.swap(true, Ordering::Relaxed));
+            while self.paused.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+            self.stored.lock().unwrap().push(saved_tower.clone());
+            let result = self.inner.store(saved_tower);
+            self.storing.store(false, Ordering::Relaxed);
+            result
+        }
+    }
+
+    impl TowerStorage for RecordingTowerStorage {
+        fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+            self.inner.load(node_pubkey)
+        }
+
+        fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+            assert!(!self.storing.swap(true, Ordering::Relaxed));
+            self.stored.lock().unwrap().push(saved_tower.clone());
+            let result = self.inner.store(saved_tower);
+            self.storing.store(false, Ordering::Relaxed);
+            result
+        }
+    }
+
+    pub(super) fn new_runtime_for_tests() -> Arc<tokio::runtime::Runtime> {
+        Arc::new(
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(2)
+                .enable_all()
+                .build()
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_tower_storage_adapters() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = BlockingAdapter::new(
+            AsyncAdapter::new(MemoryTowerStorage::new()),
+            new_runtime_for_tests(),
+        );
+
+        assert!(Tower::restore(&tower_storage, &node_pubkey)
+            .unwrap_err()
+            .is_file_missing());
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_memory_tower_storage_concurrent() {
+        let tower_storage = Arc::new(MemoryTowerStorage::new());
+        let handles = (0..4)
+            .map(|_| {
+                let tower_storage = tower_storage.clone();
+                thread::spawn(move || {
+                    let identity_keypair = Keypair::new();
+                    let node_pubkey = identity_keypair.pubkey();
+                    assert!(Tower::restore(tower_storage.as_ref(), &node_pubkey)
+                        .unwrap_err()
+                        .is_file_missing());
+                    for root in 0..100 {
+                        let tower = new_tower_for_tests(&identity_keypair, Some(root));
+                        tower
+                            .save(tower_storage.as_ref(), &identity_keypair)
+                            .unwrap();
+                        assert_eq!(
+                            Tower::restore(tower_storage.as_ref(), &node_pubkey).unwrap(),
+                            tower
+                        );
+                    }
+                    node_pubkey
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            let node_pubkey = handle.join().unwrap();
+            let tower = Tower::restore(tower_storage.as_ref(), &node_pubkey).unwrap();
+            assert_eq!(tower.vote_state.root_slot, Some(99));
+        }
+    }
+
+    #[test]
+    fn test_memory_tower_storage_failure_injection() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = MemoryTowerStorage::new();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+
+        tower_storage.fail_next_store();
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+        assert!(Tower::restore(&tower_storage, &node_pubkey)
+            .unwrap_err()
+            .is_file_missing());
+
+        // Only the next call fails
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        tower_storage.fail_next_load();
+        let err = Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        assert!(!err.is_file_missing());
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        // Towers stored for another identity fail signature verification
+        let other_keypair = Keypair::new();
+        let mut saved_tower = SavedTower::new(&tower, &identity_keypair).unwrap();
+        saved_tower.node_pubkey = other_keypair.pubkey();
+        tower_storage
+            .store(&SavedTowerVersions::from(saved_tower))
+            .unwrap();
+        assert_matches!(
+            Tower::restore(&tower_storage, &other_keypair.pubkey()),
+            Err(TowerError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_tower_migration() {
+        let tower_path = TempDir::new().unwrap();
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let mut vote_state = VoteState::default();
+        vote_state
+            .votes
+            .resize(MAX_LOCKOUT_HISTORY, LandedVote::default());
+        vote_state.root_slot = Some(1);
+
+        let vote = Vote::new(vec![1, 2, 3, 4], Hash::default());
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+
+        let old_tower = Tower1_7_14 {
+            node_pubkey,
+            threshold_depth: 10,
+            threshold_size: 0.9,
+            vote_state: VoteState1_14_11::from(vote_state),
+            last_vote: vote.clone(),
+            last_timestamp: BlockTimestamp::default(),
+            last_vote_tx_blockhash: BlockhashStatus::Uninitialized,
+            stray_restored_slot: Some(2),
+            last_switch_threshold_check: Option::default(),
+        };
+
+        {
+            let saved_tower = SavedTower1_7_14::new(&old_tower, &identity_keypair).unwrap();
+            tower_storage.store_old(&saved_tower).unwrap();
         }
 
         let loaded = Tower::restore(&tower_storage, &node_pubkey).unwrap();
@@ -422,4 +1881,588 @@ pub mod test {
         assert_eq!(loaded.vote_state.root_slot, Some(1));
         assert_eq!(loaded.stray_restored_slot(), None);
     }
+
+    fn new_tower1_7_14_for_tests(node_pubkey: &Pubkey) -> Tower1_7_14 {
+        let mut vote_state = VoteState::default();
+        vote_state
+            .votes
+            .resize(MAX_LOCKOUT_HISTORY, LandedVote::default());
+        vote_state.root_slot = Some(1);
+        Tower1_7_14 {
+            node_pubkey: *node_pubkey,
+            threshold_depth: 10,
+            threshold_size: 0.9,
+            vote_state: VoteState1_14_11::from(vote_state),
+            last_vote: Vote::new(vec![1, 2, 3, 4], Hash::default()),
+            last_timestamp: BlockTimestamp::default(),
+            last_vote_tx_blockhash: BlockhashStatus::Uninitialized,
+            stray_restored_slot: Some(2),
+            last_switch_threshold_check: Option::default(),
+        }
+    }
+
+    #[test]
+    fn test_migrate_legacy_tower() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let source = FileTowerStorage::new(tower_path.path().to_path_buf());
+        let dest = MemoryTowerStorage::default();
+
+        let old_tower = new_tower1_7_14_for_tests(&node_pubkey);
+        source
+            .store_old(&SavedTower1_7_14::new(&old_tower, &identity_keypair).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            migrate(&source, &dest, &node_pubkey, &identity_keypair).unwrap(),
+            MigrationReport {
+                source_format: SavedTowerFormat::V1_7_14,
+                last_vote_slot: Some(4),
+                verified: true,
+            }
+        );
+        let SavedTowerVersions::V2(saved_tower) = dest.load_saved_tower(&node_pubkey).unwrap()
+        else {
+            panic!("expected a SavedTower2");
+        };
+        assert_eq!(saved_tower.root_slot(), Some(1));
+        assert_eq!(saved_tower.last_vote_slot(), Some(4));
+    }
+
+    #[test]
+    fn test_migrate_signature_mismatch() {
+        let identity_keypair = Keypair::new();
+        let other_keypair = Keypair::new();
+        let node_pubkey = other_keypair.pubkey();
+        let source = MemoryTowerStorage::default();
+        let dest = MemoryTowerStorage::default();
+
+        // A tower saved under `node_pubkey` but signed by another key
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let mut saved_tower = SavedTower::new(&tower, &identity_keypair).unwrap();
+        saved_tower.node_pubkey = node_pubkey;
+        source
+            .store(&SavedTowerVersions::from(saved_tower))
+            .unwrap();
+
+        assert_matches!(
+            migrate(&source, &dest, &node_pubkey, &other_keypair),
+            Err(TowerError::InvalidSignature)
+        );
+        assert!(!dest.exists(&node_pubkey).unwrap());
+
+        // Nor is a tower re-signed with a keypair for another validator
+        assert_matches!(
+            migrate(&source, &dest, &node_pubkey, &identity_keypair),
+            Err(TowerError::WrongTower { .. })
+        );
+        assert!(!dest.exists(&node_pubkey).unwrap());
+    }
+
+    #[test]
+    fn test_saved_tower_summary() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.record_vote(5, Hash::default());
+        let saved_tower = SavedTower::new(&tower, &identity_keypair).unwrap();
+        let expected = SavedTowerSummary {
+            last_vote_slot: Some(5),
+            root_slot: Some(1),
+            vote_count: tower.vote_state.votes.len(),
+            serialized_size: saved_tower.data.len(),
+        };
+        assert_eq!(saved_tower.summary().unwrap(), expected);
+        assert_eq!(
+            SavedTowerVersions::from(SavedTower2::new(&tower, &identity_keypair).unwrap())
+                .summary()
+                .unwrap(),
+            expected
+        );
+
+        let old_tower = new_tower1_7_14_for_tests(&node_pubkey);
+        let saved_tower = SavedTower1_7_14::new(&old_tower, &identity_keypair).unwrap();
+        assert_eq!(
+            SavedTowerVersions::from(saved_tower.clone())
+                .summary()
+                .unwrap(),
+            SavedTowerSummary {
+                last_vote_slot: Some(4),
+                root_slot: Some(1),
+                vote_count: MAX_LOCKOUT_HISTORY,
+                serialized_size: saved_tower.data.len(),
+            }
+        );
+
+        // The signature isn't checked
+        let mut forged_tower = saved_tower.clone();
+        forged_tower.signature = Signature::default();
+        assert_eq!(
+            forged_tower.summary().unwrap(),
+            saved_tower.summary().unwrap()
+        );
+
+        // but the data must deserialize
+        let saved_tower =
+            SavedTowerVersions::from(SavedTower::new(&tower, &identity_keypair).unwrap());
+        for len in [0, 10, saved_tower.data().len() - 1] {
+            let corrupt_tower = saved_tower
+                .try_map_data(|data| Ok(data[..len].to_vec()))
+                .unwrap();
+            assert_matches!(corrupt_tower.summary(), Err(TowerError::SerializeError(_)));
+        }
+    }
+
+    #[test]
+    fn test_tower1_7_14_migration_round_trip() {
+        let tower_path = TempDir::new().unwrap();
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+
+        let mut old_tower = new_tower1_7_14_for_tests(&node_pubkey);
+        old_tower.last_timestamp = BlockTimestamp {
+            slot: 4,
+            timestamp: 1_700_000_000,
+        };
+        old_tower.last_vote_tx_blockhash = BlockhashStatus::Blockhash(Hash::new_unique());
+        let saved_tower = SavedTower1_7_14::new(&old_tower, &identity_keypair).unwrap();
+        tower_storage.store_old(&saved_tower).unwrap();
+
+        let (tower, report) = tower_storage
+            .load_saved_tower(&node_pubkey)
+            .unwrap()
+            .try_into_tower_with_report(&node_pubkey)
+            .unwrap();
+        assert_eq!(report, ConversionReport::SAVED_TOWER);
+        assert!(report.preserved.contains(&"last_timestamp"));
+        assert_eq!(tower.last_timestamp, old_tower.last_timestamp);
+        // Never persisted, so the first vote after restart fetches a fresh one
+        assert!(report.defaulted.contains(&"last_vote_tx_blockhash"));
+        assert_eq!(tower.last_vote_tx_blockhash, BlockhashStatus::Uninitialized);
+
+        let loaded = Tower::restore(&tower_storage, &node_pubkey).unwrap();
+        assert_eq!(loaded, tower);
+        assert_eq!(
+            loaded.last_vote(),
+            VoteTransaction::from(old_tower.last_vote)
+        );
+
+        // and survives being saved in the current format
+        loaded.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey)
+                .unwrap()
+                .last_timestamp,
+            old_tower.last_timestamp
+        );
+    }
+
+    #[test]
+    fn test_detect_and_deserialize() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let saved_tower2 =
+            SavedTowerVersions::from(SavedTower2::new(&tower, &identity_keypair).unwrap());
+        let saved_tower =
+            SavedTowerVersions::from(SavedTower::new(&tower, &identity_keypair).unwrap());
+        let saved_tower1_7_14 =
+            SavedTower1_7_14::new(&new_tower1_7_14_for_tests(&node_pubkey), &identity_keypair)
+                .unwrap();
+
+        for (data, format) in [
+            (saved_tower2.serialize().unwrap(), SavedTowerFormat::V2),
+            (saved_tower.serialize().unwrap(), SavedTowerFormat::V1_14_11),
+            // Unframed, as older releases wrote them
+            (
+                bincode::serialize(&saved_tower).unwrap(),
+                SavedTowerFormat::V1_14_11,
+            ),
+            (
+                bincode::serialize(&SavedTowerVersions::from(saved_tower1_7_14.clone())).unwrap(),
+                SavedTowerFormat::V1_7_14,
+            ),
+            // Bare, as 1.7.14 wrote them
+            (
+                bincode::serialize(&saved_tower1_7_14).unwrap(),
+                SavedTowerFormat::V1_7_14,
+            ),
+        ] {
+            let detected = detect_and_deserialize(&data, &node_pubkey).unwrap();
+            assert_eq!(SavedTowerFormat::from(&detected), format);
+            assert!(detected.try_into_tower(&node_pubkey).is_ok());
+        }
+
+        // A bare tower signed by another validator is still recognized, so
+        // loading it fails on the signature rather than deserialization
+        let other_keypair = Keypair::new();
+        let other_tower = SavedTower1_7_14::new(
+            &new_tower1_7_14_for_tests(&other_keypair.pubkey()),
+            &other_keypair,
+        )
+        .unwrap();
+        let detected =
+            detect_and_deserialize(&bincode::serialize(&other_tower).unwrap(), &node_pubkey)
+                .unwrap();
+        assert_eq!(SavedTowerFormat::from(&detected), SavedTowerFormat::V1_7_14);
+        assert_matches!(
+            detected.try_into_tower(&node_pubkey),
+            Err(TowerError::InvalidSignature)
+        );
+
+        assert_matches!(
+            detect_and_deserialize(&[0xab; 100], &node_pubkey),
+            Err(TowerError::SerializeError(_))
+        );
+        // A damaged framed tower isn't mistaken for a bare one
+        let mut data = saved_tower2.serialize().unwrap();
+        *data.last_mut().unwrap() ^= 0xff;
+        assert_matches!(
+            detect_and_deserialize(&data, &node_pubkey),
+            Err(TowerError::CorruptTower(_))
+        );
+
+        // Whichever file name the tower was saved under
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        fs::write(
+            tower_storage.filename(&node_pubkey),
+            bincode::serialize(&saved_tower1_7_14).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey)
+                .unwrap()
+                .last_voted_slot(),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn test_inspect_tower() {
+        let tower_path = TempDir::new().unwrap();
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+
+        let mut old_tower = new_tower1_7_14_for_tests(&node_pubkey);
+        old_tower.last_timestamp = BlockTimestamp {
+            slot: 4,
+            timestamp: 1_700_000_000,
+        };
+        tower_storage
+            .store_old(&SavedTower1_7_14::new(&old_tower, &identity_keypair).unwrap())
+            .unwrap();
+        // Inspection doesn't need the keypair
+        let tower_storage = ReadOnlyTowerStorage::new(tower_storage);
+        let inspection = inspect_tower(&tower_storage, &node_pubkey).unwrap();
+        assert_eq!(inspection.node_pubkey, node_pubkey);
+        assert_eq!(inspection.format, SavedTowerFormat::V1_7_14);
+        assert!(inspection.unverified);
+        assert_eq!(inspection.votes.len(), MAX_LOCKOUT_HISTORY);
+        assert_eq!(inspection.root_slot, Some(1));
+        assert_eq!(inspection.last_vote_slot, Some(4));
+        assert_eq!(inspection.last_timestamp, old_tower.last_timestamp);
+
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.record_vote(5, Hash::default());
+        tower.record_vote(6, Hash::default());
+        tower.last_timestamp = BlockTimestamp {
+            slot: 6,
+            timestamp: 1_700_000_001,
+        };
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(
+            inspect_tower(&tower_storage, &node_pubkey).unwrap(),
+            TowerInspection {
+                node_pubkey,
+                format: SavedTowerFormat::V2,
+                unverified: true,
+                votes: vec![
+                    Lockout::new_with_confirmation_count(5, 2),
+                    Lockout::new_with_confirmation_count(6, 1),
+                ],
+                root_slot: Some(1),
+                last_vote_slot: Some(6),
+                last_timestamp: tower.last_timestamp,
+            }
+        );
+
+        assert_matches!(
+            inspect_tower(&tower_storage, &Pubkey::new_unique()),
+            Err(TowerError::TowerNotFound(_))
+        );
+    }
+
+    fn storage_uri_error(uri: &str) -> String {
+        match parse_storage_uri(uri, StorageOptions::default()) {
+            Err(TowerError::InvalidStorageUri(reason)) => reason,
+            Err(err) => panic!("unexpected error for {uri}: {err}"),
+            Ok(_) => panic!("{uri} parsed"),
+        }
+    }
+
+    fn test_etcd_tls_config() -> EtcdTlsConfig {
+        EtcdTlsConfig {
+            domain_name: "etcd".to_string(),
+            ca_certificate: vec![1],
+            identity_certificate: vec![2],
+            identity_private_key: vec![3],
+        }
+    }
+
+    #[test]
+    fn test_from_uri_file() {
+        let tower_dir = TempDir::new().unwrap();
+        let tower_path = tower_dir.path().join("my towers");
+        fs::create_dir(&tower_path).unwrap();
+        let uri = format!(
+            "file://{}?durability=full&backups=2&lock=true",
+            tower_path.display().to_string().replace(' ', "%20")
+        );
+        assert!(matches!(
+            parse_storage_uri(&uri, StorageOptions::default()),
+            Ok(StorageUri::File {
+                tower_path: path,
+                durability: DurabilityLevel::Full,
+                backup_count: 2,
+                instance_lock: true,
+            }) if path == tower_path
+        ));
+        assert!(matches!(
+            parse_storage_uri("file://localhost/tmp", StorageOptions::default()),
+            Ok(StorageUri::File {
+                tower_path,
+                durability: DurabilityLevel::None,
+                backup_count: 0,
+                instance_lock: false,
+            }) if tower_path == Path::new("/tmp")
+        ));
+
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let tower_storage = from_uri(&uri, StorageOptions::default()).unwrap();
+        tower
+            .save(tower_storage.as_ref(), &identity_keypair)
+            .unwrap();
+        assert_eq!(
+            Tower::restore(tower_storage.as_ref(), &node_pubkey).unwrap(),
+            tower
+        );
+        assert!(FileTowerStorage::new(tower_path)
+            .filename(&node_pubkey)
+            .exists());
+
+        assert_eq!(
+            storage_uri_error("file://host/tmp"),
+            "expected file:///path"
+        );
+        assert_eq!(storage_uri_error("file:tmp"), "expected file:///path");
+        assert_eq!(
+            storage_uri_error("file:///tmp?durability=most"),
+            "parameter durability: invalid value \"most\""
+        );
+        assert_eq!(
+            storage_uri_error("file:///tmp/%zz"),
+            "invalid percent-encoding in \"/tmp/%zz\""
+        );
+        assert_eq!(
+            storage_uri_error("file:///tmp/%2"),
+            "invalid percent-encoding in \"/tmp/%2\""
+        );
+    }
+
+    #[test]
+    fn test_from_uri_null_and_memory() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+
+        let tower_storage = from_uri("null:", StorageOptions::default()).unwrap();
+        tower
+            .save(tower_storage.as_ref(), &identity_keypair)
+            .unwrap();
+        assert_matches!(
+            Tower::restore(tower_storage.as_ref(), &node_pubkey),
+            Err(TowerError::TowerNotFound(_))
+        );
+        let tower_storage = from_uri(
+            "null:",
+            StorageOptions {
+                default_tower: Some(node_pubkey),
+                ..StorageOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            Tower::restore(tower_storage.as_ref(), &node_pubkey).unwrap(),
+            Tower::default_for(node_pubkey)
+        );
+
+        let tower_storage = from_uri("memory:", StorageOptions::default()).unwrap();
+        tower
+            .save(tower_storage.as_ref(), &identity_keypair)
+            .unwrap();
+        assert_eq!(
+            Tower::restore(tower_storage.as_ref(), &node_pubkey).unwrap(),
+            tower
+        );
+
+        assert!(matches!(
+            parse_storage_uri("NULL://", StorageOptions::default()),
+            Ok(StorageUri::Null)
+        ));
+        assert_eq!(storage_uri_error("memory:towers"), "expected memory:");
+        assert_eq!(
+            storage_uri_error("null:?durability=full"),
+            "unknown parameter durability"
+        );
+    }
+
+    #[test]
+    fn test_from_uri_etcd() {
+        assert!(matches!(
+            parse_storage_uri(
+                "etcd://host1:2379,10.0.0.2:2379,[::1]:2379,host4?max_retries=5&lease_ttl_ms=2000",
+                StorageOptions::default(),
+            ),
+            Ok(StorageUri::Etcd {
+                config,
+                lease_ttl,
+                force: false,
+            }) if config.endpoints == ["host1:2379", "10.0.0.2:2379", "[::1]:2379", "host4"]
+                && config.tls.is_none()
+                && config.max_retries == 5
+                && config.operation_timeout == Duration::from_secs(1)
+                && lease_ttl == Duration::from_secs(2)
+        ));
+
+        // TLS takes its certificates from the extra options
+        assert_eq!(
+            storage_uri_error("etcd://host1:2379?tls=true"),
+            "parameter tls: no etcd TLS configuration given"
+        );
+        let extra = StorageOptions {
+            etcd_tls: Some(test_etcd_tls_config()),
+            ..StorageOptions::default()
+        };
+        assert!(matches!(
+            parse_storage_uri("etcd://host1:2379?tls=true&force=true", extra),
+            Ok(StorageUri::Etcd {
+                config,
+                force: true,
+                ..
+            }) if config.tls.as_ref().map(|tls| tls.domain_name.as_str()) == Some("etcd")
+        ));
+
+        // Nothing is connected to until the storage is used
+        assert!(from_uri("etcd://127.0.0.1:1", StorageOptions::default()).is_ok());
+
+        assert_eq!(storage_uri_error("etcd://"), "missing endpoints");
+        assert_eq!(storage_uri_error("etcd:"), "missing endpoints");
+        assert_eq!(
+            storage_uri_error("etcd://host1:2379,,host2:2379"),
+            "invalid endpoint \"\""
+        );
+        assert_eq!(
+            storage_uri_error("etcd://host1:2379,:2379"),
+            "invalid endpoint \":2379\""
+        );
+        assert_eq!(
+            storage_uri_error("etcd://host1:etcd"),
+            "invalid port in endpoint \"host1:etcd\""
+        );
+        assert_eq!(
+            storage_uri_error("etcd://host1:99999"),
+            "invalid port in endpoint \"host1:99999\""
+        );
+        assert_eq!(
+            storage_uri_error("etcd://user@host1:2379"),
+            "invalid endpoint \"user@host1:2379\""
+        );
+        assert_eq!(
+            storage_uri_error("etcd://host1:2379/towers"),
+            "etcd URIs take no path"
+        );
+        assert_eq!(
+            storage_uri_error("etcd://host1:2379?max_retries=-1"),
+            "parameter max_retries: invalid value \"-1\""
+        );
+        assert_eq!(
+            storage_uri_error("etcd://host1:2379?tls"),
+            "parameter tls has no value"
+        );
+        assert_eq!(
+            storage_uri_error("etcd://host1:2379?force=true&force=false"),
+            "duplicate parameter force"
+        );
+        assert_eq!(
+            storage_uri_error("etcd://host1:2379?timeout=1"),
+            "unknown parameter timeout"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zk-tower-storage")]
+    fn test_from_uri_zookeeper() {
+        assert!(matches!(
+            parse_storage_uri(
+                "zk://zk1:2181,zk2:2181/solana/my%20towers?auth_digest=user%3Apass%26word&force=true",
+                StorageOptions::default(),
+            ),
+            Ok(StorageUri::ZooKeeper {
+                config,
+                force: true,
+            }) if config.hosts == ["zk1:2181", "zk2:2181"]
+                && config.chroot.as_deref() == Some("/solana/my towers")
+                && config.auth_digest.as_deref() == Some("user:pass&word")
+                && config.session_timeout == zk::DEFAULT_ZK_SESSION_TIMEOUT
+        ));
+        assert!(matches!(
+            parse_storage_uri("zk://zk1:2181/", StorageOptions::default()),
+            Ok(StorageUri::ZooKeeper { config, .. }) if config.chroot.is_none()
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "postgres-tower-storage")]
+    fn test_from_uri_postgres() {
+        // Parameters not for the storage are left for Postgres
+        assert!(matches!(
+            parse_storage_uri(
+                "postgres://solana@db/validator?pool_size=4&connect_timeout=10&max_retries=3",
+                StorageOptions::default(),
+            ),
+            Ok(StorageUri::Postgres {
+                config,
+                force: false,
+            }) if config.dsn == "postgres://solana@db/validator?connect_timeout=10"
+                && config.pool_size == 4
+                && config.max_retries == 3
+                && config.tls.is_none()
+        ));
+        assert!(matches!(
+            parse_storage_uri("postgresql://db", StorageOptions::default()),
+            Ok(StorageUri::Postgres { config, .. }) if config.dsn == "postgresql://db"
+        ));
+        assert_eq!(
+            storage_uri_error("postgres://db?tls=true"),
+            "parameter tls: no Postgres TLS configuration given"
+        );
+    }
+
+    #[test]
+    fn test_from_uri_unknown_scheme() {
+        assert_eq!(storage_uri_error("s3://bucket/towers"), "unknown scheme s3");
+        assert_eq!(storage_uri_error("/var/towers"), "missing scheme");
+        assert_matches!(
+            from_uri("tower:", StorageOptions::default()).err(),
+            Some(err) if err.to_string() == "E016: Invalid tower storage URI: unknown scheme tower"
+        );
+    }
 }
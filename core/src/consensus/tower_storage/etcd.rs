@@ -0,0 +1,1810 @@
+use {
+    super::{
+        corrupt_tower_at, detect_and_deserialize, execute_with_retries, run_preflight,
+        AsyncTowerStorage, AttemptError, PreflightReport, SavedTowerFormat, SavedTowerVersions,
+        TowerStorage, PREFLIGHT_SENTINEL,
+    },
+    crate::consensus::{Result, Tower, TowerError},
+    async_trait::async_trait,
+    solana_pubkey::Pubkey,
+    std::{
+        collections::HashMap,
+        future::Future,
+        io,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex, RwLock,
+        },
+        time::{Duration, Instant, SystemTime},
+    },
+};
+
+// How long an instance lock outlives its holder if the holder stops refreshing it
+pub(super) const DEFAULT_ETCD_LEASE_TTL: Duration = Duration::from_secs(10);
+const INITIAL_ETCD_RECONNECT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// The etcd operations `EtcdTowerStorage` depends on
+#[async_trait]
+trait EtcdClient: Send + Sync {
+    /// Grants a lease expiring `ttl` seconds after its last keepalive
+    async fn lease_grant(&self, ttl: i64) -> Result<i64>;
+
+    /// Refreshes `lease`, returning its remaining TTL, or 0 if it has expired
+    async fn lease_keep_alive(&self, lease: i64) -> Result<i64>;
+
+    async fn lease_revoke(&self, lease: i64) -> Result<()>;
+
+    async fn get(&self, key: String) -> Result<Option<Vec<u8>>>;
+
+    /// Puts `instance_id` at `instance_key`, attached to `lease`. Unless `force`
+    /// is set, returns false without writing if `instance_key` is held by
+    /// another instance.
+    async fn put_instance(
+        &self,
+        instance_key: String,
+        instance_id: Vec<u8>,
+        lease: i64,
+        force: bool,
+    ) -> Result<bool>;
+
+    /// Gets each of `keys` if `instance_key` holds `instance_id`. The returned
+    /// bool is false if it doesn't.
+    async fn get_if_instance(
+        &self,
+        instance_key: String,
+        instance_id: Vec<u8>,
+        keys: Vec<String>,
+    ) -> Result<(bool, Vec<Option<Vec<u8>>>)>;
+
+    /// Puts each of `kvs` in one transaction if `instance_key` holds
+    /// `instance_id`, returning the revision of the put, or `None` if it
+    /// doesn't.
+    async fn put_if_instance(
+        &self,
+        instance_key: String,
+        instance_id: Vec<u8>,
+        kvs: Vec<(String, Vec<u8>)>,
+    ) -> Result<Option<i64>>;
+
+    /// Deletes `key` if `instance_key` holds `instance_id`, returning false if
+    /// it doesn't.
+    async fn delete_if_instance(
+        &self,
+        instance_key: String,
+        instance_id: Vec<u8>,
+        key: String,
+    ) -> Result<bool>;
+}
+
+#[async_trait]
+impl EtcdClient for tokio::sync::Mutex<etcd_client::Client> {
+    async fn lease_grant(&self, ttl: i64) -> Result<i64> {
+        let response = self
+            .lock()
+            .await
+            .lease_grant(ttl, None)
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        Ok(response.id())
+    }
+
+    async fn lease_keep_alive(&self, lease: i64) -> Result<i64> {
+        let (mut keeper, mut stream) = self
+            .lock()
+            .await
+            .lease_keep_alive(lease)
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        keeper
+            .keep_alive()
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        let response = stream
+            .message()
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        Ok(response.map(|response| response.ttl()).unwrap_or_default())
+    }
+
+    async fn lease_revoke(&self, lease: i64) -> Result<()> {
+        self.lock()
+            .await
+            .lease_revoke(lease)
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        Ok(())
+    }
+
+    async fn get(&self, key: String) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .lock()
+            .await
+            .get(key, None)
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        Ok(response.kvs().first().map(|kv| kv.value().to_vec()))
+    }
+
+    async fn put_instance(
+        &self,
+        instance_key: String,
+        instance_id: Vec<u8>,
+        lease: i64,
+        force: bool,
+    ) -> Result<bool> {
+        let put = etcd_client::TxnOp::put(
+            instance_key.clone(),
+            instance_id.clone(),
+            Some(etcd_client::PutOptions::new().with_lease(lease)),
+        );
+        let txn = if force {
+            etcd_client::Txn::new().and_then(vec![put])
+        } else {
+            // Put if the key doesn't exist, or else if this instance already holds it
+            etcd_client::Txn::new()
+                .when(vec![etcd_client::Compare::create_revision(
+                    instance_key.clone(),
+                    etcd_client::CompareOp::Equal,
+                    0,
+                )])
+                .and_then(vec![put.clone()])
+                .or_else(vec![etcd_client::TxnOp::txn(
+                    etcd_client::Txn::new()
+                        .when(vec![etcd_client::Compare::value(
+                            instance_key,
+                            etcd_client::CompareOp::Equal,
+                            instance_id,
+                        )])
+                        .and_then(vec![put]),
+                )])
+        };
+        let response = self
+            .lock()
+            .await
+            .txn(txn)
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        Ok(response.succeeded()
+            || response
+                .op_responses()
+                .into_iter()
+                .any(|op_response| match op_response {
+                    etcd_client::TxnOpResponse::Txn(txn_response) => txn_response.succeeded(),
+                    _ => false,
+                }))
+    }
+
+    async fn get_if_instance(
+        &self,
+        instance_key: String,
+        instance_id: Vec<u8>,
+        keys: Vec<String>,
+    ) -> Result<(bool, Vec<Option<Vec<u8>>>)> {
+        let txn = etcd_client::Txn::new()
+            .when(vec![etcd_client::Compare::value(
+                instance_key,
+                etcd_client::CompareOp::Equal,
+                instance_id,
+            )])
+            .and_then(
+                keys.into_iter()
+                    .map(|key| etcd_client::TxnOp::get(key, None))
+                    .collect::<Vec<_>>(),
+            );
+        let response = self
+            .lock()
+            .await
+            .txn(txn)
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+
+        let values = response
+            .op_responses()
+            .into_iter()
+            .filter_map(|op_response| match op_response {
+                etcd_client::TxnOpResponse::Get(get_response) => {
+                    Some(get_response.kvs().first().map(|kv| kv.value().to_vec()))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok((response.succeeded(), values))
+    }
+
+    async fn put_if_instance(
+        &self,
+        instance_key: String,
+        instance_id: Vec<u8>,
+        kvs: Vec<(String, Vec<u8>)>,
+    ) -> Result<Option<i64>> {
+        let txn = etcd_client::Txn::new()
+            .when(vec![etcd_client::Compare::value(
+                instance_key,
+                etcd_client::CompareOp::Equal,
+                instance_id,
+            )])
+            .and_then(
+                kvs.into_iter()
+                    .map(|(key, value)| etcd_client::TxnOp::put(key, value, None))
+                    .collect::<Vec<_>>(),
+            );
+        let response = self
+            .lock()
+            .await
+            .txn(txn)
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        if !response.succeeded() {
+            return Ok(None);
+        }
+        Ok(Some(
+            response
+                .header()
+                .map(|header| header.revision())
+                .unwrap_or_default(),
+        ))
+    }
+
+    async fn delete_if_instance(
+        &self,
+        instance_key: String,
+        instance_id: Vec<u8>,
+        key: String,
+    ) -> Result<bool> {
+        let txn = etcd_client::Txn::new()
+            .when(vec![etcd_client::Compare::value(
+                instance_key,
+                etcd_client::CompareOp::Equal,
+                instance_id,
+            )])
+            .and_then(vec![etcd_client::TxnOp::delete(key, None)]);
+        let response = self
+            .lock()
+            .await
+            .txn(txn)
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        Ok(response.succeeded())
+    }
+}
+
+/// An etcd instance key held under a lease, kept alive in the background
+struct EtcdInstanceLock {
+    lease: i64,
+    keepalive: tokio::task::JoinHandle<()>,
+    // Set by the keepalive task once the lease can no longer be kept alive
+    lost: Arc<Mutex<Option<String>>>,
+}
+
+impl EtcdInstanceLock {
+    fn lost(&self) -> Option<String> {
+        self.lost.lock().unwrap().clone()
+    }
+}
+
+/// Connects an `EtcdClient` to an etcd endpoint
+#[async_trait]
+trait EtcdConnector: Send + Sync {
+    async fn connect(&self, endpoint: &str) -> Result<Arc<dyn EtcdClient>>;
+}
+
+struct EtcdClientConnector {
+    connect_options: Option<etcd_client::ConnectOptions>,
+}
+
+#[async_trait]
+impl EtcdConnector for EtcdClientConnector {
+    async fn connect(&self, endpoint: &str) -> Result<Arc<dyn EtcdClient>> {
+        let client = etcd_client::Client::connect([endpoint], self.connect_options.clone())
+            .await
+            .map_err(EtcdTowerStorage::etdc_to_tower_error)?;
+        Ok(Arc::new(tokio::sync::Mutex::new(client)))
+    }
+}
+
+struct EtcdEndpoint {
+    name: String,
+    // None while reconnecting
+    client: RwLock<Option<Arc<dyn EtcdClient>>>,
+}
+
+/// Clients for each etcd endpoint, failing over between them and reconnecting
+/// them in the background
+struct EtcdEndpoints {
+    endpoints: Vec<EtcdEndpoint>,
+    // Index into `endpoints` of the endpoint operations are sent to first
+    current: AtomicUsize,
+    connector: Box<dyn EtcdConnector>,
+    operation_timeout: Duration,
+    max_retries: usize,
+    max_reconnect_backoff: Duration,
+    runtime: tokio::runtime::Handle,
+}
+
+impl EtcdEndpoints {
+    /// Runs `operation`, timing out after `operation_timeout`. On a timeout or
+    /// error it's retried up to `max_retries` times, each against the next
+    /// endpoint. `operation` is passed the client and whether this is a retry.
+    async fn execute<T, F, Fut>(self: &Arc<Self>, mut operation: F) -> Result<T>
+    where
+        F: FnMut(Arc<dyn EtcdClient>, bool) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        // Index of the endpoint of the latest attempt
+        let attempt_index = AtomicUsize::default();
+        execute_with_retries(
+            self.operation_timeout,
+            self.max_retries,
+            |retry| {
+                let index = self.current.load(Ordering::Relaxed);
+                attempt_index.store(index, Ordering::Relaxed);
+                let client = self.endpoints[index].client.read().unwrap().clone();
+                let attempt = client.map(|client| operation(client, retry));
+                async move {
+                    match attempt {
+                        Some(attempt) => attempt.await,
+                        None => Err(TowerError::IoError(io::Error::other("reconnecting"))),
+                    }
+                }
+            },
+            |err, retry| {
+                let index = attempt_index.load(Ordering::Relaxed);
+                let endpoint = &self.endpoints[index];
+                match err {
+                    AttemptError::Failed(err) if !Self::is_transport_error(err) => (),
+                    _ => self.reconnect(index),
+                }
+                if retry {
+                    let next = (index + 1) % self.endpoints.len();
+                    warn!(
+                        "etcd endpoint {} failed, retrying with {}: {err}",
+                        endpoint.name, self.endpoints[next].name
+                    );
+                    let _ = self.current.compare_exchange(
+                        index,
+                        next,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    );
+                }
+            },
+        )
+        .await
+        .map_err(|err| {
+            let endpoint = &self.endpoints[attempt_index.load(Ordering::Relaxed)];
+            TowerError::IoError(io::Error::other(format!(
+                "etcd endpoint {}: {err}",
+                endpoint.name
+            )))
+        })
+    }
+
+    fn is_transport_error(err: &TowerError) -> bool {
+        matches!(err, TowerError::IoError(err) if err.kind() == io::ErrorKind::NotConnected)
+    }
+
+    /// Drops the endpoint's client and reconnects it in the background
+    fn reconnect(self: &Arc<Self>, index: usize) {
+        let endpoint = &self.endpoints[index];
+        // Unless already reconnecting
+        if endpoint.client.write().unwrap().take().is_some() {
+            warn!("Reconnecting to etcd endpoint {}", endpoint.name);
+            self.spawn_reconnect(index);
+        }
+    }
+
+    /// Connects the endpoint, with exponential backoff between attempts
+    fn spawn_reconnect(self: &Arc<Self>, index: usize) {
+        let this = self.clone();
+        self.runtime.spawn(async move {
+            let endpoint = &this.endpoints[index];
+            let mut backoff = INITIAL_ETCD_RECONNECT_BACKOFF.min(this.max_reconnect_backoff);
+            loop {
+                tokio::time::sleep(backoff).await;
+                let client = tokio::time::timeout(
+                    this.operation_timeout,
+                    this.connector.connect(&endpoint.name),
+                )
+                .await;
+                match client {
+                    Ok(Ok(client)) => {
+                        info!("Reconnected to etcd endpoint {}", endpoint.name);
+                        *endpoint.client.write().unwrap() = Some(client);
+                        return;
+                    }
+                    Ok(Err(err)) => {
+                        warn!(
+                            "Failed to reconnect to etcd endpoint {}: {err}",
+                            endpoint.name
+                        )
+                    }
+                    Err(_) => warn!("Timed out reconnecting to etcd endpoint {}", endpoint.name),
+                }
+                backoff = backoff.saturating_mul(2).min(this.max_reconnect_backoff);
+            }
+        });
+    }
+
+    fn current_endpoint(&self) -> &EtcdEndpoint {
+        &self.endpoints[self.current.load(Ordering::Relaxed)]
+    }
+}
+
+/// Health of an `EtcdTowerStorage`'s connection to etcd
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EtcdHealth {
+    /// When a tower was last stored successfully
+    pub last_successful_store: Option<SystemTime>,
+    /// Number of stores that have failed since then
+    pub consecutive_failures: usize,
+    /// The endpoint requests are currently sent to
+    pub current_endpoint: String,
+}
+
+/// Outcomes and latencies of one kind of `EtcdTowerStorage` operation
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EtcdOperationMetrics {
+    pub successes: u64,
+    pub failures: u64,
+    pub latency_us_p50: u64,
+    pub latency_us_p99: u64,
+    pub latency_us_max: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EtcdTowerStorageMetrics {
+    pub store: EtcdOperationMetrics,
+    pub load: EtcdOperationMetrics,
+    pub last_error: Option<String>,
+    /// etcd revision of the last successful store
+    pub last_store_revision: Option<i64>,
+}
+
+impl EtcdTowerStorageMetrics {
+    pub fn report(&self) {
+        datapoint_info!(
+            "etcd_tower_storage",
+            ("store_successes", self.store.successes, i64),
+            ("store_failures", self.store.failures, i64),
+            ("store_us_p50", self.store.latency_us_p50, i64),
+            ("store_us_p99", self.store.latency_us_p99, i64),
+            ("store_us_max", self.store.latency_us_max, i64),
+            ("load_successes", self.load.successes, i64),
+            ("load_failures", self.load.failures, i64),
+            ("load_us_p50", self.load.latency_us_p50, i64),
+            ("load_us_p99", self.load.latency_us_p99, i64),
+            ("load_us_max", self.load.latency_us_max, i64),
+            (
+                "last_store_revision",
+                self.last_store_revision.unwrap_or_default(),
+                i64
+            ),
+            (
+                "last_error",
+                self.last_error.clone().unwrap_or_default(),
+                String
+            ),
+        );
+    }
+}
+
+#[derive(Default)]
+struct EtcdOperationStats {
+    successes: u64,
+    failures: u64,
+    latency_us_hist: histogram::Histogram,
+}
+
+impl EtcdOperationStats {
+    fn record<T>(&mut self, elapsed: Duration, result: &Result<T>) {
+        if result.is_ok() {
+            self.successes += 1;
+        } else {
+            self.failures += 1;
+        }
+        let _ = self.latency_us_hist.increment(elapsed.as_micros() as u64);
+    }
+
+    fn metrics(&self) -> EtcdOperationMetrics {
+        EtcdOperationMetrics {
+            successes: self.successes,
+            failures: self.failures,
+            latency_us_p50: self.latency_us_hist.percentile(50.0).unwrap_or(0),
+            latency_us_p99: self.latency_us_hist.percentile(99.0).unwrap_or(0),
+            latency_us_max: self.latency_us_hist.maximum().unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Default)]
+struct EtcdTowerStorageStats {
+    store: EtcdOperationStats,
+    load: EtcdOperationStats,
+    last_error: Option<String>,
+    last_store_revision: Option<i64>,
+}
+
+impl EtcdTowerStorageStats {
+    fn record_error<T>(&mut self, result: &Result<T>) {
+        if let Err(err) = result {
+            self.last_error = Some(err.to_string());
+        }
+    }
+}
+
+pub struct EtcdTowerStorage {
+    endpoints: Arc<EtcdEndpoints>,
+    instance_id: [u8; 8],
+    lease_ttl: Duration,
+    force: bool,
+    migration: bool,
+    instance_locks: tokio::sync::Mutex<HashMap<Pubkey, EtcdInstanceLock>>,
+    last_successful_store: Mutex<Option<SystemTime>>,
+    consecutive_store_failures: AtomicUsize,
+    stats: Mutex<EtcdTowerStorageStats>,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[derive(Clone)]
+pub struct EtcdTlsConfig {
+    pub domain_name: String,
+    pub ca_certificate: Vec<u8>,
+    pub identity_certificate: Vec<u8>,
+    pub identity_private_key: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct EtcdTowerStorageConfig {
+    pub endpoints: Vec<String>,
+    pub tls: Option<EtcdTlsConfig>,
+    /// How long each etcd request may take before it's retried
+    pub operation_timeout: Duration,
+    /// How many times a failed etcd request is retried, each time against the
+    /// next endpoint
+    pub max_retries: usize,
+    /// Upper bound on the backoff between attempts to reconnect to an endpoint
+    pub max_reconnect_backoff: Duration,
+}
+
+impl Default for EtcdTowerStorageConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::default(),
+            tls: None,
+            operation_timeout: Duration::from_secs(1),
+            max_retries: 2,
+            max_reconnect_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl EtcdTowerStorage {
+    pub fn new(config: EtcdTowerStorageConfig) -> Result<Self> {
+        let runtime = Self::new_runtime();
+
+        let connector = EtcdClientConnector {
+            connect_options: config.tls.as_ref().map(|tls_config| {
+                etcd_client::ConnectOptions::default().with_tls(
+                    etcd_client::TlsOptions::new()
+                        .domain_name(&tls_config.domain_name)
+                        .ca_certificate(etcd_client::Certificate::from_pem(
+                            &tls_config.ca_certificate,
+                        ))
+                        .identity(etcd_client::Identity::from_pem(
+                            &tls_config.identity_certificate,
+                            &tls_config.identity_private_key,
+                        )),
+                )
+            }),
+        };
+        let clients: Vec<_> = config
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let client = runtime.block_on(async {
+                    tokio::time::timeout(config.operation_timeout, connector.connect(endpoint))
+                        .await
+                });
+                let client = match client {
+                    Ok(Ok(client)) => Some(client),
+                    Ok(Err(err)) => {
+                        warn!("Failed to connect to etcd endpoint {endpoint}: {err}");
+                        None
+                    }
+                    Err(_) => {
+                        warn!("Timed out connecting to etcd endpoint {endpoint}");
+                        None
+                    }
+                };
+                (endpoint.clone(), client)
+            })
+            .collect();
+        if clients.iter().all(|(_, client)| client.is_none()) {
+            return Err(TowerError::IoError(io::Error::other(
+                "Failed to connect to any etcd endpoint",
+            )));
+        }
+
+        let tower_storage = Self::new_with_clients(clients, Box::new(connector), &config, runtime);
+        // Keep trying the endpoints that couldn't be reached
+        for (index, endpoint) in tower_storage.endpoints.endpoints.iter().enumerate() {
+            if endpoint.client.read().unwrap().is_none() {
+                tower_storage.endpoints.spawn_reconnect(index);
+            }
+        }
+        Ok(tower_storage)
+    }
+
+    fn new_with_clients(
+        clients: Vec<(String, Option<Arc<dyn EtcdClient>>)>,
+        connector: Box<dyn EtcdConnector>,
+        config: &EtcdTowerStorageConfig,
+        runtime: tokio::runtime::Runtime,
+    ) -> Self {
+        Self {
+            endpoints: Arc::new(EtcdEndpoints {
+                endpoints: clients
+                    .into_iter()
+                    .map(|(name, client)| EtcdEndpoint {
+                        name,
+                        client: RwLock::new(client),
+                    })
+                    .collect(),
+                current: AtomicUsize::default(),
+                connector,
+                operation_timeout: config.operation_timeout,
+                max_retries: config.max_retries,
+                max_reconnect_backoff: config.max_reconnect_backoff,
+                runtime: runtime.handle().clone(),
+            }),
+            instance_id: solana_time_utils::timestamp().to_le_bytes(),
+            lease_ttl: DEFAULT_ETCD_LEASE_TTL,
+            force: false,
+            migration: false,
+            instance_locks: tokio::sync::Mutex::default(),
+            last_successful_store: Mutex::default(),
+            consecutive_store_failures: AtomicUsize::default(),
+            stats: Mutex::default(),
+            runtime,
+        }
+    }
+
+    pub(super) fn new_runtime() -> tokio::runtime::Runtime {
+        // Multi-threaded so that lease keepalives run between loads and stores
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap()
+    }
+
+    /// Take over the instance lock even if another instance holds it, rather
+    /// than failing
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// How long the instance lock outlives this instance if it stops keeping
+    /// the lock alive, after which another instance may acquire it
+    pub fn with_lease_ttl(mut self, lease_ttl: Duration) -> Self {
+        self.lease_ttl = lease_ttl;
+        self
+    }
+
+    /// While migrating, also store towers under the legacy key in the unframed
+    /// format older releases read, so that instances can be rolled back
+    pub fn with_migration(mut self, migration: bool) -> Self {
+        self.migration = migration;
+        self
+    }
+
+    fn get_keys(node_pubkey: &Pubkey) -> (String, String) {
+        let instance_key = format!("{node_pubkey}/instance");
+        let tower_key = format!("{node_pubkey}/tower_v2");
+        (instance_key, tower_key)
+    }
+
+    fn get_legacy_tower_key(node_pubkey: &Pubkey) -> String {
+        format!("{node_pubkey}/tower")
+    }
+
+    fn get_preflight_key(node_pubkey: &Pubkey) -> String {
+        format!("{node_pubkey}/tower.preflight")
+    }
+
+    /// Deletes the tower stored under the legacy key, once it's no longer
+    /// needed to roll back. Fails if there's no tower under the current key.
+    pub fn cleanup_legacy(&self, node_pubkey: &Pubkey) -> Result<()> {
+        self.runtime
+            .block_on(self.cleanup_legacy_tower(node_pubkey))
+    }
+
+    async fn cleanup_legacy_tower(&self, node_pubkey: &Pubkey) -> Result<()> {
+        self.lock_instance(node_pubkey).await?;
+
+        let (instance_key, tower_key) = Self::get_keys(node_pubkey);
+        let (succeeded, values) = self
+            .endpoints
+            .execute(|client, _| {
+                let instance_key = instance_key.clone();
+                let instance_id = self.instance_id.to_vec();
+                let tower_key = tower_key.clone();
+                async move {
+                    client
+                        .get_if_instance(instance_key, instance_id, vec![tower_key])
+                        .await
+                }
+            })
+            .await?;
+        if !succeeded {
+            return Err(TowerError::IoError(io::Error::other(format!(
+                "Lost etcd instance lock for {node_pubkey}"
+            ))));
+        }
+        if values.into_iter().flatten().next().is_none() {
+            return Err(TowerError::TowerNotFound(*node_pubkey));
+        }
+
+        let legacy_tower_key = Self::get_legacy_tower_key(node_pubkey);
+        let succeeded = self
+            .endpoints
+            .execute(|client, _| {
+                let instance_key = instance_key.clone();
+                let instance_id = self.instance_id.to_vec();
+                let legacy_tower_key = legacy_tower_key.clone();
+                async move {
+                    client
+                        .delete_if_instance(instance_key, instance_id, legacy_tower_key)
+                        .await
+                }
+            })
+            .await?;
+        if !succeeded {
+            return Err(TowerError::IoError(io::Error::other(format!(
+                "Lost etcd instance lock for {node_pubkey}"
+            ))));
+        }
+        info!("Deleted legacy etcd saved tower for {node_pubkey}");
+        Ok(())
+    }
+
+    async fn store_preflight_sentinel(&self, node_pubkey: &Pubkey) -> Result<()> {
+        self.lock_instance(node_pubkey).await?;
+
+        let (instance_key, _) = Self::get_keys(node_pubkey);
+        let preflight_key = Self::get_preflight_key(node_pubkey);
+        let revision = self
+            .endpoints
+            .execute(|client, _| {
+                let instance_key = instance_key.clone();
+                let instance_id = self.instance_id.to_vec();
+                let kvs = vec![(preflight_key.clone(), PREFLIGHT_SENTINEL.to_vec())];
+                async move { client.put_if_instance(instance_key, instance_id, kvs).await }
+            })
+            .await?;
+        let deleted = revision.is_some()
+            && self
+                .endpoints
+                .execute(|client, _| {
+                    let instance_key = instance_key.clone();
+                    let instance_id = self.instance_id.to_vec();
+                    let preflight_key = preflight_key.clone();
+                    async move {
+                        client
+                            .delete_if_instance(instance_key, instance_id, preflight_key)
+                            .await
+                    }
+                })
+                .await?;
+        if !deleted {
+            return Err(TowerError::IoError(io::Error::other(format!(
+                "Lost etcd instance lock for {node_pubkey}"
+            ))));
+        }
+        Ok(())
+    }
+
+    fn etdc_to_tower_error(error: etcd_client::Error) -> TowerError {
+        // Transport errors are NotConnected so that the client is reconnected
+        let kind = match error {
+            etcd_client::Error::IoError(_) | etcd_client::Error::TransportError(_) => {
+                io::ErrorKind::NotConnected
+            }
+            _ => io::ErrorKind::Other,
+        };
+        TowerError::IoError(io::Error::new(kind, error.to_string()))
+    }
+
+    pub fn health(&self) -> EtcdHealth {
+        EtcdHealth {
+            last_successful_store: *self.last_successful_store.lock().unwrap(),
+            consecutive_failures: self.consecutive_store_failures.load(Ordering::Relaxed),
+            current_endpoint: self.endpoints.current_endpoint().name.clone(),
+        }
+    }
+
+    pub fn metrics(&self) -> EtcdTowerStorageMetrics {
+        let stats = self.stats.lock().unwrap();
+        EtcdTowerStorageMetrics {
+            store: stats.store.metrics(),
+            load: stats.load.metrics(),
+            last_error: stats.last_error.clone(),
+            last_store_revision: stats.last_store_revision,
+        }
+    }
+
+    async fn lock_instance(&self, node_pubkey: &Pubkey) -> Result<()> {
+        let mut instance_locks = self.instance_locks.lock().await;
+        if let Some(lock) = instance_locks.get(node_pubkey) {
+            match lock.lost() {
+                None => return Ok(()),
+                Some(reason) => {
+                    warn!("Reacquiring lost etcd instance lock for {node_pubkey}: {reason}");
+                    instance_locks.remove(node_pubkey);
+                }
+            }
+        }
+
+        let (instance_key, _) = Self::get_keys(node_pubkey);
+        let ttl = self.lease_ttl.as_secs().max(1) as i64;
+        let lease = self
+            .endpoints
+            .execute(|client, _| async move { client.lease_grant(ttl).await })
+            .await
+            .map_err(|err| {
+                error!("Failed to acquire etcd instance lock: {}", err);
+                err
+            })?;
+        // Retrying is safe, `put_instance` succeeds if this instance already
+        // holds the key
+        let acquired = self
+            .endpoints
+            .execute(|client, _| {
+                let instance_key = instance_key.clone();
+                let instance_id = self.instance_id.to_vec();
+                let force = self.force;
+                async move {
+                    client
+                        .put_instance(instance_key, instance_id, lease, force)
+                        .await
+                }
+            })
+            .await;
+        if !matches!(acquired, Ok(true)) {
+            // Don't leave the unused lease around until it expires
+            let _ = self
+                .endpoints
+                .execute(|client, _| async move { client.lease_revoke(lease).await })
+                .await;
+        }
+        let acquired = acquired.map_err(|err| {
+            error!("Failed to acquire etcd instance lock: {}", err);
+            err
+        })?;
+        if !acquired {
+            return Err(TowerError::TowerLocked(format!(
+                "etcd {instance_key} is held by another instance"
+            )));
+        }
+
+        let lost = Arc::<Mutex<Option<String>>>::default();
+        let keepalive = self.runtime.spawn({
+            let endpoints = self.endpoints.clone();
+            let lost = lost.clone();
+            let lease_ttl = self.lease_ttl;
+            async move {
+                let mut last_keepalive = Instant::now();
+                loop {
+                    tokio::time::sleep(lease_ttl / 3).await;
+                    let ttl = endpoints
+                        .execute(|client, _| async move { client.lease_keep_alive(lease).await })
+                        .await;
+                    let reason = match ttl {
+                        Ok(ttl) if ttl > 0 => {
+                            last_keepalive = Instant::now();
+                            continue;
+                        }
+                        Ok(_) => "lease expired".to_string(),
+                        // The lease may outlive a connection outage
+                        Err(err) if last_keepalive.elapsed() < lease_ttl => {
+                            warn!("Failed to keep etcd instance lease {lease} alive: {err}");
+                            continue;
+                        }
+                        Err(err) => err.to_string(),
+                    };
+                    error!("Failed to keep etcd instance lease {lease} alive: {reason}");
+                    *lost.lock().unwrap() = Some(reason);
+                    break;
+                }
+            }
+        });
+        instance_locks.insert(
+            *node_pubkey,
+            EtcdInstanceLock {
+                lease,
+                keepalive,
+                lost,
+            },
+        );
+        Ok(())
+    }
+
+    async fn load_tower(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        let (key, saved_tower) = self.load_saved_from_key(node_pubkey).await?;
+        saved_tower
+            .try_into_tower(node_pubkey)
+            .map_err(corrupt_tower_at(key))
+    }
+
+    async fn load_saved(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.load_saved_from_key(node_pubkey)
+            .await
+            .map(|(_, saved_tower)| saved_tower)
+    }
+
+    /// Returns the saved tower along with the key it was read from
+    async fn load_saved_from_key(
+        &self,
+        node_pubkey: &Pubkey,
+    ) -> Result<(String, SavedTowerVersions)> {
+        self.lock_instance(node_pubkey).await?;
+
+        let (instance_key, tower_key) = Self::get_keys(node_pubkey);
+        let legacy_tower_key = Self::get_legacy_tower_key(node_pubkey);
+        let (succeeded, saved_towers) = self
+            .endpoints
+            .execute(|client, _| {
+                let instance_key = instance_key.clone();
+                let instance_id = self.instance_id.to_vec();
+                let keys = vec![tower_key.clone(), legacy_tower_key.clone()];
+                async move {
+                    client
+                        .get_if_instance(instance_key, instance_id, keys)
+                        .await
+                }
+            })
+            .await
+            .map_err(|err| {
+                error!("Failed to read etcd saved tower: {}", err);
+                err
+            })?;
+
+        if !succeeded {
+            return Err(TowerError::IoError(io::Error::other(format!(
+                "Lost etcd instance lock for {node_pubkey}"
+            ))));
+        }
+
+        // Prefer the current key, falling back to a tower stored by an older release
+        let (key, saved_tower) = [tower_key, legacy_tower_key]
+            .into_iter()
+            .zip(saved_towers)
+            .find_map(|(key, saved_tower)| Some((key, saved_tower?)))
+            .ok_or(TowerError::TowerNotFound(*node_pubkey))?;
+        let saved_tower =
+            detect_and_deserialize(&saved_tower, node_pubkey).map_err(corrupt_tower_at(&key))?;
+        debug!(
+            "Found {:?} tower at {key}",
+            SavedTowerFormat::from(&saved_tower)
+        );
+        Ok((key, saved_tower))
+    }
+
+    async fn store_tower(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let pubkey = saved_tower.pubkey();
+        if let Some(lock) = self.instance_locks.lock().await.get(&pubkey) {
+            if let Some(reason) = lock.lost() {
+                return Err(TowerError::IoError(io::Error::other(format!(
+                    "Lost etcd instance lock for {pubkey}: {reason}"
+                ))));
+            }
+        }
+
+        let (instance_key, tower_key) = Self::get_keys(&pubkey);
+        let mut kvs = vec![(tower_key, saved_tower.serialize()?)];
+        if self.migration {
+            kvs.push((
+                Self::get_legacy_tower_key(&pubkey),
+                bincode::serialize(&saved_tower.to_legacy())?,
+            ));
+        }
+        let revision = self
+            .endpoints
+            .execute(|client, retry| {
+                let instance_key = instance_key.clone();
+                let instance_id = self.instance_id.to_vec();
+                let kvs = kvs.clone();
+                async move {
+                    // The failed attempt may have been applied regardless, so
+                    // check that this instance still holds the lock before
+                    // trying again
+                    if retry && client.get(instance_key.clone()).await? != Some(instance_id.clone())
+                    {
+                        return Ok(None);
+                    }
+                    client.put_if_instance(instance_key, instance_id, kvs).await
+                }
+            })
+            .await
+            .map_err(|err| {
+                error!("Failed to write etcd saved tower: {}", err);
+                err
+            })?;
+
+        let mut stats = self.stats.lock().unwrap();
+        let Some(revision) = revision else {
+            // Compare against compaction or takeover events around this revision
+            let last_store_revision = stats.last_store_revision;
+            error!(
+                "Lost etcd instance lock for {pubkey}, last stored at revision \
+                 {last_store_revision:?}"
+            );
+            return Err(TowerError::IoError(io::Error::other(format!(
+                "Lost etcd instance lock for {pubkey}, last stored at revision \
+                 {last_store_revision:?}"
+            ))));
+        };
+        stats.last_store_revision = Some(revision);
+        Ok(())
+    }
+}
+
+impl Drop for EtcdTowerStorage {
+    fn drop(&mut self) {
+        // Release the instance locks now rather than once their leases expire
+        for (_, lock) in self.instance_locks.get_mut().drain() {
+            lock.keepalive.abort();
+            let client = self
+                .endpoints
+                .current_endpoint()
+                .client
+                .read()
+                .unwrap()
+                .clone();
+            let Some(client) = client else {
+                continue;
+            };
+            let _ = self.runtime.block_on(async {
+                tokio::time::timeout(
+                    self.endpoints.operation_timeout,
+                    client.lease_revoke(lock.lease),
+                )
+                .await
+            });
+        }
+    }
+}
+
+/// Must be polled within a tokio runtime with the time driver enabled
+#[async_trait]
+impl AsyncTowerStorage for EtcdTowerStorage {
+    async fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        let start = Instant::now();
+        let result = self.load_tower(node_pubkey).await;
+        let mut stats = self.stats.lock().unwrap();
+        stats.load.record(start.elapsed(), &result);
+        stats.record_error(&result);
+        result
+    }
+
+    async fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let start = Instant::now();
+        let result = self.store_tower(saved_tower).await;
+        let mut stats = self.stats.lock().unwrap();
+        stats.store.record(start.elapsed(), &result);
+        stats.record_error(&result);
+        if result.is_ok() {
+            *self.last_successful_store.lock().unwrap() = Some(SystemTime::now());
+            self.consecutive_store_failures.store(0, Ordering::Relaxed);
+        } else {
+            self.consecutive_store_failures
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+impl TowerStorage for EtcdTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.runtime
+            .block_on(AsyncTowerStorage::load(self, node_pubkey))
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        self.runtime
+            .block_on(AsyncTowerStorage::store(self, saved_tower))
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.runtime.block_on(self.load_saved(node_pubkey))
+    }
+
+    fn store_sentinel(&self, node_pubkey: &Pubkey) -> Result<()> {
+        self.runtime
+            .block_on(self.store_preflight_sentinel(node_pubkey))
+    }
+
+    fn preflight(&self, node_pubkey: &Pubkey) -> Result<PreflightReport> {
+        // Acquire the instance lock up front, so that another instance holding
+        // it fails preflight as such, whether or not a tower is saved
+        self.runtime.block_on(self.lock_instance(node_pubkey))?;
+        run_preflight(self, node_pubkey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::consensus::tower_storage::{
+            migrate,
+            test::{check_saved_tower2_migration, new_tower_for_tests},
+            FileTowerStorage, MigrationReport, SavedTower, SavedTowerWriteFormat,
+            SAVED_TOWER_MAGIC,
+        },
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_signer::Signer,
+        std::{sync::atomic::AtomicBool, thread},
+        tempfile::TempDir,
+    };
+
+    /// In-memory `EtcdClient` whose leases only expire when told to. Clients
+    /// created with `endpoint()`, or by connecting, are other endpoints of the
+    /// same cluster.
+    #[derive(Default)]
+    struct MockEtcdClient {
+        name: &'static str,
+        state: Arc<Mutex<MockEtcdState>>,
+        // (endpoint, operation) of every request, shared between endpoints
+        requests: Arc<Mutex<Vec<(&'static str, &'static str)>>>,
+        delay: Mutex<Duration>,
+        disconnected: AtomicBool,
+        fail_keep_alive: AtomicBool,
+        fail_connect: AtomicBool,
+        connects: AtomicUsize,
+    }
+
+    #[derive(Default)]
+    struct MockEtcdState {
+        // Only advanced by `put_if_instance()`
+        revision: i64,
+        next_lease: i64,
+        // lease -> ttl
+        leases: HashMap<i64, i64>,
+        // key -> (value, lease)
+        kvs: HashMap<String, (Vec<u8>, Option<i64>)>,
+    }
+
+    impl MockEtcdClient {
+        fn endpoint(&self, name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                state: self.state.clone(),
+                requests: self.requests.clone(),
+                ..Self::default()
+            })
+        }
+
+        async fn request(&self, operation: &'static str) -> Result<()> {
+            self.requests.lock().unwrap().push((self.name, operation));
+            let delay = *self.delay.lock().unwrap();
+            tokio::time::sleep(delay).await;
+            if self.disconnected.load(Ordering::Relaxed) {
+                return Err(TowerError::IoError(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "connection dropped",
+                )));
+            }
+            Ok(())
+        }
+
+        fn take_requests(&self) -> Vec<(&'static str, &'static str)> {
+            std::mem::take(&mut *self.requests.lock().unwrap())
+        }
+
+        fn expire_lease(&self, lease: i64) {
+            let mut state = self.state.lock().unwrap();
+            state.leases.remove(&lease);
+            state
+                .kvs
+                .retain(|_, (_, kv_lease)| *kv_lease != Some(lease));
+        }
+    }
+
+    #[async_trait]
+    impl EtcdClient for MockEtcdClient {
+        async fn lease_grant(&self, ttl: i64) -> Result<i64> {
+            self.request("lease_grant").await?;
+            let mut state = self.state.lock().unwrap();
+            state.next_lease += 1;
+            let lease = state.next_lease;
+            state.leases.insert(lease, ttl);
+            Ok(lease)
+        }
+
+        async fn lease_keep_alive(&self, lease: i64) -> Result<i64> {
+            self.request("lease_keep_alive").await?;
+            if self.fail_keep_alive.load(Ordering::Relaxed) {
+                return Err(TowerError::IoError(io::Error::other(
+                    "keepalive failure injected",
+                )));
+            }
+            let state = self.state.lock().unwrap();
+            Ok(state.leases.get(&lease).copied().unwrap_or_default())
+        }
+
+        async fn lease_revoke(&self, lease: i64) -> Result<()> {
+            self.request("lease_revoke").await?;
+            self.expire_lease(lease);
+            Ok(())
+        }
+
+        async fn get(&self, key: String) -> Result<Option<Vec<u8>>> {
+            self.request("get").await?;
+            let state = self.state.lock().unwrap();
+            Ok(state.kvs.get(&key).map(|(value, _)| value.clone()))
+        }
+
+        async fn put_instance(
+            &self,
+            instance_key: String,
+            instance_id: Vec<u8>,
+            lease: i64,
+            force: bool,
+        ) -> Result<bool> {
+            self.request("put_instance").await?;
+            let mut state = self.state.lock().unwrap();
+            assert!(state.leases.contains_key(&lease));
+            if let Some((value, _)) = state.kvs.get(&instance_key) {
+                if !force && *value != instance_id {
+                    return Ok(false);
+                }
+            }
+            state.kvs.insert(instance_key, (instance_id, Some(lease)));
+            Ok(true)
+        }
+
+        async fn get_if_instance(
+            &self,
+            instance_key: String,
+            instance_id: Vec<u8>,
+            keys: Vec<String>,
+        ) -> Result<(bool, Vec<Option<Vec<u8>>>)> {
+            self.request("get_if_instance").await?;
+            let state = self.state.lock().unwrap();
+            if state.kvs.get(&instance_key).map(|(value, _)| value) != Some(&instance_id) {
+                return Ok((false, vec![]));
+            }
+            let values = keys
+                .iter()
+                .map(|key| state.kvs.get(key).map(|(value, _)| value.clone()))
+                .collect();
+            Ok((true, values))
+        }
+
+        async fn put_if_instance(
+            &self,
+            instance_key: String,
+            instance_id: Vec<u8>,
+            kvs: Vec<(String, Vec<u8>)>,
+        ) -> Result<Option<i64>> {
+            self.request("put_if_instance").await?;
+            let mut state = self.state.lock().unwrap();
+            if state.kvs.get(&instance_key).map(|(value, _)| value) != Some(&instance_id) {
+                return Ok(None);
+            }
+            for (key, value) in kvs {
+                state.kvs.insert(key, (value, None));
+            }
+            state.revision += 1;
+            Ok(Some(state.revision))
+        }
+
+        async fn delete_if_instance(
+            &self,
+            instance_key: String,
+            instance_id: Vec<u8>,
+            key: String,
+        ) -> Result<bool> {
+            self.request("delete_if_instance").await?;
+            let mut state = self.state.lock().unwrap();
+            if state.kvs.get(&instance_key).map(|(value, _)| value) != Some(&instance_id) {
+                return Ok(false);
+            }
+            state.kvs.remove(&key);
+            Ok(true)
+        }
+    }
+
+    #[async_trait]
+    impl EtcdConnector for Arc<MockEtcdClient> {
+        async fn connect(&self, endpoint: &str) -> Result<Arc<dyn EtcdClient>> {
+            self.connects.fetch_add(1, Ordering::Relaxed);
+            if self.fail_connect.load(Ordering::Relaxed) {
+                return Err(TowerError::IoError(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "connect failure injected",
+                )));
+            }
+            Ok(self.endpoint(Box::leak(endpoint.to_string().into_boxed_str())))
+        }
+    }
+
+    fn new_etcd_tower_storage_for_tests(
+        client: &Arc<MockEtcdClient>,
+        instance_id: u8,
+    ) -> EtcdTowerStorage {
+        let endpoint: Arc<dyn EtcdClient> = client.clone();
+        let mut tower_storage = EtcdTowerStorage::new_with_clients(
+            vec![(client.name.to_string(), Some(endpoint))],
+            Box::new(client.clone()),
+            &EtcdTowerStorageConfig::default(),
+            EtcdTowerStorage::new_runtime(),
+        );
+        tower_storage.instance_id = [instance_id; 8];
+        tower_storage
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_contended_lock() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockEtcdClient::default());
+        let first = new_etcd_tower_storage_for_tests(&client, 1);
+        let second = new_etcd_tower_storage_for_tests(&client, 2);
+
+        assert_matches!(
+            Tower::restore(&first, &node_pubkey),
+            Err(TowerError::TowerNotFound(_))
+        );
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&first, &identity_keypair).unwrap();
+
+        // The second instance fails fast without disturbing the first
+        assert_matches!(
+            Tower::restore(&second, &node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+        assert_eq!(client.state.lock().unwrap().leases.len(), 1);
+        tower.save(&first, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&first, &node_pubkey).unwrap(), tower);
+
+        // unless it forces a takeover
+        let second = second.with_force(true);
+        assert_eq!(Tower::restore(&second, &node_pubkey).unwrap(), tower);
+        assert_matches!(
+            tower.save(&first, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+        tower.save(&second, &identity_keypair).unwrap();
+
+        // Dropping the holder releases the lock right away
+        drop(second);
+        let third = new_etcd_tower_storage_for_tests(&client, 3);
+        assert_eq!(Tower::restore(&third, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_preflight() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockEtcdClient::default());
+        let first = new_etcd_tower_storage_for_tests(&client, 1);
+        let second = new_etcd_tower_storage_for_tests(&client, 2);
+
+        let report = first.preflight(&node_pubkey).unwrap();
+        assert!(!report.tower_found);
+        assert!(report.store_latency.is_some());
+        let preflight_key = EtcdTowerStorage::get_preflight_key(&node_pubkey);
+        assert!(!client
+            .state
+            .lock()
+            .unwrap()
+            .kvs
+            .contains_key(&preflight_key));
+
+        // Another instance holding the lock fails preflight, with or without
+        // a saved tower
+        assert_matches!(
+            second.preflight(&node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&first, &identity_keypair).unwrap();
+        assert_matches!(
+            second.preflight(&node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+        assert!(first.preflight(&node_pubkey).unwrap().tower_found);
+        assert_eq!(Tower::restore(&first, &node_pubkey).unwrap(), tower);
+
+        drop(first);
+        assert!(second.preflight(&node_pubkey).unwrap().tower_found);
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_takeover_after_lease_expiry() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockEtcdClient::default());
+        let first = new_etcd_tower_storage_for_tests(&client, 1);
+        let second = new_etcd_tower_storage_for_tests(&client, 2);
+
+        Tower::restore(&first, &node_pubkey).unwrap_err();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&first, &identity_keypair).unwrap();
+        assert_matches!(
+            Tower::restore(&second, &node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+
+        // The first instance stops keeping its lease alive, e.g. it crashed
+        let lease = first.instance_locks.blocking_lock()[&node_pubkey].lease;
+        client.expire_lease(lease);
+
+        assert_eq!(Tower::restore(&second, &node_pubkey).unwrap(), tower);
+        assert_matches!(
+            tower.save(&first, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_keepalive_failure() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockEtcdClient::default());
+        let tower_storage =
+            new_etcd_tower_storage_for_tests(&client, 1).with_lease_ttl(Duration::from_millis(300));
+
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+
+        // Keepalives succeed while the lease is alive
+        thread::sleep(Duration::from_millis(250));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+
+        client.fail_keep_alive.store(true, Ordering::Relaxed);
+        let is_lost = || {
+            tower_storage.instance_locks.blocking_lock()[&node_pubkey]
+                .lost()
+                .is_some()
+        };
+        for _ in 0..100 {
+            if is_lost() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(err)) if err.to_string().contains("keepalive failure injected")
+        );
+
+        // Loading again reacquires the lock
+        client.fail_keep_alive.store(false, Ordering::Relaxed);
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_timeout_failover() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockEtcdClient::default());
+        let endpoints = [
+            client.endpoint("a"),
+            client.endpoint("b"),
+            client.endpoint("c"),
+        ];
+        let tower_storage = EtcdTowerStorage::new_with_clients(
+            endpoints
+                .iter()
+                .map(|endpoint| {
+                    let client: Arc<dyn EtcdClient> = endpoint.clone();
+                    (endpoint.name.to_string(), Some(client))
+                })
+                .collect(),
+            Box::new(client.clone()),
+            &EtcdTowerStorageConfig {
+                operation_timeout: Duration::from_millis(100),
+                max_retries: 1,
+                ..EtcdTowerStorageConfig::default()
+            },
+            EtcdTowerStorage::new_runtime(),
+        );
+
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        assert!(client
+            .take_requests()
+            .iter()
+            .all(|(endpoint, _)| *endpoint == "a"));
+
+        // A hung request is retried against the next endpoint, first checking
+        // that the instance lock is still held
+        *endpoints[0].delay.lock().unwrap() = Duration::from_secs(10);
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(
+            client.take_requests(),
+            vec![
+                ("a", "put_if_instance"),
+                ("b", "get"),
+                ("b", "put_if_instance")
+            ]
+        );
+
+        // which then stays in use
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        assert_eq!(client.take_requests(), vec![("b", "get_if_instance")]);
+
+        // The error names the endpoint that failed last once retries run out
+        *endpoints[1].delay.lock().unwrap() = Duration::from_secs(10);
+        *endpoints[2].delay.lock().unwrap() = Duration::from_secs(10);
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(err))
+                if err.to_string() == "etcd endpoint c: timed out after 100ms"
+        );
+        assert_eq!(
+            client.take_requests(),
+            vec![("b", "put_if_instance"), ("c", "get")]
+        );
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_reconnect() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockEtcdClient::default());
+        let endpoint = client.endpoint("a");
+        let mut tower_storage = EtcdTowerStorage::new_with_clients(
+            vec![("a".to_string(), Some(endpoint.clone()))],
+            Box::new(client.clone()),
+            &EtcdTowerStorageConfig {
+                max_retries: 0,
+                max_reconnect_backoff: Duration::from_millis(200),
+                ..EtcdTowerStorageConfig::default()
+            },
+            EtcdTowerStorage::new_runtime(),
+        );
+        tower_storage.instance_id = [1; 8];
+        assert_eq!(
+            tower_storage.health(),
+            EtcdHealth {
+                last_successful_store: None,
+                consecutive_failures: 0,
+                current_endpoint: "a".to_string(),
+            }
+        );
+
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        let last_successful_store = tower_storage.health().last_successful_store;
+        assert!(last_successful_store.is_some());
+
+        // The connection drops, and etcd stays unreachable for a while
+        endpoint.disconnected.store(true, Ordering::Relaxed);
+        client.fail_connect.store(true, Ordering::Relaxed);
+        for consecutive_failures in 1..=3 {
+            assert_matches!(
+                tower.save(&tower_storage, &identity_keypair),
+                Err(TowerError::IoError(_))
+            );
+            assert_eq!(
+                tower_storage.health(),
+                EtcdHealth {
+                    last_successful_store,
+                    consecutive_failures,
+                    current_endpoint: "a".to_string(),
+                }
+            );
+        }
+
+        // Reconnect attempts back off, 100ms then 200ms apart
+        thread::sleep(Duration::from_millis(500));
+        assert!((1..=3).contains(&client.connects.load(Ordering::Relaxed)));
+        client.fail_connect.store(false, Ordering::Relaxed);
+
+        // Stores succeed again once reconnected
+        let mut result = tower.save(&tower_storage, &identity_keypair);
+        for _ in 0..100 {
+            if result.is_ok() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+            result = tower.save(&tower_storage, &identity_keypair);
+        }
+        result.unwrap();
+        let health = tower_storage.health();
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.last_successful_store > last_successful_store);
+
+        // and this instance still holds the instance lock
+        let other = new_etcd_tower_storage_for_tests(&client, 2);
+        assert_matches!(
+            Tower::restore(&other, &node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_migration() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockEtcdClient::default());
+        let tower_key = format!("{node_pubkey}/tower_v2");
+        let legacy_tower_key = format!("{node_pubkey}/tower");
+        let get = |key: &str| {
+            let state = client.state.lock().unwrap();
+            state.kvs.get(key).map(|(value, _)| value.clone())
+        };
+        let get_legacy = || {
+            let saved_tower: SavedTowerVersions =
+                bincode::deserialize(&get(&legacy_tower_key).unwrap()).unwrap();
+            saved_tower.try_into_tower(&node_pubkey).unwrap()
+        };
+
+        // An older release stored an unframed tower under the legacy key
+        let old_tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let saved_tower =
+            SavedTowerVersions::from(SavedTower::new(&old_tower, &identity_keypair).unwrap());
+        client.state.lock().unwrap().kvs.insert(
+            legacy_tower_key.clone(),
+            (bincode::serialize(&saved_tower).unwrap(), None),
+        );
+
+        // The upgraded instance restores it, and writes both keys while migrating
+        let tower_storage = new_etcd_tower_storage_for_tests(&client, 1).with_migration(true);
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap(),
+            old_tower
+        );
+        let tower = new_tower_for_tests(&identity_keypair, Some(2));
+        tower
+            .save_with_format(&tower_storage, &identity_keypair, SavedTowerWriteFormat::V2)
+            .unwrap();
+        assert!(get(&tower_key).unwrap().starts_with(&SAVED_TOWER_MAGIC));
+        assert_eq!(get_legacy(), tower);
+        // in the format older releases can deserialize
+        assert_matches!(
+            bincode::deserialize::<SavedTowerVersions>(&get(&legacy_tower_key).unwrap()).unwrap(),
+            SavedTowerVersions::Current(_)
+        );
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        drop(tower_storage);
+
+        // Once migration is off, only the current key is written
+        let tower_storage = new_etcd_tower_storage_for_tests(&client, 2);
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        let new_tower = new_tower_for_tests(&identity_keypair, Some(3));
+        new_tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(get_legacy(), tower);
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap(),
+            new_tower
+        );
+
+        tower_storage.cleanup_legacy(&node_pubkey).unwrap();
+        assert_eq!(get(&legacy_tower_key), None);
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap(),
+            new_tower
+        );
+
+        // The legacy tower is only deleted once there's a current one
+        assert_matches!(
+            tower_storage.cleanup_legacy(&Keypair::new().pubkey()),
+            Err(TowerError::TowerNotFound(_))
+        );
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_metrics() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockEtcdClient::default());
+        let tower_storage = new_etcd_tower_storage_for_tests(&client, 1);
+        assert_eq!(tower_storage.metrics(), EtcdTowerStorageMetrics::default());
+
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        let metrics = tower_storage.metrics();
+        assert_eq!((metrics.load.successes, metrics.load.failures), (0, 1));
+        assert_matches!(metrics.last_error, Some(err) if err.contains("No saved tower"));
+
+        *client.delay.lock().unwrap() = Duration::from_millis(10);
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        let metrics = tower_storage.metrics();
+        assert_eq!((metrics.store.successes, metrics.store.failures), (1, 0));
+        assert_eq!((metrics.load.successes, metrics.load.failures), (1, 1));
+        assert!(metrics.store.latency_us_p50 >= 10_000);
+        assert!(metrics.store.latency_us_p50 <= metrics.store.latency_us_p99);
+        assert!(metrics.store.latency_us_p99 <= metrics.store.latency_us_max);
+        assert_eq!(metrics.last_store_revision, Some(1));
+
+        // A failed compare reports the revision of the last successful store
+        let other = new_etcd_tower_storage_for_tests(&client, 2).with_force(true);
+        Tower::restore(&other, &node_pubkey).unwrap();
+        tower.save(&other, &identity_keypair).unwrap();
+        tower.save(&tower_storage, &identity_keypair).unwrap_err();
+        let metrics = tower_storage.metrics();
+        assert_eq!((metrics.store.successes, metrics.store.failures), (1, 1));
+        assert_eq!(metrics.last_store_revision, Some(1));
+        assert_matches!(metrics.last_error, Some(err) if err.contains("revision Some(1)"));
+        assert_eq!(other.metrics().last_store_revision, Some(2));
+    }
+
+    #[test]
+    fn test_migrate_file_to_etcd() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let source = FileTowerStorage::new(tower_path.path().to_path_buf());
+        let client = Arc::new(MockEtcdClient::default());
+        let dest = new_etcd_tower_storage_for_tests(&client, 1);
+
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.record_vote(5, Hash::default());
+        source
+            .store(&SavedTowerVersions::from(
+                SavedTower::new(&tower, &identity_keypair).unwrap(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            migrate(&source, &dest, &node_pubkey, &identity_keypair).unwrap(),
+            MigrationReport {
+                source_format: SavedTowerFormat::V1_14_11,
+                last_vote_slot: Some(5),
+                verified: true,
+            }
+        );
+        assert_matches!(
+            dest.load_saved_tower(&node_pubkey).unwrap(),
+            SavedTowerVersions::V2(_)
+        );
+        assert_eq!(Tower::restore(&dest, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_load_errors() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+
+        let client = Arc::new(MockEtcdClient::default());
+        let tower_storage = new_etcd_tower_storage_for_tests(&client, 1);
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::TowerNotFound(pubkey)) if pubkey == node_pubkey
+        );
+        assert!(!tower_storage.exists(&node_pubkey).unwrap());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert!(tower_storage.exists(&node_pubkey).unwrap());
+        let tower_key = format!("{node_pubkey}/tower_v2");
+        client
+            .state
+            .lock()
+            .unwrap()
+            .kvs
+            .insert(tower_key.clone(), (b"garbage".to_vec(), None));
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::CorruptTower(reason)) if reason.starts_with(&tower_key)
+        );
+    }
+
+    #[test]
+    fn test_etcd_tower_storage_saved_tower2_migration() {
+        let client = Arc::new(MockEtcdClient::default());
+        check_saved_tower2_migration(&new_etcd_tower_storage_for_tests(&client, 1));
+    }
+}
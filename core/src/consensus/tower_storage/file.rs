@@ -0,0 +1,1026 @@
+#[cfg(test)]
+use crate::consensus::tower1_7_14::SavedTower1_7_14;
+use {
+    super::{
+        corrupt_tower_at, detect_and_deserialize, tower_not_found, SavedTowerFormat,
+        SavedTowerVersions, TowerStorage, PREFLIGHT_SENTINEL,
+    },
+    crate::consensus::{Result, Tower, TowerError},
+    solana_pubkey::Pubkey,
+    std::{
+        collections::HashMap,
+        fmt,
+        fs::{self, File, OpenOptions},
+        io::{self, Write},
+        path::{Path, PathBuf},
+        process,
+        str::FromStr,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// How far `FileTowerStorage::store()` goes to make a saved tower survive a
+/// crash or power loss. Syncing adds latency to every vote (see the
+/// `bench_save_tower*` benches), so by default the tower is left to the OS.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DurabilityLevel {
+    /// Don't sync
+    #[default]
+    None,
+    /// Sync the tower data before renaming it into place
+    DataOnly,
+    /// Also sync the parent directory after the rename, persisting the rename
+    Full,
+}
+
+impl FromStr for DurabilityLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "data" => Ok(Self::DataOnly),
+            "full" => Ok(Self::Full),
+            _ => Err(format!("unknown durability level {s}")),
+        }
+    }
+}
+
+/// Advisory lock on a tower, held as a lock on `tower-{pubkey}.lock` which
+/// records the pid and instance id of its holder. The OS releases the lock
+/// when the file is closed, on drop or when the holder dies, so a crashed
+/// holder never leaves a stale lock behind. The file itself is left in place,
+/// since removing it would let another process lock a new file while a third
+/// still holds the old one.
+#[derive(Debug)]
+struct TowerLock {
+    // Held open to hold the lock
+    _file: File,
+}
+
+impl TowerLock {
+    fn acquire(path: PathBuf) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let mut lock = fd_lock::RwLock::new(file);
+        match lock.try_write() {
+            // Closing the file releases the lock, not dropping the guard
+            Ok(guard) => std::mem::forget(guard),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                let holder = fs::read_to_string(&path).unwrap_or_default();
+                return Err(TowerError::TowerLocked(format!(
+                    "{} is held by pid/instance {}",
+                    path.display(),
+                    holder.trim(),
+                )));
+            }
+            Err(err) => return Err(err.into()),
+        }
+        let mut file = lock.into_inner();
+        file.set_len(0)?;
+        let instance_id = solana_time_utils::timestamp();
+        writeln!(file, "{} {instance_id}", process::id())?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// Path of each tower file under the tower path of a `FileTowerStorage`,
+/// with `{pubkey}` standing for the node pubkey, e.g. `{pubkey}/tower.bin` to
+/// keep each node's tower in its own directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathTemplate(String);
+
+impl PathTemplate {
+    /// Tower files of 1.9 and later releases, directly in the tower path
+    pub const DEFAULT: &'static str = "tower-1_9-{pubkey}.bin";
+    const PUBKEY: &'static str = "{pubkey}";
+
+    /// Fails unless `template` is a relative path down from the tower path
+    /// with at least one `{pubkey}`
+    pub fn new(template: impl Into<String>) -> Result<Self> {
+        let template = template.into();
+        let invalid =
+            |reason: &str| TowerError::InvalidPathTemplate(format!("{template}: {reason}"));
+        if !template.contains(Self::PUBKEY) {
+            return Err(invalid("no {pubkey} placeholder"));
+        }
+        if !Path::new(&template)
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)))
+        {
+            return Err(invalid("must be relative to the tower path"));
+        }
+        Ok(Self(template))
+    }
+
+    pub fn expand(&self, node_pubkey: &Pubkey) -> PathBuf {
+        PathBuf::from(self.0.replace(Self::PUBKEY, &node_pubkey.to_string()))
+    }
+
+    /// The pubkey that `path`, relative to the tower path, is expanded from
+    fn match_path(&self, path: &Path) -> Option<Pubkey> {
+        let (prefix, _) = self.0.split_once(Self::PUBKEY)?;
+        let rest = path.to_str()?.strip_prefix(prefix)?;
+        // Base58 pubkeys are 32 to 44 characters long
+        (32..=44)
+            .filter_map(|len| Pubkey::from_str(rest.get(..len)?).ok())
+            .find(|node_pubkey| self.expand(node_pubkey) == path)
+    }
+
+    /// Number of path components, i.e. how deep tower files are
+    fn depth(&self) -> usize {
+        Path::new(&self.0).components().count()
+    }
+}
+
+impl Default for PathTemplate {
+    fn default() -> Self {
+        Self(Self::DEFAULT.to_string())
+    }
+}
+
+impl FromStr for PathTemplate {
+    type Err = TowerError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for PathTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Outcome of `FileTowerStorage::migrate_layout()`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LayoutMigrationReport {
+    /// Nodes whose tower files were moved to the new layout
+    pub moved: Vec<Pubkey>,
+    /// Nodes whose tower files were left in place, since the new layout
+    /// already has a tower for them
+    pub conflicts: Vec<Pubkey>,
+}
+
+// Files under `dir`, down to `max_depth` levels deep
+fn list_files(dir: &Path, max_depth: usize) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            files.push(entry.path());
+        } else if max_depth > 1 {
+            files.append(&mut list_files(&entry.path(), max_depth - 1)?);
+        }
+    }
+    Ok(files)
+}
+
+fn backup_filename_of(filename: &Path, index: usize) -> PathBuf {
+    filename.with_extension(format!("bin.{index}"))
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FileTowerStorage {
+    pub tower_path: PathBuf,
+    path_template: PathTemplate,
+    durability: DurabilityLevel,
+    backup_count: usize,
+    restore_from_backups: bool,
+    // Shared between clones, so the lock is released when the last one is dropped
+    instance_locks: Option<Arc<Mutex<HashMap<Pubkey, TowerLock>>>>,
+}
+
+impl PartialEq for FileTowerStorage {
+    fn eq(&self, other: &Self) -> bool {
+        self.tower_path == other.tower_path
+            && self.path_template == other.path_template
+            && self.durability == other.durability
+            && self.backup_count == other.backup_count
+            && self.restore_from_backups == other.restore_from_backups
+            && self.instance_locks.is_some() == other.instance_locks.is_some()
+    }
+}
+
+impl Eq for FileTowerStorage {}
+
+impl FileTowerStorage {
+    pub fn new(tower_path: PathBuf) -> Self {
+        Self {
+            tower_path,
+            path_template: PathTemplate::default(),
+            durability: DurabilityLevel::default(),
+            backup_count: 0,
+            restore_from_backups: false,
+            instance_locks: None,
+        }
+    }
+
+    /// Lay out tower files under the tower path as per `path_template`
+    /// rather than `PathTemplate::DEFAULT`. Towers saved under another layout
+    /// are not found, see `migrate_layout()`.
+    pub fn with_path_template(mut self, path_template: PathTemplate) -> Self {
+        self.path_template = path_template;
+        self
+    }
+
+    pub fn with_durability(mut self, durability: DurabilityLevel) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Keep up to `backup_count` previous towers as `<filename>.1` (newest)
+    /// through `<filename>.<backup_count>` (oldest), see
+    /// `with_backup_restore()`.
+    pub fn with_backup_count(mut self, backup_count: usize) -> Self {
+        self.backup_count = backup_count;
+        self
+    }
+
+    /// Fall back to the newest readable backup if the primary tower file
+    /// can't be read. A backup may miss the latest votes, so restoring one
+    /// risks voting against them; only for operators who accept that risk.
+    pub fn with_backup_restore(mut self) -> Self {
+        self.restore_from_backups = true;
+        self
+    }
+
+    /// Lock each tower on its first load or store, so that another process
+    /// pointed at the same tower path fails instead of overwriting the tower
+    pub fn with_instance_lock(mut self) -> Self {
+        self.instance_locks = Some(Arc::default());
+        self
+    }
+
+    // Old filename for towers pre 1.9 (VoteStateUpdate)
+    pub fn old_filename(&self, node_pubkey: &Pubkey) -> PathBuf {
+        self.tower_path
+            .join(format!("tower-{node_pubkey}"))
+            .with_extension("bin")
+    }
+
+    pub fn filename(&self, node_pubkey: &Pubkey) -> PathBuf {
+        self.tower_path.join(self.path_template.expand(node_pubkey))
+    }
+
+    pub fn lock_filename(&self, node_pubkey: &Pubkey) -> PathBuf {
+        self.tower_path
+            .join(format!("tower-{node_pubkey}"))
+            .with_extension("lock")
+    }
+
+    pub fn preflight_filename(&self, node_pubkey: &Pubkey) -> PathBuf {
+        self.tower_path
+            .join(format!("tower-{node_pubkey}"))
+            .with_extension("preflight")
+    }
+
+    /// Lists the tower files in the tower path, current or pre 1.9, with the
+    /// pubkey of each. Other files are skipped.
+    pub fn list_towers(&self) -> Result<Vec<(Pubkey, PathBuf)>> {
+        let mut towers = vec![];
+        for path in list_files(&self.tower_path, self.path_template.depth())? {
+            let relative_path = path.strip_prefix(&self.tower_path).unwrap();
+            let node_pubkey = self.path_template.match_path(relative_path).or_else(|| {
+                // Towers of pre 1.9 releases are always directly in the tower path
+                relative_path
+                    .to_str()
+                    .and_then(|file_name| file_name.strip_prefix("tower-"))
+                    .and_then(|file_name| file_name.strip_suffix(".bin"))
+                    .map(|name| name.strip_prefix("1_9-").unwrap_or(name))
+                    .and_then(|name| Pubkey::from_str(name).ok())
+            });
+            if let Some(node_pubkey) = node_pubkey {
+                towers.push((node_pubkey, path));
+            }
+        }
+        towers.sort();
+        Ok(towers)
+    }
+
+    /// Moves the tower files, and their backups, laid out as per
+    /// `old_template` under the tower path to where `new_template` puts them.
+    /// A tower already at its new path is kept, and the old one left in
+    /// place.
+    pub fn migrate_layout(
+        &self,
+        old_template: &PathTemplate,
+        new_template: &PathTemplate,
+    ) -> Result<LayoutMigrationReport> {
+        let mut report = LayoutMigrationReport::default();
+        if old_template == new_template {
+            return Ok(report);
+        }
+        let mut towers: Vec<_> = list_files(&self.tower_path, old_template.depth())?
+            .into_iter()
+            .filter_map(|path| {
+                let relative_path = path.strip_prefix(&self.tower_path).ok()?;
+                Some((old_template.match_path(relative_path)?, path))
+            })
+            .collect();
+        towers.sort();
+        for (node_pubkey, old_filename) in towers {
+            let new_filename = self.tower_path.join(new_template.expand(&node_pubkey));
+            if new_filename.exists() {
+                warn!(
+                    "Not moving tower {} over existing tower {}",
+                    old_filename.display(),
+                    new_filename.display()
+                );
+                report.conflicts.push(node_pubkey);
+                continue;
+            }
+            fs::create_dir_all(new_filename.parent().unwrap())?;
+            fs::rename(&old_filename, &new_filename)?;
+            for index in 1.. {
+                let old_backup = backup_filename_of(&old_filename, index);
+                if !old_backup.exists() {
+                    break;
+                }
+                fs::rename(&old_backup, backup_filename_of(&new_filename, index))?;
+            }
+            // Drop the node's directory if the old layout had one
+            let old_dir = old_filename.parent().unwrap();
+            if old_dir != self.tower_path {
+                let _ = fs::remove_dir(old_dir);
+            }
+            info!(
+                "Moved tower {} to {}",
+                old_filename.display(),
+                new_filename.display()
+            );
+            report.moved.push(node_pubkey);
+        }
+        Ok(report)
+    }
+
+    fn lock_instance(&self, node_pubkey: &Pubkey) -> Result<()> {
+        let Some(instance_locks) = &self.instance_locks else {
+            return Ok(());
+        };
+        let mut instance_locks = instance_locks.lock().unwrap();
+        if instance_locks.contains_key(node_pubkey) {
+            return Ok(());
+        }
+        let lock = TowerLock::acquire(self.lock_filename(node_pubkey))?;
+        instance_locks.insert(*node_pubkey, lock);
+        Ok(())
+    }
+
+    pub fn backup_filename(&self, node_pubkey: &Pubkey, index: usize) -> PathBuf {
+        backup_filename_of(&self.filename(node_pubkey), index)
+    }
+
+    fn load_from(filename: &Path, node_pubkey: &Pubkey) -> Result<Tower> {
+        Self::read_saved_tower(filename, node_pubkey).and_then(|t| {
+            t.try_into_tower(node_pubkey)
+                .map_err(corrupt_tower_at(filename.display()))
+        })
+    }
+
+    fn read_saved_tower(filename: &Path, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        let data = fs::read(filename).map_err(|err| tower_not_found(err, node_pubkey))?;
+        let saved_tower = detect_and_deserialize(&data, node_pubkey)
+            .map_err(corrupt_tower_at(filename.display()))?;
+        debug!(
+            "Found {:?} tower in {}",
+            SavedTowerFormat::from(&saved_tower),
+            filename.display()
+        );
+        Ok(saved_tower)
+    }
+
+    fn load_old(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        Self::read_saved_tower(&self.old_filename(node_pubkey), node_pubkey)
+    }
+
+    fn load_from_backups(&self, node_pubkey: &Pubkey) -> Option<Tower> {
+        if !self.restore_from_backups {
+            return None;
+        }
+        (1..=self.backup_count).find_map(|index| {
+            let filename = self.backup_filename(node_pubkey, index);
+            match Self::load_from(&filename, node_pubkey) {
+                Ok(tower) => {
+                    error!(
+                        "Restored tower from backup {}, votes since the backup are lost",
+                        filename.display()
+                    );
+                    datapoint_error!(
+                        "tower_restored_from_backup",
+                        ("backup", index, i64),
+                        (
+                            "last_vote_slot",
+                            tower.last_voted_slot().unwrap_or_default(),
+                            i64
+                        ),
+                    );
+                    Some(tower)
+                }
+                Err(err) => {
+                    if !err.is_file_missing() {
+                        warn!(
+                            "Unable to restore tower backup {}: {err}",
+                            filename.display()
+                        );
+                    }
+                    None
+                }
+            }
+        })
+    }
+
+    // Shift `<filename>.1..` up by one, dropping the oldest, and hard link the
+    // current tower as `<filename>.1`. Linking rather than renaming keeps the
+    // current tower in place until the new one is renamed over it.
+    fn rotate_backups(&self, node_pubkey: &Pubkey) -> Result<()> {
+        let filename = self.filename(node_pubkey);
+        if self.backup_count == 0 || !filename.exists() {
+            return Ok(());
+        }
+        for index in (1..self.backup_count).rev() {
+            let backup = self.backup_filename(node_pubkey, index);
+            if backup.exists() {
+                fs::rename(&backup, self.backup_filename(node_pubkey, index + 1))?;
+            }
+        }
+        let backup = self.backup_filename(node_pubkey, 1);
+        if backup.exists() {
+            fs::remove_file(&backup)?;
+        }
+        fs::hard_link(&filename, &backup)?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(super) fn store_old(&self, saved_tower: &SavedTower1_7_14) -> Result<()> {
+        let pubkey = saved_tower.node_pubkey;
+        let filename = self.old_filename(&pubkey);
+        trace!("store: {}", filename.display());
+        let new_filename = filename.with_extension("bin.new");
+
+        {
+            // overwrite anything if exists
+            let file = File::create(&new_filename)?;
+            bincode::serialize_into(file, saved_tower)?;
+            // file.sync_all() hurts performance; pipeline sync-ing and submitting votes to the cluster!
+        }
+        fs::rename(&new_filename, &filename)?;
+        // self.path.parent().sync_all() hurts performance same as the above sync
+        Ok(())
+    }
+}
+
+impl TowerStorage for FileTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        let filename = self.filename(node_pubkey);
+        trace!("load {}", filename.display());
+
+        // Ensure to create parent dir here, because restore() precedes save() always
+        fs::create_dir_all(filename.parent().unwrap())?;
+        self.lock_instance(node_pubkey)?;
+
+        if filename.exists() {
+            // New format
+            Self::load_from(&filename, node_pubkey).or_else(|err| {
+                warn!("Unable to restore tower {}: {err}", filename.display());
+                self.load_from_backups(node_pubkey).ok_or(err)
+            })
+        } else {
+            // Old format
+            let old_filename = self.old_filename(node_pubkey);
+            let (tower, report) = self
+                .load_old(node_pubkey)?
+                .try_into_tower_with_report(node_pubkey)
+                .map_err(corrupt_tower_at(old_filename.display()))?;
+            info!(
+                "Migrating tower {}, defaulting {:?}",
+                old_filename.display(),
+                report.defaulted
+            );
+            Ok(tower)
+        }
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let pubkey = saved_tower.pubkey();
+        self.lock_instance(&pubkey)?;
+        let filename = self.filename(&pubkey);
+        trace!("store: {}", filename.display());
+        let new_filename = filename.with_extension("bin.new");
+        if self.path_template.depth() > 1 {
+            // The node's directory under a nested layout
+            fs::create_dir_all(filename.parent().unwrap())?;
+        }
+
+        {
+            // overwrite anything if exists
+            let mut file = File::create(&new_filename)?;
+            saved_tower.serialize_into(&mut file)?;
+            // file.sync_all() hurts performance; only sync when a durability level is configured
+            if self.durability != DurabilityLevel::None {
+                file.sync_data()?;
+            }
+        }
+        self.rotate_backups(&pubkey)?;
+        fs::rename(&new_filename, &filename)?;
+        // self.path.parent().sync_all() hurts performance same as the above sync
+        // but is needed to persist the rename itself
+        if self.durability == DurabilityLevel::Full {
+            File::open(filename.parent().unwrap())?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        let filename = self.filename(node_pubkey);
+        fs::create_dir_all(filename.parent().unwrap())?;
+        self.lock_instance(node_pubkey)?;
+
+        if filename.exists() {
+            Self::read_saved_tower(&filename, node_pubkey).or_else(|err| {
+                warn!("Unable to read tower {}: {err}", filename.display());
+                let backup_count = if self.restore_from_backups {
+                    self.backup_count
+                } else {
+                    0
+                };
+                (1..=backup_count)
+                    .find_map(|index| {
+                        let filename = self.backup_filename(node_pubkey, index);
+                        Self::read_saved_tower(&filename, node_pubkey).ok()
+                    })
+                    .ok_or(err)
+            })
+        } else {
+            self.load_old(node_pubkey)
+        }
+    }
+
+    fn store_sentinel(&self, node_pubkey: &Pubkey) -> Result<()> {
+        let filename = self.preflight_filename(node_pubkey);
+        {
+            let mut file = File::create(&filename)?;
+            file.write_all(PREFLIGHT_SENTINEL)?;
+            if self.durability != DurabilityLevel::None {
+                file.sync_data()?;
+            }
+        }
+        fs::remove_file(&filename)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::consensus::tower_storage::{
+            test::new_tower_for_tests, SavedTower, SavedTowerWriteFormat, SAVED_TOWER_MAGIC,
+        },
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_signer::Signer,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn test_file_tower_storage_durability() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        for durability in [
+            DurabilityLevel::None,
+            DurabilityLevel::DataOnly,
+            DurabilityLevel::Full,
+        ] {
+            let tower_path = TempDir::new().unwrap();
+            let tower_storage =
+                FileTowerStorage::new(tower_path.path().to_path_buf()).with_durability(durability);
+
+            // Restoring first creates the tower directory
+            assert!(Tower::restore(&tower_storage, &node_pubkey)
+                .unwrap_err()
+                .is_file_missing());
+            let tower = new_tower_for_tests(&identity_keypair, Some(1));
+            tower.save(&tower_storage, &identity_keypair).unwrap();
+            let tower = new_tower_for_tests(&identity_keypair, Some(2));
+            tower.save(&tower_storage, &identity_keypair).unwrap();
+
+            // The new tower replaced the old one and no temporary file remains
+            assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+            let filename = tower_storage.filename(&node_pubkey);
+            assert!(filename.exists());
+            assert!(!filename.with_extension("bin.new").exists());
+        }
+    }
+
+    #[test]
+    fn test_file_tower_storage_backup_rotation() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        for backup_count in [0, 1, 3] {
+            let tower_path = TempDir::new().unwrap();
+            let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf())
+                .with_backup_count(backup_count);
+            Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+
+            for root in 1..=5 {
+                new_tower_for_tests(&identity_keypair, Some(root))
+                    .save(&tower_storage, &identity_keypair)
+                    .unwrap();
+            }
+
+            assert_eq!(
+                Tower::restore(&tower_storage, &node_pubkey).unwrap().root(),
+                5
+            );
+            // Backups hold the previous towers, newest first
+            for index in 1..=backup_count {
+                let backup = tower_storage.backup_filename(&node_pubkey, index);
+                let tower = FileTowerStorage::load_from(&backup, &node_pubkey).unwrap();
+                assert_eq!(tower.root(), 5 - index as u64);
+            }
+            assert!(!tower_storage
+                .backup_filename(&node_pubkey, backup_count + 1)
+                .exists());
+        }
+    }
+
+    #[test]
+    fn test_file_tower_storage_restore_from_backup() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf())
+            .with_backup_count(3)
+            .with_backup_restore();
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+
+        for root in 1..=3 {
+            new_tower_for_tests(&identity_keypair, Some(root))
+                .save(&tower_storage, &identity_keypair)
+                .unwrap();
+        }
+
+        // Truncate the primary tower file
+        let filename = tower_storage.filename(&node_pubkey);
+        let len = fs::metadata(&filename).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&filename)
+            .unwrap()
+            .set_len(len / 2)
+            .unwrap();
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap().root(),
+            2
+        );
+
+        // Corrupt the newest backup too
+        fs::write(tower_storage.backup_filename(&node_pubkey, 1), b"garbage").unwrap();
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap().root(),
+            1
+        );
+
+        // Without opting in to restoring backups the primary's error is reported
+        let tower_storage =
+            FileTowerStorage::new(tower_path.path().to_path_buf()).with_backup_count(3);
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::CorruptTower(_))
+        );
+
+        // Likewise without backups
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::CorruptTower(_))
+        );
+    }
+
+    #[test]
+    fn test_file_tower_storage_instance_lock() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let first = FileTowerStorage::new(tower_path.path().to_path_buf()).with_instance_lock();
+        let second = FileTowerStorage::new(tower_path.path().to_path_buf()).with_instance_lock();
+        let lock_filename = first.lock_filename(&node_pubkey);
+
+        Tower::restore(&first, &node_pubkey).unwrap_err();
+        assert!(lock_filename.exists());
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&first, &identity_keypair).unwrap();
+
+        // Clones share the lock
+        let first_clone = first.clone();
+        tower.save(&first_clone, &identity_keypair).unwrap();
+
+        assert_matches!(
+            tower.save(&second, &identity_keypair),
+            Err(TowerError::TowerLocked(_))
+        );
+        assert_matches!(
+            Tower::restore(&second, &node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+
+        // The lock is released once the last clone is dropped
+        drop(first);
+        assert_matches!(
+            tower.save(&second, &identity_keypair),
+            Err(TowerError::TowerLocked(_))
+        );
+        drop(first_clone);
+        assert!(lock_filename.exists());
+        tower.save(&second, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&second, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_file_tower_storage_reclaim_stale_lock() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage =
+            FileTowerStorage::new(tower_path.path().to_path_buf()).with_instance_lock();
+
+        // Left behind by a process that no longer exists, whose pid has since
+        // been reused by this one
+        let lock_filename = tower_storage.lock_filename(&node_pubkey);
+        let stale_holder = format!("{} 0\n", process::id());
+        fs::write(&lock_filename, &stale_holder).unwrap();
+
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        let holder = fs::read_to_string(&lock_filename).unwrap();
+        assert!(holder.starts_with(&format!("{} ", process::id())));
+        assert_ne!(holder, stale_holder);
+    }
+
+    #[test]
+    fn test_file_tower_storage_preflight() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+
+        let report = tower_storage.preflight(&node_pubkey).unwrap();
+        assert!(!report.tower_found);
+        assert!(report.store_latency.is_some());
+        assert!(!tower_storage.preflight_filename(&node_pubkey).exists());
+
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert!(tower_storage.preflight(&node_pubkey).unwrap().tower_found);
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        // A tower that can't be loaded is reported, but doesn't fail preflight
+        fs::write(tower_storage.filename(&node_pubkey), b"garbage").unwrap();
+        let report = tower_storage.preflight(&node_pubkey).unwrap();
+        assert!(report.tower_found);
+        assert_matches!(report.load_error, Some(TowerError::CorruptTower(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_tower_storage_preflight_read_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+
+        fs::set_permissions(tower_path.path(), fs::Permissions::from_mode(0o555)).unwrap();
+        // Root writes to read-only directories regardless
+        if File::create(tower_path.path().join("probe")).is_err() {
+            // The tower loads, but could not be stored
+            assert_matches!(
+                tower_storage.preflight(&node_pubkey),
+                Err(TowerError::IoError(err)) if err.kind() == io::ErrorKind::PermissionDenied
+            );
+        }
+        fs::set_permissions(tower_path.path(), fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(tower_storage.preflight(&node_pubkey).unwrap().tower_found);
+    }
+
+    #[test]
+    fn test_file_tower_storage_load_unframed() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+
+        // Towers saved before framing was introduced still load
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let saved_tower =
+            SavedTowerVersions::from(SavedTower::new(&tower, &identity_keypair).unwrap());
+        fs::write(
+            tower_storage.filename(&node_pubkey),
+            bincode::serialize(&saved_tower).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        // and are framed once stored again in the new format
+        tower
+            .save_with_format(&tower_storage, &identity_keypair, SavedTowerWriteFormat::V2)
+            .unwrap();
+        let data = fs::read(tower_storage.filename(&node_pubkey)).unwrap();
+        assert!(data.starts_with(&SAVED_TOWER_MAGIC));
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_file_tower_storage_list_towers() {
+        let tower_path = TempDir::new().unwrap();
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let old_node_pubkey = Pubkey::new_unique();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf())
+            .with_backup_count(1)
+            .with_instance_lock();
+        assert!(tower_storage.list_towers().unwrap().is_empty());
+
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        // Rotates the first tower into a backup
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        fs::write(tower_storage.old_filename(&old_node_pubkey), b"old tower").unwrap();
+        for junk in [
+            "tower-not-a-pubkey.bin",
+            "tower-1_9-.bin",
+            "tower.bin",
+            "notes.txt",
+        ] {
+            fs::write(tower_path.path().join(junk), b"junk").unwrap();
+        }
+        fs::create_dir(
+            tower_path
+                .path()
+                .join(format!("tower-{}.bin.d", Pubkey::new_unique())),
+        )
+        .unwrap();
+        assert!(tower_storage.lock_filename(&node_pubkey).exists());
+        assert!(tower_storage.backup_filename(&node_pubkey, 1).exists());
+
+        let mut expected = vec![
+            (node_pubkey, tower_storage.filename(&node_pubkey)),
+            (
+                old_node_pubkey,
+                tower_storage.old_filename(&old_node_pubkey),
+            ),
+        ];
+        expected.sort();
+        assert_eq!(tower_storage.list_towers().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_path_template() {
+        let node_pubkey = Pubkey::new_unique();
+        let tower_path = Path::new("/towers");
+        assert_eq!(
+            FileTowerStorage::new(tower_path.to_path_buf()).filename(&node_pubkey),
+            tower_path.join(format!("tower-1_9-{node_pubkey}.bin"))
+        );
+        let path_template = PathTemplate::new("{pubkey}/tower.bin").unwrap();
+        assert_eq!(
+            path_template.expand(&node_pubkey),
+            Path::new(&node_pubkey.to_string()).join("tower.bin")
+        );
+        assert_eq!(
+            path_template.match_path(&path_template.expand(&node_pubkey)),
+            Some(node_pubkey)
+        );
+        assert_eq!(path_template.match_path(Path::new("tower.bin")), None);
+        assert_eq!(
+            "{pubkey}.bin".parse::<PathTemplate>().unwrap().to_string(),
+            "{pubkey}.bin"
+        );
+
+        for template in [
+            "",
+            "tower.bin",
+            "{node}/tower.bin",
+            "/towers/{pubkey}.bin",
+            "../{pubkey}.bin",
+            "towers/../{pubkey}.bin",
+        ] {
+            assert_matches!(
+                PathTemplate::new(template),
+                Err(TowerError::InvalidPathTemplate(_)),
+                "{template}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_file_tower_storage_nested_path_template() {
+        let tower_path = TempDir::new().unwrap();
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf())
+            .with_path_template(PathTemplate::new("nodes/{pubkey}/tower.bin").unwrap())
+            .with_backup_count(1);
+        let filename = tower_path
+            .path()
+            .join("nodes")
+            .join(node_pubkey.to_string())
+            .join("tower.bin");
+        assert_eq!(tower_storage.filename(&node_pubkey), filename);
+
+        // The node's directory is created on store as well as on load
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        tower.record_vote(5, Hash::default());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert!(filename.exists());
+        assert!(tower_storage.backup_filename(&node_pubkey, 1).exists());
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        assert_eq!(
+            tower_storage.list_towers().unwrap(),
+            vec![(node_pubkey, filename)]
+        );
+
+        // Towers of the default layout are not found under another one
+        let default_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        assert!(!default_storage.exists(&node_pubkey).unwrap());
+    }
+
+    #[test]
+    fn test_file_tower_storage_migrate_layout() {
+        let tower_path = TempDir::new().unwrap();
+        let old_template = PathTemplate::default();
+        let new_template = PathTemplate::new("{pubkey}/tower.bin").unwrap();
+        let old_storage =
+            FileTowerStorage::new(tower_path.path().to_path_buf()).with_backup_count(2);
+        let new_storage = FileTowerStorage::new(tower_path.path().to_path_buf())
+            .with_path_template(new_template.clone())
+            .with_backup_count(2);
+
+        let keypair = Keypair::new();
+        let mut tower = new_tower_for_tests(&keypair, Some(1));
+        tower.save(&old_storage, &keypair).unwrap();
+        tower.record_vote(5, Hash::default());
+        tower.save(&old_storage, &keypair).unwrap();
+        // A tower already saved under the new layout is kept
+        let conflict_keypair = Keypair::new();
+        let conflict_tower = new_tower_for_tests(&conflict_keypair, Some(1));
+        conflict_tower
+            .save(&old_storage, &conflict_keypair)
+            .unwrap();
+        let mut new_conflict_tower = conflict_tower.clone();
+        new_conflict_tower.record_vote(7, Hash::default());
+        new_conflict_tower
+            .save(&new_storage, &conflict_keypair)
+            .unwrap();
+
+        let report = old_storage
+            .migrate_layout(&old_template, &new_template)
+            .unwrap();
+        assert_eq!(
+            report,
+            LayoutMigrationReport {
+                moved: vec![keypair.pubkey()],
+                conflicts: vec![conflict_keypair.pubkey()],
+            }
+        );
+        assert!(!old_storage.filename(&keypair.pubkey()).exists());
+        assert!(new_storage.backup_filename(&keypair.pubkey(), 1).exists());
+        assert_eq!(
+            Tower::restore(&new_storage, &keypair.pubkey()).unwrap(),
+            tower
+        );
+        assert!(old_storage.filename(&conflict_keypair.pubkey()).exists());
+        assert_eq!(
+            Tower::restore(&new_storage, &conflict_keypair.pubkey()).unwrap(),
+            new_conflict_tower
+        );
+
+        // and back, dropping the emptied directory
+        let report = new_storage
+            .migrate_layout(&new_template, &old_template)
+            .unwrap();
+        assert_eq!(report.moved, vec![keypair.pubkey()]);
+        assert_eq!(report.conflicts, vec![conflict_keypair.pubkey()]);
+        assert!(!tower_path
+            .path()
+            .join(keypair.pubkey().to_string())
+            .exists());
+        assert_eq!(
+            Tower::restore(&old_storage, &keypair.pubkey()).unwrap(),
+            tower
+        );
+    }
+}
@@ -0,0 +1,217 @@
+#[cfg(feature = "s3-tower-storage")]
+mod s3;
+
+#[cfg(feature = "s3-tower-storage")]
+pub use self::s3::{ObjectStoreConfig, S3ObjectStore};
+use {
+    super::{corrupt_tower_at, SavedTowerVersions, TowerStorage},
+    crate::consensus::{Result, Tower, TowerError},
+    solana_pubkey::Pubkey,
+    std::{collections::HashMap, io, sync::Mutex},
+};
+
+/// Identifies a version of an object in an `ObjectStore`
+pub type ETag = String;
+
+/// Precondition that must hold for `ObjectStore::put()` to write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PutCondition {
+    /// The object must not exist
+    IfNoneMatch,
+    /// The current version of the object must match the ETag
+    IfMatch(ETag),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PutOutcome {
+    Written(ETag),
+    PreconditionFailed,
+}
+
+/// Object store with conditional writes, such as S3
+pub trait ObjectStore: Sync + Send {
+    /// Returns the object at `key` with its ETag, or `None` if it does not exist
+    fn get(&self, key: &str) -> io::Result<Option<(Vec<u8>, ETag)>>;
+    fn put(&self, key: &str, data: Vec<u8>, condition: PutCondition) -> io::Result<PutOutcome>;
+}
+
+/// Saves towers to an object store under `tower/{node_pubkey}.bin`.
+///
+/// Every write is conditional on the version of the tower this instance last
+/// loaded or stored, so that a stale instance cannot clobber a tower written
+/// by a newer one.
+pub struct ObjectStoreTowerStorage {
+    object_store: Box<dyn ObjectStore>,
+    etags: Mutex<HashMap<Pubkey, Option<ETag>>>,
+}
+
+impl ObjectStoreTowerStorage {
+    #[cfg(feature = "s3-tower-storage")]
+    pub fn new(config: ObjectStoreConfig) -> Result<Self> {
+        Ok(Self::new_with_object_store(Box::new(S3ObjectStore::new(
+            config,
+        )?)))
+    }
+
+    pub fn new_with_object_store(object_store: Box<dyn ObjectStore>) -> Self {
+        Self {
+            object_store,
+            etags: Mutex::default(),
+        }
+    }
+
+    fn get_key(node_pubkey: &Pubkey) -> String {
+        format!("tower/{node_pubkey}.bin")
+    }
+}
+
+impl TowerStorage for ObjectStoreTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.load_saved_tower(node_pubkey)?
+            .try_into_tower(node_pubkey)
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        let key = Self::get_key(node_pubkey);
+        trace!("load {key}");
+
+        let object = self.object_store.get(&key).map_err(|err| {
+            error!("Failed to read object store saved tower: {}", err);
+            err
+        })?;
+        let mut etags = self.etags.lock().unwrap();
+        let Some((data, etag)) = object else {
+            etags.insert(*node_pubkey, None);
+            return Err(TowerError::TowerNotFound(*node_pubkey));
+        };
+        etags.insert(*node_pubkey, Some(etag));
+        SavedTowerVersions::deserialize(&data).map_err(corrupt_tower_at(key))
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let pubkey = saved_tower.pubkey();
+        let key = Self::get_key(&pubkey);
+        trace!("store: {key}");
+
+        let data = saved_tower.serialize()?;
+        let mut etags = self.etags.lock().unwrap();
+        // Without a previous load, only write if no other instance has
+        let condition = match etags.get(&pubkey) {
+            Some(Some(etag)) => PutCondition::IfMatch(etag.clone()),
+            Some(None) | None => PutCondition::IfNoneMatch,
+        };
+        let outcome = self
+            .object_store
+            .put(&key, data, condition)
+            .map_err(|err| {
+                error!("Failed to write object store saved tower: {}", err);
+                err
+            })?;
+        match outcome {
+            PutOutcome::Written(etag) => {
+                etags.insert(pubkey, Some(etag));
+                Ok(())
+            }
+            PutOutcome::PreconditionFailed => Err(TowerError::IoError(io::Error::other(format!(
+                "Saved tower for {pubkey} was written by another instance"
+            )))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::consensus::tower_storage::test::new_tower_for_tests,
+        solana_keypair::Keypair, solana_signer::Signer, std::sync::Arc,
+    };
+
+    /// In-memory `ObjectStore`, clones share the same objects
+    #[derive(Clone, Default)]
+    struct MemoryObjectStore {
+        objects: Arc<Mutex<HashMap<String, (Vec<u8>, ETag)>>>,
+        next_version: Arc<Mutex<u64>>,
+    }
+
+    impl ObjectStore for MemoryObjectStore {
+        fn get(&self, key: &str) -> io::Result<Option<(Vec<u8>, ETag)>> {
+            Ok(self.objects.lock().unwrap().get(key).cloned())
+        }
+
+        fn put(&self, key: &str, data: Vec<u8>, condition: PutCondition) -> io::Result<PutOutcome> {
+            let mut objects = self.objects.lock().unwrap();
+            let current_etag = objects.get(key).map(|(_, etag)| etag);
+            let satisfied = match &condition {
+                PutCondition::IfNoneMatch => current_etag.is_none(),
+                PutCondition::IfMatch(etag) => current_etag == Some(etag),
+            };
+            if !satisfied {
+                return Ok(PutOutcome::PreconditionFailed);
+            }
+            let mut next_version = self.next_version.lock().unwrap();
+            *next_version += 1;
+            let etag = format!("\"{next_version}\"");
+            objects.insert(key.to_string(), (data, etag.clone()));
+            Ok(PutOutcome::Written(etag))
+        }
+    }
+
+    #[test]
+    fn test_object_store_tower_storage() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let object_store = MemoryObjectStore::default();
+        let tower_storage =
+            ObjectStoreTowerStorage::new_with_object_store(Box::new(object_store.clone()));
+
+        let err = Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        assert_matches!(err, TowerError::TowerNotFound(pubkey) if pubkey == node_pubkey);
+        assert!(err.is_file_missing());
+
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert!(object_store
+            .get(&format!("tower/{node_pubkey}.bin"))
+            .unwrap()
+            .is_some());
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        // Subsequent stores are conditional on the version just written
+        let tower = new_tower_for_tests(&identity_keypair, Some(2));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_object_store_tower_storage_stale_instance() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let object_store = MemoryObjectStore::default();
+        let first = ObjectStoreTowerStorage::new_with_object_store(Box::new(object_store.clone()));
+        let second = ObjectStoreTowerStorage::new_with_object_store(Box::new(object_store));
+        assert!(Tower::restore(&first, &node_pubkey)
+            .unwrap_err()
+            .is_file_missing());
+        assert!(Tower::restore(&second, &node_pubkey)
+            .unwrap_err()
+            .is_file_missing());
+
+        // Both instances saw no tower, only the first write succeeds
+        let first_tower = new_tower_for_tests(&identity_keypair, Some(1));
+        first_tower.save(&first, &identity_keypair).unwrap();
+        let second_tower = new_tower_for_tests(&identity_keypair, Some(2));
+        assert_matches!(
+            second_tower.save(&second, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+
+        // After reloading, the second instance takes over and the first one is stale
+        assert_eq!(Tower::restore(&second, &node_pubkey).unwrap(), first_tower);
+        second_tower.save(&second, &identity_keypair).unwrap();
+        assert_matches!(
+            first_tower.save(&first, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+        assert_eq!(Tower::restore(&first, &node_pubkey).unwrap(), second_tower);
+    }
+}
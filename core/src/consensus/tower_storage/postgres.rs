@@ -0,0 +1,652 @@
+use {
+    super::{
+        corrupt_tower_at, detect_and_deserialize, execute_with_retries, EtcdTowerStorage,
+        SavedTowerVersions, TowerStorage,
+    },
+    crate::consensus::{Result, Tower, TowerError},
+    async_trait::async_trait,
+    solana_pubkey::Pubkey,
+    std::{collections::HashSet, fmt, future::Future, io, sync::Arc, time::Duration},
+};
+
+/// Creates the table `PostgresTowerStorage` stores towers in. A row without an
+/// `instance_id` isn't held by any instance.
+const POSTGRES_TOWERS_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS towers (
+    node_pubkey bytea PRIMARY KEY,
+    instance_id bytea,
+    payload bytea,
+    updated_at timestamptz NOT NULL
+)";
+
+/// The Postgres operations `PostgresTowerStorage` depends on
+#[async_trait]
+trait PostgresClient: Send + Sync {
+    async fn create_schema(&self) -> Result<()>;
+
+    /// Makes `instance_id` hold the row of `node_pubkey`, creating it if
+    /// needed. Unless `force` is set, returns false without writing if another
+    /// instance holds it and has updated it within `stale_lock_age`.
+    async fn lock_instance(
+        &self,
+        node_pubkey: Vec<u8>,
+        instance_id: Vec<u8>,
+        force: bool,
+        stale_lock_age: Duration,
+    ) -> Result<bool>;
+
+    /// Gets the payload of `node_pubkey` if `instance_id` holds its row. The
+    /// returned bool is false if it doesn't.
+    async fn get_if_instance(
+        &self,
+        node_pubkey: Vec<u8>,
+        instance_id: Vec<u8>,
+    ) -> Result<(bool, Option<Vec<u8>>)>;
+
+    /// Upserts the payload of `node_pubkey` in one statement if `instance_id`
+    /// holds its row, returning false without writing if it doesn't
+    async fn upsert_if_instance(
+        &self,
+        node_pubkey: Vec<u8>,
+        instance_id: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<bool>;
+
+    /// Releases the row of `node_pubkey` if `instance_id` holds it
+    async fn unlock_instance(&self, node_pubkey: Vec<u8>, instance_id: Vec<u8>) -> Result<()>;
+}
+
+struct PostgresPool(deadpool_postgres::Pool);
+
+impl PostgresPool {
+    fn postgres_to_tower_error(error: impl fmt::Display) -> TowerError {
+        TowerError::IoError(io::Error::other(error.to_string()))
+    }
+
+    async fn client(&self) -> Result<deadpool_postgres::Object> {
+        self.0.get().await.map_err(Self::postgres_to_tower_error)
+    }
+}
+
+#[async_trait]
+impl PostgresClient for PostgresPool {
+    async fn create_schema(&self) -> Result<()> {
+        self.client()
+            .await?
+            .batch_execute(POSTGRES_TOWERS_SCHEMA)
+            .await
+            .map_err(Self::postgres_to_tower_error)
+    }
+
+    async fn lock_instance(
+        &self,
+        node_pubkey: Vec<u8>,
+        instance_id: Vec<u8>,
+        force: bool,
+        stale_lock_age: Duration,
+    ) -> Result<bool> {
+        let mut client = self.client().await?;
+        let transaction = client
+            .transaction()
+            .await
+            .map_err(Self::postgres_to_tower_error)?;
+        transaction
+            .execute(
+                "INSERT INTO towers (node_pubkey, updated_at) VALUES ($1, now()) \
+                 ON CONFLICT (node_pubkey) DO NOTHING",
+                &[&node_pubkey],
+            )
+            .await
+            .map_err(Self::postgres_to_tower_error)?;
+        // Holds the row until the transaction ends, so that racing instances
+        // see each other's lock
+        let row = transaction
+            .query_one(
+                "SELECT instance_id IS NULL OR instance_id = $2 \
+                 OR updated_at < now() - make_interval(secs => $3) \
+                 FROM towers WHERE node_pubkey = $1 FOR UPDATE",
+                &[&node_pubkey, &instance_id, &stale_lock_age.as_secs_f64()],
+            )
+            .await
+            .map_err(Self::postgres_to_tower_error)?;
+        if !force && !row.get::<_, bool>(0) {
+            return Ok(false);
+        }
+        transaction
+            .execute(
+                "UPDATE towers SET instance_id = $2, updated_at = now() WHERE node_pubkey = $1",
+                &[&node_pubkey, &instance_id],
+            )
+            .await
+            .map_err(Self::postgres_to_tower_error)?;
+        transaction
+            .commit()
+            .await
+            .map_err(Self::postgres_to_tower_error)?;
+        Ok(true)
+    }
+
+    async fn get_if_instance(
+        &self,
+        node_pubkey: Vec<u8>,
+        instance_id: Vec<u8>,
+    ) -> Result<(bool, Option<Vec<u8>>)> {
+        let row = self
+            .client()
+            .await?
+            .query_opt(
+                "SELECT payload FROM towers WHERE node_pubkey = $1 AND instance_id = $2",
+                &[&node_pubkey, &instance_id],
+            )
+            .await
+            .map_err(Self::postgres_to_tower_error)?;
+        Ok(match row {
+            Some(row) => (true, row.get(0)),
+            None => (false, None),
+        })
+    }
+
+    async fn upsert_if_instance(
+        &self,
+        node_pubkey: Vec<u8>,
+        instance_id: Vec<u8>,
+        payload: Vec<u8>,
+    ) -> Result<bool> {
+        let upserted = self
+            .client()
+            .await?
+            .execute(
+                "INSERT INTO towers (node_pubkey, instance_id, payload, updated_at) \
+                 VALUES ($1, $2, $3, now()) \
+                 ON CONFLICT (node_pubkey) DO UPDATE \
+                 SET payload = EXCLUDED.payload, updated_at = EXCLUDED.updated_at \
+                 WHERE towers.instance_id = EXCLUDED.instance_id",
+                &[&node_pubkey, &instance_id, &payload],
+            )
+            .await
+            .map_err(Self::postgres_to_tower_error)?;
+        Ok(upserted == 1)
+    }
+
+    async fn unlock_instance(&self, node_pubkey: Vec<u8>, instance_id: Vec<u8>) -> Result<()> {
+        self.client()
+            .await?
+            .execute(
+                "UPDATE towers SET instance_id = NULL WHERE node_pubkey = $1 AND instance_id = $2",
+                &[&node_pubkey, &instance_id],
+            )
+            .await
+            .map_err(Self::postgres_to_tower_error)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct PostgresTlsConfig {
+    /// PEM certificate of the CA the server's certificate is verified against
+    pub ca_certificate: Vec<u8>,
+    /// PEM certificate and private key to authenticate with, if the server
+    /// requires client certificates
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+// An instance lock is considered stale once its holder hasn't refreshed it for
+// this long
+const STALE_TOWER_LOCK_AGE: Duration = Duration::from_secs(300);
+
+#[derive(Clone)]
+pub struct PostgresTowerStorageConfig {
+    /// Connection string, such as `host=db user=solana dbname=validator`
+    pub dsn: String,
+    pub tls: Option<PostgresTlsConfig>,
+    /// How many connections are kept open
+    pub pool_size: usize,
+    /// How long the instance lock outlives this instance once it stops
+    /// storing towers, after which another instance may acquire it
+    pub stale_lock_age: Duration,
+    /// How long each Postgres request may take before it's retried
+    pub operation_timeout: Duration,
+    /// How many times a failed Postgres request is retried
+    pub max_retries: usize,
+}
+
+impl Default for PostgresTowerStorageConfig {
+    fn default() -> Self {
+        Self {
+            dsn: String::default(),
+            tls: None,
+            pool_size: 2,
+            stale_lock_age: STALE_TOWER_LOCK_AGE,
+            operation_timeout: Duration::from_secs(1),
+            max_retries: 2,
+        }
+    }
+}
+
+/// Stores towers in the `towers` table of a Postgres database, see
+/// `create_schema()`. Like `EtcdTowerStorage`, a tower is guarded by an
+/// instance lock: the `instance_id` of its row, which is taken with the row
+/// locked and checked by every store.
+pub struct PostgresTowerStorage {
+    client: Arc<dyn PostgresClient>,
+    operation_timeout: Duration,
+    max_retries: usize,
+    stale_lock_age: Duration,
+    instance_id: [u8; 8],
+    force: bool,
+    // Towers whose row this instance holds
+    instance_locks: tokio::sync::Mutex<HashSet<Pubkey>>,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl PostgresTowerStorage {
+    pub fn new(config: PostgresTowerStorageConfig) -> Result<Self> {
+        let pg_config = config
+            .dsn
+            .parse::<tokio_postgres::Config>()
+            .map_err(PostgresPool::postgres_to_tower_error)?;
+        let manager_config = deadpool_postgres::ManagerConfig {
+            recycling_method: deadpool_postgres::RecyclingMethod::Fast,
+        };
+        let manager = match &config.tls {
+            Some(tls_config) => {
+                let mut builder =
+                    openssl::ssl::SslConnector::builder(openssl::ssl::SslMethod::tls())
+                        .map_err(PostgresPool::postgres_to_tower_error)?;
+                let ca_certificate = openssl::x509::X509::from_pem(&tls_config.ca_certificate)
+                    .map_err(PostgresPool::postgres_to_tower_error)?;
+                builder
+                    .cert_store_mut()
+                    .add_cert(ca_certificate)
+                    .map_err(PostgresPool::postgres_to_tower_error)?;
+                if let Some((certificate, private_key)) = &tls_config.identity {
+                    let certificate = openssl::x509::X509::from_pem(certificate)
+                        .map_err(PostgresPool::postgres_to_tower_error)?;
+                    let private_key = openssl::pkey::PKey::private_key_from_pem(private_key)
+                        .map_err(PostgresPool::postgres_to_tower_error)?;
+                    builder
+                        .set_certificate(&certificate)
+                        .and_then(|()| builder.set_private_key(&private_key))
+                        .map_err(PostgresPool::postgres_to_tower_error)?;
+                }
+                let tls = postgres_openssl::MakeTlsConnector::new(builder.build());
+                deadpool_postgres::Manager::from_config(pg_config, tls, manager_config)
+            }
+            None => deadpool_postgres::Manager::from_config(
+                pg_config,
+                tokio_postgres::NoTls,
+                manager_config,
+            ),
+        };
+        let pool = deadpool_postgres::Pool::builder(manager)
+            .max_size(config.pool_size)
+            .runtime(deadpool_postgres::Runtime::Tokio1)
+            .build()
+            .map_err(PostgresPool::postgres_to_tower_error)?;
+        Ok(Self::new_with_client(
+            Arc::new(PostgresPool(pool)),
+            &config,
+            EtcdTowerStorage::new_runtime(),
+        ))
+    }
+
+    fn new_with_client(
+        client: Arc<dyn PostgresClient>,
+        config: &PostgresTowerStorageConfig,
+        runtime: tokio::runtime::Runtime,
+    ) -> Self {
+        Self {
+            client,
+            operation_timeout: config.operation_timeout,
+            max_retries: config.max_retries,
+            stale_lock_age: config.stale_lock_age,
+            instance_id: solana_time_utils::timestamp().to_le_bytes(),
+            force: false,
+            instance_locks: tokio::sync::Mutex::default(),
+            runtime,
+        }
+    }
+
+    /// Take over the instance lock even if another instance holds it, rather
+    /// than failing
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Creates the `towers` table if it doesn't exist
+    pub fn create_schema(&self) -> Result<()> {
+        self.runtime
+            .block_on(self.execute(|client| async move { client.create_schema().await }))
+    }
+
+    /// Runs `operation`, retrying it on errors and timeouts
+    async fn execute<T, F, Fut>(&self, mut operation: F) -> Result<T>
+    where
+        F: FnMut(Arc<dyn PostgresClient>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        execute_with_retries(
+            self.operation_timeout,
+            self.max_retries,
+            |_| operation(self.client.clone()),
+            |err, retry| {
+                if retry {
+                    warn!("Postgres request failed, retrying: {err}");
+                }
+            },
+        )
+        .await
+        .map_err(|err| TowerError::IoError(io::Error::other(format!("Postgres: {err}"))))
+    }
+
+    async fn lock_instance(&self, node_pubkey: &Pubkey) -> Result<()> {
+        let mut instance_locks = self.instance_locks.lock().await;
+        if instance_locks.contains(node_pubkey) {
+            return Ok(());
+        }
+
+        // Retrying is safe, taking the lock again is a no-op
+        let locked = self
+            .execute(|client| {
+                let node_pubkey = node_pubkey.to_bytes().to_vec();
+                let instance_id = self.instance_id.to_vec();
+                let (force, stale_lock_age) = (self.force, self.stale_lock_age);
+                async move {
+                    client
+                        .lock_instance(node_pubkey, instance_id, force, stale_lock_age)
+                        .await
+                }
+            })
+            .await?;
+        if !locked {
+            return Err(TowerError::TowerLocked(format!(
+                "Postgres towers row for {node_pubkey} is held by another instance"
+            )));
+        }
+        instance_locks.insert(*node_pubkey);
+        Ok(())
+    }
+
+    async fn lost_instance_lock(&self, node_pubkey: &Pubkey) -> TowerError {
+        // Reacquire it next time
+        self.instance_locks.lock().await.remove(node_pubkey);
+        error!("Lost Postgres instance lock for {node_pubkey}");
+        TowerError::IoError(io::Error::other(format!(
+            "Lost Postgres instance lock for {node_pubkey}"
+        )))
+    }
+
+    async fn load_saved(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.lock_instance(node_pubkey).await?;
+        let (held, payload) = self
+            .execute(|client| {
+                let node_pubkey = node_pubkey.to_bytes().to_vec();
+                let instance_id = self.instance_id.to_vec();
+                async move { client.get_if_instance(node_pubkey, instance_id).await }
+            })
+            .await?;
+        if !held {
+            return Err(self.lost_instance_lock(node_pubkey).await);
+        }
+        let payload = payload.ok_or(TowerError::TowerNotFound(*node_pubkey))?;
+        detect_and_deserialize(&payload, node_pubkey).map_err(corrupt_tower_at(format!(
+            "Postgres towers row for {node_pubkey}"
+        )))
+    }
+
+    async fn store_tower(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let pubkey = saved_tower.pubkey();
+        self.lock_instance(&pubkey).await?;
+        let payload = saved_tower.serialize()?;
+        // Retrying is safe, the upsert only writes while this instance holds
+        // the row
+        let stored = self
+            .execute(|client| {
+                let node_pubkey = pubkey.to_bytes().to_vec();
+                let instance_id = self.instance_id.to_vec();
+                let payload = payload.clone();
+                async move {
+                    client
+                        .upsert_if_instance(node_pubkey, instance_id, payload)
+                        .await
+                }
+            })
+            .await
+            .map_err(|err| {
+                error!("Failed to write Postgres saved tower: {err}");
+                err
+            })?;
+        if !stored {
+            return Err(self.lost_instance_lock(&pubkey).await);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PostgresTowerStorage {
+    fn drop(&mut self) {
+        // Release the instance locks now rather than once they're stale
+        let instance_locks = std::mem::take(self.instance_locks.get_mut());
+        for node_pubkey in instance_locks {
+            let _ = self.runtime.block_on(async {
+                tokio::time::timeout(
+                    self.operation_timeout,
+                    self.client.unlock_instance(
+                        node_pubkey.to_bytes().to_vec(),
+                        self.instance_id.to_vec(),
+                    ),
+                )
+                .await
+            });
+        }
+    }
+}
+
+impl TowerStorage for PostgresTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.runtime
+            .block_on(self.load_saved(node_pubkey))?
+            .try_into_tower(node_pubkey)
+            .map_err(corrupt_tower_at(format!(
+                "Postgres towers row for {node_pubkey}"
+            )))
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        self.runtime.block_on(self.store_tower(saved_tower))
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.runtime.block_on(self.load_saved(node_pubkey))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::consensus::tower_storage::test::new_tower_for_tests,
+        solana_keypair::Keypair,
+        solana_signer::Signer,
+        std::{
+            collections::HashMap,
+            sync::{
+                atomic::{AtomicBool, Ordering},
+                Mutex,
+            },
+            thread,
+            time::Instant,
+        },
+    };
+
+    // (instance_id, payload, updated_at)
+    type MockTowerRow = (Option<Vec<u8>>, Option<Vec<u8>>, Instant);
+
+    /// In-memory `PostgresClient`
+    #[derive(Default)]
+    struct MockPostgresClient {
+        towers: Mutex<HashMap<Vec<u8>, MockTowerRow>>,
+        schema_created: AtomicBool,
+    }
+
+    #[async_trait]
+    impl PostgresClient for MockPostgresClient {
+        async fn create_schema(&self) -> Result<()> {
+            self.schema_created.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+
+        async fn lock_instance(
+            &self,
+            node_pubkey: Vec<u8>,
+            instance_id: Vec<u8>,
+            force: bool,
+            stale_lock_age: Duration,
+        ) -> Result<bool> {
+            let mut towers = self.towers.lock().unwrap();
+            let (holder, _, updated_at) =
+                towers
+                    .entry(node_pubkey)
+                    .or_insert((None, None, Instant::now()));
+            let free = holder.as_ref().is_none_or(|holder| *holder == instance_id)
+                || updated_at.elapsed() > stale_lock_age;
+            if !force && !free {
+                return Ok(false);
+            }
+            *holder = Some(instance_id);
+            *updated_at = Instant::now();
+            Ok(true)
+        }
+
+        async fn get_if_instance(
+            &self,
+            node_pubkey: Vec<u8>,
+            instance_id: Vec<u8>,
+        ) -> Result<(bool, Option<Vec<u8>>)> {
+            let towers = self.towers.lock().unwrap();
+            Ok(match towers.get(&node_pubkey) {
+                Some((Some(holder), payload, _)) if *holder == instance_id => {
+                    (true, payload.clone())
+                }
+                _ => (false, None),
+            })
+        }
+
+        async fn upsert_if_instance(
+            &self,
+            node_pubkey: Vec<u8>,
+            instance_id: Vec<u8>,
+            payload: Vec<u8>,
+        ) -> Result<bool> {
+            let mut towers = self.towers.lock().unwrap();
+            let (holder, row_payload, updated_at) = towers.entry(node_pubkey).or_insert((
+                Some(instance_id.clone()),
+                None,
+                Instant::now(),
+            ));
+            if *holder != Some(instance_id) {
+                return Ok(false);
+            }
+            *row_payload = Some(payload);
+            *updated_at = Instant::now();
+            Ok(true)
+        }
+
+        async fn unlock_instance(&self, node_pubkey: Vec<u8>, instance_id: Vec<u8>) -> Result<()> {
+            let mut towers = self.towers.lock().unwrap();
+            if let Some((holder, _, _)) = towers.get_mut(&node_pubkey) {
+                if *holder == Some(instance_id) {
+                    *holder = None;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn new_postgres_tower_storage_for_tests(
+        client: &Arc<MockPostgresClient>,
+        instance_id: u8,
+        stale_lock_age: Duration,
+    ) -> PostgresTowerStorage {
+        let mut tower_storage = PostgresTowerStorage::new_with_client(
+            client.clone(),
+            &PostgresTowerStorageConfig {
+                stale_lock_age,
+                ..PostgresTowerStorageConfig::default()
+            },
+            EtcdTowerStorage::new_runtime(),
+        );
+        tower_storage.instance_id = [instance_id; 8];
+        tower_storage
+    }
+
+    #[test]
+    fn test_postgres_tower_storage() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockPostgresClient::default());
+        let first = new_postgres_tower_storage_for_tests(&client, 1, STALE_TOWER_LOCK_AGE);
+        let second = new_postgres_tower_storage_for_tests(&client, 2, STALE_TOWER_LOCK_AGE);
+
+        first.create_schema().unwrap();
+        assert!(client.schema_created.load(Ordering::Relaxed));
+        assert_matches!(
+            Tower::restore(&first, &node_pubkey),
+            Err(TowerError::TowerNotFound(_))
+        );
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&first, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&first, &node_pubkey).unwrap(), tower);
+
+        // The second instance fails fast without disturbing the first
+        assert_matches!(
+            Tower::restore(&second, &node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+        tower.save(&first, &identity_keypair).unwrap();
+
+        // unless it forces a takeover
+        let second = second.with_force(true);
+        assert_eq!(Tower::restore(&second, &node_pubkey).unwrap(), tower);
+        assert_matches!(
+            tower.save(&first, &identity_keypair),
+            Err(TowerError::IoError(err)) if err.to_string().contains("Lost Postgres instance lock")
+        );
+        assert_matches!(
+            Tower::restore(&first, &node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+        tower.save(&second, &identity_keypair).unwrap();
+
+        // Dropping the holder releases the lock right away
+        drop(second);
+        let third = new_postgres_tower_storage_for_tests(&client, 3, STALE_TOWER_LOCK_AGE);
+        assert_eq!(Tower::restore(&third, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_postgres_tower_storage_stale_lock() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = Arc::new(MockPostgresClient::default());
+        let stale_lock_age = Duration::from_millis(100);
+        let first = new_postgres_tower_storage_for_tests(&client, 1, stale_lock_age);
+        let second = new_postgres_tower_storage_for_tests(&client, 2, stale_lock_age);
+
+        Tower::restore(&first, &node_pubkey).unwrap_err();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&first, &identity_keypair).unwrap();
+        assert_matches!(
+            Tower::restore(&second, &node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+
+        // The first instance stops storing towers, e.g. it crashed
+        thread::sleep(stale_lock_age * 2);
+        assert_eq!(Tower::restore(&second, &node_pubkey).unwrap(), tower);
+        assert_matches!(
+            tower.save(&first, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+    }
+}
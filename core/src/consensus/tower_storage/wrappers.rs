@@ -0,0 +1,2271 @@
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use {
+    super::{AsyncTowerStorage, SavedTowerSummary, SavedTowerVersions, TowerStorage},
+    crate::consensus::{tower1_14_11::Tower1_14_11, Result, Tower, TowerError},
+    aes_gcm_siv::{
+        aead::{Aead, KeyInit, Payload},
+        Aes256GcmSiv, Nonce,
+    },
+    solana_clock::Slot,
+    solana_pubkey::Pubkey,
+    std::{
+        collections::{HashMap, HashSet, VecDeque},
+        fmt,
+        fs::{self, File, OpenOptions},
+        io::{self, Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Condvar, Mutex, RwLock,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::{Duration, Instant, SystemTime},
+    },
+};
+
+/// Stores towers in the background so that `store()` doesn't wait on the
+/// underlying storage.
+///
+/// `store()` returns before the tower is durable. Under `ReplayStage`, which
+/// stores the tower before sending its vote, a vote can therefore go out before
+/// the tower that records it is persisted, and a crash in between can lose that
+/// tower. Only use this where that is acceptable.
+///
+/// Stores for a pubkey are applied in order, with at most one outstanding:
+/// `store()` first waits for the previous store, and if that failed returns its
+/// error instead of submitting the new tower. `load()` waits for the
+/// outstanding store too. Stores for different pubkeys don't wait on each
+/// other.
+pub struct PipelinedTowerStorage<T: AsyncTowerStorage + 'static> {
+    inner: Arc<T>,
+    runtime: Arc<tokio::runtime::Runtime>,
+    pending: Mutex<HashMap<Pubkey, PendingStore>>,
+}
+
+// Outstanding store for a pubkey. Its lock is held while waiting for the
+// store, so that stores for the pubkey stay in order.
+type PendingStore = Arc<Mutex<Option<tokio::task::JoinHandle<Result<()>>>>>;
+
+impl<T: AsyncTowerStorage + 'static> PipelinedTowerStorage<T> {
+    pub fn new(inner: Arc<T>, runtime: Arc<tokio::runtime::Runtime>) -> Self {
+        Self {
+            inner,
+            runtime,
+            pending: Mutex::default(),
+        }
+    }
+
+    /// Waits for all outstanding stores, returning the first error
+    pub fn flush(&self) -> Result<()> {
+        let pending: Vec<_> = self.pending.lock().unwrap().values().cloned().collect();
+        let mut result = Ok(());
+        for pending_store in pending {
+            let store_result = Self::wait_for(&mut pending_store.lock().unwrap());
+            if result.is_ok() {
+                result = store_result;
+            }
+        }
+        result
+    }
+
+    fn pending_store(&self, node_pubkey: &Pubkey) -> PendingStore {
+        self.pending
+            .lock()
+            .unwrap()
+            .entry(*node_pubkey)
+            .or_default()
+            .clone()
+    }
+
+    /// Waits for the outstanding store, if any
+    fn wait_for(pending_store: &mut Option<tokio::task::JoinHandle<Result<()>>>) -> Result<()> {
+        match pending_store.take() {
+            // Not `Runtime::block_on()`, which panics when called from within
+            // a runtime
+            Some(store) => futures::executor::block_on(store)
+                .map_err(|err| TowerError::IoError(io::Error::other(err)))?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: AsyncTowerStorage + 'static> TowerStorage for PipelinedTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        Self::wait_for(&mut self.pending_store(node_pubkey).lock().unwrap())?;
+        let load = self.runtime.spawn({
+            let inner = self.inner.clone();
+            let node_pubkey = *node_pubkey;
+            async move { inner.load(&node_pubkey).await }
+        });
+        futures::executor::block_on(load)
+            .map_err(|err| TowerError::IoError(io::Error::other(err)))?
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let pending_store = self.pending_store(&saved_tower.pubkey());
+        let mut pending_store = pending_store.lock().unwrap();
+        Self::wait_for(&mut pending_store)?;
+        *pending_store = Some(self.runtime.spawn({
+            let inner = self.inner.clone();
+            let saved_tower = saved_tower.clone();
+            async move { inner.store(&saved_tower).await }
+        }));
+        Ok(())
+    }
+}
+
+impl<T: AsyncTowerStorage + 'static> Drop for PipelinedTowerStorage<T> {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            error!("Failed to store tower: {err}");
+        }
+    }
+}
+
+/// Buffers stored towers and stores them from a background thread, at most
+/// once per `min_interval` for each pubkey: a tower is stored `min_interval`
+/// after it was first buffered, and towers stored in the meantime replace it.
+///
+/// A failed background store is returned by the next `store()` or `flush()`,
+/// and `store()` then doesn't buffer its tower. `load()` first stores the
+/// buffered tower, and dropping the storage stores all of them.
+pub struct ThrottledTowerStorage<T: TowerStorage + 'static> {
+    shared: Arc<ThrottledTowerStorageShared<T>>,
+    flusher: Option<JoinHandle<()>>,
+}
+
+struct ThrottledTowerStorageShared<T> {
+    inner: Arc<T>,
+    min_interval: Duration,
+    state: Mutex<ThrottledTowerStorageState>,
+    state_changed: Condvar,
+    // Held while storing into `inner`, so that towers are stored in order
+    store_lock: Mutex<()>,
+}
+
+#[derive(Default)]
+struct ThrottledTowerStorageState {
+    // Buffered tower for each pubkey, and when the first of the towers it
+    // replaced was buffered
+    pending: HashMap<Pubkey, (Instant, SavedTowerVersions)>,
+    error: Option<TowerError>,
+    exit: bool,
+}
+
+impl<T: TowerStorage + 'static> ThrottledTowerStorage<T> {
+    pub fn new(inner: Arc<T>, min_interval: Duration) -> Self {
+        let shared = Arc::new(ThrottledTowerStorageShared {
+            inner,
+            min_interval,
+            state: Mutex::default(),
+            state_changed: Condvar::new(),
+            store_lock: Mutex::default(),
+        });
+        let flusher = Builder::new()
+            .name("solTowerFlush".to_string())
+            .spawn({
+                let shared = shared.clone();
+                move || shared.run_flusher()
+            })
+            .unwrap();
+        Self {
+            shared,
+            flusher: Some(flusher),
+        }
+    }
+
+    /// Stores all buffered towers, returning the first error
+    pub fn flush(&self) -> Result<()> {
+        let result = self.shared.store_pending(|_, _| true);
+        match self.shared.state.lock().unwrap().error.take() {
+            Some(err) => Err(err),
+            None => result,
+        }
+    }
+
+    /// Stores `saved_tower` now, dropping the buffered tower it replaces. For
+    /// when the tower must be durable, such as before a restart.
+    pub fn force_store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let _store_lock = self.shared.store_lock.lock().unwrap();
+        let mut state = self.shared.state.lock().unwrap();
+        state.pending.remove(&saved_tower.pubkey());
+        drop(state);
+        self.shared.inner.store(saved_tower)
+    }
+}
+
+impl<T: TowerStorage> ThrottledTowerStorageShared<T> {
+    fn run_flusher(&self) {
+        let mut state = self.state.lock().unwrap();
+        while !state.exit {
+            let now = Instant::now();
+            let next_due = state
+                .pending
+                .values()
+                .map(|(buffered, _)| *buffered + self.min_interval)
+                .min();
+            state = match next_due {
+                Some(due) if due <= now => {
+                    drop(state);
+                    let result =
+                        self.store_pending(|_, buffered| buffered + self.min_interval <= now);
+                    let mut state = self.state.lock().unwrap();
+                    if let Err(err) = result {
+                        error!("Failed to store tower: {err}");
+                        state.error.get_or_insert(err);
+                    }
+                    state
+                }
+                Some(due) => self.state_changed.wait_timeout(state, due - now).unwrap().0,
+                None => self.state_changed.wait(state).unwrap(),
+            };
+        }
+    }
+
+    /// Stores the buffered towers for which `is_due(pubkey, buffered)` holds,
+    /// returning the first error
+    fn store_pending(&self, is_due: impl Fn(&Pubkey, Instant) -> bool) -> Result<()> {
+        let _store_lock = self.store_lock.lock().unwrap();
+        let saved_towers: Vec<_> = {
+            let mut state = self.state.lock().unwrap();
+            let due: Vec<_> = state
+                .pending
+                .iter()
+                .filter(|(pubkey, (buffered, _))| is_due(pubkey, *buffered))
+                .map(|(pubkey, _)| *pubkey)
+                .collect();
+            due.iter()
+                .filter_map(|pubkey| state.pending.remove(pubkey))
+                .map(|(_, saved_tower)| saved_tower)
+                .collect()
+        };
+        let mut result = Ok(());
+        for saved_tower in saved_towers {
+            let store_result = self.inner.store(&saved_tower);
+            if result.is_ok() {
+                result = store_result;
+            }
+        }
+        result
+    }
+}
+
+impl<T: TowerStorage + 'static> TowerStorage for ThrottledTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.shared
+            .store_pending(|pubkey, _| pubkey == node_pubkey)?;
+        self.shared.inner.load(node_pubkey)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let mut state = self.shared.state.lock().unwrap();
+        if let Some(err) = state.error.take() {
+            return Err(err);
+        }
+        let pubkey = saved_tower.pubkey();
+        let buffered = state
+            .pending
+            .get(&pubkey)
+            .map_or_else(Instant::now, |(buffered, _)| *buffered);
+        state
+            .pending
+            .insert(pubkey, (buffered, saved_tower.clone()));
+        self.shared.state_changed.notify_one();
+        Ok(())
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.shared
+            .store_pending(|pubkey, _| pubkey == node_pubkey)?;
+        self.shared.inner.load_saved_tower(node_pubkey)
+    }
+}
+
+impl<T: TowerStorage + 'static> Drop for ThrottledTowerStorage<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().exit = true;
+        self.shared.state_changed.notify_one();
+        if let Some(flusher) = self.flusher.take() {
+            if let Err(err) = flusher.join() {
+                // The inner storage panicked storing a tower, don't store the
+                // buffered towers into it again
+                error!("Tower flusher thread panicked: {err:?}");
+                return;
+            }
+        }
+        if let Err(err) = self.flush() {
+            error!("Failed to store tower: {err}");
+        }
+    }
+}
+
+/// How `CompositeTowerStorage` treats backends that fail to store a tower
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CompositeWritePolicy {
+    /// Fail if any backend fails to store the tower
+    RequireAll,
+    /// Fail only if every backend fails to store the tower
+    #[default]
+    BestEffort,
+}
+
+/// Writes towers to every backend and loads the most recent tower found in
+/// any of them, for redundancy
+pub struct CompositeTowerStorage {
+    backends: Vec<Box<dyn TowerStorage>>,
+    write_policy: CompositeWritePolicy,
+}
+
+impl CompositeTowerStorage {
+    pub fn new(backends: Vec<Box<dyn TowerStorage>>, write_policy: CompositeWritePolicy) -> Self {
+        assert!(
+            !backends.is_empty(),
+            "CompositeTowerStorage requires at least one backend"
+        );
+        Self {
+            backends,
+            write_policy,
+        }
+    }
+}
+
+impl TowerStorage for CompositeTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        let mut newest: Option<(usize, Tower)> = None;
+        let mut errors = vec![];
+        for (index, backend) in self.backends.iter().enumerate() {
+            match backend.load(node_pubkey) {
+                Ok(tower) => {
+                    let recency =
+                        |tower: &Tower| (tower.vote_state.root_slot, tower.last_voted_slot());
+                    if newest
+                        .as_ref()
+                        .is_none_or(|(_, newest)| recency(&tower) > recency(newest))
+                    {
+                        newest = Some((index, tower));
+                    }
+                }
+                Err(err) => {
+                    if !err.is_file_missing() {
+                        warn!("Failed to load tower from tower storage backend {index}: {err}");
+                    }
+                    errors.push(err);
+                }
+            }
+        }
+
+        if let Some((index, tower)) = newest {
+            info!("Loaded tower for {node_pubkey} from tower storage backend {index}");
+            return Ok(tower);
+        }
+        // Prefer reporting a failure over a missing tower, so that the caller
+        // doesn't start from a fresh tower when a saved one may exist
+        let index = errors
+            .iter()
+            .position(|err| !err.is_file_missing())
+            .unwrap_or_default();
+        Err(errors.swap_remove(index))
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let mut first_error = None;
+        let mut num_failed = 0;
+        for (index, backend) in self.backends.iter().enumerate() {
+            if let Err(err) = backend.store(saved_tower) {
+                warn!("Failed to store tower to tower storage backend {index}: {err}");
+                num_failed += 1;
+                first_error.get_or_insert(err);
+            }
+        }
+
+        match first_error {
+            Some(err)
+                if num_failed == self.backends.len()
+                    || self.write_policy == CompositeWritePolicy::RequireAll =>
+            {
+                Err(err)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Encrypted tower data is the magic, a nonce, and the AES-256-GCM-SIV
+// ciphertext of the data, with the node pubkey as associated data
+const ENCRYPTED_TOWER_MAGIC: [u8; 4] = *b"ETWR";
+const ENCRYPTED_TOWER_NONCE_LEN: usize = 12;
+
+/// Encrypts the tower data of saved towers before storing them in the inner
+/// storage, and decrypts it on load before the signature is verified. The
+/// inner storage must support `load_saved_tower()`.
+pub struct EncryptedTowerStorage<T> {
+    inner: T,
+    cipher: Aes256GcmSiv,
+    allow_plaintext_fallback: bool,
+}
+
+impl<T: TowerStorage> EncryptedTowerStorage<T> {
+    pub fn new(inner: T, key: [u8; 32]) -> Self {
+        Self {
+            inner,
+            cipher: Aes256GcmSiv::new(&key.into()),
+            allow_plaintext_fallback: false,
+        }
+    }
+
+    /// Reads the key from `keyfile`, which must contain exactly 32 bytes
+    pub fn new_with_keyfile(inner: T, keyfile: &Path) -> Result<Self> {
+        let key = fs::read(keyfile)?;
+        let key = <[u8; 32]>::try_from(key.as_slice()).map_err(|_| {
+            TowerError::IoError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "tower encryption key {} must be 32 bytes, found {}",
+                    keyfile.display(),
+                    key.len()
+                ),
+            ))
+        })?;
+        Ok(Self::new(inner, key))
+    }
+
+    /// Also load towers stored unencrypted, such as before encryption was
+    /// enabled
+    pub fn allow_plaintext_fallback(mut self, allow_plaintext_fallback: bool) -> Self {
+        self.allow_plaintext_fallback = allow_plaintext_fallback;
+        self
+    }
+
+    fn encrypt(&self, node_pubkey: &Pubkey, data: &[u8]) -> Result<Vec<u8>> {
+        let nonce: [u8; ENCRYPTED_TOWER_NONCE_LEN] = rand::random();
+        let payload = Payload {
+            msg: data,
+            aad: node_pubkey.as_ref(),
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| TowerError::IoError(io::Error::other("failed to encrypt tower")))?;
+        Ok([&ENCRYPTED_TOWER_MAGIC[..], &nonce, &ciphertext].concat())
+    }
+
+    fn decrypt(&self, node_pubkey: &Pubkey, data: &[u8]) -> Result<Vec<u8>> {
+        let Some(encrypted) = data.strip_prefix(&ENCRYPTED_TOWER_MAGIC) else {
+            if self.allow_plaintext_fallback {
+                return Ok(data.to_vec());
+            }
+            return Err(TowerError::DecryptionFailed(
+                "the saved tower is not encrypted".to_string(),
+            ));
+        };
+        if encrypted.len() < ENCRYPTED_TOWER_NONCE_LEN {
+            return Err(TowerError::DecryptionFailed("truncated nonce".to_string()));
+        }
+        let (nonce, ciphertext) = encrypted.split_at(ENCRYPTED_TOWER_NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: node_pubkey.as_ref(),
+        };
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| {
+                TowerError::DecryptionFailed("wrong key or tampered ciphertext".to_string())
+            })
+    }
+}
+
+impl<T: TowerStorage> TowerStorage for EncryptedTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.load_saved_tower(node_pubkey)?
+            .try_into_tower(node_pubkey)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let pubkey = saved_tower.pubkey();
+        let saved_tower = saved_tower.try_map_data(|data| self.encrypt(&pubkey, data))?;
+        self.inner.store(&saved_tower)
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.inner
+            .load_saved_tower(node_pubkey)?
+            .try_map_data(|data| self.decrypt(node_pubkey, data))
+    }
+}
+
+// Audit log records are framed as: payload length (u32), crc32 of the payload
+// (u32), payload (bincode `TowerAuditRecord`), all little endian
+const TOWER_AUDIT_FRAME_HEADER_LEN: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TowerAuditOp {
+    Store,
+    Load,
+}
+
+/// A tower stored or loaded through `AuditingTowerStorage`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TowerAuditRecord {
+    pub op: TowerAuditOp,
+    /// Milliseconds since the unix epoch
+    pub timestamp: u64,
+    pub node_pubkey: Pubkey,
+    /// Only known for stored towers if they were saved as `SavedTower2`
+    pub last_vote_slot: Option<Slot>,
+    pub root_slot: Option<Slot>,
+    /// blake3 of the signed tower data, so that a load can be matched with the
+    /// store that wrote it
+    pub payload_hash: [u8; 32],
+}
+
+impl TowerAuditRecord {
+    fn new_store(saved_tower: &SavedTowerVersions) -> Self {
+        let (last_vote_slot, root_slot) = match saved_tower {
+            SavedTowerVersions::V2(t) => (t.last_vote_slot, t.root_slot),
+            _ => (None, None),
+        };
+        Self {
+            op: TowerAuditOp::Store,
+            timestamp: solana_time_utils::timestamp(),
+            node_pubkey: saved_tower.pubkey(),
+            last_vote_slot,
+            root_slot,
+            payload_hash: *blake3::hash(saved_tower.data()).as_bytes(),
+        }
+    }
+
+    fn new_load(tower: &Tower) -> Result<Self> {
+        // The signed tower data is always in 1_14_11 format
+        let data = bincode::serialize(&Tower1_14_11::from(tower.clone()))?;
+        Ok(Self {
+            op: TowerAuditOp::Load,
+            timestamp: solana_time_utils::timestamp(),
+            node_pubkey: tower.node_pubkey,
+            last_vote_slot: tower.last_voted_slot(),
+            root_slot: tower.vote_state.root_slot,
+            payload_hash: *blake3::hash(&data).as_bytes(),
+        })
+    }
+}
+
+struct TowerAuditJournal {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_len: u64,
+}
+
+impl TowerAuditJournal {
+    fn open(path: PathBuf, max_len: u64) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        // Drop a record torn by a crash, so that the records appended after it
+        // can be read
+        let data = fs::read(&path)?;
+        let (_, valid_len) = read_audit_records(&data);
+        if valid_len < data.len() {
+            warn!(
+                "Truncating {} torn or corrupt bytes at the end of tower audit log {}",
+                data.len() - valid_len,
+                path.display()
+            );
+            file.set_len(valid_len as u64)?;
+        }
+        let len = valid_len as u64;
+        Ok(Self {
+            path,
+            file,
+            len,
+            max_len,
+        })
+    }
+
+    fn append(&mut self, record: &TowerAuditRecord) -> Result<()> {
+        let payload = bincode::serialize(record)?;
+        let mut frame = Vec::with_capacity(TOWER_AUDIT_FRAME_HEADER_LEN + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        frame.extend_from_slice(&payload);
+
+        if self.len > 0 && self.len + frame.len() as u64 > self.max_len {
+            fs::rename(&self.path, rotated_audit_log_path(&self.path))?;
+            *self = Self::open(self.path.clone(), self.max_len)?;
+        }
+        self.file.write_all(&frame)?;
+        self.len += frame.len() as u64;
+        Ok(())
+    }
+}
+
+fn rotated_audit_log_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".1");
+    PathBuf::from(path)
+}
+
+/// Delegates to the inner storage, and appends a `TowerAuditRecord` of every
+/// successful store and load to the audit log at `path`. Once the log would
+/// grow past `max_len` bytes it is moved to `<path>.1`, replacing the previous
+/// one. Failing to write the audit log doesn't fail the store or load.
+pub struct AuditingTowerStorage<T> {
+    inner: T,
+    journal: Mutex<TowerAuditJournal>,
+}
+
+impl<T: TowerStorage> AuditingTowerStorage<T> {
+    pub fn new(inner: T, path: PathBuf, max_len: u64) -> Result<Self> {
+        Ok(Self {
+            inner,
+            journal: Mutex::new(TowerAuditJournal::open(path, max_len)?),
+        })
+    }
+
+    fn record(&self, record: Result<TowerAuditRecord>) {
+        let mut journal = self.journal.lock().unwrap();
+        if let Err(err) = record.and_then(|record| journal.append(&record)) {
+            warn!(
+                "Unable to append to tower audit log {}: {err}",
+                journal.path.display()
+            );
+        }
+    }
+}
+
+impl<T: TowerStorage> TowerStorage for AuditingTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        let tower = self.inner.load(node_pubkey)?;
+        self.record(TowerAuditRecord::new_load(&tower));
+        Ok(tower)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        self.inner.store(saved_tower)?;
+        self.record(Ok(TowerAuditRecord::new_store(saved_tower)));
+        Ok(())
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.inner.load_saved_tower(node_pubkey)
+    }
+}
+
+/// Reads the audit log written by `AuditingTowerStorage` to `path`, oldest
+/// record first, including the rotated log. A torn or corrupt record ends the
+/// log it's found in.
+pub fn read_audit_log(path: &Path) -> Result<Vec<TowerAuditRecord>> {
+    let mut records = vec![];
+    for path in [rotated_audit_log_path(path), path.to_path_buf()] {
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        let (mut log_records, valid_len) = read_audit_records(&data);
+        if valid_len < data.len() {
+            warn!(
+                "Skipping {} torn or corrupt bytes at the end of tower audit log {}",
+                data.len() - valid_len,
+                path.display()
+            );
+        }
+        records.append(&mut log_records);
+    }
+    Ok(records)
+}
+
+// Returns the records up to the first torn or corrupt one, and their length
+fn read_audit_records(data: &[u8]) -> (Vec<TowerAuditRecord>, usize) {
+    let mut records = vec![];
+    let mut remaining = data;
+    while let Some(record) = read_audit_record(&mut remaining) {
+        records.push(record);
+    }
+    (records, data.len() - remaining.len())
+}
+
+fn read_audit_record(data: &mut &[u8]) -> Option<TowerAuditRecord> {
+    let header = data.get(..TOWER_AUDIT_FRAME_HEADER_LEN)?;
+    let (len, crc) = header.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    let crc = u32::from_le_bytes(crc.try_into().unwrap());
+    let payload = data.get(TOWER_AUDIT_FRAME_HEADER_LEN..TOWER_AUDIT_FRAME_HEADER_LEN + len)?;
+    if crc32fast::hash(payload) != crc {
+        return None;
+    }
+    let record = bincode::deserialize(payload).ok()?;
+    *data = &data[TOWER_AUDIT_FRAME_HEADER_LEN + len..];
+    Some(record)
+}
+
+const DEFAULT_TOWER_STORE_MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Retries stores that fail with a transient error, up to `max_attempts`
+/// attempts in all, doubling the wait between attempts from `initial_backoff`
+/// up to a maximum. Other errors fail the store immediately.
+pub struct RetryingTowerStorage<T> {
+    inner: T,
+    max_attempts: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl<T: TowerStorage> RetryingTowerStorage<T> {
+    pub fn new(inner: T, max_attempts: usize, initial_backoff: Duration) -> Self {
+        Self {
+            inner,
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+            max_backoff: DEFAULT_TOWER_STORE_MAX_BACKOFF,
+        }
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+}
+
+impl<T: TowerStorage> TowerStorage for RetryingTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.inner.load(node_pubkey)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let mut backoff = self.initial_backoff.min(self.max_backoff);
+        let mut attempt = 1;
+        loop {
+            let err = match self.inner.store(saved_tower) {
+                Ok(()) => return Ok(()),
+                Err(err) if !err.is_transient() => return Err(err),
+                Err(err) => err,
+            };
+            if attempt >= self.max_attempts {
+                datapoint_error!(
+                    "tower_store_retries_exhausted",
+                    ("attempts", attempt, i64),
+                    ("error", err.to_string(), String),
+                );
+                return Err(TowerError::StoreRetriesExhausted(attempt, Box::new(err)));
+            }
+            warn!(
+                "Failed to store tower (attempt {attempt} of {}), retrying in {backoff:?}: {err}",
+                self.max_attempts
+            );
+            datapoint_warn!(
+                "tower_store_retry",
+                ("attempt", attempt, i64),
+                ("error", err.to_string(), String),
+            );
+            thread::sleep(backoff);
+            backoff = backoff.saturating_mul(2).min(self.max_backoff);
+            attempt += 1;
+        }
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.inner.load_saved_tower(node_pubkey)
+    }
+}
+
+/// Refuses to store a tower whose last vote or root is older than those of the
+/// tower saved before it, which would reopen lockouts after a restart. Towers
+/// are compared by their unverified summaries, so no keypair is needed.
+pub struct MonotonicTowerStorage<T> {
+    inner: T,
+    // Summary of the last tower stored through this wrapper, by node
+    last_stored: Mutex<HashMap<Pubkey, SavedTowerSummary>>,
+    allow_regression: AtomicBool,
+}
+
+impl<T: TowerStorage> MonotonicTowerStorage<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            last_stored: Mutex::default(),
+            allow_regression: AtomicBool::default(),
+        }
+    }
+
+    /// Lets the next store go through even if it regresses the saved tower,
+    /// for operators deliberately rolling the tower back
+    pub fn allow_regression(&self) {
+        self.allow_regression.store(true, Ordering::Relaxed);
+    }
+
+    fn saved_summary(&self, node_pubkey: &Pubkey) -> Option<SavedTowerSummary> {
+        match self
+            .inner
+            .load_saved_tower(node_pubkey)
+            .and_then(|saved_tower| saved_tower.summary())
+        {
+            Ok(summary) => Some(summary),
+            Err(err) => {
+                if !err.is_file_missing() {
+                    warn!(
+                        "Unable to read the saved tower for {node_pubkey}, storing anyway: {err}"
+                    );
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<T: TowerStorage> TowerStorage for MonotonicTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.inner.load(node_pubkey)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let node_pubkey = saved_tower.pubkey();
+        let summary = saved_tower.summary()?;
+        let mut last_stored = self.last_stored.lock().unwrap();
+        let stored = match last_stored.get(&node_pubkey) {
+            Some(stored) => Some(stored.clone()),
+            None => self.saved_summary(&node_pubkey),
+        };
+        if let Some(stored) = stored {
+            if (summary.last_vote_slot < stored.last_vote_slot
+                || summary.root_slot < stored.root_slot)
+                && !self.allow_regression.swap(false, Ordering::Relaxed)
+            {
+                return Err(TowerError::TowerRegression {
+                    stored_last_vote: stored.last_vote_slot,
+                    new_last_vote: summary.last_vote_slot,
+                    stored_root: stored.root_slot,
+                    new_root: summary.root_slot,
+                });
+            }
+        }
+        self.inner.store(saved_tower)?;
+        last_stored.insert(node_pubkey, summary);
+        Ok(())
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.inner.load_saved_tower(node_pubkey)
+    }
+}
+
+/// Loads towers from `inner` but refuses to store them, for tools that
+/// inspect a validator's tower and must never overwrite it
+pub struct ReadOnlyTowerStorage<T> {
+    inner: T,
+}
+
+impl<T: TowerStorage> ReadOnlyTowerStorage<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: TowerStorage> TowerStorage for ReadOnlyTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.inner.load(node_pubkey)
+    }
+
+    fn store(&self, _saved_tower: &SavedTowerVersions) -> Result<()> {
+        Err(TowerError::ReadOnly)
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.inner.load_saved_tower(node_pubkey)
+    }
+}
+
+/// Maintains the towers of the identities a validator rotates between, e.g. a
+/// primary and a hot spare, through one inner storage. Stores for any other
+/// identity are refused, so that a keypair swap can't persist a tower for the
+/// wrong identity. Loading the tower of an identity other than the primary,
+/// the one currently voting, is logged as a warning.
+pub struct MultiIdentityTowerStorage<T> {
+    inner: T,
+    identities: HashSet<Pubkey>,
+    primary: RwLock<Option<Pubkey>>,
+    // Time of the last successful store, by identity
+    last_stored: Mutex<HashMap<Pubkey, SystemTime>>,
+}
+
+impl<T: TowerStorage> MultiIdentityTowerStorage<T> {
+    pub fn new(inner: T, identities: impl IntoIterator<Item = Pubkey>) -> Self {
+        Self {
+            inner,
+            identities: identities.into_iter().collect(),
+            primary: RwLock::default(),
+            last_stored: Mutex::default(),
+        }
+    }
+
+    /// Records `node_pubkey` as the identity currently voting
+    pub fn switch_primary(&self, node_pubkey: Pubkey) -> Result<()> {
+        self.check_identity(&node_pubkey)?;
+        let previous = self.primary.write().unwrap().replace(node_pubkey);
+        if previous != Some(node_pubkey) {
+            info!("Primary tower identity switched from {previous:?} to {node_pubkey}");
+        }
+        Ok(())
+    }
+
+    pub fn primary(&self) -> Option<Pubkey> {
+        *self.primary.read().unwrap()
+    }
+
+    /// When a tower was last stored for `node_pubkey` through this storage
+    pub fn last_store_time(&self, node_pubkey: &Pubkey) -> Option<SystemTime> {
+        self.last_stored.lock().unwrap().get(node_pubkey).copied()
+    }
+
+    fn check_identity(&self, node_pubkey: &Pubkey) -> Result<()> {
+        if self.identities.contains(node_pubkey) {
+            Ok(())
+        } else {
+            Err(TowerError::UnknownIdentity(*node_pubkey))
+        }
+    }
+}
+
+impl<T: TowerStorage> TowerStorage for MultiIdentityTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.check_identity(node_pubkey)?;
+        if let Some(primary) = self.primary().filter(|primary| primary != node_pubkey) {
+            warn!(
+                "Loading the tower of {node_pubkey}, which is not the primary identity \
+                 {primary}. Make sure the validator is not voting with {node_pubkey} elsewhere."
+            );
+            datapoint_warn!(
+                "tower_load_non_primary_identity",
+                ("node_pubkey", node_pubkey.to_string(), String),
+                ("primary", primary.to_string(), String),
+            );
+        }
+        self.inner.load(node_pubkey)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let node_pubkey = saved_tower.pubkey();
+        self.check_identity(&node_pubkey)?;
+        self.inner.store(saved_tower)?;
+        self.last_stored
+            .lock()
+            .unwrap()
+            .insert(node_pubkey, SystemTime::now());
+        Ok(())
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.check_identity(node_pubkey)?;
+        self.inner.load_saved_tower(node_pubkey)
+    }
+}
+
+// Replicated towers are sent as frames of a little endian u32 length followed
+// by the serialized `SavedTowerVersions`, as `FileTowerStorage` writes it
+const TOWER_REPLICA_FRAME_HEADER_LEN: usize = 4;
+const MAX_TOWER_REPLICA_FRAME_LEN: usize = 1 << 20;
+pub const DEFAULT_TOWER_REPLICA_QUEUE_LEN: usize = 64;
+const TOWER_REPLICA_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const TOWER_REPLICA_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+const TOWER_REPLICA_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const TOWER_REPLICA_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Address of a `TowerReplicaServer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TowerReplicaEndpoint {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl fmt::Display for TowerReplicaEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TowerReplicaEndpoint::Tcp(addr) => write!(f, "tcp://{addr}"),
+            #[cfg(unix)]
+            TowerReplicaEndpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+enum TowerReplicaStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl TowerReplicaStream {
+    fn connect(endpoint: &TowerReplicaEndpoint) -> io::Result<Self> {
+        let stream = match endpoint {
+            TowerReplicaEndpoint::Tcp(addr) => {
+                let stream = TcpStream::connect_timeout(addr, TOWER_REPLICA_CONNECT_TIMEOUT)?;
+                stream.set_nodelay(true)?;
+                Self::Tcp(stream)
+            }
+            #[cfg(unix)]
+            TowerReplicaEndpoint::Unix(path) => Self::Unix(UnixStream::connect(path)?),
+        };
+        stream.set_timeouts(TOWER_REPLICA_WRITE_TIMEOUT)?;
+        Ok(stream)
+    }
+
+    fn set_timeouts(&self, timeout: Duration) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => {
+                stream.set_nonblocking(false)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))
+            }
+            #[cfg(unix)]
+            Self::Unix(stream) => {
+                stream.set_nonblocking(false)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))
+            }
+        }
+    }
+
+    /// Fills `buf`, waiting out read timeouts until `exit` is set. Returns
+    /// false if the peer closed the stream before sending anything.
+    fn read_full(&mut self, buf: &mut [u8], exit: &AtomicBool) -> io::Result<bool> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.read(&mut buf[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(len) => filled += len,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock
+                            | io::ErrorKind::TimedOut
+                            | io::ErrorKind::Interrupted
+                    ) =>
+                {
+                    if exit.load(Ordering::Relaxed) {
+                        return Err(err);
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Reads the next frame, or None once the peer closed the stream
+    fn read_frame(&mut self, exit: &AtomicBool) -> io::Result<Option<Vec<u8>>> {
+        let mut header = [0u8; TOWER_REPLICA_FRAME_HEADER_LEN];
+        if !self.read_full(&mut header, exit)? {
+            return Ok(None);
+        }
+        let len = u32::from_le_bytes(header) as usize;
+        if len > MAX_TOWER_REPLICA_FRAME_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("frame of {len} bytes exceeds {MAX_TOWER_REPLICA_FRAME_LEN} bytes"),
+            ));
+        }
+        let mut frame = vec![0u8; len];
+        if !self.read_full(&mut frame, exit)? {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(Some(frame))
+    }
+}
+
+impl Read for TowerReplicaStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for TowerReplicaStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+enum TowerReplicaListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl TowerReplicaListener {
+    /// Binds a non-blocking listener, returning it along with the endpoint
+    /// it's bound to, which differs from `endpoint` for port 0
+    fn bind(endpoint: &TowerReplicaEndpoint) -> io::Result<(Self, TowerReplicaEndpoint)> {
+        let (listener, local_endpoint) = match endpoint {
+            TowerReplicaEndpoint::Tcp(addr) => {
+                let listener = TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                let local_addr = listener.local_addr()?;
+                (Self::Tcp(listener), TowerReplicaEndpoint::Tcp(local_addr))
+            }
+            #[cfg(unix)]
+            TowerReplicaEndpoint::Unix(path) => {
+                let listener = UnixListener::bind(path)?;
+                listener.set_nonblocking(true)?;
+                (Self::Unix(listener), endpoint.clone())
+            }
+        };
+        Ok((listener, local_endpoint))
+    }
+
+    fn accept(&self) -> io::Result<TowerReplicaStream> {
+        let stream = match self {
+            Self::Tcp(listener) => TowerReplicaStream::Tcp(listener.accept()?.0),
+            #[cfg(unix)]
+            Self::Unix(listener) => TowerReplicaStream::Unix(listener.accept()?.0),
+        };
+        stream.set_timeouts(TOWER_REPLICA_POLL_INTERVAL)?;
+        Ok(stream)
+    }
+}
+
+/// Delegates to the inner storage, and sends every successfully stored tower
+/// to the `TowerReplicaServer` at `endpoint`, so that a hot spare's tower
+/// keeps up with this validator's. Towers are sent from a background thread
+/// through a queue of up to `max_queue_len` towers, dropping the oldest when
+/// full, so replication never blocks or fails a store. While the replica is
+/// unreachable, reconnects are attempted every second.
+pub struct ReplicatingTowerStorage<T> {
+    inner: T,
+    queue: Arc<TowerReplicaQueue>,
+    sender: Option<JoinHandle<()>>,
+}
+
+struct TowerReplicaQueue {
+    max_len: usize,
+    state: Mutex<TowerReplicaQueueState>,
+    state_changed: Condvar,
+}
+
+#[derive(Default)]
+struct TowerReplicaQueueState {
+    frames: VecDeque<Vec<u8>>,
+    num_dropped: u64,
+    exit: bool,
+}
+
+impl<T: TowerStorage> ReplicatingTowerStorage<T> {
+    pub fn new(inner: T, endpoint: TowerReplicaEndpoint, max_queue_len: usize) -> Self {
+        let queue = Arc::new(TowerReplicaQueue {
+            max_len: max_queue_len.max(1),
+            state: Mutex::default(),
+            state_changed: Condvar::new(),
+        });
+        let sender = Builder::new()
+            .name("solTowerReplSnd".to_string())
+            .spawn({
+                let queue = queue.clone();
+                move || queue.run_sender(&endpoint)
+            })
+            .unwrap();
+        Self {
+            inner,
+            queue,
+            sender: Some(sender),
+        }
+    }
+
+    /// Number of towers dropped unsent because the queue was full
+    pub fn num_dropped(&self) -> u64 {
+        self.queue.state.lock().unwrap().num_dropped
+    }
+}
+
+impl TowerReplicaQueue {
+    fn push(&self, frame: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        if state.frames.len() >= self.max_len {
+            state.frames.pop_front();
+            state.num_dropped += 1;
+        }
+        state.frames.push_back(frame);
+        self.state_changed.notify_one();
+    }
+
+    // Puts back a frame that failed to send, unless newer ones filled the queue
+    fn push_front(&self, frame: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        if state.frames.len() >= self.max_len {
+            state.num_dropped += 1;
+        } else {
+            state.frames.push_front(frame);
+        }
+    }
+
+    fn pop(&self) -> Option<Vec<u8>> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.exit {
+                return None;
+            }
+            if let Some(frame) = state.frames.pop_front() {
+                return Some(frame);
+            }
+            state = self.state_changed.wait(state).unwrap();
+        }
+    }
+
+    // Waits for `timeout` or until told to exit
+    fn sleep(&self, timeout: Duration) {
+        let state = self.state.lock().unwrap();
+        let _state = self
+            .state_changed
+            .wait_timeout_while(state, timeout, |state| !state.exit)
+            .unwrap();
+    }
+
+    fn run_sender(&self, endpoint: &TowerReplicaEndpoint) {
+        let mut stream = None;
+        while let Some(frame) = self.pop() {
+            let result = match stream.take() {
+                Some(stream) => Ok(stream),
+                None => TowerReplicaStream::connect(endpoint)
+                    .inspect(|_| info!("Connected to tower replica {endpoint}")),
+            }
+            .and_then(|mut stream| stream.write_all(&frame).map(|()| stream));
+            match result {
+                Ok(connected) => stream = Some(connected),
+                Err(err) => {
+                    warn!("Unable to replicate tower to {endpoint}: {err}");
+                    self.push_front(frame);
+                    self.sleep(TOWER_REPLICA_RECONNECT_BACKOFF);
+                }
+            }
+        }
+    }
+}
+
+impl<T: TowerStorage> TowerStorage for ReplicatingTowerStorage<T> {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        self.inner.load(node_pubkey)
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        self.inner.store(saved_tower)?;
+        match saved_tower.serialize() {
+            Ok(data) if data.len() <= MAX_TOWER_REPLICA_FRAME_LEN => {
+                let mut frame = Vec::with_capacity(TOWER_REPLICA_FRAME_HEADER_LEN + data.len());
+                frame.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                frame.extend_from_slice(&data);
+                self.queue.push(frame);
+            }
+            Ok(data) => warn!("Not replicating a tower of {} bytes", data.len()),
+            Err(err) => warn!("Unable to serialize tower for replication: {err}"),
+        }
+        Ok(())
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.inner.load_saved_tower(node_pubkey)
+    }
+}
+
+impl<T> Drop for ReplicatingTowerStorage<T> {
+    fn drop(&mut self) {
+        self.queue.state.lock().unwrap().exit = true;
+        self.queue.state_changed.notify_one();
+        if let Some(sender) = self.sender.take() {
+            sender.join().unwrap();
+        }
+    }
+}
+
+/// Receives the towers sent by a `ReplicatingTowerStorage` and stores those
+/// signed by `node_pubkey` into `storage`, for the hot spare of that node.
+/// Towers that fail verification are logged and dropped.
+pub struct TowerReplicaServer {
+    local_endpoint: TowerReplicaEndpoint,
+    t_listener: JoinHandle<()>,
+}
+
+impl TowerReplicaServer {
+    pub fn new(
+        endpoint: &TowerReplicaEndpoint,
+        node_pubkey: Pubkey,
+        storage: Arc<dyn TowerStorage>,
+        exit: Arc<AtomicBool>,
+    ) -> Result<Self> {
+        let (listener, local_endpoint) = TowerReplicaListener::bind(endpoint)?;
+        let t_listener = Builder::new()
+            .name("solTowerReplLsn".to_string())
+            .spawn(move || Self::run_listener(listener, node_pubkey, storage, exit))
+            .unwrap();
+        Ok(Self {
+            local_endpoint,
+            t_listener,
+        })
+    }
+
+    /// The endpoint listened on, with the port bound if `endpoint` had port 0
+    pub fn local_endpoint(&self) -> &TowerReplicaEndpoint {
+        &self.local_endpoint
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.t_listener.join()
+    }
+
+    fn run_listener(
+        listener: TowerReplicaListener,
+        node_pubkey: Pubkey,
+        storage: Arc<dyn TowerStorage>,
+        exit: Arc<AtomicBool>,
+    ) {
+        let mut connections: Vec<JoinHandle<()>> = vec![];
+        while !exit.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok(stream) => {
+                    connections.retain(|connection| !connection.is_finished());
+                    let storage = storage.clone();
+                    let exit = exit.clone();
+                    connections.push(
+                        Builder::new()
+                            .name("solTowerReplRcv".to_string())
+                            .spawn(move || {
+                                Self::serve_connection(stream, &node_pubkey, &*storage, &exit)
+                            })
+                            .unwrap(),
+                    );
+                }
+                Err(err) => {
+                    if err.kind() != io::ErrorKind::WouldBlock {
+                        warn!("Unable to accept tower replication connection: {err}");
+                    }
+                    thread::sleep(TOWER_REPLICA_POLL_INTERVAL);
+                }
+            }
+        }
+        for connection in connections {
+            let _ = connection.join();
+        }
+    }
+
+    fn serve_connection(
+        mut stream: TowerReplicaStream,
+        node_pubkey: &Pubkey,
+        storage: &dyn TowerStorage,
+        exit: &AtomicBool,
+    ) {
+        loop {
+            let data = match stream.read_frame(exit) {
+                Ok(Some(data)) => data,
+                Ok(None) => return,
+                Err(err) => {
+                    if !exit.load(Ordering::Relaxed) {
+                        warn!("Dropping tower replication connection: {err}");
+                    }
+                    return;
+                }
+            };
+            let result = SavedTowerVersions::deserialize(&data).and_then(|saved_tower| {
+                saved_tower.try_into_tower(node_pubkey)?;
+                storage.store(&saved_tower)
+            });
+            if let Err(err) = result {
+                warn!("Rejected replicated tower for {node_pubkey}: {err}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::consensus::tower_storage::{
+            test::{new_runtime_for_tests, new_tower_for_tests, RecordingTowerStorage},
+            FileTowerStorage, MemoryTowerStorage, SavedTower, SavedTower2,
+        },
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_signer::Signer,
+        std::sync::atomic::AtomicUsize,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn test_multi_identity_tower_storage() {
+        let primary_keypair = Keypair::new();
+        let spare_keypair = Keypair::new();
+        let other_keypair = Keypair::new();
+        let tower_path = TempDir::new().unwrap();
+        let file_tower_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        let tower_storage = MultiIdentityTowerStorage::new(
+            FileTowerStorage::new(tower_path.path().to_path_buf()),
+            [primary_keypair.pubkey(), spare_keypair.pubkey()],
+        );
+
+        // Other identities are rejected
+        let tower = new_tower_for_tests(&other_keypair, Some(1));
+        assert_matches!(
+            tower.save(&tower_storage, &other_keypair),
+            Err(TowerError::UnknownIdentity(pubkey)) if pubkey == other_keypair.pubkey()
+        );
+        assert!(!file_tower_storage
+            .filename(&other_keypair.pubkey())
+            .exists());
+        assert_eq!(tower_storage.last_store_time(&other_keypair.pubkey()), None);
+        tower.save(&file_tower_storage, &other_keypair).unwrap();
+        assert_matches!(
+            Tower::restore(&tower_storage, &other_keypair.pubkey()),
+            Err(TowerError::UnknownIdentity(_))
+        );
+        assert_matches!(
+            tower_storage.switch_primary(other_keypair.pubkey()),
+            Err(TowerError::UnknownIdentity(_))
+        );
+        assert_eq!(tower_storage.primary(), None);
+
+        // Both identities store concurrently
+        let before = SystemTime::now();
+        thread::scope(|scope| {
+            for keypair in [&primary_keypair, &spare_keypair] {
+                let tower_storage = &tower_storage;
+                scope.spawn(move || {
+                    let mut tower = new_tower_for_tests(keypair, Some(1));
+                    for slot in 2..20 {
+                        tower.record_vote(slot, Hash::default());
+                        tower.save(tower_storage, keypair).unwrap();
+                    }
+                });
+            }
+        });
+        for keypair in [&primary_keypair, &spare_keypair] {
+            let node_pubkey = keypair.pubkey();
+            let tower = Tower::restore(&tower_storage, &node_pubkey).unwrap();
+            assert_eq!(tower.node_pubkey, node_pubkey);
+            assert_eq!(tower.last_voted_slot(), Some(19));
+            assert!(tower_storage.last_store_time(&node_pubkey).unwrap() >= before);
+        }
+
+        // Switching the primary only changes which loads are warned about
+        tower_storage
+            .switch_primary(primary_keypair.pubkey())
+            .unwrap();
+        assert_eq!(tower_storage.primary(), Some(primary_keypair.pubkey()));
+        tower_storage
+            .switch_primary(spare_keypair.pubkey())
+            .unwrap();
+        assert_eq!(tower_storage.primary(), Some(spare_keypair.pubkey()));
+        for keypair in [&primary_keypair, &spare_keypair] {
+            assert_eq!(
+                Tower::restore(&tower_storage, &keypair.pubkey())
+                    .unwrap()
+                    .last_voted_slot(),
+                Some(19)
+            );
+        }
+    }
+
+    #[test]
+    fn test_pipelined_tower_storage_ordering() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let inner = Arc::new(RecordingTowerStorage::default());
+        let tower_storage = PipelinedTowerStorage::new(inner.clone(), new_runtime_for_tests());
+        let towers: Vec<_> = (0..20)
+            .map(|root| new_tower_for_tests(&identity_keypair, Some(root)))
+            .collect();
+
+        // store() returns while the store is still outstanding
+        inner.paused.store(true, Ordering::Relaxed);
+        towers[0].save(&tower_storage, &identity_keypair).unwrap();
+        assert!(inner.stored.lock().unwrap().is_empty());
+        inner.paused.store(false, Ordering::Relaxed);
+
+        for tower in &towers[1..] {
+            tower.save(&tower_storage, &identity_keypair).unwrap();
+        }
+        // Loads see the outstanding store
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap(),
+            towers[19]
+        );
+        tower_storage.flush().unwrap();
+
+        let expected: Vec<SavedTowerVersions> = towers
+            .iter()
+            .map(|tower| SavedTower::new(tower, &identity_keypair).unwrap().into())
+            .collect();
+        assert_eq!(*inner.stored.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_pipelined_tower_storage_within_runtime() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let runtime = new_runtime_for_tests();
+        let tower_storage =
+            PipelinedTowerStorage::new(Arc::new(RecordingTowerStorage::default()), runtime.clone());
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+
+        // Called from within the runtime, as from an async caller
+        runtime.block_on(async {
+            tower.save(&tower_storage, &identity_keypair).unwrap();
+            assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+            tower_storage.flush().unwrap();
+        });
+    }
+
+    #[test]
+    fn test_pipelined_tower_storage_error() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let inner = Arc::new(RecordingTowerStorage::default());
+        let tower_storage = PipelinedTowerStorage::new(inner.clone(), new_runtime_for_tests());
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+
+        inner.inner.fail_next_store();
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        // The failure surfaces on the next store, which isn't submitted
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+        assert_eq!(inner.stored.lock().unwrap().len(), 1);
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        assert_eq!(inner.stored.lock().unwrap().len(), 2);
+
+        // and on flush()
+        inner.inner.fail_next_store();
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_matches!(tower_storage.flush(), Err(TowerError::IoError(_)));
+        tower_storage.flush().unwrap();
+    }
+
+    #[test]
+    fn test_throttled_tower_storage_coalescing() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let inner = Arc::new(RecordingTowerStorage::default());
+        let tower_storage = ThrottledTowerStorage::new(inner.clone(), Duration::from_secs(3600));
+
+        let towers: Vec<_> = (1..=10)
+            .map(|root| new_tower_for_tests(&identity_keypair, Some(root)))
+            .collect();
+        for tower in &towers {
+            tower.save(&tower_storage, &identity_keypair).unwrap();
+        }
+        assert!(inner.stored.lock().unwrap().is_empty());
+        tower_storage.flush().unwrap();
+        assert_eq!(inner.stored.lock().unwrap().len(), 1);
+        assert_eq!(inner.inner.load(&node_pubkey).unwrap(), towers[9]);
+
+        // Loads store the buffered tower first
+        towers[0].save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap(),
+            towers[0]
+        );
+        assert_eq!(inner.stored.lock().unwrap().len(), 2);
+
+        // Forced stores bypass and replace the buffered tower
+        towers[1].save(&tower_storage, &identity_keypair).unwrap();
+        let saved_tower =
+            SavedTowerVersions::from(SavedTower2::new(&towers[2], &identity_keypair).unwrap());
+        tower_storage.force_store(&saved_tower).unwrap();
+        assert_eq!(inner.stored.lock().unwrap().len(), 3);
+        tower_storage.flush().unwrap();
+        assert_eq!(inner.stored.lock().unwrap().len(), 3);
+        assert_eq!(inner.inner.load(&node_pubkey).unwrap(), towers[2]);
+
+        // Dropping the storage stores the buffered tower
+        towers[3].save(&tower_storage, &identity_keypair).unwrap();
+        drop(tower_storage);
+        assert_eq!(inner.stored.lock().unwrap().len(), 4);
+        assert_eq!(inner.inner.load(&node_pubkey).unwrap(), towers[3]);
+    }
+
+    #[test]
+    fn test_throttled_tower_storage_flusher_panic() {
+        struct PanickingTowerStorage;
+
+        impl TowerStorage for PanickingTowerStorage {
+            fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+                Err(TowerError::TowerNotFound(*node_pubkey))
+            }
+
+            fn store(&self, _saved_tower: &SavedTowerVersions) -> Result<()> {
+                panic!("store failed");
+            }
+        }
+
+        let identity_keypair = Keypair::new();
+        let tower_storage =
+            ThrottledTowerStorage::new(Arc::new(PanickingTowerStorage), Duration::ZERO);
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        while !tower_storage.flusher.as_ref().unwrap().is_finished() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        // Dropping the storage logs the panic rather than panicking again
+        drop(tower_storage);
+    }
+
+    #[test]
+    fn test_throttled_tower_storage_background_flush() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let inner = Arc::new(RecordingTowerStorage::default());
+        let tower_storage = ThrottledTowerStorage::new(inner.clone(), Duration::from_millis(10));
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let wait_for_stores = |count| {
+            while inner.stored.lock().unwrap().len() < count {
+                thread::sleep(Duration::from_millis(1));
+            }
+        };
+
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        wait_for_stores(1);
+        assert_eq!(inner.inner.load(&node_pubkey).unwrap(), tower);
+
+        // A failed background store is returned by the next store, which
+        // isn't buffered
+        inner.inner.fail_next_store();
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        while tower_storage.shared.state.lock().unwrap().error.is_none() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+        tower_storage.flush().unwrap();
+        assert_eq!(inner.stored.lock().unwrap().len(), 2);
+
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        wait_for_stores(3);
+        assert_eq!(inner.inner.load(&node_pubkey).unwrap(), tower);
+    }
+
+    /// `TowerStorage` whose stores always fail
+    struct FailingTowerStorage;
+
+    impl TowerStorage for FailingTowerStorage {
+        fn load(&self, _node_pubkey: &Pubkey) -> Result<Tower> {
+            Err(TowerError::IoError(io::Error::other("load failed")))
+        }
+
+        fn store(&self, _saved_tower: &SavedTowerVersions) -> Result<()> {
+            Err(TowerError::IoError(io::Error::other("store failed")))
+        }
+    }
+
+    #[test]
+    fn test_composite_tower_storage_partial_store_failure() {
+        let tower_path = TempDir::new().unwrap();
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+
+        let new_composite = |write_policy| {
+            CompositeTowerStorage::new(
+                vec![
+                    Box::new(FailingTowerStorage),
+                    Box::new(FileTowerStorage::new(tower_path.path().to_path_buf())),
+                ],
+                write_policy,
+            )
+        };
+
+        let tower_storage = new_composite(CompositeWritePolicy::RequireAll);
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+
+        let tower_storage = new_composite(CompositeWritePolicy::BestEffort);
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_composite_tower_storage_all_fail() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let tower_storage = CompositeTowerStorage::new(
+            vec![Box::new(FailingTowerStorage), Box::new(FailingTowerStorage)],
+            CompositeWritePolicy::BestEffort,
+        );
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(_))
+        );
+
+        // A failed load is reported over a missing tower
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage = CompositeTowerStorage::new(
+            vec![
+                Box::new(FileTowerStorage::new(tower_path.path().to_path_buf())),
+                Box::new(FailingTowerStorage),
+            ],
+            CompositeWritePolicy::BestEffort,
+        );
+        let err = Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        assert!(!err.is_file_missing());
+    }
+
+    #[test]
+    fn test_composite_tower_storage_backends_disagree() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let older_path = TempDir::new().unwrap();
+        let newer_path = TempDir::new().unwrap();
+        let older_tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let newer_tower = new_tower_for_tests(&identity_keypair, Some(5));
+        older_tower
+            .save(
+                &FileTowerStorage::new(older_path.path().to_path_buf()),
+                &identity_keypair,
+            )
+            .unwrap();
+        newer_tower
+            .save(
+                &FileTowerStorage::new(newer_path.path().to_path_buf()),
+                &identity_keypair,
+            )
+            .unwrap();
+
+        for paths in [[&older_path, &newer_path], [&newer_path, &older_path]] {
+            let tower_storage = CompositeTowerStorage::new(
+                paths
+                    .iter()
+                    .map(|path| {
+                        Box::new(FileTowerStorage::new(path.path().to_path_buf()))
+                            as Box<dyn TowerStorage>
+                    })
+                    .collect(),
+                CompositeWritePolicy::BestEffort,
+            );
+            assert_eq!(
+                Tower::restore(&tower_storage, &node_pubkey).unwrap(),
+                newer_tower
+            );
+        }
+
+        // Only one backend has a tower
+        let empty_path = TempDir::new().unwrap();
+        let tower_storage = CompositeTowerStorage::new(
+            vec![
+                Box::new(FileTowerStorage::new(empty_path.path().to_path_buf())),
+                Box::new(FileTowerStorage::new(older_path.path().to_path_buf())),
+            ],
+            CompositeWritePolicy::BestEffort,
+        );
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap(),
+            older_tower
+        );
+    }
+
+    #[test]
+    fn test_encrypted_tower_storage() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = EncryptedTowerStorage::new(MemoryTowerStorage::new(), [1; 32]);
+
+        assert!(Tower::restore(&tower_storage, &node_pubkey)
+            .unwrap_err()
+            .is_file_missing());
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        // The inner storage never sees the plaintext tower
+        let plaintext = bincode::serialize(&Tower1_14_11::from(tower.clone())).unwrap();
+        let stored = tower_storage.inner.towers.read().unwrap()[&node_pubkey].clone();
+        assert!(!stored
+            .windows(plaintext.len())
+            .any(|window| window == plaintext));
+        assert_matches!(
+            Tower::restore(&tower_storage.inner, &node_pubkey),
+            Err(TowerError::InvalidSignature)
+        );
+
+        // The same key read from a keyfile
+        let tmp_dir = TempDir::new().unwrap();
+        let keyfile = tmp_dir.path().join("tower.key");
+        fs::write(&keyfile, [1; 32]).unwrap();
+        let EncryptedTowerStorage { inner, .. } = tower_storage;
+        let tower_storage = EncryptedTowerStorage::new_with_keyfile(inner, &keyfile).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        fs::write(&keyfile, [1; 31]).unwrap();
+        assert_matches!(
+            EncryptedTowerStorage::new_with_keyfile(MemoryTowerStorage::new(), &keyfile),
+            Err(TowerError::IoError(_))
+        );
+    }
+
+    #[test]
+    fn test_encrypted_tower_storage_wrong_key_or_tampered() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = EncryptedTowerStorage::new(MemoryTowerStorage::new(), [1; 32]);
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+
+        let EncryptedTowerStorage { inner, .. } = tower_storage;
+        let tower_storage = EncryptedTowerStorage::new(inner, [2; 32]);
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::DecryptionFailed(_))
+        );
+
+        let EncryptedTowerStorage { inner, .. } = tower_storage;
+        let mut saved_tower = inner.load_saved_tower(&node_pubkey).unwrap();
+        let SavedTowerVersions::V2(t) = &mut saved_tower else {
+            panic!("unexpected saved tower version");
+        };
+        *t.data.last_mut().unwrap() ^= 1;
+        inner.store(&saved_tower).unwrap();
+        let tower_storage = EncryptedTowerStorage::new(inner, [1; 32]);
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::DecryptionFailed(_))
+        );
+    }
+
+    #[test]
+    fn test_encrypted_tower_storage_plaintext_fallback() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let inner = MemoryTowerStorage::new();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&inner, &identity_keypair).unwrap();
+
+        let tower_storage = EncryptedTowerStorage::new(inner, [1; 32]);
+        assert_matches!(
+            Tower::restore(&tower_storage, &node_pubkey),
+            Err(TowerError::DecryptionFailed(_))
+        );
+
+        let tower_storage = tower_storage.allow_plaintext_fallback(true);
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        let tower = new_tower_for_tests(&identity_keypair, Some(2));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        assert_matches!(
+            Tower::restore(&tower_storage.inner, &node_pubkey),
+            Err(TowerError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_auditing_tower_storage() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let audit_dir = TempDir::new().unwrap();
+        let audit_log = audit_dir.path().join("tower-audit.log");
+        let tower_storage =
+            AuditingTowerStorage::new(MemoryTowerStorage::new(), audit_log.clone(), u64::MAX)
+                .unwrap();
+
+        // Failed loads aren't recorded
+        assert!(Tower::restore(&tower_storage, &node_pubkey).is_err());
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.record_vote(5, Hash::default());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        let records = read_audit_log(&audit_log).unwrap();
+        assert_eq!(records.len(), 2);
+        let (store, load) = (&records[0], &records[1]);
+        assert_eq!(store.op, TowerAuditOp::Store);
+        assert_eq!(load.op, TowerAuditOp::Load);
+        for record in &records {
+            assert_eq!(record.node_pubkey, node_pubkey);
+            assert_eq!(record.last_vote_slot, Some(5));
+            assert_eq!(record.root_slot, Some(1));
+        }
+        // The load read back what was stored
+        assert_eq!(store.payload_hash, load.payload_hash);
+        assert!(store.timestamp <= load.timestamp);
+
+        // Reopening the log appends to it
+        drop(tower_storage);
+        let tower_storage =
+            AuditingTowerStorage::new(MemoryTowerStorage::new(), audit_log.clone(), u64::MAX)
+                .unwrap();
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(read_audit_log(&audit_log).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_tower_audit_log_rotation() {
+        let identity_keypair = Keypair::new();
+        let audit_dir = TempDir::new().unwrap();
+        let audit_log = audit_dir.path().join("tower-audit.log");
+        let new_saved_tower = |slot| {
+            let mut tower = new_tower_for_tests(&identity_keypair, Some(0));
+            tower.record_vote(slot, Hash::default());
+            SavedTowerVersions::from(SavedTower2::new(&tower, &identity_keypair).unwrap())
+        };
+        let frame_len = TOWER_AUDIT_FRAME_HEADER_LEN as u64
+            + bincode::serialized_size(&TowerAuditRecord::new_store(&new_saved_tower(1))).unwrap();
+
+        // Room for two records per log
+        let tower_storage = AuditingTowerStorage::new(
+            MemoryTowerStorage::new(),
+            audit_log.clone(),
+            2 * frame_len + 1,
+        )
+        .unwrap();
+        for slot in 1..=5 {
+            tower_storage.store(&new_saved_tower(slot)).unwrap();
+        }
+        assert_eq!(fs::metadata(&audit_log).unwrap().len(), frame_len);
+        assert_eq!(
+            fs::metadata(rotated_audit_log_path(&audit_log))
+                .unwrap()
+                .len(),
+            2 * frame_len
+        );
+
+        // Only the current and the last rotated log are kept
+        let slots: Vec<_> = read_audit_log(&audit_log)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.last_vote_slot.unwrap())
+            .collect();
+        assert_eq!(slots, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_tower_audit_log_torn_write() {
+        let identity_keypair = Keypair::new();
+        let audit_dir = TempDir::new().unwrap();
+        let audit_log = audit_dir.path().join("tower-audit.log");
+        let tower_storage =
+            AuditingTowerStorage::new(MemoryTowerStorage::new(), audit_log.clone(), u64::MAX)
+                .unwrap();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        let data = fs::read(&audit_log).unwrap();
+        let frame_len = data.len() / 2;
+
+        // A torn final write is skipped
+        for len in [1, TOWER_AUDIT_FRAME_HEADER_LEN, frame_len - 1] {
+            fs::write(&audit_log, &data[..frame_len + len]).unwrap();
+            assert_eq!(read_audit_log(&audit_log).unwrap().len(), 1);
+        }
+
+        // as is a corrupt one
+        let mut corrupt = data.clone();
+        *corrupt.last_mut().unwrap() ^= 1;
+        fs::write(&audit_log, &corrupt).unwrap();
+        assert_eq!(read_audit_log(&audit_log).unwrap().len(), 1);
+
+        // The torn record is dropped when the log is reopened, so records
+        // appended after it can be read
+        drop(tower_storage);
+        fs::write(&audit_log, &data[..frame_len + 1]).unwrap();
+        let tower_storage =
+            AuditingTowerStorage::new(MemoryTowerStorage::new(), audit_log.clone(), u64::MAX)
+                .unwrap();
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(fs::read(&audit_log).unwrap().len(), 2 * frame_len);
+        assert_eq!(read_audit_log(&audit_log).unwrap().len(), 2);
+    }
+
+    /// Fails the first `failures` stores with `error()`
+    struct FlakyTowerStorage {
+        inner: MemoryTowerStorage,
+        failures: AtomicUsize,
+        error: fn() -> TowerError,
+        stores: AtomicUsize,
+    }
+
+    impl FlakyTowerStorage {
+        fn new(failures: usize, error: fn() -> TowerError) -> Self {
+            Self {
+                inner: MemoryTowerStorage::default(),
+                failures: AtomicUsize::new(failures),
+                error,
+                stores: AtomicUsize::default(),
+            }
+        }
+    }
+
+    impl TowerStorage for FlakyTowerStorage {
+        fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+            self.inner.load(node_pubkey)
+        }
+
+        fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+            self.stores.fetch_add(1, Ordering::Relaxed);
+            if self
+                .failures
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err((self.error)());
+            }
+            self.inner.store(saved_tower)
+        }
+    }
+
+    #[test]
+    fn test_retrying_tower_storage() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let timed_out = || TowerError::IoError(io::Error::from(io::ErrorKind::TimedOut));
+
+        // Transient failures are retried until the store succeeds
+        let tower_storage =
+            RetryingTowerStorage::new(FlakyTowerStorage::new(2, timed_out), 3, Duration::ZERO);
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(tower_storage.inner.stores.load(Ordering::Relaxed), 3);
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        // or attempts run out
+        let tower_storage = RetryingTowerStorage::new(
+            FlakyTowerStorage::new(usize::MAX, timed_out),
+            3,
+            Duration::from_millis(1),
+        );
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::StoreRetriesExhausted(3, err))
+                if matches!(*err, TowerError::IoError(_))
+        );
+        assert_eq!(tower_storage.inner.stores.load(Ordering::Relaxed), 3);
+        assert!(!tower_storage.exists(&node_pubkey).unwrap());
+    }
+
+    #[test]
+    fn test_retrying_tower_storage_permanent_error() {
+        let identity_keypair = Keypair::new();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let tower_storage = RetryingTowerStorage::new(
+            FlakyTowerStorage::new(usize::MAX, || TowerError::WrongTower {
+                expected: Pubkey::new_unique(),
+                found: Pubkey::new_unique(),
+            }),
+            3,
+            Duration::ZERO,
+        );
+
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::WrongTower { .. })
+        );
+        assert_eq!(tower_storage.inner.stores.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_monotonic_tower_storage() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_path = TempDir::new().unwrap();
+        let tower_storage =
+            MonotonicTowerStorage::new(FileTowerStorage::new(tower_path.path().to_path_buf()));
+
+        // The first tower has nothing to regress
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.record_vote(5, Hash::default());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+
+        // Storing the same slots again is fine
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+
+        // but older last votes or roots are not
+        let mut old_tower = new_tower_for_tests(&identity_keypair, Some(1));
+        old_tower.record_vote(4, Hash::default());
+        assert_matches!(
+            old_tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::TowerRegression {
+                stored_last_vote: Some(5),
+                new_last_vote: Some(4),
+                ..
+            })
+        );
+        let mut old_tower = new_tower_for_tests(&identity_keypair, Some(0));
+        old_tower.record_vote(5, Hash::default());
+        assert_matches!(
+            old_tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::TowerRegression {
+                stored_root: Some(1),
+                new_root: Some(0),
+                ..
+            })
+        );
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        // including ones saved before this instance started
+        let tower_storage =
+            MonotonicTowerStorage::new(FileTowerStorage::new(tower_path.path().to_path_buf()));
+        assert_matches!(
+            old_tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::TowerRegression { .. })
+        );
+
+        // unless the operator allows it, once
+        tower_storage.allow_regression();
+        old_tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(
+            Tower::restore(&tower_storage, &node_pubkey).unwrap(),
+            old_tower
+        );
+        let mut older_tower = new_tower_for_tests(&identity_keypair, Some(0));
+        older_tower.record_vote(3, Hash::default());
+        assert_matches!(
+            older_tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::TowerRegression { .. })
+        );
+    }
+
+    #[test]
+    fn test_read_only_tower_storage() {
+        let tower_path = TempDir::new().unwrap();
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let file_storage = FileTowerStorage::new(tower_path.path().to_path_buf());
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.record_vote(5, Hash::default());
+        tower.save(&file_storage, &identity_keypair).unwrap();
+        let saved = fs::read(file_storage.filename(&node_pubkey)).unwrap();
+
+        let tower_storage = ReadOnlyTowerStorage::new(file_storage.clone());
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        let mut new_tower = tower.clone();
+        new_tower.record_vote(6, Hash::default());
+        assert_matches!(
+            new_tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::ReadOnly)
+        );
+        assert_eq!(
+            fs::read(file_storage.filename(&node_pubkey)).unwrap(),
+            saved
+        );
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+    }
+
+    // Polls `storage` until it holds `tower`, giving up after a while
+    fn wait_for_tower(storage: &dyn TowerStorage, tower: &Tower) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if storage.load(&tower.node_pubkey).ok().as_ref() == Some(tower) {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    fn check_tower_replication(endpoint: TowerReplicaEndpoint) {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let replica_storage = Arc::new(MemoryTowerStorage::new());
+        let exit = Arc::new(AtomicBool::default());
+        let server = TowerReplicaServer::new(
+            &endpoint,
+            node_pubkey,
+            replica_storage.clone(),
+            exit.clone(),
+        )
+        .unwrap();
+        let tower_storage = ReplicatingTowerStorage::new(
+            MemoryTowerStorage::new(),
+            server.local_endpoint().clone(),
+            DEFAULT_TOWER_REPLICA_QUEUE_LEN,
+        );
+
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        for slot in 2..10 {
+            tower.record_vote(slot, Hash::default());
+            tower.save(&tower_storage, &identity_keypair).unwrap();
+        }
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        assert!(wait_for_tower(&*replica_storage, &tower));
+
+        // Towers of other nodes are rejected by the replica. Towers are
+        // received in order, so the other tower was processed by the time the
+        // next one converges.
+        let other_keypair = Keypair::new();
+        let other_tower = new_tower_for_tests(&other_keypair, Some(1));
+        other_tower.save(&tower_storage, &other_keypair).unwrap();
+        tower.record_vote(10, Hash::default());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert!(wait_for_tower(&*replica_storage, &tower));
+        assert!(!replica_storage.exists(&other_keypair.pubkey()).unwrap());
+        assert_eq!(tower_storage.num_dropped(), 0);
+
+        drop(tower_storage);
+        exit.store(true, Ordering::Relaxed);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_replicating_tower_storage() {
+        check_tower_replication(TowerReplicaEndpoint::Tcp(SocketAddr::from((
+            [127, 0, 0, 1],
+            0,
+        ))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replicating_tower_storage_unix_socket() {
+        let socket_dir = TempDir::new().unwrap();
+        check_tower_replication(TowerReplicaEndpoint::Unix(
+            socket_dir.path().join("tower.sock"),
+        ));
+    }
+
+    #[test]
+    fn test_replicating_tower_storage_dead_replica() {
+        // Nothing listens on the port once the listener is dropped
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let tower_storage = ReplicatingTowerStorage::new(
+            MemoryTowerStorage::new(),
+            TowerReplicaEndpoint::Tcp(addr),
+            4,
+        );
+
+        // Stores neither fail nor wait out the reconnect backoff, and the
+        // oldest unsent towers are dropped
+        let mut tower = new_tower_for_tests(&identity_keypair, Some(1));
+        let start = Instant::now();
+        for slot in 2..100 {
+            tower.record_vote(slot, Hash::default());
+            tower.save(&tower_storage, &identity_keypair).unwrap();
+        }
+        assert!(start.elapsed() < TOWER_REPLICA_RECONNECT_BACKOFF);
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        assert!(tower_storage.num_dropped() > 0);
+
+        // Dropping the storage doesn't wait for the replica either
+        let start = Instant::now();
+        drop(tower_storage);
+        assert!(start.elapsed() < TOWER_REPLICA_RECONNECT_BACKOFF);
+    }
+}
@@ -0,0 +1,663 @@
+use {
+    super::{
+        corrupt_tower_at, detect_and_deserialize, execute_with_retries, EtcdTowerStorage,
+        SavedTowerVersions, TowerStorage,
+    },
+    crate::consensus::{Result, Tower, TowerError},
+    async_trait::async_trait,
+    solana_pubkey::Pubkey,
+    std::{collections::HashMap, future::Future, io, sync::Arc, time::Duration},
+};
+
+// How long the ZooKeeper session, and with it the instance lock, outlives its
+// holder once the holder stops heartbeating
+pub(super) const DEFAULT_ZK_SESSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The ZooKeeper operations `ZkTowerStorage` depends on
+#[async_trait]
+trait ZkClient: Send + Sync {
+    /// Creates `path` holding `data`, and any missing parents. The znode is
+    /// deleted with the session if `ephemeral`. Returns false if `path` exists.
+    async fn create(&self, path: String, data: Vec<u8>, ephemeral: bool) -> Result<bool>;
+
+    /// Gets the data of `path` and its version
+    async fn get(&self, path: String) -> Result<Option<(Vec<u8>, i32)>>;
+
+    /// Lists the names of the children of `path`
+    async fn children(&self, path: String) -> Result<Vec<String>>;
+
+    /// Deletes `path` if it's at `version`, or any version if `None`. Returns
+    /// false if it isn't or doesn't exist.
+    async fn delete(&self, path: String, version: Option<i32>) -> Result<bool>;
+
+    /// Sets `path` to `data`, creating it if needed, in one multi-op with a
+    /// check that `check_path` is at `check_version`. Returns false without
+    /// writing if it isn't or doesn't exist.
+    async fn set_if_version(
+        &self,
+        check_path: String,
+        check_version: i32,
+        path: String,
+        data: Vec<u8>,
+    ) -> Result<bool>;
+}
+
+struct ZooKeeperClient {
+    client: zookeeper_client::Client,
+    // Whether znodes are restricted to the authenticated user
+    authenticated: bool,
+}
+
+impl ZooKeeperClient {
+    fn create_options(
+        &self,
+        mode: zookeeper_client::CreateMode,
+    ) -> zookeeper_client::CreateOptions {
+        if self.authenticated {
+            mode.with_acls(zookeeper_client::Acls::creator_all())
+        } else {
+            mode.with_acls(zookeeper_client::Acls::anyone_all())
+        }
+    }
+
+    fn zk_to_tower_error(error: zookeeper_client::Error) -> TowerError {
+        let kind = match error {
+            zookeeper_client::Error::ConnectionLoss => io::ErrorKind::NotConnected,
+            _ => io::ErrorKind::Other,
+        };
+        TowerError::IoError(io::Error::new(kind, error.to_string()))
+    }
+}
+
+#[async_trait]
+impl ZkClient for ZooKeeperClient {
+    async fn create(&self, path: String, data: Vec<u8>, ephemeral: bool) -> Result<bool> {
+        let persistent = self.create_options(zookeeper_client::CreateMode::Persistent);
+        for (index, _) in path.match_indices('/').skip(1) {
+            match self.client.create(&path[..index], &[], &persistent).await {
+                Ok(_) | Err(zookeeper_client::Error::NodeExists) => (),
+                Err(err) => return Err(Self::zk_to_tower_error(err)),
+            }
+        }
+
+        let options = if ephemeral {
+            self.create_options(zookeeper_client::CreateMode::Ephemeral)
+        } else {
+            persistent
+        };
+        match self.client.create(&path, &data, &options).await {
+            Ok(_) => Ok(true),
+            Err(zookeeper_client::Error::NodeExists) => Ok(false),
+            Err(err) => Err(Self::zk_to_tower_error(err)),
+        }
+    }
+
+    async fn get(&self, path: String) -> Result<Option<(Vec<u8>, i32)>> {
+        match self.client.get_data(&path).await {
+            Ok((data, stat)) => Ok(Some((data, stat.version))),
+            Err(zookeeper_client::Error::NoNode) => Ok(None),
+            Err(err) => Err(Self::zk_to_tower_error(err)),
+        }
+    }
+
+    async fn children(&self, path: String) -> Result<Vec<String>> {
+        match self.client.list_children(&path).await {
+            Ok(children) => Ok(children),
+            Err(zookeeper_client::Error::NoNode) => Ok(vec![]),
+            Err(err) => Err(Self::zk_to_tower_error(err)),
+        }
+    }
+
+    async fn delete(&self, path: String, version: Option<i32>) -> Result<bool> {
+        match self.client.delete(&path, version).await {
+            Ok(()) => Ok(true),
+            Err(zookeeper_client::Error::NoNode | zookeeper_client::Error::BadVersion) => Ok(false),
+            Err(err) => Err(Self::zk_to_tower_error(err)),
+        }
+    }
+
+    async fn set_if_version(
+        &self,
+        check_path: String,
+        check_version: i32,
+        path: String,
+        data: Vec<u8>,
+    ) -> Result<bool> {
+        let exists = self
+            .client
+            .check_stat(&path)
+            .await
+            .map_err(Self::zk_to_tower_error)?
+            .is_some();
+        let mut writer = self.client.new_multi_writer();
+        writer
+            .add_check_version(&check_path, check_version)
+            .map_err(Self::zk_to_tower_error)?;
+        if exists {
+            writer.add_set_data(&path, &data, None)
+        } else {
+            let options = self.create_options(zookeeper_client::CreateMode::Persistent);
+            writer.add_create(&path, &data, &options)
+        }
+        .map_err(Self::zk_to_tower_error)?;
+        match writer.commit().await {
+            Ok(_) => Ok(true),
+            // The check failed
+            Err(zookeeper_client::MultiWriteError::OperationFailed {
+                index: 0,
+                source: zookeeper_client::Error::NoNode | zookeeper_client::Error::BadVersion,
+            }) => Ok(false),
+            Err(
+                zookeeper_client::MultiWriteError::OperationFailed { source, .. }
+                | zookeeper_client::MultiWriteError::RequestFailed { source },
+            ) => Err(Self::zk_to_tower_error(source)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ZkTowerStorageConfig {
+    /// `host:port` of each ZooKeeper server
+    pub hosts: Vec<String>,
+    /// Path towers are stored under, such as `/solana/towers`
+    pub chroot: Option<String>,
+    /// `user:password` to authenticate with using the digest scheme. The
+    /// znodes created are then only accessible to that user.
+    pub auth_digest: Option<String>,
+    /// How long the instance lock outlives this instance once it stops
+    /// heartbeating, after which another instance may acquire it
+    pub session_timeout: Duration,
+    /// How long each ZooKeeper request may take before it's retried
+    pub operation_timeout: Duration,
+    /// How many times a failed ZooKeeper request is retried
+    pub max_retries: usize,
+}
+
+impl Default for ZkTowerStorageConfig {
+    fn default() -> Self {
+        Self {
+            hosts: Vec::default(),
+            chroot: None,
+            auth_digest: None,
+            session_timeout: DEFAULT_ZK_SESSION_TIMEOUT,
+            operation_timeout: Duration::from_secs(1),
+            max_retries: 2,
+        }
+    }
+}
+
+/// Stores towers in ZooKeeper. Like `EtcdTowerStorage`, a tower is guarded by
+/// an instance lock: an ephemeral znode for this instance, which must be the
+/// only instance znode and is checked by every store in the same multi-op. It
+/// goes away if this instance's session expires or another instance takes
+/// the lock over.
+pub struct ZkTowerStorage {
+    client: Arc<dyn ZkClient>,
+    operation_timeout: Duration,
+    max_retries: usize,
+    instance_id: [u8; 8],
+    force: bool,
+    // Version of each instance znode this instance holds
+    instance_locks: tokio::sync::Mutex<HashMap<Pubkey, i32>>,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl ZkTowerStorage {
+    pub fn new(config: ZkTowerStorageConfig) -> Result<Self> {
+        // Multi-threaded so that the session is kept alive between loads and stores
+        let runtime = EtcdTowerStorage::new_runtime();
+        let cluster = format!(
+            "{}{}",
+            config.hosts.join(","),
+            config.chroot.as_deref().unwrap_or_default()
+        );
+        let client = runtime.block_on(async {
+            let mut connector = zookeeper_client::Client::connector();
+            connector.with_session_timeout(config.session_timeout);
+            if let Some(auth_digest) = &config.auth_digest {
+                connector.with_auth("digest".to_string(), auth_digest.as_bytes().to_vec());
+            }
+            tokio::time::timeout(config.session_timeout, connector.connect(&cluster)).await
+        });
+        let client = match client {
+            Ok(Ok(client)) => client,
+            Ok(Err(err)) => return Err(ZooKeeperClient::zk_to_tower_error(err)),
+            Err(_) => {
+                return Err(TowerError::IoError(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("Timed out connecting to ZooKeeper at {cluster}"),
+                )))
+            }
+        };
+        let client = Arc::new(ZooKeeperClient {
+            client,
+            authenticated: config.auth_digest.is_some(),
+        });
+        Ok(Self::new_with_client(client, &config, runtime))
+    }
+
+    fn new_with_client(
+        client: Arc<dyn ZkClient>,
+        config: &ZkTowerStorageConfig,
+        runtime: tokio::runtime::Runtime,
+    ) -> Self {
+        Self {
+            client,
+            operation_timeout: config.operation_timeout,
+            max_retries: config.max_retries,
+            instance_id: solana_time_utils::timestamp().to_le_bytes(),
+            force: false,
+            instance_locks: tokio::sync::Mutex::default(),
+            runtime,
+        }
+    }
+
+    /// Take over the instance lock even if another instance holds it, rather
+    /// than failing
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    fn get_paths(&self, node_pubkey: &Pubkey) -> (String, String) {
+        let instance_path = format!(
+            "{}/{:016x}",
+            Self::get_instances_path(node_pubkey),
+            u64::from_le_bytes(self.instance_id)
+        );
+        let tower_path = format!("/{node_pubkey}/tower");
+        (instance_path, tower_path)
+    }
+
+    fn get_instances_path(node_pubkey: &Pubkey) -> String {
+        format!("/{node_pubkey}/instances")
+    }
+
+    /// Runs `operation`, retrying it on errors and timeouts
+    async fn execute<T, F, Fut>(&self, mut operation: F) -> Result<T>
+    where
+        F: FnMut(Arc<dyn ZkClient>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        execute_with_retries(
+            self.operation_timeout,
+            self.max_retries,
+            |_| operation(self.client.clone()),
+            |err, retry| {
+                if retry {
+                    warn!("ZooKeeper request failed, retrying: {err}");
+                }
+            },
+        )
+        .await
+        .map_err(|err| TowerError::IoError(io::Error::other(format!("ZooKeeper: {err}"))))
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<(Vec<u8>, i32)>> {
+        self.execute(|client| {
+            let path = path.to_string();
+            async move { client.get(path).await }
+        })
+        .await
+    }
+
+    async fn delete(&self, path: &str, version: Option<i32>) -> Result<bool> {
+        self.execute(|client| {
+            let path = path.to_string();
+            async move { client.delete(path, version).await }
+        })
+        .await
+    }
+
+    /// Returns the version of the instance znode once this instance holds it
+    async fn lock_instance(&self, node_pubkey: &Pubkey) -> Result<i32> {
+        let mut instance_locks = self.instance_locks.lock().await;
+        if let Some(version) = instance_locks.get(node_pubkey) {
+            return Ok(*version);
+        }
+
+        let (instance_path, _) = self.get_paths(node_pubkey);
+        let instances_path = Self::get_instances_path(node_pubkey);
+        // Retrying is safe, a znode created by a failed attempt is this
+        // instance's
+        self.execute(|client| {
+            let instance_path = instance_path.clone();
+            let instance_id = self.instance_id.to_vec();
+            async move { client.create(instance_path, instance_id, true).await }
+        })
+        .await?;
+        let instances = self
+            .execute(|client| {
+                let instances_path = instances_path.clone();
+                async move { client.children(instances_path).await }
+            })
+            .await?;
+        for other_path in instances
+            .into_iter()
+            .map(|instance| format!("{instances_path}/{instance}"))
+            .filter(|path| *path != instance_path)
+        {
+            if !self.force {
+                // Instances racing for the lock may both back off, but never
+                // both hold it
+                let _ = self.delete(&instance_path, None).await;
+                return Err(TowerError::TowerLocked(format!(
+                    "ZooKeeper {other_path} holds the instance lock"
+                )));
+            }
+            warn!("Taking over ZooKeeper instance lock from {other_path}");
+            self.delete(&other_path, None).await?;
+        }
+
+        let Some((_, version)) = self.get(&instance_path).await? else {
+            return Err(TowerError::TowerLocked(format!(
+                "ZooKeeper {instance_path} was taken over"
+            )));
+        };
+        instance_locks.insert(*node_pubkey, version);
+        Ok(version)
+    }
+
+    async fn lost_instance_lock(&self, node_pubkey: &Pubkey) -> TowerError {
+        // Reacquire it next time
+        self.instance_locks.lock().await.remove(node_pubkey);
+        error!("Lost ZooKeeper instance lock for {node_pubkey}");
+        TowerError::IoError(io::Error::other(format!(
+            "Lost ZooKeeper instance lock for {node_pubkey}"
+        )))
+    }
+
+    async fn load_saved(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        let version = self.lock_instance(node_pubkey).await?;
+        let (instance_path, tower_path) = self.get_paths(node_pubkey);
+        if self.get(&instance_path).await? != Some((self.instance_id.to_vec(), version)) {
+            return Err(self.lost_instance_lock(node_pubkey).await);
+        }
+        let (saved_tower, _) = self
+            .get(&tower_path)
+            .await?
+            .ok_or(TowerError::TowerNotFound(*node_pubkey))?;
+        detect_and_deserialize(&saved_tower, node_pubkey).map_err(corrupt_tower_at(tower_path))
+    }
+
+    async fn store_tower(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        let pubkey = saved_tower.pubkey();
+        let version = self.lock_instance(&pubkey).await?;
+        let (instance_path, tower_path) = self.get_paths(&pubkey);
+        let data = saved_tower.serialize()?;
+        // Retrying is safe, the tower is only written while the instance
+        // znode is unchanged
+        let stored = self
+            .execute(|client| {
+                let instance_path = instance_path.clone();
+                let tower_path = tower_path.clone();
+                let data = data.clone();
+                async move {
+                    client
+                        .set_if_version(instance_path, version, tower_path, data)
+                        .await
+                }
+            })
+            .await
+            .map_err(|err| {
+                error!("Failed to write ZooKeeper saved tower: {err}");
+                err
+            })?;
+        if !stored {
+            return Err(self.lost_instance_lock(&pubkey).await);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ZkTowerStorage {
+    fn drop(&mut self) {
+        // Release the instance locks now rather than once the session expires
+        let instance_locks = std::mem::take(self.instance_locks.get_mut());
+        for (node_pubkey, version) in instance_locks {
+            let (instance_path, _) = self.get_paths(&node_pubkey);
+            let _ = self.runtime.block_on(async {
+                tokio::time::timeout(
+                    self.operation_timeout,
+                    self.client.delete(instance_path, Some(version)),
+                )
+                .await
+            });
+        }
+    }
+}
+
+impl TowerStorage for ZkTowerStorage {
+    fn load(&self, node_pubkey: &Pubkey) -> Result<Tower> {
+        let (_, tower_path) = self.get_paths(node_pubkey);
+        self.runtime
+            .block_on(self.load_saved(node_pubkey))?
+            .try_into_tower(node_pubkey)
+            .map_err(corrupt_tower_at(tower_path))
+    }
+
+    fn store(&self, saved_tower: &SavedTowerVersions) -> Result<()> {
+        self.runtime.block_on(self.store_tower(saved_tower))
+    }
+
+    fn load_saved_tower(&self, node_pubkey: &Pubkey) -> Result<SavedTowerVersions> {
+        self.runtime.block_on(self.load_saved(node_pubkey))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::consensus::tower_storage::test::new_tower_for_tests,
+        solana_keypair::Keypair,
+        solana_signer::Signer,
+        std::{collections::VecDeque, sync::Mutex},
+    };
+
+    // path -> (data, version, owning session if ephemeral)
+    type MockZnodes = HashMap<String, (Vec<u8>, i32, Option<&'static str>)>;
+
+    /// In-memory `ZkClient` with a script of failures for the next requests.
+    /// Clients created with `session()` are other sessions of the same
+    /// ensemble.
+    #[derive(Default)]
+    struct MockZkClient {
+        name: &'static str,
+        znodes: Arc<Mutex<MockZnodes>>,
+        script: Mutex<VecDeque<Option<io::ErrorKind>>>,
+    }
+
+    impl MockZkClient {
+        fn session(&self, name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                znodes: self.znodes.clone(),
+                ..Self::default()
+            })
+        }
+
+        /// Fails the next requests with `kinds`, `None` succeeds
+        fn fail_next(&self, kinds: impl IntoIterator<Item = Option<io::ErrorKind>>) {
+            self.script.lock().unwrap().extend(kinds);
+        }
+
+        fn expire(&self) {
+            self.znodes
+                .lock()
+                .unwrap()
+                .retain(|_, (_, _, owner)| *owner != Some(self.name));
+        }
+
+        fn request(&self) -> Result<()> {
+            match self.script.lock().unwrap().pop_front().flatten() {
+                Some(kind) => Err(TowerError::IoError(io::Error::new(
+                    kind,
+                    "request failure injected",
+                ))),
+                None => Ok(()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ZkClient for MockZkClient {
+        async fn create(&self, path: String, data: Vec<u8>, ephemeral: bool) -> Result<bool> {
+            self.request()?;
+            let mut znodes = self.znodes.lock().unwrap();
+            if znodes.contains_key(&path) {
+                return Ok(false);
+            }
+            znodes.insert(path, (data, 0, ephemeral.then_some(self.name)));
+            Ok(true)
+        }
+
+        async fn get(&self, path: String) -> Result<Option<(Vec<u8>, i32)>> {
+            self.request()?;
+            let znodes = self.znodes.lock().unwrap();
+            Ok(znodes
+                .get(&path)
+                .map(|(data, version, _)| (data.clone(), *version)))
+        }
+
+        async fn children(&self, path: String) -> Result<Vec<String>> {
+            self.request()?;
+            let prefix = format!("{path}/");
+            let znodes = self.znodes.lock().unwrap();
+            Ok(znodes
+                .keys()
+                .filter_map(|child| child.strip_prefix(&prefix))
+                .filter(|name| !name.contains('/'))
+                .map(str::to_string)
+                .collect())
+        }
+
+        async fn delete(&self, path: String, version: Option<i32>) -> Result<bool> {
+            self.request()?;
+            let mut znodes = self.znodes.lock().unwrap();
+            match znodes.get(&path) {
+                Some((_, current, _)) if version.is_none_or(|version| version == *current) => {
+                    znodes.remove(&path);
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+
+        async fn set_if_version(
+            &self,
+            check_path: String,
+            check_version: i32,
+            path: String,
+            data: Vec<u8>,
+        ) -> Result<bool> {
+            self.request()?;
+            let mut znodes = self.znodes.lock().unwrap();
+            if znodes.get(&check_path).map(|(_, version, _)| *version) != Some(check_version) {
+                return Ok(false);
+            }
+            let version = znodes.get(&path).map_or(0, |(_, version, _)| version + 1);
+            znodes.insert(path, (data, version, None));
+            Ok(true)
+        }
+    }
+
+    fn new_zk_tower_storage_for_tests(
+        client: &Arc<MockZkClient>,
+        instance_id: u8,
+    ) -> ZkTowerStorage {
+        let mut tower_storage = ZkTowerStorage::new_with_client(
+            client.clone(),
+            &ZkTowerStorageConfig::default(),
+            EtcdTowerStorage::new_runtime(),
+        );
+        tower_storage.instance_id = [instance_id; 8];
+        tower_storage
+    }
+
+    #[test]
+    fn test_zk_tower_storage() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = MockZkClient::default().session("first");
+        let first = new_zk_tower_storage_for_tests(&client, 1);
+        let second = new_zk_tower_storage_for_tests(&client.session("second"), 2);
+
+        assert_matches!(
+            Tower::restore(&first, &node_pubkey),
+            Err(TowerError::TowerNotFound(_))
+        );
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&first, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&first, &node_pubkey).unwrap(), tower);
+
+        // The second instance fails fast without disturbing the first
+        assert_matches!(
+            Tower::restore(&second, &node_pubkey),
+            Err(TowerError::TowerLocked(_))
+        );
+        tower.save(&first, &identity_keypair).unwrap();
+
+        // unless it forces a takeover
+        let second = second.with_force(true);
+        assert_eq!(Tower::restore(&second, &node_pubkey).unwrap(), tower);
+        assert_matches!(
+            tower.save(&first, &identity_keypair),
+            Err(TowerError::IoError(err)) if err.to_string().contains("Lost ZooKeeper instance lock")
+        );
+        tower.save(&second, &identity_keypair).unwrap();
+
+        // Dropping the holder releases the lock right away
+        drop(second);
+        let third = new_zk_tower_storage_for_tests(&client.session("third"), 3);
+        assert_eq!(Tower::restore(&third, &node_pubkey).unwrap(), tower);
+    }
+
+    #[test]
+    fn test_zk_tower_storage_session_expiry() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = MockZkClient::default().session("first");
+        let tower_storage = new_zk_tower_storage_for_tests(&client, 1);
+
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+
+        // The ephemeral instance znode goes with the session
+        client.expire();
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(err)) if err.to_string().contains("Lost ZooKeeper instance lock")
+        );
+
+        // and is recreated next time
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+    }
+
+    #[test]
+    fn test_zk_tower_storage_retries() {
+        let identity_keypair = Keypair::new();
+        let node_pubkey = identity_keypair.pubkey();
+        let client = MockZkClient::default().session("first");
+        let tower_storage = new_zk_tower_storage_for_tests(&client, 1);
+        let max_retries = ZkTowerStorageConfig::default().max_retries;
+
+        Tower::restore(&tower_storage, &node_pubkey).unwrap_err();
+        let tower = new_tower_for_tests(&identity_keypair, Some(1));
+
+        // Failures within the retry budget are hidden
+        client.fail_next(vec![Some(io::ErrorKind::NotConnected); max_retries]);
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+        assert_eq!(Tower::restore(&tower_storage, &node_pubkey).unwrap(), tower);
+
+        client.fail_next(vec![Some(io::ErrorKind::NotConnected); max_retries + 1]);
+        assert_matches!(
+            tower.save(&tower_storage, &identity_keypair),
+            Err(TowerError::IoError(err)) if err.to_string().contains("request failure injected")
+        );
+        assert!(client.script.lock().unwrap().is_empty());
+        tower.save(&tower_storage, &identity_keypair).unwrap();
+    }
+}
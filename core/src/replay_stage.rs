@@ -14,7 +14,7 @@ use {
             heaviest_subtree_fork_choice::HeaviestSubtreeForkChoice,
             latest_validator_votes_for_frozen_banks::LatestValidatorVotesForFrozenBanks,
             progress_map::{ForkProgress, ProgressMap, PropagatedStats},
-            tower_storage::{SavedTower, SavedTowerVersions, TowerStorage},
+            tower_storage::{SavedTowerVersions, SavedTowerWriteFormat, TowerStorage},
             tower_vote_state::TowerVoteState,
             BlockhashStatus, ComputedBankState, Stake, SwitchForkDecision, Tower, TowerError,
             VotedStakes, SWITCH_FORK_THRESHOLD,
@@ -262,6 +262,7 @@ pub struct ReplayStageConfig {
     pub block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
     pub wait_for_vote_to_start_leader: bool,
     pub tower_storage: Arc<dyn TowerStorage>,
+    pub saved_tower_format: SavedTowerWriteFormat,
     // Stops voting until this slot has been reached. Should be used to avoid
     // duplicate voting which can lead to slashing.
     pub wait_to_vote_slot: Option<Slot>,
@@ -564,6 +565,7 @@ impl ReplayStage {
             block_commitment_cache,
             wait_for_vote_to_start_leader,
             tower_storage,
+            saved_tower_format,
             wait_to_vote_slot,
             replay_forks_threads,
             replay_transactions_threads,
@@ -1021,6 +1023,7 @@ impl ReplayStage {
                         &mut epoch_slots_frozen_slots,
                         &drop_bank_sender,
                         wait_to_vote_slot,
+                        saved_tower_format,
                     ) {
                         error!("Unable to set root: {e}");
                         return;
@@ -2409,6 +2412,7 @@ impl ReplayStage {
         epoch_slots_frozen_slots: &mut EpochSlotsFrozenSlots,
         drop_bank_sender: &Sender<Vec<BankWithScheduler>>,
         wait_to_vote_slot: Option<Slot>,
+        saved_tower_format: SavedTowerWriteFormat,
     ) -> Result<(), SetRootError> {
         if bank.is_empty() {
             datapoint_info!("replay_stage-voted_empty_bank", ("slot", bank.slot(), i64));
@@ -2479,6 +2483,7 @@ impl ReplayStage {
             replay_timing,
             voting_sender,
             wait_to_vote_slot,
+            saved_tower_format,
         );
         Ok(())
     }
@@ -2786,6 +2791,7 @@ impl ReplayStage {
         replay_timing: &mut ReplayLoopTiming,
         voting_sender: &Sender<VoteOp>,
         wait_to_vote_slot: Option<Slot>,
+        saved_tower_format: SavedTowerWriteFormat,
     ) {
         let mut generate_time = Measure::start("generate_vote");
         let vote_tx_result = Self::generate_vote_tx(
@@ -2804,17 +2810,18 @@ impl ReplayStage {
         if let GenerateVoteTxResult::Tx(vote_tx) = vote_tx_result {
             tower.refresh_last_vote_tx_blockhash(vote_tx.message.recent_blockhash);
 
-            let saved_tower = SavedTower::new(tower, identity_keypair).unwrap_or_else(|err| {
-                error!("Unable to create saved tower: {:?}", err);
-                std::process::exit(1);
-            });
+            let saved_tower = SavedTowerVersions::new(tower, identity_keypair, saved_tower_format)
+                .unwrap_or_else(|err| {
+                    error!("Unable to create saved tower: {:?}", err);
+                    std::process::exit(1);
+                });
 
             let tower_slots = tower.tower_slots();
             voting_sender
                 .send(VoteOp::PushVote {
                     tx: vote_tx,
                     tower_slots,
-                    saved_tower: SavedTowerVersions::from(saved_tower),
+                    saved_tower,
                 })
                 .unwrap_or_else(|err| warn!("Error: {:?}", err));
         } else if vote_tx_result.is_non_voting() {
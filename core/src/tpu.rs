@@ -13,7 +13,10 @@ pub use {
 use {
     crate::{
         admin_rpc_post_init::{KeyUpdaterType, KeyUpdaters},
-        banking_stage::BankingStage,
+        banking_stage::{
+            duplicate_vote_filter::{DuplicateVoteFilter, DuplicateVoteFilterService},
+            BankingStage,
+        },
         banking_trace::{Channels, TracerThread},
         cluster_info_vote_listener::{
             ClusterInfoVoteListener, DuplicateConfirmedSlotsSender, GossipVerifiedVoteHashSender,
@@ -33,7 +36,9 @@ use {
     bytes::Bytes,
     crossbeam_channel::{bounded, unbounded, Receiver},
     solana_clock::Slot,
-    solana_gossip::cluster_info::ClusterInfo,
+    solana_gossip::{
+        cluster_info::ClusterInfo, duplicate_shred_handler::DuplicateSlotProofNotification,
+    },
     solana_keypair::Keypair,
     solana_ledger::{
         blockstore::Blockstore, blockstore_processor::TransactionStatusSender,
@@ -103,6 +108,7 @@ pub struct Tpu {
     sig_verifier: SigVerifier,
     vote_sigverify_stage: SigVerifyStage,
     banking_stage: BankingStage,
+    duplicate_vote_filter_service: DuplicateVoteFilterService,
     forwarding_stage: JoinHandle<()>,
     cluster_info_vote_listener: ClusterInfoVoteListener,
     broadcast_stage: BroadcastStage,
@@ -139,6 +145,7 @@ impl Tpu {
         bank_notification_sender: Option<BankNotificationSender>,
         tpu_coalesce: Duration,
         duplicate_confirmed_slot_sender: DuplicateConfirmedSlotsSender,
+        duplicate_slot_notification_receiver: Receiver<DuplicateSlotProofNotification>,
         client: ForwardingClientOption,
         turbine_quic_endpoint_sender: AsyncSender<(SocketAddr, Bytes)>,
         keypair: &Keypair,
@@ -320,6 +327,15 @@ impl Tpu {
             duplicate_confirmed_slot_sender,
         );
 
+        // Votes for slots proven duplicate are not held or forwarded
+        let duplicate_vote_filter = Arc::<DuplicateVoteFilter>::default();
+        let duplicate_vote_filter_service = DuplicateVoteFilterService::new(
+            duplicate_vote_filter.clone(),
+            duplicate_slot_notification_receiver,
+            bank_forks.clone(),
+            exit.clone(),
+        );
+
         let banking_stage = BankingStage::new(
             block_production_method,
             transaction_struct,
@@ -334,6 +350,7 @@ impl Tpu {
             log_messages_bytes_limit,
             bank_forks.clone(),
             prioritization_fee_cache,
+            Some(duplicate_vote_filter),
         );
 
         let SpawnForwardingStageResult {
@@ -390,6 +407,7 @@ impl Tpu {
             sig_verifier,
             vote_sigverify_stage,
             banking_stage,
+            duplicate_vote_filter_service,
             forwarding_stage,
             cluster_info_vote_listener,
             broadcast_stage,
@@ -409,6 +427,7 @@ impl Tpu {
             self.vote_sigverify_stage.join(),
             self.cluster_info_vote_listener.join(),
             self.banking_stage.join(),
+            self.duplicate_vote_filter_service.join(),
             self.forwarding_stage.join(),
             self.staked_nodes_updater_service.join(),
             self.tpu_quic_t.map_or(Ok(()), |t| t.join()),
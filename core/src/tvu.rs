@@ -10,7 +10,10 @@ use {
         },
         cluster_slots_service::{cluster_slots::ClusterSlots, ClusterSlotsService},
         completed_data_sets_service::CompletedDataSetsSender,
-        consensus::{tower_storage::TowerStorage, Tower},
+        consensus::{
+            tower_storage::{SavedTowerWriteFormat, TowerStorage},
+            Tower,
+        },
         cost_update_service::CostUpdateService,
         drop_bank_service::DropBankService,
         repair::repair_service::{OutstandingShredRepairs, RepairInfo, RepairServiceChannels},
@@ -26,7 +29,11 @@ use {
     solana_clock::Slot,
     solana_geyser_plugin_manager::block_metadata_notifier_interface::BlockMetadataNotifierArc,
     solana_gossip::{
-        cluster_info::ClusterInfo, duplicate_shred_handler::DuplicateShredHandler,
+        cluster_info::ClusterInfo,
+        duplicate_shred_handler::{
+            DuplicateShredHandler, DuplicateSlotProofNotification,
+            DEFAULT_PROOF_VERIFICATION_THREADS,
+        },
         duplicate_shred_listener::DuplicateShredListener,
     },
     solana_keypair::Keypair,
@@ -99,6 +106,7 @@ pub struct TvuConfig {
     pub replay_transactions_threads: NonZeroUsize,
     pub shred_sigverify_threads: NonZeroUsize,
     pub retransmit_xdp: Option<XdpConfig>,
+    pub saved_tower_format: SavedTowerWriteFormat,
 }
 
 impl Default for TvuConfig {
@@ -113,6 +121,7 @@ impl Default for TvuConfig {
             replay_transactions_threads: NonZeroUsize::new(1).expect("1 is non-zero"),
             shred_sigverify_threads: NonZeroUsize::new(1).expect("1 is non-zero"),
             retransmit_xdp: None,
+            saved_tower_format: SavedTowerWriteFormat::default(),
         }
     }
 }
@@ -151,6 +160,7 @@ impl Tvu {
         completed_data_sets_sender: Option<CompletedDataSetsSender>,
         bank_notification_sender: Option<BankNotificationSenderConfig>,
         duplicate_confirmed_slots_receiver: DuplicateConfirmedSlotsReceiver,
+        duplicate_slot_notification_sender: Sender<DuplicateSlotProofNotification>,
         tvu_config: TvuConfig,
         max_slots: &Arc<MaxSlots>,
         block_metadata_notifier: Option<BlockMetadataNotifierArc>,
@@ -230,6 +240,7 @@ impl Tvu {
 
         let (ancestor_duplicate_slots_sender, ancestor_duplicate_slots_receiver) = unbounded();
         let (duplicate_slots_sender, duplicate_slots_receiver) = unbounded();
+        let (duplicate_proof_sender, duplicate_proof_receiver) = unbounded();
         let (ancestor_hashes_replay_update_sender, ancestor_hashes_replay_update_receiver) =
             unbounded();
         let (dumped_slots_sender, dumped_slots_receiver) = unbounded();
@@ -265,6 +276,7 @@ impl Tvu {
                 retransmit_sender,
                 completed_data_sets_sender,
                 duplicate_slots_sender.clone(),
+                duplicate_proof_sender,
                 repair_service_channels,
             );
             WindowService::new(
@@ -327,6 +339,7 @@ impl Tvu {
             block_commitment_cache,
             wait_for_vote_to_start_leader: tvu_config.wait_for_vote_to_start_leader,
             tower_storage: tower_storage.clone(),
+            saved_tower_format: tvu_config.saved_tower_format,
             wait_to_vote_slot,
             replay_forks_threads: tvu_config.replay_forks_threads,
             replay_transactions_threads: tvu_config.replay_transactions_threads,
@@ -377,7 +390,7 @@ impl Tvu {
             BlockstoreCleanupService::new(blockstore.clone(), max_ledger_shreds, exit.clone())
         });
 
-        let duplicate_shred_listener = DuplicateShredListener::new(
+        let duplicate_shred_listener = DuplicateShredListener::new_with_publisher(
             exit,
             cluster_info.clone(),
             DuplicateShredHandler::new(
@@ -386,7 +399,12 @@ impl Tvu {
                 bank_forks.clone(),
                 duplicate_slots_sender,
                 tvu_config.shred_version,
-            ),
+            )
+            .with_proof_verification_threads(DEFAULT_PROOF_VERIFICATION_THREADS)
+            .with_duplicate_slot_subscribers(Arc::new(RwLock::new(vec![
+                duplicate_slot_notification_sender,
+            ]))),
+            duplicate_proof_receiver,
         );
 
         Ok(Tvu {
@@ -520,6 +538,7 @@ pub mod tests {
         let (_verified_vote_sender, verified_vote_receiver) = unbounded();
         let (replay_vote_sender, _replay_vote_receiver) = unbounded();
         let (_, gossip_confirmed_slots_receiver) = unbounded();
+        let (duplicate_slot_notification_sender, _) = unbounded();
         let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
         let ignored_prioritization_fee_cache = Arc::new(PrioritizationFeeCache::new(0u64));
         let outstanding_repair_requests = Arc::<RwLock<OutstandingShredRepairs>>::default();
@@ -580,6 +599,7 @@ pub mod tests {
             /*completed_data_sets_sender:*/ None,
             None,
             gossip_confirmed_slots_receiver,
+            duplicate_slot_notification_sender,
             TvuConfig::default(),
             &Arc::new(MaxSlots::default()),
             None,
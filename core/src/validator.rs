@@ -10,8 +10,11 @@ use {
         completed_data_sets_service::CompletedDataSetsService,
         consensus::{
             reconcile_blockstore_roots_with_external_source,
-            tower_storage::{NullTowerStorage, TowerStorage},
-            ExternalRootSource, Tower,
+            tower_storage::{
+                self, NullTowerStorage, SavedTowerWriteFormat, TowerConsistencyStrictness,
+                TowerStorage,
+            },
+            ExternalRootSource, Tower, TowerError,
         },
         repair::{
             self,
@@ -251,6 +254,9 @@ pub struct ValidatorConfig {
     pub run_verification: bool,
     pub require_tower: bool,
     pub tower_storage: Arc<dyn TowerStorage>,
+    /// Whether a restored tower inconsistent with blockstore is discarded
+    pub tower_consistency_strictness: TowerConsistencyStrictness,
+    pub saved_tower_format: SavedTowerWriteFormat,
     pub debug_keys: Option<Arc<HashSet<Pubkey>>>,
     pub contact_debug_interval: u64,
     pub contact_save_interval: u64,
@@ -327,6 +333,8 @@ impl Default for ValidatorConfig {
             run_verification: true,
             require_tower: false,
             tower_storage: Arc::new(NullTowerStorage::default()),
+            tower_consistency_strictness: TowerConsistencyStrictness::default(),
+            saved_tower_format: SavedTowerWriteFormat::default(),
             debug_keys: None,
             contact_debug_interval: DEFAULT_CONTACT_DEBUG_INTERVAL_MILLIS,
             contact_save_interval: DEFAULT_CONTACT_SAVE_INTERVAL_MILLIS,
@@ -625,6 +633,22 @@ impl Validator {
             })?;
         }
 
+        if !config.voting_disabled {
+            // Fail now rather than on the first vote, well after startup
+            let mut report = config
+                .tower_storage
+                .preflight(&id)
+                .map_err(ValidatorError::TowerStoragePreflight)?;
+            // Tower restore tolerates an unusable tower unless one is required
+            if let Some(err) = report.load_error.take() {
+                if config.require_tower {
+                    return Err(ValidatorError::TowerStoragePreflight(err).into());
+                }
+                warn!("tower storage preflight: unable to load the saved tower: {err}");
+            }
+            info!("tower storage preflight: {report:?}");
+        }
+
         let mut bank_notification_senders = Vec::new();
 
         let exit = Arc::new(AtomicBool::new(false));
@@ -1375,6 +1399,8 @@ impl Validator {
         let (verified_vote_sender, verified_vote_receiver) = unbounded();
         let (gossip_verified_vote_hash_sender, gossip_verified_vote_hash_receiver) = unbounded();
         let (duplicate_confirmed_slot_sender, duplicate_confirmed_slots_receiver) = unbounded();
+        let (duplicate_slot_notification_sender, duplicate_slot_notification_receiver) =
+            unbounded();
 
         let entry_notification_sender = entry_notifier_service
             .as_ref()
@@ -1529,6 +1555,7 @@ impl Validator {
             completed_data_sets_sender,
             bank_notification_sender.clone(),
             duplicate_confirmed_slots_receiver,
+            duplicate_slot_notification_sender,
             TvuConfig {
                 max_ledger_shreds: config.max_ledger_shreds,
                 shred_version: node.info.shred_version(),
@@ -1539,6 +1566,7 @@ impl Validator {
                 replay_transactions_threads: config.replay_transactions_threads,
                 shred_sigverify_threads: config.tvu_shred_sigverify_threads,
                 retransmit_xdp: config.retransmit_xdp.clone(),
+                saved_tower_format: config.saved_tower_format,
             },
             &max_slots,
             block_metadata_notifier,
@@ -1632,6 +1660,7 @@ impl Validator {
             bank_notification_sender.map(|sender| sender.sender),
             config.tpu_coalesce,
             duplicate_confirmed_slot_sender,
+            duplicate_slot_notification_receiver,
             forwarding_tpu_client,
             turbine_quic_endpoint_sender,
             &identity_keypair,
@@ -1927,6 +1956,7 @@ fn post_process_restored_tower(
     vote_account: &Pubkey,
     config: &ValidatorConfig,
     bank_forks: &BankForks,
+    blockstore: &Blockstore,
 ) -> Result<Tower, String> {
     let mut should_require_tower = config.require_tower;
 
@@ -1962,7 +1992,13 @@ fn post_process_restored_tower(
             return Err(crate::consensus::TowerError::HardFork(warp_slot));
         }
 
-        tower
+        let tower = tower?;
+        let report = tower_storage::verify_against_blockstore(&tower, blockstore, root_bank.slot());
+        if !report.is_consistent() {
+            warn!("Restored tower is inconsistent with blockstore: {report}");
+        }
+        report.into_result(config.tower_consistency_strictness)?;
+        Ok(tower)
     });
 
     let restored_tower = match restored_tower {
@@ -1976,6 +2012,12 @@ fn post_process_restored_tower(
                     ("error", format!("Unable to restore tower: {err}"), String),
                 );
             }
+            if let TowerError::TowerLocked(_) = err {
+                return Err(format!(
+                    "Tower is in use by another validator instance: {err}. Aborting to avoid \
+                     conflicting duplicate votes"
+                ));
+            }
             if should_require_tower && voting_has_been_active {
                 return Err(format!(
                     "Requested mandatory tower restore failed: {err}. And there is an existing \
@@ -2266,6 +2308,7 @@ impl<'a> ProcessBlockStore<'a> {
                     self.vote_account,
                     self.config,
                     &self.bank_forks.read().unwrap(),
+                    self.blockstore,
                 )?
             });
 
@@ -2578,6 +2621,9 @@ pub enum ValidatorError {
     #[error("shred version mismatch: actual {actual}, expected {expected}")]
     ShredVersionMismatch { actual: u16, expected: u16 },
 
+    #[error("tower storage preflight failed: {0}")]
+    TowerStoragePreflight(#[source] TowerError),
+
     #[error(transparent)]
     TraceError(#[from] TraceError),
 
@@ -15,7 +15,7 @@ use {
     crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender},
     rayon::{prelude::*, ThreadPool},
     solana_clock::{Slot, DEFAULT_MS_PER_SLOT},
-    solana_gossip::cluster_info::ClusterInfo,
+    solana_gossip::duplicate_shred_listener::LocalDuplicateProof,
     solana_ledger::{
         blockstore::{Blockstore, BlockstoreInsertionMetrics, PossibleDuplicateShred},
         leader_schedule_cache::LeaderScheduleCache,
@@ -41,6 +41,7 @@ use {
 
 type DuplicateSlotSender = Sender<Slot>;
 pub(crate) type DuplicateSlotReceiver = Receiver<Slot>;
+type DuplicateProofSender = Sender<LocalDuplicateProof>;
 
 #[derive(Default)]
 struct WindowServiceMetrics {
@@ -104,10 +105,10 @@ impl WindowServiceMetrics {
 }
 
 fn run_check_duplicate(
-    cluster_info: &ClusterInfo,
     blockstore: &Blockstore,
     shred_receiver: &Receiver<PossibleDuplicateShred>,
     duplicate_slots_sender: &DuplicateSlotSender,
+    duplicate_proof_sender: &DuplicateProofSender,
     bank_forks: &RwLock<BankForks>,
 ) -> Result<()> {
     let mut root_bank = bank_forks.read().unwrap().root_bank();
@@ -166,7 +167,7 @@ fn run_check_duplicate(
         };
 
         // Propagate duplicate proof through gossip
-        cluster_info.push_duplicate_shred(&shred1, &shred2)?;
+        duplicate_proof_sender.send((shred_slot, shred1.into_payload(), shred2))?;
         // Notify duplicate consensus state machine
         duplicate_slots_sender.send(shred_slot)?;
 
@@ -247,6 +248,8 @@ pub struct WindowServiceChannels {
     pub retransmit_sender: EvictingSender<Vec<shred::Payload>>,
     pub completed_data_sets_sender: Option<CompletedDataSetsSender>,
     pub duplicate_slots_sender: DuplicateSlotSender,
+    /// Sends locally detected duplicate slot proofs to be pushed into gossip
+    pub duplicate_proof_sender: DuplicateProofSender,
     pub repair_service_channels: RepairServiceChannels,
 }
 
@@ -256,6 +259,7 @@ impl WindowServiceChannels {
         retransmit_sender: EvictingSender<Vec<shred::Payload>>,
         completed_data_sets_sender: Option<CompletedDataSetsSender>,
         duplicate_slots_sender: DuplicateSlotSender,
+        duplicate_proof_sender: DuplicateProofSender,
         repair_service_channels: RepairServiceChannels,
     ) -> Self {
         Self {
@@ -263,6 +267,7 @@ impl WindowServiceChannels {
             retransmit_sender,
             completed_data_sets_sender,
             duplicate_slots_sender,
+            duplicate_proof_sender,
             repair_service_channels,
         }
     }
@@ -285,7 +290,6 @@ impl WindowService {
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         outstanding_repair_requests: Arc<RwLock<OutstandingShredRepairs>>,
     ) -> WindowService {
-        let cluster_info = repair_info.cluster_info.clone();
         let bank_forks = repair_info.bank_forks.clone();
 
         // In wen_restart, we discard all shreds from Turbine and keep only those from repair to
@@ -297,6 +301,7 @@ impl WindowService {
             retransmit_sender,
             completed_data_sets_sender,
             duplicate_slots_sender,
+            duplicate_proof_sender,
             repair_service_channels,
         } = window_service_channels;
 
@@ -313,11 +318,11 @@ impl WindowService {
         let (duplicate_sender, duplicate_receiver) = unbounded();
 
         let t_check_duplicate = Self::start_check_duplicate_thread(
-            cluster_info,
             exit.clone(),
             blockstore.clone(),
             duplicate_receiver,
             duplicate_slots_sender,
+            duplicate_proof_sender,
             bank_forks,
         );
 
@@ -340,11 +345,11 @@ impl WindowService {
     }
 
     fn start_check_duplicate_thread(
-        cluster_info: Arc<ClusterInfo>,
         exit: Arc<AtomicBool>,
         blockstore: Arc<Blockstore>,
         duplicate_receiver: Receiver<PossibleDuplicateShred>,
         duplicate_slots_sender: DuplicateSlotSender,
+        duplicate_proof_sender: DuplicateProofSender,
         bank_forks: Arc<RwLock<BankForks>>,
     ) -> JoinHandle<()> {
         let handle_error = || {
@@ -355,10 +360,10 @@ impl WindowService {
             .spawn(move || {
                 while !exit.load(Ordering::Relaxed) {
                     if let Err(e) = run_check_duplicate(
-                        &cluster_info,
                         &blockstore,
                         &duplicate_receiver,
                         &duplicate_slots_sender,
+                        &duplicate_proof_sender,
                         &bank_forks,
                     ) {
                         if Self::should_exit_on_error(e, &handle_error) {
@@ -463,7 +468,6 @@ mod test {
         super::*,
         rand::Rng,
         solana_entry::entry::{create_ticks, Entry},
-        solana_gossip::contact_info::ContactInfo,
         solana_hash::Hash,
         solana_keypair::Keypair,
         solana_ledger::{
@@ -473,9 +477,6 @@ mod test {
             shred::{ProcessShredsStats, Shredder},
         },
         solana_runtime::bank::Bank,
-        solana_signer::Signer,
-        solana_streamer::socket::SocketAddrSpace,
-        solana_time_utils::timestamp,
     };
 
     fn local_entries_to_shred(
@@ -539,18 +540,12 @@ mod test {
             .send(PossibleDuplicateShred::Exists(duplicate_shred.clone()))
             .unwrap();
         assert!(!blockstore.has_duplicate_shreds_in_slot(duplicate_shred_slot));
-        let keypair = Keypair::new();
-        let contact_info = ContactInfo::new_localhost(&keypair.pubkey(), timestamp());
-        let cluster_info = ClusterInfo::new(
-            contact_info,
-            Arc::new(keypair),
-            SocketAddrSpace::Unspecified,
-        );
+        let (duplicate_proof_sender, duplicate_proof_receiver) = unbounded();
         run_check_duplicate(
-            &cluster_info,
             &blockstore,
             &receiver,
             &duplicate_slot_sender,
+            &duplicate_proof_sender,
             &bank_forks,
         )
         .unwrap();
@@ -565,6 +560,12 @@ mod test {
             duplicate_slot_receiver.try_recv().unwrap(),
             duplicate_shred_slot
         );
+
+        // Make sure the proof was sent to be pushed into gossip
+        let (slot, shred1, shred2) = duplicate_proof_receiver.try_recv().unwrap();
+        assert_eq!(slot, duplicate_shred_slot);
+        assert_eq!(shred1, *duplicate_shred.payload());
+        assert_eq!(shred2, *original_shred.payload());
     }
 
     #[test]
@@ -573,24 +574,18 @@ mod test {
         let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
         let (duplicate_shred_sender, duplicate_shred_receiver) = unbounded();
         let (duplicate_slot_sender, duplicate_slot_receiver) = unbounded();
+        let (duplicate_proof_sender, _duplicate_proof_receiver) = unbounded();
         let exit = Arc::new(AtomicBool::new(false));
-        let keypair = Keypair::new();
-        let contact_info = ContactInfo::new_localhost(&keypair.pubkey(), timestamp());
-        let cluster_info = Arc::new(ClusterInfo::new(
-            contact_info,
-            Arc::new(keypair),
-            SocketAddrSpace::Unspecified,
-        ));
         let genesis_config = create_genesis_config(10_000).genesis_config;
         let bank_forks = BankForks::new_rw_arc(Bank::new_for_tests(&genesis_config));
 
         // Start duplicate thread receiving and inserting duplicates
         let t_check_duplicate = WindowService::start_check_duplicate_thread(
-            cluster_info,
             exit.clone(),
             blockstore.clone(),
             duplicate_shred_receiver,
             duplicate_slot_sender,
+            duplicate_proof_sender,
             bank_forks,
         );
 
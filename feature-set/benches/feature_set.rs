@@ -0,0 +1,24 @@
+use {
+    agave_feature_set::{FeatureSet, FEATURE_NAMES},
+    criterion::{black_box, criterion_group, criterion_main, Criterion},
+};
+
+fn bench_state_hash(c: &mut Criterion) {
+    let mut half_enabled = FeatureSet::default();
+    for (slot, feature_id) in FEATURE_NAMES.keys().step_by(2).enumerate() {
+        half_enabled.activate(feature_id, slot as u64);
+    }
+
+    for (name, feature_set) in [
+        ("default", FeatureSet::default()),
+        ("all_enabled", FeatureSet::all_enabled()),
+        ("half_enabled", half_enabled),
+    ] {
+        c.bench_function(&format!("state_hash_{name}"), |bencher| {
+            bencher.iter(|| black_box(&feature_set).state_hash())
+        });
+    }
+}
+
+criterion_group!(benches, bench_state_hash);
+criterion_main!(benches);
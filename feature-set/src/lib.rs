@@ -2,12 +2,23 @@
 
 use {
     ahash::{AHashMap, AHashSet},
+    serde::{
+        de::{self, Deserialize, Deserializer},
+        ser::{Serialize, Serializer},
+    },
+    serde_derive::{Deserialize, Serialize},
     solana_epoch_schedule::EpochSchedule,
     solana_hash::Hash,
     solana_pubkey::Pubkey,
     solana_sha256_hasher::Hasher,
     solana_svm_feature_set::SVMFeatureSet,
-    std::sync::LazyLock,
+    std::{
+        collections::HashMap,
+        fmt,
+        ops::RangeInclusive,
+        sync::{Arc, LazyLock, Mutex, RwLock},
+    },
+    thiserror::Error,
 };
 
 #[cfg_attr(feature = "frozen-abi", derive(solana_frozen_abi_macro::AbiExample))]
@@ -15,6 +26,87 @@ use {
 pub struct FeatureSet {
     active: AHashMap<Pubkey, u64>,
     inactive: AHashSet<Pubkey>,
+    /// Feature ids not known to this software, as found when deserializing
+    unknown: AHashSet<Pubkey>,
+    /// Feature ids listed in `RETIRED_FEATURES`, as found when deserializing
+    retired: AHashSet<Pubkey>,
+    /// Inactive features staged for activation at the next epoch boundary,
+    /// with the slot they were staged at
+    pending: AHashMap<Pubkey, u64>,
+    observers: FeatureSetObservers,
+}
+
+/// Callback invoked with the feature id and activation slot when a feature is
+/// activated
+pub type ActivationCallback = Box<dyn Fn(&Pubkey, u64) + Send + Sync>;
+
+/// Callback invoked with the feature id when a feature is deactivated
+pub type DeactivationCallback = Box<dyn Fn(&Pubkey) + Send + Sync>;
+
+/// Handle returned when subscribing to a `FeatureSet`, used to unsubscribe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionHandle(u64);
+
+/// Callbacks subscribed to a `FeatureSet`. Subscriptions are shared by a
+/// feature set and its clones, so that they follow a feature set as it is
+/// cloned for each new bank, and are ignored when comparing feature sets.
+/// Callbacks must not subscribe or unsubscribe.
+#[derive(Clone, Default)]
+struct FeatureSetObservers(Arc<RwLock<ObserverList>>);
+
+#[derive(Default)]
+struct ObserverList {
+    next_handle: u64,
+    on_activate: Vec<(SubscriptionHandle, ActivationCallback)>,
+    on_deactivate: Vec<(SubscriptionHandle, DeactivationCallback)>,
+}
+
+impl ObserverList {
+    fn next_handle(&mut self) -> SubscriptionHandle {
+        let handle = SubscriptionHandle(self.next_handle);
+        self.next_handle = self.next_handle.wrapping_add(1);
+        handle
+    }
+}
+
+impl FeatureSetObservers {
+    fn notify_activated(&self, feature_id: &Pubkey, slot: u64) {
+        for (_, callback) in &self.0.read().unwrap().on_activate {
+            callback(feature_id, slot);
+        }
+    }
+
+    fn notify_deactivated(&self, feature_id: &Pubkey) {
+        for (_, callback) in &self.0.read().unwrap().on_deactivate {
+            callback(feature_id);
+        }
+    }
+}
+
+impl PartialEq for FeatureSetObservers {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for FeatureSetObservers {}
+
+impl fmt::Debug for FeatureSetObservers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let observers = self.0.read().unwrap();
+        f.debug_struct("FeatureSetObservers")
+            .field("on_activate", &observers.on_activate.len())
+            .field("on_deactivate", &observers.on_deactivate.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "frozen-abi")]
+impl solana_frozen_abi::abi_example::AbiExample for FeatureSetObservers {
+    fn example() -> Self {
+        // Observers are not serialized so just rely on Default.
+        Self::default()
+    }
 }
 
 impl Default for FeatureSet {
@@ -23,13 +115,124 @@ impl Default for FeatureSet {
             // All features disabled
             active: AHashMap::new(),
             inactive: AHashSet::from_iter((*FEATURE_NAMES).keys().cloned()),
+            unknown: AHashSet::new(),
+            retired: AHashSet::new(),
+            pending: AHashMap::new(),
+            observers: FeatureSetObservers::default(),
+        }
+    }
+}
+
+/// Serialized form of a `FeatureSet`. Entries are sorted so that the
+/// output is deterministic and can be diffed.
+#[derive(Serialize, Deserialize)]
+struct SerializedFeatureSet {
+    active: Vec<(Pubkey, u64)>,
+    inactive: Vec<Pubkey>,
+    #[serde(default)]
+    unknown: Vec<Pubkey>,
+    #[serde(default)]
+    retired: Vec<Pubkey>,
+    #[serde(default)]
+    pending: Vec<(Pubkey, u64)>,
+}
+
+impl Serialize for FeatureSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut active = self
+            .active
+            .iter()
+            .map(|(feature_id, slot)| (*feature_id, *slot))
+            .collect::<Vec<_>>();
+        active.sort_unstable();
+        let mut inactive = self.inactive.iter().copied().collect::<Vec<_>>();
+        inactive.sort_unstable();
+        let mut unknown = self.unknown.iter().copied().collect::<Vec<_>>();
+        unknown.sort_unstable();
+        let mut retired = self.retired.iter().copied().collect::<Vec<_>>();
+        retired.sort_unstable();
+        let mut pending = self
+            .pending
+            .iter()
+            .map(|(feature_id, slot)| (*feature_id, *slot))
+            .collect::<Vec<_>>();
+        pending.sort_unstable();
+        SerializedFeatureSet {
+            active,
+            inactive,
+            unknown,
+            retired,
+            pending,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let serialized = SerializedFeatureSet::deserialize(deserializer)?;
+        let inactive = AHashSet::from_iter(serialized.inactive);
+        let mut unknown = AHashSet::new();
+        let mut retired = AHashSet::new();
+        let mut unrecognized = |feature_id: Pubkey| {
+            if RETIRED_FEATURES.contains_key(&feature_id) {
+                retired.insert(feature_id);
+            } else {
+                unknown.insert(feature_id);
+            }
+        };
+        let mut active = AHashMap::with_capacity(serialized.active.len());
+        for (feature_id, slot) in serialized.active {
+            if inactive.contains(&feature_id) {
+                return Err(de::Error::custom(format!(
+                    "feature {feature_id} is both active and inactive"
+                )));
+            }
+            if FEATURE_NAMES.contains_key(&feature_id) {
+                active.insert(feature_id, slot);
+            } else {
+                unrecognized(feature_id);
+            }
         }
+        inactive
+            .into_iter()
+            .chain(serialized.unknown)
+            .chain(serialized.retired)
+            .filter(|feature_id| !FEATURE_NAMES.contains_key(feature_id))
+            .for_each(unrecognized);
+        // Rebuild the inactive set so that active and inactive together cover
+        // exactly the features known to this software
+        let inactive: AHashSet<_> = FEATURE_NAMES
+            .keys()
+            .filter(|feature_id| !active.contains_key(feature_id))
+            .copied()
+            .collect();
+        let pending = serialized
+            .pending
+            .into_iter()
+            .filter(|(feature_id, _)| inactive.contains(feature_id))
+            .collect();
+        Ok(Self {
+            active,
+            inactive,
+            unknown,
+            retired,
+            pending,
+            observers: FeatureSetObservers::default(),
+        })
     }
 }
 
 impl FeatureSet {
     pub fn new(active: AHashMap<Pubkey, u64>, inactive: AHashSet<Pubkey>) -> Self {
-        Self { active, inactive }
+        Self {
+            active,
+            inactive,
+            unknown: AHashSet::new(),
+            retired: AHashSet::new(),
+            pending: AHashMap::new(),
+            observers: FeatureSetObservers::default(),
+        }
     }
 
     pub fn active(&self) -> &AHashMap<Pubkey, u64> {
@@ -48,37 +251,423 @@ impl FeatureSet {
         &mut self.inactive
     }
 
+    /// Feature ids that were not recognized by this software when deserialized
+    pub fn unknown(&self) -> &AHashSet<Pubkey> {
+        &self.unknown
+    }
+
+    /// Retired feature ids that were found when deserialized
+    pub fn retired(&self) -> &AHashSet<Pubkey> {
+        &self.retired
+    }
+
+    /// Status of a feature as seen by this software
+    pub fn classify(&self, feature_id: &Pubkey) -> FeatureStatus {
+        if let Some(slot) = self.activated_slot(feature_id) {
+            FeatureStatus::Active(slot)
+        } else if let Some(reason) = RETIRED_FEATURES.get(feature_id) {
+            FeatureStatus::Retired(reason)
+        } else if self.inactive.contains(feature_id) || FEATURE_NAMES.contains_key(feature_id) {
+            FeatureStatus::Inactive
+        } else {
+            FeatureStatus::Unknown
+        }
+    }
+
     pub fn is_active(&self, feature_id: &Pubkey) -> bool {
         self.active.contains_key(feature_id)
     }
 
+    /// Typed equivalent of `is_active`
+    pub fn is_active_feature(&self, feature: Features) -> bool {
+        self.is_active(&feature.id())
+    }
+
     pub fn activated_slot(&self, feature_id: &Pubkey) -> Option<u64> {
         self.active.get(feature_id).copied()
     }
 
-    /// Activate a feature
+    /// Whether a feature was active at `slot`, i.e. it is active and was
+    /// activated at or before `slot`
+    pub fn is_active_at(&self, feature_id: &Pubkey, slot: u64) -> bool {
+        self.activated_slot(feature_id)
+            .is_some_and(|activation_slot| activation_slot <= slot)
+    }
+
+    /// The feature set as it was at `slot`, with features activated after
+    /// `slot` moved back to inactive
+    pub fn active_at(&self, slot: u64) -> FeatureSet {
+        let mut feature_set = self.clone();
+        feature_set.active.retain(|feature_id, activation_slot| {
+            if *activation_slot <= slot {
+                true
+            } else {
+                feature_set.inactive.insert(*feature_id);
+                false
+            }
+        });
+        feature_set
+    }
+
+    /// Hash of the feature activation state: the active features with their
+    /// activation slots, followed by the inactive features, each sorted by
+    /// feature id
+    pub fn state_hash(&self) -> Hash {
+        let mut active = self
+            .active
+            .iter()
+            .map(|(feature_id, slot)| (*feature_id, *slot))
+            .collect::<Vec<_>>();
+        active.sort_unstable();
+        let mut inactive = self.inactive.iter().collect::<Vec<_>>();
+        inactive.sort_unstable();
+
+        let mut hasher = Hasher::default();
+        hasher.hash(&(active.len() as u64).to_le_bytes());
+        for (feature_id, slot) in active {
+            hasher.hash(feature_id.as_ref());
+            hasher.hash(&slot.to_le_bytes());
+        }
+        hasher.hash(&(inactive.len() as u64).to_le_bytes());
+        for feature_id in inactive {
+            hasher.hash(feature_id.as_ref());
+        }
+        hasher.result()
+    }
+
+    /// List of features activated within `range`, sorted by activation slot
+    pub fn activated_in_range(&self, range: RangeInclusive<u64>) -> Vec<(Pubkey, u64)> {
+        let mut activated = self
+            .active
+            .iter()
+            .filter(|(_, slot)| range.contains(slot))
+            .map(|(feature_id, slot)| (*feature_id, *slot))
+            .collect::<Vec<_>>();
+        activated.sort_unstable_by_key(|(feature_id, slot)| (*slot, *feature_id));
+        activated
+    }
+
+    /// Active features in activation order, sorted by slot and then by id,
+    /// paired with their description
+    pub fn activation_history(&self) -> Vec<(u64, Pubkey, &'static str)> {
+        self.activated_in_range(0..=u64::MAX)
+            .into_iter()
+            .map(|(feature_id, slot)| {
+                let description = FEATURE_NAMES.get(&feature_id).copied().unwrap_or("unknown");
+                (slot, feature_id, description)
+            })
+            .collect()
+    }
+
+    /// Most recently activated feature, ties broken by the largest id
+    pub fn last_activated(&self) -> Option<(u64, Pubkey)> {
+        self.active
+            .iter()
+            .map(|(feature_id, slot)| (*slot, *feature_id))
+            .max()
+    }
+
+    /// Features supported by this software that are not active, sorted by id
+    pub fn inactive_supported(&self) -> Vec<(Pubkey, &'static str)> {
+        let mut inactive = FEATURE_NAMES
+            .iter()
+            .filter(|(feature_id, _)| !self.is_active(feature_id))
+            .map(|(feature_id, description)| (*feature_id, *description))
+            .collect::<Vec<_>>();
+        inactive.sort_unstable();
+        inactive
+    }
+
+    /// Fraction of the features supported by this software that are active
+    pub fn activation_coverage(&self) -> f64 {
+        let active = FEATURE_NAMES
+            .keys()
+            .filter(|feature_id| self.is_active(feature_id))
+            .count();
+        active as f64 / FEATURE_NAMES.len() as f64
+    }
+
+    /// List of features activated exactly at `slot`
+    pub fn activated_at(&self, slot: u64) -> Vec<Pubkey> {
+        self.activated_in_range(slot..=slot)
+            .into_iter()
+            .map(|(feature_id, _)| feature_id)
+            .collect()
+    }
+
+    /// Compare the active features of `self` against `other`
+    pub fn diff(&self, other: &FeatureSet) -> FeatureSetDiff {
+        let mut diff = FeatureSetDiff::default();
+        for (feature_id, slot) in &self.active {
+            match other.activated_slot(feature_id) {
+                None => diff.active_only_in_self.push((*feature_id, *slot)),
+                Some(other_slot) if other_slot != *slot => {
+                    diff.slot_mismatches.push((*feature_id, *slot, other_slot))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.active_only_in_other = other
+            .active
+            .iter()
+            .filter(|(feature_id, _)| !self.is_active(feature_id))
+            .map(|(feature_id, slot)| (*feature_id, *slot))
+            .collect();
+        diff.active_only_in_self.sort_unstable();
+        diff.active_only_in_other.sort_unstable();
+        diff.slot_mismatches.sort_unstable();
+        diff
+    }
+
+    /// Whether `self` and `other` have the same active and inactive features,
+    /// regardless of the slots the features were activated at. `==` also
+    /// compares the activation slots.
+    pub fn same_features(&self, other: &FeatureSet) -> bool {
+        self.active.len() == other.active.len()
+            && self
+                .active
+                .keys()
+                .all(|feature_id| other.is_active(feature_id))
+            && self.inactive == other.inactive
+    }
+
+    /// Features active in only one of `self` and `other`, sorted by id
+    pub fn symmetric_difference(&self, other: &FeatureSet) -> Vec<Pubkey> {
+        let mut features: Vec<_> = self
+            .active
+            .keys()
+            .filter(|feature_id| !other.is_active(feature_id))
+            .chain(
+                other
+                    .active
+                    .keys()
+                    .filter(|feature_id| !self.is_active(feature_id)),
+            )
+            .copied()
+            .collect();
+        features.sort_unstable();
+        features
+    }
+
+    /// Features active on the cluster, per `cluster_active`, that this
+    /// software doesn't know, sorted by id. Retired features are known, so
+    /// they are not listed.
+    pub fn unknown_active(&self, cluster_active: &HashMap<Pubkey, u64>) -> Vec<(Pubkey, u64)> {
+        let mut unknown: Vec<_> = cluster_active
+            .iter()
+            .filter(|(feature_id, _)| {
+                !FEATURE_NAMES.contains_key(feature_id)
+                    && !RETIRED_FEATURES.contains_key(feature_id)
+            })
+            .map(|(feature_id, slot)| (*feature_id, *slot))
+            .collect();
+        unknown.sort_unstable();
+        unknown
+    }
+
+    /// Activate the known features active on the cluster, per
+    /// `cluster_active`, and record the unknown ones. A feature already active
+    /// here keeps its activation slot unless the cluster reports an earlier
+    /// one.
+    pub fn merge_cluster_state(&mut self, cluster_active: &HashMap<Pubkey, u64>) -> MergeReport {
+        let mut report = MergeReport::default();
+        for (feature_id, cluster_slot) in cluster_active {
+            if RETIRED_FEATURES.contains_key(feature_id) {
+                self.retired.insert(*feature_id);
+                continue;
+            }
+            if !FEATURE_NAMES.contains_key(feature_id) {
+                self.unknown.insert(*feature_id);
+                report.unknown.push((*feature_id, *cluster_slot));
+                continue;
+            }
+            match self.activated_slot(feature_id) {
+                Some(slot) if slot <= *cluster_slot => {
+                    if slot != *cluster_slot {
+                        report
+                            .slot_mismatches
+                            .push((*feature_id, slot, *cluster_slot));
+                    }
+                }
+                slot => {
+                    if let Some(slot) = slot {
+                        report
+                            .slot_mismatches
+                            .push((*feature_id, slot, *cluster_slot));
+                    }
+                    self.pending.remove(feature_id);
+                    self.activate(feature_id, *cluster_slot);
+                    report.activated.push((*feature_id, *cluster_slot));
+                }
+            }
+        }
+        report.activated.sort_unstable();
+        report.slot_mismatches.sort_unstable();
+        report.unknown.sort_unstable();
+        report
+    }
+
+    /// Activate a feature, notifying activation subscribers if the feature
+    /// was not already active at `slot`
     pub fn activate(&mut self, feature_id: &Pubkey, slot: u64) {
         self.inactive.remove(feature_id);
-        self.active.insert(*feature_id, slot);
+        if self.active.insert(*feature_id, slot) != Some(slot) {
+            self.observers.notify_activated(feature_id, slot);
+        }
+    }
+
+    /// Activate a feature, failing if any of its declared dependencies in
+    /// `FEATURE_DEPENDENCIES` is inactive
+    pub fn try_activate(
+        &mut self,
+        feature_id: &Pubkey,
+        slot: u64,
+    ) -> Result<(), FeatureActivationError> {
+        self.try_activate_with_dependencies(feature_id, slot, &FEATURE_DEPENDENCIES)
+    }
+
+    fn try_activate_with_dependencies(
+        &mut self,
+        feature_id: &Pubkey,
+        slot: u64,
+        dependencies: &AHashMap<Pubkey, Vec<Pubkey>>,
+    ) -> Result<(), FeatureActivationError> {
+        if let Some(dependency) = dependencies
+            .get(feature_id)
+            .into_iter()
+            .flatten()
+            .find(|dependency| !self.is_active(dependency))
+        {
+            return Err(FeatureActivationError::InactiveDependency {
+                feature_id: *feature_id,
+                dependency: *dependency,
+            });
+        }
+        self.activate(feature_id, slot);
+        Ok(())
     }
 
-    /// Deactivate a feature
+    /// List active features whose declared dependencies are not active
+    pub fn validate(&self) -> Vec<DependencyViolation> {
+        self.validate_with_dependencies(&FEATURE_DEPENDENCIES)
+    }
+
+    fn validate_with_dependencies(
+        &self,
+        dependencies: &AHashMap<Pubkey, Vec<Pubkey>>,
+    ) -> Vec<DependencyViolation> {
+        let mut violations = self
+            .active
+            .keys()
+            .filter_map(|feature_id| Some((feature_id, dependencies.get(feature_id)?)))
+            .flat_map(|(feature_id, feature_dependencies)| {
+                feature_dependencies
+                    .iter()
+                    .filter(|dependency| !self.is_active(dependency))
+                    .map(|dependency| DependencyViolation {
+                        feature_id: *feature_id,
+                        missing_dependency: *dependency,
+                    })
+            })
+            .collect::<Vec<_>>();
+        violations
+            .sort_unstable_by_key(|violation| (violation.feature_id, violation.missing_dependency));
+        violations
+    }
+
+    /// Deactivate a feature, notifying deactivation subscribers if the
+    /// feature was active
     pub fn deactivate(&mut self, feature_id: &Pubkey) {
-        self.active.remove(feature_id);
+        let was_active = self.active.remove(feature_id).is_some();
+        self.pending.remove(feature_id);
         self.inactive.insert(*feature_id);
+        if was_active {
+            self.observers.notify_deactivated(feature_id);
+        }
+    }
+
+    /// Register a callback invoked on every activation through `activate()`,
+    /// including those made in bulk such as by `promote_pending()`. Changes
+    /// made through `active_mut()` or `inactive_mut()` are not observed.
+    pub fn subscribe(&mut self, callback: ActivationCallback) -> SubscriptionHandle {
+        let mut observers = self.observers.0.write().unwrap();
+        let handle = observers.next_handle();
+        observers.on_activate.push((handle, callback));
+        handle
+    }
+
+    /// Register a callback invoked on every deactivation through `deactivate()`
+    pub fn subscribe_deactivation(&mut self, callback: DeactivationCallback) -> SubscriptionHandle {
+        let mut observers = self.observers.0.write().unwrap();
+        let handle = observers.next_handle();
+        observers.on_deactivate.push((handle, callback));
+        handle
+    }
+
+    /// Remove a subscription, returning whether it was found
+    pub fn unsubscribe(&mut self, handle: SubscriptionHandle) -> bool {
+        let mut observers = self.observers.0.write().unwrap();
+        let num_on_activate = observers.on_activate.len();
+        let num_on_deactivate = observers.on_deactivate.len();
+        observers.on_activate.retain(|(h, _)| *h != handle);
+        observers.on_deactivate.retain(|(h, _)| *h != handle);
+        observers.on_activate.len() != num_on_activate
+            || observers.on_deactivate.len() != num_on_deactivate
+    }
+
+    /// Take over the subscriptions of `previous`, notifying them of the
+    /// features active here but not there. For feature sets rebuilt rather
+    /// than cloned, such as by a bank at an epoch boundary.
+    pub fn inherit_subscriptions(&mut self, previous: &FeatureSet) {
+        self.observers = previous.observers.clone();
+        let mut activated = self
+            .active
+            .iter()
+            .filter(|(feature_id, _)| !previous.active.contains_key(feature_id))
+            .collect::<Vec<_>>();
+        activated.sort_unstable();
+        for (feature_id, slot) in activated {
+            self.observers.notify_activated(feature_id, *slot);
+        }
+    }
+
+    /// Stage an inactive feature for activation at the next epoch boundary.
+    /// Staging an active or already staged feature has no effect.
+    pub fn stage(&mut self, feature_id: &Pubkey, slot: u64) {
+        if !self.is_active(feature_id) {
+            self.pending.entry(*feature_id).or_insert(slot);
+        }
+    }
+
+    /// Activate all staged features at `activation_slot`, returning their ids
+    pub fn promote_pending(&mut self, activation_slot: u64) -> Vec<Pubkey> {
+        let mut promoted = self
+            .pending
+            .drain()
+            .map(|(feature_id, _)| feature_id)
+            .collect::<Vec<_>>();
+        promoted.sort_unstable();
+        for feature_id in &promoted {
+            self.activate(feature_id, activation_slot);
+        }
+        promoted
+    }
+
+    pub fn is_pending(&self, feature_id: &Pubkey) -> bool {
+        self.pending.contains_key(feature_id)
+    }
+
+    pub fn pending(&self) -> &AHashMap<Pubkey, u64> {
+        &self.pending
     }
 
     /// List of enabled features that trigger full inflation
     pub fn full_inflation_features_enabled(&self) -> AHashSet<Pubkey> {
-        let mut hash_set = FULL_INFLATION_FEATURE_PAIRS
-            .iter()
-            .filter_map(|pair| {
-                if self.is_active(&pair.vote_id) && self.is_active(&pair.enable_id) {
-                    Some(pair.enable_id)
-                } else {
-                    None
-                }
-            })
+        let mut hash_set = self
+            .full_inflation_candidates()
+            .into_iter()
+            .filter(FullInflationCandidateStatus::is_enabled)
+            .map(|candidate| candidate.enable_id)
             .collect::<AHashSet<_>>();
 
         if self.is_active(&full_inflation::devnet_and_testnet::id()) {
@@ -87,12 +676,121 @@ impl FeatureSet {
         hash_set
     }
 
+    /// Status of each full inflation candidate, sorted by candidate name
+    pub fn full_inflation_candidates(&self) -> Vec<FullInflationCandidateStatus> {
+        self.full_inflation_candidates_from(FULL_INFLATION_FEATURE_PAIRS.iter())
+    }
+
+    fn full_inflation_candidates_from<'a>(
+        &self,
+        pairs: impl IntoIterator<Item = &'a FullInflationFeaturePair>,
+    ) -> Vec<FullInflationCandidateStatus> {
+        let mut candidates = pairs
+            .into_iter()
+            .map(|pair| {
+                let vote_slot = self.activated_slot(&pair.vote_id);
+                let enable_slot = self.activated_slot(&pair.enable_id);
+                FullInflationCandidateStatus {
+                    name: pair.name,
+                    vote_id: pair.vote_id,
+                    enable_id: pair.enable_id,
+                    vote_active: vote_slot.is_some(),
+                    enable_active: enable_slot.is_some(),
+                    activation_slot: vote_slot.zip(enable_slot).map(|(a, b)| a.max(b)),
+                }
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_unstable_by_key(|candidate| candidate.name);
+        candidates
+    }
+
     /// All features enabled, useful for testing
     pub fn all_enabled() -> Self {
         Self {
             active: AHashMap::from_iter((*FEATURE_NAMES).keys().cloned().map(|key| (key, 0))),
             inactive: AHashSet::new(),
+            unknown: AHashSet::new(),
+            retired: AHashSet::new(),
+            pending: AHashMap::new(),
+            observers: FeatureSetObservers::default(),
+        }
+    }
+
+    /// All features enabled except `disabled`, useful for local test validators
+    pub fn all_enabled_except(disabled: &[Pubkey]) -> Result<Self, FeatureOverrideError> {
+        Self::from_overrides(&Self::all_enabled(), &[], disabled)
+    }
+
+    /// Copy of `base` with the `enable` features activated at slot 0 and the
+    /// `disable` features deactivated
+    pub fn from_overrides(
+        base: &FeatureSet,
+        enable: &[Pubkey],
+        disable: &[Pubkey],
+    ) -> Result<Self, FeatureOverrideError> {
+        if let Some(feature_id) = enable
+            .iter()
+            .chain(disable)
+            .find(|feature_id| !FEATURE_NAMES.contains_key(feature_id))
+        {
+            return Err(FeatureOverrideError::UnknownFeature(*feature_id));
+        }
+        if let Some(feature_id) = enable
+            .iter()
+            .find(|feature_id| disable.contains(feature_id))
+        {
+            return Err(FeatureOverrideError::ConflictingOverride(*feature_id));
+        }
+
+        let mut feature_set = base.clone();
+        for feature_id in enable {
+            if !feature_set.is_active(feature_id) {
+                feature_set.activate(feature_id, 0);
+            }
+        }
+        for feature_id in disable {
+            feature_set.deactivate(feature_id);
+        }
+        Ok(feature_set)
+    }
+
+    /// Reconstruct a feature set from feature accounts, e.g. read from a
+    /// snapshot, given as (feature id, owner if known, account data).
+    /// Accounts with a known owner other than the feature program are
+    /// skipped. Features activated in their account are active, and the ones
+    /// merely created are staged, with slot 0 since the account does not
+    /// record when. Accounts whose data cannot be parsed are skipped and
+    /// reported alongside the feature set.
+    pub fn from_feature_accounts<'a>(
+        accounts: impl IntoIterator<Item = (Pubkey, Option<Pubkey>, &'a [u8])>,
+    ) -> (Self, Vec<FeatureParseError>) {
+        let mut feature_set = Self::default();
+        let mut errors = Vec::new();
+        for (feature_id, owner, data) in accounts {
+            if owner.is_some_and(|owner| owner != FEATURE_PROGRAM_ID) {
+                continue;
+            }
+            let activated_at = match parse_feature_account(&feature_id, data) {
+                Ok(activated_at) => activated_at,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+            if !FEATURE_NAMES.contains_key(&feature_id) {
+                if RETIRED_FEATURES.contains_key(&feature_id) {
+                    feature_set.retired.insert(feature_id);
+                } else {
+                    feature_set.unknown.insert(feature_id);
+                }
+                continue;
+            }
+            match activated_at {
+                Some(slot) => feature_set.activate(&feature_id, slot),
+                None => feature_set.stage(&feature_id, 0),
+            }
         }
+        (feature_set, errors)
     }
 
     pub fn new_warmup_cooldown_rate_epoch(&self, epoch_schedule: &EpochSchedule) -> Option<u64> {
@@ -161,1286 +859,2369 @@ impl FeatureSet {
     }
 }
 
-pub mod deprecate_rewards_sysvar {
-    solana_pubkey::declare_id!("GaBtBJvmS4Arjj5W1NmFcyvPjsHN38UGYDq2MDwbs9Qu");
-}
+/// Builds feature sets for tests, starting from a preset and applying
+/// overrides on top of it
+///
+/// ```
+/// use agave_feature_set::{pico_inflation, FeatureSetBuilder};
+///
+/// let feature_set = FeatureSetBuilder::all_enabled()
+///     .disable(pico_inflation::id())
+///     .build()
+///     .unwrap();
+/// assert!(!feature_set.is_active(&pico_inflation::id()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FeatureSetBuilder {
+    base: FeatureSet,
+    enable: Vec<(Pubkey, u64)>,
+    disable: Vec<Pubkey>,
+}
+
+impl FeatureSetBuilder {
+    fn new(base: FeatureSet) -> Self {
+        Self {
+            base,
+            enable: Vec::new(),
+            disable: Vec::new(),
+        }
+    }
 
-pub mod pico_inflation {
-    solana_pubkey::declare_id!("4RWNif6C2WCNiKVW7otP4G7dkmkHGyKQWRpuZ1pxKU5m");
-}
+    /// Starts from all features active at slot 0
+    pub fn all_enabled() -> Self {
+        Self::new(FeatureSet::all_enabled())
+    }
 
-pub mod full_inflation {
-    pub mod devnet_and_testnet {
-        solana_pubkey::declare_id!("DT4n6ABDqs6w4bnfwrXT9rsprcPf6cdDga1egctaPkLC");
+    /// Starts from all features inactive
+    pub fn none() -> Self {
+        Self::new(FeatureSet::default())
     }
 
-    pub mod mainnet {
-        pub mod certusone {
-            pub mod vote {
-                solana_pubkey::declare_id!("BzBBveUDymEYoYzcMWNQCx3cd4jQs7puaVFHLtsbB6fm");
-            }
-            pub mod enable {
-                solana_pubkey::declare_id!("7XRJcS5Ud5vxGB54JbK9N2vBZVwnwdBNeJW1ibRgD9gx");
-            }
+    /// Starts from the features in `MAINNET_LIKE_FEATURES` active at slot 0.
+    /// This is an approximation maintained for tests, not the actual state
+    /// of mainnet-beta.
+    #[cfg(feature = "dev-context-only-utils")]
+    pub fn mainnet_like() -> Self {
+        let mut feature_set = FeatureSet::default();
+        for feature in MAINNET_LIKE_FEATURES {
+            feature_set.activate(&feature.id(), 0);
         }
+        Self::new(feature_set)
     }
-}
 
-pub mod secp256k1_program_enabled {
-    solana_pubkey::declare_id!("E3PHP7w8kB7np3CTQ1qQ2tW3KCtjRSXBQgW9vM2mWv2Y");
-}
+    /// Activates `feature_id` at slot 0
+    pub fn enable(self, feature_id: Pubkey) -> Self {
+        self.enable_at(feature_id, 0)
+    }
 
-pub mod spl_token_v2_multisig_fix {
-    solana_pubkey::declare_id!("E5JiFDQCwyC6QfT9REFyMpfK2mHcmv1GUDySU1Ue7TYv");
-}
+    /// Activates `feature_id` at `slot`
+    pub fn enable_at(mut self, feature_id: Pubkey, slot: u64) -> Self {
+        self.enable.push((feature_id, slot));
+        self
+    }
 
-pub mod no_overflow_rent_distribution {
-    solana_pubkey::declare_id!("4kpdyrcj5jS47CZb2oJGfVxjYbsMm2Kx97gFyZrxxwXz");
-}
+    /// Deactivates `feature_id`
+    pub fn disable(mut self, feature_id: Pubkey) -> Self {
+        self.disable.push(feature_id);
+        self
+    }
 
-pub mod filter_stake_delegation_accounts {
-    solana_pubkey::declare_id!("GE7fRxmW46K6EmCD9AMZSbnaJ2e3LfqCZzdHi9hmYAgi");
+    /// Applies the overrides to the preset, failing if any of them is not in
+    /// `FEATURE_NAMES` or if a feature is both enabled and disabled
+    pub fn build(self) -> Result<FeatureSet, FeatureOverrideError> {
+        let Self {
+            base: mut feature_set,
+            enable,
+            disable,
+        } = self;
+        if let Some(feature_id) = enable
+            .iter()
+            .map(|(feature_id, _)| feature_id)
+            .chain(&disable)
+            .find(|feature_id| !FEATURE_NAMES.contains_key(feature_id))
+        {
+            return Err(FeatureOverrideError::UnknownFeature(*feature_id));
+        }
+        if let Some((feature_id, _)) = enable
+            .iter()
+            .find(|(feature_id, _)| disable.contains(feature_id))
+        {
+            return Err(FeatureOverrideError::ConflictingOverride(*feature_id));
+        }
+        for (feature_id, slot) in enable {
+            feature_set.activate(&feature_id, slot);
+        }
+        for feature_id in disable {
+            feature_set.deactivate(&feature_id);
+        }
+        Ok(feature_set)
+    }
 }
 
-pub mod require_custodian_for_locked_stake_authorize {
-    solana_pubkey::declare_id!("D4jsDcXaqdW8tDAWn8H4R25Cdns2YwLneujSL1zvjW6R");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureStatus {
+    /// Active since the given slot
+    Active(u64),
+    Inactive,
+    /// No longer recognized by this software, for the given reason
+    Retired(&'static str),
+    Unknown,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum FeatureActivationError {
+    #[error("feature {feature_id} depends on inactive feature {dependency}")]
+    InactiveDependency {
+        feature_id: Pubkey,
+        dependency: Pubkey,
+    },
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum FeatureOverrideError {
+    #[error("unknown feature {0}")]
+    UnknownFeature(Pubkey),
+    #[error("feature {0} is both enabled and disabled")]
+    ConflictingOverride(Pubkey),
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum FeatureParseError {
+    #[error("feature account {feature_id} is too small: {len} bytes")]
+    AccountTooSmall { feature_id: Pubkey, len: usize },
+    #[error("feature account {feature_id} has invalid activation tag {tag}")]
+    InvalidActivationTag { feature_id: Pubkey, tag: u8 },
+}
+
+/// Id of the program owning feature accounts
+pub const FEATURE_PROGRAM_ID: Pubkey =
+    solana_pubkey::pubkey!("Feature111111111111111111111111111111111111");
+
+/// Parse the bincode serialized `Feature { activated_at: Option<u64> }` held
+/// by a feature account
+fn parse_feature_account(
+    feature_id: &Pubkey,
+    data: &[u8],
+) -> Result<Option<u64>, FeatureParseError> {
+    let too_small = || FeatureParseError::AccountTooSmall {
+        feature_id: *feature_id,
+        len: data.len(),
+    };
+    match data.first().ok_or_else(too_small)? {
+        0 => Ok(None),
+        1 => {
+            let slot = data.get(1..9).ok_or_else(too_small)?;
+            Ok(Some(u64::from_le_bytes(slot.try_into().unwrap())))
+        }
+        &tag => Err(FeatureParseError::InvalidActivationTag {
+            feature_id: *feature_id,
+            tag,
+        }),
+    }
 }
 
-pub mod spl_token_v2_self_transfer_fix {
-    solana_pubkey::declare_id!("BL99GYhdjjcv6ys22C9wPgn2aTVERDbPHHo4NbS3hgp7");
+/// An active feature whose declared dependency is not active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DependencyViolation {
+    pub feature_id: Pubkey,
+    pub missing_dependency: Pubkey,
 }
 
-pub mod warp_timestamp_again {
-    solana_pubkey::declare_id!("GvDsGDkH5gyzwpDhxNixx8vtx1kwYHH13RiNAPw27zXb");
+/// Differences between the active features of two `FeatureSet`s, as returned
+/// by `FeatureSet::diff()`. Entries are sorted by feature id.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FeatureSetDiff {
+    /// Features active in `self` but not in `other`, with their activation slot
+    pub active_only_in_self: Vec<(Pubkey, u64)>,
+    /// Features active in `other` but not in `self`, with their activation slot
+    pub active_only_in_other: Vec<(Pubkey, u64)>,
+    /// Features active in both, with the activation slots in `self` and `other`
+    pub slot_mismatches: Vec<(Pubkey, u64, u64)>,
 }
 
-pub mod check_init_vote_data {
-    solana_pubkey::declare_id!("3ccR6QpxGYsAbWyfevEtBNGfWV4xBffxRj2tD6A9i39F");
+impl FeatureSetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.active_only_in_self.is_empty()
+            && self.active_only_in_other.is_empty()
+            && self.slot_mismatches.is_empty()
+    }
 }
 
-pub mod secp256k1_recover_syscall_enabled {
-    solana_pubkey::declare_id!("6RvdSWHh8oh72Dp7wMTS2DBkf3fRPtChfNrAo3cZZoXJ");
+/// Outcome of `FeatureSet::merge_cluster_state()`. Entries are sorted by
+/// feature id.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Features activated or moved to an earlier activation slot, with the
+    /// activation slot reported by the cluster
+    pub activated: Vec<(Pubkey, u64)>,
+    /// Features active both here and on the cluster at different slots, with
+    /// the local and the cluster activation slots before the merge
+    pub slot_mismatches: Vec<(Pubkey, u64, u64)>,
+    /// Features active on the cluster but unknown to this software, with
+    /// their activation slot
+    pub unknown: Vec<(Pubkey, u64)>,
+}
+
+impl MergeReport {
+    /// Whether the cluster has activated features this software doesn't know,
+    /// which usually means it is about to diverge from the cluster
+    pub fn has_unknown(&self) -> bool {
+        !self.unknown.is_empty()
+    }
 }
 
-pub mod system_transfer_zero_check {
-    solana_pubkey::declare_id!("BrTR9hzw4WBGFP65AJMbpAo64DcA3U6jdPSga9fMV5cS");
+/// Records the activations of a `FeatureSet` since the last call to
+/// `take_changes()`, for subsystems that cache feature-derived configuration
+#[derive(Debug, Clone)]
+pub struct FeatureSetWatcher {
+    handle: SubscriptionHandle,
+    changes: Arc<Mutex<Vec<(Pubkey, u64)>>>,
 }
 
-pub mod blake3_syscall_enabled {
-    solana_pubkey::declare_id!("HTW2pSyErTj4BV6KBM9NZ9VBUJVxt7sacNWcf76wtzb3");
-}
+impl FeatureSetWatcher {
+    /// Start watching `feature_set` for activations
+    pub fn new(feature_set: &mut FeatureSet) -> Self {
+        let changes = Arc::<Mutex<Vec<(Pubkey, u64)>>>::default();
+        let handle = feature_set.subscribe({
+            let changes = changes.clone();
+            Box::new(move |feature_id, slot| {
+                changes.lock().unwrap().push((*feature_id, slot));
+            })
+        });
+        Self { handle, changes }
+    }
 
-pub mod dedupe_config_program_signers {
-    solana_pubkey::declare_id!("8kEuAshXLsgkUEdcFVLqrjCGGHVWFW99ZZpxvAzzMtBp");
-}
+    /// Handle of the underlying subscription, to stop watching with
+    /// `FeatureSet::unsubscribe()`
+    pub fn handle(&self) -> SubscriptionHandle {
+        self.handle
+    }
 
-pub mod verify_tx_signatures_len {
-    solana_pubkey::declare_id!("EVW9B5xD9FFK7vw1SBARwMA4s5eRo5eKJdKpsBikzKBz");
+    /// Activations recorded since the last call, in the order they happened
+    pub fn take_changes(&self) -> Vec<(Pubkey, u64)> {
+        std::mem::take(&mut *self.changes.lock().unwrap())
+    }
 }
 
-pub mod vote_stake_checked_instructions {
-    solana_pubkey::declare_id!("BcWknVcgvonN8sL4HE4XFuEVgfcee5MwxWPAgP6ZV89X");
-}
+/// Formats a feature id with its description from `FEATURE_NAMES`, if known
+struct FeatureDisplay<'a>(&'a Pubkey);
 
-pub mod rent_for_sysvars {
-    solana_pubkey::declare_id!("BKCPBQQBZqggVnFso5nQ8rQ4RwwogYwjuUt9biBjxwNF");
+impl fmt::Display for FeatureDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match FEATURE_NAMES.get(self.0) {
+            Some(description) => write!(f, "{} ({description})", self.0),
+            None => write!(f, "{}", self.0),
+        }
+    }
 }
 
-pub mod libsecp256k1_0_5_upgrade_enabled {
-    solana_pubkey::declare_id!("DhsYfRjxfnh2g7HKJYSzT79r74Afa1wbHkAgHndrA1oy");
+impl fmt::Display for FeatureSetDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "feature sets are identical");
+        }
+        let mut lines = vec![];
+        if !self.active_only_in_self.is_empty() {
+            lines.push("active only in self:".to_string());
+            lines.extend(self.active_only_in_self.iter().map(|(feature_id, slot)| {
+                format!("  {} at slot {slot}", FeatureDisplay(feature_id))
+            }));
+        }
+        if !self.active_only_in_other.is_empty() {
+            lines.push("active only in other:".to_string());
+            lines.extend(self.active_only_in_other.iter().map(|(feature_id, slot)| {
+                format!("  {} at slot {slot}", FeatureDisplay(feature_id))
+            }));
+        }
+        if !self.slot_mismatches.is_empty() {
+            lines.push("activation slot mismatches:".to_string());
+            lines.extend(
+                self.slot_mismatches
+                    .iter()
+                    .map(|(feature_id, slot, other_slot)| {
+                        format!(
+                            "  {} at slot {slot} in self, {other_slot} in other",
+                            FeatureDisplay(feature_id)
+                        )
+                    }),
+            );
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
 }
 
-pub mod tx_wide_compute_cap {
-    solana_pubkey::declare_id!("5ekBxc8itEnPv4NzGJtr8BVVQLNMQuLMNQQj7pHoLNZ9");
-}
+/// Declares a `pub mod` with an `id()` for each feature along with the
+/// `Features` enum naming them, so that the two are generated from a single
+/// list. Entries after the `;` name features whose modules are declared by
+/// hand, e.g. because they are nested.
+macro_rules! declare_features {
+    (
+        $(
+            $(#[$meta:meta])*
+            $module:ident: $variant:ident = $id:literal,
+        )*
+        ;
+        $($nested_variant:ident = $($nested_module:ident)::+,)*
+    ) => {
+        $(
+            $(#[$meta])*
+            pub mod $module {
+                solana_pubkey::declare_id!($id);
+            }
+        )*
 
-pub mod spl_token_v2_set_authority_fix {
-    solana_pubkey::declare_id!("FToKNBYyiF4ky9s8WsmLBXHCht17Ek7RXaLZGHzzQhJ1");
-}
+        /// Typed handle for each feature known to this software
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Features {
+            $($variant,)*
+            $($nested_variant,)*
+        }
 
-pub mod merge_nonce_error_into_system_error {
-    solana_pubkey::declare_id!("21AWDosvp3pBamFW91KB35pNoaoZVTM7ess8nr2nt53B");
-}
+        impl Features {
+            /// All features, in declaration order
+            pub const ALL: &'static [Features] = &[
+                $(Features::$variant,)*
+                $(Features::$nested_variant,)*
+            ];
+
+            pub fn id(&self) -> Pubkey {
+                match self {
+                    $(Features::$variant => $module::id(),)*
+                    $(Features::$nested_variant => $($nested_module)::+::id(),)*
+                }
+            }
 
-pub mod disable_fees_sysvar {
-    solana_pubkey::declare_id!("JAN1trEUEtZjgXYzNBYHU9DYd7GnThhXfFP7SzPXkPsG");
+            pub fn description(&self) -> &'static str {
+                FEATURE_NAMES
+                    .get(&self.id())
+                    .copied()
+                    .unwrap_or_else(|| panic!("feature {:?} is missing a description", self))
+            }
+        }
+    };
 }
 
-pub mod stake_merge_with_unmatched_credits_observed {
-    solana_pubkey::declare_id!("meRgp4ArRPhD3KtCY9c5yAf2med7mBLsjKTPeVUHqBL");
-}
+pub mod full_inflation {
+    pub mod devnet_and_testnet {
+        solana_pubkey::declare_id!("DT4n6ABDqs6w4bnfwrXT9rsprcPf6cdDga1egctaPkLC");
+    }
 
-pub mod zk_token_sdk_enabled {
-    solana_pubkey::declare_id!("zk1snxsc6Fh3wsGNbbHAJNHiJoYgF29mMnTSusGx5EJ");
+    pub mod mainnet {
+        pub mod certusone {
+            pub mod vote {
+                solana_pubkey::declare_id!("BzBBveUDymEYoYzcMWNQCx3cd4jQs7puaVFHLtsbB6fm");
+            }
+            pub mod enable {
+                solana_pubkey::declare_id!("7XRJcS5Ud5vxGB54JbK9N2vBZVwnwdBNeJW1ibRgD9gx");
+            }
+        }
+    }
 }
 
-pub mod curve25519_syscall_enabled {
-    solana_pubkey::declare_id!("7rcw5UtqgDTBBv2EcynNfYckgdAaH1MAsCjKgXMkN7Ri");
-}
+declare_features! {
+    deprecate_rewards_sysvar: DeprecateRewardsSysvar = "GaBtBJvmS4Arjj5W1NmFcyvPjsHN38UGYDq2MDwbs9Qu",
+    pico_inflation: PicoInflation = "4RWNif6C2WCNiKVW7otP4G7dkmkHGyKQWRpuZ1pxKU5m",
+    secp256k1_program_enabled: Secp256k1ProgramEnabled = "E3PHP7w8kB7np3CTQ1qQ2tW3KCtjRSXBQgW9vM2mWv2Y",
+    spl_token_v2_multisig_fix: SplTokenV2MultisigFix = "E5JiFDQCwyC6QfT9REFyMpfK2mHcmv1GUDySU1Ue7TYv",
+    no_overflow_rent_distribution: NoOverflowRentDistribution = "4kpdyrcj5jS47CZb2oJGfVxjYbsMm2Kx97gFyZrxxwXz",
+    filter_stake_delegation_accounts: FilterStakeDelegationAccounts = "GE7fRxmW46K6EmCD9AMZSbnaJ2e3LfqCZzdHi9hmYAgi",
+    require_custodian_for_locked_stake_authorize: RequireCustodianForLockedStakeAuthorize = "D4jsDcXaqdW8tDAWn8H4R25Cdns2YwLneujSL1zvjW6R",
+    spl_token_v2_self_transfer_fix: SplTokenV2SelfTransferFix = "BL99GYhdjjcv6ys22C9wPgn2aTVERDbPHHo4NbS3hgp7",
+    warp_timestamp_again: WarpTimestampAgain = "GvDsGDkH5gyzwpDhxNixx8vtx1kwYHH13RiNAPw27zXb",
+    check_init_vote_data: CheckInitVoteData = "3ccR6QpxGYsAbWyfevEtBNGfWV4xBffxRj2tD6A9i39F",
+    secp256k1_recover_syscall_enabled: Secp256k1RecoverSyscallEnabled = "6RvdSWHh8oh72Dp7wMTS2DBkf3fRPtChfNrAo3cZZoXJ",
+    system_transfer_zero_check: SystemTransferZeroCheck = "BrTR9hzw4WBGFP65AJMbpAo64DcA3U6jdPSga9fMV5cS",
+    blake3_syscall_enabled: Blake3SyscallEnabled = "HTW2pSyErTj4BV6KBM9NZ9VBUJVxt7sacNWcf76wtzb3",
+    dedupe_config_program_signers: DedupeConfigProgramSigners = "8kEuAshXLsgkUEdcFVLqrjCGGHVWFW99ZZpxvAzzMtBp",
+    verify_tx_signatures_len: VerifyTxSignaturesLen = "EVW9B5xD9FFK7vw1SBARwMA4s5eRo5eKJdKpsBikzKBz",
+    vote_stake_checked_instructions: VoteStakeCheckedInstructions = "BcWknVcgvonN8sL4HE4XFuEVgfcee5MwxWPAgP6ZV89X",
+    rent_for_sysvars: RentForSysvars = "BKCPBQQBZqggVnFso5nQ8rQ4RwwogYwjuUt9biBjxwNF",
+    libsecp256k1_0_5_upgrade_enabled: Libsecp256k105UpgradeEnabled = "DhsYfRjxfnh2g7HKJYSzT79r74Afa1wbHkAgHndrA1oy",
+    tx_wide_compute_cap: TxWideComputeCap = "5ekBxc8itEnPv4NzGJtr8BVVQLNMQuLMNQQj7pHoLNZ9",
+    spl_token_v2_set_authority_fix: SplTokenV2SetAuthorityFix = "FToKNBYyiF4ky9s8WsmLBXHCht17Ek7RXaLZGHzzQhJ1",
+    merge_nonce_error_into_system_error: MergeNonceErrorIntoSystemError = "21AWDosvp3pBamFW91KB35pNoaoZVTM7ess8nr2nt53B",
+    disable_fees_sysvar: DisableFeesSysvar = "JAN1trEUEtZjgXYzNBYHU9DYd7GnThhXfFP7SzPXkPsG",
+    stake_merge_with_unmatched_credits_observed: StakeMergeWithUnmatchedCreditsObserved = "meRgp4ArRPhD3KtCY9c5yAf2med7mBLsjKTPeVUHqBL",
+    zk_token_sdk_enabled: ZkTokenSdkEnabled = "zk1snxsc6Fh3wsGNbbHAJNHiJoYgF29mMnTSusGx5EJ",
+    curve25519_syscall_enabled: Curve25519SyscallEnabled = "7rcw5UtqgDTBBv2EcynNfYckgdAaH1MAsCjKgXMkN7Ri",
+    curve25519_restrict_msm_length: Curve25519RestrictMsmLength = "eca6zf6JJRjQsYYPkBHF3N32MTzur4n2WL4QiiacPCL",
+    versioned_tx_message_enabled: VersionedTxMessageEnabled = "3KZZ6Ks1885aGBQ45fwRcPXVBCtzUvxhUTkwKMR41Tca",
+    libsecp256k1_fail_on_bad_count: Libsecp256k1FailOnBadCount = "8aXvSuopd1PUj7UhehfXJRg6619RHp8ZvwTyyJHdUYsj",
+    libsecp256k1_fail_on_bad_count2: Libsecp256k1FailOnBadCount2 = "54KAoNiUERNoWWUhTWWwXgym94gzoXFVnHyQwPA18V9A",
+    instructions_sysvar_owned_by_sysvar: InstructionsSysvarOwnedBySysvar = "H3kBSaKdeiUsyHmeHqjJYNc27jesXZ6zWj3zWkowQbkV",
+    stake_program_advance_activating_credits_observed: StakeProgramAdvanceActivatingCreditsObserved = "SAdVFw3RZvzbo6DvySbSdBnHN4gkzSTH9dSxesyKKPj",
+    credits_auto_rewind: CreditsAutoRewind = "BUS12ciZ5gCoFafUHWW8qaFMMtwFQGVxjsDheWLdqBE2",
+    demote_program_write_locks: DemoteProgramWriteLocks = "3E3jV7v9VcdJL8iYZUMax9DiDno8j7EWUVbhm9RtShj2",
+    ed25519_program_enabled: Ed25519ProgramEnabled = "6ppMXNYLhVd7GcsZ5uV11wQEW7spppiMVfqQv5SXhDpX",
+    return_data_syscall_enabled: ReturnDataSyscallEnabled = "DwScAzPUjuv65TMbDnFY7AgwmotzWy3xpEJMXM3hZFaB",
+    reduce_required_deploy_balance: ReduceRequiredDeployBalance = "EBeznQDjcPG8491sFsKZYBi5S5jTVXMpAKNDJMQPS2kq",
+    sol_log_data_syscall_enabled: SolLogDataSyscallEnabled = "6uaHcKPGUy4J7emLBgUTeufhJdiwhngW6a1R9B7c2ob9",
+    stakes_remove_delegation_if_inactive: StakesRemoveDelegationIfInactive = "HFpdDDNQjvcXnXKec697HDDsyk6tFoWS2o8fkxuhQZpL",
+    do_support_realloc: DoSupportRealloc = "75m6ysz33AfLA5DDEzWM1obBrnPQRSsdVQ2nRmc8Vuu1",
+    prevent_calling_precompiles_as_programs: PreventCallingPrecompilesAsPrograms = "4ApgRX3ud6p7LNMJmsuaAcZY5HWctGPr5obAsjB3A54d",
+    optimize_epoch_boundary_updates: OptimizeEpochBoundaryUpdates = "265hPS8k8xJ37ot82KEgjRunsUp5w4n4Q4VwwiN9i9ps",
+    remove_native_loader: RemoveNativeLoader = "HTTgmruMYRZEntyL3EdCDdnS6e4D5wRq1FA7kQsb66qq",
+    send_to_tpu_vote_port: SendToTpuVotePort = "C5fh68nJ7uyKAuYZg2x9sEQ5YrVf3dkW6oojNBSc3Jvo",
+    requestable_heap_size: RequestableHeapSize = "CCu4boMmfLuqcmfTLPHQiUo22ZdUsXjgzPAURYaWt1Bw",
+    disable_fee_calculator: DisableFeeCalculator = "2jXx2yDmGysmBKfKYNgLj2DQyAQv6mMk2BPh4eSbyB4H",
+    add_compute_budget_program: AddComputeBudgetProgram = "4d5AKtxoh93Dwm1vHXUU3iRATuMndx1c431KgT2td52r",
+    nonce_must_be_writable: NonceMustBeWritable = "BiCU7M5w8ZCMykVSyhZ7Q3m2SWoR2qrEQ86ERcDX77ME",
+    spl_token_v3_3_0_release: SplTokenV330Release = "Ftok2jhqAqxUWEiCVRrfRs9DPppWP8cgTB7NQNKL88mS",
+    leave_nonce_on_success: LeaveNonceOnSuccess = "E8MkiWZNNPGU6n55jkGzyj8ghUmjCHRmDFdYYFYHxWhQ",
+    reject_empty_instruction_without_program: RejectEmptyInstructionWithoutProgram = "9kdtFSrXHQg3hKkbXkQ6trJ3Ja1xpJ22CTFSNAciEwmL",
+    fixed_memcpy_nonoverlapping_check: FixedMemcpyNonoverlappingCheck = "36PRUK2Dz6HWYdG9SpjeAsF5F3KxnFCakA2BZMbtMhSb",
+    reject_non_rent_exempt_vote_withdraws: RejectNonRentExemptVoteWithdraws = "7txXZZD6Um59YoLMF7XUNimbMjsqsWhc7g2EniiTrmp1",
+    evict_invalid_stakes_cache_entries: EvictInvalidStakesCacheEntries = "EMX9Q7TVFAmQ9V1CggAkhMzhXSg8ECp7fHrWQX2G1chf",
+    allow_votes_to_directly_update_vote_state: AllowVotesToDirectlyUpdateVoteState = "Ff8b1fBeB86q8cjq47ZhsQLgv5EkHu3G1C99zjUfAzrq",
+    max_tx_account_locks: MaxTxAccountLocks = "CBkDroRDqm8HwHe6ak9cguPjUomrASEkfmxEaZ5CNNxz",
+    require_rent_exempt_accounts: RequireRentExemptAccounts = "BkFDxiJQWZXGTZaJQxH7wVEHkAmwCgSEVkrvswFfRJPD",
+    filter_votes_outside_slot_hashes: FilterVotesOutsideSlotHashes = "3gtZPqvPpsbXZVCx6hceMfWxtsmrjMzmg8C7PLKSxS2d",
+    update_syscall_base_costs: UpdateSyscallBaseCosts = "2h63t332mGCCsWK2nqqqHhN4U9ayyqhLVFvczznHDoTZ",
+    stake_deactivate_delinquent_instruction: StakeDeactivateDelinquentInstruction = "437r62HoAdUb63amq3D7ENnBLDhHT2xY8eFkLJYVKK4x",
+    vote_withdraw_authority_may_change_authorized_voter: VoteWithdrawAuthorityMayChangeAuthorizedVoter = "AVZS3ZsN4gi6Rkx2QUibYuSJG3S6QHib7xCYhG6vGJxU",
+    spl_associated_token_account_v1_0_4: SplAssociatedTokenAccountV104 = "FaTa4SpiaSNH44PGC4z8bnGVTkSRYaWvrBs3KTu8XQQq",
+    reject_vote_account_close_unless_zero_credit_epoch: RejectVoteAccountCloseUnlessZeroCreditEpoch = "ALBk3EWdeAg2WAGf6GPDUf1nynyNqCdEVmgouG7rpuCj",
+    add_get_processed_sibling_instruction_syscall: AddGetProcessedSiblingInstructionSyscall = "CFK1hRCNy8JJuAAY8Pb2GjLFNdCThS2qwZNe3izzBMgn",
+    bank_transaction_count_fix: BankTransactionCountFix = "Vo5siZ442SaZBKPXNocthiXysNviW4UYPwRFggmbgAp",
+    disable_bpf_deprecated_load_instructions: DisableBpfDeprecatedLoadInstructions = "3XgNukcZWf9o3HdA3fpJbm94XFc4qpvTXc8h1wxYwiPi",
+    disable_bpf_unresolved_symbols_at_runtime: DisableBpfUnresolvedSymbolsAtRuntime = "4yuaYAj2jGMGTh1sSmi4G2eFscsDq8qjugJXZoBN6YEa",
+    record_instruction_in_transaction_context_push: RecordInstructionInTransactionContextPush = "3aJdcZqxoLpSBxgeYGjPwaYS1zzcByxUDqJkbzWAH1Zb",
+    syscall_saturated_math: SyscallSaturatedMath = "HyrbKftCdJ5CrUfEti6x26Cj7rZLNe32weugk7tLcWb8",
+    check_physical_overlapping: CheckPhysicalOverlapping = "nWBqjr3gpETbiaVj3CBJ3HFC5TMdnJDGt21hnvSTvVZ",
+    limit_secp256k1_recovery_id: LimitSecp256k1RecoveryId = "7g9EUwj4j7CS21Yx1wvgWLjSZeh5aPq8x9kpoPwXM8n8",
+    disable_deprecated_loader: DisableDeprecatedLoader = "GTUMCZ8LTNxVfxdrw7ZsDFTxXb7TutYkzJnFwinpE6dg",
+    check_slice_translation_size: CheckSliceTranslationSize = "GmC19j9qLn2RFk5NduX6QXaDhVpGncVVBzyM8e9WMz2F",
+    stake_split_uses_rent_sysvar: StakeSplitUsesRentSysvar = "FQnc7U4koHqWgRvFaBJjZnV8VPg6L6wWK33yJeDp4yvV",
+    add_get_minimum_delegation_instruction_to_stake_program: AddGetMinimumDelegationInstructionToStakeProgram = "St8k9dVXP97xT6faW24YmRSYConLbhsMJA4TJTBLmMT",
+    error_on_syscall_bpf_function_hash_collisions: ErrorOnSyscallBpfFunctionHashCollisions = "8199Q2gMD2kwgfopK5qqVWuDbegLgpuFUFHCcUJQDN8b",
+    reject_callx_r10: RejectCallxR10 = "3NKRSwpySNwD3TvP5pHnRmkAQRsdkXWRr1WaQh8p4PWX",
+    drop_redundant_turbine_path: DropRedundantTurbinePath = "4Di3y24QFLt5QEUPZtbnjyfQKfm6ZMTfa6Dw1psfoMKU",
+    executables_incur_cpi_data_cost: ExecutablesIncurCpiDataCost = "7GUcYgq4tVtaqNCKT3dho9r4665Qp5TxCZ27Qgjx3829",
+    fix_recent_blockhashes: FixRecentBlockhashes = "6iyggb5MTcsvdcugX7bEKbHV8c6jdLbpHwkncrgLMhfo",
+    update_rewards_from_cached_accounts: UpdateRewardsFromCachedAccounts = "28s7i3htzhahXQKqmS2ExzbEoUypg9krwvtK2M9UWXh9",
+    partitioned_epoch_rewards_superfeature: PartitionedEpochRewardsSuperfeature = "PERzQrt5gBD1XEe2c9XdFWqwgHY3mr7cYWbm5V772V8",
+    spl_token_v3_4_0: SplTokenV340 = "Ftok4njE8b7tDffYkC5bAbCaQv5sL6jispYrprzatUwN",
+    spl_associated_token_account_v1_1_0: SplAssociatedTokenAccountV110 = "FaTa17gVKoqbh38HcfiQonPsAaQViyDCCSg71AubYZw8",
+    default_units_per_instruction: DefaultUnitsPerInstruction = "J2QdYx8crLbTVK8nur1jeLsmc3krDbfjoxoea2V1Uy5Q",
+    stake_allow_zero_undelegated_amount: StakeAllowZeroUndelegatedAmount = "sTKz343FM8mqtyGvYWvbLpTThw3ixRM4Xk8QvZ985mw",
+    require_static_program_ids_in_transaction: RequireStaticProgramIdsInTransaction = "8FdwgyHFEjhAdjWfV2vfqk7wA1g9X3fQpKH7SBpEv3kC",
+    /// This is a feature-proposal *feature id*.  The feature keypair address is `GQXzC7YiSNkje6FFUk6sc2p53XRvKoaZ9VMktYzUMnpL`.
+    stake_raise_minimum_delegation_to_1_sol: StakeRaiseMinimumDelegationTo1Sol = "9onWzzvCzNC2jfhxxeqRgs5q7nFAAKpCUvkj6T6GJK9i",
+    stake_minimum_delegation_for_rewards: StakeMinimumDelegationForRewards = "G6ANXD6ptCSyNd9znZm7j4dEczAJCfx7Cy43oBx3rKHJ",
+    add_set_compute_unit_price_ix: AddSetComputeUnitPriceIx = "98std1NSHqXi9WYvFShfVepRdCoq1qvsp8fsR2XZtG8g",
+    disable_deploy_of_alloc_free_syscall: DisableDeployOfAllocFreeSyscall = "79HWsX9rpnnJBPcdNURVqygpMAfxdrAirzAGAVmf92im",
+    include_account_index_in_rent_error: IncludeAccountIndexInRentError = "2R72wpcQ7qV7aTJWUumdn8u5wmmTyXbK7qzEy7YSAgyY",
+    add_shred_type_to_shred_seed: AddShredTypeToShredSeed = "Ds87KVeqhbv7Jw8W6avsS1mqz3Mw5J3pRTpPoDQ2QdiJ",
+    warp_timestamp_with_a_vengeance: WarpTimestampWithAVengeance = "3BX6SBeEBibHaVQXywdkcgyUk6evfYZkHdztXiDtEpFS",
+    separate_nonce_from_blockhash: SeparateNonceFromBlockhash = "Gea3ZkK2N4pHuVZVxWcnAtS6UEDdyumdYt4pFcKjA3ar",
+    enable_durable_nonce: EnableDurableNonce = "4EJQtF2pkRyawwcTVfQutzq4Sa5hRhibF6QAK1QXhtEX",
+    vote_state_update_credit_per_dequeue: VoteStateUpdateCreditPerDequeue = "CveezY6FDLVBToHDcvJRmtMouqzsmj4UXYh5ths5G5Uv",
+    quick_bail_on_panic: QuickBailOnPanic = "DpJREPyuMZ5nDfU6H3WTqSqUFSXAfw8u7xqmWtEwJDcP",
+    nonce_must_be_authorized: NonceMustBeAuthorized = "HxrEu1gXuH7iD3Puua1ohd5n4iUKJyFNtNxk9DVJkvgr",
+    nonce_must_be_advanceable: NonceMustBeAdvanceable = "3u3Er5Vc2jVcwz4xr2GJeSAXT3fAj6ADHZ4BJMZiScFd",
+    vote_authorize_with_seed: VoteAuthorizeWithSeed = "6tRxEYKuy2L5nnv5bgn7iT28MxUbYxp5h7F3Ncf1exrT",
+    preserve_rent_epoch_for_rent_exempt_accounts: PreserveRentEpochForRentExemptAccounts = "HH3MUYReL2BvqqA3oEcAa7txju5GY6G4nxJ51zvsEjEZ",
+    enable_bpf_loader_extend_program_ix: EnableBpfLoaderExtendProgramIx = "8Zs9W7D9MpSEtUWSQdGniZk2cNmV22y6FLJwCx53asme",
+    enable_early_verification_of_account_modifications: EnableEarlyVerificationOfAccountModifications = "7Vced912WrRnfjaiKRiNBcbuFw7RrnLv3E3z95Y4GTNc",
+    skip_rent_rewrites: SkipRentRewrites = "CGB2jM8pwZkeeiXQ66kBMyBR6Np61mggL7XUsmLjVcrw",
+    prevent_crediting_accounts_that_end_rent_paying: PreventCreditingAccountsThatEndRentPaying = "812kqX67odAp5NFwM8D2N24cku7WTm9CHUTFUXaDkWPn",
+    cap_bpf_program_instruction_accounts: CapBpfProgramInstructionAccounts = "9k5ijzTbYPtjzu8wj2ErH9v45xecHzQ1x4PMYMMxFgdM",
+    loosen_cpi_size_restriction: LoosenCpiSizeRestriction = "GDH5TVdbTPUpRnXaRyQqiKUa7uZAbZ28Q2N9bhbKoMLm",
+    use_default_units_in_fee_calculation: UseDefaultUnitsInFeeCalculation = "8sKQrMQoUHtQSUP83SPG4ta2JDjSAiWs7t5aJ9uEd6To",
+    compact_vote_state_updates: CompactVoteStateUpdates = "86HpNqzutEZwLcPxS6EHDcMNYWk6ikhteg9un7Y2PBKE",
+    incremental_snapshot_only_incremental_hash_calculation: IncrementalSnapshotOnlyIncrementalHashCalculation = "25vqsfjk7Nv1prsQJmA4Xu1bN61s8LXCBGUPp8Rfy1UF",
+    disable_cpi_setting_executable_and_rent_epoch: DisableCpiSettingExecutableAndRentEpoch = "B9cdB55u4jQsDNsdTK525yE9dmSc5Ga7YBaBrDFvEhM9",
+    on_load_preserve_rent_epoch_for_rent_exempt_accounts: OnLoadPreserveRentEpochForRentExemptAccounts = "CpkdQmspsaZZ8FVAouQTtTWZkc8eeQ7V3uj7dWz543rZ",
+    account_hash_ignore_slot: AccountHashIgnoreSlot = "SVn36yVApPLYsa8koK3qUcy14zXDnqkNYWyUh1f4oK1",
+    set_exempt_rent_epoch_max: SetExemptRentEpochMax = "5wAGiy15X1Jb2hkHnPDCM8oB9V42VNA9ftNVFK84dEgv",
+    relax_authority_signer_check_for_lookup_table_creation: RelaxAuthoritySignerCheckForLookupTableCreation = "FKAcEvNgSY79RpqsPNUV5gDyumopH4cEHqUxyfm8b8Ap",
+    stop_sibling_instruction_search_at_parent: StopSiblingInstructionSearchAtParent = "EYVpEP7uzH1CoXzbD6PubGhYmnxRXPeq3PPsm1ba3gpo",
+    vote_state_update_root_fix: VoteStateUpdateRootFix = "G74BkWBzmsByZ1kxHy44H3wjwp5hp7JbrGRuDpco22tY",
+    cap_accounts_data_allocations_per_transaction: CapAccountsDataAllocationsPerTransaction = "9gxu85LYRAcZL38We8MYJ4A9AwgBBPtVBAqebMcT1241",
+    epoch_accounts_hash: EpochAccountsHash = "5GpmAKxaGsWWbPp4bNXFLJxZVvG92ctxf7jQnzTQjF3n",
+    remove_deprecated_request_unit_ix: RemoveDeprecatedRequestUnitIx = "EfhYd3SafzGT472tYQDUc4dPd2xdEfKs5fwkowUgVt4W",
+    disable_rehash_for_rent_epoch: DisableRehashForRentEpoch = "DTVTkmw3JSofd8CJVJte8PXEbxNQ2yZijvVr3pe2APPj",
+    increase_tx_account_lock_limit: IncreaseTxAccountLockLimit = "9LZdXeKGeBV6hRLdxS1rHbHoEUsKqesCC2ZAPTPKJAbK",
+    limit_max_instruction_trace_length: LimitMaxInstructionTraceLength = "GQALDaC48fEhZGWRj9iL5Q889emJKcj3aCvHF7VCbbF4",
+    check_syscall_outputs_do_not_overlap: CheckSyscallOutputsDoNotOverlap = "3uRVPBpyEJRo1emLCrq38eLRFGcu6uKSpUXqGvU8T7SZ",
+    enable_bpf_loader_set_authority_checked_ix: EnableBpfLoaderSetAuthorityCheckedIx = "5x3825XS7M2A3Ekbn5VGGkvFoAg5qrRWkTrY4bARP1GL",
+    enable_alt_bn128_syscall: EnableAltBn128Syscall = "A16q37opZdQMCbe5qJ6xpBB9usykfv8jZaMkxvZQi4GJ",
+    simplify_alt_bn128_syscall_error_codes: SimplifyAltBn128SyscallErrorCodes = "JDn5q3GBeqzvUa7z67BbmVHVdE3EbUAjvFep3weR3jxX",
+    enable_alt_bn128_compression_syscall: EnableAltBn128CompressionSyscall = "EJJewYSddEEtSZHiqugnvhQHiWyZKjkFDQASd7oKSagn",
+    fix_alt_bn128_multiplication_input_length: FixAltBn128MultiplicationInputLength = "bn2puAyxUx6JUabAxYdKdJ5QHbNNmKw8dCGuGCyRrFN",
+    enable_program_redeployment_cooldown: EnableProgramRedeploymentCooldown = "J4HFT8usBxpcF63y46t1upYobJgChmKyZPm5uTBRg25Z",
+    commission_updates_only_allowed_in_first_half_of_epoch: CommissionUpdatesOnlyAllowedInFirstHalfOfEpoch = "noRuG2kzACwgaY7TVmLRnUNPLKNVQE1fb7X55YWBehp",
+    enable_turbine_fanout_experiments: EnableTurbineFanoutExperiments = "D31EFnLgdiysi84Woo3of4JMu7VmasUS3Z7j9HYXCeLY",
+    disable_turbine_fanout_experiments: DisableTurbineFanoutExperiments = "turbnbNRp22nwZCmgVVXFSshz7H7V23zMzQgA46YpmQ",
+    move_serialized_len_ptr_in_cpi: MoveSerializedLenPtrInCpi = "74CoWuBmt3rUVUrCb2JiSTvh6nXyBWUsK4SaMj3CtE3T",
+    update_hashes_per_tick: UpdateHashesPerTick = "3uFHb9oKdGfgZGJK9EHaAXN4USvnQtAFC13Fh5gGFS5B",
+    enable_big_mod_exp_syscall: EnableBigModExpSyscall = "EBq48m8irRKuE7ZnMTLvLg2UuGSqhe8s8oMqnmja1fJw",
+    disable_builtin_loader_ownership_chains: DisableBuiltinLoaderOwnershipChains = "4UDcAfQ6EcA6bdcadkeHpkarkhZGJ7Bpq7wTAiRMjkoi",
+    cap_transaction_accounts_data_size: CapTransactionAccountsDataSize = "DdLwVYuvDz26JohmgSbA7mjpJFgX5zP2dkp8qsF2C33V",
+    remove_congestion_multiplier_from_fee_calculation: RemoveCongestionMultiplierFromFeeCalculation = "A8xyMHZovGXFkorFqEmVH2PKGLiBip5JD7jt4zsUWo4H",
+    enable_request_heap_frame_ix: EnableRequestHeapFrameIx = "Hr1nUA9b7NJ6eChS26o7Vi8gYYDDwWD3YeBfzJkTbU86",
+    prevent_rent_paying_rent_recipients: PreventRentPayingRentRecipients = "Fab5oP3DmsLYCiQZXdjyqT3ukFFPrsmqhXU4WU1AWVVF",
+    delay_visibility_of_program_deployment: DelayVisibilityOfProgramDeployment = "GmuBvtFb2aHfSfMXpuFeWZGHyDeCLPS79s48fmCWCfM5",
+    apply_cost_tracker_during_replay: ApplyCostTrackerDuringReplay = "2ry7ygxiYURULZCrypHhveanvP5tzZ4toRwVp89oCNSj",
+    bpf_account_data_direct_mapping: BpfAccountDataDirectMapping = "1ncomp1ete111111111111111111111111111111111",
+    add_set_tx_loaded_accounts_data_size_instruction: AddSetTxLoadedAccountsDataSizeInstruction = "G6vbf1UBok8MWb8m25ex86aoQHeKTzDKzuZADHkShqm6",
+    switch_to_new_elf_parser: SwitchToNewElfParser = "Cdkc8PPTeTNUPoZEfCY5AyetUrEdkZtNPMgz58nqyaHD",
+    round_up_heap_size: RoundUpHeapSize = "CE2et8pqgyQMP2mQRg3CgvX8nJBKUArMu3wfiQiQKY1y",
+    remove_bpf_loader_incorrect_program_id: RemoveBpfLoaderIncorrectProgramId = "2HmTkCj9tXuPE4ueHzdD7jPeMf9JGCoZh5AsyoATiWEe",
+    include_loaded_accounts_data_size_in_fee_calculation: IncludeLoadedAccountsDataSizeInFeeCalculation = "EaQpmC6GtRssaZ3PCUM5YksGqUdMLeZ46BQXYtHYakDS",
+    native_programs_consume_cu: NativeProgramsConsumeCu = "8pgXCMNXC8qyEFypuwpXyRxLXZdpM4Qo72gJ6k87A6wL",
+    simplify_writable_program_account_check: SimplifyWritableProgramAccountCheck = "5ZCcFAzJ1zsFKe1KSZa9K92jhx7gkcKj97ci2DBo1vwj",
+    stop_truncating_strings_in_syscalls: StopTruncatingStringsInSyscalls = "16FMCmgLzCNNz6eTwGanbyN2ZxvTBSLuQ6DZhgeMshg",
+    clean_up_delegation_errors: CleanUpDelegationErrors = "Bj2jmUsM2iRhfdLLDSTkhM5UQRQvQHm57HSmPibPtEyu",
+    vote_state_add_vote_latency: VoteStateAddVoteLatency = "7axKe5BTYBDD87ftzWbk5DfzWMGyRvqmWTduuo22Yaqy",
+    checked_arithmetic_in_fee_validation: CheckedArithmeticInFeeValidation = "5Pecy6ie6XGm22pc9d4P9W5c31BugcFBuy6hsP2zkETv",
+    last_restart_slot_sysvar: LastRestartSlotSysvar = "HooKD5NC9QNxk25QuzCssB8ecrEzGt6eXEPBUxWp1LaR",
+    reduce_stake_warmup_cooldown: ReduceStakeWarmupCooldown = "GwtDQBghCTBgmX2cpEGNPxTEBUTQRaDMGTr5qychdGMj",
+    revise_turbine_epoch_stakes: ReviseTurbineEpochStakes = "BTWmtJC8U5ZLMbBUUA1k6As62sYjPEjAiNAT55xYGdJU",
+    enable_poseidon_syscall: EnablePoseidonSyscall = "FL9RsQA6TVUoh5xJQ9d936RHSebA1NLQqe3Zv9sXZRpr",
+    timely_vote_credits: TimelyVoteCredits = "tvcF6b1TRz353zKuhBjinZkKzjmihXmBAHJdjNYw1sQ",
+    remaining_compute_units_syscall_enabled: RemainingComputeUnitsSyscallEnabled = "5TuppMutoyzhUSfuYdhgzD47F92GL1g89KpCZQKqedxP",
+    enable_loader_v4: EnableLoaderV4 = "2aQJYqER2aKyb3cZw22v4SL2xMX7vwXBRWfvS4pTrtED",
+    require_rent_exempt_split_destination: RequireRentExemptSplitDestination = "D2aip4BBr8NPWtU9vLrwrBvbuaQ8w1zV38zFLxx4pfBV",
+    better_error_codes_for_tx_lamport_check: BetterErrorCodesForTxLamportCheck = "Ffswd3egL3tccB6Rv3XY6oqfdzn913vUcjCSnpvCKpfx",
+    update_hashes_per_tick2: UpdateHashesPerTick2 = "EWme9uFqfy1ikK1jhJs8fM5hxWnK336QJpbscNtizkTU",
+    update_hashes_per_tick3: UpdateHashesPerTick3 = "8C8MCtsab5SsfammbzvYz65HHauuUYdbY2DZ4sznH6h5",
+    update_hashes_per_tick4: UpdateHashesPerTick4 = "8We4E7DPwF2WfAN8tRTtWQNhi98B99Qpuj7JoZ3Aikgg",
+    update_hashes_per_tick5: UpdateHashesPerTick5 = "BsKLKAn1WM4HVhPRDsjosmqSg2J8Tq5xP2s2daDS6Ni4",
+    update_hashes_per_tick6: UpdateHashesPerTick6 = "FKu1qYwLQSiehz644H6Si65U5ZQ2cp9GxsyFUfYcuADv",
+    validate_fee_collector_account: ValidateFeeCollectorAccount = "prpFrMtgNmzaNzkPJg9o753fVvbHKqNrNTm76foJ2wm",
+    disable_rent_fees_collection: DisableRentFeesCollection = "CJzY83ggJHqPGDq8VisV3U91jDJLuEaALZooBrXtnnLU",
+    enable_zk_transfer_with_fee: EnableZkTransferWithFee = "zkNLP7EQALfC1TYeB3biDU7akDckj8iPkvh9y2Mt2K3",
+    drop_legacy_shreds: DropLegacyShreds = "GV49KKQdBNaiv2pgqhS2Dy3GWYJGXMTVYbYkdk91orRy",
+    allow_commission_decrease_at_any_time: AllowCommissionDecreaseAtAnyTime = "decoMktMcnmiq6t3u7g5BfgcQu91nKZr6RvMYf9z1Jb",
+    add_new_reserved_account_keys: AddNewReservedAccountKeys = "8U4skmMVnF6k2kMvrWbQuRUT3qQSiTYpSjqmhmgfthZu",
+    consume_blockstore_duplicate_proofs: ConsumeBlockstoreDuplicateProofs = "6YsBCejwK96GZCkJ6mkZ4b68oP63z2PLoQmWjC7ggTqZ",
+    index_erasure_conflict_duplicate_proofs: IndexErasureConflictDuplicateProofs = "dupPajaLy2SSn8ko42aZz4mHANDNrLe8Nw8VQgFecLa",
+    merkle_conflict_duplicate_proofs: MerkleConflictDuplicateProofs = "mrkPjRg79B2oK2ZLgd7S3AfEJaX9B6gAF3H9aEykRUS",
+    disable_bpf_loader_instructions: DisableBpfLoaderInstructions = "7WeS1vfPRgeeoXArLh7879YcB9mgE9ktjPDtajXeWfXn",
+    enable_zk_proof_from_account: EnableZkProofFromAccount = "zkiTNuzBKxrCLMKehzuQeKZyLtX2yvFcEKMML8nExU8",
+    cost_model_requested_write_lock_cost: CostModelRequestedWriteLockCost = "wLckV1a64ngtcKPRGU4S4grVTestXjmNjxBjaKZrAcn",
+    enable_gossip_duplicate_proof_ingestion: EnableGossipDuplicateProofIngestion = "FNKCMBzYUdjhHyPdsKG2LSmdzH8TCHXn3ytj8RNBS4nG",
+    chained_merkle_conflict_duplicate_proofs: ChainedMerkleConflictDuplicateProofs = "chaie9S2zVfuxJKNRGkyTDokLwWxx6kD2ZLsqQHaDD8",
+    enable_chained_merkle_shreds: EnableChainedMerkleShreds = "7uZBkJXJ1HkuP6R3MJfZs7mLwymBcDbKdqbF51ZWLier",
+    remove_rounding_in_fee_calculation: RemoveRoundingInFeeCalculation = "BtVN7YjDzNE6Dk7kTT7YTDgMNUZTNgiSJgsdzAeTg2jF",
+    enable_tower_sync_ix: EnableTowerSyncIx = "tSynMCspg4xFiCj1v3TDb4c7crMR5tSBhLz4sF7rrNA",
+    deprecate_unused_legacy_vote_plumbing: DeprecateUnusedLegacyVotePlumbing = "6Uf8S75PVh91MYgPQSHnjRAPQq6an5BDv9vomrCwDqLe",
+    reward_full_priority_fee: RewardFullPriorityFee = "3opE3EzAKnUftUDURkzMgwpNgimBAypW1mNDYH4x4Zg7",
+    get_sysvar_syscall_enabled: GetSysvarSyscallEnabled = "CLCoTADvV64PSrnR6QXty6Fwrt9Xc6EdxSJE4wLRePjq",
+    abort_on_invalid_curve: AbortOnInvalidCurve = "FuS3FPfJDKSNot99ECLXtp3rueq36hMNStJkPJwWodLh",
+    migrate_feature_gate_program_to_core_bpf: MigrateFeatureGateProgramToCoreBpf = "4eohviozzEeivk1y9UbrnekbAFMDQyJz5JjA9Y6gyvky",
+    vote_only_full_fec_sets: VoteOnlyFullFecSets = "ffecLRhhakKSGhMuc6Fz2Lnfq4uT9q3iu9ZsNaPLxPc",
+    migrate_config_program_to_core_bpf: MigrateConfigProgramToCoreBpf = "2Fr57nzzkLYXW695UdDxDeR5fhnZWSttZeZYemrnpGFV",
+    enable_get_epoch_stake_syscall: EnableGetEpochStakeSyscall = "FKe75t4LXxGaQnVHdUKM6DSFifVVraGZ8LyNo7oPwy1Z",
+    migrate_address_lookup_table_program_to_core_bpf: MigrateAddressLookupTableProgramToCoreBpf = "C97eKZygrkU4JxJsZdjgbUY7iQR7rKTr4NyDWo2E5pRm",
+    zk_elgamal_proof_program_enabled: ZkElgamalProofProgramEnabled = "zkhiy5oLowR7HY4zogXjCjeMXyruLqBwSWH21qcFtnv",
+    verify_retransmitter_signature: VerifyRetransmitterSignature = "51VCKU5eV6mcTc9q9ArfWELU2CqDoi13hdAjr6fHMdtv",
+    move_stake_and_move_lamports_ixs: MoveStakeAndMoveLamportsIxs = "7bTK6Jis8Xpfrs8ZoUfiMDPazTcdPcTWheZFJTA5Z6X4",
+    ed25519_precompile_verify_strict: Ed25519PrecompileVerifyStrict = "ed9tNscbWLYBooxWA7FE2B5KHWs8A6sxfY8EzezEcoo",
+    vote_only_retransmitter_signed_fec_sets: VoteOnlyRetransmitterSignedFecSets = "RfEcA95xnhuwooVAhUUksEJLZBF7xKCLuqrJoqk4Zph",
+    move_precompile_verification_to_svm: MovePrecompileVerificationToSvm = "9ypxGLzkMxi89eDerRKXWDXe44UY2z4hBig4mDhNq5Dp",
+    enable_transaction_loading_failure_fees: EnableTransactionLoadingFailureFees = "PaymEPK2oqwT9TXAVfadjztH2H6KfLEB9Hhd5Q5frvP",
+    enable_turbine_extended_fanout_experiments: EnableTurbineExtendedFanoutExperiments = "turbRpTzBzDU6PJmWvRTbcJXXGxUs19CvQamUrRD9bN",
+    deprecate_legacy_vote_ixs: DeprecateLegacyVoteIxs = "depVvnQ2UysGrhwdiwU42tCadZL8GcBb1i2GYhMopQv",
+    disable_sbpf_v0_execution: DisableSbpfV0Execution = "TestFeature11111111111111111111111111111111",
+    reenable_sbpf_v0_execution: ReenableSbpfV0Execution = "TestFeature21111111111111111111111111111111",
+    enable_sbpf_v1_deployment_and_execution: EnableSbpfV1DeploymentAndExecution = "JE86WkYvTrzW8HgNmrHY7dFYpCmSptUpKupbo2AdQ9cG",
+    enable_sbpf_v2_deployment_and_execution: EnableSbpfV2DeploymentAndExecution = "F6UVKh1ujTEFK3en2SyAL3cdVnqko1FVEXWhmdLRu6WP",
+    enable_sbpf_v3_deployment_and_execution: EnableSbpfV3DeploymentAndExecution = "GJav1vwg2etvSWraPT96QvYuQJswJTJwtcyARrvkhuV9",
+    remove_accounts_executable_flag_checks: RemoveAccountsExecutableFlagChecks = "FXs1zh47QbNnhXcnB6YiAQoJ4sGB91tKF3UFHLcKT7PM",
+    disable_account_loader_special_case: DisableAccountLoaderSpecialCase = "EQUMpNFr7Nacb1sva56xn1aLfBxppEoSBH8RRVdkcD1x",
+    enable_secp256r1_precompile: EnableSecp256r1Precompile = "srremy31J5Y25FrAApwVb9kZcfXbusYMMsvTK9aWv5q",
+    accounts_lt_hash: AccountsLtHash = "LTHasHQX6661DaDD4S6A2TFi6QBuiwXKv66fB1obfHq",
+    snapshots_lt_hash: SnapshotsLtHash = "LTsNAP8h1voEVVToMNBNqoiNQex4aqfUrbFhRH3mSQ2",
+    remove_accounts_delta_hash: RemoveAccountsDeltaHash = "LTdLt9Ycbyoipz5fLysCi1NnDnASsZfmJLJXts5ZxZz",
+    migrate_stake_program_to_core_bpf: MigrateStakeProgramToCoreBpf = "6M4oQ6eXneVhtLoiAr4yRYQY43eVLjrKbiDZDJc892yk",
+    deplete_cu_meter_on_vm_failure: DepleteCuMeterOnVmFailure = "B7H2caeia4ZFcpE3QcgMqbiWiBtWrdBRBSJ1DY6Ktxbq",
+    reserve_minimal_cus_for_builtin_instructions: ReserveMinimalCusForBuiltinInstructions = "C9oAhLxDBm3ssWtJx1yBGzPY55r2rArHmN1pbQn6HogH",
+    raise_block_limits_to_50m: RaiseBlockLimitsTo50m = "5oMCU3JPaFLr8Zr4ct7yFA7jdk6Mw1RmB8K4u9ZbS42z",
+    drop_unchained_merkle_shreds: DropUnchainedMerkleShreds = "5KLGJSASDVxKPjLCDWNtnABLpZjsQSrYZ8HKwcEdAMC8",
+    relax_intrabatch_account_locks: RelaxIntrabatchAccountLocks = "ENTRYnPAoT5Swwx73YDGzMp3XnNH1kxacyvLosRHza1i",
+    create_slashing_program: CreateSlashingProgram = "sProgVaNWkYdP2eTRAy1CPrgb3b9p8yXCASrPEqo6VJ",
+    disable_partitioned_rent_collection: DisablePartitionedRentCollection = "2B2SBNbUcr438LtGXNcJNBP2GBSxjx81F945SdSkUSfC",
+    enable_vote_address_leader_schedule: EnableVoteAddressLeaderSchedule = "5JsG4NWH8Jbrqdd8uL6BNwnyZK3dQSoieRXG5vmofj9y",
+    require_static_nonce_account: RequireStaticNonceAccount = "7VVhpg5oAjAmnmz1zCcSHb2Z9ecZB2FQqpnEwReka9Zm",
+    raise_block_limits_to_60m: RaiseBlockLimitsTo60m = "6oMCUgfY6BzZ6jwB681J6ju5Bh6CjVXbd7NeWYqiXBSu",
+    mask_out_rent_epoch_in_vm_serialization: MaskOutRentEpochInVmSerialization = "RENtePQcDLrAbxAsP3k8dwVcnNYQ466hi2uKvALjnXx",
+    enshrine_slashing_program: EnshrineSlashingProgram = "sProgVaNWkYdP2eTRAy1CPrgb3b9p8yXCASrPEqo6VJ",
+    enable_extend_program_checked: EnableExtendProgramChecked = "2oMRZEDWT2tqtYMofhmmfQ8SsjqUFzT6sYXppQDavxwz",
+    formalize_loaded_transaction_data_size: FormalizeLoadedTransactionDataSize = "DeS7sR48ZcFTUmt5FFEVDr1v1bh73aAbZiZq3SYr8Eh8",
+    alpenglow: Alpenglow = "mustRekeyVm2QHYB3JPefBiU4BY3Z6JkW2k3Scw5GWP",
+    disable_zk_elgamal_proof_program: DisableZkElgamalProofProgram = "zkdoVwnSFnSLtGJG7irJPEYUpmb4i7sGMGcnN6T9rnC",
+    reenable_zk_elgamal_proof_program: ReenableZkElgamalProofProgram = "zkemPXcuM3G4wpMDZ36Cpw34EjUpvm1nuioiSGbGZPR",
+    gossip_duplicate_shred_proof_ingestion: GossipDuplicateShredProofIngestion = "6xFp6W7QmrXUxyqJjrNthxAjPMCdKH3uAQ42XLgdxh5a",
+    ;
+    FullInflationDevnetAndTestnet = full_inflation::devnet_and_testnet,
+    FullInflationMainnetCertusoneVote = full_inflation::mainnet::certusone::vote,
+    FullInflationMainnetCertusoneEnable = full_inflation::mainnet::certusone::enable,
+}
+
+/// Category of change that a feature gates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeatureKind {
+    /// Corrects behavior that was wrong or unsafe
+    BugFix,
+    /// Improves throughput or resource usage, including limit changes
+    Performance,
+    /// Adds or changes syscalls
+    Syscall,
+    /// Changes inflation, rent, fees, rewards or staking
+    Economic,
+    /// Adds or changes builtin programs, precompiles, loaders or transaction processing
+    Program,
+    /// Changes voting, block production, shreds or turbine
+    Consensus,
+}
+
+/// Metadata describing a feature known to this software
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureDescriptor {
+    pub id: Pubkey,
+    pub description: &'static str,
+    pub kind: FeatureKind,
+    /// Release that introduced the feature, if recorded
+    pub since_version: Option<&'static str>,
+}
+
+static FEATURE_DESCRIPTORS: LazyLock<Vec<FeatureDescriptor>> = LazyLock::new(|| {
+    [
+        (secp256k1_program_enabled::id(), "secp256k1 program", FeatureKind::Program),
+        (deprecate_rewards_sysvar::id(), "deprecate unused rewards sysvar", FeatureKind::Program),
+        (pico_inflation::id(), "pico inflation", FeatureKind::Economic),
+        (full_inflation::devnet_and_testnet::id(), "full inflation on devnet and testnet", FeatureKind::Economic),
+        (spl_token_v2_multisig_fix::id(), "spl-token multisig fix", FeatureKind::BugFix),
+        (no_overflow_rent_distribution::id(), "no overflow rent distribution", FeatureKind::Economic),
+        (filter_stake_delegation_accounts::id(), "filter stake_delegation_accounts #14062", FeatureKind::Economic),
+        (require_custodian_for_locked_stake_authorize::id(), "require custodian to authorize withdrawer change for locked stake", FeatureKind::Economic),
+        (spl_token_v2_self_transfer_fix::id(), "spl-token self-transfer fix", FeatureKind::BugFix),
+        (full_inflation::mainnet::certusone::enable::id(), "full inflation enabled by Certus One", FeatureKind::Economic),
+        (full_inflation::mainnet::certusone::vote::id(), "community vote allowing Certus One to enable full inflation", FeatureKind::Economic),
+        (warp_timestamp_again::id(), "warp timestamp again, adjust bounding to 25% fast 80% slow #15204", FeatureKind::Consensus),
+        (check_init_vote_data::id(), "check initialized Vote data", FeatureKind::BugFix),
+        (secp256k1_recover_syscall_enabled::id(), "secp256k1_recover syscall", FeatureKind::Syscall),
+        (system_transfer_zero_check::id(), "perform all checks for transfers of 0 lamports", FeatureKind::BugFix),
+        (blake3_syscall_enabled::id(), "blake3 syscall", FeatureKind::Syscall),
+        (dedupe_config_program_signers::id(), "dedupe config program signers", FeatureKind::Program),
+        (verify_tx_signatures_len::id(), "prohibit extra transaction signatures", FeatureKind::BugFix),
+        (vote_stake_checked_instructions::id(), "vote/state program checked instructions #18345", FeatureKind::Program),
+        (rent_for_sysvars::id(), "collect rent from accounts owned by sysvars", FeatureKind::Economic),
+        (libsecp256k1_0_5_upgrade_enabled::id(), "upgrade libsecp256k1 to v0.5.0", FeatureKind::Program),
+        (tx_wide_compute_cap::id(), "transaction wide compute cap", FeatureKind::Performance),
+        (spl_token_v2_set_authority_fix::id(), "spl-token set_authority fix", FeatureKind::BugFix),
+        (merge_nonce_error_into_system_error::id(), "merge NonceError into SystemError", FeatureKind::BugFix),
+        (disable_fees_sysvar::id(), "disable fees sysvar", FeatureKind::Program),
+        (stake_merge_with_unmatched_credits_observed::id(), "allow merging active stakes with unmatched credits_observed #18985", FeatureKind::Economic),
+        (zk_token_sdk_enabled::id(), "enable Zk Token proof program and syscalls", FeatureKind::Program),
+        (curve25519_syscall_enabled::id(), "enable curve25519 syscalls", FeatureKind::Syscall),
+        (versioned_tx_message_enabled::id(), "enable versioned transaction message processing", FeatureKind::Program),
+        (libsecp256k1_fail_on_bad_count::id(), "fail libsecp256k1_verify if count appears wrong", FeatureKind::BugFix),
+        (libsecp256k1_fail_on_bad_count2::id(), "fail libsecp256k1_verify if count appears wrong", FeatureKind::BugFix),
+        (instructions_sysvar_owned_by_sysvar::id(), "fix owner for instructions sysvar", FeatureKind::BugFix),
+        (stake_program_advance_activating_credits_observed::id(), "Enable advancing credits observed for activation epoch #19309", FeatureKind::Economic),
+        (credits_auto_rewind::id(), "Auto rewind stake's credits_observed if (accidental) vote recreation is detected #22546", FeatureKind::Economic),
+        (demote_program_write_locks::id(), "demote program write locks to readonly, except when upgradeable loader present #19593 #20265", FeatureKind::Program),
+        (ed25519_program_enabled::id(), "enable builtin ed25519 signature verify program", FeatureKind::Program),
+        (return_data_syscall_enabled::id(), "enable sol_{set,get}_return_data syscall", FeatureKind::Syscall),
+        (reduce_required_deploy_balance::id(), "reduce required payer balance for program deploys", FeatureKind::Economic),
+        (sol_log_data_syscall_enabled::id(), "enable sol_log_data syscall", FeatureKind::Syscall),
+        (stakes_remove_delegation_if_inactive::id(), "remove delegations from stakes cache when inactive", FeatureKind::Economic),
+        (do_support_realloc::id(), "support account data reallocation", FeatureKind::Program),
+        (prevent_calling_precompiles_as_programs::id(), "prevent calling precompiles as programs", FeatureKind::BugFix),
+        (optimize_epoch_boundary_updates::id(), "optimize epoch boundary updates", FeatureKind::Performance),
+        (remove_native_loader::id(), "remove support for the native loader", FeatureKind::Program),
+        (send_to_tpu_vote_port::id(), "send votes to the tpu vote port", FeatureKind::Performance),
+        (requestable_heap_size::id(), "Requestable heap frame size", FeatureKind::Performance),
+        (disable_fee_calculator::id(), "deprecate fee calculator", FeatureKind::Economic),
+        (add_compute_budget_program::id(), "Add compute_budget_program", FeatureKind::Program),
+        (nonce_must_be_writable::id(), "nonce must be writable", FeatureKind::Program),
+        (spl_token_v3_3_0_release::id(), "spl-token v3.3.0 release", FeatureKind::Program),
+        (leave_nonce_on_success::id(), "leave nonce as is on success", FeatureKind::Program),
+        (reject_empty_instruction_without_program::id(), "fail instructions which have native_loader as program_id directly", FeatureKind::BugFix),
+        (fixed_memcpy_nonoverlapping_check::id(), "use correct check for nonoverlapping regions in memcpy syscall", FeatureKind::BugFix),
+        (reject_non_rent_exempt_vote_withdraws::id(), "fail vote withdraw instructions which leave the account non-rent-exempt", FeatureKind::BugFix),
+        (evict_invalid_stakes_cache_entries::id(), "evict invalid stakes cache entries on epoch boundaries", FeatureKind::Economic),
+        (allow_votes_to_directly_update_vote_state::id(), "enable direct vote state update", FeatureKind::Consensus),
+        (max_tx_account_locks::id(), "enforce max number of locked accounts per transaction", FeatureKind::Performance),
+        (require_rent_exempt_accounts::id(), "require all new transaction accounts with data to be rent-exempt", FeatureKind::Economic),
+        (filter_votes_outside_slot_hashes::id(), "filter vote slots older than the slot hashes history", FeatureKind::Consensus),
+        (update_syscall_base_costs::id(), "update syscall base costs", FeatureKind::Performance),
+        (stake_deactivate_delinquent_instruction::id(), "enable the deactivate delinquent stake instruction #23932", FeatureKind::Economic),
+        (vote_withdraw_authority_may_change_authorized_voter::id(), "vote account withdraw authority may change the authorized voter #22521", FeatureKind::Consensus),
+        (spl_associated_token_account_v1_0_4::id(), "SPL Associated Token Account Program release version 1.0.4, tied to token 3.3.0 #22648", FeatureKind::Program),
+        (reject_vote_account_close_unless_zero_credit_epoch::id(), "fail vote account withdraw to 0 unless account earned 0 credits in last completed epoch", FeatureKind::Economic),
+        (add_get_processed_sibling_instruction_syscall::id(), "add add_get_processed_sibling_instruction_syscall", FeatureKind::Syscall),
+        (bank_transaction_count_fix::id(), "fixes Bank::transaction_count to include all committed transactions, not just successful ones", FeatureKind::BugFix),
+        (disable_bpf_deprecated_load_instructions::id(), "disable ldabs* and ldind* SBF instructions", FeatureKind::BugFix),
+        (disable_bpf_unresolved_symbols_at_runtime::id(), "disable reporting of unresolved SBF symbols at runtime", FeatureKind::BugFix),
+        (record_instruction_in_transaction_context_push::id(), "move the CPI stack overflow check to the end of push", FeatureKind::BugFix),
+        (syscall_saturated_math::id(), "syscalls use saturated math", FeatureKind::BugFix),
+        (check_physical_overlapping::id(), "check physical overlapping regions", FeatureKind::BugFix),
+        (limit_secp256k1_recovery_id::id(), "limit secp256k1 recovery id", FeatureKind::BugFix),
+        (disable_deprecated_loader::id(), "disable the deprecated BPF loader", FeatureKind::BugFix),
+        (check_slice_translation_size::id(), "check size when translating slices", FeatureKind::BugFix),
+        (stake_split_uses_rent_sysvar::id(), "stake split instruction uses rent sysvar", FeatureKind::BugFix),
+        (add_get_minimum_delegation_instruction_to_stake_program::id(), "add GetMinimumDelegation instruction to stake program", FeatureKind::Economic),
+        (error_on_syscall_bpf_function_hash_collisions::id(), "error on bpf function hash collisions", FeatureKind::BugFix),
+        (reject_callx_r10::id(), "Reject bpf callx r10 instructions", FeatureKind::BugFix),
+        (drop_redundant_turbine_path::id(), "drop redundant turbine path", FeatureKind::Consensus),
+        (executables_incur_cpi_data_cost::id(), "Executables incur CPI data costs", FeatureKind::Performance),
+        (fix_recent_blockhashes::id(), "stop adding hashes for skipped slots to recent blockhashes", FeatureKind::BugFix),
+        (update_rewards_from_cached_accounts::id(), "update rewards from cached accounts", FeatureKind::Economic),
+        (spl_token_v3_4_0::id(), "SPL Token Program version 3.4.0 release #24740", FeatureKind::Program),
+        (spl_associated_token_account_v1_1_0::id(), "SPL Associated Token Account Program version 1.1.0 release #24741", FeatureKind::Program),
+        (default_units_per_instruction::id(), "Default max tx-wide compute units calculated per instruction", FeatureKind::Performance),
+        (stake_allow_zero_undelegated_amount::id(), "Allow zero-lamport undelegated amount for initialized stakes #24670", FeatureKind::Economic),
+        (require_static_program_ids_in_transaction::id(), "require static program ids in versioned transactions", FeatureKind::BugFix),
+        (stake_raise_minimum_delegation_to_1_sol::id(), "Raise minimum stake delegation to 1.0 SOL #24357", FeatureKind::Economic),
+        (stake_minimum_delegation_for_rewards::id(), "stakes must be at least the minimum delegation to earn rewards", FeatureKind::Economic),
+        (add_set_compute_unit_price_ix::id(), "add compute budget ix for setting a compute unit price", FeatureKind::Performance),
+        (disable_deploy_of_alloc_free_syscall::id(), "disable new deployments of deprecated sol_alloc_free_ syscall", FeatureKind::Program),
+        (include_account_index_in_rent_error::id(), "include account index in rent tx error #25190", FeatureKind::BugFix),
+        (add_shred_type_to_shred_seed::id(), "add shred-type to shred seed #25556", FeatureKind::Consensus),
+        (warp_timestamp_with_a_vengeance::id(), "warp timestamp again, adjust bounding to 150% slow #25666", FeatureKind::Consensus),
+        (separate_nonce_from_blockhash::id(), "separate durable nonce and blockhash domains #25744", FeatureKind::BugFix),
+        (enable_durable_nonce::id(), "enable durable nonce #25744", FeatureKind::Program),
+        (vote_state_update_credit_per_dequeue::id(), "Calculate vote credits for VoteStateUpdate per vote dequeue to match credit awards for Vote instruction", FeatureKind::Economic),
+        (quick_bail_on_panic::id(), "quick bail on panic", FeatureKind::BugFix),
+        (nonce_must_be_authorized::id(), "nonce must be authorized", FeatureKind::Program),
+        (nonce_must_be_advanceable::id(), "durable nonces must be advanceable", FeatureKind::Program),
+        (vote_authorize_with_seed::id(), "An instruction you can use to change a vote accounts authority when the current authority is a derived key #25860", FeatureKind::Program),
+        (preserve_rent_epoch_for_rent_exempt_accounts::id(), "preserve rent epoch for rent exempt accounts #26479", FeatureKind::Economic),
+        (enable_bpf_loader_extend_program_ix::id(), "enable bpf upgradeable loader ExtendProgram instruction #25234", FeatureKind::Program),
+        (skip_rent_rewrites::id(), "skip rewriting rent exempt accounts during rent collection #26491", FeatureKind::Economic),
+        (enable_early_verification_of_account_modifications::id(), "enable early verification of account modifications #25899", FeatureKind::Program),
+        (disable_rehash_for_rent_epoch::id(), "on accounts hash calculation, do not try to rehash accounts #28934", FeatureKind::Performance),
+        (account_hash_ignore_slot::id(), "ignore slot when calculating an account hash #28420", FeatureKind::Performance),
+        (set_exempt_rent_epoch_max::id(), "set rent epoch to Epoch::MAX for rent-exempt accounts #28683", FeatureKind::Economic),
+        (on_load_preserve_rent_epoch_for_rent_exempt_accounts::id(), "on bank load account, do not try to fix up rent_epoch #28541", FeatureKind::Economic),
+        (prevent_crediting_accounts_that_end_rent_paying::id(), "prevent crediting rent paying accounts #26606", FeatureKind::Economic),
+        (cap_bpf_program_instruction_accounts::id(), "enforce max number of accounts per bpf program instruction #26628", FeatureKind::Program),
+        (loosen_cpi_size_restriction::id(), "loosen cpi size restrictions #26641", FeatureKind::Program),
+        (use_default_units_in_fee_calculation::id(), "use default units per instruction in fee calculation #26785", FeatureKind::Economic),
+        (compact_vote_state_updates::id(), "Compact vote state updates to lower block size", FeatureKind::Consensus),
+        (incremental_snapshot_only_incremental_hash_calculation::id(), "only hash accounts in incremental snapshot during incremental snapshot creation #26799", FeatureKind::Performance),
+        (disable_cpi_setting_executable_and_rent_epoch::id(), "disable setting is_executable and_rent_epoch in CPI #26987", FeatureKind::BugFix),
+        (relax_authority_signer_check_for_lookup_table_creation::id(), "relax authority signer check for lookup table creation #27205", FeatureKind::Program),
+        (stop_sibling_instruction_search_at_parent::id(), "stop the search in get_processed_sibling_instruction when the parent instruction is reached #27289", FeatureKind::BugFix),
+        (vote_state_update_root_fix::id(), "fix root in vote state updates #27361", FeatureKind::BugFix),
+        (cap_accounts_data_allocations_per_transaction::id(), "cap accounts data allocations per transaction #27375", FeatureKind::Performance),
+        (epoch_accounts_hash::id(), "enable epoch accounts hash calculation #27539", FeatureKind::Program),
+        (remove_deprecated_request_unit_ix::id(), "remove support for RequestUnitsDeprecated instruction #27500", FeatureKind::Program),
+        (increase_tx_account_lock_limit::id(), "increase tx account lock limit to 128 #27241", FeatureKind::Performance),
+        (limit_max_instruction_trace_length::id(), "limit max instruction trace length #27939", FeatureKind::Performance),
+        (check_syscall_outputs_do_not_overlap::id(), "check syscall outputs do_not overlap #28600", FeatureKind::Syscall),
+        (enable_bpf_loader_set_authority_checked_ix::id(), "enable bpf upgradeable loader SetAuthorityChecked instruction #28424", FeatureKind::Program),
+        (enable_alt_bn128_syscall::id(), "add alt_bn128 syscalls #27961", FeatureKind::Syscall),
+        (simplify_alt_bn128_syscall_error_codes::id(), "SIMD-0129: simplify alt_bn128 syscall error codes", FeatureKind::Syscall),
+        (enable_program_redeployment_cooldown::id(), "enable program redeployment cooldown #29135", FeatureKind::Program),
+        (commission_updates_only_allowed_in_first_half_of_epoch::id(), "validator commission updates are only allowed in the first half of an epoch #29362", FeatureKind::Economic),
+        (enable_turbine_fanout_experiments::id(), "enable turbine fanout experiments #29393", FeatureKind::Consensus),
+        (disable_turbine_fanout_experiments::id(), "disable turbine fanout experiments #29393", FeatureKind::Consensus),
+        (move_serialized_len_ptr_in_cpi::id(), "cpi ignore serialized_len_ptr #29592", FeatureKind::Program),
+        (update_hashes_per_tick::id(), "Update desired hashes per tick on epoch boundary", FeatureKind::Performance),
+        (enable_big_mod_exp_syscall::id(), "add big_mod_exp syscall #28503", FeatureKind::Syscall),
+        (disable_builtin_loader_ownership_chains::id(), "disable builtin loader ownership chains #29956", FeatureKind::Program),
+        (cap_transaction_accounts_data_size::id(), "cap transaction accounts data size up to a limit #27839", FeatureKind::Performance),
+        (remove_congestion_multiplier_from_fee_calculation::id(), "Remove congestion multiplier from transaction fee calculation #29881", FeatureKind::Economic),
+        (enable_request_heap_frame_ix::id(), "Enable transaction to request heap frame using compute budget instruction #30076", FeatureKind::Performance),
+        (prevent_rent_paying_rent_recipients::id(), "prevent recipients of rent rewards from ending in rent-paying state #30151", FeatureKind::Economic),
+        (delay_visibility_of_program_deployment::id(), "delay visibility of program upgrades #30085", FeatureKind::Program),
+        (apply_cost_tracker_during_replay::id(), "apply cost tracker to blocks during replay #29595", FeatureKind::Performance),
+        (add_set_tx_loaded_accounts_data_size_instruction::id(), "add compute budget instruction for setting account data size per transaction #30366", FeatureKind::Program),
+        (switch_to_new_elf_parser::id(), "switch to new ELF parser #30497", FeatureKind::Program),
+        (round_up_heap_size::id(), "round up heap size when calculating heap cost #30679", FeatureKind::Performance),
+        (remove_bpf_loader_incorrect_program_id::id(), "stop incorrectly throwing IncorrectProgramId in bpf_loader #30747", FeatureKind::Program),
+        (include_loaded_accounts_data_size_in_fee_calculation::id(), "include transaction loaded accounts data size in base fee calculation #30657", FeatureKind::Economic),
+        (native_programs_consume_cu::id(), "Native program should consume compute units #30620", FeatureKind::Performance),
+        (simplify_writable_program_account_check::id(), "Simplify checks performed for writable upgradeable program accounts #30559", FeatureKind::Program),
+        (stop_truncating_strings_in_syscalls::id(), "Stop truncating strings in syscalls #31029", FeatureKind::Syscall),
+        (clean_up_delegation_errors::id(), "Return InsufficientDelegation instead of InsufficientFunds or InsufficientStake where applicable #31206", FeatureKind::BugFix),
+        (vote_state_add_vote_latency::id(), "replace Lockout with LandedVote (including vote latency) in vote state #31264", FeatureKind::Consensus),
+        (checked_arithmetic_in_fee_validation::id(), "checked arithmetic in fee validation #31273", FeatureKind::BugFix),
+        (bpf_account_data_direct_mapping::id(), "use memory regions to map account data into the rbpf vm instead of copying the data", FeatureKind::Performance),
+        (last_restart_slot_sysvar::id(), "enable new sysvar last_restart_slot", FeatureKind::Program),
+        (reduce_stake_warmup_cooldown::id(), "reduce stake warmup cooldown from 25% to 9%", FeatureKind::Economic),
+        (revise_turbine_epoch_stakes::id(), "revise turbine epoch stakes", FeatureKind::Consensus),
+        (enable_poseidon_syscall::id(), "Enable Poseidon syscall", FeatureKind::Syscall),
+        (timely_vote_credits::id(), "use timeliness of votes in determining credits to award", FeatureKind::Economic),
+        (remaining_compute_units_syscall_enabled::id(), "enable the remaining_compute_units syscall", FeatureKind::Syscall),
+        (enable_loader_v4::id(), "SIMD-0167: Enable Loader-v4", FeatureKind::Program),
+        (require_rent_exempt_split_destination::id(), "Require stake split destination account to be rent exempt", FeatureKind::Economic),
+        (better_error_codes_for_tx_lamport_check::id(), "better error codes for tx lamport check #33353", FeatureKind::BugFix),
+        (enable_alt_bn128_compression_syscall::id(), "add alt_bn128 compression syscalls", FeatureKind::Syscall),
+        (update_hashes_per_tick2::id(), "Update desired hashes per tick to 2.8M", FeatureKind::Performance),
+        (update_hashes_per_tick3::id(), "Update desired hashes per tick to 4.4M", FeatureKind::Performance),
+        (update_hashes_per_tick4::id(), "Update desired hashes per tick to 7.6M", FeatureKind::Performance),
+        (update_hashes_per_tick5::id(), "Update desired hashes per tick to 9.2M", FeatureKind::Performance),
+        (update_hashes_per_tick6::id(), "Update desired hashes per tick to 10M", FeatureKind::Performance),
+        (validate_fee_collector_account::id(), "validate fee collector account #33888", FeatureKind::Economic),
+        (disable_rent_fees_collection::id(), "Disable rent fees collection #33945", FeatureKind::Economic),
+        (enable_zk_transfer_with_fee::id(), "enable Zk Token proof program transfer with fee", FeatureKind::Program),
+        (drop_legacy_shreds::id(), "drops legacy shreds #34328", FeatureKind::Consensus),
+        (allow_commission_decrease_at_any_time::id(), "Allow commission decrease at any time in epoch #33843", FeatureKind::Economic),
+        (consume_blockstore_duplicate_proofs::id(), "consume duplicate proofs from blockstore in consensus #34372", FeatureKind::Consensus),
+        (add_new_reserved_account_keys::id(), "add new unwritable reserved accounts #34899", FeatureKind::Program),
+        (index_erasure_conflict_duplicate_proofs::id(), "generate duplicate proofs for index and erasure conflicts #34360", FeatureKind::Consensus),
+        (merkle_conflict_duplicate_proofs::id(), "generate duplicate proofs for merkle root conflicts #34270", FeatureKind::Consensus),
+        (disable_bpf_loader_instructions::id(), "disable bpf loader management instructions #34194", FeatureKind::Program),
+        (enable_zk_proof_from_account::id(), "Enable zk token proof program to read proof from accounts instead of instruction data #34750", FeatureKind::Program),
+        (curve25519_restrict_msm_length::id(), "restrict curve25519 multiscalar multiplication vector lengths #34763", FeatureKind::BugFix),
+        (cost_model_requested_write_lock_cost::id(), "cost model uses number of requested write locks #34819", FeatureKind::Performance),
+        (enable_gossip_duplicate_proof_ingestion::id(), "enable gossip duplicate proof ingestion #32963", FeatureKind::Consensus),
+        (enable_chained_merkle_shreds::id(), "Enable chained Merkle shreds #34916", FeatureKind::Consensus),
+        (remove_rounding_in_fee_calculation::id(), "Removing unwanted rounding in fee calculation #34982", FeatureKind::Economic),
+        (deprecate_unused_legacy_vote_plumbing::id(), "Deprecate unused legacy vote tx plumbing", FeatureKind::Consensus),
+        (enable_tower_sync_ix::id(), "Enable tower sync vote instruction", FeatureKind::Consensus),
+        (chained_merkle_conflict_duplicate_proofs::id(), "generate duplicate proofs for chained merkle root conflicts", FeatureKind::Consensus),
+        (reward_full_priority_fee::id(), "Reward full priority fee to validators #34731", FeatureKind::Economic),
+        (abort_on_invalid_curve::id(), "SIMD-0137: Abort when elliptic curve syscalls invoked on invalid curve id", FeatureKind::Syscall),
+        (get_sysvar_syscall_enabled::id(), "Enable syscall for fetching Sysvar bytes #615", FeatureKind::Syscall),
+        (migrate_feature_gate_program_to_core_bpf::id(), "Migrate Feature Gate program to Core BPF (programify) #1003", FeatureKind::Program),
+        (vote_only_full_fec_sets::id(), "vote only full fec sets", FeatureKind::Consensus),
+        (migrate_config_program_to_core_bpf::id(), "Migrate Config program to Core BPF #1378", FeatureKind::Program),
+        (enable_get_epoch_stake_syscall::id(), "Enable syscall: sol_get_epoch_stake #884", FeatureKind::Syscall),
+        (migrate_address_lookup_table_program_to_core_bpf::id(), "Migrate Address Lookup Table program to Core BPF #1651", FeatureKind::Program),
+        (zk_elgamal_proof_program_enabled::id(), "SIMD-0153: Enable ZkElGamalProof program", FeatureKind::Program),
+        (verify_retransmitter_signature::id(), "Verify retransmitter signature #1840", FeatureKind::Consensus),
+        (move_stake_and_move_lamports_ixs::id(), "Enable MoveStake and MoveLamports stake program instructions #1610", FeatureKind::Program),
+        (ed25519_precompile_verify_strict::id(), "SIMD-0152: Use strict verification in ed25519 precompile", FeatureKind::BugFix),
+        (vote_only_retransmitter_signed_fec_sets::id(), "vote only on retransmitter signed fec sets", FeatureKind::Consensus),
+        (move_precompile_verification_to_svm::id(), "SIMD-0159: Move precompile verification into SVM", FeatureKind::Program),
+        (enable_transaction_loading_failure_fees::id(), "SIMD-0082: Enable fees for some additional transaction failures", FeatureKind::Economic),
+        (enable_turbine_extended_fanout_experiments::id(), "enable turbine extended fanout experiments #", FeatureKind::Consensus),
+        (deprecate_legacy_vote_ixs::id(), "Deprecate legacy vote instructions", FeatureKind::Consensus),
+        (partitioned_epoch_rewards_superfeature::id(), "SIMD-0118: replaces enable_partitioned_epoch_reward to enable partitioned rewards at epoch boundary", FeatureKind::Economic),
+        (disable_sbpf_v0_execution::id(), "SIMD-0161: Disables execution of SBPFv0 programs", FeatureKind::Program),
+        (reenable_sbpf_v0_execution::id(), "Re-enables execution of SBPFv0 programs", FeatureKind::Program),
+        (enable_sbpf_v1_deployment_and_execution::id(), "SIMD-0166: Enable deployment and execution of SBPFv1 programs", FeatureKind::Program),
+        (enable_sbpf_v2_deployment_and_execution::id(), "SIMD-0173 and SIMD-0174: Enable deployment and execution of SBPFv2 programs", FeatureKind::Program),
+        (enable_sbpf_v3_deployment_and_execution::id(), "SIMD-0178, SIMD-0179 and SIMD-0189: Enable deployment and execution of SBPFv3 programs", FeatureKind::Program),
+        (remove_accounts_executable_flag_checks::id(), "SIMD-0162: Remove checks of accounts is_executable flag", FeatureKind::Program),
+        (disable_account_loader_special_case::id(), "Disable account loader special case #3513", FeatureKind::Program),
+        (accounts_lt_hash::id(), "SIMD-0215: enables lattice-based accounts hash", FeatureKind::Performance),
+        (snapshots_lt_hash::id(), "SIMD-0220: snapshots use lattice-based accounts hash", FeatureKind::Performance),
+        (remove_accounts_delta_hash::id(), "SIMD-0223: removes accounts delta hash", FeatureKind::Performance),
+        (enable_secp256r1_precompile::id(), "SIMD-0075: Enable secp256r1 precompile", FeatureKind::Program),
+        (migrate_stake_program_to_core_bpf::id(), "SIMD-0196: Migrate Stake program to Core BPF #3655", FeatureKind::Program),
+        (deplete_cu_meter_on_vm_failure::id(), "SIMD-0182: Deplete compute meter for vm errors #3993", FeatureKind::Performance),
+        (reserve_minimal_cus_for_builtin_instructions::id(), "SIMD-0170: Reserve minimal CUs for builtin instructions #2562", FeatureKind::Performance),
+        (raise_block_limits_to_50m::id(), "SIMD-0207: Raise block limit to 50M", FeatureKind::Performance),
+        (fix_alt_bn128_multiplication_input_length::id(), "SIMD-0222: fix alt_bn128 multiplication input length #3686", FeatureKind::BugFix),
+        (drop_unchained_merkle_shreds::id(), "drops unchained Merkle shreds #2149", FeatureKind::Consensus),
+        (relax_intrabatch_account_locks::id(), "SIMD-0083: Allow batched transactions to read/write and write/write the same accounts", FeatureKind::Performance),
+        (create_slashing_program::id(), "SIMD-0204: creates an enshrined slashing program", FeatureKind::Program),
+        (disable_partitioned_rent_collection::id(), "SIMD-0175: Disable partitioned rent collection #4562", FeatureKind::Economic),
+        (enable_vote_address_leader_schedule::id(), "SIMD-0180: Enable vote address leader schedule #4573", FeatureKind::Consensus),
+        (require_static_nonce_account::id(), "SIMD-0242: Static Nonce Account Only", FeatureKind::BugFix),
+        (raise_block_limits_to_60m::id(), "SIMD-0256: Raise block limit to 60M", FeatureKind::Performance),
+        (mask_out_rent_epoch_in_vm_serialization::id(), "SIMD-0267: Sets rent_epoch to a constant in the VM", FeatureKind::Program),
+        (enshrine_slashing_program::id(), "SIMD-0204: Slashable event verification", FeatureKind::Program),
+        (enable_extend_program_checked::id(), "Enable ExtendProgramChecked instruction", FeatureKind::Program),
+        (formalize_loaded_transaction_data_size::id(), "SIMD-0186: Loaded transaction data size specification", FeatureKind::Program),
+        (alpenglow::id(), "Enable Alpenglow", FeatureKind::Consensus),
+        (disable_zk_elgamal_proof_program::id(), "Disables zk-elgamal-proof program", FeatureKind::BugFix),
+        (reenable_zk_elgamal_proof_program::id(), "Re-enables zk-elgamal-proof program", FeatureKind::Program),
+        (gossip_duplicate_shred_proof_ingestion::id(), "Ingest duplicate shred proofs received through gossip", FeatureKind::Consensus),
+        /*************** ADD NEW FEATURES HERE ***************/
+    ]
+    .into_iter()
+    .map(|(id, description, kind)| FeatureDescriptor {
+        id,
+        description,
+        kind,
+        since_version: None,
+    })
+    .collect()
+});
 
-pub mod curve25519_restrict_msm_length {
-    solana_pubkey::declare_id!("eca6zf6JJRjQsYYPkBHF3N32MTzur4n2WL4QiiacPCL");
+/// Iterate over the metadata of all features known to this software, in
+/// declaration order
+pub fn feature_descriptors() -> impl Iterator<Item = &'static FeatureDescriptor> {
+    FEATURE_DESCRIPTORS.iter()
 }
 
-pub mod versioned_tx_message_enabled {
-    solana_pubkey::declare_id!("3KZZ6Ks1885aGBQ45fwRcPXVBCtzUvxhUTkwKMR41Tca");
-}
+/// Map of feature ids to their descriptions, derived from `feature_descriptors()`
+pub static FEATURE_NAMES: LazyLock<AHashMap<Pubkey, &'static str>> = LazyLock::new(|| {
+    feature_descriptors()
+        .map(|descriptor| (descriptor.id, descriptor.description))
+        .collect()
+});
 
-pub mod libsecp256k1_fail_on_bad_count {
-    solana_pubkey::declare_id!("8aXvSuopd1PUj7UhehfXJRg6619RHp8ZvwTyyJHdUYsj");
-}
+/// Features that were removed from this software, with the reason they were
+/// retired. Retired ids are never part of `FEATURE_NAMES`.
+pub static RETIRED_FEATURES: LazyLock<AHashMap<Pubkey, &'static str>> = LazyLock::new(|| {
+    [
+        (
+            solana_pubkey::pubkey!("9bn2vTJUsUcnpiZWbu2woSKtTGW3ErZC9ERv88SDqQjK"),
+            "enable_partitioned_epoch_reward: replaced by partitioned_epoch_rewards_superfeature",
+        ),
+        (
+            solana_pubkey::pubkey!("AjX3A4Nv2rzUuATEUWLP4rrBaBropyUnHxEvFDj1dKbx"),
+            "bpf_account_data_direct_mapping: re-keyed",
+        ),
+        (
+            solana_pubkey::pubkey!("8Cb77yHjPWe9wuWUfXeh6iszFGCDGNCoFk3tprViYHNm"),
+            "enable_loader_v4: re-keyed",
+        ),
+        (
+            solana_pubkey::pubkey!("LtHaSHHsUge7EWTPVrmpuexKz6uVHZXZL6cgJa7W7Zn"),
+            "accounts_lt_hash: re-keyed",
+        ),
+        (
+            solana_pubkey::pubkey!("FfgtauHUWKeXTzjXkua9Px4tNGBFHKZ9WaigM5VbbzFx"),
+            "remove_accounts_executable_flag_checks: re-keyed",
+        ),
+        (
+            solana_pubkey::pubkey!("3A9WtMU4aHuryD3VN7SFKdfXto8HStLb1Jj6HjkgfnGL"),
+            "drop_unchained_merkle_shreds: re-keyed",
+        ),
+        (
+            solana_pubkey::pubkey!("EbAhnReKK8Sf88CvAfAXbgKji8DV48rsp4q2sgHqgWef"),
+            "relax_intrabatch_account_locks: re-keyed",
+        ),
+    ]
+    .into_iter()
+    .collect()
+});
 
-pub mod libsecp256k1_fail_on_bad_count2 {
-    solana_pubkey::declare_id!("54KAoNiUERNoWWUhTWWwXgym94gzoXFVnHyQwPA18V9A");
-}
+/// Features that require other features to already be active, keyed by the
+/// dependent feature
+pub static FEATURE_DEPENDENCIES: LazyLock<AHashMap<Pubkey, Vec<Pubkey>>> = LazyLock::new(|| {
+    build_feature_dependencies([
+        (
+            add_set_compute_unit_price_ix::id(),
+            vec![add_compute_budget_program::id()],
+        ),
+        (
+            enable_request_heap_frame_ix::id(),
+            vec![add_compute_budget_program::id()],
+        ),
+        (
+            add_set_tx_loaded_accounts_data_size_instruction::id(),
+            vec![add_compute_budget_program::id()],
+        ),
+        (
+            chained_merkle_conflict_duplicate_proofs::id(),
+            vec![enable_chained_merkle_shreds::id()],
+        ),
+        (
+            drop_unchained_merkle_shreds::id(),
+            vec![enable_chained_merkle_shreds::id()],
+        ),
+        (snapshots_lt_hash::id(), vec![accounts_lt_hash::id()]),
+        (
+            remove_accounts_delta_hash::id(),
+            vec![accounts_lt_hash::id()],
+        ),
+    ])
+    .unwrap_or_else(|feature_id| panic!("feature dependency cycle involving {feature_id}"))
+});
 
-pub mod instructions_sysvar_owned_by_sysvar {
-    solana_pubkey::declare_id!("H3kBSaKdeiUsyHmeHqjJYNc27jesXZ6zWj3zWkowQbkV");
-}
+/// Build a dependency map from `(feature, dependencies)` entries. Returns a
+/// feature on the cycle if the dependencies are cyclic.
+fn build_feature_dependencies(
+    entries: impl IntoIterator<Item = (Pubkey, Vec<Pubkey>)>,
+) -> Result<AHashMap<Pubkey, Vec<Pubkey>>, Pubkey> {
+    fn visit(
+        feature_id: &Pubkey,
+        dependencies: &AHashMap<Pubkey, Vec<Pubkey>>,
+        visiting: &mut AHashSet<Pubkey>,
+        visited: &mut AHashSet<Pubkey>,
+    ) -> Result<(), Pubkey> {
+        if visited.contains(feature_id) {
+            return Ok(());
+        }
+        if !visiting.insert(*feature_id) {
+            return Err(*feature_id);
+        }
+        for dependency in dependencies.get(feature_id).into_iter().flatten() {
+            visit(dependency, dependencies, visiting, visited)?;
+        }
+        visiting.remove(feature_id);
+        visited.insert(*feature_id);
+        Ok(())
+    }
 
-pub mod stake_program_advance_activating_credits_observed {
-    solana_pubkey::declare_id!("SAdVFw3RZvzbo6DvySbSdBnHN4gkzSTH9dSxesyKKPj");
+    let dependencies = entries.into_iter().collect::<AHashMap<_, _>>();
+    let mut visiting = AHashSet::new();
+    let mut visited = AHashSet::new();
+    for feature_id in dependencies.keys() {
+        visit(feature_id, &dependencies, &mut visiting, &mut visited)?;
+    }
+    Ok(dependencies)
 }
 
-pub mod credits_auto_rewind {
-    solana_pubkey::declare_id!("BUS12ciZ5gCoFafUHWW8qaFMMtwFQGVxjsDheWLdqBE2");
-}
+/// Unique identifier of the current software's feature set
+pub static ID: LazyLock<Hash> = LazyLock::new(|| {
+    let mut hasher = Hasher::default();
+    let mut feature_ids = FEATURE_NAMES.keys().collect::<Vec<_>>();
+    feature_ids.sort();
+    for feature in feature_ids {
+        hasher.hash(feature.as_ref());
+    }
+    hasher.result()
+});
 
-pub mod demote_program_write_locks {
-    solana_pubkey::declare_id!("3E3jV7v9VcdJL8iYZUMax9DiDno8j7EWUVbhm9RtShj2");
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FullInflationFeaturePair {
+    pub name: &'static str, // Name of the candidate
+    pub vote_id: Pubkey, // Feature that grants the candidate the ability to enable full inflation
+    pub enable_id: Pubkey, // Feature to enable full inflation by the candidate
 }
 
-pub mod ed25519_program_enabled {
-    solana_pubkey::declare_id!("6ppMXNYLhVd7GcsZ5uV11wQEW7spppiMVfqQv5SXhDpX");
+/// Status of a full inflation candidate's feature pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FullInflationCandidateStatus {
+    pub name: &'static str,
+    pub vote_id: Pubkey,
+    pub enable_id: Pubkey,
+    pub vote_active: bool,
+    pub enable_active: bool,
+    /// Slot at which both features were active, i.e. the later of the two
+    /// activation slots
+    pub activation_slot: Option<u64>,
 }
 
-pub mod return_data_syscall_enabled {
-    solana_pubkey::declare_id!("DwScAzPUjuv65TMbDnFY7AgwmotzWy3xpEJMXM3hZFaB");
+impl FullInflationCandidateStatus {
+    /// Whether this candidate has enabled full inflation
+    pub fn is_enabled(&self) -> bool {
+        self.vote_active && self.enable_active
+    }
 }
 
-pub mod reduce_required_deploy_balance {
-    solana_pubkey::declare_id!("EBeznQDjcPG8491sFsKZYBi5S5jTVXMpAKNDJMQPS2kq");
-}
+/// Set of feature pairs, one per candidate, that once enabled will trigger
+/// full inflation
+pub static FULL_INFLATION_FEATURE_PAIRS: LazyLock<AHashSet<FullInflationFeaturePair>> =
+    LazyLock::new(|| {
+        [FullInflationFeaturePair {
+            name: "certusone",
+            vote_id: full_inflation::mainnet::certusone::vote::id(),
+            enable_id: full_inflation::mainnet::certusone::enable::id(),
+        }]
+        .iter()
+        .cloned()
+        .collect()
+    });
 
-pub mod sol_log_data_syscall_enabled {
-    solana_pubkey::declare_id!("6uaHcKPGUy4J7emLBgUTeufhJdiwhngW6a1R9B7c2ob9");
-}
+/// Features long active on mainnet-beta, used by
+/// `FeatureSetBuilder::mainnet_like()`. Only meant as a realistic starting
+/// point for tests: it is not kept in sync with the cluster.
+#[cfg(feature = "dev-context-only-utils")]
+pub const MAINNET_LIKE_FEATURES: &[Features] = &[
+    Features::DeprecateRewardsSysvar,
+    Features::PicoInflation,
+    Features::Secp256k1ProgramEnabled,
+    Features::SplTokenV2MultisigFix,
+    Features::NoOverflowRentDistribution,
+    Features::RequireCustodianForLockedStakeAuthorize,
+    Features::SplTokenV2SelfTransferFix,
+    Features::CheckInitVoteData,
+    Features::Secp256k1RecoverSyscallEnabled,
+    Features::SystemTransferZeroCheck,
+    Features::DedupeConfigProgramSigners,
+    Features::VerifyTxSignaturesLen,
+    Features::VoteStakeCheckedInstructions,
+    Features::RentForSysvars,
+    Features::TxWideComputeCap,
+    Features::SplTokenV2SetAuthorityFix,
+    Features::MergeNonceErrorIntoSystemError,
+    Features::DisableFeesSysvar,
+    Features::VersionedTxMessageEnabled,
+    Features::Ed25519ProgramEnabled,
+    Features::ReturnDataSyscallEnabled,
+    Features::SolLogDataSyscallEnabled,
+    Features::AddComputeBudgetProgram,
+    Features::NonceMustBeWritable,
+    Features::AllowVotesToDirectlyUpdateVoteState,
+];
 
-pub mod stakes_remove_delegation_if_inactive {
-    solana_pubkey::declare_id!("HFpdDDNQjvcXnXKec697HDDsyk6tFoWS2o8fkxuhQZpL");
-}
+#[cfg(test)]
+mod test {
+    use {super::*, solana_pubkey::pubkey};
 
-pub mod do_support_realloc {
-    solana_pubkey::declare_id!("75m6ysz33AfLA5DDEzWM1obBrnPQRSsdVQ2nRmc8Vuu1");
-}
+    #[test]
+    fn test_full_inflation_features_enabled_devnet_and_testnet() {
+        let mut feature_set = FeatureSet::default();
+        assert!(feature_set.full_inflation_features_enabled().is_empty());
+        feature_set
+            .active
+            .insert(full_inflation::devnet_and_testnet::id(), 42);
+        assert_eq!(
+            feature_set.full_inflation_features_enabled(),
+            [full_inflation::devnet_and_testnet::id()]
+                .iter()
+                .cloned()
+                .collect()
+        );
+    }
 
-pub mod prevent_calling_precompiles_as_programs {
-    solana_pubkey::declare_id!("4ApgRX3ud6p7LNMJmsuaAcZY5HWctGPr5obAsjB3A54d");
-}
+    #[test]
+    fn test_full_inflation_features_enabled() {
+        // Normal sequence: vote_id then enable_id
+        let mut feature_set = FeatureSet::default();
+        assert!(feature_set.full_inflation_features_enabled().is_empty());
+        feature_set
+            .active
+            .insert(full_inflation::mainnet::certusone::vote::id(), 42);
+        assert!(feature_set.full_inflation_features_enabled().is_empty());
+        feature_set
+            .active
+            .insert(full_inflation::mainnet::certusone::enable::id(), 42);
+        assert_eq!(
+            feature_set.full_inflation_features_enabled(),
+            [full_inflation::mainnet::certusone::enable::id()]
+                .iter()
+                .cloned()
+                .collect()
+        );
 
-pub mod optimize_epoch_boundary_updates {
-    solana_pubkey::declare_id!("265hPS8k8xJ37ot82KEgjRunsUp5w4n4Q4VwwiN9i9ps");
-}
+        // Backwards sequence: enable_id and then vote_id
+        let mut feature_set = FeatureSet::default();
+        assert!(feature_set.full_inflation_features_enabled().is_empty());
+        feature_set
+            .active
+            .insert(full_inflation::mainnet::certusone::enable::id(), 42);
+        assert!(feature_set.full_inflation_features_enabled().is_empty());
+        feature_set
+            .active
+            .insert(full_inflation::mainnet::certusone::vote::id(), 42);
+        assert_eq!(
+            feature_set.full_inflation_features_enabled(),
+            [full_inflation::mainnet::certusone::enable::id()]
+                .iter()
+                .cloned()
+                .collect()
+        );
+    }
 
-pub mod remove_native_loader {
-    solana_pubkey::declare_id!("HTTgmruMYRZEntyL3EdCDdnS6e4D5wRq1FA7kQsb66qq");
-}
+    #[test]
+    fn test_activated_in_range() {
+        let mut feature_set = FeatureSet::default();
+        assert!(feature_set.activated_in_range(0..=u64::MAX).is_empty());
 
-pub mod send_to_tpu_vote_port {
-    solana_pubkey::declare_id!("C5fh68nJ7uyKAuYZg2x9sEQ5YrVf3dkW6oojNBSc3Jvo");
-}
+        feature_set.activate(&pico_inflation::id(), 0);
+        feature_set.activate(&full_inflation::devnet_and_testnet::id(), 42);
+        feature_set.activate(&secp256k1_program_enabled::id(), 42);
+        feature_set.activate(&spl_token_v2_multisig_fix::id(), 100);
 
-pub mod requestable_heap_size {
-    solana_pubkey::declare_id!("CCu4boMmfLuqcmfTLPHQiUo22ZdUsXjgzPAURYaWt1Bw");
-}
+        // Genesis activations are included
+        assert_eq!(
+            feature_set.activated_in_range(0..=0),
+            vec![(pico_inflation::id(), 0)]
+        );
 
-pub mod disable_fee_calculator {
-    solana_pubkey::declare_id!("2jXx2yDmGysmBKfKYNgLj2DQyAQv6mMk2BPh4eSbyB4H");
-}
+        // Overlapping activations at the same slot are ordered by pubkey
+        let mut expected = vec![
+            (full_inflation::devnet_and_testnet::id(), 42),
+            (secp256k1_program_enabled::id(), 42),
+        ];
+        expected.sort_unstable();
+        assert_eq!(feature_set.activated_in_range(1..=99), expected);
+
+        let mut all = vec![(pico_inflation::id(), 0)];
+        all.extend(expected.iter().cloned());
+        all.push((spl_token_v2_multisig_fix::id(), 100));
+        assert_eq!(feature_set.activated_in_range(0..=100), all);
+
+        // Ranges spanning no activations
+        assert!(feature_set.activated_in_range(43..=99).is_empty());
+        assert!(feature_set.activated_in_range(101..=u64::MAX).is_empty());
+        // Empty range
+        let (start, end) = (100, 42);
+        assert!(feature_set.activated_in_range(start..=end).is_empty());
+    }
 
-pub mod add_compute_budget_program {
-    solana_pubkey::declare_id!("4d5AKtxoh93Dwm1vHXUU3iRATuMndx1c431KgT2td52r");
-}
+    #[test]
+    fn test_activated_at() {
+        let mut feature_set = FeatureSet::default();
+        feature_set.activate(&pico_inflation::id(), 0);
+        feature_set.activate(&full_inflation::devnet_and_testnet::id(), 42);
+        feature_set.activate(&secp256k1_program_enabled::id(), 42);
+
+        assert_eq!(feature_set.activated_at(0), vec![pico_inflation::id()]);
+        let mut expected = vec![
+            full_inflation::devnet_and_testnet::id(),
+            secp256k1_program_enabled::id(),
+        ];
+        expected.sort_unstable();
+        assert_eq!(feature_set.activated_at(42), expected);
+        assert!(feature_set.activated_at(41).is_empty());
+    }
 
-pub mod nonce_must_be_writable {
-    solana_pubkey::declare_id!("BiCU7M5w8ZCMykVSyhZ7Q3m2SWoR2qrEQ86ERcDX77ME");
-}
+    fn mixed_feature_set() -> FeatureSet {
+        let mut feature_set = FeatureSet::default();
+        feature_set.activate(&pico_inflation::id(), 0);
+        feature_set.activate(&full_inflation::devnet_and_testnet::id(), 42);
+        feature_set.activate(&secp256k1_program_enabled::id(), 100);
+        feature_set
+    }
 
-pub mod spl_token_v3_3_0_release {
-    solana_pubkey::declare_id!("Ftok2jhqAqxUWEiCVRrfRs9DPppWP8cgTB7NQNKL88mS");
-}
+    #[test]
+    fn test_serde_roundtrip() {
+        for feature_set in [
+            FeatureSet::default(),
+            FeatureSet::all_enabled(),
+            mixed_feature_set(),
+        ] {
+            let bytes = bincode::serialize(&feature_set).unwrap();
+            let deserialized: FeatureSet = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(deserialized, feature_set);
+            // Serialized form is deterministic
+            assert_eq!(bincode::serialize(&deserialized).unwrap(), bytes);
+
+            let json = serde_json::to_string(&feature_set).unwrap();
+            let deserialized: FeatureSet = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized, feature_set);
+        }
+    }
 
-pub mod leave_nonce_on_success {
-    solana_pubkey::declare_id!("E8MkiWZNNPGU6n55jkGzyj8ghUmjCHRmDFdYYFYHxWhQ");
-}
+    #[test]
+    fn test_deserialize_reconstructs_inactive() {
+        // A payload missing some known features still covers all of them
+        let serialized = SerializedFeatureSet {
+            active: vec![(pico_inflation::id(), 0)],
+            inactive: vec![],
+            unknown: vec![],
+            retired: vec![],
+            pending: vec![],
+        };
+        let bytes = bincode::serialize(&serialized).unwrap();
+        let feature_set: FeatureSet = bincode::deserialize(&bytes).unwrap();
+        assert!(feature_set.is_active(&pico_inflation::id()));
+        assert_eq!(feature_set.inactive().len(), FEATURE_NAMES.len() - 1);
+        assert!(feature_set.unknown().is_empty());
+    }
 
-pub mod reject_empty_instruction_without_program {
-    solana_pubkey::declare_id!("9kdtFSrXHQg3hKkbXkQ6trJ3Ja1xpJ22CTFSNAciEwmL");
-}
+    #[test]
+    fn test_deserialize_rejects_active_and_inactive() {
+        let serialized = SerializedFeatureSet {
+            active: vec![(pico_inflation::id(), 0)],
+            inactive: vec![pico_inflation::id()],
+            unknown: vec![],
+            retired: vec![],
+            pending: vec![],
+        };
+        let bytes = bincode::serialize(&serialized).unwrap();
+        assert!(bincode::deserialize::<FeatureSet>(&bytes).is_err());
+        let json = serde_json::to_string(&serialized).unwrap();
+        assert!(serde_json::from_str::<FeatureSet>(&json).is_err());
+    }
 
-pub mod fixed_memcpy_nonoverlapping_check {
-    solana_pubkey::declare_id!("36PRUK2Dz6HWYdG9SpjeAsF5F3KxnFCakA2BZMbtMhSb");
-}
+    #[test]
+    fn test_deserialize_unknown_features() {
+        // Simulate a payload produced by newer software with extra features
+        let new_active_feature = Pubkey::new_unique();
+        let new_inactive_feature = Pubkey::new_unique();
+        let feature_set = mixed_feature_set();
+        let mut serialized: SerializedFeatureSet =
+            serde_json::from_str(&serde_json::to_string(&feature_set).unwrap()).unwrap();
+        serialized.active.push((new_active_feature, 7));
+        serialized.inactive.push(new_inactive_feature);
+
+        let json = serde_json::to_string(&serialized).unwrap();
+        let deserialized: FeatureSet = serde_json::from_str(&json).unwrap();
+        assert!(!deserialized.is_active(&new_active_feature));
+        assert!(!deserialized.inactive().contains(&new_inactive_feature));
+        assert_eq!(
+            deserialized.unknown(),
+            &AHashSet::from_iter([new_active_feature, new_inactive_feature])
+        );
+        assert_eq!(deserialized.active(), feature_set.active());
+        assert_eq!(deserialized.inactive(), feature_set.inactive());
 
-pub mod reject_non_rent_exempt_vote_withdraws {
-    solana_pubkey::declare_id!("7txXZZD6Um59YoLMF7XUNimbMjsqsWhc7g2EniiTrmp1");
-}
+        // Unknown features survive a round trip
+        let bytes = bincode::serialize(&deserialized).unwrap();
+        assert_eq!(
+            bincode::deserialize::<FeatureSet>(&bytes).unwrap(),
+            deserialized
+        );
+    }
 
-pub mod evict_invalid_stakes_cache_entries {
-    solana_pubkey::declare_id!("EMX9Q7TVFAmQ9V1CggAkhMzhXSg8ECp7fHrWQX2G1chf");
-}
+    #[test]
+    fn test_diff_identical() {
+        let feature_set = mixed_feature_set();
+        let diff = feature_set.diff(&feature_set.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff, FeatureSetDiff::default());
+        assert_eq!(diff.to_string(), "feature sets are identical");
+        assert!(FeatureSet::default()
+            .diff(&FeatureSet::default())
+            .is_empty());
+    }
 
-pub mod allow_votes_to_directly_update_vote_state {
-    solana_pubkey::declare_id!("Ff8b1fBeB86q8cjq47ZhsQLgv5EkHu3G1C99zjUfAzrq");
-}
+    #[test]
+    fn test_diff_disjoint() {
+        let mut feature_set = FeatureSet::default();
+        feature_set.activate(&pico_inflation::id(), 1);
+        let mut other = FeatureSet::default();
+        other.activate(&secp256k1_program_enabled::id(), 2);
+        let unknown_feature = Pubkey::new_unique();
+        other.activate(&unknown_feature, 3);
+
+        let diff = feature_set.diff(&other);
+        assert_eq!(diff.active_only_in_self, vec![(pico_inflation::id(), 1)]);
+        let mut expected = vec![(secp256k1_program_enabled::id(), 2), (unknown_feature, 3)];
+        expected.sort_unstable();
+        assert_eq!(diff.active_only_in_other, expected);
+        assert!(diff.slot_mismatches.is_empty());
+
+        let display = diff.to_string();
+        assert!(display.contains(&format!(
+            "{} ({}) at slot 1",
+            pico_inflation::id(),
+            FEATURE_NAMES[&pico_inflation::id()]
+        )));
+        assert!(display.contains(&format!("  {unknown_feature} at slot 3")));
+
+        // Diff is symmetric
+        let reverse = other.diff(&feature_set);
+        assert_eq!(reverse.active_only_in_self, diff.active_only_in_other);
+        assert_eq!(reverse.active_only_in_other, diff.active_only_in_self);
+    }
 
-pub mod max_tx_account_locks {
-    solana_pubkey::declare_id!("CBkDroRDqm8HwHe6ak9cguPjUomrASEkfmxEaZ5CNNxz");
-}
+    #[test]
+    fn test_same_features() {
+        let feature_set = mixed_feature_set();
+        let mut other = feature_set.clone();
+        assert_eq!(feature_set, other);
+        assert!(feature_set.same_features(&other));
+
+        // Activated at another slot
+        let (&feature_id, &slot) = feature_set.active().iter().next().unwrap();
+        other.active_mut().insert(feature_id, slot + 1);
+        assert_ne!(feature_set, other);
+        assert!(feature_set.same_features(&other));
+        assert!(other.same_features(&feature_set));
+        assert!(feature_set.symmetric_difference(&other).is_empty());
+
+        // Not active at all
+        other.deactivate(&feature_id);
+        assert!(!feature_set.same_features(&other));
+        assert!(!other.same_features(&feature_set));
+        assert_eq!(feature_set.symmetric_difference(&other), vec![feature_id]);
+
+        // Neither active nor inactive
+        let mut other = feature_set.clone();
+        let inactive_feature = *feature_set.inactive().iter().next().unwrap();
+        other.inactive_mut().remove(&inactive_feature);
+        assert!(!feature_set.same_features(&other));
+        assert!(feature_set.symmetric_difference(&other).is_empty());
+    }
 
-pub mod require_rent_exempt_accounts {
-    solana_pubkey::declare_id!("BkFDxiJQWZXGTZaJQxH7wVEHkAmwCgSEVkrvswFfRJPD");
-}
+    #[test]
+    fn test_symmetric_difference_disjoint() {
+        let mut feature_set = FeatureSet::default();
+        feature_set.activate(&pico_inflation::id(), 1);
+        let mut other = FeatureSet::default();
+        other.activate(&secp256k1_program_enabled::id(), 2);
+        let unknown_feature = Pubkey::new_unique();
+        other.activate(&unknown_feature, 3);
+
+        let mut expected = vec![
+            pico_inflation::id(),
+            secp256k1_program_enabled::id(),
+            unknown_feature,
+        ];
+        expected.sort_unstable();
+        assert_eq!(feature_set.symmetric_difference(&other), expected);
+        assert_eq!(other.symmetric_difference(&feature_set), expected);
+        assert!(!feature_set.same_features(&other));
+        assert!(feature_set
+            .symmetric_difference(&feature_set.clone())
+            .is_empty());
+    }
 
-pub mod filter_votes_outside_slot_hashes {
-    solana_pubkey::declare_id!("3gtZPqvPpsbXZVCx6hceMfWxtsmrjMzmg8C7PLKSxS2d");
-}
+    #[test]
+    fn test_unknown_active() {
+        let feature_set = FeatureSet::default();
+        let unknown_feature = Pubkey::new_unique();
+        let retired_feature = *RETIRED_FEATURES.keys().next().unwrap();
+        let cluster_active = HashMap::from([
+            (pico_inflation::id(), 1),
+            (unknown_feature, 2),
+            (retired_feature, 3),
+        ]);
+        assert_eq!(
+            feature_set.unknown_active(&cluster_active),
+            vec![(unknown_feature, 2)]
+        );
+        assert!(feature_set.unknown_active(&HashMap::new()).is_empty());
+    }
 
-pub mod update_syscall_base_costs {
-    solana_pubkey::declare_id!("2h63t332mGCCsWK2nqqqHhN4U9ayyqhLVFvczznHDoTZ");
-}
+    #[test]
+    fn test_merge_cluster_state() {
+        let mut feature_set = FeatureSet::default();
+        feature_set.activate(&pico_inflation::id(), 10);
+        feature_set.activate(&secp256k1_program_enabled::id(), 10);
+        feature_set.stage(&full_inflation::devnet_and_testnet::id(), 5);
+        let unknown_feature = Pubkey::new_unique();
+        let retired_feature = *RETIRED_FEATURES.keys().next().unwrap();
+        let cluster_active = HashMap::from([
+            // Later than the local slot, which is kept
+            (pico_inflation::id(), 20),
+            // Earlier than the local slot, which is replaced
+            (secp256k1_program_enabled::id(), 5),
+            (full_inflation::devnet_and_testnet::id(), 7),
+            (unknown_feature, 8),
+            (retired_feature, 9),
+        ]);
+
+        let report = feature_set.merge_cluster_state(&cluster_active);
+        let mut activated = vec![
+            (secp256k1_program_enabled::id(), 5),
+            (full_inflation::devnet_and_testnet::id(), 7),
+        ];
+        activated.sort_unstable();
+        let mut slot_mismatches = vec![
+            (pico_inflation::id(), 10, 20),
+            (secp256k1_program_enabled::id(), 10, 5),
+        ];
+        slot_mismatches.sort_unstable();
+        assert_eq!(
+            report,
+            MergeReport {
+                activated,
+                slot_mismatches,
+                unknown: vec![(unknown_feature, 8)],
+            }
+        );
+        assert!(report.has_unknown());
+        assert_eq!(feature_set.activated_slot(&pico_inflation::id()), Some(10));
+        assert_eq!(
+            feature_set.activated_slot(&secp256k1_program_enabled::id()),
+            Some(5)
+        );
+        assert_eq!(
+            feature_set.activated_slot(&full_inflation::devnet_and_testnet::id()),
+            Some(7)
+        );
+        assert!(!feature_set.is_pending(&full_inflation::devnet_and_testnet::id()));
+        assert!(!feature_set.is_active(&unknown_feature));
+        assert!(feature_set.unknown().contains(&unknown_feature));
+        assert!(!feature_set.is_active(&retired_feature));
+        assert!(feature_set.retired().contains(&retired_feature));
+
+        // Merging again changes nothing
+        let expected = feature_set.clone();
+        let report = feature_set.merge_cluster_state(&cluster_active);
+        assert!(report.activated.is_empty());
+        assert_eq!(report.slot_mismatches, vec![(pico_inflation::id(), 10, 20)]);
+        assert_eq!(feature_set, expected);
+    }
 
-pub mod stake_deactivate_delinquent_instruction {
-    solana_pubkey::declare_id!("437r62HoAdUb63amq3D7ENnBLDhHT2xY8eFkLJYVKK4x");
-}
+    #[test]
+    fn test_merge_empty_cluster_state() {
+        let mut feature_set = mixed_feature_set();
+        let expected = feature_set.clone();
+        let report = feature_set.merge_cluster_state(&HashMap::new());
+        assert_eq!(report, MergeReport::default());
+        assert!(!report.has_unknown());
+        assert_eq!(feature_set, expected);
+    }
 
-pub mod vote_withdraw_authority_may_change_authorized_voter {
-    solana_pubkey::declare_id!("AVZS3ZsN4gi6Rkx2QUibYuSJG3S6QHib7xCYhG6vGJxU");
-}
+    #[test]
+    fn test_diff_slot_mismatch() {
+        let feature_set = mixed_feature_set();
+        let mut other = feature_set.clone();
+        other.activate(&secp256k1_program_enabled::id(), 101);
+
+        let diff = feature_set.diff(&other);
+        assert!(diff.active_only_in_self.is_empty());
+        assert!(diff.active_only_in_other.is_empty());
+        assert_eq!(
+            diff.slot_mismatches,
+            vec![(secp256k1_program_enabled::id(), 100, 101)]
+        );
+        assert!(diff
+            .to_string()
+            .contains("at slot 100 in self, 101 in other"));
+    }
 
-pub mod spl_associated_token_account_v1_0_4 {
-    solana_pubkey::declare_id!("FaTa4SpiaSNH44PGC4z8bnGVTkSRYaWvrBs3KTu8XQQq");
-}
+    #[test]
+    fn test_feature_descriptors() {
+        let ids = feature_descriptors()
+            .map(|descriptor| descriptor.id)
+            .collect::<AHashSet<_>>();
+        assert_eq!(ids.len(), FEATURE_NAMES.len());
+        assert!(ids.iter().all(|id| FEATURE_NAMES.contains_key(id)));
 
-pub mod reject_vote_account_close_unless_zero_credit_epoch {
-    solana_pubkey::declare_id!("ALBk3EWdeAg2WAGf6GPDUf1nynyNqCdEVmgouG7rpuCj");
-}
+        // `create_slashing_program` and `enshrine_slashing_program` share an
+        // id, in which case the later declaration's description is used
+        assert_eq!(
+            feature_descriptors()
+                .filter(|descriptor| descriptor.id == enshrine_slashing_program::id())
+                .count(),
+            2
+        );
+        assert_eq!(
+            FEATURE_NAMES[&enshrine_slashing_program::id()],
+            "SIMD-0204: Slashable event verification"
+        );
 
-pub mod add_get_processed_sibling_instruction_syscall {
-    solana_pubkey::declare_id!("CFK1hRCNy8JJuAAY8Pb2GjLFNdCThS2qwZNe3izzBMgn");
-}
+        assert_eq!(
+            feature_descriptors()
+                .find(|descriptor| descriptor.id == blake3_syscall_enabled::id())
+                .unwrap()
+                .kind,
+            FeatureKind::Syscall
+        );
+    }
 
-pub mod bank_transaction_count_fix {
-    solana_pubkey::declare_id!("Vo5siZ442SaZBKPXNocthiXysNviW4UYPwRFggmbgAp");
-}
+    #[test]
+    fn test_id_unchanged() {
+        // Restructuring the feature declarations must not change the id
+        assert_eq!(
+            ID.to_string(),
+            "6wxBNz2iJC7pWXpikSkomyJs2r8YygcM7bJqTWhpihhc"
+        );
+    }
 
-pub mod disable_bpf_deprecated_load_instructions {
-    solana_pubkey::declare_id!("3XgNukcZWf9o3HdA3fpJbm94XFc4qpvTXc8h1wxYwiPi");
-}
+    #[test]
+    fn test_is_active_at() {
+        let feature_set = mixed_feature_set();
+        // Genesis features are always active
+        assert!(feature_set.is_active_at(&pico_inflation::id(), 0));
+        assert!(feature_set.is_active_at(&pico_inflation::id(), u64::MAX));
+
+        let feature_id = full_inflation::devnet_and_testnet::id();
+        assert!(!feature_set.is_active_at(&feature_id, 41));
+        assert!(feature_set.is_active_at(&feature_id, 42));
+        assert!(feature_set.is_active_at(&feature_id, 43));
+
+        assert!(!feature_set.is_active_at(&spl_token_v2_multisig_fix::id(), u64::MAX));
+    }
 
-pub mod disable_bpf_unresolved_symbols_at_runtime {
-    solana_pubkey::declare_id!("4yuaYAj2jGMGTh1sSmi4G2eFscsDq8qjugJXZoBN6YEa");
-}
+    #[test]
+    fn test_active_at() {
+        let feature_set = mixed_feature_set();
 
-pub mod record_instruction_in_transaction_context_push {
-    solana_pubkey::declare_id!("3aJdcZqxoLpSBxgeYGjPwaYS1zzcByxUDqJkbzWAH1Zb");
-}
+        let at_genesis = feature_set.active_at(0);
+        assert_eq!(at_genesis.active().len(), 1);
+        assert!(at_genesis.is_active(&pico_inflation::id()));
+        assert_eq!(at_genesis.inactive().len(), FEATURE_NAMES.len() - 1);
 
-pub mod syscall_saturated_math {
-    solana_pubkey::declare_id!("HyrbKftCdJ5CrUfEti6x26Cj7rZLNe32weugk7tLcWb8");
-}
+        let at_42 = feature_set.active_at(42);
+        assert!(at_42.is_active(&full_inflation::devnet_and_testnet::id()));
+        assert!(!at_42.is_active(&secp256k1_program_enabled::id()));
+        assert!(at_42.inactive().contains(&secp256k1_program_enabled::id()));
 
-pub mod check_physical_overlapping {
-    solana_pubkey::declare_id!("nWBqjr3gpETbiaVj3CBJ3HFC5TMdnJDGt21hnvSTvVZ");
-}
+        assert_eq!(feature_set.active_at(100), feature_set);
+        assert_eq!(feature_set.active_at(u64::MAX), feature_set);
+    }
 
-pub mod limit_secp256k1_recovery_id {
-    solana_pubkey::declare_id!("7g9EUwj4j7CS21Yx1wvgWLjSZeh5aPq8x9kpoPwXM8n8");
-}
+    #[test]
+    fn test_active_at_hashes_differently() {
+        fn hash_feature_set(feature_set: &FeatureSet) -> Hash {
+            let mut hasher = Hasher::default();
+            hasher.hash(&bincode::serialize(feature_set).unwrap());
+            hasher.result()
+        }
 
-pub mod disable_deprecated_loader {
-    solana_pubkey::declare_id!("GTUMCZ8LTNxVfxdrw7ZsDFTxXb7TutYkzJnFwinpE6dg");
-}
+        let feature_set = mixed_feature_set();
+        let full_hash = hash_feature_set(&feature_set);
+        assert_ne!(hash_feature_set(&feature_set.active_at(42)), full_hash);
+        assert_eq!(hash_feature_set(&feature_set.active_at(100)), full_hash);
+    }
 
-pub mod check_slice_translation_size {
-    solana_pubkey::declare_id!("GmC19j9qLn2RFk5NduX6QXaDhVpGncVVBzyM8e9WMz2F");
-}
+    #[test]
+    fn test_feature_dependencies() {
+        // Declared dependencies are acyclic and refer to known features
+        for (feature_id, dependencies) in FEATURE_DEPENDENCIES.iter() {
+            assert!(FEATURE_NAMES.contains_key(feature_id));
+            assert!(dependencies
+                .iter()
+                .all(|dependency| FEATURE_NAMES.contains_key(dependency)));
+        }
+        assert!(FeatureSet::all_enabled().validate().is_empty());
+        assert!(FeatureSet::default().validate().is_empty());
+    }
 
-pub mod stake_split_uses_rent_sysvar {
-    solana_pubkey::declare_id!("FQnc7U4koHqWgRvFaBJjZnV8VPg6L6wWK33yJeDp4yvV");
-}
+    #[test]
+    fn test_try_activate() {
+        let mut feature_set = FeatureSet::default();
+        assert_eq!(
+            feature_set.try_activate(&add_set_compute_unit_price_ix::id(), 1),
+            Err(FeatureActivationError::InactiveDependency {
+                feature_id: add_set_compute_unit_price_ix::id(),
+                dependency: add_compute_budget_program::id(),
+            })
+        );
+        assert!(!feature_set.is_active(&add_set_compute_unit_price_ix::id()));
 
-pub mod add_get_minimum_delegation_instruction_to_stake_program {
-    solana_pubkey::declare_id!("St8k9dVXP97xT6faW24YmRSYConLbhsMJA4TJTBLmMT");
-}
+        feature_set
+            .try_activate(&add_compute_budget_program::id(), 1)
+            .unwrap();
+        feature_set
+            .try_activate(&add_set_compute_unit_price_ix::id(), 2)
+            .unwrap();
+        assert_eq!(
+            feature_set.activated_slot(&add_set_compute_unit_price_ix::id()),
+            Some(2)
+        );
+    }
 
-pub mod error_on_syscall_bpf_function_hash_collisions {
-    solana_pubkey::declare_id!("8199Q2gMD2kwgfopK5qqVWuDbegLgpuFUFHCcUJQDN8b");
-}
+    #[test]
+    fn test_try_activate_chained_dependencies() {
+        let (a, b, c) = (
+            pico_inflation::id(),
+            secp256k1_program_enabled::id(),
+            spl_token_v2_multisig_fix::id(),
+        );
+        // a depends on b, which depends on c
+        let dependencies = build_feature_dependencies([(a, vec![b]), (b, vec![c])]).unwrap();
 
-pub mod reject_callx_r10 {
-    solana_pubkey::declare_id!("3NKRSwpySNwD3TvP5pHnRmkAQRsdkXWRr1WaQh8p4PWX");
-}
+        let mut feature_set = FeatureSet::default();
+        assert_eq!(
+            feature_set.try_activate_with_dependencies(&a, 0, &dependencies),
+            Err(FeatureActivationError::InactiveDependency {
+                feature_id: a,
+                dependency: b,
+            })
+        );
+        assert_eq!(
+            feature_set.try_activate_with_dependencies(&b, 0, &dependencies),
+            Err(FeatureActivationError::InactiveDependency {
+                feature_id: b,
+                dependency: c,
+            })
+        );
+        feature_set
+            .try_activate_with_dependencies(&c, 0, &dependencies)
+            .unwrap();
+        feature_set
+            .try_activate_with_dependencies(&b, 1, &dependencies)
+            .unwrap();
+        feature_set
+            .try_activate_with_dependencies(&a, 2, &dependencies)
+            .unwrap();
 
-pub mod drop_redundant_turbine_path {
-    solana_pubkey::declare_id!("4Di3y24QFLt5QEUPZtbnjyfQKfm6ZMTfa6Dw1psfoMKU");
-}
+        // Deactivating the root of the chain violates the next link only
+        feature_set.deactivate(&c);
+        assert_eq!(
+            feature_set.validate_with_dependencies(&dependencies),
+            vec![DependencyViolation {
+                feature_id: b,
+                missing_dependency: c,
+            }]
+        );
+    }
 
-pub mod executables_incur_cpi_data_cost {
-    solana_pubkey::declare_id!("7GUcYgq4tVtaqNCKT3dho9r4665Qp5TxCZ27Qgjx3829");
-}
+    #[test]
+    fn test_feature_dependency_cycles_rejected() {
+        let (a, b, c) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        assert!(build_feature_dependencies([(a, vec![a])]).is_err());
+        assert!(build_feature_dependencies([(a, vec![b]), (b, vec![a])]).is_err());
+        assert!(build_feature_dependencies([(a, vec![b]), (b, vec![c]), (c, vec![a])]).is_err());
+        // Diamonds are not cycles
+        assert!(build_feature_dependencies([(a, vec![b, c]), (b, vec![c]),]).is_ok());
+    }
 
-pub mod fix_recent_blockhashes {
-    solana_pubkey::declare_id!("6iyggb5MTcsvdcugX7bEKbHV8c6jdLbpHwkncrgLMhfo");
-}
+    #[test]
+    fn test_retired_features() {
+        let retired_id = pubkey!("9bn2vTJUsUcnpiZWbu2woSKtTGW3ErZC9ERv88SDqQjK");
+        assert!(RETIRED_FEATURES.contains_key(&retired_id));
+        for retired_id in RETIRED_FEATURES.keys() {
+            assert!(!FEATURE_NAMES.contains_key(retired_id));
+            assert!(!FeatureSet::default().inactive().contains(retired_id));
+            assert!(!FeatureSet::all_enabled().is_active(retired_id));
+        }
 
-pub mod update_rewards_from_cached_accounts {
-    solana_pubkey::declare_id!("28s7i3htzhahXQKqmS2ExzbEoUypg9krwvtK2M9UWXh9");
-}
+        // Retired ids do not contribute to the feature set id
+        let mut hasher = Hasher::default();
+        let mut feature_ids = FEATURE_NAMES
+            .keys()
+            .chain(RETIRED_FEATURES.keys())
+            .collect::<Vec<_>>();
+        feature_ids.sort();
+        for feature in feature_ids {
+            hasher.hash(feature.as_ref());
+        }
+        assert_ne!(hasher.result(), *ID);
+    }
 
-pub mod partitioned_epoch_rewards_superfeature {
-    solana_pubkey::declare_id!("PERzQrt5gBD1XEe2c9XdFWqwgHY3mr7cYWbm5V772V8");
-}
+    #[test]
+    fn test_classify() {
+        let feature_set = mixed_feature_set();
+        assert_eq!(
+            feature_set.classify(&full_inflation::devnet_and_testnet::id()),
+            FeatureStatus::Active(42)
+        );
+        assert_eq!(
+            feature_set.classify(&spl_token_v2_multisig_fix::id()),
+            FeatureStatus::Inactive
+        );
+        let retired_id = pubkey!("9bn2vTJUsUcnpiZWbu2woSKtTGW3ErZC9ERv88SDqQjK");
+        assert_eq!(
+            feature_set.classify(&retired_id),
+            FeatureStatus::Retired(RETIRED_FEATURES[&retired_id])
+        );
+        assert_eq!(
+            feature_set.classify(&Pubkey::new_unique()),
+            FeatureStatus::Unknown
+        );
+    }
 
-pub mod spl_token_v3_4_0 {
-    solana_pubkey::declare_id!("Ftok4njE8b7tDffYkC5bAbCaQv5sL6jispYrprzatUwN");
-}
+    #[test]
+    fn test_deserialize_retired_features() {
+        let retired_id = pubkey!("9bn2vTJUsUcnpiZWbu2woSKtTGW3ErZC9ERv88SDqQjK");
+        let other_retired_id = pubkey!("AjX3A4Nv2rzUuATEUWLP4rrBaBropyUnHxEvFDj1dKbx");
+        let unknown_id = Pubkey::new_unique();
+        let serialized = SerializedFeatureSet {
+            active: vec![(pico_inflation::id(), 0), (retired_id, 5), (unknown_id, 6)],
+            inactive: vec![other_retired_id],
+            unknown: vec![],
+            retired: vec![],
+            pending: vec![],
+        };
+        let bytes = bincode::serialize(&serialized).unwrap();
+        let feature_set: FeatureSet = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(
+            feature_set.retired(),
+            &AHashSet::from_iter([retired_id, other_retired_id])
+        );
+        assert_eq!(feature_set.unknown(), &AHashSet::from_iter([unknown_id]));
+        assert!(!feature_set.is_active(&retired_id));
+        assert!(!feature_set.inactive().contains(&other_retired_id));
 
-pub mod spl_associated_token_account_v1_1_0 {
-    solana_pubkey::declare_id!("FaTa17gVKoqbh38HcfiQonPsAaQViyDCCSg71AubYZw8");
-}
-
-pub mod default_units_per_instruction {
-    solana_pubkey::declare_id!("J2QdYx8crLbTVK8nur1jeLsmc3krDbfjoxoea2V1Uy5Q");
-}
-
-pub mod stake_allow_zero_undelegated_amount {
-    solana_pubkey::declare_id!("sTKz343FM8mqtyGvYWvbLpTThw3ixRM4Xk8QvZ985mw");
-}
-
-pub mod require_static_program_ids_in_transaction {
-    solana_pubkey::declare_id!("8FdwgyHFEjhAdjWfV2vfqk7wA1g9X3fQpKH7SBpEv3kC");
-}
-
-pub mod stake_raise_minimum_delegation_to_1_sol {
-    // This is a feature-proposal *feature id*.  The feature keypair address is `GQXzC7YiSNkje6FFUk6sc2p53XRvKoaZ9VMktYzUMnpL`.
-    solana_pubkey::declare_id!("9onWzzvCzNC2jfhxxeqRgs5q7nFAAKpCUvkj6T6GJK9i");
-}
-
-pub mod stake_minimum_delegation_for_rewards {
-    solana_pubkey::declare_id!("G6ANXD6ptCSyNd9znZm7j4dEczAJCfx7Cy43oBx3rKHJ");
-}
-
-pub mod add_set_compute_unit_price_ix {
-    solana_pubkey::declare_id!("98std1NSHqXi9WYvFShfVepRdCoq1qvsp8fsR2XZtG8g");
-}
-
-pub mod disable_deploy_of_alloc_free_syscall {
-    solana_pubkey::declare_id!("79HWsX9rpnnJBPcdNURVqygpMAfxdrAirzAGAVmf92im");
-}
-
-pub mod include_account_index_in_rent_error {
-    solana_pubkey::declare_id!("2R72wpcQ7qV7aTJWUumdn8u5wmmTyXbK7qzEy7YSAgyY");
-}
-
-pub mod add_shred_type_to_shred_seed {
-    solana_pubkey::declare_id!("Ds87KVeqhbv7Jw8W6avsS1mqz3Mw5J3pRTpPoDQ2QdiJ");
-}
-
-pub mod warp_timestamp_with_a_vengeance {
-    solana_pubkey::declare_id!("3BX6SBeEBibHaVQXywdkcgyUk6evfYZkHdztXiDtEpFS");
-}
-
-pub mod separate_nonce_from_blockhash {
-    solana_pubkey::declare_id!("Gea3ZkK2N4pHuVZVxWcnAtS6UEDdyumdYt4pFcKjA3ar");
-}
-
-pub mod enable_durable_nonce {
-    solana_pubkey::declare_id!("4EJQtF2pkRyawwcTVfQutzq4Sa5hRhibF6QAK1QXhtEX");
-}
-
-pub mod vote_state_update_credit_per_dequeue {
-    solana_pubkey::declare_id!("CveezY6FDLVBToHDcvJRmtMouqzsmj4UXYh5ths5G5Uv");
-}
-
-pub mod quick_bail_on_panic {
-    solana_pubkey::declare_id!("DpJREPyuMZ5nDfU6H3WTqSqUFSXAfw8u7xqmWtEwJDcP");
-}
-
-pub mod nonce_must_be_authorized {
-    solana_pubkey::declare_id!("HxrEu1gXuH7iD3Puua1ohd5n4iUKJyFNtNxk9DVJkvgr");
-}
-
-pub mod nonce_must_be_advanceable {
-    solana_pubkey::declare_id!("3u3Er5Vc2jVcwz4xr2GJeSAXT3fAj6ADHZ4BJMZiScFd");
-}
-
-pub mod vote_authorize_with_seed {
-    solana_pubkey::declare_id!("6tRxEYKuy2L5nnv5bgn7iT28MxUbYxp5h7F3Ncf1exrT");
-}
-
-pub mod preserve_rent_epoch_for_rent_exempt_accounts {
-    solana_pubkey::declare_id!("HH3MUYReL2BvqqA3oEcAa7txju5GY6G4nxJ51zvsEjEZ");
-}
-
-pub mod enable_bpf_loader_extend_program_ix {
-    solana_pubkey::declare_id!("8Zs9W7D9MpSEtUWSQdGniZk2cNmV22y6FLJwCx53asme");
-}
-
-pub mod enable_early_verification_of_account_modifications {
-    solana_pubkey::declare_id!("7Vced912WrRnfjaiKRiNBcbuFw7RrnLv3E3z95Y4GTNc");
-}
-
-pub mod skip_rent_rewrites {
-    solana_pubkey::declare_id!("CGB2jM8pwZkeeiXQ66kBMyBR6Np61mggL7XUsmLjVcrw");
-}
-
-pub mod prevent_crediting_accounts_that_end_rent_paying {
-    solana_pubkey::declare_id!("812kqX67odAp5NFwM8D2N24cku7WTm9CHUTFUXaDkWPn");
-}
-
-pub mod cap_bpf_program_instruction_accounts {
-    solana_pubkey::declare_id!("9k5ijzTbYPtjzu8wj2ErH9v45xecHzQ1x4PMYMMxFgdM");
-}
-
-pub mod loosen_cpi_size_restriction {
-    solana_pubkey::declare_id!("GDH5TVdbTPUpRnXaRyQqiKUa7uZAbZ28Q2N9bhbKoMLm");
-}
-
-pub mod use_default_units_in_fee_calculation {
-    solana_pubkey::declare_id!("8sKQrMQoUHtQSUP83SPG4ta2JDjSAiWs7t5aJ9uEd6To");
-}
-
-pub mod compact_vote_state_updates {
-    solana_pubkey::declare_id!("86HpNqzutEZwLcPxS6EHDcMNYWk6ikhteg9un7Y2PBKE");
-}
-
-pub mod incremental_snapshot_only_incremental_hash_calculation {
-    solana_pubkey::declare_id!("25vqsfjk7Nv1prsQJmA4Xu1bN61s8LXCBGUPp8Rfy1UF");
-}
-
-pub mod disable_cpi_setting_executable_and_rent_epoch {
-    solana_pubkey::declare_id!("B9cdB55u4jQsDNsdTK525yE9dmSc5Ga7YBaBrDFvEhM9");
-}
-
-pub mod on_load_preserve_rent_epoch_for_rent_exempt_accounts {
-    solana_pubkey::declare_id!("CpkdQmspsaZZ8FVAouQTtTWZkc8eeQ7V3uj7dWz543rZ");
-}
-
-pub mod account_hash_ignore_slot {
-    solana_pubkey::declare_id!("SVn36yVApPLYsa8koK3qUcy14zXDnqkNYWyUh1f4oK1");
-}
-
-pub mod set_exempt_rent_epoch_max {
-    solana_pubkey::declare_id!("5wAGiy15X1Jb2hkHnPDCM8oB9V42VNA9ftNVFK84dEgv");
-}
-
-pub mod relax_authority_signer_check_for_lookup_table_creation {
-    solana_pubkey::declare_id!("FKAcEvNgSY79RpqsPNUV5gDyumopH4cEHqUxyfm8b8Ap");
-}
-
-pub mod stop_sibling_instruction_search_at_parent {
-    solana_pubkey::declare_id!("EYVpEP7uzH1CoXzbD6PubGhYmnxRXPeq3PPsm1ba3gpo");
-}
-
-pub mod vote_state_update_root_fix {
-    solana_pubkey::declare_id!("G74BkWBzmsByZ1kxHy44H3wjwp5hp7JbrGRuDpco22tY");
-}
-
-pub mod cap_accounts_data_allocations_per_transaction {
-    solana_pubkey::declare_id!("9gxu85LYRAcZL38We8MYJ4A9AwgBBPtVBAqebMcT1241");
-}
-
-pub mod epoch_accounts_hash {
-    solana_pubkey::declare_id!("5GpmAKxaGsWWbPp4bNXFLJxZVvG92ctxf7jQnzTQjF3n");
-}
-
-pub mod remove_deprecated_request_unit_ix {
-    solana_pubkey::declare_id!("EfhYd3SafzGT472tYQDUc4dPd2xdEfKs5fwkowUgVt4W");
-}
-
-pub mod disable_rehash_for_rent_epoch {
-    solana_pubkey::declare_id!("DTVTkmw3JSofd8CJVJte8PXEbxNQ2yZijvVr3pe2APPj");
-}
-
-pub mod increase_tx_account_lock_limit {
-    solana_pubkey::declare_id!("9LZdXeKGeBV6hRLdxS1rHbHoEUsKqesCC2ZAPTPKJAbK");
-}
-
-pub mod limit_max_instruction_trace_length {
-    solana_pubkey::declare_id!("GQALDaC48fEhZGWRj9iL5Q889emJKcj3aCvHF7VCbbF4");
-}
-
-pub mod check_syscall_outputs_do_not_overlap {
-    solana_pubkey::declare_id!("3uRVPBpyEJRo1emLCrq38eLRFGcu6uKSpUXqGvU8T7SZ");
-}
-
-pub mod enable_bpf_loader_set_authority_checked_ix {
-    solana_pubkey::declare_id!("5x3825XS7M2A3Ekbn5VGGkvFoAg5qrRWkTrY4bARP1GL");
-}
-
-pub mod enable_alt_bn128_syscall {
-    solana_pubkey::declare_id!("A16q37opZdQMCbe5qJ6xpBB9usykfv8jZaMkxvZQi4GJ");
-}
-
-pub mod simplify_alt_bn128_syscall_error_codes {
-    solana_pubkey::declare_id!("JDn5q3GBeqzvUa7z67BbmVHVdE3EbUAjvFep3weR3jxX");
-}
-
-pub mod enable_alt_bn128_compression_syscall {
-    solana_pubkey::declare_id!("EJJewYSddEEtSZHiqugnvhQHiWyZKjkFDQASd7oKSagn");
-}
-
-pub mod fix_alt_bn128_multiplication_input_length {
-    solana_pubkey::declare_id!("bn2puAyxUx6JUabAxYdKdJ5QHbNNmKw8dCGuGCyRrFN");
-}
-
-pub mod enable_program_redeployment_cooldown {
-    solana_pubkey::declare_id!("J4HFT8usBxpcF63y46t1upYobJgChmKyZPm5uTBRg25Z");
-}
-
-pub mod commission_updates_only_allowed_in_first_half_of_epoch {
-    solana_pubkey::declare_id!("noRuG2kzACwgaY7TVmLRnUNPLKNVQE1fb7X55YWBehp");
-}
-
-pub mod enable_turbine_fanout_experiments {
-    solana_pubkey::declare_id!("D31EFnLgdiysi84Woo3of4JMu7VmasUS3Z7j9HYXCeLY");
-}
-
-pub mod disable_turbine_fanout_experiments {
-    solana_pubkey::declare_id!("turbnbNRp22nwZCmgVVXFSshz7H7V23zMzQgA46YpmQ");
-}
-
-pub mod move_serialized_len_ptr_in_cpi {
-    solana_pubkey::declare_id!("74CoWuBmt3rUVUrCb2JiSTvh6nXyBWUsK4SaMj3CtE3T");
-}
-
-pub mod update_hashes_per_tick {
-    solana_pubkey::declare_id!("3uFHb9oKdGfgZGJK9EHaAXN4USvnQtAFC13Fh5gGFS5B");
-}
-
-pub mod enable_big_mod_exp_syscall {
-    solana_pubkey::declare_id!("EBq48m8irRKuE7ZnMTLvLg2UuGSqhe8s8oMqnmja1fJw");
-}
-
-pub mod disable_builtin_loader_ownership_chains {
-    solana_pubkey::declare_id!("4UDcAfQ6EcA6bdcadkeHpkarkhZGJ7Bpq7wTAiRMjkoi");
-}
-
-pub mod cap_transaction_accounts_data_size {
-    solana_pubkey::declare_id!("DdLwVYuvDz26JohmgSbA7mjpJFgX5zP2dkp8qsF2C33V");
-}
-
-pub mod remove_congestion_multiplier_from_fee_calculation {
-    solana_pubkey::declare_id!("A8xyMHZovGXFkorFqEmVH2PKGLiBip5JD7jt4zsUWo4H");
-}
-
-pub mod enable_request_heap_frame_ix {
-    solana_pubkey::declare_id!("Hr1nUA9b7NJ6eChS26o7Vi8gYYDDwWD3YeBfzJkTbU86");
-}
-
-pub mod prevent_rent_paying_rent_recipients {
-    solana_pubkey::declare_id!("Fab5oP3DmsLYCiQZXdjyqT3ukFFPrsmqhXU4WU1AWVVF");
-}
-
-pub mod delay_visibility_of_program_deployment {
-    solana_pubkey::declare_id!("GmuBvtFb2aHfSfMXpuFeWZGHyDeCLPS79s48fmCWCfM5");
-}
-
-pub mod apply_cost_tracker_during_replay {
-    solana_pubkey::declare_id!("2ry7ygxiYURULZCrypHhveanvP5tzZ4toRwVp89oCNSj");
-}
-
-pub mod bpf_account_data_direct_mapping {
-    solana_pubkey::declare_id!("1ncomp1ete111111111111111111111111111111111");
-}
-
-pub mod add_set_tx_loaded_accounts_data_size_instruction {
-    solana_pubkey::declare_id!("G6vbf1UBok8MWb8m25ex86aoQHeKTzDKzuZADHkShqm6");
-}
-
-pub mod switch_to_new_elf_parser {
-    solana_pubkey::declare_id!("Cdkc8PPTeTNUPoZEfCY5AyetUrEdkZtNPMgz58nqyaHD");
-}
-
-pub mod round_up_heap_size {
-    solana_pubkey::declare_id!("CE2et8pqgyQMP2mQRg3CgvX8nJBKUArMu3wfiQiQKY1y");
-}
-
-pub mod remove_bpf_loader_incorrect_program_id {
-    solana_pubkey::declare_id!("2HmTkCj9tXuPE4ueHzdD7jPeMf9JGCoZh5AsyoATiWEe");
-}
-
-pub mod include_loaded_accounts_data_size_in_fee_calculation {
-    solana_pubkey::declare_id!("EaQpmC6GtRssaZ3PCUM5YksGqUdMLeZ46BQXYtHYakDS");
-}
-
-pub mod native_programs_consume_cu {
-    solana_pubkey::declare_id!("8pgXCMNXC8qyEFypuwpXyRxLXZdpM4Qo72gJ6k87A6wL");
-}
-
-pub mod simplify_writable_program_account_check {
-    solana_pubkey::declare_id!("5ZCcFAzJ1zsFKe1KSZa9K92jhx7gkcKj97ci2DBo1vwj");
-}
-
-pub mod stop_truncating_strings_in_syscalls {
-    solana_pubkey::declare_id!("16FMCmgLzCNNz6eTwGanbyN2ZxvTBSLuQ6DZhgeMshg");
-}
-
-pub mod clean_up_delegation_errors {
-    solana_pubkey::declare_id!("Bj2jmUsM2iRhfdLLDSTkhM5UQRQvQHm57HSmPibPtEyu");
-}
-
-pub mod vote_state_add_vote_latency {
-    solana_pubkey::declare_id!("7axKe5BTYBDD87ftzWbk5DfzWMGyRvqmWTduuo22Yaqy");
-}
-
-pub mod checked_arithmetic_in_fee_validation {
-    solana_pubkey::declare_id!("5Pecy6ie6XGm22pc9d4P9W5c31BugcFBuy6hsP2zkETv");
-}
-
-pub mod last_restart_slot_sysvar {
-    solana_pubkey::declare_id!("HooKD5NC9QNxk25QuzCssB8ecrEzGt6eXEPBUxWp1LaR");
-}
-
-pub mod reduce_stake_warmup_cooldown {
-    solana_pubkey::declare_id!("GwtDQBghCTBgmX2cpEGNPxTEBUTQRaDMGTr5qychdGMj");
-}
-
-pub mod revise_turbine_epoch_stakes {
-    solana_pubkey::declare_id!("BTWmtJC8U5ZLMbBUUA1k6As62sYjPEjAiNAT55xYGdJU");
-}
-
-pub mod enable_poseidon_syscall {
-    solana_pubkey::declare_id!("FL9RsQA6TVUoh5xJQ9d936RHSebA1NLQqe3Zv9sXZRpr");
-}
-
-pub mod timely_vote_credits {
-    solana_pubkey::declare_id!("tvcF6b1TRz353zKuhBjinZkKzjmihXmBAHJdjNYw1sQ");
-}
-
-pub mod remaining_compute_units_syscall_enabled {
-    solana_pubkey::declare_id!("5TuppMutoyzhUSfuYdhgzD47F92GL1g89KpCZQKqedxP");
-}
-
-pub mod enable_loader_v4 {
-    solana_pubkey::declare_id!("2aQJYqER2aKyb3cZw22v4SL2xMX7vwXBRWfvS4pTrtED");
-}
-
-pub mod require_rent_exempt_split_destination {
-    solana_pubkey::declare_id!("D2aip4BBr8NPWtU9vLrwrBvbuaQ8w1zV38zFLxx4pfBV");
-}
-
-pub mod better_error_codes_for_tx_lamport_check {
-    solana_pubkey::declare_id!("Ffswd3egL3tccB6Rv3XY6oqfdzn913vUcjCSnpvCKpfx");
-}
-
-pub mod update_hashes_per_tick2 {
-    solana_pubkey::declare_id!("EWme9uFqfy1ikK1jhJs8fM5hxWnK336QJpbscNtizkTU");
-}
-
-pub mod update_hashes_per_tick3 {
-    solana_pubkey::declare_id!("8C8MCtsab5SsfammbzvYz65HHauuUYdbY2DZ4sznH6h5");
-}
-
-pub mod update_hashes_per_tick4 {
-    solana_pubkey::declare_id!("8We4E7DPwF2WfAN8tRTtWQNhi98B99Qpuj7JoZ3Aikgg");
-}
-
-pub mod update_hashes_per_tick5 {
-    solana_pubkey::declare_id!("BsKLKAn1WM4HVhPRDsjosmqSg2J8Tq5xP2s2daDS6Ni4");
-}
-
-pub mod update_hashes_per_tick6 {
-    solana_pubkey::declare_id!("FKu1qYwLQSiehz644H6Si65U5ZQ2cp9GxsyFUfYcuADv");
-}
-
-pub mod validate_fee_collector_account {
-    solana_pubkey::declare_id!("prpFrMtgNmzaNzkPJg9o753fVvbHKqNrNTm76foJ2wm");
-}
-
-pub mod disable_rent_fees_collection {
-    solana_pubkey::declare_id!("CJzY83ggJHqPGDq8VisV3U91jDJLuEaALZooBrXtnnLU");
-}
-
-pub mod enable_zk_transfer_with_fee {
-    solana_pubkey::declare_id!("zkNLP7EQALfC1TYeB3biDU7akDckj8iPkvh9y2Mt2K3");
-}
-
-pub mod drop_legacy_shreds {
-    solana_pubkey::declare_id!("GV49KKQdBNaiv2pgqhS2Dy3GWYJGXMTVYbYkdk91orRy");
-}
-
-pub mod allow_commission_decrease_at_any_time {
-    solana_pubkey::declare_id!("decoMktMcnmiq6t3u7g5BfgcQu91nKZr6RvMYf9z1Jb");
-}
-
-pub mod add_new_reserved_account_keys {
-    solana_pubkey::declare_id!("8U4skmMVnF6k2kMvrWbQuRUT3qQSiTYpSjqmhmgfthZu");
-}
-
-pub mod consume_blockstore_duplicate_proofs {
-    solana_pubkey::declare_id!("6YsBCejwK96GZCkJ6mkZ4b68oP63z2PLoQmWjC7ggTqZ");
-}
-
-pub mod index_erasure_conflict_duplicate_proofs {
-    solana_pubkey::declare_id!("dupPajaLy2SSn8ko42aZz4mHANDNrLe8Nw8VQgFecLa");
-}
-
-pub mod merkle_conflict_duplicate_proofs {
-    solana_pubkey::declare_id!("mrkPjRg79B2oK2ZLgd7S3AfEJaX9B6gAF3H9aEykRUS");
-}
-
-pub mod disable_bpf_loader_instructions {
-    solana_pubkey::declare_id!("7WeS1vfPRgeeoXArLh7879YcB9mgE9ktjPDtajXeWfXn");
-}
-
-pub mod enable_zk_proof_from_account {
-    solana_pubkey::declare_id!("zkiTNuzBKxrCLMKehzuQeKZyLtX2yvFcEKMML8nExU8");
-}
-
-pub mod cost_model_requested_write_lock_cost {
-    solana_pubkey::declare_id!("wLckV1a64ngtcKPRGU4S4grVTestXjmNjxBjaKZrAcn");
-}
-
-pub mod enable_gossip_duplicate_proof_ingestion {
-    solana_pubkey::declare_id!("FNKCMBzYUdjhHyPdsKG2LSmdzH8TCHXn3ytj8RNBS4nG");
-}
-
-pub mod chained_merkle_conflict_duplicate_proofs {
-    solana_pubkey::declare_id!("chaie9S2zVfuxJKNRGkyTDokLwWxx6kD2ZLsqQHaDD8");
-}
-
-pub mod enable_chained_merkle_shreds {
-    solana_pubkey::declare_id!("7uZBkJXJ1HkuP6R3MJfZs7mLwymBcDbKdqbF51ZWLier");
-}
-
-pub mod remove_rounding_in_fee_calculation {
-    solana_pubkey::declare_id!("BtVN7YjDzNE6Dk7kTT7YTDgMNUZTNgiSJgsdzAeTg2jF");
-}
-
-pub mod enable_tower_sync_ix {
-    solana_pubkey::declare_id!("tSynMCspg4xFiCj1v3TDb4c7crMR5tSBhLz4sF7rrNA");
-}
-
-pub mod deprecate_unused_legacy_vote_plumbing {
-    solana_pubkey::declare_id!("6Uf8S75PVh91MYgPQSHnjRAPQq6an5BDv9vomrCwDqLe");
-}
-
-pub mod reward_full_priority_fee {
-    solana_pubkey::declare_id!("3opE3EzAKnUftUDURkzMgwpNgimBAypW1mNDYH4x4Zg7");
-}
-
-pub mod get_sysvar_syscall_enabled {
-    solana_pubkey::declare_id!("CLCoTADvV64PSrnR6QXty6Fwrt9Xc6EdxSJE4wLRePjq");
-}
-
-pub mod abort_on_invalid_curve {
-    solana_pubkey::declare_id!("FuS3FPfJDKSNot99ECLXtp3rueq36hMNStJkPJwWodLh");
-}
-
-pub mod migrate_feature_gate_program_to_core_bpf {
-    solana_pubkey::declare_id!("4eohviozzEeivk1y9UbrnekbAFMDQyJz5JjA9Y6gyvky");
-}
-
-pub mod vote_only_full_fec_sets {
-    solana_pubkey::declare_id!("ffecLRhhakKSGhMuc6Fz2Lnfq4uT9q3iu9ZsNaPLxPc");
-}
-
-pub mod migrate_config_program_to_core_bpf {
-    solana_pubkey::declare_id!("2Fr57nzzkLYXW695UdDxDeR5fhnZWSttZeZYemrnpGFV");
-}
-
-pub mod enable_get_epoch_stake_syscall {
-    solana_pubkey::declare_id!("FKe75t4LXxGaQnVHdUKM6DSFifVVraGZ8LyNo7oPwy1Z");
-}
-
-pub mod migrate_address_lookup_table_program_to_core_bpf {
-    solana_pubkey::declare_id!("C97eKZygrkU4JxJsZdjgbUY7iQR7rKTr4NyDWo2E5pRm");
-}
-
-pub mod zk_elgamal_proof_program_enabled {
-    solana_pubkey::declare_id!("zkhiy5oLowR7HY4zogXjCjeMXyruLqBwSWH21qcFtnv");
-}
-
-pub mod verify_retransmitter_signature {
-    solana_pubkey::declare_id!("51VCKU5eV6mcTc9q9ArfWELU2CqDoi13hdAjr6fHMdtv");
-}
-
-pub mod move_stake_and_move_lamports_ixs {
-    solana_pubkey::declare_id!("7bTK6Jis8Xpfrs8ZoUfiMDPazTcdPcTWheZFJTA5Z6X4");
-}
-
-pub mod ed25519_precompile_verify_strict {
-    solana_pubkey::declare_id!("ed9tNscbWLYBooxWA7FE2B5KHWs8A6sxfY8EzezEcoo");
-}
-
-pub mod vote_only_retransmitter_signed_fec_sets {
-    solana_pubkey::declare_id!("RfEcA95xnhuwooVAhUUksEJLZBF7xKCLuqrJoqk4Zph");
-}
-
-pub mod move_precompile_verification_to_svm {
-    solana_pubkey::declare_id!("9ypxGLzkMxi89eDerRKXWDXe44UY2z4hBig4mDhNq5Dp");
-}
-
-pub mod enable_transaction_loading_failure_fees {
-    solana_pubkey::declare_id!("PaymEPK2oqwT9TXAVfadjztH2H6KfLEB9Hhd5Q5frvP");
-}
-
-pub mod enable_turbine_extended_fanout_experiments {
-    solana_pubkey::declare_id!("turbRpTzBzDU6PJmWvRTbcJXXGxUs19CvQamUrRD9bN");
-}
+        // Retired ids survive a round trip
+        let bytes = bincode::serialize(&feature_set).unwrap();
+        assert_eq!(
+            bincode::deserialize::<FeatureSet>(&bytes).unwrap(),
+            feature_set
+        );
+    }
 
-pub mod deprecate_legacy_vote_ixs {
-    solana_pubkey::declare_id!("depVvnQ2UysGrhwdiwU42tCadZL8GcBb1i2GYhMopQv");
-}
+    #[test]
+    fn test_full_inflation_candidates() {
+        let pairs = [
+            FullInflationFeaturePair {
+                name: "b",
+                vote_id: pico_inflation::id(),
+                enable_id: secp256k1_program_enabled::id(),
+            },
+            FullInflationFeaturePair {
+                name: "a",
+                vote_id: spl_token_v2_multisig_fix::id(),
+                enable_id: spl_token_v2_self_transfer_fix::id(),
+            },
+            FullInflationFeaturePair {
+                name: "c",
+                vote_id: blake3_syscall_enabled::id(),
+                enable_id: check_init_vote_data::id(),
+            },
+        ];
+        let mut feature_set = FeatureSet::default();
+        // Only the vote id is active for "a"
+        feature_set.activate(&spl_token_v2_multisig_fix::id(), 10);
+        // Only the enable id is active for "b"
+        feature_set.activate(&secp256k1_program_enabled::id(), 20);
+        // Both are active for "c"
+        feature_set.activate(&blake3_syscall_enabled::id(), 40);
+        feature_set.activate(&check_init_vote_data::id(), 30);
+
+        let candidates = feature_set.full_inflation_candidates_from(&pairs);
+        assert_eq!(
+            candidates,
+            vec![
+                FullInflationCandidateStatus {
+                    name: "a",
+                    vote_id: spl_token_v2_multisig_fix::id(),
+                    enable_id: spl_token_v2_self_transfer_fix::id(),
+                    vote_active: true,
+                    enable_active: false,
+                    activation_slot: None,
+                },
+                FullInflationCandidateStatus {
+                    name: "b",
+                    vote_id: pico_inflation::id(),
+                    enable_id: secp256k1_program_enabled::id(),
+                    vote_active: false,
+                    enable_active: true,
+                    activation_slot: None,
+                },
+                FullInflationCandidateStatus {
+                    name: "c",
+                    vote_id: blake3_syscall_enabled::id(),
+                    enable_id: check_init_vote_data::id(),
+                    vote_active: true,
+                    enable_active: true,
+                    activation_slot: Some(40),
+                },
+            ]
+        );
+        assert_eq!(
+            candidates
+                .iter()
+                .filter(|candidate| candidate.is_enabled())
+                .map(|candidate| candidate.name)
+                .collect::<Vec<_>>(),
+            vec!["c"]
+        );
 
-pub mod disable_sbpf_v0_execution {
-    solana_pubkey::declare_id!("TestFeature11111111111111111111111111111111");
-}
+        assert_eq!(
+            FeatureSet::all_enabled().full_inflation_candidates().len(),
+            FULL_INFLATION_FEATURE_PAIRS.len()
+        );
+    }
 
-pub mod reenable_sbpf_v0_execution {
-    solana_pubkey::declare_id!("TestFeature21111111111111111111111111111111");
-}
+    #[test]
+    fn test_state_hash() {
+        let feature_set = mixed_feature_set();
+        let state_hash = feature_set.state_hash();
+        assert_eq!(feature_set.state_hash(), state_hash);
+        // Independent of map insertion order
+        let mut reordered = FeatureSet::default();
+        reordered.activate(&secp256k1_program_enabled::id(), 100);
+        reordered.activate(&full_inflation::devnet_and_testnet::id(), 42);
+        reordered.activate(&pico_inflation::id(), 0);
+        assert_eq!(reordered.state_hash(), state_hash);
+
+        // Sensitive to a single activation slot changing by one
+        let mut perturbed = feature_set.clone();
+        perturbed.activate(&full_inflation::devnet_and_testnet::id(), 43);
+        assert_ne!(perturbed.state_hash(), state_hash);
+
+        // Sensitive to a feature moving between active and inactive
+        let mut perturbed = feature_set.clone();
+        perturbed.deactivate(&pico_inflation::id());
+        assert_ne!(perturbed.state_hash(), state_hash);
+
+        assert_ne!(
+            FeatureSet::default().state_hash(),
+            FeatureSet::all_enabled().state_hash()
+        );
+    }
 
-pub mod enable_sbpf_v1_deployment_and_execution {
-    solana_pubkey::declare_id!("JE86WkYvTrzW8HgNmrHY7dFYpCmSptUpKupbo2AdQ9cG");
-}
+    #[test]
+    fn test_stage_and_promote_pending() {
+        let slots_per_epoch = 32;
+        let mut feature_set = FeatureSet::default();
+        let feature_id = pico_inflation::id();
 
-pub mod enable_sbpf_v2_deployment_and_execution {
-    solana_pubkey::declare_id!("F6UVKh1ujTEFK3en2SyAL3cdVnqko1FVEXWhmdLRu6WP");
-}
+        // Staged during epoch 0
+        feature_set.stage(&feature_id, 10);
+        assert!(feature_set.is_pending(&feature_id));
+        assert!(!feature_set.is_active(&feature_id));
+        assert!(feature_set.inactive().contains(&feature_id));
 
-pub mod enable_sbpf_v3_deployment_and_execution {
-    solana_pubkey::declare_id!("GJav1vwg2etvSWraPT96QvYuQJswJTJwtcyARrvkhuV9");
-}
+        // Activated at the epoch 1 boundary
+        assert_eq!(
+            feature_set.promote_pending(slots_per_epoch),
+            vec![feature_id]
+        );
+        assert!(!feature_set.is_pending(&feature_id));
+        assert_eq!(
+            feature_set.activated_slot(&feature_id),
+            Some(slots_per_epoch)
+        );
+        assert!(!feature_set.inactive().contains(&feature_id));
 
-pub mod remove_accounts_executable_flag_checks {
-    solana_pubkey::declare_id!("FXs1zh47QbNnhXcnB6YiAQoJ4sGB91tKF3UFHLcKT7PM");
-}
+        // Nothing left to promote at the next boundary
+        assert!(feature_set.promote_pending(2 * slots_per_epoch).is_empty());
+        assert_eq!(
+            feature_set.activated_slot(&feature_id),
+            Some(slots_per_epoch)
+        );
 
-pub mod disable_account_loader_special_case {
-    solana_pubkey::declare_id!("EQUMpNFr7Nacb1sva56xn1aLfBxppEoSBH8RRVdkcD1x");
-}
+        // Staging an active feature has no effect
+        feature_set.stage(&feature_id, 40);
+        assert!(!feature_set.is_pending(&feature_id));
+    }
 
-pub mod enable_secp256r1_precompile {
-    solana_pubkey::declare_id!("srremy31J5Y25FrAApwVb9kZcfXbusYMMsvTK9aWv5q");
-}
+    #[test]
+    fn test_double_stage() {
+        let mut feature_set = FeatureSet::default();
+        let feature_id = pico_inflation::id();
+        feature_set.stage(&feature_id, 10);
+        feature_set.stage(&feature_id, 20);
+        assert_eq!(feature_set.pending().len(), 1);
+        assert_eq!(feature_set.pending()[&feature_id], 10);
+        assert_eq!(feature_set.promote_pending(32), vec![feature_id]);
+    }
 
-pub mod accounts_lt_hash {
-    solana_pubkey::declare_id!("LTHasHQX6661DaDD4S6A2TFi6QBuiwXKv66fB1obfHq");
-}
+    #[test]
+    fn test_deactivate_clears_pending() {
+        let mut feature_set = FeatureSet::default();
+        let feature_id = pico_inflation::id();
+        feature_set.stage(&feature_id, 10);
+        feature_set.deactivate(&feature_id);
+        assert!(!feature_set.is_pending(&feature_id));
+        assert!(feature_set.promote_pending(32).is_empty());
+        assert!(!feature_set.is_active(&feature_id));
+    }
 
-pub mod snapshots_lt_hash {
-    solana_pubkey::declare_id!("LTsNAP8h1voEVVToMNBNqoiNQex4aqfUrbFhRH3mSQ2");
-}
+    #[test]
+    fn test_serde_pending_roundtrip() {
+        let mut feature_set = mixed_feature_set();
+        feature_set.stage(&spl_token_v2_multisig_fix::id(), 120);
+        let bytes = bincode::serialize(&feature_set).unwrap();
+        assert_eq!(
+            bincode::deserialize::<FeatureSet>(&bytes).unwrap(),
+            feature_set
+        );
+    }
 
-pub mod remove_accounts_delta_hash {
-    solana_pubkey::declare_id!("LTdLt9Ycbyoipz5fLysCi1NnDnASsZfmJLJXts5ZxZz");
-}
+    #[test]
+    fn test_all_enabled_except() {
+        let disabled = [pico_inflation::id(), blake3_syscall_enabled::id()];
+        let feature_set = FeatureSet::all_enabled_except(&disabled).unwrap();
+        assert_eq!(feature_set.active().len(), FEATURE_NAMES.len() - 2);
+        assert_eq!(feature_set.inactive(), &AHashSet::from_iter(disabled));
+        let unknown_id = Pubkey::new_unique();
+        assert_eq!(
+            FeatureSet::all_enabled_except(&[unknown_id]),
+            Err(FeatureOverrideError::UnknownFeature(unknown_id))
+        );
+        assert_eq!(
+            FeatureSet::all_enabled_except(&[]).unwrap(),
+            FeatureSet::all_enabled()
+        );
+    }
 
-pub mod migrate_stake_program_to_core_bpf {
-    solana_pubkey::declare_id!("6M4oQ6eXneVhtLoiAr4yRYQY43eVLjrKbiDZDJc892yk");
-}
+    #[test]
+    fn test_from_overrides() {
+        let base = mixed_feature_set();
+        assert_eq!(
+            FeatureSet::from_overrides(&base, &[], &[]),
+            Ok(base.clone())
+        );
 
-pub mod deplete_cu_meter_on_vm_failure {
-    solana_pubkey::declare_id!("B7H2caeia4ZFcpE3QcgMqbiWiBtWrdBRBSJ1DY6Ktxbq");
-}
+        let feature_set = FeatureSet::from_overrides(
+            &base,
+            &[
+                blake3_syscall_enabled::id(),
+                secp256k1_program_enabled::id(),
+            ],
+            &[pico_inflation::id()],
+        )
+        .unwrap();
+        assert_eq!(
+            feature_set.activated_slot(&blake3_syscall_enabled::id()),
+            Some(0)
+        );
+        // Already active features keep their activation slot
+        assert_eq!(
+            feature_set.activated_slot(&secp256k1_program_enabled::id()),
+            Some(100)
+        );
+        assert!(!feature_set.is_active(&pico_inflation::id()));
+        assert!(feature_set.inactive().contains(&pico_inflation::id()));
+    }
 
-pub mod reserve_minimal_cus_for_builtin_instructions {
-    solana_pubkey::declare_id!("C9oAhLxDBm3ssWtJx1yBGzPY55r2rArHmN1pbQn6HogH");
-}
+    #[test]
+    fn test_from_overrides_errors() {
+        let base = FeatureSet::default();
+        let unknown_id = Pubkey::new_unique();
+        assert_eq!(
+            FeatureSet::from_overrides(&base, &[unknown_id], &[]),
+            Err(FeatureOverrideError::UnknownFeature(unknown_id))
+        );
+        assert_eq!(
+            FeatureSet::from_overrides(&base, &[], &[unknown_id]),
+            Err(FeatureOverrideError::UnknownFeature(unknown_id))
+        );
+        assert!(FeatureOverrideError::UnknownFeature(unknown_id)
+            .to_string()
+            .contains(&unknown_id.to_string()));
 
-pub mod raise_block_limits_to_50m {
-    solana_pubkey::declare_id!("5oMCU3JPaFLr8Zr4ct7yFA7jdk6Mw1RmB8K4u9ZbS42z");
-}
+        let feature_id = pico_inflation::id();
+        assert_eq!(
+            FeatureSet::from_overrides(&base, &[feature_id], &[feature_id]),
+            Err(FeatureOverrideError::ConflictingOverride(feature_id))
+        );
+    }
 
-pub mod drop_unchained_merkle_shreds {
-    solana_pubkey::declare_id!("5KLGJSASDVxKPjLCDWNtnABLpZjsQSrYZ8HKwcEdAMC8");
-}
+    #[test]
+    fn test_feature_set_builder_presets() {
+        assert_eq!(
+            FeatureSetBuilder::all_enabled().build(),
+            Ok(FeatureSet::all_enabled())
+        );
+        assert_eq!(FeatureSetBuilder::none().build(), Ok(FeatureSet::default()));
 
-pub mod relax_intrabatch_account_locks {
-    solana_pubkey::declare_id!("ENTRYnPAoT5Swwx73YDGzMp3XnNH1kxacyvLosRHza1i");
-}
+        let feature_set = FeatureSetBuilder::mainnet_like().build().unwrap();
+        assert_eq!(feature_set.active().len(), MAINNET_LIKE_FEATURES.len());
+        for feature in MAINNET_LIKE_FEATURES {
+            assert!(FEATURE_NAMES.contains_key(&feature.id()));
+            assert_eq!(feature_set.activated_slot(&feature.id()), Some(0));
+        }
+        assert_eq!(
+            feature_set.inactive().len(),
+            FEATURE_NAMES.len() - MAINNET_LIKE_FEATURES.len()
+        );
+    }
 
-pub mod create_slashing_program {
-    solana_pubkey::declare_id!("sProgVaNWkYdP2eTRAy1CPrgb3b9p8yXCASrPEqo6VJ");
-}
+    #[test]
+    fn test_feature_set_builder_overrides() {
+        let feature_set = FeatureSetBuilder::none()
+            .enable(pico_inflation::id())
+            .enable_at(blake3_syscall_enabled::id(), 42)
+            .build()
+            .unwrap();
+        assert_eq!(feature_set.active().len(), 2);
+        assert_eq!(feature_set.activated_slot(&pico_inflation::id()), Some(0));
+        assert_eq!(
+            feature_set.activated_slot(&blake3_syscall_enabled::id()),
+            Some(42)
+        );
 
-pub mod disable_partitioned_rent_collection {
-    solana_pubkey::declare_id!("2B2SBNbUcr438LtGXNcJNBP2GBSxjx81F945SdSkUSfC");
-}
+        let feature_set = FeatureSetBuilder::all_enabled()
+            .disable(pico_inflation::id())
+            .enable_at(blake3_syscall_enabled::id(), 7)
+            .build()
+            .unwrap();
+        assert!(!feature_set.is_active(&pico_inflation::id()));
+        assert!(feature_set.inactive().contains(&pico_inflation::id()));
+        assert_eq!(
+            feature_set.activated_slot(&blake3_syscall_enabled::id()),
+            Some(7)
+        );
+        assert_eq!(feature_set.active().len(), FEATURE_NAMES.len() - 1);
+    }
 
-pub mod enable_vote_address_leader_schedule {
-    solana_pubkey::declare_id!("5JsG4NWH8Jbrqdd8uL6BNwnyZK3dQSoieRXG5vmofj9y");
-}
+    #[test]
+    fn test_feature_set_builder_errors() {
+        let unknown_id = Pubkey::new_unique();
+        assert_eq!(
+            FeatureSetBuilder::none().enable(unknown_id).build(),
+            Err(FeatureOverrideError::UnknownFeature(unknown_id))
+        );
+        assert_eq!(
+            FeatureSetBuilder::all_enabled().disable(unknown_id).build(),
+            Err(FeatureOverrideError::UnknownFeature(unknown_id))
+        );
 
-pub mod require_static_nonce_account {
-    solana_pubkey::declare_id!("7VVhpg5oAjAmnmz1zCcSHb2Z9ecZB2FQqpnEwReka9Zm");
-}
+        let feature_id = pico_inflation::id();
+        assert_eq!(
+            FeatureSetBuilder::mainnet_like()
+                .enable_at(feature_id, 10)
+                .disable(feature_id)
+                .build(),
+            Err(FeatureOverrideError::ConflictingOverride(feature_id))
+        );
+        assert_eq!(
+            FeatureSetBuilder::none()
+                .disable(feature_id)
+                .enable(feature_id)
+                .build(),
+            Err(FeatureOverrideError::ConflictingOverride(feature_id))
+        );
+    }
 
-pub mod raise_block_limits_to_60m {
-    solana_pubkey::declare_id!("6oMCUgfY6BzZ6jwB681J6ju5Bh6CjVXbd7NeWYqiXBSu");
-}
+    #[test]
+    fn test_activation_history() {
+        let mut feature_set = FeatureSet::default();
+        assert!(feature_set.activation_history().is_empty());
+        assert_eq!(feature_set.last_activated(), None);
 
-pub mod mask_out_rent_epoch_in_vm_serialization {
-    solana_pubkey::declare_id!("RENtePQcDLrAbxAsP3k8dwVcnNYQ466hi2uKvALjnXx");
-}
+        let mut tied = [pico_inflation::id(), blake3_syscall_enabled::id()];
+        tied.sort_unstable();
+        let unknown_id = Pubkey::new_unique();
+        feature_set.activate(&tied[1], 10);
+        feature_set.activate(&unknown_id, 5);
+        feature_set.activate(&tied[0], 10);
 
-pub mod enshrine_slashing_program {
-    solana_pubkey::declare_id!("sProgVaNWkYdP2eTRAy1CPrgb3b9p8yXCASrPEqo6VJ");
-}
+        assert_eq!(
+            feature_set.activation_history(),
+            vec![
+                (5, unknown_id, "unknown"),
+                (10, tied[0], FEATURE_NAMES[&tied[0]]),
+                (10, tied[1], FEATURE_NAMES[&tied[1]]),
+            ]
+        );
+        assert_eq!(feature_set.last_activated(), Some((10, tied[1])));
+    }
 
-pub mod enable_extend_program_checked {
-    solana_pubkey::declare_id!("2oMRZEDWT2tqtYMofhmmfQ8SsjqUFzT6sYXppQDavxwz");
-}
+    #[test]
+    fn test_features_enum() {
+        let ids = Features::ALL
+            .iter()
+            .map(|feature| {
+                assert!(FEATURE_NAMES.contains_key(&feature.id()));
+                feature.id()
+            })
+            .collect::<AHashSet<_>>();
+        assert_eq!(ids.len(), FEATURE_NAMES.len());
+        // create_slashing_program and enshrine_slashing_program share an id
+        assert_eq!(
+            Features::CreateSlashingProgram.id(),
+            Features::EnshrineSlashingProgram.id()
+        );
+        assert_eq!(Features::ALL.len(), FEATURE_NAMES.len() + 1);
 
-pub mod formalize_loaded_transaction_data_size {
-    solana_pubkey::declare_id!("DeS7sR48ZcFTUmt5FFEVDr1v1bh73aAbZiZq3SYr8Eh8");
-}
+        assert_eq!(Features::PicoInflation.id(), pico_inflation::id());
+        assert_eq!(Features::PicoInflation.description(), "pico inflation");
+        assert_eq!(
+            Features::FullInflationMainnetCertusoneVote.id(),
+            full_inflation::mainnet::certusone::vote::id()
+        );
 
-pub mod alpenglow {
-    solana_pubkey::declare_id!("mustRekeyVm2QHYB3JPefBiU4BY3Z6JkW2k3Scw5GWP");
-}
+        let mut feature_set = FeatureSet::default();
+        assert!(!feature_set.is_active_feature(Features::PicoInflation));
+        feature_set.activate(&pico_inflation::id(), 0);
+        assert!(feature_set.is_active_feature(Features::PicoInflation));
+        assert!(!feature_set.is_active_feature(Features::Blake3SyscallEnabled));
+    }
 
-pub mod disable_zk_elgamal_proof_program {
-    solana_pubkey::declare_id!("zkdoVwnSFnSLtGJG7irJPEYUpmb4i7sGMGcnN6T9rnC");
-}
+    #[test]
+    fn test_subscribe_multiple() {
+        let mut feature_set = FeatureSet::default();
+        let first = Arc::<Mutex<Vec<(Pubkey, u64)>>>::default();
+        let second = Arc::<Mutex<Vec<(Pubkey, u64)>>>::default();
+        for recorded in [&first, &second] {
+            let recorded = recorded.clone();
+            feature_set.subscribe(Box::new(move |feature_id, slot| {
+                recorded.lock().unwrap().push((*feature_id, slot));
+            }));
+        }
 
-pub mod reenable_zk_elgamal_proof_program {
-    solana_pubkey::declare_id!("zkemPXcuM3G4wpMDZ36Cpw34EjUpvm1nuioiSGbGZPR");
-}
+        feature_set.activate(&pico_inflation::id(), 10);
+        // Re-activating at the same slot is not a change
+        feature_set.activate(&pico_inflation::id(), 10);
+        let expected = vec![(pico_inflation::id(), 10)];
+        assert_eq!(*first.lock().unwrap(), expected);
+        assert_eq!(*second.lock().unwrap(), expected);
+
+        // Subscriptions are shared with clones
+        let mut cloned = feature_set.clone();
+        assert_eq!(cloned, feature_set);
+        cloned.activate(&blake3_syscall_enabled::id(), 11);
+        let expected = vec![
+            (pico_inflation::id(), 10),
+            (blake3_syscall_enabled::id(), 11),
+        ];
+        assert_eq!(*first.lock().unwrap(), expected);
+        assert_eq!(*second.lock().unwrap(), expected);
+    }
 
-pub static FEATURE_NAMES: LazyLock<AHashMap<Pubkey, &'static str>> = LazyLock::new(|| {
-    [
-        (secp256k1_program_enabled::id(), "secp256k1 program"),
-        (deprecate_rewards_sysvar::id(), "deprecate unused rewards sysvar"),
-        (pico_inflation::id(), "pico inflation"),
-        (full_inflation::devnet_and_testnet::id(), "full inflation on devnet and testnet"),
-        (spl_token_v2_multisig_fix::id(), "spl-token multisig fix"),
-        (no_overflow_rent_distribution::id(), "no overflow rent distribution"),
-        (filter_stake_delegation_accounts::id(), "filter stake_delegation_accounts #14062"),
-        (require_custodian_for_locked_stake_authorize::id(), "require custodian to authorize withdrawer change for locked stake"),
-        (spl_token_v2_self_transfer_fix::id(), "spl-token self-transfer fix"),
-        (full_inflation::mainnet::certusone::enable::id(), "full inflation enabled by Certus One"),
-        (full_inflation::mainnet::certusone::vote::id(), "community vote allowing Certus One to enable full inflation"),
-        (warp_timestamp_again::id(), "warp timestamp again, adjust bounding to 25% fast 80% slow #15204"),
-        (check_init_vote_data::id(), "check initialized Vote data"),
-        (secp256k1_recover_syscall_enabled::id(), "secp256k1_recover syscall"),
-        (system_transfer_zero_check::id(), "perform all checks for transfers of 0 lamports"),
-        (blake3_syscall_enabled::id(), "blake3 syscall"),
-        (dedupe_config_program_signers::id(), "dedupe config program signers"),
-        (verify_tx_signatures_len::id(), "prohibit extra transaction signatures"),
-        (vote_stake_checked_instructions::id(), "vote/state program checked instructions #18345"),
-        (rent_for_sysvars::id(), "collect rent from accounts owned by sysvars"),
-        (libsecp256k1_0_5_upgrade_enabled::id(), "upgrade libsecp256k1 to v0.5.0"),
-        (tx_wide_compute_cap::id(), "transaction wide compute cap"),
-        (spl_token_v2_set_authority_fix::id(), "spl-token set_authority fix"),
-        (merge_nonce_error_into_system_error::id(), "merge NonceError into SystemError"),
-        (disable_fees_sysvar::id(), "disable fees sysvar"),
-        (stake_merge_with_unmatched_credits_observed::id(), "allow merging active stakes with unmatched credits_observed #18985"),
-        (zk_token_sdk_enabled::id(), "enable Zk Token proof program and syscalls"),
-        (curve25519_syscall_enabled::id(), "enable curve25519 syscalls"),
-        (versioned_tx_message_enabled::id(), "enable versioned transaction message processing"),
-        (libsecp256k1_fail_on_bad_count::id(), "fail libsecp256k1_verify if count appears wrong"),
-        (libsecp256k1_fail_on_bad_count2::id(), "fail libsecp256k1_verify if count appears wrong"),
-        (instructions_sysvar_owned_by_sysvar::id(), "fix owner for instructions sysvar"),
-        (stake_program_advance_activating_credits_observed::id(), "Enable advancing credits observed for activation epoch #19309"),
-        (credits_auto_rewind::id(), "Auto rewind stake's credits_observed if (accidental) vote recreation is detected #22546"),
-        (demote_program_write_locks::id(), "demote program write locks to readonly, except when upgradeable loader present #19593 #20265"),
-        (ed25519_program_enabled::id(), "enable builtin ed25519 signature verify program"),
-        (return_data_syscall_enabled::id(), "enable sol_{set,get}_return_data syscall"),
-        (reduce_required_deploy_balance::id(), "reduce required payer balance for program deploys"),
-        (sol_log_data_syscall_enabled::id(), "enable sol_log_data syscall"),
-        (stakes_remove_delegation_if_inactive::id(), "remove delegations from stakes cache when inactive"),
-        (do_support_realloc::id(), "support account data reallocation"),
-        (prevent_calling_precompiles_as_programs::id(), "prevent calling precompiles as programs"),
-        (optimize_epoch_boundary_updates::id(), "optimize epoch boundary updates"),
-        (remove_native_loader::id(), "remove support for the native loader"),
-        (send_to_tpu_vote_port::id(), "send votes to the tpu vote port"),
-        (requestable_heap_size::id(), "Requestable heap frame size"),
-        (disable_fee_calculator::id(), "deprecate fee calculator"),
-        (add_compute_budget_program::id(), "Add compute_budget_program"),
-        (nonce_must_be_writable::id(), "nonce must be writable"),
-        (spl_token_v3_3_0_release::id(), "spl-token v3.3.0 release"),
-        (leave_nonce_on_success::id(), "leave nonce as is on success"),
-        (reject_empty_instruction_without_program::id(), "fail instructions which have native_loader as program_id directly"),
-        (fixed_memcpy_nonoverlapping_check::id(), "use correct check for nonoverlapping regions in memcpy syscall"),
-        (reject_non_rent_exempt_vote_withdraws::id(), "fail vote withdraw instructions which leave the account non-rent-exempt"),
-        (evict_invalid_stakes_cache_entries::id(), "evict invalid stakes cache entries on epoch boundaries"),
-        (allow_votes_to_directly_update_vote_state::id(), "enable direct vote state update"),
-        (max_tx_account_locks::id(), "enforce max number of locked accounts per transaction"),
-        (require_rent_exempt_accounts::id(), "require all new transaction accounts with data to be rent-exempt"),
-        (filter_votes_outside_slot_hashes::id(), "filter vote slots older than the slot hashes history"),
-        (update_syscall_base_costs::id(), "update syscall base costs"),
-        (stake_deactivate_delinquent_instruction::id(), "enable the deactivate delinquent stake instruction #23932"),
-        (vote_withdraw_authority_may_change_authorized_voter::id(), "vote account withdraw authority may change the authorized voter #22521"),
-        (spl_associated_token_account_v1_0_4::id(), "SPL Associated Token Account Program release version 1.0.4, tied to token 3.3.0 #22648"),
-        (reject_vote_account_close_unless_zero_credit_epoch::id(), "fail vote account withdraw to 0 unless account earned 0 credits in last completed epoch"),
-        (add_get_processed_sibling_instruction_syscall::id(), "add add_get_processed_sibling_instruction_syscall"),
-        (bank_transaction_count_fix::id(), "fixes Bank::transaction_count to include all committed transactions, not just successful ones"),
-        (disable_bpf_deprecated_load_instructions::id(), "disable ldabs* and ldind* SBF instructions"),
-        (disable_bpf_unresolved_symbols_at_runtime::id(), "disable reporting of unresolved SBF symbols at runtime"),
-        (record_instruction_in_transaction_context_push::id(), "move the CPI stack overflow check to the end of push"),
-        (syscall_saturated_math::id(), "syscalls use saturated math"),
-        (check_physical_overlapping::id(), "check physical overlapping regions"),
-        (limit_secp256k1_recovery_id::id(), "limit secp256k1 recovery id"),
-        (disable_deprecated_loader::id(), "disable the deprecated BPF loader"),
-        (check_slice_translation_size::id(), "check size when translating slices"),
-        (stake_split_uses_rent_sysvar::id(), "stake split instruction uses rent sysvar"),
-        (add_get_minimum_delegation_instruction_to_stake_program::id(), "add GetMinimumDelegation instruction to stake program"),
-        (error_on_syscall_bpf_function_hash_collisions::id(), "error on bpf function hash collisions"),
-        (reject_callx_r10::id(), "Reject bpf callx r10 instructions"),
-        (drop_redundant_turbine_path::id(), "drop redundant turbine path"),
-        (executables_incur_cpi_data_cost::id(), "Executables incur CPI data costs"),
-        (fix_recent_blockhashes::id(), "stop adding hashes for skipped slots to recent blockhashes"),
-        (update_rewards_from_cached_accounts::id(), "update rewards from cached accounts"),
-        (spl_token_v3_4_0::id(), "SPL Token Program version 3.4.0 release #24740"),
-        (spl_associated_token_account_v1_1_0::id(), "SPL Associated Token Account Program version 1.1.0 release #24741"),
-        (default_units_per_instruction::id(), "Default max tx-wide compute units calculated per instruction"),
-        (stake_allow_zero_undelegated_amount::id(), "Allow zero-lamport undelegated amount for initialized stakes #24670"),
-        (require_static_program_ids_in_transaction::id(), "require static program ids in versioned transactions"),
-        (stake_raise_minimum_delegation_to_1_sol::id(), "Raise minimum stake delegation to 1.0 SOL #24357"),
-        (stake_minimum_delegation_for_rewards::id(), "stakes must be at least the minimum delegation to earn rewards"),
-        (add_set_compute_unit_price_ix::id(), "add compute budget ix for setting a compute unit price"),
-        (disable_deploy_of_alloc_free_syscall::id(), "disable new deployments of deprecated sol_alloc_free_ syscall"),
-        (include_account_index_in_rent_error::id(), "include account index in rent tx error #25190"),
-        (add_shred_type_to_shred_seed::id(), "add shred-type to shred seed #25556"),
-        (warp_timestamp_with_a_vengeance::id(), "warp timestamp again, adjust bounding to 150% slow #25666"),
-        (separate_nonce_from_blockhash::id(), "separate durable nonce and blockhash domains #25744"),
-        (enable_durable_nonce::id(), "enable durable nonce #25744"),
-        (vote_state_update_credit_per_dequeue::id(), "Calculate vote credits for VoteStateUpdate per vote dequeue to match credit awards for Vote instruction"),
-        (quick_bail_on_panic::id(), "quick bail on panic"),
-        (nonce_must_be_authorized::id(), "nonce must be authorized"),
-        (nonce_must_be_advanceable::id(), "durable nonces must be advanceable"),
-        (vote_authorize_with_seed::id(), "An instruction you can use to change a vote accounts authority when the current authority is a derived key #25860"),
-        (preserve_rent_epoch_for_rent_exempt_accounts::id(), "preserve rent epoch for rent exempt accounts #26479"),
-        (enable_bpf_loader_extend_program_ix::id(), "enable bpf upgradeable loader ExtendProgram instruction #25234"),
-        (skip_rent_rewrites::id(), "skip rewriting rent exempt accounts during rent collection #26491"),
-        (enable_early_verification_of_account_modifications::id(), "enable early verification of account modifications #25899"),
-        (disable_rehash_for_rent_epoch::id(), "on accounts hash calculation, do not try to rehash accounts #28934"),
-        (account_hash_ignore_slot::id(), "ignore slot when calculating an account hash #28420"),
-        (set_exempt_rent_epoch_max::id(), "set rent epoch to Epoch::MAX for rent-exempt accounts #28683"),
-        (on_load_preserve_rent_epoch_for_rent_exempt_accounts::id(), "on bank load account, do not try to fix up rent_epoch #28541"),
-        (prevent_crediting_accounts_that_end_rent_paying::id(), "prevent crediting rent paying accounts #26606"),
-        (cap_bpf_program_instruction_accounts::id(), "enforce max number of accounts per bpf program instruction #26628"),
-        (loosen_cpi_size_restriction::id(), "loosen cpi size restrictions #26641"),
-        (use_default_units_in_fee_calculation::id(), "use default units per instruction in fee calculation #26785"),
-        (compact_vote_state_updates::id(), "Compact vote state updates to lower block size"),
-        (incremental_snapshot_only_incremental_hash_calculation::id(), "only hash accounts in incremental snapshot during incremental snapshot creation #26799"),
-        (disable_cpi_setting_executable_and_rent_epoch::id(), "disable setting is_executable and_rent_epoch in CPI #26987"),
-        (relax_authority_signer_check_for_lookup_table_creation::id(), "relax authority signer check for lookup table creation #27205"),
-        (stop_sibling_instruction_search_at_parent::id(), "stop the search in get_processed_sibling_instruction when the parent instruction is reached #27289"),
-        (vote_state_update_root_fix::id(), "fix root in vote state updates #27361"),
-        (cap_accounts_data_allocations_per_transaction::id(), "cap accounts data allocations per transaction #27375"),
-        (epoch_accounts_hash::id(), "enable epoch accounts hash calculation #27539"),
-        (remove_deprecated_request_unit_ix::id(), "remove support for RequestUnitsDeprecated instruction #27500"),
-        (increase_tx_account_lock_limit::id(), "increase tx account lock limit to 128 #27241"),
-        (limit_max_instruction_trace_length::id(), "limit max instruction trace length #27939"),
-        (check_syscall_outputs_do_not_overlap::id(), "check syscall outputs do_not overlap #28600"),
-        (enable_bpf_loader_set_authority_checked_ix::id(), "enable bpf upgradeable loader SetAuthorityChecked instruction #28424"),
-        (enable_alt_bn128_syscall::id(), "add alt_bn128 syscalls #27961"),
-        (simplify_alt_bn128_syscall_error_codes::id(), "SIMD-0129: simplify alt_bn128 syscall error codes"),
-        (enable_program_redeployment_cooldown::id(), "enable program redeployment cooldown #29135"),
-        (commission_updates_only_allowed_in_first_half_of_epoch::id(), "validator commission updates are only allowed in the first half of an epoch #29362"),
-        (enable_turbine_fanout_experiments::id(), "enable turbine fanout experiments #29393"),
-        (disable_turbine_fanout_experiments::id(), "disable turbine fanout experiments #29393"),
-        (move_serialized_len_ptr_in_cpi::id(), "cpi ignore serialized_len_ptr #29592"),
-        (update_hashes_per_tick::id(), "Update desired hashes per tick on epoch boundary"),
-        (enable_big_mod_exp_syscall::id(), "add big_mod_exp syscall #28503"),
-        (disable_builtin_loader_ownership_chains::id(), "disable builtin loader ownership chains #29956"),
-        (cap_transaction_accounts_data_size::id(), "cap transaction accounts data size up to a limit #27839"),
-        (remove_congestion_multiplier_from_fee_calculation::id(), "Remove congestion multiplier from transaction fee calculation #29881"),
-        (enable_request_heap_frame_ix::id(), "Enable transaction to request heap frame using compute budget instruction #30076"),
-        (prevent_rent_paying_rent_recipients::id(), "prevent recipients of rent rewards from ending in rent-paying state #30151"),
-        (delay_visibility_of_program_deployment::id(), "delay visibility of program upgrades #30085"),
-        (apply_cost_tracker_during_replay::id(), "apply cost tracker to blocks during replay #29595"),
-        (add_set_tx_loaded_accounts_data_size_instruction::id(), "add compute budget instruction for setting account data size per transaction #30366"),
-        (switch_to_new_elf_parser::id(), "switch to new ELF parser #30497"),
-        (round_up_heap_size::id(), "round up heap size when calculating heap cost #30679"),
-        (remove_bpf_loader_incorrect_program_id::id(), "stop incorrectly throwing IncorrectProgramId in bpf_loader #30747"),
-        (include_loaded_accounts_data_size_in_fee_calculation::id(), "include transaction loaded accounts data size in base fee calculation #30657"),
-        (native_programs_consume_cu::id(), "Native program should consume compute units #30620"),
-        (simplify_writable_program_account_check::id(), "Simplify checks performed for writable upgradeable program accounts #30559"),
-        (stop_truncating_strings_in_syscalls::id(), "Stop truncating strings in syscalls #31029"),
-        (clean_up_delegation_errors::id(), "Return InsufficientDelegation instead of InsufficientFunds or InsufficientStake where applicable #31206"),
-        (vote_state_add_vote_latency::id(), "replace Lockout with LandedVote (including vote latency) in vote state #31264"),
-        (checked_arithmetic_in_fee_validation::id(), "checked arithmetic in fee validation #31273"),
-        (bpf_account_data_direct_mapping::id(), "use memory regions to map account data into the rbpf vm instead of copying the data"),
-        (last_restart_slot_sysvar::id(), "enable new sysvar last_restart_slot"),
-        (reduce_stake_warmup_cooldown::id(), "reduce stake warmup cooldown from 25% to 9%"),
-        (revise_turbine_epoch_stakes::id(), "revise turbine epoch stakes"),
-        (enable_poseidon_syscall::id(), "Enable Poseidon syscall"),
-        (timely_vote_credits::id(), "use timeliness of votes in determining credits to award"),
-        (remaining_compute_units_syscall_enabled::id(), "enable the remaining_compute_units syscall"),
-        (enable_loader_v4::id(), "SIMD-0167: Enable Loader-v4"),
-        (require_rent_exempt_split_destination::id(), "Require stake split destination account to be rent exempt"),
-        (better_error_codes_for_tx_lamport_check::id(), "better error codes for tx lamport check #33353"),
-        (enable_alt_bn128_compression_syscall::id(), "add alt_bn128 compression syscalls"),
-        (update_hashes_per_tick2::id(), "Update desired hashes per tick to 2.8M"),
-        (update_hashes_per_tick3::id(), "Update desired hashes per tick to 4.4M"),
-        (update_hashes_per_tick4::id(), "Update desired hashes per tick to 7.6M"),
-        (update_hashes_per_tick5::id(), "Update desired hashes per tick to 9.2M"),
-        (update_hashes_per_tick6::id(), "Update desired hashes per tick to 10M"),
-        (validate_fee_collector_account::id(), "validate fee collector account #33888"),
-        (disable_rent_fees_collection::id(), "Disable rent fees collection #33945"),
-        (enable_zk_transfer_with_fee::id(), "enable Zk Token proof program transfer with fee"),
-        (drop_legacy_shreds::id(), "drops legacy shreds #34328"),
-        (allow_commission_decrease_at_any_time::id(), "Allow commission decrease at any time in epoch #33843"),
-        (consume_blockstore_duplicate_proofs::id(), "consume duplicate proofs from blockstore in consensus #34372"),
-        (add_new_reserved_account_keys::id(), "add new unwritable reserved accounts #34899"),
-        (index_erasure_conflict_duplicate_proofs::id(), "generate duplicate proofs for index and erasure conflicts #34360"),
-        (merkle_conflict_duplicate_proofs::id(), "generate duplicate proofs for merkle root conflicts #34270"),
-        (disable_bpf_loader_instructions::id(), "disable bpf loader management instructions #34194"),
-        (enable_zk_proof_from_account::id(), "Enable zk token proof program to read proof from accounts instead of instruction data #34750"),
-        (curve25519_restrict_msm_length::id(), "restrict curve25519 multiscalar multiplication vector lengths #34763"),
-        (cost_model_requested_write_lock_cost::id(), "cost model uses number of requested write locks #34819"),
-        (enable_gossip_duplicate_proof_ingestion::id(), "enable gossip duplicate proof ingestion #32963"),
-        (enable_chained_merkle_shreds::id(), "Enable chained Merkle shreds #34916"),
-        (remove_rounding_in_fee_calculation::id(), "Removing unwanted rounding in fee calculation #34982"),
-        (deprecate_unused_legacy_vote_plumbing::id(), "Deprecate unused legacy vote tx plumbing"),
-        (enable_tower_sync_ix::id(), "Enable tower sync vote instruction"),
-        (chained_merkle_conflict_duplicate_proofs::id(), "generate duplicate proofs for chained merkle root conflicts"),
-        (reward_full_priority_fee::id(), "Reward full priority fee to validators #34731"),
-        (abort_on_invalid_curve::id(), "SIMD-0137: Abort when elliptic curve syscalls invoked on invalid curve id"),
-        (get_sysvar_syscall_enabled::id(), "Enable syscall for fetching Sysvar bytes #615"),
-        (migrate_feature_gate_program_to_core_bpf::id(), "Migrate Feature Gate program to Core BPF (programify) #1003"),
-        (vote_only_full_fec_sets::id(), "vote only full fec sets"),
-        (migrate_config_program_to_core_bpf::id(), "Migrate Config program to Core BPF #1378"),
-        (enable_get_epoch_stake_syscall::id(), "Enable syscall: sol_get_epoch_stake #884"),
-        (migrate_address_lookup_table_program_to_core_bpf::id(), "Migrate Address Lookup Table program to Core BPF #1651"),
-        (zk_elgamal_proof_program_enabled::id(), "SIMD-0153: Enable ZkElGamalProof program"),
-        (verify_retransmitter_signature::id(), "Verify retransmitter signature #1840"),
-        (move_stake_and_move_lamports_ixs::id(), "Enable MoveStake and MoveLamports stake program instructions #1610"),
-        (ed25519_precompile_verify_strict::id(), "SIMD-0152: Use strict verification in ed25519 precompile"),
-        (vote_only_retransmitter_signed_fec_sets::id(), "vote only on retransmitter signed fec sets"),
-        (move_precompile_verification_to_svm::id(), "SIMD-0159: Move precompile verification into SVM"),
-        (enable_transaction_loading_failure_fees::id(), "SIMD-0082: Enable fees for some additional transaction failures"),
-        (enable_turbine_extended_fanout_experiments::id(), "enable turbine extended fanout experiments #"),
-        (deprecate_legacy_vote_ixs::id(), "Deprecate legacy vote instructions"),
-        (partitioned_epoch_rewards_superfeature::id(), "SIMD-0118: replaces enable_partitioned_epoch_reward to enable partitioned rewards at epoch boundary"),
-        (disable_sbpf_v0_execution::id(), "SIMD-0161: Disables execution of SBPFv0 programs"),
-        (reenable_sbpf_v0_execution::id(), "Re-enables execution of SBPFv0 programs"),
-        (enable_sbpf_v1_deployment_and_execution::id(), "SIMD-0166: Enable deployment and execution of SBPFv1 programs"),
-        (enable_sbpf_v2_deployment_and_execution::id(), "SIMD-0173 and SIMD-0174: Enable deployment and execution of SBPFv2 programs"),
-        (enable_sbpf_v3_deployment_and_execution::id(), "SIMD-0178, SIMD-0179 and SIMD-0189: Enable deployment and execution of SBPFv3 programs"),
-        (remove_accounts_executable_flag_checks::id(), "SIMD-0162: Remove checks of accounts is_executable flag"),
-        (disable_account_loader_special_case::id(), "Disable account loader special case #3513"),
-        (accounts_lt_hash::id(), "SIMD-0215: enables lattice-based accounts hash"),
-        (snapshots_lt_hash::id(), "SIMD-0220: snapshots use lattice-based accounts hash"),
-        (remove_accounts_delta_hash::id(), "SIMD-0223: removes accounts delta hash"),
-        (enable_secp256r1_precompile::id(), "SIMD-0075: Enable secp256r1 precompile"),
-        (migrate_stake_program_to_core_bpf::id(), "SIMD-0196: Migrate Stake program to Core BPF #3655"),
-        (deplete_cu_meter_on_vm_failure::id(), "SIMD-0182: Deplete compute meter for vm errors #3993"),
-        (reserve_minimal_cus_for_builtin_instructions::id(), "SIMD-0170: Reserve minimal CUs for builtin instructions #2562"),
-        (raise_block_limits_to_50m::id(), "SIMD-0207: Raise block limit to 50M"),
-        (fix_alt_bn128_multiplication_input_length::id(), "SIMD-0222: fix alt_bn128 multiplication input length #3686"),
-        (drop_unchained_merkle_shreds::id(), "drops unchained Merkle shreds #2149"),
-        (relax_intrabatch_account_locks::id(), "SIMD-0083: Allow batched transactions to read/write and write/write the same accounts"),
-        (create_slashing_program::id(), "SIMD-0204: creates an enshrined slashing program"),
-        (disable_partitioned_rent_collection::id(), "SIMD-0175: Disable partitioned rent collection #4562"),
-        (enable_vote_address_leader_schedule::id(), "SIMD-0180: Enable vote address leader schedule #4573"),
-        (require_static_nonce_account::id(), "SIMD-0242: Static Nonce Account Only"),
-        (raise_block_limits_to_60m::id(), "SIMD-0256: Raise block limit to 60M"),
-        (mask_out_rent_epoch_in_vm_serialization::id(), "SIMD-0267: Sets rent_epoch to a constant in the VM"),
-        (enshrine_slashing_program::id(), "SIMD-0204: Slashable event verification"),
-        (enable_extend_program_checked::id(), "Enable ExtendProgramChecked instruction"),
-        (formalize_loaded_transaction_data_size::id(), "SIMD-0186: Loaded transaction data size specification"),
-        (alpenglow::id(), "Enable Alpenglow"),
-        (disable_zk_elgamal_proof_program::id(), "Disables zk-elgamal-proof program"),
-        (reenable_zk_elgamal_proof_program::id(), "Re-enables zk-elgamal-proof program"),
-        /*************** ADD NEW FEATURES HERE ***************/
-    ]
-    .iter()
-    .cloned()
-    .collect()
-});
+    #[test]
+    fn test_inherit_subscriptions() {
+        let mut feature_set = FeatureSet::default();
+        feature_set.activate(&pico_inflation::id(), 10);
+        let watcher = FeatureSetWatcher::new(&mut feature_set);
+
+        // A rebuilt feature set only reports the features it newly activates
+        let mut active = feature_set.active().clone();
+        active.insert(blake3_syscall_enabled::id(), 20);
+        let mut rebuilt = FeatureSet::new(active, AHashSet::new());
+        assert!(watcher.take_changes().is_empty());
+        rebuilt.inherit_subscriptions(&feature_set);
+        assert_eq!(
+            watcher.take_changes(),
+            vec![(blake3_syscall_enabled::id(), 20)]
+        );
 
-/// Unique identifier of the current software's feature set
-pub static ID: LazyLock<Hash> = LazyLock::new(|| {
-    let mut hasher = Hasher::default();
-    let mut feature_ids = FEATURE_NAMES.keys().collect::<Vec<_>>();
-    feature_ids.sort();
-    for feature in feature_ids {
-        hasher.hash(feature.as_ref());
+        rebuilt.activate(&secp256k1_program_enabled::id(), 30);
+        assert_eq!(
+            watcher.take_changes(),
+            vec![(secp256k1_program_enabled::id(), 30)]
+        );
     }
-    hasher.result()
-});
-
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub struct FullInflationFeaturePair {
-    pub vote_id: Pubkey, // Feature that grants the candidate the ability to enable full inflation
-    pub enable_id: Pubkey, // Feature to enable full inflation by the candidate
-}
 
-/// Set of feature pairs that once enabled will trigger full inflationi
-pub static FULL_INFLATION_FEATURE_PAIRS: LazyLock<AHashSet<FullInflationFeaturePair>> =
-    LazyLock::new(|| {
-        [FullInflationFeaturePair {
-            vote_id: full_inflation::mainnet::certusone::vote::id(),
-            enable_id: full_inflation::mainnet::certusone::enable::id(),
-        }]
-        .iter()
-        .cloned()
-        .collect()
-    });
+    #[test]
+    fn test_unsubscribe() {
+        let mut feature_set = FeatureSet::default();
+        let recorded = Arc::<Mutex<Vec<Pubkey>>>::default();
+        let handle = feature_set.subscribe({
+            let recorded = recorded.clone();
+            Box::new(move |feature_id, _| recorded.lock().unwrap().push(*feature_id))
+        });
+
+        feature_set.activate(&pico_inflation::id(), 10);
+        assert!(feature_set.unsubscribe(handle));
+        assert!(!feature_set.unsubscribe(handle));
+        feature_set.activate(&blake3_syscall_enabled::id(), 11);
+        assert_eq!(*recorded.lock().unwrap(), vec![pico_inflation::id()]);
+    }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    #[test]
+    fn test_subscribe_deactivation() {
+        let mut feature_set = FeatureSet::default();
+        let activated = Arc::<Mutex<Vec<Pubkey>>>::default();
+        let deactivated = Arc::<Mutex<Vec<Pubkey>>>::default();
+        feature_set.subscribe({
+            let activated = activated.clone();
+            Box::new(move |feature_id, _| activated.lock().unwrap().push(*feature_id))
+        });
+        feature_set.subscribe_deactivation({
+            let deactivated = deactivated.clone();
+            Box::new(move |feature_id| deactivated.lock().unwrap().push(*feature_id))
+        });
+
+        // Deactivating an inactive feature is not a change
+        feature_set.deactivate(&blake3_syscall_enabled::id());
+        feature_set.activate(&pico_inflation::id(), 10);
+        feature_set.deactivate(&pico_inflation::id());
+        assert_eq!(*activated.lock().unwrap(), vec![pico_inflation::id()]);
+        assert_eq!(*deactivated.lock().unwrap(), vec![pico_inflation::id()]);
+    }
 
     #[test]
-    fn test_full_inflation_features_enabled_devnet_and_testnet() {
+    fn test_feature_set_watcher() {
         let mut feature_set = FeatureSet::default();
-        assert!(feature_set.full_inflation_features_enabled().is_empty());
-        feature_set
-            .active
-            .insert(full_inflation::devnet_and_testnet::id(), 42);
+        let watcher = FeatureSetWatcher::new(&mut feature_set);
+        assert!(watcher.take_changes().is_empty());
+
+        feature_set.activate(&pico_inflation::id(), 10);
+        assert_eq!(watcher.take_changes(), vec![(pico_inflation::id(), 10)]);
+        assert!(watcher.take_changes().is_empty());
+
+        // Bulk activations are reported once per feature
+        let mut staged = [
+            blake3_syscall_enabled::id(),
+            secp256k1_program_enabled::id(),
+        ];
+        staged.sort_unstable();
+        for feature_id in &staged {
+            feature_set.stage(feature_id, 20);
+        }
+        feature_set.promote_pending(32);
         assert_eq!(
-            feature_set.full_inflation_features_enabled(),
-            [full_inflation::devnet_and_testnet::id()]
+            watcher.take_changes(),
+            staged
                 .iter()
-                .cloned()
-                .collect()
+                .map(|feature_id| (*feature_id, 32))
+                .collect::<Vec<_>>()
         );
+
+        assert!(feature_set.unsubscribe(watcher.handle()));
+        feature_set.activate(&check_init_vote_data::id(), 40);
+        assert!(watcher.take_changes().is_empty());
     }
 
     #[test]
-    fn test_full_inflation_features_enabled() {
-        // Normal sequence: vote_id then enable_id
-        let mut feature_set = FeatureSet::default();
-        assert!(feature_set.full_inflation_features_enabled().is_empty());
-        feature_set
-            .active
-            .insert(full_inflation::mainnet::certusone::vote::id(), 42);
-        assert!(feature_set.full_inflation_features_enabled().is_empty());
-        feature_set
-            .active
-            .insert(full_inflation::mainnet::certusone::enable::id(), 42);
+    fn test_from_feature_accounts() {
+        let activated_data = |slot: u64| bincode::serialize(&Some(slot)).unwrap();
+        let staged_data = bincode::serialize(&None::<u64>).unwrap();
+        let owner = Some(FEATURE_PROGRAM_ID);
+        let unknown_feature = Pubkey::new_unique();
+        let retired_feature = *RETIRED_FEATURES.keys().next().unwrap();
+        let accounts = [
+            (enable_loader_v4::id(), owner, activated_data(42)),
+            (blake3_syscall_enabled::id(), None, activated_data(7)),
+            (last_restart_slot_sysvar::id(), owner, staged_data.clone()),
+            // Not owned by the feature program.
+            (
+                enable_poseidon_syscall::id(),
+                Some(Pubkey::new_unique()),
+                activated_data(3),
+            ),
+            (unknown_feature, owner, activated_data(5)),
+            (retired_feature, owner, activated_data(5)),
+        ];
+        let (feature_set, errors) = FeatureSet::from_feature_accounts(
+            accounts
+                .iter()
+                .map(|(feature_id, owner, data)| (*feature_id, *owner, data.as_slice())),
+        );
+        assert!(errors.is_empty());
         assert_eq!(
-            feature_set.full_inflation_features_enabled(),
-            [full_inflation::mainnet::certusone::enable::id()]
+            feature_set.active(),
+            &AHashMap::from_iter([
+                (enable_loader_v4::id(), 42),
+                (blake3_syscall_enabled::id(), 7)
+            ])
+        );
+        assert_eq!(
+            feature_set.pending(),
+            &AHashMap::from_iter([(last_restart_slot_sysvar::id(), 0)])
+        );
+        assert!(feature_set
+            .inactive()
+            .contains(&last_restart_slot_sysvar::id()));
+        assert!(feature_set
+            .inactive()
+            .contains(&enable_poseidon_syscall::id()));
+        assert_eq!(
+            feature_set.unknown(),
+            &AHashSet::from_iter([unknown_feature])
+        );
+        assert_eq!(
+            feature_set.retired(),
+            &AHashSet::from_iter([retired_feature])
+        );
+        assert_eq!(
+            feature_set.active().len() + feature_set.inactive().len(),
+            FEATURE_NAMES.len()
+        );
+    }
+
+    #[test]
+    fn test_from_feature_accounts_corrupt() {
+        let activated_data = bincode::serialize(&Some(9u64)).unwrap();
+        let accounts: [(Pubkey, &[u8]); 4] = [
+            (enable_loader_v4::id(), &[]),
+            (blake3_syscall_enabled::id(), &[2, 0, 0, 0, 0, 0, 0, 0, 0]),
+            (last_restart_slot_sysvar::id(), &activated_data[..5]),
+            (enable_poseidon_syscall::id(), &activated_data[..]),
+        ];
+        let (feature_set, errors) = FeatureSet::from_feature_accounts(
+            accounts
                 .iter()
-                .cloned()
-                .collect()
+                .map(|(feature_id, data)| (*feature_id, None, *data)),
+        );
+        // Corrupt accounts do not prevent the others from being read.
+        assert_eq!(
+            feature_set.active(),
+            &AHashMap::from_iter([(enable_poseidon_syscall::id(), 9)])
         );
+        assert!(feature_set.pending().is_empty());
+        assert_eq!(
+            errors,
+            vec![
+                FeatureParseError::AccountTooSmall {
+                    feature_id: enable_loader_v4::id(),
+                    len: 0,
+                },
+                FeatureParseError::InvalidActivationTag {
+                    feature_id: blake3_syscall_enabled::id(),
+                    tag: 2,
+                },
+                FeatureParseError::AccountTooSmall {
+                    feature_id: last_restart_slot_sysvar::id(),
+                    len: 5,
+                },
+            ]
+        );
+    }
 
-        // Backwards sequence: enable_id and then vote_id
+    #[test]
+    fn test_activation_coverage() {
         let mut feature_set = FeatureSet::default();
-        assert!(feature_set.full_inflation_features_enabled().is_empty());
-        feature_set
-            .active
-            .insert(full_inflation::mainnet::certusone::enable::id(), 42);
-        assert!(feature_set.full_inflation_features_enabled().is_empty());
-        feature_set
-            .active
-            .insert(full_inflation::mainnet::certusone::vote::id(), 42);
+        assert_eq!(feature_set.activation_coverage(), 0.0);
+        assert_eq!(feature_set.inactive_supported().len(), FEATURE_NAMES.len());
+
+        // Features unknown to this software are ignored
+        feature_set.activate(&Pubkey::new_unique(), 0);
+        assert_eq!(feature_set.activation_coverage(), 0.0);
+        assert_eq!(feature_set.inactive_supported().len(), FEATURE_NAMES.len());
+
+        feature_set.activate(&pico_inflation::id(), 10);
         assert_eq!(
-            feature_set.full_inflation_features_enabled(),
-            [full_inflation::mainnet::certusone::enable::id()]
-                .iter()
-                .cloned()
-                .collect()
+            feature_set.activation_coverage(),
+            1.0 / FEATURE_NAMES.len() as f64
         );
+        let inactive_supported = feature_set.inactive_supported();
+        assert_eq!(inactive_supported.len(), FEATURE_NAMES.len() - 1);
+        assert!(inactive_supported.is_sorted());
+        assert!(inactive_supported.contains(&(
+            blake3_syscall_enabled::id(),
+            FEATURE_NAMES[&blake3_syscall_enabled::id()]
+        )));
+        assert!(!inactive_supported
+            .iter()
+            .any(|(feature_id, _)| *feature_id == pico_inflation::id()));
+
+        let feature_set = FeatureSet::all_enabled();
+        assert_eq!(feature_set.activation_coverage(), 1.0);
+        assert!(feature_set.inactive_supported().is_empty());
     }
 }
@@ -991,6 +991,26 @@ impl ClusterInfo {
             .collect()
     }
 
+    /// Returns the values inserted since the given cursor which match
+    /// `filter`, along with the number of values which don't.
+    pub(crate) fn get_entries_matching(
+        &self,
+        cursor: &mut Cursor,
+        filter: impl Fn(&CrdsData) -> bool,
+    ) -> (Vec<CrdsValue>, usize) {
+        let gossip_crds = self.gossip.crds.read().unwrap();
+        let mut num_unmatched = 0;
+        let entries = gossip_crds
+            .get_entries(cursor)
+            .filter_map(|entry| {
+                let matched = filter(entry.value.data());
+                num_unmatched += usize::from(!matched);
+                matched.then(|| entry.value.clone())
+            })
+            .collect();
+        (entries, num_unmatched)
+    }
+
     /// Returns duplicate-shreds inserted since the given cursor.
     pub(crate) fn get_duplicate_shreds(&self, cursor: &mut Cursor) -> Vec<DuplicateShred> {
         let gossip_crds = self.gossip.crds.read().unwrap();
@@ -1003,6 +1023,19 @@ impl ClusterInfo {
             .collect()
     }
 
+    /// Returns the duplicate shreds in gossip which originate from the
+    /// given node.
+    pub(crate) fn get_duplicate_shreds_from(&self, pubkey: &Pubkey) -> Vec<DuplicateShred> {
+        let gossip_crds = self.gossip.crds.read().unwrap();
+        gossip_crds
+            .get_records(pubkey)
+            .filter_map(|entry| match entry.value.data() {
+                CrdsData::DuplicateShred(_, dup) => Some(dup.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn get_node_version(&self, pubkey: &Pubkey) -> Option<solana_version::Version> {
         let gossip_crds = self.gossip.crds.read().unwrap();
         gossip_crds
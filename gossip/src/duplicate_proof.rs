@@ -0,0 +1,341 @@
+//! Reassembly of duplicate slot proofs from the chunks gossiped as
+//! DuplicateShred values, without any blockstore or channel side effects.
+
+use {
+    crate::duplicate_shred::{self, check_chunk, DuplicateShred, Error},
+    solana_clock::Slot,
+    solana_ledger::{
+        blockstore_meta::DuplicateSlotProof,
+        shred::{self, Shred},
+    },
+    solana_pubkey::Pubkey,
+    std::collections::{btree_map::Entry, BTreeMap},
+    thiserror::Error,
+};
+
+/// Duplicate slot proof reassembled from its chunks and verified against the
+/// slot leader
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssembledProof {
+    pub slot: Slot,
+    pub shred1: Shred,
+    pub shred2: Shred,
+}
+
+#[derive(Debug, Error)]
+pub enum ProofError {
+    /// Not all chunks of the proof are received yet. If no chunks are given,
+    /// `need` is 1 since the number of chunks is not known.
+    #[error("incomplete proof: {have} of {need} chunks")]
+    Incomplete { have: usize, need: usize },
+    /// Chunks are inconsistent with each other
+    #[error("corrupt proof chunks: {0}")]
+    Corrupt(#[source] Error),
+    /// Chunks are consistent, but do not make a valid duplicate slot proof
+    #[error("invalid duplicate shreds: {0}")]
+    InvalidShreds(#[source] Error),
+}
+
+impl From<ProofError> for Error {
+    fn from(err: ProofError) -> Self {
+        match err {
+            ProofError::Incomplete { .. } => Error::MissingDataChunk,
+            ProofError::Corrupt(err) | ProofError::InvalidShreds(err) => err,
+        }
+    }
+}
+
+/// Reassembles a duplicate slot proof from its chunks, which may be in any
+/// order and include repeated chunks, and verifies both shreds against the
+/// leader of the slot
+pub fn assemble<F>(
+    chunks: Vec<DuplicateShred>,
+    leader_schedule: F,
+    shred_version: u16,
+) -> Result<AssembledProof, ProofError>
+where
+    F: FnOnce(Slot) -> Option<Pubkey>,
+{
+    let Some(first) = chunks.first() else {
+        return Err(ProofError::Incomplete { have: 0, need: 1 });
+    };
+    let (slot, from, wallclock) = (first.slot, first.from, first.wallclock);
+    let num_chunks = first.num_chunks();
+    let check_chunk = check_chunk(slot, num_chunks);
+    let mut indexed = BTreeMap::new();
+    for chunk in chunks {
+        check_chunk(&chunk).map_err(ProofError::Corrupt)?;
+        if chunk.from != from || chunk.wallclock != wallclock {
+            return Err(ProofError::Corrupt(Error::ProofWallclockMismatch));
+        }
+        match indexed.entry(chunk.chunk_index()) {
+            Entry::Vacant(entry) => {
+                entry.insert(chunk);
+            }
+            Entry::Occupied(entry) => {
+                if *entry.get() != chunk {
+                    return Err(ProofError::Corrupt(Error::DataChunkMismatch));
+                }
+            }
+        }
+    }
+    if indexed.len() < usize::from(num_chunks) {
+        return Err(ProofError::Incomplete {
+            have: indexed.len(),
+            need: usize::from(num_chunks),
+        });
+    }
+    let leader =
+        leader_schedule(slot).ok_or(ProofError::InvalidShreds(Error::UnknownSlotLeader(slot)))?;
+    let (shred1, shred2) =
+        duplicate_shred::into_shreds(&leader, indexed.into_values(), shred_version)
+            .map_err(ProofError::InvalidShreds)?;
+    Ok(AssembledProof {
+        slot,
+        shred1,
+        shred2,
+    })
+}
+
+/// Indices of the chunks of a proof split into `num_chunks` chunks which are
+/// not among `have`, in ascending order
+pub fn missing_chunks(have: &[DuplicateShred], num_chunks: usize) -> Vec<u8> {
+    (0..=u8::MAX)
+        .take(num_chunks)
+        .filter(|&chunk_index| !have.iter().any(|chunk| chunk.chunk_index() == chunk_index))
+        .collect()
+}
+
+/// Splits the duplicate slot proof of the shreds with payloads `shred1` and
+/// `shred2` into chunks of up to `chunk_size` bytes, exactly as `from` chunked
+/// it for gossip at `wallclock`, so that chunks a peer is missing can be
+/// published again from the original shreds
+pub fn rechunk(
+    shred1: &[u8],
+    shred2: &[u8],
+    slot: Slot,
+    from: Pubkey,
+    wallclock: u64,
+    chunk_size: usize,
+) -> Result<Vec<DuplicateShred>, Error> {
+    let proof = DuplicateSlotProof {
+        shred1: shred::Payload::from(shred1.to_vec()),
+        shred2: shred::Payload::from(shred2.to_vec()),
+    };
+    duplicate_shred::into_chunks(&proof, slot, from, wallclock, chunk_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            duplicate_shred::{from_shred, tests::new_rand_shred, DUPLICATE_SHRED_HEADER_SIZE},
+            protocol::DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+        },
+        itertools::Itertools,
+        rand::seq::SliceRandom,
+        solana_keypair::Keypair,
+        solana_ledger::shred::Shredder,
+        solana_signer::Signer,
+    };
+
+    const SLOT: Slot = 53084024;
+    const SHRED_VERSION: u16 = 0;
+
+    fn new_shreds(leader: &Keypair) -> (Shred, Shred) {
+        let mut rng = rand::thread_rng();
+        let shredder = Shredder::new(SLOT, SLOT - 1, 0, SHRED_VERSION).unwrap();
+        let next_shred_index = 353;
+        (
+            new_rand_shred(&mut rng, next_shred_index, &shredder, leader),
+            new_rand_shred(&mut rng, next_shred_index, &shredder, leader),
+        )
+    }
+
+    fn new_chunks(
+        (shred1, shred2): &(Shred, Shred),
+        wallclock: u64,
+        max_size: usize,
+    ) -> Vec<DuplicateShred> {
+        from_shred(
+            shred1.clone(),
+            Pubkey::default(), // self_pubkey
+            shred2.payload().clone(),
+            None::<fn(Slot) -> Option<Pubkey>>,
+            wallclock,
+            max_size,
+            SHRED_VERSION,
+        )
+        .unwrap()
+        .collect()
+    }
+
+    #[test]
+    fn test_assemble() {
+        let leader = Keypair::new();
+        let shreds = new_shreds(&leader);
+        let mut chunks = new_chunks(&shreds, 1, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_eq!(chunks.len(), 3);
+        let expected = AssembledProof {
+            slot: SLOT,
+            shred1: shreds.0.clone(),
+            shred2: shreds.1.clone(),
+        };
+        assert_eq!(
+            assemble(chunks.clone(), |_| Some(leader.pubkey()), SHRED_VERSION).unwrap(),
+            expected
+        );
+
+        // Chunks may be in any order, and repeated.
+        chunks.push(chunks[1].clone());
+        chunks.shuffle(&mut rand::thread_rng());
+        assert_eq!(
+            assemble(chunks, |_| Some(leader.pubkey()), SHRED_VERSION).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_assemble_incomplete() {
+        let leader = Keypair::new();
+        let chunks = new_chunks(&new_shreds(&leader), 1, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_matches!(
+            assemble(vec![], |_| Some(leader.pubkey()), SHRED_VERSION),
+            Err(ProofError::Incomplete { have: 0, need: 1 })
+        );
+        // Repeated chunks do not count towards completion.
+        let partial = vec![chunks[2].clone(), chunks[0].clone(), chunks[2].clone()];
+        assert_matches!(
+            assemble(partial, |_| Some(leader.pubkey()), SHRED_VERSION),
+            Err(ProofError::Incomplete { have: 2, need: 3 })
+        );
+    }
+
+    #[test]
+    fn test_assemble_corrupt() {
+        let leader = Keypair::new();
+        let shreds = new_shreds(&leader);
+        let chunks = new_chunks(&shreds, 1, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        let assemble = |chunks: Vec<DuplicateShred>| {
+            assemble(chunks, |_| Some(leader.pubkey()), SHRED_VERSION)
+        };
+
+        // Chunks declaring different numbers of chunks.
+        let other = new_chunks(&shreds, 1, 2 * DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_ne!(other.len(), chunks.len());
+        let mixed = vec![chunks[0].clone(), other[1].clone(), chunks[2].clone()];
+        assert_matches!(
+            assemble(mixed),
+            Err(ProofError::Corrupt(Error::NumChunksMismatch))
+        );
+
+        // Chunks of different versions of the proof.
+        let other = new_chunks(&shreds, 2, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        let mixed = vec![chunks[0].clone(), other[1].clone(), chunks[2].clone()];
+        assert_matches!(
+            assemble(mixed),
+            Err(ProofError::Corrupt(Error::ProofWallclockMismatch))
+        );
+
+        // Chunks with the same index but different data.
+        let other = new_chunks(&new_shreds(&leader), 1, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        let mixed = chunks.iter().chain(&other[..1]).cloned().collect_vec();
+        assert_matches!(
+            assemble(mixed),
+            Err(ProofError::Corrupt(Error::DataChunkMismatch))
+        );
+    }
+
+    #[test]
+    fn test_assemble_invalid_shreds() {
+        let leader = Keypair::new();
+        let chunks = new_chunks(&new_shreds(&leader), 1, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_matches!(
+            assemble(chunks.clone(), |_| None, SHRED_VERSION),
+            Err(ProofError::InvalidShreds(Error::UnknownSlotLeader(SLOT)))
+        );
+        assert_matches!(
+            assemble(
+                chunks.clone(),
+                |_| Some(Pubkey::new_unique()),
+                SHRED_VERSION
+            ),
+            Err(ProofError::InvalidShreds(Error::InvalidSignature))
+        );
+        assert_matches!(
+            assemble(chunks, |_| Some(leader.pubkey()), SHRED_VERSION + 1),
+            Err(ProofError::InvalidShreds(Error::InvalidShredVersion(_)))
+        );
+    }
+
+    #[test]
+    fn test_missing_chunks() {
+        let leader = Keypair::new();
+        let chunks = new_chunks(&new_shreds(&leader), 1, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_eq!(missing_chunks(&[], 3), vec![0, 1, 2]);
+        assert_eq!(missing_chunks(&chunks[1..2], 3), vec![0, 2]);
+        assert_eq!(
+            missing_chunks(&[chunks[2].clone(), chunks[2].clone()], 3),
+            vec![0, 1]
+        );
+        assert!(missing_chunks(&chunks, 3).is_empty());
+        assert!(missing_chunks(&[], 0).is_empty());
+        assert_eq!(missing_chunks(&[], 1000).len(), 256);
+    }
+
+    #[test]
+    fn test_rechunk() {
+        let leader = Keypair::new();
+        let shreds = new_shreds(&leader);
+        let wallclock = 1_700_000_000_000;
+        let chunk_size = DUPLICATE_SHRED_MAX_PAYLOAD_SIZE - DUPLICATE_SHRED_HEADER_SIZE;
+        let rechunk = |chunk_size| {
+            rechunk(
+                shreds.0.payload().as_ref(),
+                shreds.1.payload().as_ref(),
+                SLOT,
+                Pubkey::default(),
+                wallclock,
+                chunk_size,
+            )
+        };
+
+        // Reproduces the chunks published to gossip.
+        let chunks = new_chunks(&shreds, wallclock, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_eq!(rechunk(chunk_size).unwrap(), chunks);
+        assert_eq!(rechunk(chunk_size).unwrap(), rechunk(chunk_size).unwrap());
+
+        // A proof missing chunks is completed by the chunks rechunked.
+        let have = vec![chunks[1].clone()];
+        let missing = missing_chunks(&have, chunks.len());
+        let resent = rechunk(chunk_size)
+            .unwrap()
+            .into_iter()
+            .filter(|chunk| missing.contains(&chunk.chunk_index()));
+        let proof = have.into_iter().chain(resent).collect_vec();
+        assert_eq!(
+            assemble(proof, |_| Some(leader.pubkey()), SHRED_VERSION).unwrap(),
+            AssembledProof {
+                slot: SLOT,
+                shred1: shreds.0.clone(),
+                shred2: shreds.1.clone(),
+            }
+        );
+
+        // Other chunk sizes round-trip through into_shreds.
+        for chunk_size in [chunk_size / 2, 4 * chunk_size] {
+            let chunks = rechunk(chunk_size).unwrap();
+            assert!(chunks
+                .iter()
+                .all(|chunk| chunk.chunk_index() < chunk.num_chunks()));
+            assert_eq!(
+                duplicate_shred::into_shreds(&leader.pubkey(), chunks, SHRED_VERSION).unwrap(),
+                shreds
+            );
+        }
+
+        assert_matches!(rechunk(0), Err(Error::InvalidSizeLimit));
+        assert_matches!(rechunk(1), Err(Error::TryFromIntError(_)));
+    }
+}
@@ -17,7 +17,7 @@ use {
     thiserror::Error,
 };
 
-const DUPLICATE_SHRED_HEADER_SIZE: usize = 63;
+pub(crate) const DUPLICATE_SHRED_HEADER_SIZE: usize = 63;
 
 pub(crate) type DuplicateShredIndex = u16;
 pub(crate) const MAX_DUPLICATE_SHREDS: DuplicateShredIndex = 512;
@@ -80,6 +80,8 @@ pub enum Error {
     InvalidShred(#[from] shred::Error),
     #[error("number of chunks mismatch")]
     NumChunksMismatch,
+    #[error("chunks from different proofs")]
+    ProofWallclockMismatch,
     #[error("missing data chunk")]
     MissingDataChunk,
     #[error("(de)serialization error")]
@@ -113,6 +115,7 @@ impl Error {
             | Self::InvalidSizeLimit
             | Self::InvalidShred(_)
             | Self::NumChunksMismatch
+            | Self::ProofWallclockMismatch
             | Self::MissingDataChunk
             | Self::SerializationError(_)
             | Self::TryFromIntError(_)
@@ -228,33 +231,53 @@ where
         shred1: shred.into_payload(),
         shred2: other_shred.into_payload(),
     };
-    let data = bincode::serialize(&proof)?;
     let chunk_size = if DUPLICATE_SHRED_HEADER_SIZE < max_size {
         max_size - DUPLICATE_SHRED_HEADER_SIZE
     } else {
         return Err(Error::InvalidSizeLimit);
     };
-    let chunks: Vec<_> = data.chunks(chunk_size).map(Vec::from).collect();
-    let num_chunks = u8::try_from(chunks.len())?;
-    let chunks = chunks
-        .into_iter()
+    let chunks = into_chunks(&proof, slot, self_pubkey, wallclock, chunk_size)?;
+    Ok(chunks.into_iter())
+}
+
+// Splits the serialized proof into DuplicateShred values carrying up to
+// chunk_size bytes each. The chunks depend only on the arguments, so that the
+// same proof can be chunked again identically.
+pub(crate) fn into_chunks(
+    proof: &DuplicateSlotProof,
+    slot: Slot,
+    self_pubkey: Pubkey,
+    wallclock: u64,
+    chunk_size: usize,
+) -> Result<Vec<DuplicateShred>, Error> {
+    if chunk_size == 0 {
+        return Err(Error::InvalidSizeLimit);
+    }
+    let data = bincode::serialize(proof)?;
+    let num_chunks = u8::try_from(data.len().div_ceil(chunk_size))?;
+    let chunks = data
+        .chunks(chunk_size)
         .enumerate()
-        .map(move |(i, chunk)| DuplicateShred {
+        .map(|(i, chunk)| DuplicateShred {
             from: self_pubkey,
             wallclock,
             slot,
             num_chunks,
             chunk_index: i as u8,
-            chunk,
+            chunk: chunk.to_vec(),
             _unused: 0,
             _unused_shred_type: ShredType::Code.into(),
-        });
+        })
+        .collect();
     Ok(chunks)
 }
 
 // Returns a predicate checking if a duplicate-shred chunk matches
 // the slot and has valid chunk_index.
-fn check_chunk(slot: Slot, num_chunks: u8) -> impl Fn(&DuplicateShred) -> Result<(), Error> {
+pub(crate) fn check_chunk(
+    slot: Slot,
+    num_chunks: u8,
+) -> impl Fn(&DuplicateShred) -> Result<(), Error> {
     move |dup| {
         if dup.slot != slot {
             Err(Error::SlotMismatch)
@@ -1,18 +1,35 @@
 use {
     crate::{
-        duplicate_shred::{self, DuplicateShred, Error},
+        duplicate_proof::{self, AssembledProof, ProofError},
+        duplicate_shred::{DuplicateShred, Error},
         duplicate_shred_listener::DuplicateShredHandlerTrait,
     },
-    crossbeam_channel::Sender,
+    agave_feature_set as feature_set,
+    crossbeam_channel::{unbounded, Receiver, Sender},
     log::error,
+    rayon::{ThreadPool, ThreadPoolBuilder},
     solana_clock::{Epoch, Slot},
-    solana_ledger::{blockstore::Blockstore, leader_schedule_cache::LeaderScheduleCache},
+    solana_ledger::{
+        blockstore::Blockstore,
+        blockstore_meta::DuplicateSlotProvenance,
+        leader_schedule_cache::LeaderScheduleCache,
+        leader_schedule_utils,
+        shred::{Shred, ShredType},
+    },
     solana_pubkey::Pubkey,
     solana_runtime::bank_forks::BankForks,
+    solana_time_utils::timestamp,
     std::{
         cmp::Reverse,
-        collections::HashMap,
-        sync::{Arc, RwLock},
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+        fs, io,
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        time::{Duration, Instant},
     },
 };
 
@@ -23,15 +40,718 @@ const MAX_NUM_CHUNKS: usize = 3;
 const MAX_NUM_ENTRIES_PER_PUBKEY: usize = 128;
 const BUFFER_CAPACITY: usize = 512 * MAX_NUM_ENTRIES_PER_PUBKEY;
 
+// Incomplete proofs and tombstones older than this are dropped.
+const DEFAULT_PROOF_CHUNK_MAX_AGE: Duration = Duration::from_secs(300);
+// Limit how often the chunk cache is scanned for expired entries.
+const PROOF_CHUNK_EXPIRY_INTERVAL: Duration = Duration::from_secs(1);
+// Incomplete proofs pending for longer than this are logged with the indices
+// of the chunks still missing.
+const PROOF_CHUNK_PENDING_REPORT_AGE: Duration = Duration::from_secs(30);
+
+// A peer is muted once it sends more than this many invalid proofs within
+// DEFAULT_PEER_PENALTY_WINDOW.
+const DEFAULT_PEER_PENALTY_THRESHOLD: usize = 8;
+const DEFAULT_PEER_PENALTY_WINDOW: Duration = Duration::from_secs(60);
+// How long chunks from a muted peer are dropped for.
+const DEFAULT_PEER_MUTE_COOLDOWN: Duration = Duration::from_secs(600);
+// Limit how often muted peers are logged and stale penalties are dropped.
+const PEER_PENALTY_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+// Limit number of duplicate slots waiting for shreds before replay is
+// notified.
+const MAX_PARKED_DUPLICATE_SLOTS: usize = 512;
+// Limit number of slots remembered as having an ingested proof.
+const MAX_INGESTED_SLOTS: usize = 512;
+// How often ingestion stats are reported as a datapoint.
+const STATS_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+// Directory under the ledger path where incomplete proofs are persisted.
+const DUPLICATE_PROOF_CHUNKS_DIR: &str = "duplicate_proof_chunks";
+// Limit total size of the chunks persisted across restarts.
+const MAX_PERSISTED_PROOF_CHUNKS_SIZE: u64 = 16 * 1024 * 1024;
+/// Number of threads verifying the signatures of complete proofs.
+pub const DEFAULT_PROOF_VERIFICATION_THREADS: usize = 2;
+// Once more proofs than this per verification thread are being verified, the
+// listener waits for the oldest ones.
+const MAX_PROOFS_IN_FLIGHT_PER_THREAD: usize = 16;
+
 type BufferEntry = [Option<DuplicateShred>; MAX_NUM_CHUNKS];
 
+struct ProofChunks {
+    chunks: BufferEntry,
+    first_received: Instant,
+    // Whether the proof was logged as long pending.
+    reported_pending: bool,
+}
+
+// Chunks of the duplicate shred proofs being pieced together, keyed by slot
+// and the node which sent them. Once a proof is reassembled it is tombstoned,
+// whether or not the proof turns out valid, so that retransmitted chunks do
+// not reassemble it again.
+struct ProofChunkCache {
+    entries: HashMap<(Slot, Pubkey), ProofChunks>,
+    tombstones: HashMap<(Slot, Pubkey), Instant>,
+    max_age: Duration,
+    last_expiry: Instant,
+    last_pending_report: Instant,
+    // If set, chunks of incomplete proofs are persisted to survive a restart.
+    store: Option<ProofChunkStore>,
+}
+
+impl ProofChunkCache {
+    fn new(max_age: Duration) -> Self {
+        Self {
+            entries: HashMap::default(),
+            tombstones: HashMap::default(),
+            max_age,
+            last_expiry: Instant::now(),
+            last_pending_report: Instant::now(),
+            store: None,
+        }
+    }
+
+    // Adds the chunk, returning true if it completes its proof. Chunks
+    // already received, or of tombstoned proofs, are dropped.
+    // The first chunk received for a proof determines its num_chunks and
+    // wallclock, and chunks which disagree are rejected, so that a proof is
+    // only reassembled from a consistent set of chunks indexed 0..num_chunks.
+    // The caller must check that chunk_index < num_chunks <= MAX_NUM_CHUNKS.
+    fn insert(&mut self, chunk: DuplicateShred, now: Instant) -> Result<bool, Error> {
+        let key = (chunk.slot, chunk.from);
+        if self.tombstones.contains_key(&key) {
+            inc_new_counter_debug!("duplicate_shred_handler-tombstoned_chunk", 1);
+            return Ok(false);
+        }
+        let num_chunks = usize::from(chunk.num_chunks());
+        let entry = self.entries.entry(key).or_insert_with(|| ProofChunks {
+            chunks: BufferEntry::default(),
+            first_received: now,
+            reported_pending: false,
+        });
+        if let Some(other) = entry.chunks.iter().flatten().next() {
+            if other.num_chunks() != chunk.num_chunks() {
+                inc_new_counter_debug!("duplicate_shred_handler-inconsistent_chunk", 1);
+                return Err(Error::NumChunksMismatch);
+            }
+            if other.wallclock != chunk.wallclock {
+                inc_new_counter_debug!("duplicate_shred_handler-inconsistent_chunk", 1);
+                return Err(Error::ProofWallclockMismatch);
+            }
+        }
+        let Some(stored) = entry.chunks.get_mut(usize::from(chunk.chunk_index())) else {
+            return Ok(false);
+        };
+        if stored.is_some() {
+            inc_new_counter_debug!("duplicate_shred_handler-duplicate_chunk", 1);
+            return Ok(false);
+        }
+        *stored = Some(chunk);
+        if let Some(store) = &mut self.store {
+            store.write(key, entry.chunks.iter().flatten().collect());
+        }
+        Ok(entry.chunks.iter().flatten().count() == num_chunks)
+    }
+
+    // Removes the chunks of a proof to reassemble it, and tombstones it.
+    fn take(&mut self, key: (Slot, Pubkey), now: Instant) -> Option<ProofChunks> {
+        let entry = self.entries.remove(&key)?;
+        self.tombstones.insert(key, now);
+        if let Some(store) = &mut self.store {
+            store.remove(&key);
+        }
+        Some(entry)
+    }
+
+    // Deletes persisted chunks of proofs which are no longer cached.
+    fn forget_evicted(&mut self) {
+        if let Some(store) = &mut self.store {
+            store.retain(|key| self.entries.contains_key(key));
+        }
+    }
+
+    // Drops proofs and tombstones for slots at or below the root, regardless
+    // of when entries were last expired.
+    fn purge_rooted(&mut self, root: Slot) {
+        let num_entries = self.entries.len();
+        self.entries.retain(|&(slot, _), _| slot > root);
+        report_evicted_incomplete_proofs(num_entries.saturating_sub(self.entries.len()));
+        self.forget_evicted();
+        self.tombstones.retain(|&(slot, _), _| slot > root);
+    }
+
+    // Drops proofs for slots at or below the root, and incomplete proofs and
+    // tombstones older than max_age.
+    fn expire(&mut self, root: Slot, now: Instant) {
+        if now.saturating_duration_since(self.last_expiry) < PROOF_CHUNK_EXPIRY_INTERVAL {
+            return;
+        }
+        self.last_expiry = now;
+        let max_age = self.max_age;
+        let num_entries = self.entries.len();
+        self.entries.retain(|&(slot, _), entry| {
+            slot > root && now.saturating_duration_since(entry.first_received) < max_age
+        });
+        report_evicted_incomplete_proofs(num_entries.saturating_sub(self.entries.len()));
+        self.forget_evicted();
+        self.tombstones.retain(|&(slot, _), &mut tombstoned| {
+            slot > root && now.saturating_duration_since(tombstoned) < max_age
+        });
+    }
+
+    // Logs the indices of the chunks still missing from proofs pending for
+    // longer than PROOF_CHUNK_PENDING_REPORT_AGE, once per proof. Returns the
+    // proofs logged along with their missing chunks.
+    fn report_long_pending(&mut self, now: Instant) -> Vec<((Slot, Pubkey), Vec<u8>)> {
+        if now.saturating_duration_since(self.last_pending_report) < PROOF_CHUNK_EXPIRY_INTERVAL {
+            return Vec::default();
+        }
+        self.last_pending_report = now;
+        let mut reported = Vec::new();
+        for (&(slot, pubkey), entry) in &mut self.entries {
+            let pending = now.saturating_duration_since(entry.first_received);
+            if entry.reported_pending || pending < PROOF_CHUNK_PENDING_REPORT_AGE {
+                continue;
+            }
+            entry.reported_pending = true;
+            let chunks: Vec<DuplicateShred> = entry.chunks.iter().flatten().cloned().collect();
+            let Some(num_chunks) = chunks.first().map(DuplicateShred::num_chunks) else {
+                continue;
+            };
+            let missing = duplicate_proof::missing_chunks(&chunks, usize::from(num_chunks));
+            info!(
+                "Duplicate proof for slot {slot} from {pubkey} pending for {pending:?}, waiting \
+                 on chunks {missing:?} of {num_chunks}"
+            );
+            reported.push(((slot, pubkey), missing));
+        }
+        reported
+    }
+}
+
+#[derive(Default)]
+struct PeerPenalty {
+    // When each invalid proof within the current window was received.
+    penalties: VecDeque<Instant>,
+    muted_until: Option<Instant>,
+}
+
+// Tracks peers sending invalid duplicate shred proofs, so that the cost of
+// verifying their chunks can be skipped once they exceed the threshold.
+struct PeerPenalties {
+    peers: HashMap<Pubkey, PeerPenalty>,
+    threshold: usize,
+    window: Duration,
+    cooldown: Duration,
+    last_report: Instant,
+}
+
+impl PeerPenalties {
+    fn new(threshold: usize, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            peers: HashMap::default(),
+            threshold,
+            window,
+            cooldown,
+            last_report: Instant::now(),
+        }
+    }
+
+    fn is_muted(&mut self, pubkey: &Pubkey, now: Instant) -> bool {
+        let Some(peer) = self.peers.get_mut(pubkey) else {
+            return false;
+        };
+        match peer.muted_until {
+            Some(muted_until) if now < muted_until => true,
+            Some(_) => {
+                peer.muted_until = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    // Records an invalid proof from the peer, muting it if it has sent more
+    // than threshold of them within the window.
+    fn penalize(&mut self, pubkey: Pubkey, now: Instant) {
+        let peer = self.peers.entry(pubkey).or_default();
+        while peer
+            .penalties
+            .front()
+            .is_some_and(|&penalty| now.saturating_duration_since(penalty) >= self.window)
+        {
+            peer.penalties.pop_front();
+        }
+        peer.penalties.push_back(now);
+        if peer.penalties.len() > self.threshold {
+            inc_new_counter_info!("duplicate_shred_handler-muted_peer", 1);
+            peer.penalties.clear();
+            peer.muted_until = Some(now + self.cooldown);
+        }
+    }
+
+    // Periodically logs muted peers and drops penalties which have aged out.
+    fn maybe_report(&mut self, now: Instant) {
+        if now.saturating_duration_since(self.last_report) < PEER_PENALTY_REPORT_INTERVAL {
+            return;
+        }
+        self.last_report = now;
+        self.peers.retain(|_, peer| {
+            peer.muted_until
+                .is_some_and(|muted_until| now < muted_until)
+                || peer
+                    .penalties
+                    .back()
+                    .is_some_and(|&penalty| now.saturating_duration_since(penalty) < self.window)
+        });
+        let muted: Vec<_> = self
+            .peers
+            .iter()
+            .filter(|(_, peer)| {
+                peer.muted_until
+                    .is_some_and(|muted_until| now < muted_until)
+            })
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+        if !muted.is_empty() {
+            info!(
+                "Dropping duplicate shred proofs from {} peers which sent invalid proofs: {muted:?}",
+                muted.len()
+            );
+        }
+    }
+}
+
+// Persists chunks of incomplete proofs under the ledger path, one file per
+// proof, so that they are not lost if the node restarts before the proof
+// completes.
+struct ProofChunkStore {
+    dir: PathBuf,
+    // Size of each persisted proof's file.
+    sizes: HashMap<(Slot, Pubkey), u64>,
+    total_size: u64,
+}
+
+impl ProofChunkStore {
+    // Opens the store, returning the chunks persisted for slots above the
+    // root. Chunks at or below the root, or over the size limit, are deleted.
+    fn open(dir: PathBuf, root: Slot) -> io::Result<(Self, Vec<DuplicateShred>)> {
+        fs::create_dir_all(&dir)?;
+        let mut files: Vec<_> = fs::read_dir(&dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                Some((Self::parse_key(&path)?, path))
+            })
+            .collect();
+        files.sort_unstable();
+        let mut store = Self {
+            dir,
+            sizes: HashMap::default(),
+            total_size: 0,
+        };
+        let mut chunks = Vec::new();
+        for (key, path) in files {
+            let proof = (key.0 > root)
+                .then(|| fs::read(&path).ok())
+                .flatten()
+                .filter(|bytes| {
+                    store.total_size.saturating_add(bytes.len() as u64)
+                        <= MAX_PERSISTED_PROOF_CHUNKS_SIZE
+                })
+                .and_then(|bytes| {
+                    let proof: Vec<DuplicateShred> = bincode::deserialize(&bytes).ok()?;
+                    Some((bytes.len() as u64, proof))
+                })
+                .filter(|(_, proof)| {
+                    proof.iter().all(|chunk| {
+                        (chunk.slot, chunk.from) == key
+                            && usize::from(chunk.num_chunks()) <= MAX_NUM_CHUNKS
+                            && chunk.chunk_index() < chunk.num_chunks()
+                    })
+                });
+            match proof {
+                None => {
+                    if let Err(err) = fs::remove_file(&path) {
+                        error!("Unable to delete duplicate proof chunks {path:?}: {err:?}");
+                    }
+                }
+                Some((size, proof)) => {
+                    store.sizes.insert(key, size);
+                    store.total_size = store.total_size.saturating_add(size);
+                    chunks.extend(proof);
+                }
+            }
+        }
+        Ok((store, chunks))
+    }
+
+    fn path(&self, (slot, pubkey): &(Slot, Pubkey)) -> PathBuf {
+        self.dir.join(format!("{slot}-{pubkey}.bin"))
+    }
+
+    fn parse_key(path: &Path) -> Option<(Slot, Pubkey)> {
+        let name = path.file_name()?.to_str()?.strip_suffix(".bin")?;
+        let (slot, pubkey) = name.split_once('-')?;
+        Some((slot.parse().ok()?, Pubkey::from_str(pubkey).ok()?))
+    }
+
+    // Replaces the persisted chunks of the proof.
+    fn write(&mut self, key: (Slot, Pubkey), chunks: Vec<&DuplicateShred>) {
+        let bytes = match bincode::serialize(&chunks) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                error!("Unable to serialize duplicate proof chunks: {err:?}");
+                return;
+            }
+        };
+        let size = bytes.len() as u64;
+        let old_size = self.sizes.get(&key).copied().unwrap_or_default();
+        let total_size = self
+            .total_size
+            .saturating_sub(old_size)
+            .saturating_add(size);
+        if total_size > MAX_PERSISTED_PROOF_CHUNKS_SIZE {
+            inc_new_counter_debug!("duplicate_shred_handler-persisted_chunks_full", 1);
+            return;
+        }
+        let path = self.path(&key);
+        let tmp_path = path.with_extension("tmp");
+        if let Err(err) = fs::write(&tmp_path, bytes).and_then(|()| fs::rename(&tmp_path, &path)) {
+            error!("Unable to persist duplicate proof chunks {path:?}: {err:?}");
+            return;
+        }
+        self.sizes.insert(key, size);
+        self.total_size = total_size;
+    }
+
+    fn remove(&mut self, key: &(Slot, Pubkey)) {
+        let Some(size) = self.sizes.remove(key) else {
+            return;
+        };
+        self.total_size = self.total_size.saturating_sub(size);
+        let path = self.path(key);
+        if let Err(err) = fs::remove_file(&path) {
+            error!("Unable to delete duplicate proof chunks {path:?}: {err:?}");
+        }
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&(Slot, Pubkey)) -> bool) {
+        let evicted: Vec<_> = self
+            .sizes
+            .keys()
+            .filter(|key| !keep(key))
+            .copied()
+            .collect();
+        for key in evicted {
+            self.remove(&key);
+        }
+    }
+}
+
+/// Sent to subscribers when a slot is first marked duplicate by a proof
+/// received through gossip
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateSlotProofNotification {
+    pub slot: Slot,
+    /// Node which sent the proof
+    pub origin: Pubkey,
+    pub shred1_index: u32,
+    pub shred1_type: ShredType,
+    pub shred2_index: u32,
+    pub shred2_type: ShredType,
+    /// Wallclock in milliseconds when the slot was marked duplicate
+    pub timestamp: u64,
+}
+
+impl DuplicateSlotProofNotification {
+    fn new(origin: Pubkey, shred1: &Shred, shred2: &Shred) -> Self {
+        Self {
+            slot: shred1.slot(),
+            origin,
+            shred1_index: shred1.index(),
+            shred1_type: shred1.shred_type(),
+            shred2_index: shred2.index(),
+            shred2_type: shred2.shred_type(),
+            timestamp: timestamp(),
+        }
+    }
+}
+
+/// Evidence of a duplicate block, written by `DuplicateShredHandler` for
+/// tools outside the validator, see `read_evidence()`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DuplicateSlotEvidence {
+    pub slot: Slot,
+    /// Leader of the slot, which signed both shreds
+    pub leader: Pubkey,
+    #[serde(with = "serde_bytes")]
+    pub shred1: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    pub shred2: Vec<u8>,
+    /// Node which sent the proof through gossip
+    pub origin: Pubkey,
+    /// Wallclock in milliseconds when the proof was ingested
+    pub timestamp: u64,
+}
+
+impl DuplicateSlotEvidence {
+    fn new(leader: Pubkey, origin: Pubkey, shred1: &Shred, shred2: &Shred) -> Self {
+        Self {
+            slot: shred1.slot(),
+            leader,
+            shred1: shred1.payload().to_vec(),
+            shred2: shred2.payload().to_vec(),
+            origin,
+            timestamp: timestamp(),
+        }
+    }
+
+    /// Path of the bincode evidence of the slot under `dir`. The JSON
+    /// evidence is next to it, with an additional `.json` extension.
+    pub fn path(dir: &Path, slot: Slot, leader: &Pubkey) -> PathBuf {
+        dir.join(format!("duplicate-{slot}-{leader}.evidence"))
+    }
+
+    // Writes the bincode and JSON evidence under dir, unless evidence of the
+    // slot is already there. Returns whether the evidence was written.
+    fn write(&self, dir: &Path) -> io::Result<bool> {
+        let path = Self::path(dir, self.slot, &self.leader);
+        if path.exists() {
+            return Ok(false);
+        }
+        fs::create_dir_all(dir)?;
+        let json = serde_json::to_vec_pretty(self)?;
+        let bytes = bincode::serialize(self).map_err(io::Error::other)?;
+        // The bincode evidence goes last, so that it only exists once both do.
+        for (path, bytes) in [(path.with_extension("evidence.json"), json), (path, bytes)] {
+            let tmp_path = path.with_extension("tmp");
+            fs::write(&tmp_path, bytes)?;
+            fs::rename(&tmp_path, &path)?;
+        }
+        Ok(true)
+    }
+}
+
+/// Reads evidence written by `DuplicateShredHandler`, either the bincode
+/// evidence or its JSON counterpart
+pub fn read_evidence(path: &Path) -> io::Result<DuplicateSlotEvidence> {
+    let bytes = fs::read(path)?;
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        Ok(serde_json::from_slice(&bytes)?)
+    } else {
+        bincode::deserialize(&bytes).map_err(io::Error::other)
+    }
+}
+
+pub type DuplicateSlotSubscribers = Arc<RwLock<Vec<Sender<DuplicateSlotProofNotification>>>>;
+
+/// Counts of duplicate shred proof ingestion outcomes since the handler was
+/// created
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DuplicateShredHandlerStats {
+    pub chunks_received: u64,
+    /// Proofs reassembled from all their chunks which passed validation
+    pub proofs_completed: u64,
+    /// Proofs with invalid chunks, or which failed validation
+    pub proofs_invalid: u64,
+    /// Proofs still waiting for chunks
+    pub proofs_pending: u64,
+    /// Completed proofs for slots which already had a duplicate proof stored
+    pub slots_already_marked_duplicate: u64,
+    /// Chunks dropped because their slot is at or below the root
+    pub proofs_below_root: u64,
+    /// Total time from the first to the last chunk of completed proofs
+    pub assembly_latency_us: u64,
+}
+
+impl DuplicateShredHandlerStats {
+    fn report(&self, last_report: &Self) {
+        let proofs_completed = self
+            .proofs_completed
+            .saturating_sub(last_report.proofs_completed);
+        let assembly_latency_us = self
+            .assembly_latency_us
+            .saturating_sub(last_report.assembly_latency_us);
+        datapoint_info!(
+            "duplicate_shred_handler_stats",
+            (
+                "chunks_received",
+                self.chunks_received
+                    .saturating_sub(last_report.chunks_received),
+                i64
+            ),
+            ("proofs_completed", proofs_completed, i64),
+            (
+                "proofs_invalid",
+                self.proofs_invalid
+                    .saturating_sub(last_report.proofs_invalid),
+                i64
+            ),
+            ("proofs_pending", self.proofs_pending, i64),
+            (
+                "slots_already_marked_duplicate",
+                self.slots_already_marked_duplicate
+                    .saturating_sub(last_report.slots_already_marked_duplicate),
+                i64
+            ),
+            (
+                "proofs_below_root",
+                self.proofs_below_root
+                    .saturating_sub(last_report.proofs_below_root),
+                i64
+            ),
+            (
+                "mean_assembly_latency_us",
+                assembly_latency_us
+                    .checked_div(proofs_completed)
+                    .unwrap_or_default(),
+                i64
+            ),
+        );
+    }
+}
+
+fn report_evicted_incomplete_proofs(count: usize) {
+    if count > 0 {
+        inc_new_counter_info!("duplicate_shred_handler-evicted_incomplete_proofs", count);
+    }
+}
+
+// Complete proof whose signatures are not verified yet.
+struct UnverifiedProof {
+    key: (Slot, Pubkey),
+    leader: Pubkey,
+    wallclock: u64,
+    first_received: Instant,
+    chunks: Vec<DuplicateShred>,
+}
+
+struct VerifiedProof {
+    key: (Slot, Pubkey),
+    leader: Pubkey,
+    wallclock: u64,
+    first_received: Instant,
+    result: Result<AssembledProof, ProofError>,
+}
+
+impl UnverifiedProof {
+    fn verify(self, shred_version: u16) -> VerifiedProof {
+        let Self {
+            key,
+            leader,
+            wallclock,
+            first_received,
+            chunks,
+        } = self;
+        VerifiedProof {
+            key,
+            leader,
+            wallclock,
+            first_received,
+            result: duplicate_proof::assemble(chunks, |_| Some(leader), shred_version),
+        }
+    }
+}
+
+// Verifies complete proofs on a thread pool, so that a burst of proofs does
+// not serialize behind the listener thread, and hands them back in the order
+// they were submitted. Proofs still queued when dropped are abandoned.
+struct ProofVerifier {
+    thread_pool: ThreadPool,
+    shred_version: u16,
+    max_in_flight: usize,
+    next_seq: u64,
+    // Submitted proofs by sequence number, with the outcome once verified.
+    in_flight: BTreeMap<u64, Option<VerifiedProof>>,
+    sender: Sender<(u64, VerifiedProof)>,
+    receiver: Receiver<(u64, VerifiedProof)>,
+    abandon: Arc<AtomicBool>,
+}
+
+impl ProofVerifier {
+    fn new(num_threads: usize, shred_version: u16) -> Self {
+        let num_threads = num_threads.max(1);
+        let thread_pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|i| format!("solDupPrfVfy{i:02}"))
+            .build()
+            .unwrap();
+        let (sender, receiver) = unbounded();
+        Self {
+            thread_pool,
+            shred_version,
+            max_in_flight: num_threads.saturating_mul(MAX_PROOFS_IN_FLIGHT_PER_THREAD),
+            next_seq: 0,
+            in_flight: BTreeMap::new(),
+            sender,
+            receiver,
+            abandon: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // Queues the proof for verification, and returns the proofs verified so
+    // far, waiting for some if too many are in flight.
+    fn submit(&mut self, proof: UnverifiedProof) -> Vec<VerifiedProof> {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        self.in_flight.insert(seq, None);
+        let sender = self.sender.clone();
+        let abandon = self.abandon.clone();
+        let shred_version = self.shred_version;
+        self.thread_pool.spawn(move || {
+            if !abandon.load(Ordering::Relaxed) {
+                // The verifier may be gone by now.
+                let _ = sender.send((seq, proof.verify(shred_version)));
+            }
+        });
+        let mut verified = self.take_verified();
+        while self.in_flight.len() > self.max_in_flight {
+            // The channel can't disconnect while self holds a sender.
+            let (seq, proof) = self.receiver.recv().unwrap();
+            self.in_flight.insert(seq, Some(proof));
+            verified.extend(self.take_verified());
+        }
+        verified
+    }
+
+    // Returns the verified proofs which are not queued behind a proof still
+    // being verified.
+    fn take_verified(&mut self) -> Vec<VerifiedProof> {
+        for (seq, proof) in self.receiver.try_iter() {
+            self.in_flight.insert(seq, Some(proof));
+        }
+        let mut verified = vec![];
+        while let Some(entry) = self.in_flight.first_entry() {
+            if entry.get().is_none() {
+                break;
+            }
+            verified.extend(entry.remove());
+        }
+        verified
+    }
+}
+
+impl Drop for ProofVerifier {
+    fn drop(&mut self) {
+        self.abandon.store(true, Ordering::Relaxed);
+    }
+}
+
 pub struct DuplicateShredHandler {
     // Because we use UDP for packet transfer, we can normally only send ~1500 bytes
     // in each packet. We send both shreds and meta data in duplicate shred proof, and
     // each shred is normally 1 packet(1500 bytes), so the whole proof is larger than
     // 1 packet and it needs to be cut down as chunks for transfer. So we need to piece
     // together the chunks into the original proof before anything useful is done.
-    buffer: HashMap<(Slot, Pubkey), BufferEntry>,
+    chunk_cache: ProofChunkCache,
+    // Complete proofs waiting for the leader schedule of their slot's epoch.
+    deferred_proofs: HashSet<(Slot, Pubkey)>,
+    // Peers which sent invalid proofs, and are possibly muted.
+    peer_penalties: PeerPenalties,
+    stats: DuplicateShredHandlerStats,
+    // Stats as of the last time they were reported.
+    last_stats_report: (Instant, DuplicateShredHandlerStats),
     // Slots for which a duplicate proof is already ingested.
     consumed: HashMap<Slot, bool>,
     // Cache last root to reduce read lock.
@@ -43,8 +763,22 @@ pub struct DuplicateShredHandler {
     cached_on_epoch: Epoch,
     cached_staked_nodes: Arc<HashMap<Pubkey, u64>>,
     cached_slots_in_epoch: u64,
+    // Whether duplicate shred proof ingestion is active on the root bank.
+    cached_proof_ingestion_enabled: bool,
     // Used to notify duplicate consensus state machine
     duplicate_slots_sender: Sender<Slot>,
+    // Duplicate slots without any shreds in blockstore yet, which replay is
+    // notified of once their shreds arrive.
+    parked_duplicate_slots: BTreeSet<Slot>,
+    // Slots above the root for which a proof is already ingested, so that
+    // proofs from other origins are discarded.
+    ingested_slots: BTreeSet<Slot>,
+    // Notified when a proof from gossip first marks a slot duplicate.
+    duplicate_slot_subscribers: Option<DuplicateSlotSubscribers>,
+    // Verifies proofs off the listener thread, if configured.
+    proof_verifier: Option<ProofVerifier>,
+    // Where evidence of the duplicate slots is written, if configured.
+    evidence_dir: Option<PathBuf>,
     shred_version: u16,
 }
 
@@ -52,16 +786,48 @@ impl DuplicateShredHandlerTrait for DuplicateShredHandler {
     // Here we are sending data one by one rather than in a batch because in the future
     // we may send different type of CrdsData to different senders.
     fn handle(&mut self, shred_data: DuplicateShred) {
+        let last_root = self.last_root;
         self.cache_root_info();
+        if !self.cached_proof_ingestion_enabled {
+            inc_new_counter_debug!("duplicate_shred_handler-ingestion_disabled", 1);
+            return;
+        }
+        let now = Instant::now();
+        self.stats.chunks_received = self.stats.chunks_received.saturating_add(1);
+        self.chunk_cache.expire(self.last_root, now);
+        self.peer_penalties.maybe_report(now);
+        self.maybe_report_stats(now);
         self.maybe_prune_buffer();
+        if self.last_root != last_root {
+            self.chunk_cache.purge_rooted(self.last_root);
+            self.ingested_slots = self
+                .ingested_slots
+                .split_off(&self.last_root.saturating_add(1));
+            self.retry_deferred_proofs();
+        }
         let slot = shred_data.slot;
         let pubkey = shred_data.from;
+        if self.peer_penalties.is_muted(&pubkey, now) {
+            inc_new_counter_debug!("duplicate_shred_handler-muted_peer_chunk", 1);
+            return;
+        }
         if let Err(error) = self.handle_shred_data(shred_data) {
-            if error.is_non_critical() {
-                info!("Received invalid duplicate shred proof from {pubkey} for slot {slot}: {error:?}");
-            } else {
-                error!("Unable to process duplicate shred proof from {pubkey} for slot {slot}: {error:?}");
-            }
+            report_proof_error(pubkey, slot, error);
+        }
+    }
+
+    fn tick(&mut self) {
+        if let Some(proof_verifier) = &mut self.proof_verifier {
+            let verified = proof_verifier.take_verified();
+            self.ingest_verified_proofs(verified);
+        }
+        self.chunk_cache.report_long_pending(Instant::now());
+        if self.parked_duplicate_slots.is_empty() {
+            return;
+        }
+        self.cache_root_info();
+        if let Err(error) = self.notify_parked_duplicate_slots() {
+            error!("Unable to notify parked duplicate slots: {error:?}");
         }
     }
 }
@@ -75,20 +841,127 @@ impl DuplicateShredHandler {
         shred_version: u16,
     ) -> Self {
         Self {
-            buffer: HashMap::<(Slot, Pubkey), BufferEntry>::default(),
+            chunk_cache: ProofChunkCache::new(DEFAULT_PROOF_CHUNK_MAX_AGE),
+            deferred_proofs: HashSet::default(),
+            peer_penalties: PeerPenalties::new(
+                DEFAULT_PEER_PENALTY_THRESHOLD,
+                DEFAULT_PEER_PENALTY_WINDOW,
+                DEFAULT_PEER_MUTE_COOLDOWN,
+            ),
+            stats: DuplicateShredHandlerStats::default(),
+            last_stats_report: (Instant::now(), DuplicateShredHandlerStats::default()),
             consumed: HashMap::<Slot, bool>::default(),
             last_root: 0,
             cached_on_epoch: 0,
             cached_staked_nodes: Arc::new(HashMap::new()),
             cached_slots_in_epoch: 0,
+            cached_proof_ingestion_enabled: false,
             blockstore,
             leader_schedule_cache,
             bank_forks,
             duplicate_slots_sender,
+            parked_duplicate_slots: BTreeSet::new(),
+            ingested_slots: BTreeSet::new(),
+            duplicate_slot_subscribers: None,
+            proof_verifier: None,
+            evidence_dir: None,
             shred_version,
         }
     }
 
+    /// Drop incomplete proofs, and ignore chunks of reassembled ones, after
+    /// `max_age` rather than the default of a few minutes
+    pub fn with_proof_chunk_max_age(mut self, max_age: Duration) -> Self {
+        self.chunk_cache.max_age = max_age;
+        self
+    }
+
+    /// Persist chunks of incomplete proofs under `ledger_path`, and reload
+    /// the ones persisted before a restart
+    pub fn with_proof_chunk_persistence(mut self, ledger_path: &Path) -> Self {
+        let dir = ledger_path.join(DUPLICATE_PROOF_CHUNKS_DIR);
+        let (store, chunks) = match ProofChunkStore::open(dir, self.blockstore.max_root()) {
+            Ok(store) => store,
+            Err(err) => {
+                error!("Unable to open persisted duplicate proof chunks: {err:?}");
+                return self;
+            }
+        };
+        let now = Instant::now();
+        for chunk in chunks {
+            let key = (chunk.slot, chunk.from);
+            if matches!(self.chunk_cache.insert(chunk, now), Ok(true)) {
+                // Reassembled once the root is known.
+                self.deferred_proofs.insert(key);
+            }
+        }
+        self.chunk_cache.store = Some(store);
+        self
+    }
+
+    /// Notify `subscribers` when a proof received through gossip first marks
+    /// a slot duplicate
+    pub fn with_duplicate_slot_subscribers(
+        mut self,
+        subscribers: DuplicateSlotSubscribers,
+    ) -> Self {
+        self.duplicate_slot_subscribers = Some(subscribers);
+        self
+    }
+
+    /// Verify the signatures of complete proofs on a pool of `num_threads`
+    /// threads rather than on the listener thread. Verified proofs are still
+    /// ingested by the listener thread, in the order they completed.
+    pub fn with_proof_verification_threads(mut self, num_threads: usize) -> Self {
+        self.proof_verifier = Some(ProofVerifier::new(num_threads, self.shred_version));
+        self
+    }
+
+    /// Write evidence of each slot a proof from gossip marks duplicate under
+    /// `evidence_dir`, see `DuplicateSlotEvidence`. Evidence already written
+    /// for a slot is kept.
+    pub fn with_evidence_dir(mut self, evidence_dir: PathBuf) -> Self {
+        self.evidence_dir = Some(evidence_dir);
+        self
+    }
+
+    /// Drop chunks from peers for `cooldown` once they send more than
+    /// `threshold` invalid proofs within `window`
+    pub fn with_peer_penalty(
+        mut self,
+        threshold: usize,
+        window: Duration,
+        cooldown: Duration,
+    ) -> Self {
+        self.peer_penalties = PeerPenalties::new(threshold, window, cooldown);
+        self
+    }
+
+    /// Returns counts of proof ingestion outcomes since the handler was created
+    pub fn stats(&self) -> DuplicateShredHandlerStats {
+        // Complete proofs deferred until their slot leader is known are
+        // still cached, but are not waiting for chunks.
+        let proofs_pending = self
+            .chunk_cache
+            .entries
+            .len()
+            .saturating_sub(self.deferred_proofs.len());
+        DuplicateShredHandlerStats {
+            proofs_pending: proofs_pending as u64,
+            ..self.stats.clone()
+        }
+    }
+
+    fn maybe_report_stats(&mut self, now: Instant) {
+        let (last_report, last_stats) = &self.last_stats_report;
+        if now.saturating_duration_since(*last_report) < STATS_REPORT_INTERVAL {
+            return;
+        }
+        let stats = self.stats();
+        stats.report(last_stats);
+        self.last_stats_report = (now, stats);
+    }
+
     fn cache_root_info(&mut self) {
         let last_root = self.blockstore.max_root();
         if last_root == self.last_root && !self.cached_staked_nodes.is_empty() {
@@ -97,6 +970,9 @@ impl DuplicateShredHandler {
         self.last_root = last_root;
         if let Ok(bank_fork) = self.bank_forks.try_read() {
             let root_bank = bank_fork.root_bank();
+            self.cached_proof_ingestion_enabled = root_bank
+                .feature_set
+                .is_active(&feature_set::gossip_duplicate_shred_proof_ingestion::id());
             let epoch_info = root_bank.get_epoch_info();
             if self.cached_staked_nodes.is_empty() || self.cached_on_epoch < epoch_info.epoch {
                 self.cached_on_epoch = epoch_info.epoch;
@@ -109,6 +985,12 @@ impl DuplicateShredHandler {
     }
 
     fn handle_shred_data(&mut self, chunk: DuplicateShred) -> Result<(), Error> {
+        // Duplicate proofs for rooted slots are no longer actionable.
+        if chunk.slot <= self.last_root {
+            inc_new_counter_debug!("duplicate_shred_handler-proofs_below_root", 1);
+            self.stats.proofs_below_root = self.stats.proofs_below_root.saturating_add(1);
+            return Ok(());
+        }
         if !self.should_consume_slot(chunk.slot) {
             return Ok(());
         }
@@ -116,62 +998,285 @@ impl DuplicateShredHandler {
         let num_chunks = chunk.num_chunks();
         let chunk_index = chunk.chunk_index();
         if usize::from(num_chunks) > MAX_NUM_CHUNKS || chunk_index >= num_chunks {
+            self.peer_penalties.penalize(chunk.from, Instant::now());
+            self.stats.proofs_invalid = self.stats.proofs_invalid.saturating_add(1);
             return Err(Error::InvalidChunkIndex {
                 chunk_index,
                 num_chunks,
             });
         }
-        let entry = self.buffer.entry((chunk.slot, chunk.from)).or_default();
-        *entry
-            .get_mut(usize::from(chunk_index))
-            .ok_or(Error::InvalidChunkIndex {
-                chunk_index,
-                num_chunks,
-            })? = Some(chunk);
         // If all chunks are already received, reconstruct and store
         // the duplicate slot proof in blockstore
-        if entry.iter().flatten().count() == usize::from(num_chunks) {
-            let chunks = std::mem::take(entry).into_iter().flatten();
-            let pubkey = self
-                .leader_schedule_cache
-                .slot_leader_at(slot, /*bank:*/ None)
-                .ok_or(Error::UnknownSlotLeader(slot))?;
-            let (shred1, shred2) =
-                duplicate_shred::into_shreds(&pubkey, chunks, self.shred_version)?;
-            if !self.blockstore.has_duplicate_shreds_in_slot(slot) {
-                self.blockstore.store_duplicate_slot(
-                    slot,
-                    shred1.into_payload(),
-                    shred2.into_payload(),
-                )?;
-                // Notify duplicate consensus state machine
+        let key = (slot, chunk.from);
+        let complete = self
+            .chunk_cache
+            .insert(chunk, Instant::now())
+            .inspect_err(|_| self.peer_penalties.penalize(key.1, Instant::now()))?;
+        if complete {
+            self.reassemble_proof(key)?;
+        }
+        Ok(())
+    }
+
+    fn reassemble_proof(&mut self, key: (Slot, Pubkey)) -> Result<(), Error> {
+        let slot = key.0;
+        let Some(pubkey) = self.slot_leader(slot) else {
+            // Keep the chunks until the leader schedule is known.
+            inc_new_counter_debug!("duplicate_shred_handler-deferred_proof", 1);
+            self.deferred_proofs.insert(key);
+            return Ok(());
+        };
+        let now = Instant::now();
+        let Some(ProofChunks {
+            chunks,
+            first_received,
+            ..
+        }) = self.chunk_cache.take(key, now)
+        else {
+            return Ok(());
+        };
+        // The first complete proof for the slot wins.
+        if self.ingested_slots.contains(&slot) {
+            inc_new_counter_debug!("duplicate_shred_handler-later_proof_discarded", 1);
+            return Ok(());
+        }
+        let chunks: Vec<DuplicateShred> = chunks.into_iter().flatten().collect();
+        let wallclock = chunks
+            .first()
+            .map(|chunk| chunk.wallclock)
+            .unwrap_or_default();
+        let proof = UnverifiedProof {
+            key,
+            leader: pubkey,
+            wallclock,
+            first_received,
+            chunks,
+        };
+        match &mut self.proof_verifier {
+            Some(proof_verifier) => {
+                let verified = proof_verifier.submit(proof);
+                self.ingest_verified_proofs(verified);
+                Ok(())
+            }
+            None => self.ingest_verified_proof(proof.verify(self.shred_version)),
+        }
+    }
+
+    fn ingest_verified_proofs(&mut self, proofs: Vec<VerifiedProof>) {
+        for proof in proofs {
+            let (slot, pubkey) = proof.key;
+            if let Err(error) = self.ingest_verified_proof(proof) {
+                report_proof_error(pubkey, slot, error);
+            }
+        }
+    }
+
+    fn ingest_verified_proof(&mut self, proof: VerifiedProof) -> Result<(), Error> {
+        let VerifiedProof {
+            key,
+            leader: pubkey,
+            wallclock,
+            first_received,
+            result,
+        } = proof;
+        let slot = key.0;
+        let now = Instant::now();
+        let AssembledProof { shred1, shred2, .. } = result.inspect_err(|_| {
+            self.peer_penalties.penalize(key.1, now);
+            self.stats.proofs_invalid = self.stats.proofs_invalid.saturating_add(1);
+        })?;
+        // Another proof for the slot may have been ingested, or the slot
+        // rooted, while this one was verified.
+        if slot <= self.last_root || self.ingested_slots.contains(&slot) {
+            inc_new_counter_debug!("duplicate_shred_handler-later_proof_discarded", 1);
+            return Ok(());
+        }
+        let assembly_latency = now.saturating_duration_since(first_received);
+        self.stats.proofs_completed = self.stats.proofs_completed.saturating_add(1);
+        self.stats.assembly_latency_us = self
+            .stats
+            .assembly_latency_us
+            .saturating_add(u64::try_from(assembly_latency.as_micros()).unwrap_or(u64::MAX));
+        if self.blockstore.has_duplicate_shreds_in_slot(slot) {
+            self.stats.slots_already_marked_duplicate =
+                self.stats.slots_already_marked_duplicate.saturating_add(1);
+        } else {
+            let notification = self
+                .duplicate_slot_subscribers
+                .is_some()
+                .then(|| DuplicateSlotProofNotification::new(key.1, &shred1, &shred2));
+            let evidence = self
+                .evidence_dir
+                .is_some()
+                .then(|| DuplicateSlotEvidence::new(pubkey, key.1, &shred1, &shred2));
+            if let Err(err) = self.blockstore.store_duplicate_slot(
+                slot,
+                shred1.into_payload(),
+                shred2.into_payload(),
+            ) {
+                // The slot may have been marked duplicate elsewhere since
+                // the check above, in which case there is nothing to do.
+                if !self.blockstore.has_duplicate_shreds_in_slot(slot) {
+                    return Err(Error::from(err));
+                }
+                inc_new_counter_debug!("duplicate_shred_handler-store_raced", 1);
+                self.mark_ingested(slot);
+                return Ok(());
+            }
+            let provenance = DuplicateSlotProvenance {
+                origin: key.1,
+                wallclock,
+                leader: pubkey,
+            };
+            if let Err(err) = self
+                .blockstore
+                .store_duplicate_slot_provenance(slot, &provenance)
+            {
+                error!("Unable to store duplicate slot provenance for slot {slot}: {err:?}");
+            }
+            if let Some(notification) = notification {
+                self.notify_duplicate_slot_subscribers(notification);
+            }
+            if let Some(evidence) = evidence {
+                self.write_evidence(&evidence);
+            }
+            // Notify duplicate consensus state machine, which can only act
+            // on the slot once it has shreds.
+            if self.blockstore.has_existing_shreds_for_slot(slot) {
                 self.duplicate_slots_sender
                     .send(slot)
                     .map_err(|_| Error::DuplicateSlotSenderFailure)?;
+            } else {
+                inc_new_counter_debug!("duplicate_shred_handler-parked_duplicate_slot", 1);
+                self.parked_duplicate_slots.insert(slot);
+                // Keep the slots closest to the root.
+                while self.parked_duplicate_slots.len() > MAX_PARKED_DUPLICATE_SLOTS {
+                    self.parked_duplicate_slots.pop_last();
+                }
             }
-            self.consumed.insert(slot, true);
         }
+        self.mark_ingested(slot);
         Ok(())
     }
 
-    fn should_consume_slot(&mut self, slot: Slot) -> bool {
-        slot > self.last_root
-            && slot < self.last_root.saturating_add(self.cached_slots_in_epoch)
-            && should_consume_slot(slot, &self.blockstore, &mut self.consumed)
+    fn mark_ingested(&mut self, slot: Slot) {
+        self.consumed.insert(slot, true);
+        self.ingested_slots.insert(slot);
+        // Keep the slots closest to the root.
+        while self.ingested_slots.len() > MAX_INGESTED_SLOTS {
+            self.ingested_slots.pop_last();
+        }
     }
 
-    fn maybe_prune_buffer(&mut self) {
-        // The buffer is allowed to grow to twice the intended capacity, at
-        // which point the extraneous entries are removed in linear time,
-        // resulting an amortized O(1) performance.
-        if self.buffer.len() < BUFFER_CAPACITY.saturating_mul(2) {
+    fn notify_duplicate_slot_subscribers(&self, notification: DuplicateSlotProofNotification) {
+        let Some(subscribers) = &self.duplicate_slot_subscribers else {
+            return;
+        };
+        // Subscribers which hung up are dropped.
+        subscribers
+            .write()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(notification.clone()).is_ok());
+    }
+
+    fn write_evidence(&self, evidence: &DuplicateSlotEvidence) {
+        let Some(evidence_dir) = &self.evidence_dir else {
             return;
+        };
+        match evidence.write(evidence_dir) {
+            Ok(true) => (),
+            Ok(false) => inc_new_counter_debug!("duplicate_shred_handler-evidence_exists", 1),
+            Err(err) => error!(
+                "Unable to write duplicate slot evidence for slot {}: {err:?}",
+                evidence.slot
+            ),
         }
-        self.consumed.retain(|&slot, _| slot > self.last_root);
-        // Filter out obsolete slots and limit number of entries per pubkey.
-        {
-            let mut counts = HashMap::<Pubkey, usize>::new();
-            self.buffer.retain(|(slot, pubkey), _| {
+    }
+
+    // Notifies the duplicate consensus state machine of parked slots which
+    // now have shreds, and drops the ones at or below the root.
+    fn notify_parked_duplicate_slots(&mut self) -> Result<(), Error> {
+        self.parked_duplicate_slots = self
+            .parked_duplicate_slots
+            .split_off(&self.last_root.saturating_add(1));
+        let arrived: Vec<Slot> = self
+            .parked_duplicate_slots
+            .iter()
+            .copied()
+            .filter(|&slot| self.blockstore.has_existing_shreds_for_slot(slot))
+            .collect();
+        for slot in arrived {
+            self.parked_duplicate_slots.remove(&slot);
+            self.duplicate_slots_sender
+                .send(slot)
+                .map_err(|_| Error::DuplicateSlotSenderFailure)?;
+        }
+        Ok(())
+    }
+
+    fn retry_deferred_proofs(&mut self) {
+        for key @ (slot, pubkey) in std::mem::take(&mut self.deferred_proofs) {
+            if !self.should_consume_slot(slot)
+                || self.peer_penalties.is_muted(&pubkey, Instant::now())
+            {
+                continue;
+            }
+            if let Err(error) = self.reassemble_proof(key) {
+                report_proof_error(pubkey, slot, error);
+            }
+        }
+    }
+
+    // Returns the leader of the slot, or None if the leader schedule of its
+    // epoch can't be determined yet.
+    fn slot_leader(&self, slot: Slot) -> Option<Pubkey> {
+        if let Some(leader) = self
+            .leader_schedule_cache
+            .slot_leader_at(slot, /*bank:*/ None)
+        {
+            return Some(leader);
+        }
+        // The cache only holds schedules up to the leader schedule epoch of
+        // its root. A bank in the slot's epoch, or else the root bank, may
+        // already have the epoch's stakes.
+        let banks: Vec<_> = {
+            let bank_forks = self.bank_forks.read().unwrap();
+            let root_bank = bank_forks.root_bank();
+            let epoch = root_bank.epoch_schedule().get_epoch(slot);
+            bank_forks
+                .banks()
+                .values()
+                .filter(|bank| bank.epoch() == epoch)
+                .max_by_key(|bank| bank.slot())
+                .map(|bank| bank.clone_without_scheduler())
+                .into_iter()
+                .chain([root_bank])
+                .collect()
+        };
+        banks
+            .iter()
+            .find_map(|bank| leader_schedule_utils::slot_leader_at(slot, bank))
+    }
+
+    fn should_consume_slot(&mut self, slot: Slot) -> bool {
+        slot > self.last_root
+            && slot < self.last_root.saturating_add(self.cached_slots_in_epoch)
+            && should_consume_slot(slot, &self.blockstore, &mut self.consumed)
+    }
+
+    fn maybe_prune_buffer(&mut self) {
+        // The buffer is allowed to grow to twice the intended capacity, at
+        // which point the extraneous entries are removed in linear time,
+        // resulting an amortized O(1) performance.
+        let num_entries = self.chunk_cache.entries.len();
+        if num_entries < BUFFER_CAPACITY.saturating_mul(2) {
+            return;
+        }
+        self.consumed.retain(|&slot, _| slot > self.last_root);
+        // Filter out obsolete slots and limit number of entries per pubkey.
+        {
+            let mut counts = HashMap::<Pubkey, usize>::new();
+            self.chunk_cache.entries.retain(|(slot, pubkey), _| {
                 *slot > self.last_root
                     && should_consume_slot(*slot, &self.blockstore, &mut self.consumed)
                     && {
@@ -181,12 +1286,17 @@ impl DuplicateShredHandler {
                     }
             });
         }
-        if self.buffer.len() < BUFFER_CAPACITY {
+        if self.chunk_cache.entries.len() < BUFFER_CAPACITY {
+            self.chunk_cache.forget_evicted();
+            report_evicted_incomplete_proofs(
+                num_entries.saturating_sub(self.chunk_cache.entries.len()),
+            );
             return;
         }
         // Lookup stake for each entry.
         let mut buffer: Vec<_> = self
-            .buffer
+            .chunk_cache
+            .entries
             .drain()
             .map(|entry @ ((_, pubkey), _)| {
                 let stake = self
@@ -199,12 +1309,24 @@ impl DuplicateShredHandler {
             .collect();
         // Drop entries with lowest stake and rebuffer remaining ones.
         buffer.select_nth_unstable_by_key(BUFFER_CAPACITY, |&(stake, _)| Reverse(stake));
-        self.buffer.extend(
+        self.chunk_cache.entries.extend(
             buffer
                 .into_iter()
                 .take(BUFFER_CAPACITY)
                 .map(|(_, entry)| entry),
         );
+        self.chunk_cache.forget_evicted();
+        report_evicted_incomplete_proofs(
+            num_entries.saturating_sub(self.chunk_cache.entries.len()),
+        );
+    }
+}
+
+fn report_proof_error(pubkey: Pubkey, slot: Slot, error: Error) {
+    if error.is_non_critical() {
+        info!("Received invalid duplicate shred proof from {pubkey} for slot {slot}: {error:?}");
+    } else {
+        error!("Unable to process duplicate shred proof from {pubkey} for slot {slot}: {error:?}");
     }
 }
 
@@ -225,22 +1347,41 @@ mod tests {
     use {
         super::*,
         crate::{
-            duplicate_shred::{from_shred, tests::new_rand_shred},
+            duplicate_shred::{self, from_shred, tests::new_rand_shred},
             protocol::DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
         },
         crossbeam_channel::unbounded,
         itertools::Itertools,
+        solana_epoch_schedule::EpochSchedule,
         solana_keypair::Keypair,
         solana_ledger::{
             genesis_utils::{create_genesis_config_with_leader, GenesisConfigInfo},
             get_tmp_ledger_path_auto_delete,
             shred::Shredder,
         },
-        solana_runtime::bank::Bank,
+        solana_runtime::{bank::Bank, genesis_utils::deactivate_features},
         solana_signer::Signer,
         solana_time_utils::timestamp,
     };
 
+    // Inserts a shred for each slot, so that replay is notified of duplicate
+    // proofs for them right away.
+    fn insert_slot_shreds(
+        blockstore: &Blockstore,
+        keypair: &Keypair,
+        slots: impl IntoIterator<Item = Slot>,
+    ) {
+        let mut rng = rand::thread_rng();
+        let shreds: Vec<_> = slots
+            .into_iter()
+            .map(|slot| {
+                let shredder = Shredder::new(slot, slot - 1, 0, /*shred_version:*/ 0).unwrap();
+                new_rand_shred(&mut rng, /*next_shred_index:*/ 0, &shredder, keypair)
+            })
+            .collect();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+    }
+
     fn create_duplicate_proof(
         keypair: Arc<Keypair>,
         sender_pubkey: Option<Pubkey>,
@@ -301,6 +1442,7 @@ mod tests {
             bank_forks.set_root(9, None, None).unwrap();
         }
         blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
         let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
             &bank_forks_arc.read().unwrap().working_bank(),
         ));
@@ -393,6 +1535,7 @@ mod tests {
             bank_forks.set_root(9, None, None).unwrap();
         }
         blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
         let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
             &bank_forks_arc.read().unwrap().working_bank(),
         ));
@@ -462,4 +1605,1263 @@ mod tests {
         assert!(blockstore.has_duplicate_shreds_in_slot(start_slot));
         assert_eq!(receiver.try_iter().collect_vec(), vec![start_slot]);
     }
+
+    #[test]
+    fn test_proof_ingestion_feature_gate() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let feature_id = feature_set::gossip_duplicate_shred_proof_ingestion::id();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        deactivate_features(&mut genesis_config, &vec![feature_id]);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, receiver) = unbounded();
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc.clone(),
+            sender,
+            shred_version,
+        );
+
+        // The feature is inactive at the root, so the proof is dropped.
+        let start_slot: Slot = 10;
+        let chunks = create_duplicate_proof(
+            my_keypair.clone(),
+            None,
+            start_slot,
+            None,
+            DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+            shred_version,
+        )
+        .unwrap();
+        for chunk in chunks {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert!(!duplicate_shred_handler.cached_proof_ingestion_enabled);
+        assert!(!blockstore.has_duplicate_shreds_in_slot(start_slot));
+        assert!(receiver.is_empty());
+
+        // Root past the activation, after which proofs are ingested.
+        let activation_slot: Slot = 20;
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank9 = bank_forks.get(9).unwrap();
+            let mut bank20 = Bank::new_from_parent(bank9, &Pubkey::default(), activation_slot);
+            bank20.activate_feature(&feature_id);
+            bank_forks.insert(bank20);
+            bank_forks.set_root(activation_slot, None, None).unwrap();
+        }
+        blockstore.set_roots([activation_slot].iter()).unwrap();
+        let chunks = create_duplicate_proof(
+            my_keypair,
+            None,
+            activation_slot + 1,
+            None,
+            DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+            shred_version,
+        )
+        .unwrap();
+        for chunk in chunks {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert!(duplicate_shred_handler.cached_proof_ingestion_enabled);
+        assert!(blockstore.has_duplicate_shreds_in_slot(activation_slot + 1));
+        assert_eq!(receiver.try_iter().collect_vec(), vec![activation_slot + 1]);
+    }
+
+    #[test]
+    fn test_proof_leader_in_next_epoch() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        genesis_config.epoch_schedule = EpochSchedule::custom(32, 1, false);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
+        // The cache only knows the leader schedule of epoch 0.
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, receiver) = unbounded();
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc.clone(),
+            sender,
+            shred_version,
+        );
+        let mut handle_proof = |slot: Slot| {
+            let chunks = create_duplicate_proof(
+                my_keypair.clone(),
+                None,
+                slot,
+                None,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap();
+            for chunk in chunks {
+                duplicate_shred_handler.handle(chunk);
+            }
+        };
+
+        // No bank knows the stakes of epoch 1 yet, so the proof is deferred.
+        handle_proof(34);
+        assert!(!blockstore.has_duplicate_shreds_in_slot(34));
+        assert!(receiver.is_empty());
+
+        // A bank in epoch 1 is used to validate proofs for the epoch.
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank9 = bank_forks.get(9).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank9, &Pubkey::default(), 33));
+        }
+        handle_proof(35);
+        assert!(blockstore.has_duplicate_shreds_in_slot(35));
+        assert_eq!(receiver.try_iter().collect_vec(), vec![35]);
+
+        // Deferred proofs are retried once the root advances.
+        bank_forks_arc
+            .write()
+            .unwrap()
+            .set_root(33, None, None)
+            .unwrap();
+        blockstore.set_roots([33].iter()).unwrap();
+        handle_proof(36);
+        assert!(blockstore.has_duplicate_shreds_in_slot(34));
+        assert!(blockstore.has_duplicate_shreds_in_slot(36));
+        assert_eq!(receiver.try_iter().collect_vec(), vec![34, 36]);
+    }
+
+    #[test]
+    fn test_mute_invalid_proofs() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, receiver) = unbounded();
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc,
+            sender,
+            shred_version,
+        )
+        .with_peer_penalty(
+            2,                        // threshold
+            Duration::from_secs(60),  // window
+            Duration::from_secs(600), // cooldown
+        );
+        let new_proof = |slot: Slot, error: Option<Error>| {
+            create_duplicate_proof(
+                my_keypair.clone(),
+                Some(my_pubkey),
+                slot,
+                error,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap()
+            .collect_vec()
+        };
+
+        // Invalid proofs up to the threshold do not mute the peer.
+        for slot in [10, 11] {
+            for chunk in new_proof(slot, Some(Error::InvalidSignature)) {
+                duplicate_shred_handler.handle(chunk);
+            }
+        }
+        for chunk in new_proof(12, None) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert!(blockstore.has_duplicate_shreds_in_slot(12));
+        assert_eq!(receiver.try_iter().collect_vec(), vec![12]);
+
+        // Once past the threshold, even valid proofs from the peer are dropped.
+        for chunk in new_proof(13, Some(Error::InvalidSignature)) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        let chunks = new_proof(14, None);
+        for chunk in chunks.clone() {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert!(!blockstore.has_duplicate_shreds_in_slot(14));
+        assert!(receiver.is_empty());
+        assert!(duplicate_shred_handler.chunk_cache.entries.is_empty());
+
+        // After the cooldown, proofs from the peer are processed again.
+        duplicate_shred_handler
+            .peer_penalties
+            .peers
+            .get_mut(&my_pubkey)
+            .unwrap()
+            .muted_until = Some(Instant::now());
+        for chunk in chunks {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert!(blockstore.has_duplicate_shreds_in_slot(14));
+        assert_eq!(receiver.try_iter().collect_vec(), vec![14]);
+    }
+
+    #[test]
+    fn test_handler_stats() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, receiver) = unbounded();
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc,
+            sender,
+            shred_version,
+        );
+        let new_proof = |slot: Slot, sender: Pubkey, error: Option<Error>| {
+            create_duplicate_proof(
+                my_keypair.clone(),
+                Some(sender),
+                slot,
+                error,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap()
+            .collect_vec()
+        };
+        let assert_stats = |handler: &DuplicateShredHandler,
+                            expected: DuplicateShredHandlerStats| {
+            let stats = handler.stats();
+            assert_eq!(
+                stats,
+                DuplicateShredHandlerStats {
+                    assembly_latency_us: stats.assembly_latency_us,
+                    ..expected
+                }
+            );
+        };
+
+        // Chunks are pending until the proof completes.
+        let mut chunks = new_proof(10, my_pubkey, None);
+        let last = chunks.pop().unwrap();
+        for chunk in chunks {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert_stats(
+            &duplicate_shred_handler,
+            DuplicateShredHandlerStats {
+                chunks_received: 2,
+                proofs_pending: 1,
+                ..DuplicateShredHandlerStats::default()
+            },
+        );
+        duplicate_shred_handler.handle(last);
+        assert_stats(
+            &duplicate_shred_handler,
+            DuplicateShredHandlerStats {
+                chunks_received: 3,
+                proofs_completed: 1,
+                ..DuplicateShredHandlerStats::default()
+            },
+        );
+
+        // Proofs failing validation are invalid.
+        for chunk in new_proof(11, my_pubkey, Some(Error::InvalidSignature)) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert_stats(
+            &duplicate_shred_handler,
+            DuplicateShredHandlerStats {
+                chunks_received: 6,
+                proofs_completed: 1,
+                proofs_invalid: 1,
+                ..DuplicateShredHandlerStats::default()
+            },
+        );
+
+        // A proof completing after the slot was marked duplicate locally.
+        let mut chunks = new_proof(12, my_pubkey, None);
+        let last = chunks.pop().unwrap();
+        for chunk in chunks {
+            duplicate_shred_handler.handle(chunk);
+        }
+        let (shred1, shred2) = duplicate_shred::into_shreds(
+            &my_pubkey,
+            new_proof(12, Pubkey::new_unique(), None),
+            shred_version,
+        )
+        .unwrap();
+        blockstore
+            .store_duplicate_slot(12, shred1.into_payload(), shred2.into_payload())
+            .unwrap();
+        duplicate_shred_handler.handle(last);
+        assert_stats(
+            &duplicate_shred_handler,
+            DuplicateShredHandlerStats {
+                chunks_received: 9,
+                proofs_completed: 2,
+                proofs_invalid: 1,
+                slots_already_marked_duplicate: 1,
+                ..DuplicateShredHandlerStats::default()
+            },
+        );
+        assert_eq!(receiver.try_iter().collect_vec(), vec![10]);
+    }
+
+    #[test]
+    fn test_duplicate_slot_subscribers() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, _receiver) = unbounded();
+        let (subscriber1, receiver1) = unbounded();
+        let (subscriber2, receiver2) = unbounded();
+        let (hung_up_subscriber, _) = unbounded();
+        let subscribers = Arc::new(RwLock::new(vec![
+            subscriber1,
+            hung_up_subscriber,
+            subscriber2,
+        ]));
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc,
+            sender,
+            shred_version,
+        )
+        .with_duplicate_slot_subscribers(subscribers.clone());
+        let new_proof = |slot: Slot, sender: Pubkey| {
+            create_duplicate_proof(
+                my_keypair.clone(),
+                Some(sender),
+                slot,
+                None,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap()
+            .collect_vec()
+        };
+
+        // All subscribers are notified when a proof marks the slot duplicate.
+        let origin = Pubkey::new_unique();
+        for chunk in new_proof(10, origin) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        let proof = blockstore.get_duplicate_slot(10).unwrap();
+        let shred1 = Shred::new_from_serialized_shred(proof.shred1).unwrap();
+        let shred2 = Shred::new_from_serialized_shred(proof.shred2).unwrap();
+        let notifications = receiver1.try_iter().collect_vec();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(
+            notifications[0],
+            DuplicateSlotProofNotification {
+                slot: 10,
+                origin,
+                shred1_index: shred1.index(),
+                shred1_type: shred1.shred_type(),
+                shred2_index: shred2.index(),
+                shred2_type: shred2.shred_type(),
+                timestamp: notifications[0].timestamp,
+            }
+        );
+        assert_eq!(receiver2.try_iter().collect_vec(), notifications);
+        assert_eq!(subscribers.read().unwrap().len(), 2);
+
+        // Further proofs for the slot do not notify.
+        for chunk in new_proof(10, my_pubkey) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert!(receiver1.is_empty());
+
+        // Neither do proofs for slots already marked duplicate locally.
+        let mut chunks = new_proof(12, my_pubkey);
+        let last = chunks.pop().unwrap();
+        for chunk in chunks {
+            duplicate_shred_handler.handle(chunk);
+        }
+        let (shred1, shred2) =
+            duplicate_shred::into_shreds(&my_pubkey, new_proof(12, origin), shred_version).unwrap();
+        blockstore
+            .store_duplicate_slot(12, shred1.into_payload(), shred2.into_payload())
+            .unwrap();
+        duplicate_shred_handler.handle(last);
+        assert_eq!(
+            duplicate_shred_handler
+                .stats()
+                .slots_already_marked_duplicate,
+            1
+        );
+        assert!(receiver1.is_empty());
+        assert!(receiver2.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_slot_evidence() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..20);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, _receiver) = unbounded();
+        let evidence_dir = ledger_path.path().join("evidence");
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc,
+            sender,
+            shred_version,
+        )
+        .with_evidence_dir(evidence_dir.clone());
+        let new_proof = |slot: Slot, sender: Pubkey| {
+            create_duplicate_proof(
+                my_keypair.clone(),
+                Some(sender),
+                slot,
+                None,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap()
+        };
+
+        let origin = Pubkey::new_unique();
+        for chunk in new_proof(10, origin) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        let path = DuplicateSlotEvidence::path(&evidence_dir, 10, &my_pubkey);
+        let evidence = read_evidence(&path).unwrap();
+        let proof = blockstore.get_duplicate_slot(10).unwrap();
+        assert_eq!(
+            evidence,
+            DuplicateSlotEvidence {
+                slot: 10,
+                leader: my_pubkey,
+                shred1: proof.shred1.to_vec(),
+                shred2: proof.shred2.to_vec(),
+                origin,
+                timestamp: evidence.timestamp,
+            }
+        );
+        // The JSON evidence agrees with the bincode one
+        let json_path = path.with_extension("evidence.json");
+        assert_eq!(read_evidence(&json_path).unwrap(), evidence);
+        let mut names: Vec<_> = fs::read_dir(&evidence_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            [path.file_name().unwrap(), json_path.file_name().unwrap()]
+        );
+
+        // Evidence is written once per slot
+        for chunk in new_proof(10, Pubkey::new_unique()) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        let mut other = evidence.clone();
+        other.origin = Pubkey::new_unique();
+        assert!(!other.write(&evidence_dir).unwrap());
+        assert_eq!(read_evidence(&path).unwrap(), evidence);
+        assert_eq!(read_evidence(&json_path).unwrap(), evidence);
+
+        // Slots already marked duplicate locally have no evidence
+        let (shred1, shred2) =
+            duplicate_shred::into_shreds(&my_pubkey, new_proof(12, origin), shred_version).unwrap();
+        blockstore
+            .store_duplicate_slot(12, shred1.into_payload(), shred2.into_payload())
+            .unwrap();
+        for chunk in new_proof(12, origin) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert!(!DuplicateSlotEvidence::path(&evidence_dir, 12, &my_pubkey).exists());
+        assert_eq!(fs::read_dir(&evidence_dir).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_proofs_from_multiple_origins() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        genesis_config.epoch_schedule = EpochSchedule::custom(32, 1, false);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, receiver) = unbounded();
+        let (subscriber, notifications) = unbounded();
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc.clone(),
+            sender,
+            shred_version,
+        )
+        .with_duplicate_slot_subscribers(Arc::new(RwLock::new(vec![subscriber])));
+        let new_proof = |slot: Slot, sender: Pubkey| {
+            create_duplicate_proof(
+                my_keypair.clone(),
+                Some(sender),
+                slot,
+                None,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap()
+            .collect_vec()
+        };
+        let (origin1, origin2) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut handle_proofs = |slot: Slot| {
+            let chunks1 = new_proof(slot, origin1);
+            let chunks2 = new_proof(slot, origin2);
+            for (chunk1, chunk2) in chunks1.into_iter().zip(chunks2) {
+                duplicate_shred_handler.handle(chunk1);
+                duplicate_shred_handler.handle(chunk2);
+            }
+        };
+
+        // Two origins send their proofs for the slot at the same time. The
+        // slot is stored and notified only once.
+        handle_proofs(12);
+        assert_eq!(receiver.try_iter().collect_vec(), vec![12]);
+        let notified = notifications.try_iter().collect_vec();
+        assert_eq!(notified.len(), 1);
+        assert_eq!((notified[0].slot, notified[0].origin), (12, origin1));
+        // Provenance of the first proof is recorded with it.
+        let evidence = blockstore.get_duplicate_slot_evidence(12).unwrap().unwrap();
+        let provenance = evidence.provenance.unwrap();
+        assert_eq!((provenance.origin, provenance.leader), (origin1, my_pubkey));
+        assert!(provenance.wallclock > 0);
+
+        // Complete proofs from both origins are deferred until the leader
+        // schedule of their epoch is known, and then retried together.
+        handle_proofs(34);
+        assert!(!blockstore.has_duplicate_shreds_in_slot(34));
+        assert!(receiver.is_empty());
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank9 = bank_forks.get(9).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank9, &Pubkey::default(), 33));
+            bank_forks.set_root(33, None, None).unwrap();
+        }
+        blockstore.set_roots([33].iter()).unwrap();
+        for chunk in new_proof(36, origin1) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert_eq!(receiver.try_iter().collect_vec(), vec![34, 36]);
+        let notified = notifications
+            .try_iter()
+            .map(|notification| notification.slot)
+            .collect_vec();
+        assert_eq!(notified, vec![34, 36]);
+    }
+
+    #[test]
+    fn test_drop_proofs_below_root() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, receiver) = unbounded();
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc.clone(),
+            sender,
+            shred_version,
+        );
+        let new_proof = |slot: Slot| {
+            create_duplicate_proof(
+                my_keypair.clone(),
+                None,
+                slot,
+                None,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap()
+            .collect_vec()
+        };
+        let mut chunks11 = new_proof(11);
+        let mut chunks12 = new_proof(12);
+        let last11 = chunks11.pop().unwrap();
+        let last12 = chunks12.pop().unwrap();
+        for chunk in chunks11.into_iter().chain(chunks12) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert_eq!(duplicate_shred_handler.chunk_cache.entries.len(), 2);
+        assert_eq!(duplicate_shred_handler.stats().proofs_below_root, 0);
+
+        // Once the root advances, cached chunks of rooted slots are purged,
+        // and their remaining chunks are dropped.
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank9 = bank_forks.get(9).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank9, &Pubkey::default(), 11));
+            bank_forks.set_root(11, None, None).unwrap();
+        }
+        blockstore.set_roots([11].iter()).unwrap();
+        duplicate_shred_handler.handle(last11);
+        assert_eq!(duplicate_shred_handler.stats().proofs_below_root, 1);
+        assert_eq!(
+            duplicate_shred_handler
+                .chunk_cache
+                .entries
+                .keys()
+                .collect_vec(),
+            vec![&(12, my_pubkey)]
+        );
+        assert!(!blockstore.has_duplicate_shreds_in_slot(11));
+
+        // Proofs for descendants of the root are still processed.
+        duplicate_shred_handler.handle(last12);
+        assert!(blockstore.has_duplicate_shreds_in_slot(12));
+        assert_eq!(receiver.try_iter().collect_vec(), vec![12]);
+        assert_eq!(duplicate_shred_handler.stats().proofs_below_root, 1);
+    }
+
+    #[test]
+    fn test_park_duplicate_slot_notifications() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, receiver) = unbounded();
+        let mut duplicate_shred_handler = DuplicateShredHandler::new(
+            blockstore.clone(),
+            leader_schedule_cache,
+            bank_forks_arc,
+            sender,
+            shred_version,
+        );
+        let mut handle_proof = |slot: Slot| {
+            let chunks = create_duplicate_proof(
+                my_keypair.clone(),
+                None,
+                slot,
+                None,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap();
+            for chunk in chunks {
+                duplicate_shred_handler.handle(chunk);
+            }
+        };
+
+        // Replay is notified right away of slots which have shreds, while
+        // the others are parked.
+        insert_slot_shreds(&blockstore, &my_keypair, [11]);
+        handle_proof(10);
+        handle_proof(11);
+        handle_proof(12);
+        assert!(blockstore.has_duplicate_shreds_in_slot(10));
+        assert!(blockstore.has_duplicate_shreds_in_slot(12));
+        assert_eq!(receiver.try_iter().collect_vec(), vec![11]);
+        assert_eq!(
+            duplicate_shred_handler.parked_duplicate_slots,
+            BTreeSet::from([10, 12])
+        );
+
+        // Parked slots are notified once their shreds arrive.
+        duplicate_shred_handler.tick();
+        assert!(receiver.is_empty());
+        insert_slot_shreds(&blockstore, &my_keypair, [10]);
+        duplicate_shred_handler.tick();
+        assert_eq!(receiver.try_iter().collect_vec(), vec![10]);
+        assert_eq!(
+            duplicate_shred_handler.parked_duplicate_slots,
+            BTreeSet::from([12])
+        );
+
+        // Parked slots at or below the root are dropped.
+        blockstore.set_roots([12].iter()).unwrap();
+        duplicate_shred_handler.tick();
+        assert!(duplicate_shred_handler.parked_duplicate_slots.is_empty());
+        insert_slot_shreds(&blockstore, &my_keypair, [12]);
+        duplicate_shred_handler.tick();
+        assert!(receiver.is_empty());
+    }
+
+    #[test]
+    fn test_persist_proof_chunks() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks_arc = BankForks::new_rw_arc(bank);
+        {
+            let mut bank_forks = bank_forks_arc.write().unwrap();
+            let bank0 = bank_forks.get(0).unwrap();
+            bank_forks.insert(Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 9));
+            bank_forks.set_root(9, None, None).unwrap();
+        }
+        blockstore.set_roots([0, 9].iter()).unwrap();
+        insert_slot_shreds(&blockstore, &my_keypair, 10..40);
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks_arc.read().unwrap().working_bank(),
+        ));
+        let (sender, receiver) = unbounded();
+        let new_handler = || {
+            DuplicateShredHandler::new(
+                blockstore.clone(),
+                leader_schedule_cache.clone(),
+                bank_forks_arc.clone(),
+                sender.clone(),
+                shred_version,
+            )
+            .with_proof_chunk_persistence(ledger_path.path())
+        };
+        let new_proof = |slot: Slot| {
+            create_duplicate_proof(
+                my_keypair.clone(),
+                None,
+                slot,
+                None,
+                DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                shred_version,
+            )
+            .unwrap()
+            .collect_vec()
+        };
+        let persisted_files = || {
+            fs::read_dir(ledger_path.path().join(DUPLICATE_PROOF_CHUNKS_DIR))
+                .unwrap()
+                .count()
+        };
+
+        // Receive all but the last chunk of two proofs.
+        let mut duplicate_shred_handler = new_handler();
+        let mut chunks10 = new_proof(10);
+        let mut chunks15 = new_proof(15);
+        let last10 = chunks10.pop().unwrap();
+        let last15 = chunks15.pop().unwrap();
+        for chunk in chunks10.into_iter().chain(chunks15) {
+            duplicate_shred_handler.handle(chunk);
+        }
+        assert_eq!(persisted_files(), 2);
+        drop(duplicate_shred_handler);
+
+        // Chunks at or below the root are dropped on restart.
+        blockstore.set_roots([12].iter()).unwrap();
+        let mut duplicate_shred_handler = new_handler();
+        assert_eq!(persisted_files(), 1);
+        let entries = &duplicate_shred_handler.chunk_cache.entries;
+        assert!(!entries.contains_key(&(10, my_pubkey)));
+        assert_eq!(
+            entries[&(15, my_pubkey)].chunks.iter().flatten().count(),
+            MAX_NUM_CHUNKS - 1
+        );
+
+        // The missing chunk completes the reloaded proof, deleting its file.
+        duplicate_shred_handler.handle(last10);
+        duplicate_shred_handler.handle(last15);
+        assert!(!blockstore.has_duplicate_shreds_in_slot(10));
+        assert!(blockstore.has_duplicate_shreds_in_slot(15));
+        assert_eq!(receiver.try_iter().collect_vec(), vec![15]);
+        assert_eq!(persisted_files(), 0);
+    }
+
+    #[test]
+    fn test_peer_penalties() {
+        let now = Instant::now();
+        let window = Duration::from_secs(60);
+        let cooldown = Duration::from_secs(600);
+        let mut penalties = PeerPenalties::new(/*threshold:*/ 2, window, cooldown);
+        let pubkey = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        // Penalties which aged out of the window do not count.
+        penalties.penalize(pubkey, now);
+        penalties.penalize(pubkey, now + Duration::from_secs(1));
+        penalties.penalize(pubkey, now + window);
+        assert!(!penalties.is_muted(&pubkey, now + window));
+        penalties.penalize(other, now + window);
+        assert!(!penalties.is_muted(&other, now + window));
+
+        // Exceeding the threshold within the window mutes only that peer.
+        let muted_at = now + window;
+        penalties.penalize(pubkey, muted_at);
+        assert!(penalties.is_muted(&pubkey, muted_at));
+        assert!(penalties.is_muted(&pubkey, muted_at + cooldown / 2));
+        assert!(!penalties.is_muted(&other, muted_at));
+
+        // Stale penalties are dropped, but muted peers are kept.
+        let later = muted_at + window;
+        penalties.maybe_report(later);
+        assert!(penalties.peers.contains_key(&pubkey));
+        assert!(!penalties.peers.contains_key(&other));
+
+        // The peer is unmuted after the cooldown with a clean slate.
+        let unmuted_at = muted_at + cooldown;
+        assert!(!penalties.is_muted(&pubkey, unmuted_at));
+        penalties.penalize(pubkey, unmuted_at);
+        penalties.penalize(pubkey, unmuted_at);
+        assert!(!penalties.is_muted(&pubkey, unmuted_at));
+        penalties.penalize(pubkey, unmuted_at);
+        assert!(penalties.is_muted(&pubkey, unmuted_at));
+    }
+
+    fn new_proof_chunks(slot: Slot) -> Vec<DuplicateShred> {
+        let chunks = create_duplicate_proof(
+            Arc::new(Keypair::new()),
+            None,
+            slot,
+            None,
+            DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+            /*shred_version:*/ 0,
+        )
+        .unwrap()
+        .collect_vec();
+        assert!(chunks.len() > 1);
+        chunks
+    }
+
+    #[test]
+    fn test_proof_chunk_cache_dedup() {
+        let now = Instant::now();
+        let mut cache = ProofChunkCache::new(DEFAULT_PROOF_CHUNK_MAX_AGE);
+        let chunks = new_proof_chunks(10);
+        let (last, rest) = chunks.split_last().unwrap();
+        for chunk in rest {
+            assert!(!cache.insert(chunk.clone(), now).unwrap());
+            // Retransmitted chunks are dropped rather than completing the proof.
+            assert!(!cache.insert(chunk.clone(), now).unwrap());
+        }
+        let key = (last.slot, last.from);
+        assert_eq!(
+            cache.entries[&key].chunks.iter().flatten().count(),
+            rest.len()
+        );
+        assert!(cache.insert(last.clone(), now).unwrap());
+        let proof = cache.take(key, now).unwrap();
+        assert_eq!(proof.chunks.into_iter().flatten().collect_vec(), chunks);
+        assert!(cache.entries.is_empty());
+        assert!(cache.take(key, now).is_none());
+
+        // Interleaved proofs for other slots are pieced together separately.
+        let chunks1 = new_proof_chunks(11);
+        let chunks2 = new_proof_chunks(12);
+        let num_chunks = chunks1.len();
+        for (index, (chunk1, chunk2)) in chunks1.into_iter().zip(chunks2).enumerate() {
+            let complete = index + 1 == num_chunks;
+            assert_eq!(cache.insert(chunk1, now).unwrap(), complete);
+            assert_eq!(cache.insert(chunk2, now).unwrap(), complete);
+        }
+        assert_eq!(cache.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_proof_chunk_cache_inconsistent_chunks() {
+        let now = Instant::now();
+        let mut cache = ProofChunkCache::new(DEFAULT_PROOF_CHUNK_MAX_AGE);
+        let keypair = Arc::new(Keypair::new());
+        let new_chunks = |max_size: usize| {
+            create_duplicate_proof(
+                keypair.clone(),
+                None,
+                10, // slot
+                None,
+                max_size,
+                0, // shred_version
+            )
+            .unwrap()
+            .collect_vec()
+        };
+        let chunks = new_chunks(DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_eq!(chunks.len(), 3);
+        let other_num_chunks = new_chunks(2 * DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_eq!(other_num_chunks.len(), 2);
+        let mut other_wallclock = new_chunks(DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        for chunk in &mut other_wallclock {
+            chunk.wallclock = chunks[0].wallclock + 1;
+        }
+
+        // Chunks disagreeing with the ones already received are rejected.
+        assert!(!cache.insert(chunks[0].clone(), now).unwrap());
+        assert_matches!(
+            cache.insert(other_num_chunks[1].clone(), now),
+            Err(Error::NumChunksMismatch)
+        );
+        assert_matches!(
+            cache.insert(other_wallclock[1].clone(), now),
+            Err(Error::ProofWallclockMismatch)
+        );
+        // Repeated chunk indices are dropped.
+        assert!(!cache.insert(chunks[0].clone(), now).unwrap());
+        assert!(!cache.insert(chunks[1].clone(), now).unwrap());
+
+        // The proof is reassembled from the consistent set of chunks.
+        assert!(cache.insert(chunks[2].clone(), now).unwrap());
+        let proof = cache.take((10, keypair.pubkey()), now).unwrap();
+        let proof = proof.chunks.into_iter().flatten().collect_vec();
+        assert_eq!(proof, chunks);
+        assert!(duplicate_shred::into_shreds(&keypair.pubkey(), proof, 0).is_ok());
+    }
+
+    #[test]
+    fn test_proof_chunk_cache_tombstones() {
+        let now = Instant::now();
+        let max_age = Duration::from_secs(60);
+        let mut cache = ProofChunkCache::new(max_age);
+        let chunks = new_proof_chunks(10);
+        let key = (chunks[0].slot, chunks[0].from);
+        assert_eq!(
+            chunks
+                .iter()
+                .filter(|&chunk| cache.insert(chunk.clone(), now).unwrap())
+                .count(),
+            1
+        );
+        // Complete proofs are kept until taken for reassembly.
+        assert!(cache.tombstones.is_empty());
+        assert!(cache.take(key, now).is_some());
+        assert!(cache.tombstones.contains_key(&key));
+
+        // The proof is not reassembled again from retransmitted chunks.
+        for chunk in &chunks {
+            assert!(!cache.insert(chunk.clone(), now).unwrap());
+        }
+        assert!(cache.entries.is_empty());
+
+        // Other nodes' proofs for the same slot are not affected.
+        let other_chunks = new_proof_chunks(10);
+        assert_eq!(
+            other_chunks
+                .into_iter()
+                .filter(|chunk| cache.insert(chunk.clone(), now).unwrap())
+                .count(),
+            1
+        );
+
+        // Tombstones expire along with incomplete proofs.
+        cache.expire(/*root:*/ 0, now + max_age);
+        assert!(cache.tombstones.is_empty());
+        assert_eq!(
+            chunks
+                .into_iter()
+                .filter(|chunk| cache.insert(chunk.clone(), now + max_age).unwrap())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_proof_chunk_cache_expiry() {
+        let now = Instant::now();
+        let max_age = Duration::from_secs(60);
+        let mut cache = ProofChunkCache::new(max_age);
+        let chunks10 = new_proof_chunks(10);
+        let chunks20 = new_proof_chunks(20);
+        let chunks30 = new_proof_chunks(30);
+        assert!(!cache.insert(chunks10[0].clone(), now).unwrap());
+        assert!(!cache.insert(chunks20[0].clone(), now).unwrap());
+        let later = now + max_age / 2;
+        assert!(!cache.insert(chunks30[0].clone(), later).unwrap());
+
+        // Scans for expired entries are rate limited.
+        cache.expire(/*root:*/ 15, now);
+        assert_eq!(cache.entries.len(), 3);
+
+        // Incomplete proofs at or below the root are dropped.
+        cache.expire(/*root:*/ 15, later);
+        let slots = |cache: &ProofChunkCache| {
+            cache
+                .entries
+                .keys()
+                .map(|&(slot, _)| slot)
+                .sorted()
+                .collect_vec()
+        };
+        assert_eq!(slots(&cache), vec![20, 30]);
+
+        // Then ones older than max_age.
+        cache.expire(/*root:*/ 15, now + max_age);
+        assert_eq!(slots(&cache), vec![30]);
+        cache.expire(/*root:*/ 15, later + max_age);
+        assert!(cache.entries.is_empty());
+
+        // Expired proofs are pieced together from scratch.
+        assert!(!cache.insert(chunks30[0].clone(), later + max_age).unwrap());
+        assert_eq!(
+            cache.entries[&(30, chunks30[0].from)]
+                .chunks
+                .iter()
+                .flatten()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_proof_chunk_cache_report_long_pending() {
+        let now = Instant::now();
+        let mut cache = ProofChunkCache::new(DEFAULT_PROOF_CHUNK_MAX_AGE);
+        let chunks10 = new_proof_chunks(10);
+        let chunks20 = new_proof_chunks(20);
+        assert!(!cache.insert(chunks10[1].clone(), now).unwrap());
+        let later = now + PROOF_CHUNK_PENDING_REPORT_AGE / 2;
+        assert!(!cache.insert(chunks20[0].clone(), later).unwrap());
+        assert!(!cache.insert(chunks20[2].clone(), later).unwrap());
+
+        assert!(cache.report_long_pending(later).is_empty());
+        let key10 = (10, chunks10[0].from);
+        assert_eq!(
+            cache.report_long_pending(now + PROOF_CHUNK_PENDING_REPORT_AGE),
+            vec![(key10, vec![0, 2])]
+        );
+        let key20 = (20, chunks20[0].from);
+        assert_eq!(
+            cache.report_long_pending(later + PROOF_CHUNK_PENDING_REPORT_AGE),
+            vec![(key20, vec![1])]
+        );
+        // Each proof is reported once.
+        assert!(cache
+            .report_long_pending(now + 2 * DEFAULT_PROOF_CHUNK_MAX_AGE)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_proof_verification_threads() {
+        solana_logger::setup();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let my_keypair = Arc::new(Keypair::new());
+        let my_pubkey = my_keypair.pubkey();
+        let shred_version = 0;
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_leader(10_000, &my_pubkey, 10_000);
+        // Proofs are only ingested for slots within an epoch of the root.
+        genesis_config.epoch_schedule = EpochSchedule::without_warmup();
+        let bank_forks = BankForks::new_rw_arc(Bank::new_for_tests(&genesis_config));
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(
+            &bank_forks.read().unwrap().working_bank(),
+        ));
+        let slots: Vec<Slot> = (1..=100).collect();
+        insert_slot_shreds(&blockstore, &my_keypair, slots.iter().copied());
+        let new_proofs = || {
+            slots
+                .iter()
+                .flat_map(|&slot| {
+                    create_duplicate_proof(
+                        my_keypair.clone(),
+                        None,
+                        slot,
+                        None,
+                        DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
+                        shred_version,
+                    )
+                    .unwrap()
+                })
+                .collect_vec()
+        };
+        let new_handler = |sender| {
+            DuplicateShredHandler::new(
+                blockstore.clone(),
+                leader_schedule_cache.clone(),
+                bank_forks.clone(),
+                sender,
+                shred_version,
+            )
+            .with_proof_verification_threads(DEFAULT_PROOF_VERIFICATION_THREADS)
+        };
+
+        // Proofs completed in one burst are all ingested, in the order they
+        // completed.
+        let (sender, receiver) = unbounded();
+        let mut duplicate_shred_handler = new_handler(sender);
+        for chunk in new_proofs() {
+            duplicate_shred_handler.handle(chunk);
+        }
+        let deadline = Instant::now() + Duration::from_secs(30);
+        let mut notified = vec![];
+        while notified.len() < slots.len() && Instant::now() < deadline {
+            duplicate_shred_handler.tick();
+            notified.extend(receiver.try_iter());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(notified, slots);
+        assert!(slots
+            .iter()
+            .all(|&slot| blockstore.has_duplicate_shreds_in_slot(slot)));
+        assert_eq!(duplicate_shred_handler.stats().proofs_completed, 100);
+
+        // Shutting down abandons the proofs still being verified.
+        for &slot in &slots {
+            blockstore.remove_slot_duplicate_proof(slot).unwrap();
+        }
+        let (sender, _receiver) = unbounded();
+        let mut duplicate_shred_handler = new_handler(sender);
+        for chunk in new_proofs() {
+            duplicate_shred_handler.handle(chunk);
+        }
+        let start = Instant::now();
+        drop(duplicate_shred_handler);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
 }
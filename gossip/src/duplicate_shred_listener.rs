@@ -2,83 +2,555 @@ use {
     crate::{
         cluster_info::{ClusterInfo, GOSSIP_SLEEP_MILLIS},
         crds::Cursor,
-        duplicate_shred::DuplicateShred,
+        crds_data::CrdsData,
+        crds_value::CrdsValue,
+        duplicate_shred::{self, DuplicateShred},
+        gossip_error::GossipError,
     },
+    crossbeam_channel::{Receiver, Sender},
+    solana_clock::Slot,
+    solana_ledger::shred::{self, Shred},
+    solana_pubkey::Pubkey,
     std::{
+        collections::{BTreeSet, HashSet},
         sync::{
-            atomic::{AtomicBool, Ordering},
-            Arc,
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, RwLock,
         },
         thread::{self, sleep, Builder, JoinHandle},
-        time::Duration,
+        time::{Duration, Instant},
     },
 };
 
+// Limit number of slots remembered as already published.
+const MAX_PUBLISHED_SLOTS: usize = 512;
+// While gossip is quiet the poll interval doubles up to this value.
+const DEFAULT_MAX_POLL_INTERVAL: Duration = Duration::from_millis(8 * GOSSIP_SLEEP_MILLIS);
+// Number of consecutive non-empty polls done without sleeping.
+const DEFAULT_MAX_POLL_BURST: usize = 16;
+
+/// Locally detected duplicate slot proof: the slot and the payloads of the
+/// two conflicting shreds.
+pub type LocalDuplicateProof = (Slot, shred::Payload, shred::Payload);
+
 pub trait DuplicateShredHandlerTrait: Send {
     fn handle(&mut self, data: DuplicateShred);
+
+    // Called on every pass of the listener, whether or not there was data.
+    fn tick(&mut self) {}
+}
+
+#[derive(Clone, Debug)]
+pub struct DuplicateShredListenerConfig {
+    /// Interval between polls of gossip for duplicate shreds
+    pub poll_interval: Duration,
+    /// Maximum interval the listener backs off to while gossip is quiet
+    pub max_poll_interval: Duration,
+    /// Maximum number of consecutive non-empty polls done without sleeping
+    pub max_burst: usize,
+    /// Origins whose duplicate shreds are handed to the handler, consulted
+    /// on every poll so that it can be updated while the listener runs
+    pub origin_filter: Arc<RwLock<DuplicateShredOriginFilter>>,
+}
+
+impl Default for DuplicateShredListenerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(GOSSIP_SLEEP_MILLIS),
+            max_poll_interval: DEFAULT_MAX_POLL_INTERVAL,
+            max_burst: DEFAULT_MAX_POLL_BURST,
+            origin_filter: Arc::default(),
+        }
+    }
+}
+
+/// Selects duplicate shreds by the node which pushed them into gossip, e.g.
+/// to ignore nodes known to push bogus proofs. The deny list takes precedence
+/// over the allow list.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DuplicateShredOriginFilter {
+    /// If set, only duplicate shreds from these origins are handled
+    pub origin_allow_list: Option<HashSet<Pubkey>>,
+    /// Duplicate shreds from these origins are ignored
+    pub origin_deny_list: HashSet<Pubkey>,
+}
+
+/// Selects the gossip values sent to a handler registered with
+/// `CrdsDispatcher::register_handler`
+pub type CrdsMatcher = fn(&CrdsData) -> bool;
+
+/// Numbers of gossip values sent to a registered handler, and dropped
+/// because its channel was full or disconnected
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CrdsHandlerStats {
+    pub num_sent: u64,
+    pub num_dropped: u64,
+}
+
+struct CrdsHandler {
+    matcher: CrdsMatcher,
+    sender: Sender<CrdsValue>,
+    num_sent: AtomicU64,
+    num_dropped: AtomicU64,
+}
+
+/// Sends each value inserted into gossip to every registered handler whose
+/// matcher it satisfies. Each handler receives its values in the order they
+/// were inserted. Gossip is only scanned while a handler is registered.
+#[derive(Default)]
+pub struct CrdsDispatcher {
+    handlers: RwLock<Vec<CrdsHandler>>,
+    num_unhandled: AtomicU64,
+}
+
+impl CrdsDispatcher {
+    /// Registers a handler for the values satisfying `matcher`, returning
+    /// its id. Values are dropped rather than waited for if `sender` is full.
+    pub fn register_handler(&self, matcher: CrdsMatcher, sender: Sender<CrdsValue>) -> usize {
+        let mut handlers = self.handlers.write().unwrap();
+        handlers.push(CrdsHandler {
+            matcher,
+            sender,
+            num_sent: AtomicU64::default(),
+            num_dropped: AtomicU64::default(),
+        });
+        handlers.len() - 1
+    }
+
+    pub fn handler_stats(&self, id: usize) -> Option<CrdsHandlerStats> {
+        let handlers = self.handlers.read().unwrap();
+        let handler = handlers.get(id)?;
+        Some(CrdsHandlerStats {
+            num_sent: handler.num_sent.load(Ordering::Relaxed),
+            num_dropped: handler.num_dropped.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Number of values which matched no handler
+    pub fn num_unhandled(&self) -> u64 {
+        self.num_unhandled.load(Ordering::Relaxed)
+    }
+
+    // Sends the values inserted since the cursor to their handlers, and
+    // returns how many matched any handler.
+    fn dispatch(&self, source: &impl GossipEntriesSource, cursor: &mut Cursor) -> usize {
+        let handlers = self.handlers.read().unwrap();
+        if handlers.is_empty() {
+            return 0;
+        }
+        let (entries, num_unhandled) = source.get_entries(cursor, &|data| {
+            handlers.iter().any(|handler| (handler.matcher)(data))
+        });
+        if num_unhandled > 0 {
+            self.num_unhandled
+                .fetch_add(num_unhandled as u64, Ordering::Relaxed);
+            inc_new_counter_debug!("duplicate_shred_listener-unhandled", num_unhandled);
+        }
+        let num_entries = entries.len();
+        for value in entries {
+            for handler in handlers
+                .iter()
+                .filter(|handler| (handler.matcher)(value.data()))
+            {
+                let counter = match handler.sender.try_send(value.clone()) {
+                    Ok(()) => &handler.num_sent,
+                    Err(_) => &handler.num_dropped,
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        num_entries
+    }
+}
+
+// The handlers of polled gossip values. The duplicate shred handler is fed
+// on the listener thread from the index of duplicate shreds in gossip, so
+// that other values are only scanned for the handlers of the dispatcher.
+struct EntryHandlers<H> {
+    dispatcher: Arc<CrdsDispatcher>,
+    dispatcher_cursor: Cursor,
+    handler: H,
+    origin_filter: Arc<RwLock<DuplicateShredOriginFilter>>,
+}
+
+impl<H: DuplicateShredHandlerTrait> EntryHandlers<H> {
+    fn new(dispatcher: Arc<CrdsDispatcher>, handler: H) -> Self {
+        Self {
+            dispatcher,
+            dispatcher_cursor: Cursor::default(),
+            handler,
+            origin_filter: Arc::default(),
+        }
+    }
+
+    fn with_origin_filter(
+        mut self,
+        origin_filter: Arc<RwLock<DuplicateShredOriginFilter>>,
+    ) -> Self {
+        self.origin_filter = origin_filter;
+        self
+    }
+
+    // Handles the duplicate shreds inserted since the cursor and dispatches
+    // the other new values, and returns how many values were handed over.
+    fn poll(&mut self, source: &impl GossipEntriesSource, cursor: &mut Cursor) -> usize {
+        let chunks = source.get_duplicate_shreds(cursor);
+        let num_entries = chunks.len()
+            + self
+                .dispatcher
+                .dispatch(source, &mut self.dispatcher_cursor);
+        let chunks: Vec<_> = {
+            let origin_filter = self.origin_filter.read().unwrap();
+            let mut num_denied = 0;
+            let mut num_not_allowed = 0;
+            let chunks = chunks
+                .into_iter()
+                .filter(|chunk| {
+                    if origin_filter.origin_deny_list.contains(&chunk.from) {
+                        num_denied += 1;
+                        false
+                    } else if origin_filter
+                        .origin_allow_list
+                        .as_ref()
+                        .is_some_and(|allow_list| !allow_list.contains(&chunk.from))
+                    {
+                        num_not_allowed += 1;
+                        false
+                    } else {
+                        true
+                    }
+                })
+                .collect();
+            if num_denied > 0 {
+                inc_new_counter_info!("duplicate_shred_listener-origin_denied", num_denied);
+            }
+            if num_not_allowed > 0 {
+                inc_new_counter_info!(
+                    "duplicate_shred_listener-origin_not_allowed",
+                    num_not_allowed
+                );
+            }
+            chunks
+        };
+        for chunk in chunks {
+            self.handler.handle(chunk);
+        }
+        self.handler.tick();
+        num_entries
+    }
 }
 
 pub struct DuplicateShredListener {
     thread_hdl: JoinHandle<()>,
+    dispatcher: Arc<CrdsDispatcher>,
+    origin_filter: Arc<RwLock<DuplicateShredOriginFilter>>,
 }
 
-// Right now we only need to process duplicate proof, in the future the receiver
-// should be a map from enum value to handlers.
 impl DuplicateShredListener {
     pub fn new(
         exit: Arc<AtomicBool>,
         cluster_info: Arc<ClusterInfo>,
         handler: impl DuplicateShredHandlerTrait + 'static,
     ) -> Self {
+        Self::new_with_config(
+            exit,
+            cluster_info,
+            handler,
+            None,
+            DuplicateShredListenerConfig::default(),
+        )
+    }
+
+    /// Like `new`, but also pushes duplicate slot proofs received from
+    /// `duplicate_proof_receiver` into gossip
+    pub fn new_with_publisher(
+        exit: Arc<AtomicBool>,
+        cluster_info: Arc<ClusterInfo>,
+        handler: impl DuplicateShredHandlerTrait + 'static,
+        duplicate_proof_receiver: Receiver<LocalDuplicateProof>,
+    ) -> Self {
+        Self::new_with_config(
+            exit,
+            cluster_info,
+            handler,
+            Some(duplicate_proof_receiver),
+            DuplicateShredListenerConfig::default(),
+        )
+    }
+
+    pub fn new_with_config(
+        exit: Arc<AtomicBool>,
+        cluster_info: Arc<ClusterInfo>,
+        handler: impl DuplicateShredHandlerTrait + 'static,
+        duplicate_proof_receiver: Option<Receiver<LocalDuplicateProof>>,
+        config: DuplicateShredListenerConfig,
+    ) -> Self {
+        let publisher = duplicate_proof_receiver.map(DuplicateProofPublisher::new);
+        let dispatcher = Arc::<CrdsDispatcher>::default();
+        let origin_filter = config.origin_filter.clone();
+        let handlers = EntryHandlers::new(dispatcher.clone(), handler)
+            .with_origin_filter(origin_filter.clone());
         let listen_thread = Builder::new()
             .name("solCiEntryLstnr".to_string())
             .spawn(move || {
-                Self::recv_loop(exit, &cluster_info, handler);
+                let self_pubkey = cluster_info.id();
+                recv_loop(
+                    exit,
+                    &*cluster_info,
+                    &self_pubkey,
+                    handlers,
+                    publisher,
+                    config,
+                );
             })
             .unwrap();
 
         Self {
             thread_hdl: listen_thread,
+            dispatcher,
+            origin_filter,
         }
     }
 
+    /// Filter of the origins of handled duplicate shreds, which takes effect
+    /// on the next poll when updated
+    pub fn origin_filter(&self) -> &Arc<RwLock<DuplicateShredOriginFilter>> {
+        &self.origin_filter
+    }
+
+    /// Dispatches gossip values to handlers registered in addition to the
+    /// duplicate shred handler
+    pub fn dispatcher(&self) -> &CrdsDispatcher {
+        &self.dispatcher
+    }
+
+    /// Also sends the gossip values satisfying `matcher` to `sender`, see
+    /// `CrdsDispatcher::register_handler`
+    pub fn register_handler(&self, matcher: CrdsMatcher, sender: Sender<CrdsValue>) -> usize {
+        self.dispatcher.register_handler(matcher, sender)
+    }
+
     pub fn join(self) -> thread::Result<()> {
         self.thread_hdl.join()
     }
 
-    // Here we are sending data one by one rather than in a batch because in the future
-    // we may send different type of CrdsData to different senders.
-    fn recv_loop(
-        exit: Arc<AtomicBool>,
-        cluster_info: &ClusterInfo,
-        mut handler: impl DuplicateShredHandlerTrait + 'static,
-    ) {
-        let mut cursor = Cursor::default();
-        while !exit.load(Ordering::Relaxed) {
-            let entries: Vec<DuplicateShred> = cluster_info.get_duplicate_shreds(&mut cursor);
-            for x in entries {
-                handler.handle(x);
+    /// Like `join`, but gives up if the listener thread has not stopped by
+    /// `deadline`, returning the listener so that it can be joined later
+    pub fn join_with_deadline(self, deadline: Instant) -> Result<thread::Result<()>, Self> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(10);
+        while !self.thread_hdl.is_finished() {
+            if Instant::now() >= deadline {
+                warn!(
+                    "Thread {} did not stop by the deadline",
+                    self.thread_hdl.thread().name().unwrap_or_default()
+                );
+                return Err(self);
             }
-            sleep(Duration::from_millis(GOSSIP_SLEEP_MILLIS));
+            sleep(POLL_INTERVAL);
+        }
+        Ok(self.join())
+    }
+}
+
+// Gossip access needed by the listener, so that it can be tested without
+// running gossip.
+trait GossipEntriesSource {
+    // Returns the values inserted since the given cursor which match the
+    // filter, along with the number of values which don't.
+    fn get_entries(
+        &self,
+        cursor: &mut Cursor,
+        filter: &dyn Fn(&CrdsData) -> bool,
+    ) -> (Vec<CrdsValue>, usize);
+
+    // Returns the duplicate shreds inserted since the given cursor.
+    fn get_duplicate_shreds(&self, cursor: &mut Cursor) -> Vec<DuplicateShred>;
+
+    // Returns the duplicate shreds in gossip which originate from pubkey.
+    fn get_duplicate_shreds_from(&self, pubkey: &Pubkey) -> Vec<DuplicateShred>;
+
+    // Chunks the duplicate shreds and pushes them into gossip.
+    fn push_duplicate_shred(&self, shred: &Shred, other_payload: &[u8]) -> Result<(), GossipError>;
+}
+
+impl GossipEntriesSource for ClusterInfo {
+    fn get_entries(
+        &self,
+        cursor: &mut Cursor,
+        filter: &dyn Fn(&CrdsData) -> bool,
+    ) -> (Vec<CrdsValue>, usize) {
+        self.get_entries_matching(cursor, filter)
+    }
+
+    fn get_duplicate_shreds(&self, cursor: &mut Cursor) -> Vec<DuplicateShred> {
+        ClusterInfo::get_duplicate_shreds(self, cursor)
+    }
+
+    fn get_duplicate_shreds_from(&self, pubkey: &Pubkey) -> Vec<DuplicateShred> {
+        ClusterInfo::get_duplicate_shreds_from(self, pubkey)
+    }
+
+    fn push_duplicate_shred(&self, shred: &Shred, other_payload: &[u8]) -> Result<(), GossipError> {
+        ClusterInfo::push_duplicate_shred(self, shred, other_payload)
+    }
+}
+
+fn recv_loop(
+    exit: Arc<AtomicBool>,
+    source: &impl GossipEntriesSource,
+    self_pubkey: &Pubkey,
+    mut handlers: EntryHandlers<impl DuplicateShredHandlerTrait>,
+    mut publisher: Option<DuplicateProofPublisher>,
+    config: DuplicateShredListenerConfig,
+) {
+    if let Some(publisher) = publisher.as_mut() {
+        // Proofs published before a restart may still be in gossip.
+        for chunk in source.get_duplicate_shreds_from(self_pubkey) {
+            publisher.mark_published(chunk.slot);
+        }
+    }
+    let mut cursor = Cursor::default();
+    let mut backoff = PollBackoff::new(config);
+    while !exit.load(Ordering::Relaxed) {
+        if let Some(publisher) = publisher.as_mut() {
+            publisher.publish(source);
+        }
+        let delay = poll_entries(source, &mut cursor, &mut handlers, &mut backoff);
+        if !delay.is_zero() {
+            sleep(delay);
+        }
+    }
+}
+
+// Hands new gossip values over to the handlers, and returns how long to wait
+// before polling again.
+fn poll_entries(
+    source: &impl GossipEntriesSource,
+    cursor: &mut Cursor,
+    handlers: &mut EntryHandlers<impl DuplicateShredHandlerTrait>,
+    backoff: &mut PollBackoff,
+) -> Duration {
+    let num_entries = handlers.poll(source, cursor);
+    backoff.next_delay(num_entries)
+}
+
+// Polls again right away while gossip has new entries, up to a burst cap, and
+// backs off exponentially while gossip is quiet.
+struct PollBackoff {
+    config: DuplicateShredListenerConfig,
+    interval: Duration,
+    burst: usize,
+}
+
+impl PollBackoff {
+    fn new(mut config: DuplicateShredListenerConfig) -> Self {
+        config.max_poll_interval = config.max_poll_interval.max(config.poll_interval);
+        Self {
+            interval: config.poll_interval,
+            config,
+            burst: 0,
+        }
+    }
+
+    fn next_delay(&mut self, num_entries: usize) -> Duration {
+        if num_entries == 0 {
+            self.burst = 0;
+            let delay = self.interval;
+            self.interval = self
+                .interval
+                .saturating_mul(2)
+                .min(self.config.max_poll_interval);
+            return delay;
+        }
+        self.interval = self.config.poll_interval;
+        if self.burst < self.config.max_burst {
+            self.burst = self.burst.saturating_add(1);
+            Duration::ZERO
+        } else {
+            self.burst = 0;
+            self.config.poll_interval
         }
     }
 }
 
+// Pushes locally detected duplicate slot proofs into gossip.
+struct DuplicateProofPublisher {
+    receiver: Receiver<LocalDuplicateProof>,
+    // Slots whose proofs are already published, so that they are not
+    // chunked and pushed again.
+    published: BTreeSet<Slot>,
+}
+
+impl DuplicateProofPublisher {
+    fn new(receiver: Receiver<LocalDuplicateProof>) -> Self {
+        Self {
+            receiver,
+            published: BTreeSet::new(),
+        }
+    }
+
+    fn publish(&mut self, source: &impl GossipEntriesSource) {
+        for (slot, shred1, shred2) in self.receiver.try_iter() {
+            if self.published.contains(&slot) {
+                inc_new_counter_debug!("duplicate_shred_listener-already_published", 1);
+                continue;
+            }
+            if let Err(err) = push_duplicate_proof(source, slot, shred1, &shred2) {
+                warn!("Unable to publish duplicate proof for slot {slot}: {err:?}");
+                continue;
+            }
+            self.mark_published(slot);
+        }
+    }
+
+    fn mark_published(&mut self, slot: Slot) {
+        self.published.insert(slot);
+        // Forget the oldest slots first.
+        while self.published.len() > MAX_PUBLISHED_SLOTS {
+            self.published.pop_first();
+        }
+    }
+}
+
+// Chunks the proof into DuplicateShred values and inserts them into crds,
+// unless there are already values from this node for the slot.
+fn push_duplicate_proof(
+    source: &impl GossipEntriesSource,
+    slot: Slot,
+    shred1: shred::Payload,
+    shred2: &[u8],
+) -> Result<(), GossipError> {
+    let shred = Shred::new_from_serialized_shred(shred1).map_err(duplicate_shred::Error::from)?;
+    if shred.slot() != slot {
+        return Err(GossipError::from(duplicate_shred::Error::SlotMismatch));
+    }
+    source.push_duplicate_shred(&shred, shred2)
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
         crate::{
-            cluster_info::Node, duplicate_shred::tests::new_rand_shred,
+            cluster_info::Node,
+            contact_info::ContactInfo,
+            crds_data::LowestSlot,
+            duplicate_proof::{self, ProofError},
+            duplicate_shred::tests::new_rand_shred,
             duplicate_shred_listener::DuplicateShredHandlerTrait,
+            protocol::DUPLICATE_SHRED_MAX_PAYLOAD_SIZE,
         },
+        crossbeam_channel::{bounded, unbounded},
         solana_keypair::Keypair,
         solana_ledger::shred::Shredder,
         solana_signer::Signer,
         solana_streamer::socket::SocketAddrSpace,
-        std::sync::{
-            atomic::{AtomicU32, Ordering},
-            Arc,
+        std::{
+            collections::VecDeque,
+            sync::{
+                atomic::{AtomicU32, AtomicUsize, Ordering},
+                Arc, Mutex,
+            },
         },
     };
     struct FakeHandler {
@@ -98,6 +570,500 @@ mod tests {
         }
     }
 
+    const SLOT: Slot = 53084024;
+
+    // Returns scripted batches of entries, one batch per poll. Once the
+    // script is exhausted, returns nothing and sets exit.
+    struct MockGossipEntriesSource {
+        batches: Mutex<VecDeque<Vec<CrdsValue>>>,
+        // Batch of the current poll, handed to the dispatcher
+        batch: Mutex<Vec<CrdsValue>>,
+        polls: AtomicUsize,
+        records: Vec<DuplicateShred>,
+        pushed: Mutex<Vec<Slot>>,
+        exit: Arc<AtomicBool>,
+    }
+
+    impl MockGossipEntriesSource {
+        fn new(batches: impl IntoIterator<Item = Vec<DuplicateShred>>) -> Self {
+            Self::new_with_values(batches.into_iter().map(|batch| {
+                batch
+                    .into_iter()
+                    .map(|chunk| CrdsValue::new_unsigned(CrdsData::DuplicateShred(0, chunk)))
+                    .collect()
+            }))
+        }
+
+        fn new_with_values(batches: impl IntoIterator<Item = Vec<CrdsValue>>) -> Self {
+            Self {
+                batches: Mutex::new(batches.into_iter().collect()),
+                batch: Mutex::default(),
+                polls: AtomicUsize::default(),
+                records: Vec::default(),
+                pushed: Mutex::default(),
+                exit: Arc::default(),
+            }
+        }
+
+        fn polls(&self) -> usize {
+            self.polls.load(Ordering::Relaxed)
+        }
+    }
+
+    impl GossipEntriesSource for MockGossipEntriesSource {
+        fn get_entries(
+            &self,
+            _cursor: &mut Cursor,
+            filter: &dyn Fn(&CrdsData) -> bool,
+        ) -> (Vec<CrdsValue>, usize) {
+            let batch = std::mem::take(&mut *self.batch.lock().unwrap());
+            let (entries, unmatched): (Vec<_>, Vec<_>) =
+                batch.into_iter().partition(|value| filter(value.data()));
+            (entries, unmatched.len())
+        }
+
+        // Called first on every poll
+        fn get_duplicate_shreds(&self, _cursor: &mut Cursor) -> Vec<DuplicateShred> {
+            self.polls.fetch_add(1, Ordering::Relaxed);
+            let batch = self.batches.lock().unwrap().pop_front();
+            if batch.is_none() {
+                self.exit.store(true, Ordering::Relaxed);
+            }
+            let batch = batch.unwrap_or_default();
+            let chunks = batch
+                .iter()
+                .filter_map(|value| match value.data() {
+                    CrdsData::DuplicateShred(_, chunk) => Some(chunk.clone()),
+                    _ => None,
+                })
+                .collect();
+            *self.batch.lock().unwrap() = batch;
+            chunks
+        }
+
+        fn get_duplicate_shreds_from(&self, pubkey: &Pubkey) -> Vec<DuplicateShred> {
+            self.records
+                .iter()
+                .filter(|chunk| chunk.from == *pubkey)
+                .cloned()
+                .collect()
+        }
+
+        fn push_duplicate_shred(
+            &self,
+            shred: &Shred,
+            _other_payload: &[u8],
+        ) -> Result<(), GossipError> {
+            self.pushed.lock().unwrap().push(shred.slot());
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Outcome {
+        Wait,
+        Ingest(Slot),
+        Corrupt,
+    }
+
+    // Reassembles proofs from the chunks handed over by the listener, and
+    // records the outcome after each chunk.
+    struct AssemblingHandler {
+        leader: Pubkey,
+        chunks: Vec<DuplicateShred>,
+        outcomes: Arc<Mutex<Vec<Outcome>>>,
+        // Sets exit once this many chunks are handled.
+        exit_after: Option<(usize, Arc<AtomicBool>)>,
+    }
+
+    impl AssemblingHandler {
+        fn new(leader: Pubkey, outcomes: Arc<Mutex<Vec<Outcome>>>) -> Self {
+            Self {
+                leader,
+                chunks: Vec::default(),
+                outcomes,
+                exit_after: None,
+            }
+        }
+    }
+
+    impl DuplicateShredHandlerTrait for AssemblingHandler {
+        fn handle(&mut self, data: DuplicateShred) {
+            self.chunks.push(data);
+            let outcome =
+                match duplicate_proof::assemble(self.chunks.clone(), |_| Some(self.leader), 0) {
+                    Ok(proof) => Outcome::Ingest(proof.slot),
+                    Err(ProofError::Incomplete { .. }) => Outcome::Wait,
+                    Err(_) => Outcome::Corrupt,
+                };
+            if outcome != Outcome::Wait {
+                self.chunks.clear();
+            }
+            let mut outcomes = self.outcomes.lock().unwrap();
+            outcomes.push(outcome);
+            if let Some((num_chunks, exit)) = &self.exit_after {
+                if outcomes.len() >= *num_chunks {
+                    exit.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    fn new_shreds(leader: &Keypair) -> (Shred, Shred) {
+        let mut rng = rand::thread_rng();
+        let shredder = Shredder::new(SLOT, SLOT - 1, 0, 0).unwrap();
+        let next_shred_index = 353;
+        (
+            new_rand_shred(&mut rng, next_shred_index, &shredder, leader),
+            new_rand_shred(&mut rng, next_shred_index, &shredder, leader),
+        )
+    }
+
+    fn new_duplicate_shreds(leader: &Keypair, max_payload_size: usize) -> Vec<DuplicateShred> {
+        let (shred1, shred2) = new_shreds(leader);
+        duplicate_shred::from_shred(
+            shred1,
+            Pubkey::default(), // self_pubkey
+            shred2.payload().clone(),
+            None::<fn(Slot) -> Option<Pubkey>>,
+            0, // wallclock
+            max_payload_size,
+            0, // shred_version
+        )
+        .unwrap()
+        .collect()
+    }
+
+    // Runs the listener over the scripted batches until the script is
+    // exhausted or the handler sets exit.
+    fn run_listener(source: &MockGossipEntriesSource, handler: impl DuplicateShredHandlerTrait) {
+        let config = DuplicateShredListenerConfig {
+            poll_interval: Duration::from_millis(1),
+            max_poll_interval: Duration::from_millis(1),
+            ..DuplicateShredListenerConfig::default()
+        };
+        recv_loop(
+            source.exit.clone(),
+            source,
+            &Pubkey::new_unique(),
+            EntryHandlers::new(Arc::default(), handler),
+            None,
+            config,
+        );
+    }
+
+    // Polls the source for the given duration of simulated time, and returns
+    // the delays between polls.
+    fn run_polls(
+        source: &MockGossipEntriesSource,
+        handlers: &mut EntryHandlers<FakeHandler>,
+        backoff: &mut PollBackoff,
+        duration: Duration,
+    ) -> Vec<Duration> {
+        let mut cursor = Cursor::default();
+        let mut elapsed = Duration::ZERO;
+        let mut delays = Vec::new();
+        while elapsed < duration {
+            let delay = poll_entries(source, &mut cursor, handlers, backoff);
+            elapsed += delay;
+            delays.push(delay);
+        }
+        delays
+    }
+
+    #[test]
+    fn test_poll_backoff() {
+        let ms = Duration::from_millis;
+        let config = DuplicateShredListenerConfig {
+            poll_interval: ms(100),
+            max_poll_interval: ms(400),
+            max_burst: 4,
+            ..DuplicateShredListenerConfig::default()
+        };
+        let entries = new_duplicate_shreds(&Keypair::new(), DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        let count = Arc::new(AtomicU32::new(0));
+        let mut handlers = EntryHandlers::new(Arc::default(), FakeHandler::new(count.clone()));
+        let mut backoff = PollBackoff::new(config);
+
+        // A burst of 10 batches is drained without sleeping, except once the
+        // burst cap is reached, after which gossip is quiet.
+        let source = MockGossipEntriesSource::new(vec![entries.clone(); 10]);
+        let delays = run_polls(&source, &mut handlers, &mut backoff, ms(1_500));
+        assert_eq!(
+            delays,
+            [0, 0, 0, 0, 100, 0, 0, 0, 0, 100, 100, 200, 400, 400, 400]
+                .map(ms)
+                .to_vec()
+        );
+        assert_eq!(source.polls(), 15);
+        assert_eq!(count.load(Ordering::Relaxed), 10 * entries.len() as u32);
+
+        // A quiet period polls far less often than the fixed interval would.
+        let source = MockGossipEntriesSource::new([]);
+        let delays = run_polls(&source, &mut handlers, &mut backoff, ms(2_000));
+        assert!(delays.iter().all(|&delay| delay == ms(400)));
+        assert_eq!(source.polls(), 5);
+
+        // New entries reset the backoff.
+        let source = MockGossipEntriesSource::new([entries.clone(), vec![], entries, vec![]]);
+        let delays = run_polls(&source, &mut handlers, &mut backoff, ms(700));
+        assert_eq!(delays, [0, 100, 0, 100, 200, 400].map(ms).to_vec());
+        assert_eq!(source.polls(), 6);
+    }
+
+    #[test]
+    fn test_listener_waits_for_missing_chunks() {
+        let leader = Keypair::new();
+        let chunks = new_duplicate_shreds(&leader, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        assert_eq!(chunks.len(), 3);
+        let source =
+            MockGossipEntriesSource::new([vec![chunks[2].clone()], vec![chunks[0].clone()]]);
+        let outcomes = Arc::default();
+        run_listener(
+            &source,
+            AssemblingHandler::new(leader.pubkey(), Arc::clone(&outcomes)),
+        );
+        assert_eq!(*outcomes.lock().unwrap(), [Outcome::Wait, Outcome::Wait]);
+        assert_eq!(source.polls(), 3);
+    }
+
+    #[test]
+    fn test_listener_ingests_complete_proof() {
+        let leader = Keypair::new();
+        let chunks = new_duplicate_shreds(&leader, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        let source = MockGossipEntriesSource::new([
+            vec![chunks[1].clone()],
+            vec![],
+            vec![chunks[2].clone(), chunks[0].clone()],
+        ]);
+        let outcomes = Arc::default();
+        run_listener(
+            &source,
+            AssemblingHandler::new(leader.pubkey(), Arc::clone(&outcomes)),
+        );
+        assert_eq!(
+            *outcomes.lock().unwrap(),
+            [Outcome::Wait, Outcome::Wait, Outcome::Ingest(SLOT)]
+        );
+        assert_eq!(source.polls(), 4);
+    }
+
+    #[test]
+    fn test_listener_rejects_extra_chunks() {
+        let leader = Keypair::new();
+        let chunks = new_duplicate_shreds(&leader, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        // Chunks of a proof split into more chunks.
+        let other = new_duplicate_shreds(&leader, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE / 2);
+        assert!(other.len() > chunks.len());
+        let source = MockGossipEntriesSource::new([
+            vec![chunks[0].clone(), chunks[1].clone()],
+            vec![other[3].clone()],
+        ]);
+        let outcomes = Arc::default();
+        run_listener(
+            &source,
+            AssemblingHandler::new(leader.pubkey(), Arc::clone(&outcomes)),
+        );
+        assert_eq!(
+            *outcomes.lock().unwrap(),
+            [Outcome::Wait, Outcome::Wait, Outcome::Corrupt]
+        );
+    }
+
+    #[test]
+    fn test_listener_exit_mid_processing() {
+        let leader = Keypair::new();
+        let chunks = new_duplicate_shreds(&leader, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        let source = MockGossipEntriesSource::new([chunks.clone(), chunks]);
+        let outcomes = Arc::default();
+        let mut handler = AssemblingHandler::new(leader.pubkey(), Arc::clone(&outcomes));
+        handler.exit_after = Some((1, source.exit.clone()));
+        run_listener(&source, handler);
+        // The batch being processed is finished, but gossip is not polled
+        // again once exit is set.
+        assert_eq!(
+            *outcomes.lock().unwrap(),
+            [Outcome::Wait, Outcome::Wait, Outcome::Ingest(SLOT)]
+        );
+        assert_eq!(source.polls(), 1);
+    }
+
+    #[test]
+    fn test_dispatcher_routing() {
+        let chunks = new_duplicate_shreds(&Keypair::new(), DUPLICATE_SHRED_MAX_PAYLOAD_SIZE);
+        let pubkey = Pubkey::new_unique();
+        let lowest_slot = |slot| {
+            CrdsValue::new_unsigned(CrdsData::LowestSlot(0, LowestSlot::new(pubkey, slot, 0)))
+        };
+        let duplicate_shred = |index: usize| {
+            CrdsValue::new_unsigned(CrdsData::DuplicateShred(
+                index as u16,
+                chunks[index].clone(),
+            ))
+        };
+        let contact_info = CrdsValue::new_unsigned(CrdsData::ContactInfo(
+            ContactInfo::new_localhost(&pubkey, 0),
+        ));
+        let source = MockGossipEntriesSource::new_with_values([
+            vec![lowest_slot(0), contact_info.clone()],
+            vec![lowest_slot(1), duplicate_shred(0), lowest_slot(2)],
+            vec![
+                contact_info,
+                duplicate_shred(1),
+                lowest_slot(3),
+                duplicate_shred(2),
+            ],
+        ]);
+        let dispatcher = Arc::<CrdsDispatcher>::default();
+        let count = Arc::new(AtomicU32::new(0));
+        let mut handlers = EntryHandlers::new(dispatcher.clone(), FakeHandler::new(count.clone()));
+        let mut cursor = Cursor::default();
+        // Values other than duplicate shreds are not scanned without handlers
+        assert_eq!(handlers.poll(&source, &mut cursor), 0);
+        assert_eq!(dispatcher.num_unhandled(), 0);
+
+        let (lowest_slot_sender, lowest_slot_receiver) = unbounded();
+        let lowest_slot_id = dispatcher.register_handler(
+            |data| matches!(data, CrdsData::LowestSlot(..)),
+            lowest_slot_sender,
+        );
+        // With room for a single value
+        let (duplicate_shred_sender, duplicate_shred_receiver) = bounded(1);
+        let duplicate_shred_id = dispatcher.register_handler(
+            |data| matches!(data, CrdsData::DuplicateShred(..)),
+            duplicate_shred_sender,
+        );
+
+        // The duplicate shreds are counted for the duplicate shred handler
+        // and for the dispatcher
+        assert_eq!(handlers.poll(&source, &mut cursor), 4);
+        assert_eq!(handlers.poll(&source, &mut cursor), 5);
+
+        // Each handler gets the values it matches, in order
+        let slots: Vec<_> = lowest_slot_receiver
+            .try_iter()
+            .map(|value| match value.data() {
+                CrdsData::LowestSlot(_, lowest_slot) => lowest_slot.lowest,
+                _ => panic!("unexpected value"),
+            })
+            .collect();
+        assert_eq!(slots, [1, 2, 3]);
+        assert_eq!(count.load(Ordering::Relaxed), 3);
+        assert_eq!(
+            duplicate_shred_receiver.try_iter().collect::<Vec<_>>(),
+            [duplicate_shred(0)]
+        );
+
+        assert_eq!(
+            dispatcher.handler_stats(lowest_slot_id),
+            Some(CrdsHandlerStats {
+                num_sent: 3,
+                num_dropped: 0,
+            })
+        );
+        // Values which don't fit in a full channel are dropped
+        assert_eq!(
+            dispatcher.handler_stats(duplicate_shred_id),
+            Some(CrdsHandlerStats {
+                num_sent: 1,
+                num_dropped: 2,
+            })
+        );
+        assert_eq!(dispatcher.handler_stats(2), None);
+        // The contact info matched no handler
+        assert_eq!(dispatcher.num_unhandled(), 1);
+    }
+
+    #[test]
+    fn test_origin_filter() {
+        struct OriginsHandler(Arc<Mutex<Vec<Pubkey>>>);
+
+        impl DuplicateShredHandlerTrait for OriginsHandler {
+            fn handle(&mut self, data: DuplicateShred) {
+                self.0.lock().unwrap().push(data.from);
+            }
+        }
+
+        let chunk =
+            new_duplicate_shreds(&Keypair::new(), DUPLICATE_SHRED_MAX_PAYLOAD_SIZE).swap_remove(0);
+        let origins: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        // Every batch has a chunk from each origin
+        let batch = || {
+            origins
+                .iter()
+                .map(|origin| {
+                    let mut chunk = chunk.clone();
+                    chunk.from = *origin;
+                    chunk
+                })
+                .collect::<Vec<_>>()
+        };
+        let source = MockGossipEntriesSource::new([batch(), batch(), batch(), batch()]);
+        let origin_filter = Arc::new(RwLock::new(DuplicateShredOriginFilter {
+            origin_allow_list: None,
+            origin_deny_list: HashSet::from([origins[0]]),
+        }));
+        let handled = Arc::<Mutex<Vec<Pubkey>>>::default();
+        let mut handlers = EntryHandlers::new(Arc::default(), OriginsHandler(handled.clone()))
+            .with_origin_filter(origin_filter.clone());
+        let mut cursor = Cursor::default();
+        let mut poll = || {
+            // Filtered chunks still count as polled entries
+            assert_eq!(handlers.poll(&source, &mut cursor), 3);
+            std::mem::take(&mut *handled.lock().unwrap())
+        };
+
+        assert_eq!(poll(), [origins[1], origins[2]]);
+
+        // Updates take effect on the next poll, and deny takes precedence
+        // over allow
+        origin_filter.write().unwrap().origin_allow_list =
+            Some(HashSet::from([origins[0], origins[1]]));
+        assert_eq!(poll(), [origins[1]]);
+
+        *origin_filter.write().unwrap() = DuplicateShredOriginFilter {
+            origin_allow_list: Some(HashSet::default()),
+            origin_deny_list: HashSet::default(),
+        };
+        assert!(poll().is_empty());
+
+        *origin_filter.write().unwrap() = DuplicateShredOriginFilter::default();
+        assert_eq!(poll(), origins);
+    }
+
+    #[test]
+    fn test_publisher_skips_proofs_in_gossip() {
+        let self_pubkey = Pubkey::new_unique();
+        let leader = Keypair::new();
+        let (shred1, shred2) = new_shreds(&leader);
+        let mut chunk = new_duplicate_shreds(&leader, DUPLICATE_SHRED_MAX_PAYLOAD_SIZE)
+            .pop()
+            .unwrap();
+        chunk.from = self_pubkey;
+        let run = |records: Vec<DuplicateShred>| {
+            let (sender, receiver) = unbounded();
+            sender
+                .send((SLOT, shred1.payload().clone(), shred2.payload().clone()))
+                .unwrap();
+            let mut source = MockGossipEntriesSource::new([]);
+            source.records = records;
+            recv_loop(
+                source.exit.clone(),
+                &source,
+                &self_pubkey,
+                EntryHandlers::new(Arc::default(), FakeHandler::new(Arc::default())),
+                Some(DuplicateProofPublisher::new(receiver)),
+                DuplicateShredListenerConfig::default(),
+            );
+            source.pushed.into_inner().unwrap()
+        };
+        // This node already has a proof for the slot in gossip.
+        assert!(run(vec![chunk.clone()]).is_empty());
+        // Proofs from other nodes do not prevent publishing.
+        chunk.from = Pubkey::new_unique();
+        assert_eq!(run(vec![chunk]), [SLOT]);
+    }
+
     #[test]
     fn test_listener_get_entries() {
         let host1_key = Arc::new(Keypair::new());
@@ -122,9 +1088,83 @@ mod tests {
             .push_duplicate_shred(&shred1, shred2.payload())
             .is_ok());
         cluster_info.flush_push_queue();
-        sleep(Duration::from_millis(GOSSIP_SLEEP_MILLIS));
+        // The listener may be backed off since gossip was quiet so far.
+        let deadline = Instant::now() + 2 * DEFAULT_MAX_POLL_INTERVAL;
+        while count.load(Ordering::Relaxed) < 3 && Instant::now() < deadline {
+            sleep(Duration::from_millis(GOSSIP_SLEEP_MILLIS));
+        }
         assert_eq!(count.load(Ordering::Relaxed), 3);
         exit.store(true, Ordering::Relaxed);
         assert!(listener.join().is_ok());
     }
+
+    #[test]
+    fn test_publish_local_duplicate_proofs() {
+        let host1_key = Arc::new(Keypair::new());
+        let host1_pubkey = host1_key.pubkey();
+        let node = Node::new_localhost_with_pubkey(&host1_pubkey);
+        let cluster_info = ClusterInfo::new(node.info, host1_key, SocketAddrSpace::Unspecified);
+        let (sender, receiver) = unbounded();
+        let mut publisher = DuplicateProofPublisher::new(receiver);
+        let mut rng = rand::thread_rng();
+        let (slot, parent_slot, reference_tick, version) = (53084024, 53084023, 0, 0);
+        let shredder = Shredder::new(slot, parent_slot, reference_tick, version).unwrap();
+        let next_shred_index = 353;
+        let leader = Arc::new(Keypair::new());
+        let shred1 = new_rand_shred(&mut rng, next_shred_index, &shredder, &leader);
+        let shred2 = new_rand_shred(&mut rng, next_shred_index, &shredder, &leader);
+        let proof = (slot, shred1.payload().clone(), shred2.payload().clone());
+
+        sender.send(proof.clone()).unwrap();
+        publisher.publish(&cluster_info);
+        let mut cursor = Cursor::default();
+        let chunks = cluster_info.get_duplicate_shreds(&mut cursor);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.from == host1_pubkey && chunk.slot == slot));
+        let (shred3, shred4) =
+            duplicate_shred::into_shreds(&leader.pubkey(), chunks, version).unwrap();
+        assert_eq!(shred3.payload(), shred1.payload());
+        assert_eq!(shred4.payload(), shred2.payload());
+        assert_eq!(publisher.published, BTreeSet::from([slot]));
+
+        // The same slot is not published again, and proofs whose shreds are
+        // for another slot are rejected.
+        sender.send(proof).unwrap();
+        sender
+            .send((slot + 1, shred1.payload().clone(), shred2.payload().clone()))
+            .unwrap();
+        publisher.publish(&cluster_info);
+        assert!(cluster_info.get_duplicate_shreds(&mut cursor).is_empty());
+        assert_eq!(publisher.published, BTreeSet::from([slot]));
+    }
+
+    #[test]
+    fn test_listener_join_with_deadline() {
+        let host1_key = Arc::new(Keypair::new());
+        let node = Node::new_localhost_with_pubkey(&host1_key.pubkey());
+        let cluster_info = Arc::new(ClusterInfo::new(
+            node.info,
+            host1_key,
+            SocketAddrSpace::Unspecified,
+        ));
+        let exit = Arc::new(AtomicBool::new(false));
+        let handler = FakeHandler::new(Arc::new(AtomicU32::new(0)));
+        let listener = DuplicateShredListener::new(exit.clone(), cluster_info, handler);
+
+        // The listener keeps running until exit is set.
+        let deadline = Instant::now() + Duration::from_millis(GOSSIP_SLEEP_MILLIS);
+        let Err(listener) = listener.join_with_deadline(deadline) else {
+            panic!("listener stopped without exit being set");
+        };
+
+        // With no traffic, the listener stops promptly once exit is set.
+        exit.store(true, Ordering::Relaxed);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let Ok(result) = listener.join_with_deadline(deadline) else {
+            panic!("listener did not stop by the deadline");
+        };
+        assert!(result.is_ok());
+    }
 }
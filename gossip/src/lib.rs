@@ -24,6 +24,7 @@ pub mod crds_gossip_push;
 pub mod crds_shards;
 pub mod crds_value;
 mod deprecated;
+pub mod duplicate_proof;
 pub mod duplicate_shred;
 pub mod duplicate_shred_handler;
 pub mod duplicate_shred_listener;
@@ -116,6 +116,7 @@ fn analyze_storage(blockstore: &Blockstore) -> Result<()> {
     analyze_column(blockstore, Orphans::NAME)?;
     analyze_column(blockstore, DeadSlots::NAME)?;
     analyze_column(blockstore, DuplicateSlots::NAME)?;
+    analyze_column(blockstore, DuplicateSlotProvenance::NAME)?;
     analyze_column(blockstore, ErasureMeta::NAME)?;
     analyze_column(blockstore, BankHash::NAME)?;
     analyze_column(blockstore, Root::NAME)?;
@@ -140,6 +141,7 @@ fn raw_key_to_slot(key: &[u8], column_name: &str) -> Option<Slot> {
         cf::Orphans::NAME => Some(cf::Orphans::slot(cf::Orphans::index(key))),
         cf::DeadSlots::NAME => Some(cf::SlotMeta::slot(cf::SlotMeta::index(key))),
         cf::DuplicateSlots::NAME => Some(cf::SlotMeta::slot(cf::SlotMeta::index(key))),
+        cf::DuplicateSlotProvenance::NAME => Some(cf::SlotMeta::slot(cf::SlotMeta::index(key))),
         cf::ErasureMeta::NAME => Some(cf::ErasureMeta::slot(cf::ErasureMeta::index(key))),
         cf::BankHash::NAME => Some(cf::BankHash::slot(cf::BankHash::index(key))),
         cf::Root::NAME => Some(cf::Root::slot(cf::Root::index(key))),
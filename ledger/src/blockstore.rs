@@ -256,6 +256,7 @@ pub struct Blockstore {
     data_shred_cf: LedgerColumn<cf::ShredData>,
     dead_slots_cf: LedgerColumn<cf::DeadSlots>,
     duplicate_slots_cf: LedgerColumn<cf::DuplicateSlots>,
+    duplicate_slot_provenance_cf: LedgerColumn<cf::DuplicateSlotProvenance>,
     erasure_meta_cf: LedgerColumn<cf::ErasureMeta>,
     index_cf: LedgerColumn<cf::Index>,
     merkle_root_meta_cf: LedgerColumn<cf::MerkleRootMeta>,
@@ -403,6 +404,7 @@ impl Blockstore {
         let data_shred_cf = db.column();
         let dead_slots_cf = db.column();
         let duplicate_slots_cf = db.column();
+        let duplicate_slot_provenance_cf = db.column();
         let erasure_meta_cf = db.column();
         let index_cf = db.column();
         let merkle_root_meta_cf = db.column();
@@ -437,6 +439,7 @@ impl Blockstore {
             data_shred_cf,
             dead_slots_cf,
             duplicate_slots_cf,
+            duplicate_slot_provenance_cf,
             erasure_meta_cf,
             index_cf,
             merkle_root_meta_cf,
@@ -850,6 +853,8 @@ impl Blockstore {
         self.meta_cf.submit_rocksdb_cf_metrics();
         self.dead_slots_cf.submit_rocksdb_cf_metrics();
         self.duplicate_slots_cf.submit_rocksdb_cf_metrics();
+        self.duplicate_slot_provenance_cf
+            .submit_rocksdb_cf_metrics();
         self.roots_cf.submit_rocksdb_cf_metrics();
         self.erasure_meta_cf.submit_rocksdb_cf_metrics();
         self.orphans_cf.submit_rocksdb_cf_metrics();
@@ -4033,7 +4038,12 @@ impl Blockstore {
     }
 
     pub fn remove_slot_duplicate_proof(&self, slot: Slot) -> Result<()> {
-        self.duplicate_slots_cf.delete(slot)
+        let mut write_batch = self.get_write_batch()?;
+        self.duplicate_slots_cf
+            .delete_in_batch(&mut write_batch, slot)?;
+        self.duplicate_slot_provenance_cf
+            .delete_in_batch(&mut write_batch, slot)?;
+        self.write_batch(write_batch)
     }
 
     pub fn get_first_duplicate_proof(&self) -> Option<(Slot, DuplicateSlotProof)> {
@@ -4059,6 +4069,33 @@ impl Blockstore {
             .expect("fetch from DuplicateSlots column family failed")
     }
 
+    /// Records where the duplicate slot proof already stored for `slot` came
+    /// from. Returns false without writing anything if there is no proof for
+    /// the slot, or if its provenance is already recorded.
+    pub fn store_duplicate_slot_provenance(
+        &self,
+        slot: Slot,
+        provenance: &DuplicateSlotProvenance,
+    ) -> Result<bool> {
+        if !self.has_duplicate_shreds_in_slot(slot)
+            || self.duplicate_slot_provenance_cf.get(slot)?.is_some()
+        {
+            return Ok(false);
+        }
+        self.duplicate_slot_provenance_cf.put(slot, provenance)?;
+        Ok(true)
+    }
+
+    /// Returns the duplicate slot proof stored for `slot`, along with where it
+    /// came from if that is recorded.
+    pub fn get_duplicate_slot_evidence(&self, slot: Slot) -> Result<Option<DuplicateSlotEvidence>> {
+        let Some(proof) = self.duplicate_slots_cf.get(slot)? else {
+            return Ok(None);
+        };
+        let provenance = self.duplicate_slot_provenance_cf.get(slot)?;
+        Ok(Some(DuplicateSlotEvidence { proof, provenance }))
+    }
+
     /// Returns the shred already stored in blockstore if it has a different
     /// payload than the given `shred` but the same (slot, index, shred-type).
     /// This implies the leader generated two different shreds with the same
@@ -10372,6 +10409,78 @@ pub mod tests {
         assert_eq!(duplicate_proof.shred2, *duplicate_shred.payload());
     }
 
+    #[test]
+    fn test_duplicate_slot_provenance() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let (slot, shred1, shred2) = (7, vec![1u8; 32], vec![2u8; 32]);
+        let provenance = DuplicateSlotProvenance {
+            origin: Pubkey::new_unique(),
+            wallclock: 1_723_000_000_000,
+            leader: Pubkey::new_unique(),
+        };
+
+        // Provenance is not recorded without a proof for the slot.
+        assert!(!blockstore
+            .store_duplicate_slot_provenance(slot, &provenance)
+            .unwrap());
+        assert!(blockstore
+            .get_duplicate_slot_evidence(slot)
+            .unwrap()
+            .is_none());
+
+        // Proofs stored without provenance still make evidence.
+        blockstore
+            .store_duplicate_slot(slot, shred1.clone(), shred2.clone())
+            .unwrap();
+        let evidence = blockstore
+            .get_duplicate_slot_evidence(slot)
+            .unwrap()
+            .unwrap();
+        assert_eq!(evidence.proof.shred1.as_ref(), shred1);
+        assert_eq!(evidence.proof.shred2.as_ref(), shred2);
+        assert_eq!(evidence.provenance, None);
+
+        assert!(blockstore
+            .store_duplicate_slot_provenance(slot, &provenance)
+            .unwrap());
+        let evidence = blockstore
+            .get_duplicate_slot_evidence(slot)
+            .unwrap()
+            .unwrap();
+        assert_eq!(evidence.proof.shred1.as_ref(), shred1);
+        assert_eq!(evidence.provenance, Some(provenance));
+
+        // Ingesting the slot again does not overwrite the original provenance.
+        let other = DuplicateSlotProvenance {
+            origin: Pubkey::new_unique(),
+            ..provenance
+        };
+        assert!(!blockstore
+            .store_duplicate_slot_provenance(slot, &other)
+            .unwrap());
+        let evidence = blockstore
+            .get_duplicate_slot_evidence(slot)
+            .unwrap()
+            .unwrap();
+        assert_eq!(evidence.provenance, Some(provenance));
+
+        // Removing the proof removes its provenance.
+        blockstore.remove_slot_duplicate_proof(slot).unwrap();
+        assert!(blockstore
+            .get_duplicate_slot_evidence(slot)
+            .unwrap()
+            .is_none());
+        blockstore
+            .store_duplicate_slot(slot, shred1, shred2)
+            .unwrap();
+        let evidence = blockstore
+            .get_duplicate_slot_evidence(slot)
+            .unwrap()
+            .unwrap();
+        assert_eq!(evidence.provenance, None);
+    }
+
     #[test]
     fn test_clear_unconfirmed_slot() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -276,6 +276,10 @@ impl Blockstore {
                 .duplicate_slots_cf
                 .delete_range_in_batch(write_batch, from_slot, to_slot)
                 .is_ok()
+            & self
+                .duplicate_slot_provenance_cf
+                .delete_range_in_batch(write_batch, from_slot, to_slot)
+                .is_ok()
             & self
                 .erasure_meta_cf
                 .delete_range_in_batch(write_batch, from_slot, to_slot)
@@ -356,6 +360,10 @@ impl Blockstore {
                 .duplicate_slots_cf
                 .delete_file_in_range(from_slot, to_slot)
                 .is_ok()
+            & self
+                .duplicate_slot_provenance_cf
+                .delete_file_in_range(from_slot, to_slot)
+                .is_ok()
             & self
                 .erasure_meta_cf
                 .delete_file_in_range(from_slot, to_slot)
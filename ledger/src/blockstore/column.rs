@@ -73,6 +73,17 @@ pub mod columns {
     /// * value type: [`blockstore_meta::DuplicateSlotProof`]
     pub struct DuplicateSlots;
 
+    #[derive(Debug)]
+    /// The duplicate slot provenance column
+    ///
+    /// This column family tracks where the proofs in the duplicate slots
+    /// column came from, if they were received through gossip. A value is
+    /// only written once the proof for the slot is stored.
+    ///
+    /// * index type: `u64` (see [`SlotColumn`])
+    /// * value type: [`blockstore_meta::DuplicateSlotProvenance`]
+    pub struct DuplicateSlotProvenance;
+
     #[derive(Debug)]
     /// The erasure meta column.
     ///
@@ -705,6 +716,14 @@ impl TypedColumn for columns::DuplicateSlots {
     type Type = blockstore_meta::DuplicateSlotProof;
 }
 
+impl SlotColumn for columns::DuplicateSlotProvenance {}
+impl ColumnName for columns::DuplicateSlotProvenance {
+    const NAME: &'static str = "duplicate_slot_provenance";
+}
+impl TypedColumn for columns::DuplicateSlotProvenance {
+    type Type = blockstore_meta::DuplicateSlotProvenance;
+}
+
 impl SlotColumn for columns::Orphans {}
 impl ColumnName for columns::Orphans {
     const NAME: &'static str = "orphans";
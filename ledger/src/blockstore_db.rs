@@ -177,6 +177,7 @@ impl Rocks {
             new_cf_descriptor::<columns::SlotMeta>(options, oldest_slot),
             new_cf_descriptor::<columns::DeadSlots>(options, oldest_slot),
             new_cf_descriptor::<columns::DuplicateSlots>(options, oldest_slot),
+            new_cf_descriptor::<columns::DuplicateSlotProvenance>(options, oldest_slot),
             new_cf_descriptor::<columns::ErasureMeta>(options, oldest_slot),
             new_cf_descriptor::<columns::Orphans>(options, oldest_slot),
             new_cf_descriptor::<columns::BankHash>(options, oldest_slot),
@@ -242,11 +243,12 @@ impl Rocks {
         cf_descriptors
     }
 
-    const fn columns() -> [&'static str; 20] {
+    const fn columns() -> [&'static str; 21] {
         [
             columns::ErasureMeta::NAME,
             columns::DeadSlots::NAME,
             columns::DuplicateSlots::NAME,
+            columns::DuplicateSlotProvenance::NAME,
             columns::Index::NAME,
             columns::Orphans::NAME,
             columns::BankHash::NAME,
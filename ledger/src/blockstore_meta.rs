@@ -8,6 +8,7 @@ use {
     serde::{Deserialize, Deserializer, Serialize, Serializer},
     solana_clock::{Slot, UnixTimestamp},
     solana_hash::Hash,
+    solana_pubkey::Pubkey,
     std::{
         collections::BTreeSet,
         ops::{Range, RangeBounds},
@@ -797,6 +798,24 @@ impl DuplicateSlotProof {
     }
 }
 
+/// Where a duplicate slot proof received through gossip came from
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DuplicateSlotProvenance {
+    /// Node which pushed the proof into gossip
+    pub origin: Pubkey,
+    /// Wallclock of the gossip values carrying the proof
+    pub wallclock: u64,
+    /// Leader of the slot which the proof was verified against
+    pub leader: Pubkey,
+}
+
+/// Duplicate slot proof, along with where it came from if it was received
+/// through gossip
+pub struct DuplicateSlotEvidence {
+    pub proof: DuplicateSlotProof,
+    pub provenance: Option<DuplicateSlotProvenance>,
+}
+
 #[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct TransactionStatusIndexMeta {
     pub max_slot: Slot,
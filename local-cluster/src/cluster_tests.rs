@@ -52,7 +52,7 @@ use {
 #[cfg(feature = "dev-context-only-utils")]
 use {
     solana_core::consensus::tower_storage::{
-        FileTowerStorage, SavedTower, SavedTowerVersions, TowerStorage,
+        FileTowerStorage, SavedTower2, SavedTowerVersions, TowerStorage,
     },
     std::path::PathBuf,
 };
@@ -339,7 +339,7 @@ pub fn apply_votes_to_tower(node_keypair: &Keypair, votes: Vec<(Slot, Hash)>, to
     for (slot, hash) in votes {
         tower.record_vote(slot, hash);
     }
-    let saved_tower = SavedTowerVersions::from(SavedTower::new(&tower, node_keypair).unwrap());
+    let saved_tower = SavedTowerVersions::from(SavedTower2::new(&tower, node_keypair).unwrap());
     tower_storage.store(&saved_tower).unwrap();
 }
 
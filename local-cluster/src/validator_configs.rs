@@ -34,6 +34,8 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         run_verification: config.run_verification,
         require_tower: config.require_tower,
         tower_storage: config.tower_storage.clone(),
+        tower_consistency_strictness: config.tower_consistency_strictness,
+        saved_tower_format: config.saved_tower_format,
         debug_keys: config.debug_keys.clone(),
         contact_debug_interval: config.contact_debug_interval,
         contact_save_interval: config.contact_save_interval,
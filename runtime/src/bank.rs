@@ -5740,9 +5740,11 @@ impl Bank {
             Caller::NewFromParent => true,
             Caller::WarpFromParent => false,
         };
-        let (feature_set, new_feature_activations) =
+        let (mut feature_set, new_feature_activations) =
             self.compute_active_feature_set(allow_new_activations);
+        feature_set.inherit_subscriptions(&self.feature_set);
         self.feature_set = Arc::new(feature_set);
+        report_feature_metrics(&self.feature_set);
 
         // Update activation slot of features in `new_feature_activations`
         for feature_id in new_feature_activations.iter() {
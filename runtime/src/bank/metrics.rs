@@ -1,5 +1,6 @@
 use {
     crate::bank::Bank,
+    agave_feature_set::FeatureSet,
     solana_clock::{Epoch, Slot},
     solana_program_runtime::loaded_programs::ProgramCacheStats,
     std::sync::atomic::{
@@ -206,6 +207,24 @@ pub(crate) fn report_partitioned_reward_metrics(bank: &Bank, timings: RewardsSto
     );
 }
 
+/// Reports how many of the features supported by this software are not yet
+/// active, to estimate the risk of being stranded on an old release
+pub(crate) fn report_feature_metrics(feature_set: &FeatureSet) {
+    datapoint_info!(
+        "bank-feature_set",
+        (
+            "inactive_supported",
+            feature_set.inactive_supported().len(),
+            i64
+        ),
+        (
+            "activation_coverage",
+            feature_set.activation_coverage(),
+            f64
+        ),
+    );
+}
+
 /// Logs the measurement values
 pub(crate) fn report_loaded_programs_stats(stats: &ProgramCacheStats, slot: Slot) {
     let hits = stats.hits.load(Ordering::Relaxed);
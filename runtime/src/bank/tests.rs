@@ -17,7 +17,7 @@ use {
         stakes::InvalidCacheEntryReason,
         status_cache::MAX_CACHE_ENTRIES,
     },
-    agave_feature_set::{self as feature_set, FeatureSet},
+    agave_feature_set::{self as feature_set, FeatureSet, FeatureSetWatcher},
     agave_reserved_account_keys::ReservedAccount,
     agave_transaction_view::static_account_keys_frame::MAX_STATIC_ACCOUNTS_PER_PACKET,
     ahash::AHashMap,
@@ -6752,6 +6752,38 @@ fn test_compute_active_feature_set() {
     assert!(feature_set.is_active(&test_feature));
 }
 
+#[test]
+fn test_apply_feature_activations_notifies_subscribers() {
+    let (bank0, _bank_forks) = create_simple_test_arc_bank(100_000);
+    let mut bank = Bank::new_from_parent(bank0, &Pubkey::default(), 1);
+
+    let test_feature = "TestFeature11111111111111111111111111111111"
+        .parse::<Pubkey>()
+        .unwrap();
+    let mut feature_set = FeatureSet::default();
+    feature_set.inactive_mut().insert(test_feature);
+    let watcher = FeatureSetWatcher::new(&mut feature_set);
+    bank.feature_set = Arc::new(feature_set);
+
+    // Subscriptions follow the feature set into child banks
+    let mut bank = Bank::new_from_parent(Arc::new(bank), &Pubkey::default(), 2);
+    bank.store_account(
+        &test_feature,
+        &feature::create_account(&Feature::default(), 42),
+    );
+    bank.apply_feature_activations(ApplyFeatureActivationsCaller::NewFromParent, true);
+    assert!(bank.feature_set.is_active(&test_feature));
+    assert_eq!(watcher.take_changes(), vec![(test_feature, 2)]);
+
+    // and into the feature sets rebuilt by applying activations, which don't
+    // report features already active
+    bank.apply_feature_activations(ApplyFeatureActivationsCaller::NewFromParent, true);
+    assert!(watcher.take_changes().is_empty());
+    let other_feature = Pubkey::new_unique();
+    Arc::make_mut(&mut bank.feature_set).activate(&other_feature, 2);
+    assert_eq!(watcher.take_changes(), vec![(other_feature, 2)]);
+}
+
 #[test]
 fn test_reserved_account_keys() {
     let (bank0, _bank_forks) = create_simple_test_arc_bank(100_000);
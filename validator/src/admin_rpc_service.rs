@@ -1023,7 +1023,7 @@ mod tests {
                 validator_exit,
                 validator_exit_backpressure: HashMap::default(),
                 authorized_voter_keypairs: Arc::new(RwLock::new(vec![vote_keypair])),
-                tower_storage: Arc::new(NullTowerStorage {}),
+                tower_storage: Arc::new(NullTowerStorage::default()),
                 post_init: Arc::new(RwLock::new(Some(AdminRpcRequestMetadataPostInit {
                     cluster_info,
                     bank_forks: bank_forks.clone(),
@@ -1457,7 +1457,7 @@ mod tests {
                 validator_exit: validator_config.validator_exit.clone(),
                 validator_exit_backpressure: HashMap::default(),
                 authorized_voter_keypairs: authorized_voter_keypairs.clone(),
-                tower_storage: Arc::new(NullTowerStorage {}),
+                tower_storage: Arc::new(NullTowerStorage::default()),
                 post_init: post_init.clone(),
                 staked_nodes_overrides: Arc::new(RwLock::new(HashMap::new())),
                 rpc_to_plugin_manager_sender: None,
@@ -404,7 +404,7 @@ fn main() {
     genesis.transaction_account_lock_limit =
         value_t!(matches, "transaction_account_lock_limit", usize).ok();
 
-    let tower_storage = Arc::new(FileTowerStorage::new(ledger_path.clone()));
+    let tower_storage = Arc::new(FileTowerStorage::new(ledger_path.clone()).with_instance_lock());
 
     let admin_service_post_init = Arc::new(RwLock::new(None));
     // If geyser_plugin_config value is invalid, the validator will exit when the values are extracted below
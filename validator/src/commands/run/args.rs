@@ -400,6 +400,69 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .takes_value(true)
             .help("Use DIR as file tower storage location [default: --ledger value]"),
     )
+    .arg(
+        Arg::with_name("tower_storage")
+            .long("tower-storage")
+            .value_name("URI")
+            .takes_value(true)
+            .conflicts_with_all(&["tower", "tower_durability", "tower_backups"])
+            .help(
+                "Select the tower storage with a URI, e.g. file:///path?durability=full&lock=true \
+                 or etcd://host:2379 [default: file storage under --tower]",
+            ),
+    )
+    .arg(
+        Arg::with_name("tower_durability")
+            .long("tower-durability")
+            .value_name("LEVEL")
+            .takes_value(true)
+            .possible_values(&["none", "data", "full"])
+            .help(
+                "How far to sync each saved tower to disk: not at all, its data only, or its \
+                 data and the rename into place [default: none]",
+            ),
+    )
+    .arg(
+        Arg::with_name("tower_backups")
+            .long("tower-backups")
+            .value_name("COUNT")
+            .takes_value(true)
+            .validator(is_parsable::<usize>)
+            .help("Keep COUNT previous towers next to the saved tower [default: 0]"),
+    )
+    .arg(
+        Arg::with_name("tower_restore_from_backup")
+            .long("tower-restore-from-backup")
+            .takes_value(false)
+            .requires("tower_backups")
+            .conflicts_with_all(&["tower_storage", "require_tower"])
+            .help(
+                "Restore the newest readable backup if the saved tower can't be read. Votes \
+                 since the backup are lost, risking duplicate votes",
+            ),
+    )
+    .arg(
+        Arg::with_name("tower_format")
+            .long("tower-format")
+            .value_name("FORMAT")
+            .takes_value(true)
+            .possible_values(&["legacy", "v2"])
+            .help(
+                "Format to save the tower in. v2 adds a checksum and the root and last vote \
+                 slots, but can't be read by older releases [default: legacy]",
+            ),
+    )
+    .arg(
+        Arg::with_name("tower_consistency")
+            .long("tower-consistency")
+            .value_name("STRICTNESS")
+            .takes_value(true)
+            .possible_values(&["lenient", "strict"])
+            .help(
+                "Whether a saved tower whose votes don't match the blockstore fails the tower \
+                 restore, or is only reported [default: lenient]",
+            ),
+    )
     .arg(
         Arg::with_name("gossip_port")
             .long("gossip-port")
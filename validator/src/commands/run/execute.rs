@@ -345,8 +345,39 @@ pub fn execute(
     let tower_path = value_t!(matches, "tower", PathBuf)
         .ok()
         .unwrap_or_else(|| ledger_path.clone());
+    let tower_consistency_strictness = value_t!(
+        matches,
+        "tower_consistency",
+        tower_storage::TowerConsistencyStrictness
+    )
+    .unwrap_or_default();
+    let tower_restore_from_backup = matches.is_present("tower_restore_from_backup");
+    if tower_restore_from_backup
+        && tower_consistency_strictness == tower_storage::TowerConsistencyStrictness::Strict
+    {
+        Err("--tower-restore-from-backup is not allowed with --tower-consistency strict")?;
+    }
     let tower_storage: Arc<dyn tower_storage::TowerStorage> =
-        Arc::new(tower_storage::FileTowerStorage::new(tower_path));
+        match matches.value_of("tower_storage") {
+            Some(uri) => Arc::from(
+                tower_storage::from_uri(uri, tower_storage::StorageOptions::default())
+                    .map_err(|err| format!("invalid --tower-storage: {err}"))?,
+            ),
+            None => {
+                let tower_storage = tower_storage::FileTowerStorage::new(tower_path)
+                    .with_durability(
+                        value_t!(matches, "tower_durability", tower_storage::DurabilityLevel)
+                            .unwrap_or_default(),
+                    )
+                    .with_backup_count(value_t!(matches, "tower_backups", usize).unwrap_or(0))
+                    .with_instance_lock();
+                if tower_restore_from_backup {
+                    Arc::new(tower_storage.with_backup_restore())
+                } else {
+                    Arc::new(tower_storage)
+                }
+            }
+        };
 
     let mut accounts_index_config = AccountsIndexConfig {
         num_flush_threads: Some(accounts_index_flush_threads),
@@ -575,6 +606,13 @@ pub fn execute(
     let mut validator_config = ValidatorConfig {
         require_tower: matches.is_present("require_tower"),
         tower_storage,
+        tower_consistency_strictness,
+        saved_tower_format: value_t!(
+            matches,
+            "tower_format",
+            tower_storage::SavedTowerWriteFormat
+        )
+        .unwrap_or_default(),
         halt_at_slot: value_t!(matches, "dev_halt_at_slot", Slot).ok(),
         expected_genesis_hash: matches
             .value_of("expected_genesis_hash")